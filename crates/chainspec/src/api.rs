@@ -1,13 +1,13 @@
 use crate::{ChainSpec, DepositContract};
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
 use alloy_chains::Chain;
 use alloy_eips::{calc_next_block_base_fee, eip1559::BaseFeeParams, eip7840::BlobParams};
-use alloy_genesis::Genesis;
-use alloy_primitives::{B256, U256};
+use alloy_genesis::{Genesis, GenesisAccount};
+use alloy_primitives::{Address, B256, U256};
 use core::fmt::{Debug, Display};
 use reth_ethereum_forks::EthereumHardforks;
 use reth_network_peers::NodeRecord;
-use reth_primitives_traits::{AlloyBlockHeader, BlockHeader};
+use reth_primitives_traits::{AlloyBlockHeader, BlockHeader, SealedHeader};
 
 /// Trait representing type configuring a chain spec.
 #[auto_impl::auto_impl(&, Arc)]
@@ -72,6 +72,24 @@ pub trait EthChainSpec: Send + Sync + Unpin + Debug {
             self.base_fee_params_at_timestamp(target_timestamp),
         ))
     }
+
+    /// Returns a copy of this chain spec whose genesis is the given block of this chain, with the
+    /// given accounts allocated on top of the state of that block.
+    ///
+    /// This is used to run a local chain that continues from a block of this chain. Returns `None`
+    /// if the chain spec can't be forked.
+    #[auto_impl(keep_default_for(&, Arc))]
+    fn fork_at(
+        &self,
+        header: SealedHeader<Self::Header>,
+        alloc: BTreeMap<Address, GenesisAccount>,
+    ) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let _ = (header, alloc);
+        None
+    }
 }
 
 impl<H: BlockHeader> EthChainSpec for ChainSpec<H> {
@@ -134,4 +152,23 @@ impl<H: BlockHeader> EthChainSpec for ChainSpec<H> {
     fn final_paris_total_difficulty(&self) -> Option<U256> {
         self.get_final_paris_total_difficulty()
     }
+
+    fn fork_at(
+        &self,
+        header: SealedHeader<H>,
+        alloc: BTreeMap<Address, GenesisAccount>,
+    ) -> Option<Self> {
+        let genesis = Genesis {
+            number: Some(header.number()),
+            timestamp: header.timestamp(),
+            gas_limit: header.gas_limit(),
+            difficulty: header.difficulty(),
+            base_fee_per_gas: header.base_fee_per_gas().map(Into::into),
+            excess_blob_gas: header.excess_blob_gas(),
+            blob_gas_used: header.blob_gas_used(),
+            alloc,
+            ..self.genesis.clone()
+        };
+        Some(Self { genesis, genesis_header: header, ..self.clone() })
+    }
 }
@@ -2966,4 +2966,30 @@ Post-merge hard forks (timestamp based):
             }
         )
     }
+
+    #[test]
+    fn fork_at_reroots_genesis() {
+        let header = Header {
+            number: 20_000_000,
+            timestamp: 1_717_000_000,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            ..Default::default()
+        };
+        let header = SealedHeader::seal_slow(header);
+        let account = address!("0x0000000000000000000000000000000000000001");
+        let alloc = BTreeMap::from([(
+            account,
+            GenesisAccount { balance: U256::from(ETH_TO_WEI), ..Default::default() },
+        )]);
+
+        let forked = ChainSpec::fork_at(&MAINNET, header.clone(), alloc.clone()).unwrap();
+        assert_eq!(forked.genesis_hash(), header.hash());
+        assert_eq!(forked.genesis_header().number, 20_000_000);
+        assert_eq!(forked.genesis().number, Some(20_000_000));
+        assert_eq!(forked.genesis().base_fee_per_gas, Some(7));
+        assert_eq!(forked.genesis().alloc, alloc);
+        assert_eq!(forked.chain, MAINNET.chain);
+        assert_eq!(forked.hardforks, MAINNET.hardforks);
+    }
 }
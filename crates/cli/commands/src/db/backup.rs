@@ -0,0 +1,84 @@
+use crate::download::{manifest::generate_manifest, manifest_cmd::infer_blocks_per_file};
+use clap::Parser;
+use reth_provider::{
+    backup::backup_datadir, DatabaseProviderFactory, RocksDBProviderFactory, StageCheckpointReader,
+    StaticFileProviderFactory, StorageSettingsCache,
+};
+use std::path::PathBuf;
+use tracing::info;
+
+/// Name of the directory the raw backup is staged in before it's packaged.
+const STAGING_DIR: &str = "datadir";
+
+/// Takes a consistent backup of the database, static files and `RocksDB`.
+///
+/// The backup is taken from a single database read transaction and a `RocksDB` checkpoint, so it
+/// can be made while a node is writing to the datadir. By default it's packaged into archives and
+/// a `manifest.json` that `reth download` can restore from.
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Output directory of the backup. Must not exist or be empty.
+    output_dir: PathBuf,
+
+    /// Write the backup as a plain datadir instead of packaging it into archives.
+    ///
+    /// The output can be used directly as `--datadir`, or packaged later with
+    /// `reth download snapshot-manifest`.
+    #[arg(long)]
+    raw: bool,
+
+    /// Base URL the archives will be hosted at, recorded in the manifest.
+    #[arg(long, conflicts_with = "raw")]
+    base_url: Option<String>,
+
+    /// Blocks per archive file for chunked components.
+    ///
+    /// If omitted, this is inferred from the header static file ranges.
+    #[arg(long, conflicts_with = "raw")]
+    blocks_per_file: Option<u64>,
+}
+
+impl Command {
+    /// Execute `db backup` command
+    pub fn execute<F>(self, factory: &F, chain_id: u64) -> eyre::Result<()>
+    where
+        F: DatabaseProviderFactory<Provider: StageCheckpointReader + StorageSettingsCache>
+            + StaticFileProviderFactory
+            + RocksDBProviderFactory,
+    {
+        if self.raw {
+            let backup = backup_datadir(factory, &self.output_dir)?;
+            info!(target: "reth::cli", path = ?backup.path, block = backup.block, "Backup written");
+            return Ok(())
+        }
+
+        eyre::ensure!(
+            !self.output_dir.exists() || reth_fs_util::read_dir(&self.output_dir)?.next().is_none(),
+            "Output directory {} is not empty",
+            self.output_dir.display()
+        );
+
+        let staging = self.output_dir.join(STAGING_DIR);
+        let backup = backup_datadir(factory, &staging)?;
+        let blocks_per_file = match self.blocks_per_file {
+            Some(blocks_per_file) => blocks_per_file,
+            None => infer_blocks_per_file(&staging)?,
+        };
+
+        info!(target: "reth::cli", block = backup.block, blocks_per_file, "Packaging backup");
+        let manifest = generate_manifest(
+            &staging,
+            &self.output_dir,
+            self.base_url.as_deref(),
+            backup.block,
+            chain_id,
+            blocks_per_file,
+        )?;
+        let manifest_path = self.output_dir.join("manifest.json");
+        reth_fs_util::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        reth_fs_util::remove_dir_all(&staging)?;
+
+        info!(target: "reth::cli", path = ?manifest_path, block = backup.block, "Backup written");
+        Ok(())
+    }
+}
@@ -10,6 +10,7 @@ use std::{
     sync::Arc,
 };
 mod account_storage;
+mod backup;
 mod checksum;
 mod clear;
 mod copy;
@@ -22,6 +23,7 @@ mod repair_trie;
 mod settings;
 mod stage_checkpoints;
 mod state;
+mod static_file;
 mod static_file_header;
 mod stats;
 /// DB List TUI
@@ -48,6 +50,8 @@ pub enum Subcommands {
     Checksum(checksum::Command),
     /// Copies the MDBX database to a new location (bundled mdbx_copy)
     Copy(copy::Command),
+    /// Takes a consistent backup of the database, static files and `RocksDB`
+    Backup(backup::Command),
     /// Create a diff between two database tables or two entire databases.
     Diff(diff::Command),
     /// Gets the content of a table for the given key
@@ -64,6 +68,8 @@ pub enum Subcommands {
     RepairTrie(repair_trie::Command),
     /// Reads and displays the static file segment header
     StaticFileHeader(static_file_header::Command),
+    /// Static file maintenance commands
+    StaticFile(static_file::Command),
     /// Lists current and local database versions
     Version,
     /// Returns the full database path
@@ -143,6 +149,12 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> Command<C>
                     command.execute(tool.provider_factory.db_ref())?;
                 });
             }
+            Subcommands::Backup(command) => {
+                let chain_id = self.env.chain.chain().id();
+                db_exec!(self.env, tool, N, AccessRights::RO, {
+                    command.execute(&tool.provider_factory, chain_id)?;
+                });
+            }
             Subcommands::Diff(command) => {
                 db_exec!(self.env, tool, N, AccessRights::RO, {
                     command.execute(&tool)?;
@@ -192,6 +204,13 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> Command<C>
                     command.execute(&tool)?;
                 });
             }
+            Subcommands::StaticFile(command) => {
+                let Environment { provider_factory, config, data_dir } =
+                    self.env.init::<N>(command.access_rights(), ctx.task_executor.clone())?;
+                command
+                    .execute::<N>(provider_factory, config, data_dir, ctx.task_executor.clone())
+                    .await?;
+            }
             Subcommands::Version => {
                 let local_db_version = match get_db_version(&db_path) {
                     Ok(version) => Some(version),
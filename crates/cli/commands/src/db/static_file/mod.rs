@@ -0,0 +1,55 @@
+use crate::common::{AccessRights, CliNodeTypes};
+use clap::{Parser, Subcommand};
+use reth_config::Config;
+use reth_db::DatabaseEnv;
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_node_core::dirs::{ChainPath, DataDirPath};
+use reth_provider::{ProviderFactory, StaticFileProviderFactory};
+use reth_tasks::Runtime;
+
+mod recompress;
+mod verify;
+
+/// The arguments for the `reth db static-file` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    #[command(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth db static-file` subcommands
+#[derive(Subcommand, Debug)]
+enum Subcommands {
+    /// Rewrites existing static files in place with a different compression
+    Recompress(recompress::Command),
+    /// Verifies the row checksums of static files, optionally refetching corrupted rows from peers
+    Verify(verify::Command),
+}
+
+impl Command {
+    /// Returns database access rights required for the command.
+    pub const fn access_rights(&self) -> AccessRights {
+        match &self.command {
+            Subcommands::Recompress(_) => AccessRights::RW,
+            Subcommands::Verify(command) => command.access_rights(),
+        }
+    }
+
+    /// Execute `db static-file` command
+    pub async fn execute<N: CliNodeTypes>(
+        self,
+        provider_factory: ProviderFactory<NodeTypesWithDBAdapter<N, DatabaseEnv>>,
+        config: Config,
+        data_dir: ChainPath<DataDirPath>,
+        runtime: Runtime,
+    ) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Recompress(command) => {
+                command.execute(provider_factory.static_file_provider().directory())
+            }
+            Subcommands::Verify(command) => {
+                command.execute::<N>(provider_factory, config, data_dir, runtime).await
+            }
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::WrapErr;
+use human_bytes::human_bytes;
+use reth_db::static_file::iter_static_files;
+use reth_nippy_jar::{
+    compression::{Compression as _, Compressors, Lz4, Zstd},
+    NippyJar, NippyJarCursor,
+};
+use reth_static_file_types::{Compression, SegmentHeader, StaticFileSegment};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// The arguments for the `reth db static-file recompress` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Static file segments to recompress. Defaults to all segments.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    segments: Vec<StaticFileSegment>,
+
+    /// Compression to rewrite the static files with.
+    ///
+    /// With `zstd-with-dictionary`, one dictionary per column is trained on rows sampled across
+    /// all static files of a segment, and shared by all of them.
+    #[arg(long, value_enum)]
+    compression: Compression,
+
+    /// Zstd compression level. `0` uses the zstd default.
+    #[arg(long, default_value_t = 0)]
+    level: i32,
+
+    /// Maximum size in bytes of each trained zstd dictionary.
+    #[arg(long, default_value_t = 100 * 1024)]
+    dictionary_size: usize,
+
+    /// Number of rows sampled per segment to train the zstd dictionaries.
+    #[arg(long, default_value_t = 10_000)]
+    samples: usize,
+}
+
+impl Command {
+    /// Execute `db static-file recompress` command
+    pub fn execute(self, static_files_path: &Path) -> eyre::Result<()> {
+        let static_files = iter_static_files(static_files_path)?;
+        let segments = if self.segments.is_empty() {
+            StaticFileSegment::iter().collect()
+        } else {
+            self.segments.clone()
+        };
+
+        let mut table = ComfyTable::new();
+        table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        table.set_header([
+            "Segment",
+            "Static Files",
+            "Size Before",
+            "Size After",
+            "Ratio",
+            "Elapsed",
+            "Throughput",
+        ]);
+
+        let mut total = RecompressStats::default();
+        for segment in segments {
+            let Some(ranges) = static_files.get(segment) else { continue };
+            let paths = ranges
+                .iter()
+                .map(|(_, header)| {
+                    static_files_path.join(segment.filename(&header.expected_block_range()))
+                })
+                .collect::<Vec<_>>();
+
+            let compressor = match self.compression {
+                Compression::Uncompressed => None,
+                Compression::Lz4 => Some(Compressors::Lz4(Lz4::default())),
+                Compression::Zstd => Some(Compressors::Zstd(
+                    Zstd::new(false, 0, segment.columns()).with_level(self.level),
+                )),
+                Compression::ZstdWithDictionary => {
+                    match self.train_dictionaries(segment, &paths)? {
+                        Some(zstd) => Some(Compressors::Zstd(zstd)),
+                        None => {
+                            warn!(
+                                target: "reth::cli",
+                                %segment,
+                                "No rows to train dictionaries on, skipping"
+                            );
+                            continue
+                        }
+                    }
+                }
+            };
+
+            let mut stats = RecompressStats::default();
+            for path in &paths {
+                let jar = NippyJar::<SegmentHeader>::load(path)?;
+                let size_before = jar_size(&jar)?;
+
+                let start = Instant::now();
+                let jar = jar
+                    .recompress(compressor.clone())
+                    .wrap_err_with(|| format!("Failed to recompress {}", path.display()))?;
+                let elapsed = start.elapsed();
+
+                let file_stats =
+                    RecompressStats { files: 1, size_before, size_after: jar_size(&jar)?, elapsed };
+                info!(
+                    target: "reth::cli",
+                    path = %path.display(),
+                    rows = jar.rows(),
+                    before = %human_bytes(size_before as f64),
+                    after = %human_bytes(file_stats.size_after as f64),
+                    ?elapsed,
+                    "Recompressed static file"
+                );
+                stats.add(&file_stats);
+            }
+
+            table.add_row(stats.row(segment.as_str()));
+            total.add(&stats);
+        }
+
+        let mut separator = Row::new();
+        for width in table.column_max_content_widths() {
+            separator.add_cell(Cell::new("-".repeat(width as usize)));
+        }
+        table.add_row(separator);
+        table.add_row(total.row("Total"));
+
+        println!("{table}");
+
+        Ok(())
+    }
+
+    /// Trains a zstd dictionary per column on rows sampled evenly across the given static files.
+    ///
+    /// Returns `None` if the static files have no rows.
+    fn train_dictionaries(
+        &self,
+        segment: StaticFileSegment,
+        paths: &[PathBuf],
+    ) -> eyre::Result<Option<Zstd>> {
+        let jars = paths
+            .iter()
+            .map(|path| NippyJar::<SegmentHeader>::load(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let total_rows = jars.iter().map(|jar| jar.rows()).sum::<usize>();
+        if total_rows == 0 {
+            return Ok(None)
+        }
+
+        let step = total_rows.div_ceil(self.samples.max(1));
+        let mut columns = vec![Vec::new(); segment.columns()];
+        let mut offset = 0;
+        for jar in &jars {
+            let mut cursor = NippyJarCursor::new(jar)?;
+            // First row of this jar that falls on the sampling grid.
+            let mut row = offset.next_multiple_of(step) - offset;
+            while row < jar.rows() {
+                if let Some(values) = cursor.row_by_number(row)? {
+                    for (column, value) in columns.iter_mut().zip(values) {
+                        column.push(value.to_vec());
+                    }
+                }
+                row += step;
+            }
+            offset += jar.rows();
+        }
+
+        info!(
+            target: "reth::cli",
+            %segment,
+            samples = columns[0].len(),
+            "Training zstd dictionaries"
+        );
+
+        let mut zstd =
+            Zstd::new(true, self.dictionary_size, segment.columns()).with_level(self.level);
+        zstd.prepare_compression(columns)
+            .wrap_err_with(|| format!("Failed to train dictionaries for {segment}"))?;
+
+        info!(
+            target: "reth::cli",
+            %segment,
+            dictionary_sizes = ?zstd.dictionary_sizes().unwrap_or_default(),
+            "Trained zstd dictionaries"
+        );
+
+        Ok(Some(zstd))
+    }
+}
+
+/// Returns the on-disk size of the data, offsets and configuration files of a jar.
+fn jar_size(jar: &NippyJar<SegmentHeader>) -> eyre::Result<u64> {
+    let mut size = 0;
+    for path in [jar.data_path().to_path_buf(), jar.offsets_path(), jar.config_path()] {
+        size += reth_fs_util::metadata(path)?.len();
+    }
+    Ok(size)
+}
+
+/// Size and timing of recompressed static files.
+#[derive(Debug, Default)]
+struct RecompressStats {
+    files: usize,
+    size_before: u64,
+    size_after: u64,
+    elapsed: Duration,
+}
+
+impl RecompressStats {
+    fn add(&mut self, other: &Self) {
+        self.files += other.files;
+        self.size_before += other.size_before;
+        self.size_after += other.size_after;
+        self.elapsed += other.elapsed;
+    }
+
+    fn row(&self, name: &str) -> Row {
+        let ratio = if self.size_before == 0 {
+            1.0
+        } else {
+            self.size_after as f64 / self.size_before as f64
+        };
+        let throughput = self.size_before as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON);
+
+        let mut row = Row::new();
+        row.add_cell(Cell::new(name))
+            .add_cell(Cell::new(self.files))
+            .add_cell(Cell::new(human_bytes(self.size_before as f64)))
+            .add_cell(Cell::new(human_bytes(self.size_after as f64)))
+            .add_cell(Cell::new(format!("{:.2}%", ratio * 100.0)))
+            .add_cell(Cell::new(humantime::format_duration(Duration::from_millis(
+                self.elapsed.as_millis() as u64,
+            ))))
+            .add_cell(Cell::new(format!("{}/s", human_bytes(throughput))));
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_nippy_jar::NippyJarWriter;
+    use reth_static_file_types::SegmentRangeInclusive;
+
+    #[test]
+    fn parse_recompress_command() {
+        let cmd = Command::parse_from([
+            "reth",
+            "--segments",
+            "headers,receipts",
+            "--compression",
+            "zstd-with-dictionary",
+        ]);
+        assert_eq!(cmd.segments, vec![StaticFileSegment::Headers, StaticFileSegment::Receipts]);
+        assert!(matches!(cmd.compression, Compression::ZstdWithDictionary));
+        assert_eq!(cmd.dictionary_size, 100 * 1024);
+    }
+
+    #[test]
+    fn recompress_round_trip() {
+        let static_files = tempfile::tempdir().unwrap();
+        let segment = StaticFileSegment::Headers;
+        let range = SegmentRangeInclusive::new(0, 99);
+        let header = SegmentHeader::new(range, Some(range), None, segment);
+        let path = static_files.path().join(segment.filename(&range));
+        let rows = (0..100)
+            .map(|row| {
+                (0..segment.columns())
+                    .map(|column| format!("row {row} column {column} ").repeat(4).into_bytes())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut writer =
+            NippyJarWriter::new(NippyJar::new(segment.columns(), &path, header.clone()).with_lz4())
+                .unwrap();
+        for value in rows.iter().flatten() {
+            writer.append_column(Some(Ok(value))).unwrap();
+        }
+        writer.commit().unwrap();
+
+        for (compression, compressed) in [("zstd", true), ("uncompressed", false)] {
+            Command::parse_from(["reth", "--compression", compression])
+                .execute(static_files.path())
+                .unwrap();
+
+            let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+            assert_eq!(matches!(jar.compressor(), Some(Compressors::Zstd(_))), compressed);
+            assert_eq!(jar.user_header(), &header);
+            assert!(!static_files.path().join("recompress").exists());
+
+            let mut cursor = NippyJarCursor::new(&jar).unwrap();
+            for row in &rows {
+                let values = cursor.next_row().unwrap().unwrap();
+                assert_eq!(values, row.iter().map(Vec::as_slice).collect::<Vec<_>>());
+            }
+            assert!(cursor.next_row().unwrap().is_none());
+        }
+    }
+}
@@ -0,0 +1,648 @@
+use crate::common::{AccessRights, CliNodeTypes};
+use alloy_consensus::{proofs::calculate_receipt_root, BlockHeader, TxReceipt};
+use alloy_eips::BlockHashOrNumber;
+use alloy_primitives::{BlockNumber, U256};
+use backon::{ConstantBuilder, Retryable};
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::{eyre, WrapErr};
+use reth_cli_util::get_secret_key;
+use reth_codecs::Compact;
+use reth_config::Config;
+use reth_db::{
+    static_file::{iter_static_files, TotalDifficultyMask},
+    DatabaseEnv,
+};
+use reth_db_api::models::CompactU256;
+use reth_network::BlockDownloaderProvider;
+use reth_network_p2p::{
+    bodies::client::BodiesClient, headers::client::HeadersClient, receipts::client::ReceiptsClient,
+};
+use reth_nippy_jar::{NippyJar, NippyJarCursor, NippyJarError};
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_node_core::{
+    args::NetworkArgs,
+    dirs::{ChainPath, DataDirPath},
+    utils::get_single_header,
+};
+use reth_primitives_traits::{BlockBody, Receipt};
+use reth_provider::{
+    providers::BlockchainProvider, BlockBodyIndicesProvider, BlockReader, ChainSpecProvider,
+    HeaderProvider, ProviderFactory, StaticFileProviderFactory,
+};
+use reth_static_file_types::{SegmentHeader, StaticFileSegment};
+use reth_tasks::Runtime;
+use std::{collections::BTreeMap, path::PathBuf};
+use tracing::{debug, info, warn};
+
+/// Replacing rows of a static file, by row number.
+type ReplacedRows = BTreeMap<usize, Vec<Vec<u8>>>;
+
+/// The arguments for the `reth db static-file verify` command
+///
+/// Reads every row of the static files, verifying it against its checksum. Static files written
+/// before checksums were introduced are only checked for rows that can't be decompressed.
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Static file segments to verify. Defaults to all segments.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    segments: Vec<StaticFileSegment>,
+
+    /// Refetch corrupted headers, transactions and receipts from peers, and rewrite the static
+    /// files containing them.
+    ///
+    /// The other segments are derived from these and can't be refetched, their corrupted rows are
+    /// only reported.
+    #[arg(long)]
+    repair: bool,
+
+    /// The number of retries per request to peers.
+    #[arg(long, default_value_t = 5)]
+    retries: usize,
+
+    #[command(flatten)]
+    network: NetworkArgs,
+}
+
+impl Command {
+    /// Returns database access rights required for the command.
+    pub const fn access_rights(&self) -> AccessRights {
+        if self.repair {
+            AccessRights::RW
+        } else {
+            AccessRights::RO
+        }
+    }
+
+    /// Execute `db static-file verify` command
+    pub async fn execute<N: CliNodeTypes>(
+        self,
+        provider_factory: ProviderFactory<NodeTypesWithDBAdapter<N, DatabaseEnv>>,
+        config: Config,
+        data_dir: ChainPath<DataDirPath>,
+        runtime: Runtime,
+    ) -> eyre::Result<()> {
+        let static_files_path = provider_factory.static_file_provider().directory().to_path_buf();
+        let static_files = iter_static_files(&static_files_path)?;
+        let segments = if self.segments.is_empty() {
+            StaticFileSegment::iter().collect()
+        } else {
+            self.segments.clone()
+        };
+
+        let mut table = ComfyTable::new();
+        table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        table.set_header([
+            "Segment",
+            "Static Files",
+            "Rows",
+            "Without Checksums",
+            "Corrupted Rows",
+        ]);
+
+        let mut corrupted = Vec::new();
+        for segment in segments {
+            let Some(ranges) = static_files.get(segment) else { continue };
+            info!(target: "reth::cli", %segment, files = ranges.len(), "Verifying static files");
+
+            let mut stats = VerifyStats::default();
+            for (_, header) in ranges {
+                let path = static_files_path.join(segment.filename(&header.expected_block_range()));
+                let jar = NippyJar::<SegmentHeader>::load(&path)?;
+                let rows = corrupted_rows(&jar)
+                    .wrap_err_with(|| format!("Failed to verify {}", path.display()))?;
+                debug!(
+                    target: "reth::cli",
+                    path = %path.display(),
+                    rows = jar.rows(),
+                    corrupted = rows.len(),
+                    "Verified static file"
+                );
+
+                stats.files += 1;
+                stats.rows += jar.rows();
+                stats.without_checksums += usize::from(!jar.has_checksums());
+                stats.corrupted_rows += rows.len();
+
+                for (row, err) in &rows {
+                    warn!(
+                        target: "reth::cli",
+                        path = %path.display(),
+                        row,
+                        number = ?header.start().map(|start| start + *row as u64),
+                        %err,
+                        "Corrupted static file row"
+                    );
+                }
+                if !rows.is_empty() {
+                    corrupted.push(CorruptedStaticFile {
+                        path,
+                        header: header.clone(),
+                        rows: rows.into_iter().map(|(row, _)| row).collect(),
+                    });
+                }
+            }
+
+            table.add_row(stats.row(segment.as_str()));
+        }
+
+        println!("{table}");
+
+        let corrupted_rows = corrupted.iter().map(|file| file.rows.len()).sum::<usize>();
+        if corrupted_rows == 0 {
+            info!(target: "reth::cli", "No corrupted static file rows found");
+            return Ok(())
+        }
+        if !self.repair {
+            eyre::bail!(
+                "Found {corrupted_rows} corrupted static file rows, run with `--repair` to refetch \
+                 them from peers"
+            )
+        }
+
+        self.repair::<N>(provider_factory, config, data_dir, runtime, corrupted).await
+    }
+
+    /// Refetches the corrupted rows from peers and rewrites the static files containing them.
+    async fn repair<N: CliNodeTypes>(
+        &self,
+        provider_factory: ProviderFactory<NodeTypesWithDBAdapter<N, DatabaseEnv>>,
+        mut config: Config,
+        data_dir: ChainPath<DataDirPath>,
+        runtime: Runtime,
+        corrupted: Vec<CorruptedStaticFile>,
+    ) -> eyre::Result<()> {
+        let (repairable, unrepairable): (Vec<_>, Vec<_>) =
+            corrupted.into_iter().partition(|file| {
+                matches!(
+                    file.header.segment(),
+                    StaticFileSegment::Headers |
+                        StaticFileSegment::Transactions |
+                        StaticFileSegment::Receipts
+                )
+            });
+        for file in &unrepairable {
+            warn!(
+                target: "reth::cli",
+                segment = %file.header.segment(),
+                path = %file.path.display(),
+                "Corrupted rows can't be refetched, unwind and rerun the stage producing them"
+            );
+        }
+
+        if !repairable.is_empty() {
+            config.peers.trusted_nodes_only |= self.network.trusted_only;
+            config.peers.trusted_nodes.extend(self.network.trusted_peers.clone());
+
+            let network_secret_path =
+                self.network.p2p_secret_key.clone().unwrap_or_else(|| data_dir.p2p_secret());
+            let p2p_secret_key = get_secret_key(&network_secret_path)?;
+            let network = self
+                .network
+                .network_config::<N::NetworkPrimitives>(
+                    &config,
+                    provider_factory.chain_spec(),
+                    p2p_secret_key,
+                    data_dir.known_peers(),
+                    runtime,
+                )
+                .build(BlockchainProvider::new(provider_factory.clone())?)
+                .start_network()
+                .await?;
+            let client = network.fetch_client().await?;
+            let backoff = ConstantBuilder::default().with_max_times(self.retries.max(1));
+
+            for file in repairable {
+                info!(
+                    target: "reth::cli",
+                    path = %file.path.display(),
+                    rows = file.rows.len(),
+                    "Refetching corrupted rows"
+                );
+                let rows = match file.header.segment() {
+                    StaticFileSegment::Headers => {
+                        refetch_headers(&provider_factory, &client, backoff, &file).await?
+                    }
+                    StaticFileSegment::Transactions => {
+                        refetch_transactions(&provider_factory, &client, backoff, &file).await?
+                    }
+                    StaticFileSegment::Receipts => {
+                        refetch_receipts(&provider_factory, &client, backoff, &file).await?
+                    }
+                    segment => unreachable!("{segment} can't be refetched"),
+                };
+
+                replace_rows(&file, rows)?;
+                // The repaired rows are read through the provider by the following repairs.
+                provider_factory.static_file_provider().remove_cached_provider(
+                    file.header.segment(),
+                    file.header.expected_block_range().end(),
+                );
+                info!(
+                    target: "reth::cli",
+                    path = %file.path.display(),
+                    rows = file.rows.len(),
+                    "Repaired static file"
+                );
+            }
+        }
+
+        eyre::ensure!(
+            unrepairable.is_empty(),
+            "Corrupted rows of {} static files couldn't be repaired",
+            unrepairable.len()
+        );
+
+        Ok(())
+    }
+}
+
+/// A static file with corrupted rows.
+#[derive(Debug)]
+struct CorruptedStaticFile {
+    /// Path of the static file.
+    path: PathBuf,
+    /// Segment header of the static file.
+    header: SegmentHeader,
+    /// The corrupted rows.
+    rows: Vec<usize>,
+}
+
+/// Reads every row of a static file, returning the rows which fail their checksum or can't be
+/// decompressed.
+fn corrupted_rows(jar: &NippyJar<SegmentHeader>) -> eyre::Result<Vec<(usize, NippyJarError)>> {
+    let mut cursor = NippyJarCursor::new(jar)?;
+    let mut corrupted = Vec::new();
+    for row in 0..jar.rows() {
+        if let Err(err) = cursor.row_by_number(row) {
+            corrupted.push((row, err));
+        }
+    }
+    Ok(corrupted)
+}
+
+/// Rewrites a static file with its refetched rows, and verifies that no corrupted rows remain.
+fn replace_rows(file: &CorruptedStaticFile, rows: ReplacedRows) -> eyre::Result<()> {
+    let jar = NippyJar::<SegmentHeader>::load(&file.path)?
+        .replace_rows(rows)
+        .wrap_err_with(|| format!("Failed to rewrite {}", file.path.display()))?;
+    let remaining = corrupted_rows(&jar)?;
+    eyre::ensure!(
+        remaining.is_empty(),
+        "{} still has {} corrupted rows after the repair",
+        file.path.display(),
+        remaining.len()
+    );
+    Ok(())
+}
+
+/// Refetches the corrupted headers of a static file.
+///
+/// If the child of a corrupted header is intact, the header is requested by the child's parent
+/// hash. Otherwise it's requested by number and must link to its parent.
+async fn refetch_headers<P, C>(
+    provider: &P,
+    client: &C,
+    backoff: ConstantBuilder,
+    file: &CorruptedStaticFile,
+) -> eyre::Result<ReplacedRows>
+where
+    P: HeaderProvider<Header: BlockHeader> + StaticFileProviderFactory,
+    C: HeadersClient<Header: reth_primitives_traits::BlockHeader + Compact> + Clone,
+{
+    let block_start =
+        file.header.block_start().ok_or_else(|| eyre!("{} has no blocks", file.path.display()))?;
+
+    let mut rows = ReplacedRows::new();
+    // Total difficulty of the last replaced row, which the next row derives its own from.
+    let mut replaced_total_difficulty = None;
+    for &row in &file.rows {
+        let number = block_start + row as u64;
+        let id = match provider.sealed_header(number + 1) {
+            Ok(Some(child)) => BlockHashOrNumber::Hash(child.parent_hash()),
+            _ => BlockHashOrNumber::Number(number),
+        };
+
+        let header = (|| get_single_header(client.clone(), id))
+            .retry(backoff)
+            .notify(|err, _| {
+                warn!(target: "reth::cli", %err, number, "Error requesting header. Retrying...")
+            })
+            .await?;
+
+        if id.is_number() {
+            let parent = number.checked_sub(1).map(|parent| provider.sealed_header(parent));
+            let linked = match parent {
+                Some(Ok(Some(parent))) => parent.hash() == header.parent_hash(),
+                _ => false,
+            };
+            eyre::ensure!(
+                linked,
+                "Header {number} can't be verified, neither its parent nor its child is intact"
+            );
+        }
+
+        // Total difficulties are derived from the parent, and zero since the merge.
+        let parent_total_difficulty = match (number.checked_sub(1), replaced_total_difficulty) {
+            (None, _) => U256::ZERO,
+            (Some(_), Some((replaced_row, total_difficulty))) if replaced_row + 1 == row => {
+                total_difficulty
+            }
+            (Some(parent), _) => {
+                provider
+                    .static_file_provider()
+                    .get_segment_provider_for_block(StaticFileSegment::Headers, parent, None)?
+                    .cursor()?
+                    .get_one::<TotalDifficultyMask>(parent.into())
+                    .wrap_err_with(|| {
+                        format!(
+                            "Total difficulty of header {number} can't be derived from its parent"
+                        )
+                    })?
+                    .ok_or_else(|| eyre!("Parent of header {number} is missing"))?
+                    .0
+            }
+        };
+        let total_difficulty = if parent_total_difficulty.is_zero() {
+            U256::ZERO
+        } else {
+            parent_total_difficulty + header.difficulty()
+        };
+        replaced_total_difficulty = Some((row, total_difficulty));
+
+        rows.insert(
+            row,
+            vec![
+                compact(header.header()),
+                compact(CompactU256::from(total_difficulty)),
+                compact(header.hash()),
+            ],
+        );
+    }
+
+    Ok(rows)
+}
+
+/// Refetches the block bodies of the corrupted transactions of a static file.
+///
+/// Bodies are verified against the transactions root of their header.
+async fn refetch_transactions<P, C>(
+    provider: &P,
+    client: &C,
+    backoff: ConstantBuilder,
+    file: &CorruptedStaticFile,
+) -> eyre::Result<ReplacedRows>
+where
+    P: BlockReader + HeaderProvider<Header: BlockHeader> + BlockBodyIndicesProvider,
+    C: BodiesClient<Body: BlockBody<Transaction: Compact>>,
+{
+    let mut rows = ReplacedRows::new();
+    for (block, block_rows) in rows_by_block(provider, file)? {
+        let header = provider
+            .sealed_header(block)?
+            .ok_or_else(|| eyre!("Header of block {block} not found"))?;
+
+        let body = (|| async {
+            let (peer_id, bodies) = client.get_block_bodies(vec![header.hash()]).await?.split();
+            let body = bodies.into_iter().next().ok_or_else(|| eyre!("No body received"))?;
+            if body.calculate_tx_root() != header.transactions_root() {
+                client.report_bad_message(peer_id);
+                eyre::bail!("Received body doesn't match the transactions root")
+            }
+            Ok::<_, eyre::Report>(body)
+        })
+        .retry(backoff)
+        .notify(
+            |err, _| warn!(target: "reth::cli", %err, block, "Error requesting body. Retrying..."),
+        )
+        .await?;
+
+        for (row, index) in block_rows {
+            let transaction = body
+                .transactions()
+                .get(index)
+                .ok_or_else(|| eyre!("Transaction {index} of block {block} not found"))?;
+            rows.insert(row, vec![compact(transaction)]);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Refetches the block receipts of the corrupted receipts of a static file.
+///
+/// Receipts are verified against the receipts root of their header.
+async fn refetch_receipts<P, C>(
+    provider: &P,
+    client: &C,
+    backoff: ConstantBuilder,
+    file: &CorruptedStaticFile,
+) -> eyre::Result<ReplacedRows>
+where
+    P: BlockReader + HeaderProvider<Header: BlockHeader> + BlockBodyIndicesProvider,
+    C: ReceiptsClient<Receipt: Receipt + Compact>,
+{
+    let mut rows = ReplacedRows::new();
+    for (block, block_rows) in rows_by_block(provider, file)? {
+        let header = provider
+            .sealed_header(block)?
+            .ok_or_else(|| eyre!("Header of block {block} not found"))?;
+
+        let receipts = (|| async {
+            let (peer_id, response) = client.get_receipts(vec![header.hash()]).await?.split();
+            let receipts =
+                response.receipts.into_iter().next().ok_or_else(|| eyre!("No receipts received"))?;
+            let receipts_with_bloom =
+                receipts.iter().map(TxReceipt::with_bloom_ref).collect::<Vec<_>>();
+            if calculate_receipt_root(&receipts_with_bloom) != header.receipts_root() {
+                client.report_bad_message(peer_id);
+                eyre::bail!("Received receipts don't match the receipts root")
+            }
+            Ok::<_, eyre::Report>(receipts)
+        })
+        .retry(backoff)
+        .notify(|err, _| {
+            warn!(target: "reth::cli", %err, block, "Error requesting receipts. Retrying...")
+        })
+        .await?;
+
+        for (row, index) in block_rows {
+            let receipt = receipts
+                .get(index)
+                .ok_or_else(|| eyre!("Receipt {index} of block {block} not found"))?;
+            rows.insert(row, vec![compact(receipt)]);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Groups the corrupted rows of a transaction based static file by block, together with the index
+/// of their transaction in the block.
+fn rows_by_block<P>(
+    provider: &P,
+    file: &CorruptedStaticFile,
+) -> eyre::Result<BTreeMap<BlockNumber, Vec<(usize, usize)>>>
+where
+    P: BlockReader + BlockBodyIndicesProvider,
+{
+    let tx_start = file
+        .header
+        .tx_start()
+        .ok_or_else(|| eyre!("{} has no transactions", file.path.display()))?;
+
+    let mut blocks = BTreeMap::<_, Vec<_>>::new();
+    for &row in &file.rows {
+        let tx = tx_start + row as u64;
+        let block = provider
+            .block_by_transaction_id(tx)?
+            .ok_or_else(|| eyre!("Block of transaction {tx} not found"))?;
+        let indices = provider
+            .block_body_indices(block)?
+            .ok_or_else(|| eyre!("Body indices of block {block} not found"))?;
+        blocks.entry(block).or_default().push((row, (tx - indices.first_tx_num()) as usize));
+    }
+    Ok(blocks)
+}
+
+/// Encodes a column value the way the static file writer does.
+fn compact(value: impl Compact) -> Vec<u8> {
+    let mut buf = Vec::new();
+    value.to_compact(&mut buf);
+    buf
+}
+
+/// Verification results of the static files of a segment.
+#[derive(Debug, Default)]
+struct VerifyStats {
+    files: usize,
+    rows: usize,
+    without_checksums: usize,
+    corrupted_rows: usize,
+}
+
+impl VerifyStats {
+    fn row(&self, name: &str) -> Row {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(name))
+            .add_cell(Cell::new(self.files))
+            .add_cell(Cell::new(self.rows))
+            .add_cell(Cell::new(self.without_checksums))
+            .add_cell(Cell::new(self.corrupted_rows));
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::Header;
+    use reth_network_p2p::test_utils::TestFullBlockClient;
+    use reth_primitives_traits::SealedHeader;
+    use reth_provider::test_utils::create_test_provider_factory;
+    use std::{
+        fs::OpenOptions,
+        io::{Read, Seek, SeekFrom, Write},
+    };
+
+    #[test]
+    fn parse_verify_command() {
+        let cmd = Command::parse_from(["reth", "--segments", "headers,receipts", "--repair"]);
+        assert_eq!(cmd.segments, vec![StaticFileSegment::Headers, StaticFileSegment::Receipts]);
+        assert!(cmd.repair);
+        assert!(matches!(cmd.access_rights(), AccessRights::RW));
+
+        let cmd = Command::parse_from(["reth"]);
+        assert!(cmd.segments.is_empty());
+        assert!(matches!(cmd.access_rights(), AccessRights::RO));
+    }
+
+    #[tokio::test]
+    async fn repair_corrupted_headers() {
+        let factory = create_test_provider_factory();
+        let client = TestFullBlockClient::default();
+        let mut headers = Vec::<SealedHeader>::new();
+        for number in 0..4 {
+            let parent_hash = headers.last().map(|parent| parent.hash()).unwrap_or_default();
+            let header =
+                SealedHeader::seal_slow(Header { number, parent_hash, ..Default::default() });
+            client.insert(header.clone(), Default::default());
+            headers.push(header);
+        }
+        {
+            let static_file_provider = factory.static_file_provider();
+            let mut writer =
+                static_file_provider.latest_writer(StaticFileSegment::Headers).unwrap();
+            for header in &headers {
+                writer.append_header(header.header(), &header.hash()).unwrap();
+            }
+            writer.commit().unwrap();
+        }
+
+        let static_files_path = factory.static_file_provider().directory().to_path_buf();
+        let static_files = iter_static_files(&static_files_path).unwrap();
+        let (_, header) = static_files.get(StaticFileSegment::Headers).unwrap()[0].clone();
+        let path = static_files_path
+            .join(StaticFileSegment::Headers.filename(&header.expected_block_range()));
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+        assert!(corrupted_rows(&jar).unwrap().is_empty());
+
+        let corrupt_row = |row: usize| {
+            let range =
+                jar.open_data_reader().unwrap().row_range(row, jar.columns(), jar.rows()).unwrap();
+            let mut data_file = OpenOptions::new().read(true).write(true).open(&path).unwrap();
+            let mut byte = [0];
+            data_file.seek(SeekFrom::Start(range.start as u64)).unwrap();
+            data_file.read_exact(&mut byte).unwrap();
+            data_file.seek(SeekFrom::Start(range.start as u64)).unwrap();
+            data_file.write_all(&[byte[0] ^ 1]).unwrap();
+        };
+
+        // Flip a bit of the third header
+        corrupt_row(2);
+
+        let corrupted = corrupted_rows(&jar).unwrap();
+        assert!(matches!(
+            corrupted.as_slice(),
+            [(2, NippyJarError::ChecksumMismatch { row: 2, .. })]
+        ));
+
+        let file =
+            CorruptedStaticFile { path: path.clone(), header: header.clone(), rows: vec![2] };
+        let rows =
+            refetch_headers(&factory, &client, ConstantBuilder::default(), &file).await.unwrap();
+        replace_rows(&file, rows).unwrap();
+
+        let assert_headers = || {
+            let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+            let mut cursor = NippyJarCursor::new(&jar).unwrap();
+            for header in &headers {
+                let values = cursor.next_row().unwrap().unwrap();
+                assert_eq!(values[0], compact(header.header()));
+                assert_eq!(values[2], compact(header.hash()));
+            }
+            assert!(cursor.next_row().unwrap().is_none());
+        };
+        assert_headers();
+
+        // The total difficulty of a header can't be derived from a corrupted parent
+        factory.static_file_provider().remove_cached_provider(
+            StaticFileSegment::Headers,
+            header.expected_block_range().end(),
+        );
+        corrupt_row(1);
+        corrupt_row(2);
+        let file =
+            CorruptedStaticFile { path: path.clone(), header: header.clone(), rows: vec![2] };
+        assert!(refetch_headers(&factory, &client, ConstantBuilder::default(), &file)
+            .await
+            .is_err());
+
+        // Unless the parent is refetched as well
+        let file = CorruptedStaticFile { path: path.clone(), header, rows: vec![1, 2] };
+        let rows =
+            refetch_headers(&factory, &client, ConstantBuilder::default(), &file).await.unwrap();
+        replace_rows(&file, rows).unwrap();
+        assert_headers();
+    }
+}
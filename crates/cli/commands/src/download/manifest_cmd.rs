@@ -140,7 +140,7 @@ fn infer_snapshot_block_from_headers(source_datadir: &std::path::Path) -> Result
 }
 
 /// Infers the static-file block span from header file ranges.
-fn infer_blocks_per_file(source_datadir: &std::path::Path) -> Result<u64> {
+pub(crate) fn infer_blocks_per_file(source_datadir: &std::path::Path) -> Result<u64> {
     let mut inferred = None;
     for (start, end) in header_ranges(source_datadir)? {
         let span = end.saturating_sub(start).saturating_add(1);
@@ -24,7 +24,10 @@ pub mod node;
 pub mod p2p;
 pub mod prune;
 pub mod re_execute;
+pub mod replica;
+pub mod rpc_replay;
 pub mod stage;
+pub mod stateless_validate;
 #[cfg(feature = "arbitrary")]
 pub mod test_vectors;
 
@@ -11,7 +11,7 @@ use reth_node_core::{
     args::{
         DatabaseArgs, DatadirArgs, DebugArgs, DevArgs, EngineArgs, EraArgs, JitArgs, MetricArgs,
         NetworkArgs, PayloadBuilderArgs, PruningArgs, RpcServerArgs, StaticFilesArgs, StorageArgs,
-        TxPoolArgs,
+        TrieVerifierArgs, TxPoolArgs,
     },
     node_config::NodeConfig,
     version,
@@ -123,6 +123,10 @@ pub struct NodeCommand<C: ChainSpecParser, Ext: clap::Args + fmt::Debug = NoArgs
     #[command(flatten, next_help_heading = "JIT")]
     pub jit: JitArgs,
 
+    /// All background trie verifier related arguments with --trie-verifier prefix
+    #[command(flatten, next_help_heading = "Trie Verifier")]
+    pub trie_verifier: TrieVerifierArgs,
+
     /// Additional cli arguments
     #[command(flatten, next_help_heading = "Extension")]
     pub ext: Ext,
@@ -180,6 +184,7 @@ where
             static_files,
             storage,
             jit,
+            trie_verifier,
             ext,
         } = self;
 
@@ -205,6 +210,7 @@ where
             static_files,
             storage,
             jit,
+            trie_verifier,
         };
 
         let data_dir = node_config.datadir();
@@ -0,0 +1,884 @@
+//! Network crawler command
+
+use alloy_primitives::{hex, B256, U256};
+use clap::{Parser, ValueEnum};
+use futures::{stream::FuturesUnordered, StreamExt};
+use reth_chainspec::{EthChainSpec, ForkFilter, ForkId, Hardforks, Head, ValidationError};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_util::{get_secret_key, load_secret_key::rng_secret_key};
+use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config};
+use reth_discv5::{
+    discv5::{self, ListenConfig},
+    Discv5,
+};
+use reth_ecies::stream::ECIESStream;
+use reth_eth_wire::{
+    errors::{EthHandshakeError, EthStreamError, P2PStreamError},
+    CanDisconnect, DisconnectReason, EthNetworkPrimitives, HelloMessage, HelloMessageWithProtocols,
+    UnauthedEthStream, UnauthedP2PStream, UnifiedStatus,
+};
+use reth_network_peers::{pk2id, NodeRecord, PeerId, TrustedPeer};
+use secp256k1::{SecretKey, SECP256K1};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fs::File,
+    io::Write,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpStream, sync::mpsc, time::timeout};
+use tracing::{info, warn};
+
+/// Interval of the discv4 lookups, much shorter than the default to walk the network quickly.
+const LOOKUP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Interval of the discv5 lookups, in seconds.
+const DISCV5_LOOKUP_INTERVAL: u64 = 5;
+
+/// Interval of the progress logs, the census file is rewritten at the same interval.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Crawl the discovery network and write a census of the discovered nodes.
+///
+/// Every discovered node is probed once: the crawler connects to it, performs the RLPx and `eth`
+/// handshakes and records what the node advertised.
+#[derive(Debug, Parser)]
+pub struct Command<C: ChainSpecParser> {
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = C::help_message(),
+        default_value = C::default_value(),
+        value_parser = C::parser()
+    )]
+    pub chain: Arc<C::ChainSpec>,
+
+    /// Listen address for discv4.
+    #[arg(long, default_value = "0.0.0.0:30305")]
+    pub addr: SocketAddr,
+
+    /// Also crawl discv5, listening on the given address.
+    #[arg(long = "discv5.addr", value_name = "ADDR")]
+    pub discv5_addr: Option<SocketAddr>,
+
+    /// Comma separated enode URLs to start the crawl from.
+    ///
+    /// Defaults to the bootnodes of the chain.
+    #[arg(long, value_delimiter = ',')]
+    pub bootnodes: Option<Vec<TrustedPeer>>,
+
+    /// Secret key to use for discovery and the RLPx handshakes.
+    ///
+    /// If a path is provided but no key exists at that path, a new random secret will be
+    /// generated and stored there. If no path is specified, a new ephemeral random secret will
+    /// be used.
+    #[arg(long, value_name = "PATH")]
+    pub p2p_secret_key: Option<PathBuf>,
+
+    /// How long to crawl.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "5m")]
+    pub duration: Duration,
+
+    /// Stop once this many nodes have been probed.
+    #[arg(long)]
+    pub max_nodes: Option<usize>,
+
+    /// Maximum number of nodes that are probed concurrently.
+    #[arg(long, default_value_t = 32)]
+    pub concurrency: usize,
+
+    /// Timeout for connecting to a node, and for the handshakes with it.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "10s")]
+    pub timeout: Duration,
+
+    /// Output format of the census.
+    #[arg(long, value_enum, default_value_t = CensusFormat::Json)]
+    pub format: CensusFormat,
+
+    /// File the census is written to.
+    ///
+    /// The file is rewritten periodically while crawling, so an interrupted crawl keeps the
+    /// nodes probed so far.
+    #[arg(long, short, value_name = "FILE")]
+    pub output: PathBuf,
+
+    /// Also write the ENRs of the reachable nodes to this file, one `enr:` record per line.
+    ///
+    /// Only nodes found with discv5 have a known ENR. The file can be passed to
+    /// `reth p2p dns-tree --enrs`.
+    #[arg(long, value_name = "FILE")]
+    pub enr_output: Option<PathBuf>,
+}
+
+impl<C: ChainSpecParser<ChainSpec: EthChainSpec + Hardforks>> Command<C> {
+    /// Execute the `p2p crawl` command.
+    pub async fn execute(self) -> eyre::Result<()> {
+        let secret_key = match &self.p2p_secret_key {
+            Some(path) => get_secret_key(path)?,
+            None => rng_secret_key(),
+        };
+        let bootnodes = self.resolved_bootnodes().await;
+        if bootnodes.is_empty() {
+            eyre::bail!("No bootnodes to start the crawl from, set them with `--bootnodes`");
+        }
+        let prober = Arc::new(Prober::new(&*self.chain, secret_key, self.timeout));
+
+        // the crawler doesn't accept connections, it advertises no RLPx port
+        let local_record = NodeRecord::from_secret_key(self.addr, &secret_key).with_tcp_port(0);
+        let discv4_config = Discv4Config::builder()
+            .add_boot_nodes(bootnodes.clone())
+            .lookup_interval(LOOKUP_INTERVAL)
+            .enable_dht_random_walk(true)
+            .build();
+        let (discv4, mut discv4_service) =
+            Discv4::bind(self.addr, local_record, secret_key, discv4_config).await?;
+        let mut discv4_updates = discv4_service.update_stream();
+        discv4_service.spawn();
+        info!(target: "reth::cli", addr = %self.addr, "Started discv4");
+
+        let (discv5, mut discv5_updates) = match self.discv5_addr {
+            Some(addr) => {
+                let (discv5, updates) = start_discv5(addr, &secret_key, &bootnodes).await?;
+                info!(target: "reth::cli", %addr, "Started discv5");
+                (Some(discv5), Some(updates))
+            }
+            None => (None, None),
+        };
+
+        let mut crawl = Crawl::new(local_record.id);
+        let mut probes = FuturesUnordered::new();
+        let mut deadline = std::pin::pin!(tokio::time::sleep(self.duration));
+        let mut progress = tokio::time::interval_at(
+            tokio::time::Instant::now() + PROGRESS_INTERVAL,
+            PROGRESS_INTERVAL,
+        );
+
+        loop {
+            let probed = crawl.census.len() + probes.len();
+            let remaining = self.max_nodes.map_or(usize::MAX, |max| max.saturating_sub(probed));
+            let available = self.concurrency.saturating_sub(probes.len()).min(remaining);
+            for node in crawl.queue.drain(..available.min(crawl.queue.len())) {
+                probes.push(prober.clone().probe(node));
+            }
+            if remaining == 0 && probes.is_empty() {
+                break
+            }
+
+            tokio::select! {
+                _ = &mut deadline => break,
+                Some(update) = discv4_updates.next() => crawl.on_discv4_update(update),
+                Some(event) = async {
+                    match &mut discv5_updates {
+                        Some(updates) => updates.recv().await,
+                        None => None,
+                    }
+                } => {
+                    let enr = match &event {
+                        discv5::Event::SessionEstablished(enr, _) => Some(enr.to_base64()),
+                        _ => None,
+                    };
+                    if let Some(peer) = discv5.as_ref().and_then(|d| d.on_discv5_update(event)) {
+                        if let Some(enr) = enr {
+                            crawl.enrs.insert(peer.node_record.id, enr);
+                        }
+                        let source = DiscoverySource::Discv5;
+                        crawl.on_discovered(peer.node_record, source, peer.fork_id);
+                    }
+                }
+                Some(census) = probes.next() => crawl.census.push(census),
+                _ = progress.tick() => {
+                    crawl.log_progress(probes.len());
+                    crawl.fill_enr_fields();
+                    self.write_output(&crawl.census)?;
+                }
+            }
+        }
+
+        if !probes.is_empty() {
+            info!(target: "reth::cli", in_flight = probes.len(), "Waiting for in-flight probes");
+            while let Some(census) = probes.next().await {
+                crawl.census.push(census);
+            }
+        }
+        discv4.terminate();
+
+        crawl.fill_enr_fields();
+        self.write_output(&crawl.census)?;
+        crawl.log_summary();
+        info!(target: "reth::cli", output = %self.output.display(), "Wrote census");
+
+        Ok(())
+    }
+
+    /// Writes the census, and the ENRs of the reachable nodes if configured.
+    fn write_output(&self, census: &[NodeCensus]) -> eyre::Result<()> {
+        write_census(&self.output, self.format, census)?;
+        if let Some(path) = &self.enr_output {
+            write_enrs(path, census)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the bootnodes to start the crawl from.
+    async fn resolved_bootnodes(&self) -> Vec<NodeRecord> {
+        let Some(bootnodes) = &self.bootnodes else {
+            return self.chain.bootnodes().unwrap_or_default()
+        };
+        let mut resolved = Vec::with_capacity(bootnodes.len());
+        for node in bootnodes {
+            match node.resolve().await {
+                Ok(record) => resolved.push(record),
+                Err(err) => {
+                    warn!(target: "reth::cli", %node, %err, "Failed to resolve bootnode")
+                }
+            }
+        }
+        resolved
+    }
+}
+
+/// Starts discv5 on the given address.
+async fn start_discv5(
+    addr: SocketAddr,
+    secret_key: &SecretKey,
+    bootnodes: &[NodeRecord],
+) -> eyre::Result<(Discv5, mpsc::Receiver<discv5::Event>)> {
+    let listen = match addr {
+        SocketAddr::V4(addr) => ListenConfig::Ipv4 { ip: *addr.ip(), port: addr.port() },
+        SocketAddr::V6(addr) => ListenConfig::Ipv6 { ip: *addr.ip(), port: addr.port() },
+    };
+    // no RLPx listener, the record carries `tcp=0`
+    let config = reth_discv5::Config::builder(SocketAddr::new(addr.ip(), 0))
+        .discv5_config(discv5::ConfigBuilder::new(listen).build())
+        .add_unsigned_boot_nodes(bootnodes.iter().copied())
+        .lookup_interval(DISCV5_LOOKUP_INTERVAL)
+        .build();
+    Ok(Discv5::start(secret_key, config).await?)
+}
+
+/// State of a crawl.
+#[derive(Debug)]
+struct Crawl {
+    /// The id of the crawler itself.
+    local_id: PeerId,
+    /// All nodes discovered so far.
+    seen: HashSet<PeerId>,
+    /// Discovered nodes that have not been probed yet.
+    queue: VecDeque<DiscoveredNode>,
+    /// Fork ids that discv4 reported via EIP-868 after the node was discovered.
+    enr_fork_ids: HashMap<PeerId, ForkId>,
+    /// The ENRs of nodes found with discv5, in their `enr:` text form.
+    enrs: HashMap<PeerId, String>,
+    /// The probed nodes.
+    census: Vec<NodeCensus>,
+}
+
+impl Crawl {
+    fn new(local_id: PeerId) -> Self {
+        Self {
+            local_id,
+            seen: HashSet::new(),
+            queue: VecDeque::new(),
+            enr_fork_ids: HashMap::new(),
+            enrs: HashMap::new(),
+            census: Vec::new(),
+        }
+    }
+
+    fn on_discv4_update(&mut self, update: DiscoveryUpdate) {
+        match update {
+            DiscoveryUpdate::Added(record) | DiscoveryUpdate::DiscoveredAtCapacity(record) => {
+                self.on_discovered(record, DiscoverySource::Discv4, None)
+            }
+            DiscoveryUpdate::EnrForkId(record, fork_id) => {
+                self.enr_fork_ids.insert(record.id, fork_id);
+            }
+            DiscoveryUpdate::Removed(_) => {}
+            DiscoveryUpdate::Batch(updates) => {
+                for update in updates {
+                    self.on_discv4_update(update);
+                }
+            }
+        }
+    }
+
+    /// Queues the node for probing, unless it has been discovered before.
+    fn on_discovered(
+        &mut self,
+        record: NodeRecord,
+        source: DiscoverySource,
+        enr_fork_id: Option<ForkId>,
+    ) {
+        if record.id == self.local_id || !self.seen.insert(record.id) {
+            return
+        }
+        self.queue.push_back(DiscoveredNode { record, source, enr_fork_id });
+    }
+
+    /// Sets the ENRs and fork ids that were reported after the node had been queued, e.g. if the
+    /// node was found with discv4 first.
+    fn fill_enr_fields(&mut self) {
+        for census in &mut self.census {
+            if census.enr_fork_id.is_none() &&
+                let Some(fork_id) = self.enr_fork_ids.get(&census.id)
+            {
+                census.enr_fork_id = Some(format_fork_id(fork_id));
+            }
+            if census.enr.is_none() &&
+                let Some(enr) = self.enrs.get(&census.id)
+            {
+                census.enr = Some(enr.clone());
+            }
+        }
+    }
+
+    fn reachable(&self) -> usize {
+        self.census.iter().filter(|census| census.reachability == Reachability::Reachable).count()
+    }
+
+    fn log_progress(&self, in_flight: usize) {
+        info!(target: "reth::cli",
+            discovered = self.seen.len(),
+            probed = self.census.len(),
+            reachable = self.reachable(),
+            queued = self.queue.len(),
+            in_flight,
+            "Crawling"
+        );
+    }
+
+    fn log_summary(&self) {
+        let mut reachability = BTreeMap::<_, usize>::new();
+        let mut clients = BTreeMap::<_, usize>::new();
+        for census in &self.census {
+            *reachability.entry(census.reachability.as_str()).or_default() += 1;
+            if let Some(client) = &census.client_version {
+                // the client name without the version, e.g. `Geth`
+                let name = client.split('/').next().unwrap_or(client);
+                *clients.entry(name.to_string()).or_default() += 1;
+            }
+        }
+        info!(target: "reth::cli",
+            discovered = self.seen.len(),
+            probed = self.census.len(),
+            ?reachability,
+            ?clients,
+            "Crawl finished"
+        );
+    }
+}
+
+/// The discovery protocol a node was found with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoverySource {
+    /// Discovery v4.
+    Discv4,
+    /// Discovery v5.
+    Discv5,
+}
+
+impl DiscoverySource {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Discv4 => "discv4",
+            Self::Discv5 => "discv5",
+        }
+    }
+}
+
+/// A node that was found via discovery.
+#[derive(Debug, Clone)]
+struct DiscoveredNode {
+    record: NodeRecord,
+    source: DiscoverySource,
+    /// The fork id of the `eth` entry of the node's ENR.
+    enr_fork_id: Option<ForkId>,
+}
+
+/// The outcome of probing a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reachability {
+    /// Completed the RLPx and `eth` handshakes.
+    Reachable,
+    /// Completed the RLPx handshake, but the `eth` handshake failed, e.g. because the node is on
+    /// another chain. The parts of the node's status that the handshake rejected are recorded.
+    Incompatible,
+    /// Disconnected during the handshakes, e.g. because it has no free peer slots.
+    Disconnected,
+    /// Accepted the connection, but the RLPx handshake failed or timed out.
+    HandshakeFailed,
+    /// Could not be connected to.
+    Unreachable,
+}
+
+impl Reachability {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reachable => "reachable",
+            Self::Incompatible => "incompatible",
+            Self::Disconnected => "disconnected",
+            Self::HandshakeFailed => "handshake_failed",
+            Self::Unreachable => "unreachable",
+        }
+    }
+}
+
+/// Output formats of the census.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CensusFormat {
+    /// A JSON array with an object per node.
+    Json,
+    /// CSV with a row per node.
+    Csv,
+}
+
+/// What a probed node advertised.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeCensus {
+    /// The node id.
+    pub id: PeerId,
+    /// The `enode://` URL of the node.
+    pub enode: String,
+    /// The `enr:` record of the node, if it was found with discv5.
+    pub enr: Option<String>,
+    /// The discovery protocol the node was found with.
+    pub discovery: DiscoverySource,
+    /// The fork id of the `eth` entry of the node's ENR.
+    pub enr_fork_id: Option<String>,
+    /// The outcome of the probe.
+    pub reachability: Reachability,
+    /// Why the probe failed.
+    pub error: Option<String>,
+    /// Time it took to establish the TCP connection.
+    pub latency_ms: Option<u64>,
+    /// The client version from the `p2p` hello.
+    pub client_version: Option<String>,
+    /// The capabilities from the `p2p` hello, e.g. `eth/68`.
+    pub capabilities: Vec<String>,
+    /// The negotiated `eth` version.
+    pub eth_version: Option<u8>,
+    /// The chain id from the `eth` status.
+    pub chain_id: Option<u64>,
+    /// The genesis hash from the `eth` status.
+    pub genesis: Option<B256>,
+    /// The fork id from the `eth` status.
+    pub fork_id: Option<String>,
+    /// The hash of the node's head block.
+    pub head_hash: Option<B256>,
+    /// The number of the node's head block, advertised since `eth/69`.
+    pub head_number: Option<u64>,
+    /// The total difficulty of the node's chain, advertised before `eth/69`.
+    pub total_difficulty: Option<U256>,
+}
+
+impl NodeCensus {
+    /// Column names of the CSV output.
+    const CSV_HEADER: [&'static str; 17] = [
+        "id",
+        "enode",
+        "enr",
+        "discovery",
+        "enr_fork_id",
+        "reachability",
+        "error",
+        "latency_ms",
+        "client_version",
+        "capabilities",
+        "eth_version",
+        "chain_id",
+        "genesis",
+        "fork_id",
+        "head_hash",
+        "head_number",
+        "total_difficulty",
+    ];
+
+    fn new(node: &DiscoveredNode) -> Self {
+        Self {
+            id: node.record.id,
+            enode: node.record.to_string(),
+            enr: None,
+            discovery: node.source,
+            enr_fork_id: node.enr_fork_id.as_ref().map(format_fork_id),
+            reachability: Reachability::Unreachable,
+            error: None,
+            latency_ms: None,
+            client_version: None,
+            capabilities: Vec::new(),
+            eth_version: None,
+            chain_id: None,
+            genesis: None,
+            fork_id: None,
+            head_hash: None,
+            head_number: None,
+            total_difficulty: None,
+        }
+    }
+
+    fn failed(mut self, reachability: Reachability, error: impl ToString) -> Self {
+        self.reachability = reachability;
+        self.error = Some(error.to_string());
+        self
+    }
+
+    fn on_hello(&mut self, hello: HelloMessage) {
+        self.client_version = Some(hello.client_version);
+        self.capabilities = hello.capabilities.iter().map(ToString::to_string).collect();
+    }
+
+    /// Records what the `eth` handshake error reveals about the node's status, e.g. the chain of
+    /// a node on another chain.
+    fn on_handshake_error(&mut self, err: &EthStreamError) {
+        let EthStreamError::EthHandshakeError(err) = err else { return };
+        match err {
+            EthHandshakeError::MismatchedGenesis(genesis) => self.genesis = Some(genesis.got),
+            EthHandshakeError::MismatchedChain(chain) => self.chain_id = Some(chain.got.id()),
+            EthHandshakeError::InvalidFork(
+                ValidationError::RemoteStale { remote, .. } |
+                ValidationError::LocalIncompatibleOrStale { remote, .. },
+            ) => self.fork_id = Some(format_fork_id(remote)),
+            _ => {}
+        }
+    }
+
+    fn on_status(&mut self, status: UnifiedStatus) {
+        self.reachability = Reachability::Reachable;
+        self.eth_version = Some(status.version as u8);
+        self.chain_id = Some(status.chain.id());
+        self.genesis = Some(status.genesis);
+        self.fork_id = Some(format_fork_id(&status.forkid));
+        self.head_hash = Some(status.blockhash);
+        self.head_number = status.latest_block;
+        self.total_difficulty = status.total_difficulty;
+    }
+
+    fn csv_record(&self) -> [String; 17] {
+        fn opt<T: ToString>(value: Option<&T>) -> String {
+            value.map(ToString::to_string).unwrap_or_default()
+        }
+        [
+            self.id.to_string(),
+            self.enode.clone(),
+            opt(self.enr.as_ref()),
+            self.discovery.as_str().to_string(),
+            opt(self.enr_fork_id.as_ref()),
+            self.reachability.as_str().to_string(),
+            opt(self.error.as_ref()),
+            opt(self.latency_ms.as_ref()),
+            opt(self.client_version.as_ref()),
+            self.capabilities.join(" "),
+            opt(self.eth_version.as_ref()),
+            opt(self.chain_id.as_ref()),
+            opt(self.genesis.as_ref()),
+            opt(self.fork_id.as_ref()),
+            opt(self.head_hash.as_ref()),
+            opt(self.head_number.as_ref()),
+            opt(self.total_difficulty.as_ref()),
+        ]
+    }
+}
+
+/// Formats the fork id as `<hash>/<next>`.
+fn format_fork_id(fork_id: &ForkId) -> String {
+    format!("{}/{}", hex::encode_prefixed(fork_id.hash.0), fork_id.next)
+}
+
+/// Writes the census to the file, replacing it atomically.
+fn write_census(path: &Path, format: CensusFormat, census: &[NodeCensus]) -> eyre::Result<()> {
+    reth_fs_util::atomic_write_file(path, |file| match format {
+        CensusFormat::Json => {
+            serde_json::to_writer_pretty(file, census).map_err(eyre::Report::from)
+        }
+        CensusFormat::Csv => write_csv(file, census),
+    })?;
+    Ok(())
+}
+
+/// Writes the ENRs of the reachable nodes to the file, one per line, replacing it atomically.
+fn write_enrs(path: &Path, census: &[NodeCensus]) -> eyre::Result<()> {
+    reth_fs_util::atomic_write_file(path, |file| {
+        let reachable = census.iter().filter(|node| node.reachability == Reachability::Reachable);
+        for enr in reachable.filter_map(|node| node.enr.as_ref()) {
+            writeln!(file, "{enr}")?;
+        }
+        Ok::<_, std::io::Error>(())
+    })?;
+    Ok(())
+}
+
+fn write_csv(file: &mut File, census: &[NodeCensus]) -> eyre::Result<()> {
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(NodeCensus::CSV_HEADER)?;
+    for node in census {
+        writer.write_record(node.csv_record())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Why probing a node failed.
+#[derive(Debug)]
+struct ProbeFailure {
+    reachability: Reachability,
+    error: String,
+}
+
+impl From<P2PStreamError> for ProbeFailure {
+    fn from(err: P2PStreamError) -> Self {
+        let reachability = if err.as_disconnected().is_some() {
+            Reachability::Disconnected
+        } else {
+            Reachability::HandshakeFailed
+        };
+        Self { reachability, error: err.to_string() }
+    }
+}
+
+impl From<EthStreamError> for ProbeFailure {
+    fn from(err: EthStreamError) -> Self {
+        let reachability = if err.as_disconnected().is_some() {
+            Reachability::Disconnected
+        } else {
+            Reachability::Incompatible
+        };
+        Self { reachability, error: err.to_string() }
+    }
+}
+
+/// Performs the handshakes with discovered nodes.
+#[derive(Debug)]
+struct Prober {
+    secret_key: SecretKey,
+    hello: HelloMessageWithProtocols,
+    status: UnifiedStatus,
+    fork_filter: ForkFilter,
+    timeout: Duration,
+}
+
+impl Prober {
+    /// Creates a prober that announces the genesis of the chain as its head, like a node that
+    /// just started syncing.
+    fn new<Spec>(chain_spec: &Spec, secret_key: SecretKey, timeout: Duration) -> Self
+    where
+        Spec: EthChainSpec + Hardforks,
+    {
+        let head = Head {
+            hash: chain_spec.genesis_hash(),
+            number: 0,
+            timestamp: chain_spec.genesis().timestamp,
+            difficulty: chain_spec.genesis().difficulty,
+            total_difficulty: chain_spec.genesis().difficulty,
+        };
+        let peer_id = pk2id(&secret_key.public_key(SECP256K1));
+        Self {
+            secret_key,
+            // the crawler doesn't accept connections, it advertises no listening port
+            hello: HelloMessageWithProtocols::builder(peer_id).port(0).build(),
+            status: UnifiedStatus::spec_builder(chain_spec, &head),
+            fork_filter: chain_spec.fork_filter(head),
+            timeout,
+        }
+    }
+
+    /// Connects to the node and records the outcome of the handshakes.
+    async fn probe(self: Arc<Self>, node: DiscoveredNode) -> NodeCensus {
+        let census = NodeCensus::new(&node);
+        let record = node.record;
+        if record.tcp_port == 0 {
+            return census.failed(Reachability::Unreachable, "no RLPx port advertised")
+        }
+
+        let start = Instant::now();
+        let stream = match timeout(self.timeout, TcpStream::connect(record.tcp_addr())).await {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(err)) => return census.failed(Reachability::Unreachable, err),
+            Err(_) => return census.failed(Reachability::Unreachable, "connection timed out"),
+        };
+        let mut census = census;
+        census.latency_ms = Some(start.elapsed().as_millis() as u64);
+
+        let result = timeout(self.timeout, self.handshake(stream, record.id, &mut census)).await;
+        match result {
+            Ok(Ok(())) => census,
+            Ok(Err(failure)) => census.failed(failure.reachability, failure.error),
+            Err(_) => {
+                // the p2p handshake completed if the hello was recorded
+                let reachability = if census.client_version.is_some() {
+                    Reachability::Incompatible
+                } else {
+                    Reachability::HandshakeFailed
+                };
+                census.failed(reachability, "handshake timed out")
+            }
+        }
+    }
+
+    async fn handshake(
+        &self,
+        stream: TcpStream,
+        id: PeerId,
+        census: &mut NodeCensus,
+    ) -> Result<(), ProbeFailure> {
+        let ecies_stream =
+            ECIESStream::connect(stream, self.secret_key, id).await.map_err(|err| {
+                ProbeFailure { reachability: Reachability::HandshakeFailed, error: err.to_string() }
+            })?;
+
+        let (p2p_stream, their_hello) =
+            UnauthedP2PStream::new(ecies_stream).handshake(self.hello.clone()).await?;
+        census.on_hello(their_hello);
+
+        let mut status = self.status;
+        status.version = p2p_stream
+            .shared_capabilities()
+            .eth()?
+            .version()
+            .try_into()
+            .map_err(EthStreamError::from)?;
+
+        // the probe is already bounded by its own timeout
+        let (mut eth_stream, their_status) = UnauthedEthStream::new(p2p_stream)
+            .handshake_without_timeout::<EthNetworkPrimitives>(status, self.fork_filter.clone())
+            .await
+            .inspect_err(|err| census.on_handshake_error(err))?;
+        census.on_status(their_status);
+
+        let _ = eth_stream.disconnect(DisconnectReason::ClientQuitting).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_chainspec::Chain;
+    use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+    use reth_primitives_traits::GotExpected;
+
+    #[test]
+    fn parse_crawl_args() {
+        let cmd = Command::<EthereumChainSpecParser>::try_parse_from([
+            "reth",
+            "--chain",
+            "sepolia",
+            "--duration",
+            "1m",
+            "--discv5.addr",
+            "0.0.0.0:30306",
+            "--format",
+            "csv",
+            "-o",
+            "census.csv",
+        ])
+        .unwrap();
+        assert_eq!(cmd.duration, Duration::from_secs(60));
+        assert_eq!(cmd.discv5_addr, Some("0.0.0.0:30306".parse().unwrap()));
+        assert_eq!(cmd.format, CensusFormat::Csv);
+        assert_eq!(cmd.timeout, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn crawl_dedups_discovered_nodes() {
+        let local = NodeRecord::new("127.0.0.1:30303".parse().unwrap(), PeerId::random());
+        let node = NodeRecord::new("127.0.0.1:30304".parse().unwrap(), PeerId::random());
+        let fork_id = ForkId { hash: reth_chainspec::ForkHash([1, 2, 3, 4]), next: 5 };
+
+        let mut crawl = Crawl::new(local.id);
+        crawl.on_discv4_update(DiscoveryUpdate::Batch(vec![
+            DiscoveryUpdate::Added(local),
+            DiscoveryUpdate::Added(node),
+            DiscoveryUpdate::EnrForkId(node, fork_id),
+        ]));
+        crawl.enrs.insert(node.id, "enr:-test".to_string());
+        crawl.on_discovered(node, DiscoverySource::Discv5, None);
+        assert_eq!(crawl.queue.len(), 1);
+
+        let queued = crawl.queue.pop_front().unwrap();
+        assert_eq!(queued.source, DiscoverySource::Discv4);
+        crawl.census.push(NodeCensus::new(&queued).failed(Reachability::Unreachable, "refused"));
+        crawl.fill_enr_fields();
+        assert_eq!(crawl.census[0].enr_fork_id.as_deref(), Some("0x01020304/5"));
+        assert_eq!(crawl.census[0].enr.as_deref(), Some("enr:-test"));
+    }
+
+    #[test]
+    fn records_status_of_incompatible_nodes() {
+        let node = DiscoveredNode {
+            record: NodeRecord::new("127.0.0.1:30303".parse().unwrap(), PeerId::random()),
+            source: DiscoverySource::Discv4,
+            enr_fork_id: None,
+        };
+        let local = ForkId { hash: reth_chainspec::ForkHash([1, 2, 3, 4]), next: 0 };
+        let remote = ForkId { hash: reth_chainspec::ForkHash([5, 6, 7, 8]), next: 0 };
+        let errors = [
+            EthHandshakeError::MismatchedChain(GotExpected {
+                got: Chain::sepolia(),
+                expected: Chain::mainnet(),
+            }),
+            EthHandshakeError::InvalidFork(ValidationError::LocalIncompatibleOrStale {
+                local,
+                remote,
+            }),
+        ];
+
+        let mut census = NodeCensus::new(&node);
+        for err in errors {
+            let err = EthStreamError::from(err);
+            census.on_handshake_error(&err);
+            let ProbeFailure { reachability, error } = err.into();
+            census = census.failed(reachability, error);
+        }
+        assert_eq!(census.reachability, Reachability::Incompatible);
+        assert_eq!(census.chain_id, Some(Chain::sepolia().id()));
+        assert_eq!(census.fork_id.as_deref(), Some("0x05060708/0"));
+    }
+
+    #[test]
+    fn write_census_formats() {
+        let node = DiscoveredNode {
+            record: NodeRecord::new("127.0.0.1:30303".parse().unwrap(), PeerId::random()),
+            source: DiscoverySource::Discv4,
+            enr_fork_id: None,
+        };
+        let mut census = NodeCensus::new(&node);
+        census.enr = Some("enr:-test".to_string());
+        census.on_hello(HelloMessage {
+            client_version: "Geth/v1.14.0, linux".to_string(),
+            ..HelloMessageWithProtocols::builder(node.record.id).build().message()
+        });
+        census.on_status(UnifiedStatus::default());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("census");
+
+        write_census(&path, CensusFormat::Json, std::slice::from_ref(&census)).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&reth_fs_util::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json[0]["reachability"], "reachable");
+        assert_eq!(json[0]["chain_id"], 1);
+        assert_eq!(json[0]["discovery"], "discv4");
+
+        write_census(&path, CensusFormat::Csv, std::slice::from_ref(&census)).unwrap();
+        let csv = reth_fs_util::read_to_string(&path).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap(), NodeCensus::CSV_HEADER.join(","));
+        // the client version contains a comma and is quoted
+        assert!(
+            lines.next().unwrap().contains(",enr:-test,discv4,,reachable,,") &&
+                csv.contains("\"Geth/v1.14.0, linux\"")
+        );
+
+        // only reachable nodes are written to the ENR file
+        let unreachable = census.clone().failed(Reachability::Unreachable, "refused");
+        write_enrs(&path, &[census, unreachable]).unwrap();
+        assert_eq!(reth_fs_util::read_to_string(&path).unwrap(), "enr:-test\n");
+    }
+}
@@ -0,0 +1,164 @@
+//! Command for building EIP-1459 DNS node trees
+
+use clap::{Parser, ValueEnum};
+use eyre::WrapErr;
+use reth_cli_util::get_secret_key;
+use reth_dns_discovery::{tree::LinkEntry, DnsTree};
+use reth_network_peers::Enr;
+use secp256k1::SecretKey;
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Build and sign an EIP-1459 node tree that can be published via DNS.
+///
+/// Prints the records of the tree, followed by the `enrtree://` link clients use to sync it.
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The domain the tree is published at, e.g. `nodes.example.org`.
+    #[arg(long)]
+    pub domain: String,
+
+    /// Secret key the root of the tree is signed with.
+    ///
+    /// If no key exists at the path, a new random key is generated and stored there.
+    #[arg(long, value_name = "PATH")]
+    pub signing_key: PathBuf,
+
+    /// File with the ENRs of the tree, one `enr:` record per line, `-` reads from stdin.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    #[arg(long, value_name = "FILE")]
+    pub enrs: PathBuf,
+
+    /// Link to another tree (`enrtree://<key>@<domain>`) that is included in the tree.
+    ///
+    /// Can be repeated.
+    #[arg(long = "link", value_name = "ENRTREE")]
+    pub links: Vec<LinkEntry>,
+
+    /// Sequence number of the tree root, must increase with every update of the tree.
+    ///
+    /// Defaults to the current unix timestamp.
+    #[arg(long)]
+    pub seq: Option<u64>,
+
+    /// Time to live of the records in the zone file, in seconds.
+    #[arg(long, default_value_t = 3600)]
+    pub ttl: u32,
+
+    /// Output format of the records.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Zone)]
+    pub format: OutputFormat,
+
+    /// File to write the records to, prints them to stdout if not set.
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Output formats of the `dns-tree` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// TXT records in zone file syntax.
+    Zone,
+    /// A JSON object that maps every domain name to its TXT record.
+    Json,
+}
+
+impl Command {
+    /// Execute the `dns-tree` command.
+    pub fn execute(self) -> eyre::Result<()> {
+        let enrs = read_enrs(&self.enrs)?;
+        let key = get_secret_key(&self.signing_key)?;
+        let seq = match self.seq {
+            Some(seq) => seq,
+            None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+
+        let node_count = enrs.len();
+        let tree = DnsTree::build(enrs, self.links, seq, &key);
+        let records = match self.format {
+            OutputFormat::Zone => tree.to_zone_file(&self.domain, self.ttl),
+            OutputFormat::Json => serde_json::to_string_pretty(&tree.records(&self.domain))? + "\n",
+        };
+
+        match &self.output {
+            Some(path) => reth_fs_util::write(path, records)?,
+            None => print!("{records}"),
+        }
+        // keep stdout parseable, the link goes to stderr
+        eprintln!("Built tree with {node_count} nodes, seq={seq}: {}", tree.link(&self.domain));
+        Ok(())
+    }
+}
+
+/// Reads the ENRs from the file, or from stdin if the path is `-`.
+fn read_enrs(path: &Path) -> eyre::Result<Vec<Enr<SecretKey>>> {
+    let content = if path.as_os_str() == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        reth_fs_util::read_to_string(path)?
+    };
+    parse_enrs(&content)
+}
+
+/// Parses one ENR per line, skipping empty lines and comments.
+fn parse_enrs(content: &str) -> eyre::Result<Vec<Enr<SecretKey>>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(idx, line)| {
+            line.parse::<Enr<SecretKey>>()
+                .map_err(|err| eyre::eyre!(err))
+                .wrap_err_with(|| format!("invalid ENR on line {}", idx + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_cli_util::load_secret_key::rng_secret_key;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn parse_dns_tree_args() {
+        let cmd = Command::try_parse_from([
+            "reth",
+            "--domain",
+            "nodes.example.org",
+            "--signing-key",
+            "key",
+            "--enrs",
+            "-",
+            "--link",
+            "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@nodes.example.com",
+            "--format",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(cmd.links.len(), 1);
+        assert_eq!(cmd.format, OutputFormat::Json);
+        assert_eq!(cmd.ttl, 3600);
+        assert!(cmd.seq.is_none());
+    }
+
+    #[test]
+    fn parse_enr_lines() {
+        let key = rng_secret_key();
+        let enr = Enr::builder().ip4(Ipv4Addr::LOCALHOST).tcp4(30303).build(&key).unwrap();
+        let content = format!("# crawled nodes\n\n{}\n  {}  \n", enr.to_base64(), enr.to_base64());
+
+        let enrs = parse_enrs(&content).unwrap();
+        assert_eq!(enrs, vec![enr.clone(), enr]);
+
+        let err = parse_enrs("enr:-invalid").unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+    }
+}
@@ -19,6 +19,8 @@ use reth_node_core::{
 use reth_tasks::Runtime;
 
 pub mod bootnode;
+pub mod crawl;
+pub mod dns_tree;
 pub mod enode;
 pub mod rlpx;
 
@@ -90,6 +92,12 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + Hardforks + EthereumHardforks>
             Subcommands::Enode(command) => {
                 command.execute()?;
             }
+            Subcommands::DnsTree(command) => {
+                command.execute()?;
+            }
+            Subcommands::Crawl(command) => {
+                command.execute().await?;
+            }
         }
 
         Ok(())
@@ -105,6 +113,8 @@ impl<C: ChainSpecParser> Command<C> {
             Subcommands::Rlpx(_) => None,
             Subcommands::Bootnode(_) => None,
             Subcommands::Enode(_) => None,
+            Subcommands::DnsTree(_) => None,
+            Subcommands::Crawl(command) => Some(&command.chain),
         }
     }
 }
@@ -134,6 +144,10 @@ pub enum Subcommands<C: ChainSpecParser> {
     Bootnode(bootnode::Command),
     /// Print enode identifier
     Enode(enode::Command),
+    /// Build and sign an EIP-1459 DNS node tree
+    DnsTree(dns_tree::Command),
+    /// Crawl the network and write a census of the discovered nodes
+    Crawl(crawl::Command<C>),
 }
 
 #[derive(Debug, Clone, Parser)]
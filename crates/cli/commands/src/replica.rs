@@ -0,0 +1,242 @@
+//! Command that serves RPC from the datadir of another running node.
+
+use crate::common::{CliComponentsBuilder, CliNodeComponents, CliNodeTypes, EnvironmentArgs};
+use alloy_network::Ethereum;
+use clap::Parser;
+use reth_chainspec::{EthChainSpec, EthereumHardforks, Hardforks};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_consensus::FullConsensus;
+use reth_db::DatabaseEnv;
+use reth_network_api::noop::NoopNetwork;
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_node_core::args::RpcServerArgs;
+use reth_provider::providers::{
+    BlockchainProvider, CaughtUpBlocks, ProviderFactoryBuilder, ReadOnlyConfig,
+};
+use reth_rpc::EthApi;
+use reth_rpc_builder::{config::RethRpcServerConfig, RpcModuleBuilder, TransportRpcModules};
+use reth_rpc_eth_api::{
+    helpers::pending_block::PendingEnvBuilder, node::RpcNodeCoreAdapter, FullEthApiServer,
+    RpcConvert, RpcConverter, RpcNodeCore,
+};
+use reth_rpc_eth_types::receipt::EthReceiptConverter;
+use reth_rpc_server_types::{RethRpcModule, RpcModuleSelection};
+use reth_tasks::Runtime;
+use reth_transaction_pool::noop::NoopTransactionPool;
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, info, warn};
+
+/// Provider the replica serves RPC from.
+pub type ReplicaProvider<N> = BlockchainProvider<NodeTypesWithDBAdapter<N, DatabaseEnv>>;
+
+/// RPC components of the replica: there is no transaction pool or network to serve from.
+type ReplicaRpcCore<N> = RpcNodeCoreAdapter<
+    ReplicaProvider<N>,
+    NoopTransactionPool,
+    NoopNetwork,
+    <N as CliNodeTypes>::Evm,
+>;
+
+/// RPC converter of the replica's `eth` API.
+type ReplicaRpcConverter<N> = RpcConverter<
+    Ethereum,
+    <N as CliNodeTypes>::Evm,
+    EthReceiptConverter<<N as reth_node_builder::NodeTypes>::ChainSpec>,
+>;
+
+/// `eth` API served by the replica.
+type ReplicaEthApi<N> = EthApi<ReplicaRpcCore<N>, ReplicaRpcConverter<N>>;
+
+/// RPC modules served by the replica if no `--http.api` or `--ws.api` is configured.
+const DEFAULT_REPLICA_MODULES: [RethRpcModule; 4] =
+    [RethRpcModule::Eth, RethRpcModule::Debug, RethRpcModule::Trace, RethRpcModule::Web3];
+
+/// `reth replica` command
+///
+/// Serves RPC from the datadir of a node running on the same host, without syncing.
+///
+/// The database is opened read-only, `RocksDB` as a secondary instance and static files read-only.
+/// The replica follows the blocks the primary node persists, so it trails the primary's tip by the
+/// blocks that are only in the primary's memory. Ports and the IPC path must not collide with the
+/// primary's.
+#[derive(Debug, Parser)]
+pub struct Command<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Interval in which the replica checks for blocks persisted by the primary node.
+    #[arg(
+        long = "replica.poll-interval",
+        value_name = "DURATION",
+        default_value = "1s",
+        value_parser = humantime::parse_duration
+    )]
+    poll_interval: Duration,
+
+    #[command(flatten)]
+    rpc: RpcServerArgs,
+}
+
+impl<C: ChainSpecParser> Command<C> {
+    /// Execute `replica` command
+    pub async fn execute<N>(
+        self,
+        components: impl CliComponentsBuilder<N>,
+        runtime: Runtime,
+    ) -> eyre::Result<()>
+    where
+        N: ReplicaNodeTypes,
+        C: ChainSpecParser<ChainSpec = N::ChainSpec>,
+    {
+        let Self { env, poll_interval, mut rpc } = self;
+
+        let data_dir = env.datadir.clone().resolve_datadir(env.chain.chain());
+        info!(target: "reth::cli", path = ?data_dir.data_dir(), "Opening primary datadir");
+        let factory = ProviderFactoryBuilder::<N>::default().open_read_only(
+            env.chain.clone(),
+            ReadOnlyConfig::from_dirs(data_dir.db(), data_dir.static_files(), data_dir.rocksdb())
+                .with_db_args(env.db.database_args()),
+            runtime.clone(),
+        )?;
+        let provider = BlockchainProvider::new(factory)?;
+        let head = provider.canonical_in_memory_state().get_canonical_head();
+        info!(target: "reth::cli", head = ?head.num_hash(), "Opened primary storage");
+
+        if rpc.http && rpc.http_api.is_none() {
+            rpc.http_api = Some(RpcModuleSelection::from(DEFAULT_REPLICA_MODULES));
+        }
+        if rpc.ws && rpc.ws_api.is_none() {
+            rpc.ws_api = Some(RpcModuleSelection::from(DEFAULT_REPLICA_MODULES));
+        }
+
+        let components = components(env.chain.clone());
+        let modules = N::replica_rpc_modules(
+            provider.clone(),
+            components.evm_config().clone(),
+            components.consensus().clone(),
+            &rpc,
+            runtime,
+        );
+        let server = rpc.rpc_server_config().start(&modules).await?;
+        info!(
+            target: "reth::cli",
+            http = ?server.http_local_addr(),
+            ws = ?server.ws_local_addr(),
+            ipc = ?server.ipc_endpoint(),
+            "RPC server started"
+        );
+
+        let mut interval = tokio::time::interval(poll_interval);
+        let mut announced = CaughtUpBlocks::default();
+        loop {
+            interval.tick().await;
+
+            let provider = provider.clone();
+            let (result, blocks) = tokio::task::spawn_blocking(move || {
+                (provider.catch_up_with_storage(&mut announced), announced)
+            })
+            .await?;
+            announced = blocks;
+            match result {
+                Ok(Some(head)) => {
+                    debug!(target: "reth::cli", head = ?head.num_hash(), "Caught up with primary")
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(target: "reth::cli", %err, "Failed to catch up with primary")
+                }
+            }
+        }
+    }
+
+    /// Returns the underlying chain being used to run this command
+    pub fn chain_spec(&self) -> Option<&Arc<C::ChainSpec>> {
+        Some(&self.env.chain)
+    }
+}
+
+/// Node types a replica can serve the RPC API for.
+///
+/// This is implemented for all [`CliNodeTypes`] the Ethereum `eth` API can be built for.
+pub trait ReplicaNodeTypes: CliNodeTypes {
+    /// Configures the RPC modules selected in `rpc`, served from `provider`.
+    fn replica_rpc_modules<Consensus>(
+        provider: ReplicaProvider<Self>,
+        evm_config: Self::Evm,
+        consensus: Consensus,
+        rpc: &RpcServerArgs,
+        runtime: Runtime,
+    ) -> TransportRpcModules
+    where
+        Consensus: FullConsensus<Self::Primitives> + Clone + 'static;
+}
+
+impl<N> ReplicaNodeTypes for N
+where
+    N: CliNodeTypes<ChainSpec: EthChainSpec + Hardforks + EthereumHardforks>,
+    ReplicaRpcCore<N>: RpcNodeCore<Provider = ReplicaProvider<N>, Evm = N::Evm>,
+    ReplicaRpcConverter<N>: RpcConvert,
+    (): PendingEnvBuilder<N::Evm>,
+    ReplicaEthApi<N>: FullEthApiServer<Provider = ReplicaProvider<N>, Pool = NoopTransactionPool>,
+{
+    fn replica_rpc_modules<Consensus>(
+        provider: ReplicaProvider<Self>,
+        evm_config: Self::Evm,
+        consensus: Consensus,
+        rpc: &RpcServerArgs,
+        runtime: Runtime,
+    ) -> TransportRpcModules
+    where
+        Consensus: FullConsensus<Self::Primitives> + Clone + 'static,
+    {
+        let rpc_builder = RpcModuleBuilder::<N::Primitives, _, _, _, _, _>::default()
+            .with_provider(provider)
+            .with_noop_pool()
+            .with_noop_network()
+            .with_executor(runtime.clone())
+            .with_evm_config(evm_config)
+            .with_consensus(consensus);
+
+        let eth_config = rpc.eth_config();
+        let eth_api = rpc_builder
+            .eth_api_builder()
+            .task_spawner(runtime)
+            .eth_state_cache_config(eth_config.cache)
+            .gas_oracle_config(eth_config.gas_oracle)
+            .gas_cap(eth_config.rpc_gas_cap.into())
+            .max_simulate_blocks(eth_config.rpc_max_simulate_blocks)
+            .compute_state_root_for_eth_simulate(eth_config.compute_state_root_for_eth_simulate)
+            .eth_proof_window(eth_config.eth_proof_window)
+            .fee_history_cache_config(eth_config.fee_history_cache)
+            .proof_permits(eth_config.proof_permits)
+            .max_blocking_io_requests(eth_config.max_blocking_io_requests)
+            .build();
+
+        rpc_builder.build(rpc.transport_rpc_module_config(), eth_api, Default::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+
+    #[test]
+    fn parse_replica_args() {
+        let cmd = Command::<EthereumChainSpecParser>::try_parse_from([
+            "reth",
+            "--datadir",
+            "/primary",
+            "--replica.poll-interval",
+            "250ms",
+            "--http",
+            "--http.port",
+            "9545",
+        ])
+        .unwrap();
+        assert_eq!(cmd.poll_interval, Duration::from_millis(250));
+        assert!(cmd.rpc.http);
+        assert_eq!(cmd.rpc.http_port, 9545);
+        assert!(cmd.rpc.http_api.is_none());
+    }
+}
@@ -0,0 +1,611 @@
+//! Command that replays recorded RPC traffic against an endpoint.
+//!
+//! Recordings are written by a node started with `--rpc.recorder.dir`.
+
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::{ensure, WrapErr};
+use parking_lot::Mutex;
+use reth_rpc_server_types::recording::{RecordedCall, RecordedTransport, RECORDING_FILE_EXTENSION};
+use serde::{Deserialize, Serialize};
+use serde_json::{value::RawValue, Value};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// `reth rpc-replay` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// Recording files or directories of recording files to replay.
+    #[arg(required = true, value_name = "PATH")]
+    recordings: Vec<PathBuf>,
+
+    /// The HTTP endpoint the calls are replayed against.
+    #[arg(long, value_name = "URL", default_value = "http://localhost:8545")]
+    endpoint: Url,
+
+    /// Maximum number of calls in flight.
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(u32).range(1..))]
+    concurrency: u32,
+
+    /// Replay speed relative to the recorded timing, e.g. `2` replays twice as fast.
+    ///
+    /// If `0`, calls are replayed as fast as the concurrency allows.
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Only replay calls that were received on this transport.
+    #[arg(long, value_name = "TRANSPORT")]
+    transport: Option<RecordedTransport>,
+
+    /// Only replay calls of these methods.
+    #[arg(long, value_delimiter = ',', value_name = "METHODS")]
+    methods: Vec<String>,
+
+    /// Maximum number of response mismatches that are kept and printed.
+    #[arg(long, default_value_t = 10)]
+    show_mismatches: usize,
+
+    /// Also replay calls that change the state of the endpoint, such as
+    /// `eth_sendRawTransaction` or `admin_addPeer`.
+    ///
+    /// By default these calls are skipped.
+    #[arg(long, default_value_t = false)]
+    allow_state_changes: bool,
+}
+
+impl Command {
+    /// Execute `rpc-replay` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        ensure!(self.speed >= 0.0, "--speed must not be negative");
+
+        let files = recording_files(&self.recordings)?;
+        ensure!(!files.is_empty(), "No recording files found");
+        info!(
+            target: "reth::cli",
+            files = files.len(),
+            endpoint = %self.endpoint,
+            "Replaying recorded calls"
+        );
+
+        let client = reqwest::Client::new();
+        let permits = Arc::new(Semaphore::new(self.concurrency as usize));
+        let report = Arc::new(Mutex::new(ReplayReport::new(self.show_mismatches)));
+
+        let mut calls = RecordingReader::open(&files)?;
+        let started = Instant::now();
+        let mut first_timestamp = None;
+        let mut id = 0u64;
+        let mut skipped_state_changes = 0usize;
+        while let Some(call) = calls.next_call() {
+            if self.transport.is_some_and(|transport| transport != call.transport) ||
+                (!self.methods.is_empty() && !self.methods.contains(&call.method))
+            {
+                continue
+            }
+            if !self.allow_state_changes && is_state_changing(&call.method) {
+                skipped_state_changes += 1;
+                continue
+            }
+
+            // keep the recorded spacing between calls, scaled by the speed
+            let first_timestamp = *first_timestamp.get_or_insert(call.timestamp_us);
+            if self.speed > 0.0 {
+                let offset = call.timestamp_us.saturating_sub(first_timestamp) as f64 / self.speed;
+                let due = started + Duration::from_micros(offset as u64);
+                tokio::time::sleep_until(due.into()).await;
+            }
+
+            let permit = permits.clone().acquire_owned().await?;
+            let client = client.clone();
+            let endpoint = self.endpoint.clone();
+            let report = report.clone();
+            id += 1;
+            tokio::spawn(async move {
+                let replayed = replay_call(&client, endpoint, id, &call).await;
+                report.lock().record(call, replayed);
+                drop(permit);
+            });
+        }
+
+        // wait for all calls in flight
+        let _ = permits.acquire_many(self.concurrency).await?;
+        let elapsed = started.elapsed();
+
+        let report = report.lock();
+        println!("Replayed {} calls in {elapsed:?}", report.calls());
+        if skipped_state_changes > 0 {
+            println!(
+                "Skipped {skipped_state_changes} calls that change state, use \
+                 --allow-state-changes to replay them"
+            );
+        }
+        println!("{}", report.table());
+        for mismatch in &report.mismatches {
+            println!("\n{} {}", mismatch.method, mismatch.params.as_deref().unwrap_or("[]"));
+            println!("  recorded: {}", mismatch.recorded);
+            println!("  replayed: {}", mismatch.replayed);
+        }
+        let mismatches = report.total_mismatches();
+        if mismatches > report.mismatches.len() {
+            println!("\n... and {} more mismatches", mismatches - report.mismatches.len());
+        }
+
+        Ok(())
+    }
+}
+
+/// Namespaces whose methods may change the state of the endpoint.
+const STATE_CHANGING_NAMESPACES: [&str; 5] = ["admin_", "engine_", "miner_", "mev_", "personal_"];
+
+/// Methods of other namespaces that change the state of the endpoint.
+const STATE_CHANGING_METHODS: [&str; 4] =
+    ["eth_cancelPrivateTransaction", "eth_submitHashrate", "eth_submitWork", "debug_setHead"];
+
+/// Returns true if the method may change the state of the endpoint, e.g. by submitting a
+/// transaction.
+fn is_state_changing(method: &str) -> bool {
+    method.starts_with("eth_send") ||
+        STATE_CHANGING_NAMESPACES.iter().any(|namespace| method.starts_with(namespace)) ||
+        STATE_CHANGING_METHODS.contains(&method)
+}
+
+/// Returns the recording files of the given paths, directories are expanded to the recording
+/// files they contain.
+fn recording_files(paths: &[PathBuf]) -> eyre::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut dir_files = Vec::new();
+            for entry in reth_fs_util::read_dir(path)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == RECORDING_FILE_EXTENSION) {
+                    dir_files.push(path);
+                }
+            }
+            dir_files.sort();
+            files.extend(dir_files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Reads the calls of multiple recording files in the order they were received.
+#[derive(Debug)]
+struct RecordingReader {
+    files: Vec<RecordingFile>,
+}
+
+/// A recording file that is being read.
+#[derive(Debug)]
+struct RecordingFile {
+    path: PathBuf,
+    lines: Lines<BufReader<File>>,
+    /// The next call of the file.
+    next: Option<RecordedCall>,
+}
+
+impl RecordingReader {
+    fn open(files: &[PathBuf]) -> eyre::Result<Self> {
+        let mut readers = Vec::with_capacity(files.len());
+        for path in files {
+            let file =
+                File::open(path).wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+            let mut lines = BufReader::new(file).lines();
+            let next = read_call(&mut lines, path);
+            readers.push(RecordingFile { path: path.clone(), lines, next });
+        }
+        Ok(Self { files: readers })
+    }
+
+    /// Returns the next call across all files by timestamp.
+    fn next_call(&mut self) -> Option<RecordedCall> {
+        let file = self
+            .files
+            .iter_mut()
+            .filter_map(|file| Some((file.next.as_ref()?.timestamp_us, file)))
+            .min_by_key(|(timestamp, _)| *timestamp)?
+            .1;
+        let next = read_call(&mut file.lines, &file.path);
+        std::mem::replace(&mut file.next, next)
+    }
+}
+
+/// Reads the next call, skipping lines that can not be decoded.
+fn read_call(lines: &mut Lines<BufReader<File>>, path: &Path) -> Option<RecordedCall> {
+    for line in lines {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                warn!(
+                    target: "reth::cli",
+                    %err,
+                    path = %path.display(),
+                    "Failed to read recording"
+                );
+                return None
+            }
+        };
+        match serde_json::from_str(&line) {
+            Ok(call) => return Some(call),
+            // the last line may be incomplete if the node was stopped while writing
+            Err(err) => debug!(target: "reth::cli", %err, "Skipping invalid recorded call"),
+        }
+    }
+    None
+}
+
+/// The outcome of a replayed call.
+#[derive(Debug)]
+enum Replayed {
+    /// The endpoint responded, with a result or an error.
+    Response { result: Option<Value>, error: Option<Value>, latency: Duration },
+    /// The call could not be sent or the response could not be decoded.
+    Failed(String),
+}
+
+/// Sends the recorded call to the endpoint.
+async fn replay_call(
+    client: &reqwest::Client,
+    endpoint: Url,
+    id: u64,
+    call: &RecordedCall,
+) -> Replayed {
+    #[derive(Serialize)]
+    struct Request<'a> {
+        jsonrpc: &'static str,
+        id: u64,
+        method: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        params: Option<&'a RawValue>,
+    }
+
+    #[derive(Deserialize)]
+    struct Response {
+        #[serde(default)]
+        result: Option<Value>,
+        #[serde(default)]
+        error: Option<Value>,
+    }
+
+    let request =
+        Request { jsonrpc: "2.0", id, method: &call.method, params: call.params.as_deref() };
+    let body = match serde_json::to_vec(&request) {
+        Ok(body) => body,
+        Err(err) => return Replayed::Failed(err.to_string()),
+    };
+
+    let started = Instant::now();
+    let response = client
+        .post(endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await;
+    let bytes = match response {
+        Ok(response) => response.bytes().await,
+        Err(err) => return Replayed::Failed(err.to_string()),
+    };
+    let latency = started.elapsed();
+
+    match bytes
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| serde_json::from_slice::<Response>(&bytes).map_err(|err| err.to_string()))
+    {
+        Ok(Response { result, error }) => Replayed::Response { result, error, latency },
+        Err(err) => Replayed::Failed(err),
+    }
+}
+
+/// A replayed call whose response differs from the recorded response.
+#[derive(Debug)]
+struct Mismatch {
+    method: String,
+    params: Option<String>,
+    recorded: String,
+    replayed: String,
+}
+
+/// Statistics of the replayed calls of a single method.
+#[derive(Debug, Default)]
+struct MethodStats {
+    /// The number of replayed calls.
+    calls: usize,
+    /// The number of calls that could not be replayed.
+    failed: usize,
+    /// The number of calls that returned a JSON-RPC error.
+    errors: usize,
+    /// The number of calls whose response differs from the recorded response.
+    mismatches: usize,
+    /// The latencies of the replayed calls, in microseconds.
+    latencies: Vec<u64>,
+    /// The latencies of the recorded calls, in microseconds.
+    recorded_latencies: Vec<u64>,
+}
+
+/// The results of a replay, per method.
+#[derive(Debug)]
+struct ReplayReport {
+    methods: BTreeMap<String, MethodStats>,
+    /// The first mismatches, at most `max_mismatches`.
+    mismatches: Vec<Mismatch>,
+    max_mismatches: usize,
+}
+
+impl ReplayReport {
+    /// Creates an empty report that keeps at most `max_mismatches` mismatches.
+    const fn new(max_mismatches: usize) -> Self {
+        Self { methods: BTreeMap::new(), mismatches: Vec::new(), max_mismatches }
+    }
+
+    /// Returns the total number of replayed calls.
+    fn calls(&self) -> usize {
+        self.methods.values().map(|stats| stats.calls).sum()
+    }
+
+    /// Returns the total number of mismatches, including those that were not kept.
+    fn total_mismatches(&self) -> usize {
+        self.methods.values().map(|stats| stats.mismatches).sum()
+    }
+
+    /// Records the outcome of a replayed call.
+    fn record(&mut self, call: RecordedCall, replayed: Replayed) {
+        let stats = self.methods.entry(call.method.clone()).or_default();
+        stats.calls += 1;
+        stats.recorded_latencies.push(call.latency_us);
+
+        let (result, error) = match replayed {
+            Replayed::Response { result, error, latency } => {
+                stats.latencies.push(latency.as_micros() as u64);
+                (result, error)
+            }
+            Replayed::Failed(err) => {
+                stats.failed += 1;
+                debug!(target: "reth::cli", method = %call.method, %err, "Failed to replay call");
+                return
+            }
+        };
+        if error.is_some() {
+            stats.errors += 1;
+        }
+
+        let recorded = match (&call.result, &call.error) {
+            (Some(result), _) => Outcome::Result(serde_json::from_str(result.get()).ok()),
+            (None, Some(error)) => {
+                Outcome::Error(error_code(serde_json::from_str(error.get()).ok()))
+            }
+            // responses were not recorded
+            (None, None) => return,
+        };
+        let replayed = match (result, error) {
+            (_, Some(error)) => Outcome::Error(error_code(Some(error))),
+            (result, None) => Outcome::Result(result),
+        };
+        if recorded != replayed {
+            stats.mismatches += 1;
+            if self.mismatches.len() >= self.max_mismatches {
+                return
+            }
+            self.mismatches.push(Mismatch {
+                method: call.method,
+                params: call.params.map(|params| params.get().to_string()),
+                recorded: recorded.to_string(),
+                replayed: replayed.to_string(),
+            });
+        }
+    }
+
+    /// Returns the per method statistics as a table.
+    fn table(&self) -> ComfyTable {
+        let mut table = ComfyTable::new();
+        table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        table.set_header([
+            "Method",
+            "Calls",
+            "Failed",
+            "Errors",
+            "Mismatches",
+            "p50",
+            "p90",
+            "p99",
+            "Max",
+            "Recorded p50",
+        ]);
+
+        for (method, stats) in &self.methods {
+            let mut latencies = stats.latencies.clone();
+            latencies.sort_unstable();
+            let mut recorded = stats.recorded_latencies.clone();
+            recorded.sort_unstable();
+
+            let mut row = Row::new();
+            row.add_cell(Cell::new(method))
+                .add_cell(Cell::new(stats.calls))
+                .add_cell(Cell::new(stats.failed))
+                .add_cell(Cell::new(stats.errors))
+                .add_cell(Cell::new(stats.mismatches))
+                .add_cell(Cell::new(format_latency(percentile(&latencies, 50.0))))
+                .add_cell(Cell::new(format_latency(percentile(&latencies, 90.0))))
+                .add_cell(Cell::new(format_latency(percentile(&latencies, 99.0))))
+                .add_cell(Cell::new(format_latency(latencies.last().copied())))
+                .add_cell(Cell::new(format_latency(percentile(&recorded, 50.0))));
+            table.add_row(row);
+        }
+        table
+    }
+}
+
+/// The comparable part of a response.
+///
+/// Errors are compared by their code only, since messages commonly differ between versions.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Result(Option<Value>),
+    Error(Option<Value>),
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Result(Some(result)) => write!(f, "{result}"),
+            Self::Result(None) => write!(f, "<invalid result>"),
+            Self::Error(Some(code)) => write!(f, "error {code}"),
+            Self::Error(None) => write!(f, "error <invalid code>"),
+        }
+    }
+}
+
+/// Returns the code of a JSON-RPC error object.
+fn error_code(error: Option<Value>) -> Option<Value> {
+    error?.get("code").cloned()
+}
+
+/// Returns the nearest-rank percentile of the sorted values.
+fn percentile(sorted: &[u64], percentile: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.saturating_sub(1).min(sorted.len() - 1)).copied()
+}
+
+/// Formats a latency in microseconds.
+fn format_latency(latency_us: Option<u64>) -> String {
+    latency_us
+        .map(|latency| format!("{:?}", Duration::from_micros(latency)))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(method: &str, result: Option<&str>, error: Option<&str>) -> RecordedCall {
+        let raw = |json: &str| RawValue::from_string(json.to_string()).unwrap();
+        RecordedCall {
+            timestamp_us: 0,
+            transport: RecordedTransport::Http,
+            method: method.to_string(),
+            params: Some(raw("[]")),
+            result: result.map(raw),
+            error: error.map(raw),
+            latency_us: 100,
+        }
+    }
+
+    fn response(result: Option<Value>, error: Option<Value>) -> Replayed {
+        Replayed::Response { result, error, latency: Duration::from_micros(50) }
+    }
+
+    #[test]
+    fn percentiles() {
+        let latencies = (1..=100).collect::<Vec<u64>>();
+        assert_eq!(percentile(&latencies, 50.0), Some(50));
+        assert_eq!(percentile(&latencies, 99.0), Some(99));
+        assert_eq!(percentile(&latencies, 100.0), Some(100));
+        assert_eq!(percentile(&[7], 50.0), Some(7));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn reports_mismatches() {
+        let mut report = ReplayReport::new(10);
+        // same result, formatted differently
+        report.record(
+            call("eth_getBlockByNumber", Some(r#"{"number": "0x1"}"#), None),
+            response(Some(serde_json::json!({"number": "0x1"})), None),
+        );
+        // different result
+        report.record(
+            call("eth_getBlockByNumber", Some(r#""0x1""#), None),
+            response(Some(serde_json::json!("0x2")), None),
+        );
+        // same error code, different message
+        report.record(
+            call("eth_call", None, Some(r#"{"code":3,"message":"execution reverted"}"#)),
+            response(None, Some(serde_json::json!({"code": 3, "message": "reverted"}))),
+        );
+        // no recorded response
+        report.record(call("eth_call", None, None), response(None, Some(serde_json::json!({}))));
+        report.record(call("eth_call", None, None), Replayed::Failed("timeout".to_string()));
+
+        assert_eq!(report.calls(), 5);
+        let block = &report.methods["eth_getBlockByNumber"];
+        assert_eq!((block.calls, block.mismatches, block.errors), (2, 1, 0));
+        let eth_call = &report.methods["eth_call"];
+        assert_eq!(
+            (eth_call.calls, eth_call.mismatches, eth_call.errors, eth_call.failed),
+            (3, 0, 2, 1)
+        );
+        assert_eq!(eth_call.latencies.len(), 2);
+
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].recorded, r#""0x1""#);
+        assert_eq!(report.mismatches[0].replayed, r#""0x2""#);
+    }
+
+    #[test]
+    fn keeps_limited_mismatches() {
+        let mut report = ReplayReport::new(2);
+        for _ in 0..5 {
+            report.record(
+                call("eth_blockNumber", Some(r#""0x1""#), None),
+                response(Some(serde_json::json!("0x2")), None),
+            );
+        }
+        assert_eq!(report.mismatches.len(), 2);
+        assert_eq!(report.total_mismatches(), 5);
+    }
+
+    #[test]
+    fn state_changing_methods() {
+        for method in [
+            "eth_sendRawTransaction",
+            "eth_sendRawTransactionSync",
+            "admin_addPeer",
+            "debug_setHead",
+        ] {
+            assert!(is_state_changing(method), "{method}");
+        }
+        for method in ["eth_call", "eth_getBalance", "debug_traceTransaction", "txpool_content"] {
+            assert!(!is_state_changing(method), "{method}");
+        }
+    }
+
+    #[test]
+    fn reads_calls_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, timestamps: &[u64]| {
+            let lines = timestamps
+                .iter()
+                .map(|timestamp_us| {
+                    let mut call = call("eth_chainId", None, None);
+                    call.timestamp_us = *timestamp_us;
+                    serde_json::to_string(&call).unwrap()
+                })
+                .collect::<Vec<_>>();
+            std::fs::write(dir.path().join(name), lines.join("\n") + "\n{\"incomplete").unwrap();
+        };
+        write("http-000000.jsonl", &[1, 4, 5]);
+        write("ws-000000.jsonl", &[2, 3, 6]);
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let files = recording_files(&[dir.path().to_path_buf()]).unwrap();
+        assert_eq!(files.len(), 2);
+        let mut reader = RecordingReader::open(&files).unwrap();
+        let timestamps = std::iter::from_fn(|| reader.next_call())
+            .map(|call| call.timestamp_us)
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![1, 2, 3, 4, 5, 6]);
+    }
+}
@@ -10,6 +10,7 @@ use reth_cli_runner::CliContext;
 
 pub mod drop;
 pub mod dump;
+pub mod restore_receipts;
 pub mod run;
 pub mod unwind;
 
@@ -36,6 +37,8 @@ pub enum Subcommands<C: ChainSpecParser> {
     Dump(dump::Command<C>),
     /// Unwinds a certain block range, deleting it from the database.
     Unwind(unwind::Command<C>),
+    /// Restores pruned receipts from peers or by re-executing blocks.
+    RestoreReceipts(restore_receipts::Command<C>),
 }
 
 impl<C: ChainSpecParser<ChainSpec: EthChainSpec + Hardforks + EthereumHardforks>> Command<C> {
@@ -55,6 +58,9 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + Hardforks + EthereumHardforks>
             Subcommands::Drop(command) => command.execute::<N>(executor).await,
             Subcommands::Dump(command) => command.execute::<N, _, _>(components, executor).await,
             Subcommands::Unwind(command) => command.execute::<N, _, _>(components, executor).await,
+            Subcommands::RestoreReceipts(command) => {
+                command.execute::<N, _, _>(components, executor).await
+            }
         }
     }
 }
@@ -67,6 +73,7 @@ impl<C: ChainSpecParser> Command<C> {
             Subcommands::Drop(ref command) => command.chain_spec(),
             Subcommands::Dump(ref command) => command.chain_spec(),
             Subcommands::Unwind(ref command) => command.chain_spec(),
+            Subcommands::RestoreReceipts(ref command) => command.chain_spec(),
         }
     }
 }
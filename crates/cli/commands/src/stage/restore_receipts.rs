@@ -0,0 +1,619 @@
+//! `reth stage restore-receipts` command
+//!
+//! Restores pruned receipts from peers or by re-executing blocks.
+
+use crate::common::{AccessRights, CliNodeComponents, CliNodeTypes, Environment, EnvironmentArgs};
+use alloy_consensus::{proofs::calculate_receipt_root, BlockHeader, TxReceipt};
+use alloy_primitives::BlockNumber;
+use backon::{ConstantBuilder, Retryable};
+use clap::{Parser, ValueEnum};
+use eyre::WrapErr;
+use reth_chainspec::{EthChainSpec, EthereumHardforks, Hardforks};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_util::get_secret_key;
+use reth_consensus::FullConsensus;
+use reth_db::DatabaseEnv;
+use reth_db_api::{tables, transaction::DbTxMut};
+use reth_evm::{execute::Executor, ConfigureEvm};
+use reth_network::{BlockDownloaderProvider, FetchClient};
+use reth_network_p2p::receipts::client::ReceiptsClient;
+use reth_node_api::ReceiptTy;
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_node_core::args::NetworkArgs;
+use reth_primitives_traits::{Receipt, SealedHeader};
+use reth_provider::{
+    providers::BlockchainProvider, BlockBodyIndicesProvider, BlockReader, ChainSpecProvider,
+    DBProvider, DatabaseProviderFactory, EitherWriter, HeaderProvider, ProviderError,
+    ProviderFactory, PruneCheckpointReader, PruneCheckpointWriter, StaticFileProviderFactory,
+    TransactionVariant,
+};
+use reth_prune_types::{PruneCheckpoint, PruneSegment};
+use reth_revm::database::StateProviderDatabase;
+use reth_static_file_types::StaticFileSegment;
+use reth_tasks::Runtime;
+use std::{ops::RangeInclusive, sync::Arc};
+use tracing::{info, warn};
+
+/// Maximum number of blocks whose receipts are requested from a peer at once.
+const MAX_RECEIPTS_REQUEST_BLOCKS: usize = 16;
+
+/// Size of the execution state after which a new executor is created, to bound memory usage.
+const MAX_EXECUTOR_SIZE_HINT: usize = 5_000_000;
+
+/// Where pruned receipts are restored from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReceiptsSource {
+    /// Request receipts from peers with `GetReceipts`, validated against the block headers.
+    ///
+    /// Receipts of blocks before Byzantium commit to intermediate state roots that can't be
+    /// validated without executing the block, so these blocks are re-executed instead.
+    Peers,
+    /// Re-execute the blocks on top of their historical state, which must not be pruned.
+    Execution,
+}
+
+/// `reth stage restore-receipts` command
+///
+/// Restores the receipts pruned from `--from` up to the receipts prune checkpoint, and lowers the
+/// checkpoint to the block before `--from`.
+///
+/// Receipts are restored from the highest pruned block downwards, lowering the checkpoint as they
+/// are written, so an interrupted restore can be resumed. Receipts kept in static files can only
+/// be restored in whole static files, so `--from` is rounded down to the first block of its static
+/// file.
+///
+/// Receipts pruning must be disabled in the config, or the pruner prunes the restored receipts
+/// again.
+#[derive(Debug, Parser)]
+pub struct Command<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// The first block to restore the receipts of.
+    #[arg(long)]
+    from: BlockNumber,
+
+    /// Where to restore the receipts from.
+    #[arg(long, value_enum, default_value_t = ReceiptsSource::Peers)]
+    source: ReceiptsSource,
+
+    /// Number of blocks whose receipts are restored and committed at once.
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    batch_size: u64,
+
+    /// The number of retries per request to peers.
+    #[arg(long, default_value_t = 5)]
+    retries: usize,
+
+    #[command(flatten)]
+    network: NetworkArgs,
+}
+
+impl<C: ChainSpecParser<ChainSpec: EthChainSpec + Hardforks + EthereumHardforks>> Command<C> {
+    /// Execute `stage restore-receipts` command
+    pub async fn execute<N, Comp, F>(self, components: F, runtime: Runtime) -> eyre::Result<()>
+    where
+        N: CliNodeTypes<ChainSpec = C::ChainSpec>,
+        Comp: CliNodeComponents<N>,
+        F: FnOnce(Arc<C::ChainSpec>) -> Comp,
+    {
+        let Environment { provider_factory, mut config, data_dir } =
+            self.env.init::<N>(AccessRights::RW, runtime.clone())?;
+        let components = components(provider_factory.chain_spec());
+
+        let provider = provider_factory.provider()?;
+        // `PruneSegment::ContractLogs` only prunes some of the receipts, restoring all of them
+        // restores these as well.
+        let pruned_to = [PruneSegment::Receipts, PruneSegment::ContractLogs]
+            .into_iter()
+            .map(|segment| provider.get_prune_checkpoint(segment))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .filter_map(|checkpoint| checkpoint.block_number)
+            .max();
+        let destination = EitherWriter::receipts_destination(&provider);
+        drop(provider);
+
+        let Some(pruned_to) = pruned_to else {
+            info!(target: "reth::cli", "No receipts have been pruned");
+            return Ok(())
+        };
+        eyre::ensure!(
+            self.from <= pruned_to,
+            "Receipts are only pruned up to block {pruned_to}, nothing to restore from block {}",
+            self.from
+        );
+        if config.prune.segments.has_receipts_pruning() {
+            warn!(
+                target: "reth::cli",
+                "Receipts pruning is configured, restored receipts will be pruned again"
+            );
+        }
+
+        let client = if self.source == ReceiptsSource::Peers {
+            config.peers.trusted_nodes_only |= self.network.trusted_only;
+            config.peers.trusted_nodes.extend(self.network.trusted_peers.clone());
+
+            let network_secret_path =
+                self.network.p2p_secret_key.clone().unwrap_or_else(|| data_dir.p2p_secret());
+            let p2p_secret_key = get_secret_key(&network_secret_path)?;
+            let network = self
+                .network
+                .network_config::<N::NetworkPrimitives>(
+                    &config,
+                    provider_factory.chain_spec(),
+                    p2p_secret_key,
+                    data_dir.known_peers(),
+                    runtime,
+                )
+                .build(BlockchainProvider::new(provider_factory.clone())?)
+                .start_network()
+                .await?;
+            Some(network.fetch_client().await?)
+        } else {
+            None
+        };
+
+        let restorer = Restorer {
+            provider_factory,
+            components,
+            client,
+            backoff: ConstantBuilder::default().with_max_times(self.retries.max(1)),
+            source: self.source,
+            batch_size: self.batch_size,
+        };
+        if destination.is_static_file() {
+            restorer.restore_to_static_files(self.from, pruned_to).await
+        } else {
+            restorer.restore_to_database(self.from, pruned_to).await
+        }
+    }
+}
+
+impl<C: ChainSpecParser> Command<C> {
+    /// Returns the underlying chain being used to run this command
+    pub fn chain_spec(&self) -> Option<&Arc<C::ChainSpec>> {
+        Some(&self.env.chain)
+    }
+}
+
+/// Restores receipts of block ranges and writes them back.
+struct Restorer<N: CliNodeTypes, Comp> {
+    provider_factory: ProviderFactory<NodeTypesWithDBAdapter<N, DatabaseEnv>>,
+    components: Comp,
+    client: Option<FetchClient<N::NetworkPrimitives>>,
+    backoff: ConstantBuilder,
+    source: ReceiptsSource,
+    batch_size: u64,
+}
+
+impl<N, Comp> Restorer<N, Comp>
+where
+    N: CliNodeTypes<ChainSpec: EthChainSpec + EthereumHardforks>,
+    Comp: CliNodeComponents<N>,
+{
+    /// Restores the receipts of `from..=pruned_to` to the `Receipts` table.
+    async fn restore_to_database(
+        &self,
+        from: BlockNumber,
+        pruned_to: BlockNumber,
+    ) -> eyre::Result<()> {
+        let mut end = pruned_to;
+        loop {
+            let start = end.saturating_sub(self.batch_size - 1).max(from);
+            let receipts = self.receipts(start..=end).await?;
+
+            let provider_rw = self.provider_factory.database_provider_rw()?;
+            for (number, block_receipts) in (start..=end).zip(receipts) {
+                let first_tx = provider_rw
+                    .block_body_indices(number)?
+                    .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?
+                    .first_tx_num();
+                for (tx_num, receipt) in (first_tx..).zip(block_receipts) {
+                    provider_rw.tx_ref().put::<tables::Receipts<ReceiptTy<N>>>(tx_num, receipt)?;
+                }
+            }
+            lower_prune_checkpoints(&provider_rw, start)?;
+            provider_rw.commit()?;
+            info!(target: "reth::cli", start, end, "Restored receipts");
+
+            if start == from {
+                return Ok(())
+            }
+            end = start - 1;
+        }
+    }
+
+    /// Restores the receipts of `from..=pruned_to` to the receipts static files deleted by
+    /// pruning, one static file at a time.
+    async fn restore_to_static_files(
+        &self,
+        from: BlockNumber,
+        pruned_to: BlockNumber,
+    ) -> eyre::Result<()> {
+        let static_file_provider = self.provider_factory.static_file_provider();
+        let segment = StaticFileSegment::Receipts;
+
+        let pruned_range = static_file_provider.find_fixed_range(segment, pruned_to);
+        eyre::ensure!(
+            pruned_range.end() == pruned_to,
+            "Receipts of blocks {}..={pruned_to} are pruned from a static file that still exists, \
+             they can't be restored",
+            pruned_range.start()
+        );
+
+        let mut end = pruned_to;
+        loop {
+            let block_range = static_file_provider.find_fixed_range(segment, end);
+            let mut writer = static_file_provider.pruned_range_writer(segment, end)?;
+
+            let mut start = writer.next_block_number();
+            while start <= block_range.end() {
+                let end = (start + self.batch_size - 1).min(block_range.end());
+                let receipts = self.receipts(start..=end).await?;
+
+                let provider = self.provider_factory.provider()?;
+                for (number, block_receipts) in (start..=end).zip(receipts) {
+                    let first_tx = provider
+                        .block_body_indices(number)?
+                        .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?
+                        .first_tx_num();
+                    writer.increment_block(number)?;
+                    for (tx_num, receipt) in (first_tx..).zip(&block_receipts) {
+                        writer.append_receipt(tx_num, receipt)?;
+                    }
+                }
+                writer.commit()?;
+                info!(target: "reth::cli", start, end, "Restored receipts");
+
+                start = end + 1;
+            }
+            drop(writer);
+            static_file_provider.initialize_index()?;
+
+            let provider_rw = self.provider_factory.database_provider_rw()?;
+            lower_prune_checkpoints(&provider_rw, block_range.start())?;
+            provider_rw.commit()?;
+            info!(target: "reth::cli", %block_range, "Restored receipts static file");
+
+            if block_range.start() <= from {
+                return Ok(())
+            }
+            end = block_range.start() - 1;
+        }
+    }
+
+    /// Returns the receipts of each block of the range, from the configured source.
+    async fn receipts(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> eyre::Result<Vec<Vec<ReceiptTy<N>>>> {
+        match self.source {
+            ReceiptsSource::Peers => {
+                let client = self.client.as_ref().expect("network is started for peers source");
+                let chain_spec = self.provider_factory.chain_spec();
+
+                let (start, end) = range.into_inner();
+                let byzantium = (start..=end)
+                    .find(|number| chain_spec.is_byzantium_active_at_block(*number))
+                    .unwrap_or(end + 1);
+                let mut receipts = if start < byzantium {
+                    receipts_from_execution(
+                        &self.provider_factory,
+                        self.components.evm_config(),
+                        self.components.consensus(),
+                        start..=byzantium - 1,
+                    )
+                    .wrap_err("Receipts before Byzantium can only be restored by execution")?
+                } else {
+                    Vec::new()
+                };
+                if byzantium <= end {
+                    receipts.extend(
+                        receipts_from_peers(
+                            &self.provider_factory,
+                            client,
+                            self.backoff,
+                            byzantium..=end,
+                        )
+                        .await?,
+                    );
+                }
+                Ok(receipts)
+            }
+            ReceiptsSource::Execution => receipts_from_execution(
+                &self.provider_factory,
+                self.components.evm_config(),
+                self.components.consensus(),
+                range,
+            ),
+        }
+    }
+}
+
+/// Requests the receipts of each block of the range from peers, validated against the headers.
+///
+/// All blocks of the range must have Byzantium active.
+async fn receipts_from_peers<P, C>(
+    provider: &P,
+    client: &C,
+    backoff: ConstantBuilder,
+    range: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<Vec<C::Receipt>>>
+where
+    P: HeaderProvider<Header: BlockHeader> + BlockBodyIndicesProvider,
+    C: ReceiptsClient<Receipt: Receipt>,
+{
+    let mut receipts = Vec::new();
+    let mut pending = Vec::new();
+    for number in range {
+        let header = provider
+            .sealed_header(number)?
+            .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))?;
+        let tx_count = provider
+            .block_body_indices(number)?
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?
+            .tx_count();
+        if tx_count > 0 {
+            pending.push((receipts.len(), header, tx_count));
+        }
+        receipts.push(Vec::new());
+    }
+
+    let mut pending = pending.as_slice();
+    while !pending.is_empty() {
+        let request = &pending[..pending.len().min(MAX_RECEIPTS_REQUEST_BLOCKS)];
+        let response = (|| async {
+            let hashes = request.iter().map(|(_, header, _)| header.hash()).collect();
+            let (peer_id, response) = client.get_receipts(hashes).await?.split();
+            eyre::ensure!(!response.receipts.is_empty(), "No receipts received");
+            for ((_, header, tx_count), block_receipts) in request.iter().zip(&response.receipts) {
+                if let Err(err) = validate_receipts(header, *tx_count, block_receipts) {
+                    client.report_bad_message(peer_id);
+                    return Err(err)
+                }
+            }
+            Ok::<_, eyre::Report>(response.receipts)
+        })
+        .retry(backoff)
+        .notify(|err, _| {
+            warn!(
+                target: "reth::cli",
+                %err,
+                block = request[0].1.number(),
+                "Error requesting receipts. Retrying..."
+            )
+        })
+        .await?;
+
+        // Peers may respond with the receipts of only some of the requested blocks.
+        let received = response.len().min(request.len());
+        for ((index, ..), block_receipts) in request.iter().zip(response) {
+            receipts[*index] = block_receipts;
+        }
+        pending = &pending[received..];
+    }
+
+    Ok(receipts)
+}
+
+/// Validates the receipts of a block against the receipts root of its header.
+///
+/// Only valid for blocks with Byzantium active, earlier receipts commit to intermediate state
+/// roots instead of a status code.
+fn validate_receipts<H, R>(
+    header: &SealedHeader<H>,
+    tx_count: u64,
+    receipts: &[R],
+) -> eyre::Result<()>
+where
+    H: BlockHeader,
+    R: Receipt,
+{
+    eyre::ensure!(
+        receipts.len() as u64 == tx_count,
+        "Received {} receipts for block {} with {tx_count} transactions",
+        receipts.len(),
+        header.number()
+    );
+
+    let receipts_with_bloom = receipts.iter().map(TxReceipt::with_bloom_ref).collect::<Vec<_>>();
+    eyre::ensure!(
+        calculate_receipt_root(&receipts_with_bloom) == header.receipts_root(),
+        "Received receipts don't match the receipts root of block {}",
+        header.number()
+    );
+
+    Ok(())
+}
+
+/// Re-executes the blocks of the range on top of their historical state, returning the receipts
+/// of each block.
+fn receipts_from_execution<N, Evm, Consensus>(
+    provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<N, DatabaseEnv>>,
+    evm_config: &Evm,
+    consensus: &Consensus,
+    range: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<Vec<ReceiptTy<N>>>>
+where
+    N: CliNodeTypes,
+    Evm: ConfigureEvm<Primitives = N::Primitives>,
+    Consensus: FullConsensus<N::Primitives>,
+{
+    // The genesis block has no receipts and isn't executed.
+    let genesis = provider_factory.chain_spec().genesis_header().number();
+    let first = (*range.start()).max(genesis + 1);
+    let mut receipts = (*range.start()..first).map(|_| Vec::new()).collect::<Vec<_>>();
+    if first > *range.end() {
+        return Ok(receipts)
+    }
+
+    let db_at = |number| {
+        provider_factory
+            .history_by_block_number(number)
+            .map(StateProviderDatabase::new)
+            .wrap_err_with(|| format!("State at block {number} is not available"))
+    };
+
+    let mut executor = evm_config.batch_executor(db_at(first - 1)?);
+    for number in first..=*range.end() {
+        let block = provider_factory
+            .recovered_block(number.into(), TransactionVariant::NoHash)?
+            .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))?;
+        let result = executor.execute_one(&block)?;
+        consensus
+            .validate_block_post_execution(&block, &result, None, None)
+            .wrap_err_with(|| format!("Re-executed block {number} doesn't match its header"))?;
+        receipts.push(result.receipts);
+
+        if executor.size_hint() > MAX_EXECUTOR_SIZE_HINT {
+            executor = evm_config.batch_executor(db_at(number)?);
+        }
+    }
+
+    Ok(receipts)
+}
+
+/// Lowers the receipts prune checkpoints to the block before `block`, after the receipts of
+/// `block` and all blocks above it have been restored.
+fn lower_prune_checkpoints<P>(provider: &P, block: BlockNumber) -> eyre::Result<()>
+where
+    P: PruneCheckpointReader + PruneCheckpointWriter + BlockBodyIndicesProvider,
+{
+    let block_number = block.checked_sub(1);
+    let tx_number = provider
+        .block_body_indices(block)?
+        .ok_or(ProviderError::BlockBodyIndicesNotFound(block))?
+        .first_tx_num()
+        .checked_sub(1);
+
+    for segment in [PruneSegment::Receipts, PruneSegment::ContractLogs] {
+        if let Some(checkpoint) = provider.get_prune_checkpoint(segment)? &&
+            checkpoint.block_number > block_number
+        {
+            provider.save_prune_checkpoint(
+                segment,
+                PruneCheckpoint { block_number, tx_number, ..checkpoint },
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::Header;
+    use alloy_primitives::{Address, Log};
+    use reth_db_api::models::StoredBlockBodyIndices;
+    use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+    use reth_ethereum_primitives::Receipt;
+    use reth_provider::test_utils::create_test_provider_factory;
+    use reth_prune_types::PruneMode;
+
+    #[test]
+    fn parse_restore_receipts_command() {
+        let cmd = Command::<EthereumChainSpecParser>::try_parse_from([
+            "reth",
+            "--from",
+            "100",
+            "--source",
+            "execution",
+            "--batch-size",
+            "500",
+        ])
+        .unwrap();
+        assert_eq!(cmd.from, 100);
+        assert_eq!(cmd.source, ReceiptsSource::Execution);
+        assert_eq!(cmd.batch_size, 500);
+
+        let cmd =
+            Command::<EthereumChainSpecParser>::try_parse_from(["reth", "--from", "1"]).unwrap();
+        assert_eq!(cmd.source, ReceiptsSource::Peers);
+
+        assert!(Command::<EthereumChainSpecParser>::try_parse_from([
+            "reth",
+            "--from",
+            "1",
+            "--batch-size",
+            "0"
+        ])
+        .is_err());
+    }
+
+    fn receipts() -> Vec<Receipt> {
+        (1..=3)
+            .map(|i| Receipt {
+                success: i != 2,
+                cumulative_gas_used: i * 21_000,
+                logs: vec![Log::new_unchecked(
+                    Address::with_last_byte(i as u8),
+                    vec![],
+                    Default::default(),
+                )],
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn validate_receipts_against_receipts_root() {
+        let receipts = receipts();
+        let receipts_root = calculate_receipt_root(
+            &receipts.iter().map(TxReceipt::with_bloom_ref).collect::<Vec<_>>(),
+        );
+        let header =
+            SealedHeader::seal_slow(Header { number: 10, receipts_root, ..Default::default() });
+
+        validate_receipts(&header, 3, &receipts).unwrap();
+        assert!(validate_receipts(&header, 4, &receipts).is_err());
+
+        // Forging a receipt keeping the logs bloom and gas used of the block.
+        let mut forged = receipts.clone();
+        forged[1].success = true;
+        assert!(validate_receipts(&header, 3, &forged).is_err());
+
+        let mut forged = receipts;
+        forged.swap(0, 2);
+        assert!(validate_receipts(&header, 3, &forged).is_err());
+    }
+
+    #[test]
+    fn lower_prune_checkpoints_to_restored_block() {
+        let factory = create_test_provider_factory();
+        let provider_rw = factory.database_provider_rw().unwrap();
+        for number in 0..=10 {
+            provider_rw
+                .tx_ref()
+                .put::<tables::BlockBodyIndices>(
+                    number,
+                    StoredBlockBodyIndices { first_tx_num: number * 2, tx_count: 2 },
+                )
+                .unwrap();
+        }
+        let checkpoint = |block_number| PruneCheckpoint {
+            block_number: Some(block_number),
+            tx_number: Some(block_number * 2 + 1),
+            prune_mode: PruneMode::Full,
+        };
+        provider_rw.save_prune_checkpoint(PruneSegment::Receipts, checkpoint(10)).unwrap();
+        provider_rw.save_prune_checkpoint(PruneSegment::ContractLogs, checkpoint(3)).unwrap();
+
+        lower_prune_checkpoints(&provider_rw, 5).unwrap();
+        assert_eq!(
+            provider_rw.get_prune_checkpoint(PruneSegment::Receipts).unwrap(),
+            Some(checkpoint(4))
+        );
+        // Checkpoints already below the restored block are kept.
+        assert_eq!(
+            provider_rw.get_prune_checkpoint(PruneSegment::ContractLogs).unwrap(),
+            Some(checkpoint(3))
+        );
+
+        lower_prune_checkpoints(&provider_rw, 0).unwrap();
+        let checkpoint = provider_rw.get_prune_checkpoint(PruneSegment::Receipts).unwrap().unwrap();
+        assert_eq!((checkpoint.block_number, checkpoint.tx_number), (None, None));
+    }
+}
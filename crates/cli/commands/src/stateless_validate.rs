@@ -0,0 +1,125 @@
+//! Command that validates a block against an execution witness without a database.
+
+use crate::common::{CliComponentsBuilder, CliNodeComponents, CliNodeTypes};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::hex;
+use alloy_rlp::Decodable;
+use clap::Parser;
+use eyre::{eyre, WrapErr};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_primitives_traits::{BlockBody, NodePrimitives, SealedBlock};
+use reth_stateless::{stateless_validation, ExecutionWitness};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+/// `reth stateless-validate` command
+///
+/// Validates a block using only the state contained in an execution witness.
+#[derive(Debug, Parser)]
+pub struct Command<C: ChainSpecParser> {
+    /// The chain the block belongs to.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = C::help_message(),
+        default_value = C::default_value(),
+        value_parser = C::parser()
+    )]
+    chain: Arc<C::ChainSpec>,
+
+    /// Path to the RLP-encoded block, e.g. the result of `debug_getRawBlock`.
+    ///
+    /// The file may contain the raw bytes, a hex string or a JSON-encoded hex string.
+    #[arg(long, value_name = "FILE")]
+    block: PathBuf,
+
+    /// Path to the JSON-encoded execution witness, e.g. the result of `debug_executionWitness`.
+    #[arg(long, value_name = "FILE")]
+    witness: PathBuf,
+}
+
+impl<C: ChainSpecParser> Command<C> {
+    /// Execute `stateless-validate` command
+    pub async fn execute<N>(self, components: impl CliComponentsBuilder<N>) -> eyre::Result<()>
+    where
+        N: CliNodeTypes<ChainSpec = C::ChainSpec>,
+    {
+        let block = read_block::<<N::Primitives as NodePrimitives>::Block>(&self.block)?;
+        let block = SealedBlock::seal_slow(block)
+            .try_recover()
+            .map_err(|_| eyre!("Failed to recover block senders"))?;
+        let witness: ExecutionWitness = serde_json::from_slice(&reth_fs_util::read(&self.witness)?)
+            .wrap_err("Failed to parse execution witness")?;
+
+        info!(
+            target: "reth::cli",
+            number = block.number(),
+            hash = %block.hash(),
+            state_nodes = witness.state.len(),
+            codes = witness.codes.len(),
+            headers = witness.headers.len(),
+            "Validating block"
+        );
+
+        let components = components(self.chain);
+        let output =
+            stateless_validation(&block, &witness, components.evm_config(), components.consensus())
+                .map_err(|err| eyre!("Block {} is invalid: {err}", block.hash()))?;
+
+        info!(
+            target: "reth::cli",
+            number = block.number(),
+            hash = %block.hash(),
+            transactions = block.body().transaction_count(),
+            gas_used = output.result.gas_used,
+            state_root = %block.state_root(),
+            "Block is valid"
+        );
+
+        Ok(())
+    }
+}
+
+impl<C: ChainSpecParser> Command<C> {
+    /// Returns the underlying chain being used to run this command
+    pub fn chain_spec(&self) -> Option<&Arc<C::ChainSpec>> {
+        Some(&self.chain)
+    }
+}
+
+/// Reads an RLP-encoded block that is stored either as raw bytes or as a (JSON) hex string.
+fn read_block<B: Decodable>(path: &Path) -> eyre::Result<B> {
+    let contents = reth_fs_util::read(path)?;
+    let text = core::str::from_utf8(&contents).ok().map(|text| text.trim().trim_matches('"'));
+    let rlp = match text.and_then(|text| hex::decode(text).ok()) {
+        Some(decoded) => decoded,
+        None => contents,
+    };
+    B::decode(&mut rlp.as_slice()).wrap_err("Failed to decode block RLP")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+
+    #[test]
+    fn parse_stateless_validate_command() {
+        let cmd: Command<EthereumChainSpecParser> = Command::parse_from([
+            "reth",
+            "--chain",
+            "sepolia",
+            "--block",
+            "block.rlp",
+            "--witness",
+            "witness.json",
+        ]);
+        assert_eq!(cmd.block, PathBuf::from("block.rlp"));
+        assert_eq!(cmd.witness, PathBuf::from("witness.json"));
+    }
+}
@@ -1,4 +1,5 @@
 //! Configuration files.
+use reth_etl::FileCompression;
 use reth_network_peers::TrustedPeer;
 use reth_network_types::{PeersConfig, SessionsConfig};
 use reth_prune_types::{PruneModes, MINIMUM_UNWIND_SAFE_DISTANCE};
@@ -397,18 +398,46 @@ pub struct EtlConfig {
     pub dir: Option<PathBuf>,
     /// The maximum size in bytes of data held in memory before being flushed to disk as a file.
     pub file_size: usize,
+    /// Compression of the temporary files.
+    pub compression: FileCompression,
+    /// Maximum number of threads reading and merging the temporary files in parallel.
+    ///
+    /// Defaults to a single thread, merging the files on the thread iterating the collector.
+    pub merge_threads: usize,
 }
 
 impl Default for EtlConfig {
     fn default() -> Self {
-        Self { dir: None, file_size: Self::default_file_size() }
+        Self {
+            dir: None,
+            file_size: Self::default_file_size(),
+            compression: FileCompression::default(),
+            merge_threads: Self::default_merge_threads(),
+        }
     }
 }
 
 impl EtlConfig {
     /// Creates an ETL configuration
     pub const fn new(dir: Option<PathBuf>, file_size: usize) -> Self {
-        Self { dir, file_size }
+        Self {
+            dir,
+            file_size,
+            compression: FileCompression::None,
+            merge_threads: Self::default_merge_threads(),
+        }
+    }
+
+    /// Sets the compression of the temporary files.
+    pub const fn with_compression(mut self, compression: FileCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the maximum number of threads merging the temporary files.
+    pub const fn with_merge_threads(mut self, merge_threads: usize) -> Self {
+        self.merge_threads = merge_threads;
+        self
     }
 
     /// Return default ETL directory from datadir path.
@@ -421,6 +450,11 @@ impl EtlConfig {
         // 500 MB
         500 * (1024 * 1024)
     }
+
+    /// Default maximum number of threads merging the temporary files.
+    pub const fn default_merge_threads() -> usize {
+        1
+    }
 }
 
 /// Static files configuration.
@@ -643,9 +677,10 @@ where
 
 #[cfg(all(test, feature = "serde"))]
 mod tests {
-    use super::{Config, EXTENSION};
+    use super::{Config, EtlConfig, EXTENSION};
     use crate::PruneConfig;
     use alloy_primitives::Address;
+    use reth_etl::FileCompression;
     use reth_network_peers::TrustedPeer;
     use reth_prune_types::{
         PruneMode, PruneModes, ReceiptsLogPruneConfig, MINIMUM_UNWIND_SAFE_DISTANCE,
@@ -1238,4 +1273,28 @@ connect_trusted_nodes_only = true
         let conf: Config = toml::from_str("").unwrap();
         assert!(conf.bootnodes.is_empty());
     }
+
+    #[test]
+    fn test_etl_compression() {
+        let conf: Config = toml::from_str("").unwrap();
+        assert_eq!(conf.stages.etl, EtlConfig::default());
+        assert_eq!(conf.stages.etl.compression, FileCompression::None);
+
+        let reth_toml = r#"
+    [stages.etl]
+    file_size = 1024
+    compression = "zstd"
+    merge_threads = 8
+    "#;
+        let conf: Config = toml::from_str(reth_toml).unwrap();
+        assert_eq!(
+            conf.stages.etl,
+            EtlConfig::new(None, 1024)
+                .with_compression(FileCompression::Zstd)
+                .with_merge_threads(8)
+        );
+
+        let serialized = toml::to_string(&conf).unwrap();
+        assert_eq!(toml::from_str::<Config>(&serialized).unwrap(), conf);
+    }
 }
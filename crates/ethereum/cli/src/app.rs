@@ -9,6 +9,7 @@ use reth_cli::chainspec::ChainSpecParser;
 use reth_cli_commands::{
     common::{CliComponentsBuilder, CliNodeTypes, HeaderMut},
     launcher::{FnLauncher, Launcher},
+    replica::{self, ReplicaNodeTypes},
 };
 use reth_cli_runner::CliRunner;
 use reth_db::DatabaseEnv;
@@ -81,7 +82,7 @@ where
             (evm_config, Arc::new(EthBeaconConsensus::new(spec)))
         };
 
-        self.run_with_components::<EthereumNode>(components, async move |builder, ext| {
+        self.run_with_replica_components::<EthereumNode>(components, async move |builder, ext| {
             launcher.entrypoint(builder, ext).await
         })
     }
@@ -91,8 +92,10 @@ where
     /// This accepts a closure that is used to launch the node via the
     /// [`NodeCommand`](reth_cli_commands::node::NodeCommand) and allows providing custom
     /// components.
+    ///
+    /// The [`replica`] command is not supported, see [`Self::run_with_replica_components`].
     pub fn run_with_components<N>(
-        mut self,
+        self,
         components: impl CliComponentsBuilder<N>,
         launcher: impl AsyncFnOnce(
             WithLaunchContext<NodeBuilder<DatabaseEnv, C::ChainSpec>>,
@@ -102,6 +105,49 @@ where
     where
         N: CliNodeTypes<Primitives: NodePrimitives<BlockHeader: HeaderMut>, ChainSpec: Hardforks>,
         C: ChainSpecParser<ChainSpec = N::ChainSpec>,
+    {
+        self.run_with_components_and_replica(components, launcher, |_, _, _| {
+            Err(eyre!("The replica command is not supported by this node"))
+        })
+    }
+
+    /// Execute the configured cli command with the provided [`CliComponentsBuilder`], including
+    /// the [`replica`] command.
+    ///
+    /// See [`Self::run_with_components`].
+    pub fn run_with_replica_components<N>(
+        self,
+        components: impl CliComponentsBuilder<N>,
+        launcher: impl AsyncFnOnce(
+            WithLaunchContext<NodeBuilder<DatabaseEnv, C::ChainSpec>>,
+            Ext,
+        ) -> Result<()>,
+    ) -> Result<()>
+    where
+        N: CliNodeTypes<Primitives: NodePrimitives<BlockHeader: HeaderMut>, ChainSpec: Hardforks>
+            + ReplicaNodeTypes,
+        C: ChainSpecParser<ChainSpec = N::ChainSpec>,
+    {
+        self.run_with_components_and_replica(components, launcher, |command, runner, components| {
+            let rt = runner.runtime();
+            runner.run_until_ctrl_c(command.execute::<N>(components, rt))
+        })
+    }
+
+    /// Execute the configured cli command, running the [`replica`] command with `replica`.
+    fn run_with_components_and_replica<N, B>(
+        mut self,
+        components: B,
+        launcher: impl AsyncFnOnce(
+            WithLaunchContext<NodeBuilder<DatabaseEnv, C::ChainSpec>>,
+            Ext,
+        ) -> Result<()>,
+        replica: impl FnOnce(replica::Command<C>, CliRunner, B) -> Result<()>,
+    ) -> Result<()>
+    where
+        N: CliNodeTypes<Primitives: NodePrimitives<BlockHeader: HeaderMut>, ChainSpec: Hardforks>,
+        C: ChainSpecParser<ChainSpec = N::ChainSpec>,
+        B: CliComponentsBuilder<N>,
     {
         let runner = match self.runner.take() {
             Some(runner) => runner,
@@ -141,7 +187,9 @@ where
         // Install the prometheus recorder to be sure to record all metrics
         install_prometheus_recorder();
 
-        run_commands_with::<C, Ext, Rpc, N, SubCmd>(self.cli, runner, components, launcher)
+        run_commands_with::<C, Ext, Rpc, N, SubCmd, _>(
+            self.cli, runner, components, launcher, replica,
+        )
     }
 
     /// Initializes tracing with the configured options.
@@ -158,14 +206,18 @@ where
 
 /// Run CLI commands with the provided runner, components and launcher.
 /// This is the shared implementation used by both `CliApp` and Cli methods.
-pub(crate) fn run_commands_with<C, Ext, Rpc, N, SubCmd>(
+///
+/// The [`replica`] command is run with `replica`, because it requires node types that implement
+/// [`ReplicaNodeTypes`].
+pub(crate) fn run_commands_with<C, Ext, Rpc, N, SubCmd, B>(
     cli: Cli<C, Ext, Rpc, SubCmd>,
     runner: CliRunner,
-    components: impl CliComponentsBuilder<N>,
+    components: B,
     launcher: impl AsyncFnOnce(
         WithLaunchContext<NodeBuilder<DatabaseEnv, C::ChainSpec>>,
         Ext,
     ) -> Result<()>,
+    replica: impl FnOnce(replica::Command<C>, CliRunner, B) -> Result<()>,
 ) -> Result<()>
 where
     C: ChainSpecParser<ChainSpec = N::ChainSpec>,
@@ -173,6 +225,7 @@ where
     Rpc: RpcModuleValidator,
     N: CliNodeTypes<Primitives: NodePrimitives<BlockHeader: HeaderMut>, ChainSpec: Hardforks>,
     SubCmd: ExtendedCommand + Subcommand + fmt::Debug,
+    B: CliComponentsBuilder<N>,
 {
     let rt = runner.runtime();
 
@@ -214,6 +267,11 @@ where
         Commands::ReExecute(command) => {
             runner.run_until_ctrl_c(command.execute::<N>(components, rt))
         }
+        Commands::Replica(command) => replica(command, runner, components),
+        Commands::RpcReplay(command) => runner.run_until_ctrl_c(command.execute()),
+        Commands::StatelessValidate(command) => {
+            runner.run_until_ctrl_c(command.execute::<N>(components))
+        }
         Commands::Ext(command) => command.execute(runner),
     }
 }
@@ -11,7 +11,7 @@ use reth_cli_commands::{
     dump_genesis, export_era, import, import_era, init_cmd, init_state,
     launcher::FnLauncher,
     node::{self, NoArgs},
-    p2p, prune, re_execute, stage,
+    p2p, prune, re_execute, replica, rpc_replay, stage, stateless_validate,
 };
 use reth_cli_runner::CliRunner;
 use reth_db::DatabaseEnv;
@@ -171,6 +171,8 @@ impl<
     /// [`NodeCommand`](node::NodeCommand).
     ///
     /// This command will be run on the default tokio runtime.
+    ///
+    /// The [`replica`] command is not supported, see [`CliApp::run_with_replica_components`].
     pub fn run_with_components<N>(
         self,
         components: impl CliComponentsBuilder<N>,
@@ -334,6 +336,15 @@ pub enum Commands<
     /// Re-execute blocks in parallel to verify historical sync correctness.
     #[command(name = "re-execute")]
     ReExecute(re_execute::Command<C>),
+    /// Serve RPC from the datadir of a running node without syncing.
+    #[command(name = "replica")]
+    Replica(replica::Command<C>),
+    /// Replay recorded RPC traffic against an endpoint and compare the responses.
+    #[command(name = "rpc-replay")]
+    RpcReplay(rpc_replay::Command),
+    /// Validate a block against an execution witness without a database.
+    #[command(name = "stateless-validate")]
+    StatelessValidate(stateless_validate::Command<C>),
     /// Extension subcommands provided by consumers.
     #[command(flatten)]
     Ext(SubCmd),
@@ -375,6 +386,9 @@ impl<C: ChainSpecParser, Ext: clap::Args + fmt::Debug, SubCmd: Subcommand + fmt:
             Self::Config(_) => None,
             Self::Prune(cmd) => cmd.chain_spec(),
             Self::ReExecute(cmd) => cmd.chain_spec(),
+            Self::Replica(cmd) => cmd.chain_spec(),
+            Self::RpcReplay(_) => None,
+            Self::StatelessValidate(cmd) => cmd.chain_spec(),
             Self::Ext(_) => None,
         }
     }
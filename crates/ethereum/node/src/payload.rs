@@ -7,11 +7,16 @@ use reth_ethereum_primitives::EthPrimitives;
 use reth_evm::ConfigureEvm;
 use reth_node_api::{FullNodeTypes, NodeTypes, PrimitivesTy, TxTy};
 use reth_node_builder::{
-    components::PayloadBuilderBuilder, BuilderContext, PayloadBuilderConfig, PayloadTypes,
+    components::{spawn_simulation_task, PayloadBuilderBuilder},
+    BuilderContext, PayloadBuilderConfig, PayloadTypes,
 };
-use reth_transaction_pool::{PoolTransaction, TransactionPool};
+use reth_transaction_pool::{PoolTransaction, TransactionPoolExt};
 
 /// A basic ethereum payload service.
+///
+/// This also spawns the simulation of pending transactions if enabled, see
+/// [`spawn_simulation_task`], whose outcomes the payload builder uses to skip reverting
+/// transactions.
 #[derive(Clone, Default, Debug)]
 #[non_exhaustive]
 pub struct EthereumPayloadBuilder;
@@ -20,7 +25,7 @@ impl<Types, Node, Pool, Evm> PayloadBuilderBuilder<Node, Pool, Evm> for Ethereum
 where
     Types: NodeTypes<ChainSpec: EthereumHardforks, Primitives = EthPrimitives>,
     Node: FullNodeTypes<Types = Types>,
-    Pool: TransactionPool<Transaction: PoolTransaction<Consensus = TxTy<Node::Types>>>
+    Pool: TransactionPoolExt<Transaction: PoolTransaction<Consensus = TxTy<Node::Types>>>
         + Unpin
         + 'static,
     Evm: ConfigureEvm<
@@ -44,6 +49,8 @@ where
         let gas_limit = conf.gas_limit_for(chain);
         let skip_state_root = ctx.config().tree_config().skip_state_root();
 
+        spawn_simulation_task(ctx, pool.clone(), evm_config.clone());
+
         Ok(reth_ethereum_payload_builder::EthereumPayloadBuilder::new(
             ctx.provider().clone(),
             pool,
@@ -52,7 +59,8 @@ where
                 .with_gas_limit(gas_limit)
                 .with_max_blobs_per_block(conf.max_blobs_per_block())
                 .with_extra_data(conf.extra_data())
-                .with_skip_state_root(skip_state_root),
+                .with_skip_state_root(skip_state_root)
+                .with_skip_reverting_transactions(ctx.config().txpool.simulate),
         ))
     }
 }
@@ -1,14 +1,15 @@
 use alloy_genesis::Genesis;
 use alloy_primitives::{b256, hex, Address};
 use futures::StreamExt;
-use reth_chainspec::ChainSpec;
+use reth_chainspec::{ChainSpec, EthChainSpec};
 use reth_node_api::{BlockBody, FullNodeComponents};
 use reth_node_builder::{rpc::RethRpcAddOns, FullNode, NodeBuilder, NodeConfig, NodeHandle};
-use reth_node_core::args::DevArgs;
+use reth_node_core::args::{DevArgs, RpcServerArgs};
 use reth_node_ethereum::{node::EthereumAddOns, EthereumNode};
 use reth_primitives_traits::transaction::TxHashRef;
 use reth_provider::{
     providers::BlockchainProvider, BlockIdReader, BlockNumReader, CanonStateSubscriptions,
+    ChainSpecProvider,
 };
 use reth_rpc_eth_api::{helpers::EthTransactions, EthApiServer};
 use reth_tasks::Runtime;
@@ -83,6 +84,48 @@ async fn can_run_dev_node_custom_attributes() -> eyre::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_fork_dev_node() -> eyre::Result<()> {
+    reth_tracing::init_test_tracing();
+    let runtime = Runtime::test();
+
+    // The chain to fork, serving its state over http. It only mines when it receives
+    // transactions, so it stays at genesis.
+    let remote_config = NodeConfig::test()
+        .with_chain(custom_chain())
+        .with_unused_ports()
+        .with_rpc(RpcServerArgs::default().with_unused_ports().with_http())
+        .with_dev(DevArgs { dev: true, ..Default::default() });
+    let NodeHandle { node: remote, .. } = NodeBuilder::new(remote_config)
+        .testing_node(runtime.clone())
+        .with_types_and_provider::<EthereumNode, BlockchainProvider<_>>()
+        .with_components(EthereumNode::components())
+        .with_add_ons(EthereumAddOns::default())
+        .launch_with_debug_capabilities()
+        .await?;
+    let fork_url = remote.rpc_server_handle().http_url().unwrap().parse()?;
+
+    let node_config = NodeConfig::test()
+        .with_chain(custom_chain())
+        .with_unused_ports()
+        .with_dev(DevArgs { dev: true, fork_url: Some(fork_url), ..Default::default() });
+    let NodeHandle { node, .. } = NodeBuilder::new(node_config)
+        .testing_node(runtime.clone())
+        .with_types_and_provider::<EthereumNode, BlockchainProvider<_>>()
+        .with_components(EthereumNode::components())
+        .with_add_ons(EthereumAddOns::default())
+        .launch_with_debug_capabilities()
+        .await?;
+
+    assert_eq!(node.provider.chain_spec().genesis_hash(), remote.provider.chain_info()?.best_hash);
+
+    // The sender of the transaction is only funded in the remote state.
+    assert_chain_advances(&node).await;
+    assert_eq!(remote.provider.best_block_number()?, 0);
+
+    Ok(())
+}
+
 async fn assert_chain_advances<N, AddOns>(node: &FullNode<N, AddOns>)
 where
     N: FullNodeComponents<Provider: CanonStateSubscriptions>,
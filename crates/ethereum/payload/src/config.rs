@@ -18,6 +18,11 @@ pub struct EthereumBuilderConfig {
     pub extra_data: Bytes,
     /// Whether payload builds should skip state-root computation.
     pub skip_state_root: bool,
+    /// Whether transactions that reverted in their latest speculative execution in the pool
+    /// should be left out of the payload.
+    ///
+    /// Only executions on top of the payload's parent block are considered.
+    pub skip_reverting_transactions: bool,
 }
 
 impl Default for EthereumBuilderConfig {
@@ -35,6 +40,7 @@ impl EthereumBuilderConfig {
             max_blobs_per_block: None,
             extra_data: Bytes::new(),
             skip_state_root: false,
+            skip_reverting_transactions: false,
         }
     }
 
@@ -68,6 +74,16 @@ impl EthereumBuilderConfig {
         self.skip_state_root = skip_state_root;
         self
     }
+
+    /// Configures whether transactions that reverted in their latest speculative execution in the
+    /// pool should be left out of the payload.
+    pub const fn with_skip_reverting_transactions(
+        mut self,
+        skip_reverting_transactions: bool,
+    ) -> Self {
+        self.skip_reverting_transactions = skip_reverting_transactions;
+        self
+    }
 }
 
 impl EthereumBuilderConfig {
@@ -35,6 +35,7 @@ use reth_revm::{database::StateProviderDatabase, db::State};
 use reth_storage_api::StateProviderFactory;
 use reth_transaction_pool::{
     error::{Eip4844PoolTransactionError, InvalidPoolTransactionError},
+    simulate::{reverted_in_simulation, SimulatedRevertError},
     BestTransactions, BestTransactionsAttributes, PoolTransaction, TransactionPool,
     ValidPoolTransaction,
 };
@@ -255,6 +256,17 @@ where
         attributes.withdrawals.as_ref().map(|withdrawals| withdrawals.length()).unwrap_or(0);
 
     while let Some(pool_tx) = best_txs.next() {
+        // skip transactions that are known to revert on top of the parent block, this also skips
+        // their descendants
+        if builder_config.skip_reverting_transactions &&
+            reverted_in_simulation(&pool_tx, parent_header.number + 1)
+        {
+            trace!(target: "payload_builder", tx_hash=?pool_tx.hash(), "skipping transaction that reverted in simulation");
+            best_txs
+                .mark_invalid(&pool_tx, InvalidPoolTransactionError::other(SimulatedRevertError));
+            continue
+        }
+
         // ensure we still have capacity for this transaction
         let exceeds_gas_limit = if is_amsterdam {
             let regular_available_gas = block_gas_limit.saturating_sub(block_regular_gas_used);
@@ -505,3 +517,75 @@ where
 
     Ok(BuildOutcome::Better { payload, cached_reads })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::Header;
+    use alloy_primitives::B256;
+    use alloy_rpc_types_engine::PayloadId;
+    use reth_chainspec::{ChainSpecBuilder, MIN_TRANSACTION_GAS};
+    use reth_primitives_traits::SealedHeader;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_transaction_pool::test_utils::{testing_pool, MockTransaction};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn skips_transactions_reverted_in_simulation() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().london_activated().build());
+        let provider = MockEthProvider::default().with_chain_spec(chain_spec.clone());
+        let pool = testing_pool();
+
+        let parent = Arc::new(SealedHeader::seal_slow(Header {
+            number: 1,
+            timestamp: 12,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            ..Default::default()
+        }));
+        let block_number = parent.number + 1;
+
+        let reverted = MockTransaction::eip1559().with_gas_limit(MIN_TRANSACTION_GAS);
+        let stale = MockTransaction::eip1559().with_gas_limit(MIN_TRANSACTION_GAS);
+        for tx in [&reverted, &stale] {
+            provider.add_account(tx.sender(), ExtendedAccount::new(0, U256::from(u64::MAX)));
+            pool.add_external_transaction(tx.clone()).await.unwrap();
+        }
+        // reverted on top of the parent
+        pool.get(reverted.hash()).unwrap().simulation.record(block_number, 50_000, true, 0);
+        // reverted on top of an older block
+        pool.get(stale.hash()).unwrap().simulation.record(block_number - 1, 50_000, true, 0);
+
+        let config = PayloadConfig::new(
+            parent,
+            EthPayloadAttributes { timestamp: 24, ..Default::default() },
+            PayloadId::new([0; 8]),
+        );
+        let args =
+            BuildArguments::new(Default::default(), None, None, config, Default::default(), None);
+        let builder_config = EthereumBuilderConfig::new()
+            .with_skip_state_root(true)
+            .with_skip_reverting_transactions(true);
+
+        let outcome = default_ethereum_payload(
+            EthEvmConfig::new(chain_spec),
+            provider,
+            pool.clone(),
+            builder_config,
+            args,
+            |attributes| pool.best_transactions_with_attributes(attributes),
+        )
+        .unwrap();
+
+        let BuildOutcome::Better { payload, .. } = outcome else {
+            panic!("expected a payload, got {outcome:?}")
+        };
+        let included = payload
+            .block()
+            .body()
+            .transactions
+            .iter()
+            .map(|tx| *tx.tx_hash())
+            .collect::<Vec<B256>>();
+        assert_eq!(included, vec![*stale.hash()]);
+    }
+}
@@ -17,17 +17,50 @@
 use std::{
     cmp::Reverse,
     collections::BinaryHeap,
-    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
+    sync::mpsc::{sync_channel, Receiver, SyncSender},
+    thread::JoinHandle,
+    time::Instant,
 };
 
 /// Key len and Value len encode use [`usize::to_be_bytes()`] the length is 8.
 const KV_LEN: usize = 8;
 
+/// Size of the uncompressed blocks that compressed files are written in.
+const COMPRESSION_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Zstd compression level of compressed files, favouring speed over ratio.
+const ZSTD_COMPRESSION_LEVEL: i32 = 1;
+
+/// Number of entries a merge thread sends to the [`EtlIter`] at once.
+const MERGE_BATCH_SIZE: usize = 4096;
+
+/// Number of batches buffered per merge thread.
+const MERGE_CHANNEL_CAPACITY: usize = 4;
+
 use rayon::prelude::*;
 use reth_db_api::table::{Compress, Encode, Key, Value};
+use reth_metrics::{
+    metrics::{Counter, Histogram},
+    Metrics,
+};
 use tempfile::{NamedTempFile, TempDir};
 
+/// Compression of the temporary ETL files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum FileCompression {
+    /// Files are not compressed.
+    #[default]
+    None,
+    /// Files are compressed with LZ4, which is fast but compresses less.
+    Lz4,
+    /// Files are compressed with Zstandard, which compresses better at a higher CPU cost.
+    Zstd,
+}
+
 /// An ETL (extract, transform, load) data collector.
 ///
 /// Data is pushed (extract) to the collector which internally flushes the data in a sorted
@@ -37,6 +70,9 @@ use tempfile::{NamedTempFile, TempDir};
 /// Used mainly to insert data into `MDBX` in a sorted manner. This is important because performance
 /// and storage space degrades greatly if the data is inserted unsorted (eg. tables with hashes as
 /// keys.) as opposed to append & sorted insert. Some benchmarks can be found [here](https://github.com/paradigmxyz/reth/pull/1130#issuecomment-1418642755).
+///
+/// Files can optionally be compressed, and merged by multiple threads in parallel, see
+/// [`Collector::with_compression`] and [`Collector::with_merge_threads`].
 #[derive(Debug)]
 pub struct Collector<K, V>
 where
@@ -57,6 +93,12 @@ where
     buffer: Vec<(<K as Encode>::Encoded, <V as Compress>::Compressed)>,
     /// Total number of elements in the collector, including all files
     len: usize,
+    /// Compression of the temporary files
+    compression: FileCompression,
+    /// Maximum number of threads merging the temporary files
+    merge_threads: usize,
+    /// Metrics of the collector, if enabled
+    metrics: Option<EtlMetrics>,
 }
 
 impl<K, V> Collector<K, V>
@@ -76,9 +118,42 @@ where
             buffer_capacity_bytes,
             buffer: Vec::new(),
             len: 0,
+            compression: FileCompression::None,
+            merge_threads: 1,
+            metrics: None,
         }
     }
 
+    /// Sets the maximum buffer capacity in bytes, which triggers a flush when reached.
+    pub const fn with_buffer_capacity(mut self, buffer_capacity_bytes: usize) -> Self {
+        self.buffer_capacity_bytes = buffer_capacity_bytes;
+        self
+    }
+
+    /// Sets the compression of the temporary files.
+    ///
+    /// Files are compressed in blocks, trading CPU for temporary disk space and IO.
+    pub const fn with_compression(mut self, compression: FileCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the maximum number of threads merging the temporary files.
+    ///
+    /// With more than one thread, the files are split into groups which are read, decompressed
+    /// and merged on separate threads, and [`Collector::iter`] merges the sorted output of the
+    /// groups.
+    pub const fn with_merge_threads(mut self, merge_threads: usize) -> Self {
+        self.merge_threads = merge_threads;
+        self
+    }
+
+    /// Enables throughput metrics of the collector, labeled with the given stage.
+    pub fn with_metrics(mut self, stage: &str) -> Self {
+        self.metrics = Some(EtlMetrics::new_with_labels(&[("stage", stage.to_string())]));
+        self
+    }
+
     /// Returns number of elements currently in the collector.
     pub const fn len(&self) -> usize {
         self.len
@@ -131,13 +206,22 @@ where
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.buffer_size_bytes = 0;
+        let started_at = Instant::now();
+        let buffer_size_bytes = std::mem::take(&mut self.buffer_size_bytes);
         self.buffer.par_sort_unstable_by(|a, b| a.0.cmp(&b.0));
         let mut buf = Vec::with_capacity(self.buffer.len());
         std::mem::swap(&mut buf, &mut self.buffer);
 
         let path = self.dir()?.path().to_path_buf();
-        self.files.push(EtlFile::new(path.as_path(), buf)?);
+        let file = EtlFile::new(path.as_path(), buf, self.compression)?;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.entries_flushed.increment(file.len as u64);
+            metrics.bytes_flushed.increment(buffer_size_bytes as u64);
+            metrics.disk_bytes_written.increment(file.file.get_ref().as_file().metadata()?.len());
+            metrics.flush_duration_seconds.record(started_at.elapsed());
+        }
+        self.files.push(file);
 
         Ok(())
     }
@@ -156,14 +240,31 @@ where
             self.flush()?;
         }
 
+        let mut sources = if self.merge_threads > 1 && self.files.len() > 1 {
+            // Distribute the files across the merge threads, so that each thread merges the same
+            // number of files.
+            let mut groups = (0..self.merge_threads.min(self.files.len()))
+                .map(|_| Vec::new())
+                .collect::<Vec<_>>();
+            for (id, file) in std::mem::take(&mut self.files).into_iter().enumerate() {
+                let group_count = groups.len();
+                groups[id % group_count].push(file);
+            }
+            EtlSources::Merged(
+                groups.into_iter().map(MergeWorker::spawn).collect::<Result<_, _>>()?,
+            )
+        } else {
+            EtlSources::Files(&mut self.files)
+        };
+
         let mut heap = BinaryHeap::new();
-        for (current_id, file) in self.files.iter_mut().enumerate() {
-            if let Some((current_key, current_value)) = file.read_next()? {
+        for current_id in 0..sources.len() {
+            if let Some((current_key, current_value)) = sources.read_next(current_id)? {
                 heap.push((Reverse((current_key, current_value)), current_id));
             }
         }
 
-        Ok(EtlIter { heap, files: &mut self.files })
+        Ok(EtlIter { heap, sources, metrics: self.metrics.as_ref() })
     }
 }
 
@@ -186,8 +287,10 @@ type HeapItem = (Reverse<(Vec<u8>, Vec<u8>)>, usize);
 pub struct EtlIter<'a> {
     /// Heap managing the next items to be iterated.
     heap: BinaryHeap<HeapItem>,
-    /// Reference to the vector of ETL files being iterated over.
-    files: &'a mut Vec<EtlFile>,
+    /// Sources of the sorted entries being iterated over.
+    sources: EtlSources<'a>,
+    /// Metrics of the collector, if enabled.
+    metrics: Option<&'a EtlMetrics>,
 }
 
 impl EtlIter<'_> {
@@ -204,8 +307,12 @@ impl Iterator for EtlIter<'_> {
         // Get the next sorted entry from the heap
         let (Reverse(entry), id) = self.heap.pop()?;
 
-        // Populate the heap with the next entry from the same file
-        match self.files[id].read_next() {
+        if let Some(metrics) = self.metrics {
+            metrics.entries_merged.increment(1);
+        }
+
+        // Populate the heap with the next entry from the same source
+        match self.sources.read_next(id) {
             Ok(Some((key, value))) => {
                 self.heap.push((Reverse((key, value)), id));
                 Some(Ok(entry))
@@ -216,18 +323,132 @@ impl Iterator for EtlIter<'_> {
     }
 }
 
+/// Sources of sorted entries merged by [`EtlIter`].
+#[derive(Debug)]
+enum EtlSources<'a> {
+    /// ETL files read on the iterating thread.
+    Files(&'a mut Vec<EtlFile>),
+    /// Groups of ETL files merged on separate threads.
+    Merged(Vec<MergeWorker>),
+}
+
+impl EtlSources<'_> {
+    /// Returns the number of sources.
+    fn len(&self) -> usize {
+        match self {
+            Self::Files(files) => files.len(),
+            Self::Merged(workers) => workers.len(),
+        }
+    }
+
+    /// Read the next entry of the source with the given id.
+    fn read_next(&mut self, id: usize) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+        match self {
+            Self::Files(files) => files[id].read_next(),
+            Self::Merged(workers) => workers[id].read_next(),
+        }
+    }
+}
+
+/// Merges a group of ETL files on a separate thread, sending the sorted entries in batches.
+#[derive(Debug)]
+struct MergeWorker {
+    /// Receiver of the sorted batches.
+    receiver: Receiver<io::Result<Vec<(Vec<u8>, Vec<u8>)>>>,
+    /// The current batch.
+    batch: std::vec::IntoIter<(Vec<u8>, Vec<u8>)>,
+    /// Handle of the merge thread, taken once the thread finished.
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MergeWorker {
+    /// Spawns a thread merging the given files.
+    fn spawn(files: Vec<EtlFile>) -> io::Result<Self> {
+        let (sender, receiver) = sync_channel(MERGE_CHANNEL_CAPACITY);
+        let handle = std::thread::Builder::new()
+            .name("etl-merge".to_string())
+            .spawn(move || Self::merge(files, sender))?;
+        Ok(Self { receiver, batch: Vec::new().into_iter(), handle: Some(handle) })
+    }
+
+    /// Merges the files, sending the entries in batches. Returns early if the receiver is dropped.
+    fn merge(mut files: Vec<EtlFile>, sender: SyncSender<io::Result<Vec<(Vec<u8>, Vec<u8>)>>>) {
+        let mut merge = || -> io::Result<()> {
+            let mut heap = BinaryHeap::new();
+            for (id, file) in files.iter_mut().enumerate() {
+                if let Some(entry) = file.read_next()? {
+                    heap.push((Reverse(entry), id));
+                }
+            }
+
+            let mut batch = Vec::with_capacity(MERGE_BATCH_SIZE);
+            while let Some((Reverse(entry), id)) = heap.pop() {
+                batch.push(entry);
+                if let Some(next) = files[id].read_next()? {
+                    heap.push((Reverse(next), id));
+                }
+                if batch.len() == MERGE_BATCH_SIZE {
+                    let batch = std::mem::replace(&mut batch, Vec::with_capacity(MERGE_BATCH_SIZE));
+                    if sender.send(Ok(batch)).is_err() {
+                        return Ok(())
+                    }
+                }
+            }
+            if !batch.is_empty() {
+                let _ = sender.send(Ok(batch));
+            }
+            Ok(())
+        };
+
+        if let Err(err) = merge() {
+            let _ = sender.send(Err(err));
+        }
+    }
+
+    /// Read the next merged entry.
+    fn read_next(&mut self) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+        loop {
+            if let Some(entry) = self.batch.next() {
+                return Ok(Some(entry))
+            }
+
+            match self.receiver.recv() {
+                Ok(batch) => self.batch = batch?.into_iter(),
+                Err(_) => {
+                    // The thread finished, make sure it didn't panic before sending all entries.
+                    if let Some(handle) = self.handle.take() &&
+                        handle.join().is_err()
+                    {
+                        return Err(io::Error::other("ETL merge thread panicked"))
+                    }
+                    return Ok(None)
+                }
+            }
+        }
+    }
+}
+
 /// A temporary ETL file.
 #[derive(Debug)]
 struct EtlFile {
     file: BufReader<NamedTempFile>,
     len: usize,
+    compression: FileCompression,
+    /// The current decompressed block, if the file is compressed.
+    block: Cursor<Vec<u8>>,
 }
 
 impl EtlFile {
     /// Create a new file with the given data (which should be pre-sorted) at the given path.
     ///
-    /// The file will be a temporary file.
-    pub(crate) fn new<K, V>(dir: &Path, buffer: Vec<(K, V)>) -> std::io::Result<Self>
+    /// The file will be a temporary file. Compressed files are written in blocks of
+    /// [`COMPRESSION_BLOCK_SIZE`] uncompressed bytes, each prefixed with its uncompressed and
+    /// compressed length.
+    pub(crate) fn new<K, V>(
+        dir: &Path,
+        buffer: Vec<(K, V)>,
+        compression: FileCompression,
+    ) -> std::io::Result<Self>
     where
         Self: Sized,
         K: AsRef<[u8]>,
@@ -235,20 +456,28 @@ impl EtlFile {
     {
         let file = NamedTempFile::new_in(dir)?;
         let mut w = BufWriter::new(file);
-        for entry in &buffer {
-            let k = entry.0.as_ref();
-            let v = entry.1.as_ref();
-
-            w.write_all(&k.len().to_be_bytes())?;
-            w.write_all(&v.len().to_be_bytes())?;
-            w.write_all(k)?;
-            w.write_all(v)?;
+        if compression == FileCompression::None {
+            for entry in &buffer {
+                write_entry(&mut w, entry.0.as_ref(), entry.1.as_ref())?;
+            }
+        } else {
+            let mut block = Vec::with_capacity(COMPRESSION_BLOCK_SIZE);
+            for entry in &buffer {
+                write_entry(&mut block, entry.0.as_ref(), entry.1.as_ref())?;
+                if block.len() >= COMPRESSION_BLOCK_SIZE {
+                    write_block(&mut w, &block, compression)?;
+                    block.clear();
+                }
+            }
+            if !block.is_empty() {
+                write_block(&mut w, &block, compression)?;
+            }
         }
 
         let mut file = BufReader::new(w.into_inner()?);
         file.seek(SeekFrom::Start(0))?;
         let len = buffer.len();
-        Ok(Self { file, len })
+        Ok(Self { file, len, compression, block: Cursor::default() })
     }
 
     /// Read the next entry in the file.
@@ -259,26 +488,100 @@ impl EtlFile {
             return Ok(None)
         }
 
-        let mut buffer_key_length = [0; KV_LEN];
-        let mut buffer_value_length = [0; KV_LEN];
+        let entry = if self.compression == FileCompression::None {
+            read_entry(&mut self.file)?
+        } else {
+            if self.block.position() as usize >= self.block.get_ref().len() {
+                self.read_block()?;
+            }
+            read_entry(&mut self.block)?
+        };
 
-        self.file.read_exact(&mut buffer_key_length)?;
-        self.file.read_exact(&mut buffer_value_length)?;
+        self.len -= 1;
 
-        let key_length = usize::from_be_bytes(buffer_key_length);
-        let value_length = usize::from_be_bytes(buffer_value_length);
-        let mut key = vec![0; key_length];
-        let mut value = vec![0; value_length];
+        Ok(Some(entry))
+    }
 
-        self.file.read_exact(&mut key)?;
-        self.file.read_exact(&mut value)?;
+    /// Read and decompress the next block of the file.
+    fn read_block(&mut self) -> std::io::Result<()> {
+        let mut buffer_uncompressed_length = [0; KV_LEN];
+        let mut buffer_compressed_length = [0; KV_LEN];
 
-        self.len -= 1;
+        self.file.read_exact(&mut buffer_uncompressed_length)?;
+        self.file.read_exact(&mut buffer_compressed_length)?;
 
-        Ok(Some((key, value)))
+        let uncompressed_length = usize::from_be_bytes(buffer_uncompressed_length);
+        let mut compressed = vec![0; usize::from_be_bytes(buffer_compressed_length)];
+        self.file.read_exact(&mut compressed)?;
+
+        let block = match self.compression {
+            FileCompression::None => compressed,
+            FileCompression::Lz4 => lz4_flex::decompress(&compressed, uncompressed_length)
+                .map_err(|err| io::Error::other(err.to_string()))?,
+            FileCompression::Zstd => zstd::bulk::decompress(&compressed, uncompressed_length)?,
+        };
+        self.block = Cursor::new(block);
+
+        Ok(())
     }
 }
 
+/// Writes a length-prefixed key-value pair.
+fn write_entry(w: &mut impl Write, key: &[u8], value: &[u8]) -> io::Result<()> {
+    w.write_all(&key.len().to_be_bytes())?;
+    w.write_all(&value.len().to_be_bytes())?;
+    w.write_all(key)?;
+    w.write_all(value)
+}
+
+/// Reads a key-value pair written by [`write_entry`].
+fn read_entry(r: &mut impl Read) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut buffer_key_length = [0; KV_LEN];
+    let mut buffer_value_length = [0; KV_LEN];
+
+    r.read_exact(&mut buffer_key_length)?;
+    r.read_exact(&mut buffer_value_length)?;
+
+    let key_length = usize::from_be_bytes(buffer_key_length);
+    let value_length = usize::from_be_bytes(buffer_value_length);
+    let mut key = vec![0; key_length];
+    let mut value = vec![0; value_length];
+
+    r.read_exact(&mut key)?;
+    r.read_exact(&mut value)?;
+
+    Ok((key, value))
+}
+
+/// Compresses a block of entries and writes it prefixed with its uncompressed and compressed
+/// length.
+fn write_block(w: &mut impl Write, block: &[u8], compression: FileCompression) -> io::Result<()> {
+    let compressed = match compression {
+        FileCompression::None => block.to_vec(),
+        FileCompression::Lz4 => lz4_flex::compress(block),
+        FileCompression::Zstd => zstd::bulk::compress(block, ZSTD_COMPRESSION_LEVEL)?,
+    };
+    w.write_all(&block.len().to_be_bytes())?;
+    w.write_all(&compressed.len().to_be_bytes())?;
+    w.write_all(&compressed)
+}
+
+/// Throughput metrics of a [`Collector`].
+#[derive(Metrics)]
+#[metrics(scope = "etl")]
+struct EtlMetrics {
+    /// Number of entries flushed to temporary files
+    entries_flushed: Counter,
+    /// Number of encoded bytes flushed to temporary files, before compression
+    bytes_flushed: Counter,
+    /// Number of bytes written to temporary files, after compression
+    disk_bytes_written: Counter,
+    /// Time it took to sort and write a temporary file
+    flush_duration_seconds: Histogram,
+    /// Number of entries yielded in sorted order
+    entries_merged: Counter,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,4 +619,34 @@ mod tests {
         assert!(collector.is_empty());
         assert!(!temp_dir_path.exists());
     }
+
+    #[test]
+    fn etl_compressed_parallel_merge() {
+        let mut entries: Vec<_> =
+            (0..10_000).map(|id| (TxHash::random(), id as TxNumber)).collect();
+        let mut sorted = entries.clone();
+        sorted.sort_unstable_by_key(|entry| entry.0);
+
+        for compression in [FileCompression::None, FileCompression::Lz4, FileCompression::Zstd] {
+            for merge_threads in [1, 3] {
+                let mut collector = Collector::new(1024, None)
+                    .with_compression(compression)
+                    .with_merge_threads(merge_threads)
+                    .with_metrics("test");
+                for (k, v) in entries.clone() {
+                    collector.insert(k, v).unwrap();
+                }
+                assert!(collector.files.len() > merge_threads);
+                assert_eq!(collector.len(), entries.len());
+
+                let collected = collector.iter().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+                let expected = sorted
+                    .iter()
+                    .map(|(k, v)| (k.encode().to_vec(), v.compress()))
+                    .collect::<Vec<_>>();
+                assert_eq!(collected, expected, "{compression:?} with {merge_threads} threads");
+            }
+            entries.reverse();
+        }
+    }
 }
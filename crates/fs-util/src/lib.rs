@@ -103,6 +103,17 @@ pub enum FsPathError {
         to: PathBuf,
     },
 
+    /// Error variant for failed file copy operation with additional path context.
+    #[error("failed to copy {from:?} to {to:?}: {source}")]
+    Copy {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The original path.
+        from: PathBuf,
+        /// The target path.
+        to: PathBuf,
+    },
+
     /// Error variant for failed file opening operation with additional path context.
     #[error("failed to open file {path:?}: {source}")]
     Open {
@@ -199,6 +210,11 @@ impl FsPathError {
         Self::Rename { source, from: from.into(), to: to.into() }
     }
 
+    /// Returns the complementary error variant for [`std::fs::copy`].
+    pub fn copy(source: io::Error, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        Self::Copy { source, from: from.into(), to: to.into() }
+    }
+
     /// Returns the complementary error variant for [`std::fs::File::metadata`].
     pub fn metadata(source: io::Error, path: impl Into<PathBuf>) -> Self {
         Self::Metadata { source, path: path.into() }
@@ -290,6 +306,13 @@ pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
     fs::rename(from, to).map_err(|err| FsPathError::rename(err, from, to))
 }
 
+/// Wrapper for `std::fs::copy`
+pub fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<u64> {
+    let from = from.as_ref();
+    let to = to.as_ref();
+    fs::copy(from, to).map_err(|err| FsPathError::copy(err, from, to))
+}
+
 /// Wrapper for `std::fs::metadata`
 pub fn metadata(path: impl AsRef<Path>) -> Result<fs::Metadata> {
     let path = path.as_ref();
@@ -314,6 +337,16 @@ pub fn write_json_file<T: Serialize>(path: &Path, obj: &T) -> Result<()> {
     writer.flush().map_err(|e| FsPathError::write(e, path))
 }
 
+/// Writes the object as a JSON object, atomically replacing the file with
+/// [`atomic_write_file`].
+pub fn atomic_write_json_file<T: Serialize>(path: &Path, obj: &T) -> Result<()> {
+    atomic_write_file(path, |file| -> io::Result<()> {
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, obj)?;
+        writer.flush()
+    })
+}
+
 /// Writes atomically to file.
 ///
 /// 1. Creates a temporary file with a `.tmp` extension in the same file directory.
@@ -10,7 +10,14 @@
 
 type PeerId = alloy_primitives::B512;
 
-use std::{collections::HashMap, net::IpAddr, str::FromStr, time::Instant};
+use ipnet::IpNet;
+use std::{
+    collections::HashMap,
+    fmt,
+    net::IpAddr,
+    str::FromStr,
+    time::{Duration, Instant, SystemTime},
+};
 
 /// Determines whether or not the IP is globally routable.
 /// Should be replaced with [`IpAddr::is_global`](std::net::IpAddr::is_global) once it is stable.
@@ -25,6 +32,136 @@ pub const fn is_global(ip: &IpAddr) -> bool {
     }
 }
 
+/// What a [`BanEntry`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BanTarget {
+    /// A single peer.
+    Peer(PeerId),
+    /// A single IP address.
+    Ip(IpAddr),
+    /// A range of IP addresses.
+    Network(IpNet),
+}
+
+impl BanTarget {
+    /// Returns true if the target covers the given ip address.
+    pub fn contains_ip(&self, ip: &IpAddr) -> bool {
+        match self {
+            Self::Peer(_) => false,
+            Self::Ip(banned) => banned == ip,
+            Self::Network(net) => net.contains(ip),
+        }
+    }
+}
+
+impl fmt::Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Peer(peer_id) => write!(f, "{peer_id}"),
+            Self::Ip(ip) => write!(f, "{ip}"),
+            Self::Network(net) => write!(f, "{net}"),
+        }
+    }
+}
+
+impl FromStr for BanTarget {
+    type Err = ParseBanTargetError;
+
+    /// Parses a CIDR range (`10.0.0.0/8`), an IP address or a hex encoded peer id.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('/') {
+            return s.parse().map(Self::Network).map_err(|_| ParseBanTargetError(s.to_string()))
+        }
+        if let Ok(ip) = s.parse() {
+            return Ok(Self::Ip(ip))
+        }
+        s.parse().map(Self::Peer).map_err(|_| ParseBanTargetError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for BanTarget {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BanTarget {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Error returned when parsing an invalid [`BanTarget`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid ban target {0:?}, expected a peer id, an IP address or a CIDR range")]
+pub struct ParseBanTargetError(String);
+
+/// A ban with its reason and expiry.
+///
+/// Unlike the bans tracked with an [`Instant`], these are expressed in wall clock time, so that
+/// they can be persisted and restored after a restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BanEntry {
+    /// The banned peer, IP address or range.
+    pub target: BanTarget,
+    /// Why the target was banned.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub reason: Option<String>,
+    /// When the ban was created.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub banned_at: SystemTime,
+    /// When the ban expires, `None` if the target is banned indefinitely.
+    #[cfg_attr(feature = "serde", serde(default, with = "humantime_serde"))]
+    pub expires_at: Option<SystemTime>,
+}
+
+impl BanEntry {
+    /// Creates an indefinite ban of the target, starting now.
+    pub fn new(target: BanTarget) -> Self {
+        Self { target, reason: None, banned_at: SystemTime::now(), expires_at: None }
+    }
+
+    /// Sets the reason of the ban.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Lets the ban expire after the given duration.
+    ///
+    /// A duration that exceeds the range of [`SystemTime`] leaves the ban indefinite.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.expires_at = self.banned_at.checked_add(duration);
+        self
+    }
+
+    /// Returns true if the ban expired at the given time.
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Returns the instant the ban expires at, relative to the given current time.
+    ///
+    /// Returns `None` if the ban is indefinite or expires beyond the range of [`Instant`].
+    pub fn expires_at_instant(&self, now: SystemTime, now_instant: Instant) -> Option<Instant> {
+        self.expires_at.and_then(|expires_at| {
+            now_instant.checked_add(expires_at.duration_since(now).unwrap_or(Duration::ZERO))
+        })
+    }
+}
+
+/// A [`BanEntry`] tracked by the [`BanList`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PersistentBan {
+    entry: BanEntry,
+    until: Option<Instant>,
+}
+
 /// Stores peers that should be taken out of circulation either indefinitely or until a certain
 /// timestamp
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -33,6 +170,10 @@ pub struct BanList {
     banned_ips: HashMap<IpAddr, Option<Instant>>,
     /// A set of [`PeerId`] whose packets get dropped instantly.
     banned_peers: HashMap<PeerId, Option<Instant>>,
+    /// Bans with a reason and expiry that are persisted across restarts.
+    bans: HashMap<BanTarget, PersistentBan>,
+    /// The banned ranges, the [`BanTarget::Network`] keys of `bans`.
+    banned_networks: Vec<IpNet>,
 }
 
 impl BanList {
@@ -48,11 +189,23 @@ impl BanList {
     }
 
     /// Creates a new ban list that bans the given peers and ips with an optional timeout.
-    pub const fn new_with_timeout(
+    pub fn new_with_timeout(
         banned_peers: HashMap<PeerId, Option<Instant>>,
         banned_ips: HashMap<IpAddr, Option<Instant>>,
     ) -> Self {
-        Self { banned_ips, banned_peers }
+        Self { banned_ips, banned_peers, bans: HashMap::new(), banned_networks: Vec::new() }
+    }
+
+    /// Creates a new ban list from the given entries, skipping entries that already expired.
+    pub fn from_entries(entries: impl IntoIterator<Item = BanEntry>) -> Self {
+        let mut ban_list = Self::default();
+        let now = SystemTime::now();
+        for entry in entries {
+            if !entry.is_expired_at(now) {
+                ban_list.ban(entry);
+            }
+        }
+        ban_list
     }
 
     /// Removes all peers that are no longer banned.
@@ -67,6 +220,13 @@ impl BanList {
             }
             true
         });
+        for target in self.evict_bans(now, |target| matches!(target, BanTarget::Peer(_))) {
+            if let BanTarget::Peer(peer_id) = target &&
+                !evicted.contains(&peer_id)
+            {
+                evicted.push(peer_id);
+            }
+        }
         evicted
     }
 
@@ -82,6 +242,38 @@ impl BanList {
             }
             true
         });
+        // this also evicts the expired ranges
+        for target in self.evict_bans(now, |target| !matches!(target, BanTarget::Peer(_))) {
+            if let BanTarget::Ip(ip) = target &&
+                !evicted.contains(&ip)
+            {
+                evicted.push(ip);
+            }
+        }
+        evicted
+    }
+
+    /// Removes the expired [`BanEntry`]s of the targets matching the filter and returns their
+    /// targets.
+    fn evict_bans(
+        &mut self,
+        now: Instant,
+        mut filter: impl FnMut(&BanTarget) -> bool,
+    ) -> Vec<BanTarget> {
+        let mut evicted = Vec::new();
+        self.bans.retain(|target, ban| {
+            if let Some(until) = ban.until &&
+                now > until &&
+                filter(target)
+            {
+                evicted.push(*target);
+                return false
+            }
+            true
+        });
+        if evicted.iter().any(|target| matches!(target, BanTarget::Network(_))) {
+            self.banned_networks.retain(|net| !evicted.contains(&BanTarget::Network(*net)));
+        }
         evicted
     }
 
@@ -100,26 +292,76 @@ impl BanList {
         self.is_banned_peer(peer_id) || self.is_banned_ip(ip)
     }
 
-    /// checks the ban list to see if it contains the given ip
+    /// checks the ban list to see if it contains the given ip, or a range that contains it
     #[inline]
     pub fn is_banned_ip(&self, ip: &IpAddr) -> bool {
-        self.banned_ips.contains_key(ip)
+        self.banned_ips.contains_key(ip) ||
+            self.bans.contains_key(&BanTarget::Ip(*ip)) ||
+            self.banned_networks.iter().any(|net| net.contains(ip))
     }
 
     /// checks the ban list to see if it contains the given peer
     #[inline]
     pub fn is_banned_peer(&self, peer_id: &PeerId) -> bool {
-        self.banned_peers.contains_key(peer_id)
+        self.banned_peers.contains_key(peer_id) ||
+            self.bans.contains_key(&BanTarget::Peer(*peer_id))
     }
 
     /// Unbans the ip address
+    ///
+    /// This does not lift the ban of a range that contains the ip.
     pub fn unban_ip(&mut self, ip: &IpAddr) {
         self.banned_ips.remove(ip);
+        self.bans.remove(&BanTarget::Ip(*ip));
     }
 
     /// Unbans the peer
     pub fn unban_peer(&mut self, peer_id: &PeerId) {
         self.banned_peers.remove(peer_id);
+        self.bans.remove(&BanTarget::Peer(*peer_id));
+    }
+
+    /// Lifts all bans of the target.
+    ///
+    /// Returns true if the target was banned.
+    pub fn unban(&mut self, target: &BanTarget) -> bool {
+        let was_banned = match target {
+            BanTarget::Peer(peer_id) => self.banned_peers.remove(peer_id).is_some(),
+            BanTarget::Ip(ip) => self.banned_ips.remove(ip).is_some(),
+            BanTarget::Network(net) => {
+                let len = self.banned_networks.len();
+                self.banned_networks.retain(|banned| banned != net);
+                len != self.banned_networks.len()
+            }
+        };
+        self.bans.remove(target).is_some() || was_banned
+    }
+
+    /// Bans the target of the entry until the entry expires.
+    ///
+    /// Unlike [`Self::ban_ip`], this also bans non-global IPs. If the target is already banned
+    /// with an entry, the entry is replaced.
+    pub fn ban(&mut self, entry: BanEntry) {
+        let until = entry.expires_at_instant(SystemTime::now(), Instant::now());
+        if let BanTarget::Network(net) = entry.target &&
+            !self.banned_networks.contains(&net)
+        {
+            self.banned_networks.push(net);
+        }
+        self.bans.insert(entry.target, PersistentBan { entry, until });
+    }
+
+    /// Returns the [`BanEntry`] of the target, if any.
+    pub fn ban_entry(&self, target: &BanTarget) -> Option<&BanEntry> {
+        self.bans.get(target).map(|ban| &ban.entry)
+    }
+
+    /// Returns all [`BanEntry`]s, the bans that should be persisted across restarts.
+    ///
+    /// Bans without an entry, like the short-lived IP bans used to throttle inbound connections,
+    /// are not included.
+    pub fn entries(&self) -> impl Iterator<Item = &BanEntry> + '_ {
+        self.bans.values().map(|ban| &ban.entry)
     }
 
     /// Bans the IP until the timestamp.
@@ -214,6 +456,105 @@ mod tests {
         banlist.ban_ip(ip);
         assert!(!banlist.is_banned_ip(&ip));
     }
+
+    #[test]
+    fn can_ban_unban_network() {
+        let target: BanTarget = "192.168.0.0/16".parse().unwrap();
+        let mut banlist = BanList::default();
+        banlist.ban(BanEntry::new(target).with_reason("spam"));
+        assert!(banlist.is_banned_ip(&IpAddr::from([192, 168, 1, 1])));
+        assert!(!banlist.is_banned_ip(&IpAddr::from([192, 169, 1, 1])));
+
+        // unbanning a single ip of the range does not lift the ban of the range
+        banlist.unban_ip(&IpAddr::from([192, 168, 1, 1]));
+        assert!(banlist.is_banned_ip(&IpAddr::from([192, 168, 1, 1])));
+
+        assert!(banlist.unban(&target));
+        assert!(!banlist.is_banned_ip(&IpAddr::from([192, 168, 1, 1])));
+        assert!(!banlist.unban(&target));
+    }
+
+    #[test]
+    fn evict_expired_entries() {
+        let peer = PeerId::new([1; 64]);
+        let ip = IpAddr::from([1, 1, 1, 1]);
+        let net = "2001:db8::/32".parse().unwrap();
+        let mut banlist = BanList::default();
+        banlist.ban(BanEntry::new(BanTarget::Peer(peer)).with_duration(Duration::from_secs(60)));
+        banlist.ban(BanEntry::new(BanTarget::Ip(ip)).with_duration(Duration::from_secs(60)));
+        banlist.ban(BanEntry::new(BanTarget::Network(net)).with_duration(Duration::from_secs(60)));
+        assert_eq!(banlist.entries().count(), 3);
+
+        let (ips, peers) = banlist.evict(Instant::now());
+        assert!(ips.is_empty() && peers.is_empty());
+
+        let (ips, peers) = banlist.evict(Instant::now() + Duration::from_secs(61));
+        assert_eq!(ips, vec![ip]);
+        assert_eq!(peers, vec![peer]);
+        assert!(!banlist.is_banned_ip(&"2001:db8::1".parse().unwrap()));
+        assert_eq!(banlist.entries().count(), 0);
+    }
+
+    #[test]
+    fn overflowing_duration_is_indefinite() {
+        let ip = IpAddr::from([1, 1, 1, 1]);
+        let entry = BanEntry::new(BanTarget::Ip(ip)).with_duration(Duration::MAX);
+        assert_eq!(entry.expires_at, None);
+
+        let mut entry = BanEntry::new(BanTarget::Ip(ip));
+        entry.expires_at = Some(SystemTime::now() + Duration::from_secs(u32::MAX as u64));
+        let mut banlist = BanList::default();
+        banlist.ban(entry);
+        let (ips, _) = banlist.evict(Instant::now() + Duration::from_secs(3600));
+        assert!(ips.is_empty());
+        assert!(banlist.is_banned_ip(&ip));
+    }
+
+    #[test]
+    fn from_entries_skips_expired() {
+        let peer = PeerId::new([1; 64]);
+        let mut expired = BanEntry::new(BanTarget::Ip(IpAddr::from([1, 1, 1, 1])));
+        expired.expires_at = Some(SystemTime::now() - Duration::from_secs(1));
+        let banlist = BanList::from_entries([BanEntry::new(BanTarget::Peer(peer)), expired]);
+
+        assert!(banlist.is_banned_peer(&peer));
+        assert!(!banlist.is_banned_ip(&IpAddr::from([1, 1, 1, 1])));
+        assert_eq!(banlist.entries().count(), 1);
+    }
+
+    #[test]
+    fn parse_ban_target() {
+        let peer = PeerId::new([1; 64]);
+        assert_eq!(peer.to_string().parse::<BanTarget>().unwrap(), BanTarget::Peer(peer));
+        assert_eq!(
+            "10.0.0.1".parse::<BanTarget>().unwrap(),
+            BanTarget::Ip(IpAddr::from([10, 0, 0, 1]))
+        );
+        assert_eq!(
+            "10.0.0.0/8".parse::<BanTarget>().unwrap(),
+            BanTarget::Network("10.0.0.0/8".parse().unwrap())
+        );
+        assert!("10.0.0.0/33".parse::<BanTarget>().is_err());
+        assert!("enode".parse::<BanTarget>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ban_entry_serde_roundtrip() {
+        let entry = BanEntry::new("10.0.0.0/8".parse().unwrap())
+            .with_reason("spam")
+            .with_duration(Duration::from_secs(3600));
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains(r#""target":"10.0.0.0/8""#));
+        assert!(json.contains(r#""reason":"spam""#));
+
+        let decoded: BanEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.target, entry.target);
+        assert_eq!(decoded.reason, entry.reason);
+        let expires_at = decoded.expires_at.unwrap();
+        let expected = entry.expires_at.unwrap();
+        assert!(expected.duration_since(expires_at).unwrap() < Duration::from_secs(1));
+    }
 }
 
 /// IP filter for restricting network communication to specific IP ranges using CIDR notation.
@@ -42,6 +42,7 @@ use itertools::Itertools;
 use parking_lot::Mutex;
 use proto::{EnrRequest, EnrResponse};
 use reth_ethereum_forks::ForkId;
+use reth_net_banlist::{BanEntry, BanTarget};
 use reth_network_peers::{pk2id, PeerId};
 use secp256k1::SecretKey;
 use std::{
@@ -416,6 +417,21 @@ impl Discv4 {
         self.send_to_service(cmd);
     }
 
+    /// Adds the target of the entry, a peer, ip or range, to the ban list until the entry
+    /// expires.
+    ///
+    /// This will remove the covered nodes from the table and prevent any future inclusion
+    pub fn ban_entry(&self, entry: BanEntry) {
+        let cmd = Discv4Command::BanEntry(entry);
+        self.send_to_service(cmd);
+    }
+
+    /// Lifts all bans of the target.
+    pub fn unban(&self, target: BanTarget) {
+        let cmd = Discv4Command::Unban(target);
+        self.send_to_service(cmd);
+    }
+
     /// Sets the tcp port
     ///
     /// This will update our [`NodeRecord`]'s tcp port.
@@ -424,6 +440,22 @@ impl Discv4 {
         self.send_to_service(cmd);
     }
 
+    /// Sets the udp port
+    ///
+    /// This will update our [`NodeRecord`]'s udp port, e.g. if the discovery port is forwarded
+    /// from a different external port by the NAT gateway.
+    pub fn set_udp_port(&self, port: u16) {
+        let cmd = Discv4Command::SetUdpPort(port);
+        self.send_to_service(cmd);
+    }
+
+    /// Sets the given ip address as the node's external IP in the node record announced in
+    /// discovery
+    pub fn set_external_ip_addr(&self, external_ip: IpAddr) {
+        let cmd = Discv4Command::SetExternalIp(external_ip);
+        self.send_to_service(cmd);
+    }
+
     /// Sets the pair in the EIP-868 [`Enr`] of the node.
     ///
     /// If the key already exists, this will update it.
@@ -923,6 +955,30 @@ impl Discv4Service {
         self.config.ban_list.ban_peer(node_id);
     }
 
+    /// Adds the target of the entry to the ban list and removes the nodes it covers from the
+    /// table.
+    pub fn ban_entry(&mut self, entry: BanEntry) {
+        let banned = self
+            .kbuckets
+            .iter_ref()
+            .map(|entry| entry.node.value.record)
+            .filter(|record| match entry.target {
+                BanTarget::Peer(node_id) => record.id == node_id,
+                target => target.contains_ip(&record.address),
+            })
+            .map(|record| record.id)
+            .collect::<Vec<_>>();
+        for node_id in banned {
+            self.remove_node(node_id);
+        }
+        self.config.ban_list.ban(entry);
+    }
+
+    /// Lifts all bans of the target.
+    pub fn unban(&mut self, target: &BanTarget) {
+        self.config.ban_list.unban(target);
+    }
+
     /// Adds the ip to the ban list until the given timestamp.
     pub fn ban_ip_until(&mut self, ip: IpAddr, until: Instant) {
         self.config.ban_list.ban_ip_until(ip, until);
@@ -1851,6 +1907,8 @@ impl Discv4Service {
                     Discv4Command::BanIp(ip) => {
                         self.ban_ip(ip);
                     }
+                    Discv4Command::BanEntry(entry) => self.ban_entry(entry),
+                    Discv4Command::Unban(target) => self.unban(&target),
                     Discv4Command::SetEIP868RLPPair { key, rlp } => {
                         debug!(target: "discv4", key=%String::from_utf8_lossy(&key), "Update EIP-868 extension pair");
 
@@ -1864,7 +1922,19 @@ impl Discv4Service {
                         } else {
                             let _ = self.local_eip_868_enr.set_tcp6(port, &self.secret_key);
                         }
+                        *self.shared_node_record.lock() = self.local_node_record;
+                    }
+                    Discv4Command::SetUdpPort(port) => {
+                        debug!(target: "discv4", %port, "Update udp port");
+                        self.local_node_record.udp_port = port;
+                        if self.local_node_record.address.is_ipv4() {
+                            let _ = self.local_eip_868_enr.set_udp4(port, &self.secret_key);
+                        } else {
+                            let _ = self.local_eip_868_enr.set_udp6(port, &self.secret_key);
+                        }
+                        *self.shared_node_record.lock() = self.local_node_record;
                     }
+                    Discv4Command::SetExternalIp(ip) => self.set_external_ip_addr(ip),
 
                     Discv4Command::Terminated => {
                         // terminate the service
@@ -1938,9 +2008,10 @@ impl Discv4Service {
                 self.evict_expired_requests(Instant::now());
             }
 
-            // evict expired nodes
+            // evict expired nodes and bans
             while self.expire_interval.poll_tick(cx).is_ready() {
                 self.received_pongs.evict_expired(Instant::now(), EXPIRE_DURATION);
+                self.config.ban_list.evict(Instant::now());
             }
 
             if self.queued_events.is_empty() {
@@ -2167,10 +2238,14 @@ enum Discv4Command {
     Add(NodeRecord),
     AddBootNode(NodeRecord),
     SetTcpPort(u16),
+    SetUdpPort(u16),
+    SetExternalIp(IpAddr),
     SetEIP868RLPPair { key: Vec<u8>, rlp: Bytes },
     Ban(PeerId, IpAddr),
     BanPeer(PeerId),
     BanIp(IpAddr),
+    BanEntry(BanEntry),
+    Unban(BanTarget),
     Remove(PeerId),
     Lookup { node_id: Option<PeerId>, tx: Option<NodeRecordSender> },
     SetLookupInterval(Duration),
@@ -2969,6 +3044,60 @@ mod tests {
         let _ = discv4.lookup_self().await;
     }
 
+    #[tokio::test]
+    async fn test_ban_entry() {
+        reth_tracing::init_test_tracing();
+
+        let config = Discv4Config::builder().build();
+        let (_discv4, mut service) = create_discv4_with_config(config).await;
+
+        let id = PeerId::random();
+        let record = NodeRecord::new("10.0.0.1:30303".parse().unwrap(), id);
+        let _ = service.kbuckets.insert_or_update(
+            &kad_key(id),
+            NodeEntry::new_proven(record),
+            NodeStatus {
+                direction: ConnectionDirection::Incoming,
+                state: ConnectionState::Connected,
+            },
+        );
+        assert_eq!(service.kbuckets.iter_ref().count(), 1);
+
+        let target: BanTarget = "10.0.0.0/8".parse().unwrap();
+        service.ban_entry(BanEntry::new(target));
+        assert_eq!(service.kbuckets.iter_ref().count(), 0);
+        assert!(service.config.ban_list.is_banned_ip(&record.address));
+
+        service.unban(&target);
+        assert!(!service.config.ban_list.is_banned_ip(&record.address));
+    }
+
+    #[tokio::test]
+    async fn test_set_external_endpoint() {
+        reth_tracing::init_test_tracing();
+
+        let config = Discv4Config::builder().external_ip_resolver(None).build();
+        let (discv4, mut service) = create_discv4_with_config(config).await;
+
+        let ip: IpAddr = "203.0.113.1".parse().unwrap();
+        discv4.set_external_ip_addr(ip);
+        discv4.set_tcp_port(31303);
+        discv4.set_udp_port(31304);
+
+        // drain the commands
+        poll_fn(|cx| {
+            let _ = service.poll(cx);
+            Poll::Ready(())
+        })
+        .await;
+
+        let record = discv4.node_record();
+        assert_eq!((record.address, record.tcp_port, record.udp_port), (ip, 31303, 31304));
+        assert_eq!(service.local_eip_868_enr.ip4(), Some("203.0.113.1".parse().unwrap()));
+        assert_eq!(service.local_eip_868_enr.tcp4(), Some(31303));
+        assert_eq!(service.local_eip_868_enr.udp4(), Some(31304));
+    }
+
     #[tokio::test]
     async fn test_requests_timeout() {
         reth_tracing::init_test_tracing();
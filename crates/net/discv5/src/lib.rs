@@ -12,14 +12,15 @@ use std::{
     collections::HashSet,
     fmt,
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
-    sync::Arc,
-    time::Duration,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
 
 use ::enr::Enr;
 use alloy_primitives::bytes::Bytes;
 use enr::{discv4_id_to_discv5_id, EnrCombinedKeyWrapper};
 use futures::future::join_all;
+use ipnet::IpNet;
 use itertools::Itertools;
 use rand::{Rng, RngCore};
 use reth_ethereum_forks::{EnrForkIdEntry, ForkId};
@@ -72,6 +73,9 @@ pub struct Discv5 {
     fork_key: Option<&'static [u8]>,
     /// Filter applied to a discovered peers before passing it up to app.
     discovered_peer_filter: MustNotIncludeKeys,
+    /// Banned IP ranges and when their bans expire. [`discv5::Discv5`] can only ban single IPs,
+    /// so discovered peers in these ranges are filtered out before passing them up to app.
+    banned_networks: Arc<RwLock<Vec<(IpNet, Option<Instant>)>>>,
     /// Metrics for underlying [`discv5::Discv5`] node and filtered discovered peers.
     metrics: Discv5Metrics,
     /// Returns the _local_ [`NodeRecord`] this service was started with.
@@ -126,6 +130,26 @@ impl Discv5 {
         self.set_eip868_in_local_enr(key, buf.into())
     }
 
+    /// Sets the endpoint the node is reachable at in the local [`Enr`], e.g. if the ports are
+    /// forwarded from different external ports by the NAT gateway.
+    ///
+    /// Ports that are `None` keep their current value.
+    pub fn set_external_endpoint(&self, ip: IpAddr, tcp_port: Option<u16>, udp_port: Option<u16>) {
+        let enr = self.discv5.local_enr();
+        let (udp, tcp) =
+            if ip.is_ipv4() { (enr.udp4(), enr.tcp4()) } else { (enr.udp6(), enr.tcp6()) };
+
+        let udp_port = udp_port.or(udp).unwrap_or_else(|| self.local_port());
+        if !self.discv5.update_local_enr_socket((ip, udp_port).into(), false) {
+            debug!(target: "net::discv5", %ip, %udp_port, "failed to update udp socket in local enr");
+        }
+        if let Some(tcp_port) = tcp_port.or(tcp) &&
+            !self.discv5.update_local_enr_socket((ip, tcp_port).into(), true)
+        {
+            debug!(target: "net::discv5", %ip, %tcp_port, "failed to update tcp socket in local enr");
+        }
+    }
+
     /// Adds the peer and id to the ban list.
     ///
     /// This will prevent any future inclusion in the table
@@ -149,6 +173,77 @@ impl Discv5 {
         self.discv5.ban_ip(ip, None);
     }
 
+    /// Adds the peer to the ban list until the given instant, or indefinitely if `None`.
+    ///
+    /// This removes the peer from the table and will prevent any future inclusion
+    pub fn ban_peer_until(&self, peer_id: PeerId, until: Option<Instant>) {
+        match discv4_id_to_discv5_id(peer_id) {
+            Ok(node_id) => self.discv5.ban_node(&node_id, until),
+            Err(err) => error!(target: "net::discv5",
+                %err,
+                "failed to ban peer"
+            ),
+        }
+    }
+
+    /// Adds the ip to the ban list until the given instant, or indefinitely if `None`.
+    pub fn ban_ip_until(&self, ip: IpAddr, until: Option<Instant>) {
+        self.discv5.ban_ip(ip, until);
+    }
+
+    /// Bans the IP range until the given instant, or indefinitely if `None`.
+    ///
+    /// Nodes in the range are removed from the table and discovered peers in the range are no
+    /// longer passed up to app.
+    pub fn ban_network_until(&self, net: IpNet, until: Option<Instant>) {
+        for enr in self.discv5.table_entries_enr() {
+            if enr
+                .ip4()
+                .map(IpAddr::from)
+                .or_else(|| enr.ip6().map(IpAddr::from))
+                .is_some_and(|ip| net.contains(&ip))
+            {
+                self.discv5.remove_node(&enr.node_id());
+            }
+        }
+        let now = Instant::now();
+        let mut banned_networks = self.banned_networks.write().unwrap_or_else(|e| e.into_inner());
+        // also drop expired bans, so the list doesn't grow unbounded
+        banned_networks
+            .retain(|(banned, until)| *banned != net && until.is_none_or(|until| now < until));
+        banned_networks.push((net, until));
+    }
+
+    /// Lifts the ban of the peer.
+    pub fn unban_peer(&self, peer_id: PeerId) {
+        if let Ok(node_id) = discv4_id_to_discv5_id(peer_id) {
+            self.discv5.ban_node_remove(&node_id);
+        }
+    }
+
+    /// Lifts the ban of the ip.
+    pub fn unban_ip(&self, ip: IpAddr) {
+        self.discv5.ban_ip_remove(&ip);
+    }
+
+    /// Lifts the ban of the IP range.
+    pub fn unban_network(&self, net: IpNet) {
+        self.banned_networks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(banned, _)| *banned != net);
+    }
+
+    /// Returns true if the ip is in a banned range whose ban did not expire yet.
+    fn is_in_banned_network(&self, ip: &IpAddr) -> bool {
+        let now = Instant::now();
+        self.banned_networks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|(net, until)| net.contains(ip) && until.is_none_or(|until| now < until))
+    }
+
     /// Returns the [`NodeRecord`] of the local node.
     ///
     /// This includes the currently tracked external IP address of the node.
@@ -233,6 +328,7 @@ impl Discv5 {
                 rlpx_ip_mode,
                 fork_key,
                 discovered_peer_filter,
+                banned_networks: Default::default(),
                 metrics,
                 local_node_record,
             },
@@ -326,6 +422,16 @@ impl Discv5 {
                 return None
             }
         };
+        if self.is_in_banned_network(&node_record.address) {
+            trace!(target: "net::discv5",
+                ?enr,
+                "discovered peer is in a banned ip range"
+            );
+
+            self.metrics.discovered_peers.increment_established_sessions_filtered(1);
+
+            return None
+        }
         if let FilterOutcome::Ignore { reason } = self.filter_discovered_peer(enr) {
             trace!(target: "net::discv5",
                 ?enr,
@@ -743,6 +849,7 @@ mod test {
             rlpx_ip_mode: IpMode::Ip4,
             fork_key: None,
             discovered_peer_filter: MustNotIncludeKeys::default(),
+            banned_networks: Default::default(),
             metrics: Discv5Metrics::default(),
             local_node_record: NodeRecord::new(
                 (Ipv4Addr::LOCALHOST, 30303).into(),
@@ -802,6 +909,24 @@ mod test {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn set_external_endpoint() {
+        reth_tracing::init_test_tracing();
+
+        let (node, _) = start_discovery_node(unused_udp_port()).await;
+        let ip: IpAddr = "203.0.113.1".parse().unwrap();
+
+        node.set_external_endpoint(ip, Some(31303), None);
+        let record = node.node_record().unwrap();
+        assert_eq!((record.address, record.tcp_port), (ip, 31303));
+        // the udp port is kept
+        assert_eq!(record.udp_port, node.local_port());
+
+        node.set_external_endpoint(ip, None, Some(31304));
+        let record = node.node_record().unwrap();
+        assert_eq!((record.tcp_port, record.udp_port), (31303, 31304));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn discv5_releases_port_on_drop() {
         reth_tracing::init_test_tracing();
@@ -897,6 +1022,25 @@ mod test {
         )
     }
 
+    #[test]
+    fn discovered_peer_in_banned_network() {
+        let remote_socket: SocketAddr = "104.28.44.25:9000".parse().unwrap();
+        let remote_key = CombinedKey::generate_secp256k1();
+        let remote_enr = Enr::builder().tcp4(30303).build(&remote_key).unwrap();
+        let net = "104.28.0.0/16".parse().unwrap();
+
+        let discv5 = discv5_noop();
+        discv5.ban_network_until(net, None);
+        assert!(discv5.on_discovered_peer(&remote_enr, remote_socket).is_none());
+
+        discv5.unban_network(net);
+        assert!(discv5.on_discovered_peer(&remote_enr, remote_socket).is_some());
+
+        // expired bans are ignored
+        discv5.ban_network_until(net, Some(Instant::now() - Duration::from_secs(1)));
+        assert!(discv5.on_discovered_peer(&remote_enr, remote_socket).is_some());
+    }
+
     // Copied from sigp/discv5 with slight modification (U256 type)
     // <https://github.com/sigp/discv5/blob/master/src/kbucket/key.rs#L89-L101>
     #[expect(unreachable_pub)]
@@ -0,0 +1,294 @@
+//! Building and signing [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) node trees.
+//!
+//! The ENRs and links are the leaves of two merkle trees, the `enr-root` and `link-root` subtrees.
+//! Every entry is published as a TXT record below the base32 encoding of the abbreviated keccak256
+//! hash of its content, the signed root entry is published at the domain itself. The layout
+//! matches the trees built by geth's `devp2p dns` tooling.
+
+use crate::tree::{BranchEntry, LinkEntry, NodeEntry, TreeRootEntry};
+use alloy_primitives::keccak256;
+use data_encoding::BASE32_NOPAD;
+use enr::{Enr, EnrKey};
+use secp256k1::SecretKey;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+/// Number of bytes of the keccak256 hash that are used as subdomain of an entry.
+const HASH_ABBREV_LEN: usize = 16;
+
+/// Maximum number of children of a branch entry.
+///
+/// This keeps branch entries below 370 bytes, so that a TXT record fits into a single UDP DNS
+/// response.
+const MAX_CHILDREN: usize = 370 / (26 + 1);
+
+/// Maximum length of a single character string in a TXT record.
+const MAX_TXT_STRING_LEN: usize = 255;
+
+/// A signed node tree, ready to be published as DNS TXT records.
+#[derive(Debug, Clone)]
+pub struct DnsTree {
+    root: TreeRootEntry,
+    /// The TXT content of all subtree entries, keyed by their hash.
+    entries: BTreeMap<String, String>,
+    /// The public key of the key the root was signed with.
+    pubkey: <SecretKey as EnrKey>::PublicKey,
+}
+
+impl DnsTree {
+    /// Builds a tree of the given nodes and links and signs its root with the given key.
+    ///
+    /// If there are several records of the same node, only the one with the highest sequence
+    /// number is included.
+    pub fn build(
+        nodes: impl IntoIterator<Item = Enr<SecretKey>>,
+        links: impl IntoIterator<Item = LinkEntry>,
+        sequence_number: u64,
+        key: &SecretKey,
+    ) -> Self {
+        let mut latest = HashMap::<_, Enr<SecretKey>>::new();
+        for enr in nodes {
+            match latest.get(&enr.node_id()) {
+                Some(existing) if existing.seq() >= enr.seq() => {}
+                _ => {
+                    latest.insert(enr.node_id(), enr);
+                }
+            }
+        }
+        let mut nodes = latest.into_values().collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|enr| enr.node_id().raw());
+        let mut links = links.into_iter().map(|link| link.to_string()).collect::<Vec<_>>();
+        links.sort_unstable();
+        links.dedup();
+
+        let mut entries = BTreeMap::new();
+        let node_entries =
+            nodes.into_iter().map(|enr| NodeEntry { enr }.to_string()).collect::<Vec<_>>();
+        let enr_root = build_subtree(&mut entries, node_entries);
+        let enr_root = insert_entry(&mut entries, enr_root);
+        let link_root = build_subtree(&mut entries, links);
+        let link_root = insert_entry(&mut entries, link_root);
+
+        let mut root =
+            TreeRootEntry { enr_root, link_root, sequence_number, signature: Default::default() };
+        root.sign_recoverable(key);
+
+        Self { root, entries, pubkey: key.public() }
+    }
+
+    /// Returns the signed root entry.
+    pub const fn root(&self) -> &TreeRootEntry {
+        &self.root
+    }
+
+    /// Returns the link that clients use to sync the tree when published at the given domain.
+    pub fn link(&self, domain: impl Into<String>) -> LinkEntry {
+        LinkEntry { domain: domain.into(), pubkey: self.pubkey }
+    }
+
+    /// Returns the TXT records of the tree when published at the given domain, keyed by their
+    /// fully qualified domain name.
+    pub fn records(&self, domain: &str) -> BTreeMap<String, String> {
+        let domain = domain.trim_end_matches('.');
+        let mut records = self
+            .entries
+            .iter()
+            .map(|(hash, entry)| (format!("{hash}.{domain}"), entry.clone()))
+            .collect::<BTreeMap<_, _>>();
+        records.insert(domain.to_string(), self.root.to_string());
+        records
+    }
+
+    /// Returns the tree as zone file records for the given domain.
+    ///
+    /// Entries longer than 255 characters are split into multiple character strings, which
+    /// resolvers concatenate again.
+    pub fn to_zone_file(&self, domain: &str, ttl: u32) -> String {
+        let domain = domain.trim_end_matches('.');
+        let mut zone = format!(
+            "; EIP-1459 node tree, seq={}\n; {}\n",
+            self.root.sequence_number,
+            self.link(domain)
+        );
+        let records = std::iter::once((domain.to_string(), self.root.to_string())).chain(
+            self.entries.iter().map(|(hash, entry)| (format!("{hash}.{domain}"), entry.clone())),
+        );
+        for (name, txt) in records {
+            let _ = write!(zone, "{name}. {ttl} IN TXT");
+            for chunk in txt.as_bytes().chunks(MAX_TXT_STRING_LEN) {
+                // entries are ASCII, chunks are valid UTF-8
+                let _ = write!(zone, " \"{}\"", String::from_utf8_lossy(chunk));
+            }
+            zone.push('\n');
+        }
+        zone
+    }
+}
+
+/// Returns the subdomain an entry is published at.
+fn entry_hash(entry: &str) -> String {
+    BASE32_NOPAD.encode(&keccak256(entry.as_bytes())[..HASH_ABBREV_LEN])
+}
+
+/// Inserts the entry and returns its hash.
+fn insert_entry(entries: &mut BTreeMap<String, String>, entry: String) -> String {
+    let hash = entry_hash(&entry);
+    entries.insert(hash.clone(), entry);
+    hash
+}
+
+/// Builds the subtree of the given entries and returns its root entry.
+///
+/// All entries below the root are inserted.
+fn build_subtree(entries: &mut BTreeMap<String, String>, mut leaves: Vec<String>) -> String {
+    if leaves.len() == 1 {
+        return leaves.remove(0)
+    }
+    if leaves.len() <= MAX_CHILDREN {
+        let children = leaves.into_iter().map(|leaf| insert_entry(entries, leaf)).collect();
+        return BranchEntry { children }.to_string()
+    }
+
+    let subtrees = leaves
+        .chunks(MAX_CHILDREN)
+        .map(|chunk| build_subtree(entries, chunk.to_vec()))
+        .collect::<Vec<_>>();
+    build_subtree(entries, subtrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tree::DnsEntry, DnsDiscoveryEvent, DnsDiscoveryService, MapResolver};
+    use secp256k1::rand::thread_rng;
+    use std::{collections::HashSet, future::poll_fn, net::Ipv4Addr, sync::Arc, task::Poll};
+
+    fn random_enr(port: u16) -> Enr<SecretKey> {
+        let key = SecretKey::new(&mut thread_rng());
+        Enr::builder().ip4(Ipv4Addr::LOCALHOST).udp4(port).tcp4(port).build(&key).unwrap()
+    }
+
+    #[test]
+    fn build_tree_layout() {
+        let key = SecretKey::new(&mut thread_rng());
+        let nodes = (0..200).map(random_enr).collect::<Vec<_>>();
+        let tree = DnsTree::build(nodes.clone(), [], 7, &key);
+
+        assert!(tree.root().verify::<SecretKey>(&key.public()));
+        assert_eq!(tree.root().signature.len(), 65);
+        assert_eq!(tree.root().sequence_number, 7);
+
+        let mut leaves = HashSet::new();
+        for (hash, txt) in &tree.entries {
+            assert_eq!(&entry_hash(txt), hash);
+            match txt.parse::<DnsEntry<SecretKey>>().unwrap() {
+                DnsEntry::Branch(branch) => {
+                    assert!(branch.children.len() <= MAX_CHILDREN);
+                    // a branch only references published entries
+                    assert!(branch.children.iter().all(|child| tree.entries.contains_key(child)));
+                }
+                DnsEntry::Node(node) => {
+                    leaves.insert(node.enr.node_id());
+                }
+                _ => unreachable!(),
+            }
+        }
+        assert_eq!(leaves, nodes.iter().map(|enr| enr.node_id()).collect());
+
+        // the link root is an empty branch
+        assert_eq!(tree.entries[&tree.root().link_root], "enrtree-branch:");
+
+        // building is deterministic
+        let mut reversed = nodes;
+        reversed.reverse();
+        let rebuilt = DnsTree::build(reversed, [], 7, &key);
+        assert_eq!(rebuilt.entries, tree.entries);
+        assert_eq!(rebuilt.root().enr_root, tree.root().enr_root);
+    }
+
+    #[test]
+    fn build_tree_dedups_nodes() {
+        let key = SecretKey::new(&mut thread_rng());
+        let node_key = SecretKey::new(&mut thread_rng());
+        let old = Enr::builder().ip4(Ipv4Addr::LOCALHOST).tcp4(1).build(&node_key).unwrap();
+        let mut new = old.clone();
+        // bumps the sequence number
+        new.set_tcp4(2, &node_key).unwrap();
+
+        let tree = DnsTree::build([new.clone(), old.clone(), old], [], 1, &key);
+        assert_eq!(
+            tree.entries.get(&tree.root().enr_root),
+            Some(&NodeEntry { enr: new }.to_string())
+        );
+    }
+
+    #[test]
+    fn zone_file_splits_long_entries() {
+        let key = SecretKey::new(&mut thread_rng());
+        let link: LinkEntry =
+            "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@nodes.example.org"
+                .parse()
+                .unwrap();
+        // an ENR close to the size limit, its entry is longer than a single character string
+        let node_key = SecretKey::new(&mut thread_rng());
+        let enr = Enr::builder()
+            .ip4(Ipv4Addr::LOCALHOST)
+            .add_value("padding", &alloy_rlp::Bytes::from(vec![0xab; 150]))
+            .build(&node_key)
+            .unwrap();
+        let node_entry = NodeEntry { enr: enr.clone() }.to_string();
+        assert!(node_entry.len() > MAX_TXT_STRING_LEN);
+        let tree = DnsTree::build([enr], [link.clone()], 1, &key);
+
+        let zone = tree.to_zone_file("nodes.example.com.", 300);
+        let root_line = format!("nodes.example.com. 300 IN TXT \"{}\"", tree.root());
+        assert!(zone.lines().any(|line| line == root_line));
+        let link_line =
+            format!("{}.nodes.example.com. 300 IN TXT \"{link}\"", tree.root().link_root);
+        assert!(zone.lines().any(|line| line == link_line));
+
+        let node_line = zone
+            .lines()
+            .find(|line| line.starts_with(&format!("{}.", tree.root().enr_root)))
+            .unwrap();
+        let strings = node_line.split('"').skip(1).step_by(2).collect::<Vec<_>>();
+        assert_eq!(strings.len(), 2);
+        assert!(strings.iter().all(|s| s.len() <= MAX_TXT_STRING_LEN));
+        assert_eq!(strings.concat(), node_entry);
+
+        let records = tree.records("nodes.example.com");
+        assert_eq!(records.len(), 3);
+        assert_eq!(records["nodes.example.com"], tree.root().to_string());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sync_built_tree() {
+        reth_tracing::init_test_tracing();
+
+        let key = SecretKey::new(&mut thread_rng());
+        let nodes = (0..30).map(random_enr).collect::<Vec<_>>();
+        let tree = DnsTree::build(nodes.clone(), [], 1, &key);
+
+        let resolver = MapResolver::default();
+        for (name, txt) in tree.records("nodes.example.org") {
+            resolver.insert(name, txt);
+        }
+        let mut service = DnsDiscoveryService::new(Arc::new(resolver), Default::default());
+        service.sync_tree_with_link(tree.link("nodes.example.org"));
+
+        let mut discovered = HashSet::new();
+        while discovered.len() < nodes.len() {
+            let DnsDiscoveryEvent::Enr(enr) = poll_fn(|cx| service.poll(cx)).await;
+            discovered.insert(enr.node_id());
+        }
+        assert_eq!(discovered, nodes.iter().map(|enr| enr.node_id()).collect());
+
+        poll_fn(|cx| {
+            assert!(service.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+    }
+}
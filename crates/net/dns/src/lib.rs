@@ -19,6 +19,7 @@ use crate::{
     sync::{ResolveKind, SyncAction},
     tree::{DnsEntry, LinkEntry},
 };
+pub use builder::DnsTree;
 pub use config::DnsDiscoveryConfig;
 use enr::Enr;
 pub use error::ParseDnsEntryError;
@@ -48,6 +49,7 @@ use tokio_stream::{
 };
 use tracing::{debug, trace};
 
+pub mod builder;
 mod config;
 mod error;
 mod query;
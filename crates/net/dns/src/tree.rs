@@ -21,10 +21,10 @@ use crate::error::{
     ParseDnsEntryError::{FieldNotFound, UnknownEntry},
     ParseEntryResult,
 };
-use alloy_primitives::{hex, Bytes};
+use alloy_primitives::{hex, keccak256, Bytes};
 use data_encoding::{BASE32_NOPAD, BASE64URL_NOPAD};
 use enr::{Enr, EnrKey, EnrKeyUnambiguous, EnrPublicKey, Error as EnrError};
-use secp256k1::SecretKey;
+use secp256k1::{SecretKey, SECP256K1};
 #[cfg(feature = "serde")]
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 use std::{
@@ -141,6 +141,16 @@ impl TreeRootEntry {
         Ok(())
     }
 
+    /// Signs the content with the given key, using the 65 byte recoverable signature that is
+    /// published by other EIP-1459 implementations.
+    pub fn sign_recoverable(&mut self, key: &SecretKey) {
+        let msg = secp256k1::Message::from_digest(keccak256(self.content().as_bytes()).0);
+        let (rec, sig) = SECP256K1.sign_ecdsa_recoverable(&msg, key).serialize_compact();
+        let mut signature = sig.to_vec();
+        signature.push(i32::from(rec) as u8);
+        self.signature = signature.into();
+    }
+
     /// Verify the signature of the record.
     #[must_use]
     pub fn verify<K: EnrKey>(&self, pubkey: &K::PublicKey) -> bool {
@@ -210,8 +220,13 @@ impl BranchEntry {
             Ok(hash.to_string())
         }
 
+        let input = input.trim();
+        if input.is_empty() {
+            // the link subtree of a tree without links
+            return Ok(Self { children: Vec::new() })
+        }
         let children =
-            input.trim().split(',').map(ensure_valid_hash).collect::<ParseEntryResult<Vec<_>>>()?;
+            input.split(',').map(ensure_valid_hash).collect::<ParseEntryResult<Vec<_>>>()?;
         Ok(Self { children })
     }
 }
@@ -424,6 +439,14 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn parse_empty_branch_entry() {
+        let s = "enrtree-branch:";
+        let entry = s.parse::<BranchEntry>().unwrap();
+        assert!(entry.children.is_empty());
+        assert_eq!(entry.to_string(), s);
+    }
+
     #[test]
     fn parse_link_entry() {
         let s = "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@nodes.example.org";
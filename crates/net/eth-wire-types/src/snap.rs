@@ -410,6 +410,21 @@ impl SnapProtocolMessage {
         }
     }
 
+    /// Returns the length of the encoded message, see [`Self::encode`].
+    pub fn length(&self) -> usize {
+        // message ID byte
+        1 + match self {
+            Self::GetAccountRange(msg) => msg.length(),
+            Self::AccountRange(msg) => msg.length(),
+            Self::GetStorageRanges(msg) => msg.length(),
+            Self::StorageRanges(msg) => msg.length(),
+            Self::GetByteCodes(msg) => msg.length(),
+            Self::ByteCodes(msg) => msg.length(),
+            Self::GetBlockAccessLists(msg) => msg.length(),
+            Self::BlockAccessLists(msg) => msg.length(),
+        }
+    }
+
     /// Encode the message to bytes
     pub fn encode(&self) -> Bytes {
         let mut buf = Vec::new();
@@ -580,6 +595,7 @@ mod tests {
     // Helper function to test roundtrip encoding/decoding
     fn test_roundtrip(original: SnapProtocolMessage) {
         let encoded = original.encode();
+        assert_eq!(encoded.len(), original.length());
 
         // Verify the first byte matches the expected message ID
         assert_eq!(encoded[0], original.message_id() as u8);
@@ -23,6 +23,7 @@ pub mod multiplex;
 mod p2pstream;
 mod pinger;
 pub mod protocol;
+pub mod recorder;
 
 /// Handshake logic
 pub mod handshake;
@@ -3,6 +3,7 @@ use crate::{
     disconnect::CanDisconnect,
     errors::{P2PHandshakeError, P2PStreamError},
     pinger::{Pinger, PingerEvent},
+    recorder::{RecordDirection, SessionRecorder},
     DisconnectReason, HelloMessage, HelloMessageWithProtocols,
 };
 use alloy_primitives::{
@@ -295,6 +296,9 @@ pub struct P2PStream<S> {
     /// Whether a queued p2p control message needs to be flushed even if no subprotocol messages
     /// are sent by the caller.
     needs_control_flush: bool,
+
+    /// Records the subprotocol messages of this stream, if enabled.
+    recorder: Option<SessionRecorder>,
 }
 
 impl<S> P2PStream<S> {
@@ -314,6 +318,7 @@ impl<S> P2PStream<S> {
             disconnecting: false,
             needs_flush: false,
             needs_control_flush: false,
+            recorder: None,
         }
     }
 
@@ -340,6 +345,17 @@ impl<S> P2PStream<S> {
         &self.shared_capabilities
     }
 
+    /// Records all subprotocol messages sent and received on this stream with the given
+    /// [`SessionRecorder`].
+    pub fn set_recorder(&mut self, recorder: SessionRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Stops recording and returns the [`SessionRecorder`], if one is set.
+    pub const fn take_recorder(&mut self) -> Option<SessionRecorder> {
+        self.recorder.take()
+    }
+
     /// Returns `true` if the stream has outgoing capacity.
     fn has_outgoing_capacity(&self) -> bool {
         self.outgoing_messages.len() < self.outgoing_message_buffer_capacity
@@ -582,6 +598,10 @@ where
                     //
                     decompress_buf[0] = bytes[0] - MAX_RESERVED_MESSAGE_ID - 1;
 
+                    if let Some(recorder) = &mut this.recorder {
+                        recorder.record(RecordDirection::Inbound, &decompress_buf);
+                    }
+
                     return Poll::Ready(Some(Ok(decompress_buf)))
                 }
             }
@@ -647,6 +667,10 @@ where
 
         let this = self.project();
 
+        if let Some(recorder) = this.recorder {
+            recorder.record(RecordDirection::Outbound, &item);
+        }
+
         // all messages sent in this stream are subprotocol messages, so we need to switch the
         // message id based on the offset
         let compressed = compress_frame(
@@ -0,0 +1,786 @@
+//! Opt-in recording of session traffic and replay of recordings.
+//!
+//! A [`SessionRecorder`] is attached to a [`P2PStream`](crate::P2PStream) via
+//! [`P2PStream::set_recorder`](crate::P2PStream::set_recorder) and writes every subprotocol
+//! message that passes through the stream, in either direction, to a file. Since all capabilities
+//! are multiplexed over the same [`P2PStream`](crate::P2PStream), this covers `eth`, `snap` and
+//! any additional `RLPx` subprotocols alike.
+//!
+//! A recording starts with a header, followed by one tab separated line per message:
+//!
+//! ```text
+//! # peer <peer id>
+//! # capabilities eth/68 snap/2
+//! <unix micros>	<in|out>	<capability>	<message>	<payload>	<decoded>
+//! ```
+//!
+//! The payload is the hex encoded, uncompressed message including the message id local to its
+//! capability, and the decoded column is a human readable rendering of the message produced by a
+//! [`MessageDecoder`], see [`MessageDecoder::eth`].
+//!
+//! A [`Recording`] parses such a file. To replay a session, the remote end of a connection sends
+//! the messages returned by [`Recording::replay_messages`] to the session handler under test, so
+//! that it sees the exact same message sequence the peer sent, without any timing dependencies.
+//! For tests of the stream layers only, a [`ReplayStream`] feeds the inbound messages directly
+//! into a [`P2PStream`](crate::P2PStream).
+
+use crate::{
+    capability::{SharedCapabilities, SharedCapability},
+    Capability, NetworkPrimitives, ProtocolMessage, MAX_RESERVED_MESSAGE_ID,
+};
+use alloy_primitives::{hex, Bytes};
+use bytes::BytesMut;
+use futures::{Sink, Stream};
+use reth_eth_wire_types::{
+    snap::{SnapMessageId, SnapProtocolMessage, SnapVersion},
+    EthMessageID,
+};
+use reth_network_peers::PeerId;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError},
+    task::{Context, Poll},
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::debug;
+
+/// Names and renders recorded messages.
+///
+/// Messages are named before they are filtered and rendered on the recorder's writer thread, so
+/// that filtered out messages are never decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageDecoder {
+    /// Returns the name of the message type, for example `BlockBodies`, from the message id local
+    /// to the capability.
+    pub name: fn(&SharedCapability, u8) -> Option<String>,
+    /// Renders the message, starting with the message id local to the capability, into a human
+    /// readable string. Returns `None` if the message can't be decoded.
+    pub render: fn(&SharedCapability, &[u8]) -> Option<String>,
+}
+
+impl MessageDecoder {
+    /// The default decoder which decodes `eth` and `snap` messages.
+    pub fn eth<N: NetworkPrimitives>() -> Self {
+        Self { name: message_name, render: render_message::<N> }
+    }
+}
+
+/// Returns the name of an `eth` or `snap` message.
+fn message_name(capability: &SharedCapability, id: u8) -> Option<String> {
+    if capability.is_eth() {
+        return EthMessageID::try_from(id as usize).ok().map(|id| format!("{id:?}"))
+    }
+
+    if capability.name() == "snap" && capability.version() == SnapVersion::V2 as u8 {
+        let id = match id {
+            0x00 => SnapMessageId::GetAccountRange,
+            0x01 => SnapMessageId::AccountRange,
+            0x02 => SnapMessageId::GetStorageRanges,
+            0x03 => SnapMessageId::StorageRanges,
+            0x04 => SnapMessageId::GetByteCodes,
+            0x05 => SnapMessageId::ByteCodes,
+            0x08 => SnapMessageId::GetBlockAccessLists,
+            0x09 => SnapMessageId::BlockAccessLists,
+            _ => return None,
+        };
+        return Some(format!("{id:?}"))
+    }
+
+    None
+}
+
+/// Renders an `eth` or `snap` message.
+fn render_message<N: NetworkPrimitives>(
+    capability: &SharedCapability,
+    payload: &[u8],
+) -> Option<String> {
+    if let Some(version) = capability.eth_version() {
+        let msg = ProtocolMessage::<N>::decode_message(version, &mut &payload[..]).ok()?;
+        return Some(format!("{:?}", msg.message))
+    }
+
+    if capability.name() == "snap" && capability.version() == SnapVersion::V2 as u8 {
+        let msg = SnapProtocolMessage::decode_versioned(SnapVersion::V2, payload).ok()?;
+        return Some(format!("{msg:?}"))
+    }
+
+    None
+}
+
+/// The direction of a recorded message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordDirection {
+    /// Message received from the peer.
+    Inbound,
+    /// Message sent to the peer.
+    Outbound,
+}
+
+impl RecordDirection {
+    /// Returns the representation of the direction in a recording.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Inbound => "in",
+            Self::Outbound => "out",
+        }
+    }
+}
+
+impl fmt::Display for RecordDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Configures which sessions are recorded and where recordings are written to.
+///
+/// Every recorded session is written to its own file in the configured directory, named after the
+/// peer id and the time the session was established.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecorderConfig {
+    /// Directory the recordings are written to.
+    dir: PathBuf,
+    /// Peers to record, all peers if empty.
+    peers: HashSet<PeerId>,
+    /// Messages to record, all messages if empty.
+    ///
+    /// Entries are either a capability name, e.g. `snap`, or a capability name and message name,
+    /// e.g. `eth/BlockBodies`.
+    messages: HashSet<String>,
+}
+
+impl RecorderConfig {
+    /// Creates a new config that records all messages of all sessions into the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), peers: Default::default(), messages: Default::default() }
+    }
+
+    /// Only records sessions with the given peers.
+    pub fn with_peers(mut self, peers: impl IntoIterator<Item = PeerId>) -> Self {
+        self.peers.extend(peers);
+        self
+    }
+
+    /// Only records the given messages.
+    ///
+    /// Entries are either a capability name, e.g. `snap`, to record all messages of the
+    /// capability, or a capability name and message name, e.g. `eth/BlockBodies`.
+    pub fn with_messages(mut self, messages: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.messages.extend(messages.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns the directory the recordings are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns `true` if sessions with the given peer are recorded.
+    pub fn records_peer(&self, peer_id: &PeerId) -> bool {
+        self.peers.is_empty() || self.peers.contains(peer_id)
+    }
+
+    /// Creates the recording file for a new session with the given peer and returns the
+    /// [`SessionRecorder`] writing to it.
+    ///
+    /// Returns `Ok(None)` if sessions with this peer are not recorded.
+    pub fn session_recorder(
+        &self,
+        peer_id: PeerId,
+        capabilities: SharedCapabilities,
+        decoder: MessageDecoder,
+    ) -> io::Result<Option<SessionRecorder>> {
+        if !self.records_peer(&peer_id) {
+            return Ok(None)
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+        let file = File::create(self.dir.join(format!("{peer_id:x}-{}.log", unix_micros())))?;
+        SessionRecorder::new(file, peer_id, capabilities, decoder)
+            .map(|recorder| Some(recorder.with_messages(self.messages.clone())))
+    }
+}
+
+/// Records the subprotocol messages of a single session, see the [module docs](self).
+///
+/// Messages are filtered on the session's task and handed to a dedicated writer thread, which
+/// renders and writes them and flushes the recording every [`RECORDING_FLUSH_INTERVAL`]. If the
+/// writer falls behind by more than [`RECORDING_CHANNEL_CAPACITY`] messages, messages are dropped
+/// from the recording instead of slowing down the session.
+///
+/// Write errors are not propagated to the session; the writer logs the error and stops recording
+/// instead.
+pub struct SessionRecorder {
+    /// The shared capabilities of the session, used to resolve message ids.
+    capabilities: SharedCapabilities,
+    /// Messages to record, all messages if empty.
+    messages: HashSet<String>,
+    /// Names the recorded messages.
+    decoder: MessageDecoder,
+    /// Sends messages to the writer thread, `None` once the writer stopped.
+    records: Option<SyncSender<Record>>,
+    /// The writer thread.
+    writer: Option<JoinHandle<()>>,
+    /// Number of messages dropped because the writer fell behind.
+    dropped: u64,
+}
+
+/// Interval in which the writer thread of a [`SessionRecorder`] flushes the recording.
+pub const RECORDING_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of messages a [`SessionRecorder`] buffers for its writer thread.
+pub const RECORDING_CHANNEL_CAPACITY: usize = 1024;
+
+/// A message sent to the writer thread of a [`SessionRecorder`].
+#[derive(Debug)]
+struct Record {
+    timestamp: u64,
+    direction: RecordDirection,
+    capability: SharedCapability,
+    name: String,
+    /// The message including the message id local to the capability.
+    payload: Vec<u8>,
+}
+
+impl SessionRecorder {
+    /// Creates a new recorder that writes to the given writer.
+    ///
+    /// The recording header is written by the writer thread spawned here.
+    pub fn new(
+        writer: impl Write + Send + 'static,
+        peer_id: PeerId,
+        capabilities: SharedCapabilities,
+        decoder: MessageDecoder,
+    ) -> io::Result<Self> {
+        let mut header = format!("# peer {peer_id:x}\n# capabilities");
+        for cap in capabilities.iter_caps() {
+            header.push_str(&format!(" {}/{}", cap.name(), cap.version()));
+        }
+        header.push('\n');
+
+        let (records, rx) = mpsc::sync_channel(RECORDING_CHANNEL_CAPACITY);
+        let render = decoder.render;
+        let writer =
+            std::thread::Builder::new().name("session-recorder".to_string()).spawn(move || {
+                if let Err(err) = write_recording(writer, header, rx, render) {
+                    debug!(
+                        target: "net::recorder",
+                        %err,
+                        "failed to write recording, stop recording"
+                    );
+                }
+            })?;
+
+        Ok(Self {
+            capabilities,
+            messages: Default::default(),
+            decoder,
+            records: Some(records),
+            writer: Some(writer),
+            dropped: 0,
+        })
+    }
+
+    /// Only records the given messages, see [`RecorderConfig::with_messages`].
+    pub fn with_messages(mut self, messages: HashSet<String>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Records a message.
+    ///
+    /// The message id is the first byte of the message and relative to the first shared
+    /// capability, as it is yielded and accepted by the [`P2PStream`](crate::P2PStream).
+    pub fn record(&mut self, direction: RecordDirection, msg: &[u8]) {
+        let Some(records) = &self.records else { return };
+        let Some((&id, body)) = msg.split_first() else { return };
+        let Some(cap) = self.capabilities.find_by_relative_offset(id) else { return };
+
+        // the id local to the capability
+        let id = id - cap.relative_message_id_offset();
+        let name = (self.decoder.name)(cap, id).unwrap_or_else(|| id.to_string());
+        if !self.messages.is_empty() &&
+            !self.messages.contains(cap.name()) &&
+            !self.messages.contains(&format!("{}/{name}", cap.name()))
+        {
+            return
+        }
+
+        let mut payload = Vec::with_capacity(msg.len());
+        payload.push(id);
+        payload.extend_from_slice(body);
+        let record =
+            Record { timestamp: unix_micros(), direction, capability: cap.clone(), name, payload };
+        match records.try_send(record) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped += 1;
+                if self.dropped.is_power_of_two() {
+                    debug!(
+                        target: "net::recorder",
+                        dropped=%self.dropped,
+                        "recording falls behind, dropping messages"
+                    );
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => self.records = None,
+        }
+    }
+
+    /// Stops recording and waits until the writer thread wrote and flushed all recorded
+    /// messages.
+    ///
+    /// Dropping the recorder also stops recording, without waiting for the writer thread.
+    pub fn finish(mut self) {
+        self.records = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Writes the header and all messages received on the channel, flushing the writer every
+/// [`RECORDING_FLUSH_INTERVAL`] and once the channel is closed.
+fn write_recording(
+    writer: impl Write,
+    header: String,
+    records: Receiver<Record>,
+    render: fn(&SharedCapability, &[u8]) -> Option<String>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writer.write_all(header.as_bytes())?;
+
+    let mut last_flush = Instant::now();
+    loop {
+        let timeout = RECORDING_FLUSH_INTERVAL.saturating_sub(last_flush.elapsed());
+        match records.recv_timeout(timeout) {
+            Ok(Record { timestamp, direction, capability, name, payload }) => {
+                writeln!(
+                    writer,
+                    "{timestamp}\t{direction}\t{}/{}\t{name}\t{}\t{}",
+                    capability.name(),
+                    capability.version(),
+                    hex::encode_prefixed(&payload),
+                    render(&capability, &payload).unwrap_or_default(),
+                )?;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return writer.flush(),
+        }
+
+        if last_flush.elapsed() >= RECORDING_FLUSH_INTERVAL {
+            writer.flush()?;
+            last_flush = Instant::now();
+        }
+    }
+}
+
+impl fmt::Debug for SessionRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionRecorder")
+            .field("capabilities", &self.capabilities)
+            .field("messages", &self.messages)
+            .field("recording", &self.records.is_some())
+            .field("dropped", &self.dropped)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A single message of a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMessage {
+    /// Time the message was recorded at, in microseconds since the unix epoch.
+    pub timestamp: u64,
+    /// The direction of the message.
+    pub direction: RecordDirection,
+    /// The capability of the message.
+    pub capability: Capability,
+    /// The name of the message type, or the message id if it couldn't be decoded.
+    pub name: String,
+    /// The message including the message id local to the capability.
+    pub payload: Bytes,
+}
+
+/// A parsed recording of a session, see the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// The peer the session was established with.
+    pub peer_id: Option<PeerId>,
+    /// The shared capabilities of the session.
+    pub capabilities: Vec<Capability>,
+    /// The recorded messages in recording order.
+    pub messages: Vec<RecordedMessage>,
+}
+
+impl Recording {
+    /// Reads the recording at the given path.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(BufReader::new(File::open(path)?))
+    }
+
+    /// Parses a recording.
+    pub fn parse(reader: impl BufRead) -> io::Result<Self> {
+        let mut recording = Self::default();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let invalid = |msg: &str| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {msg}", idx + 1))
+            };
+
+            if line.trim().is_empty() {
+                continue
+            }
+
+            if let Some(header) = line.strip_prefix('#') {
+                let mut parts = header.split_whitespace();
+                match parts.next() {
+                    Some("peer") => {
+                        let peer_id = parts.next().ok_or_else(|| invalid("missing peer id"))?;
+                        recording.peer_id =
+                            Some(peer_id.parse().map_err(|_| invalid("invalid peer id"))?);
+                    }
+                    Some("capabilities") => {
+                        recording.capabilities = parts
+                            .map(|cap| {
+                                parse_capability(cap).ok_or_else(|| invalid("invalid capability"))
+                            })
+                            .collect::<Result<_, _>>()?;
+                    }
+                    _ => {}
+                }
+                continue
+            }
+
+            let mut fields = line.splitn(6, '\t');
+            let mut next =
+                |field: &str| fields.next().ok_or_else(|| invalid(&format!("missing {field}")));
+
+            let timestamp = next("timestamp")?.parse().map_err(|_| invalid("invalid timestamp"))?;
+            let direction = match next("direction")? {
+                "in" => RecordDirection::Inbound,
+                "out" => RecordDirection::Outbound,
+                _ => return Err(invalid("invalid direction")),
+            };
+            let capability = next("capability")?;
+            let capability =
+                parse_capability(capability).ok_or_else(|| invalid("invalid capability"))?;
+            let name = next("message")?.to_string();
+            let payload: Bytes =
+                next("payload")?.parse().map_err(|_| invalid("invalid payload"))?;
+            if payload.is_empty() {
+                return Err(invalid("empty payload"))
+            }
+
+            recording.messages.push(RecordedMessage {
+                timestamp,
+                direction,
+                capability,
+                name,
+                payload,
+            });
+        }
+
+        Ok(recording)
+    }
+
+    /// Returns the messages received from the peer.
+    pub fn inbound(&self) -> impl Iterator<Item = &RecordedMessage> {
+        self.messages.iter().filter(|msg| msg.direction == RecordDirection::Inbound)
+    }
+
+    /// Returns the messages sent to the peer.
+    pub fn outbound(&self) -> impl Iterator<Item = &RecordedMessage> {
+        self.messages.iter().filter(|msg| msg.direction == RecordDirection::Outbound)
+    }
+
+    /// Returns the inbound messages in recording order, with message ids relative to the given
+    /// shared capabilities.
+    ///
+    /// These are the messages as accepted by the [`P2PStream`](crate::P2PStream) sink, so sending
+    /// them from the remote end of a connection replays the recorded session to the local session
+    /// handler.
+    ///
+    /// Returns an error if a recorded message belongs to a capability that is not shared.
+    pub fn replay_messages(&self, capabilities: &SharedCapabilities) -> io::Result<Vec<Bytes>> {
+        self.inbound()
+            .map(|msg| {
+                let not_shared = || {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{} message {} is not shared", msg.capability, msg.name),
+                    )
+                };
+                let (&id, body) = msg.payload.split_first().ok_or_else(not_shared)?;
+                let id =
+                    capabilities.relative_message_id(&msg.capability, id).ok_or_else(not_shared)?;
+
+                let mut replayed = Vec::with_capacity(msg.payload.len());
+                replayed.push(id);
+                replayed.extend_from_slice(body);
+                Ok(replayed.into())
+            })
+            .collect()
+    }
+}
+
+/// A transport that replays the inbound messages of a [`Recording`].
+///
+/// This replays the messages below the [`P2PStream`](crate::P2PStream), to replay them to a
+/// session handler see [`Recording::replay_messages`].
+///
+/// This is meant to be wrapped in a [`P2PStream`](crate::P2PStream) with the shared capabilities
+/// of the session under test: the stream yields the recorded inbound messages as snappy compressed
+/// `RLPx` frames in recording order, and ends once all of them have been consumed. Frames sent to
+/// the stream are collected and can be inspected with [`ReplayStream::sent`].
+#[derive(Debug)]
+pub struct ReplayStream {
+    /// The frames that are yet to be replayed.
+    inbound: VecDeque<BytesMut>,
+    /// The frames sent to the stream.
+    sent: Vec<Bytes>,
+}
+
+impl ReplayStream {
+    /// Creates a new stream replaying the inbound messages of the recording.
+    ///
+    /// Returns an error if a recorded message belongs to a capability that is not shared.
+    pub fn new(recording: &Recording, capabilities: &SharedCapabilities) -> io::Result<Self> {
+        let mut encoder = snap::raw::Encoder::new();
+        let inbound = recording
+            .replay_messages(capabilities)?
+            .into_iter()
+            .map(|msg| -> io::Result<BytesMut> {
+                let mut frame = BytesMut::from(&[msg[0] + MAX_RESERVED_MESSAGE_ID + 1][..]);
+                frame.extend_from_slice(&encoder.compress_vec(&msg[1..])?);
+                Ok(frame)
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self { inbound, sent: Vec::new() })
+    }
+
+    /// Returns the number of recorded messages that are yet to be replayed.
+    pub fn remaining(&self) -> usize {
+        self.inbound.len()
+    }
+
+    /// Returns the snappy compressed `RLPx` frames sent to the stream.
+    pub fn sent(&self) -> &[Bytes] {
+        &self.sent
+    }
+}
+
+impl Stream for ReplayStream {
+    type Item = io::Result<BytesMut>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().inbound.pop_front().map(Ok))
+    }
+}
+
+impl Sink<Bytes> for ReplayStream {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Bytes) -> Result<(), Self::Error> {
+        self.get_mut().sent.push(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Parses a capability in the `<name>/<version>` form.
+fn parse_capability(s: &str) -> Option<Capability> {
+    let (name, version) = s.split_once('/')?;
+    Some(Capability::new(name.to_string(), version.parse().ok()?))
+}
+
+/// Returns the current time in microseconds since the unix epoch.
+fn unix_micros() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EthMessage, EthNetworkPrimitives, EthStream, EthVersion, P2PStream};
+    use alloy_primitives::B256;
+    use futures::{SinkExt, StreamExt};
+    use reth_eth_wire_types::{message::RequestPair, BlockBodies, GetBlockBodies};
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that can be read back after it has been handed to a recorder.
+    #[derive(Debug, Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuf {
+        fn recording(&self) -> Recording {
+            Recording::parse(&self.0.lock().unwrap()[..]).unwrap()
+        }
+    }
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn shared_capabilities() -> SharedCapabilities {
+        SharedCapabilities::try_new(vec![EthVersion::Eth68.into()], vec![EthVersion::Eth68.into()])
+            .unwrap()
+    }
+
+    fn recorder(buf: &SharedBuf) -> SessionRecorder {
+        SessionRecorder::new(
+            buf.clone(),
+            PeerId::with_last_byte(1),
+            shared_capabilities(),
+            MessageDecoder::eth::<EthNetworkPrimitives>(),
+        )
+        .unwrap()
+    }
+
+    fn get_block_bodies() -> EthMessage {
+        EthMessage::GetBlockBodies(RequestPair {
+            request_id: 1,
+            message: GetBlockBodies(vec![B256::with_last_byte(1)]),
+        })
+    }
+
+    fn block_bodies() -> EthMessage {
+        EthMessage::BlockBodies(RequestPair { request_id: 1, message: BlockBodies(vec![]) })
+    }
+
+    fn encode(msg: EthMessage) -> Vec<u8> {
+        alloy_rlp::encode(ProtocolMessage::from(msg))
+    }
+
+    #[test]
+    fn record_and_parse() {
+        let buf = SharedBuf::default();
+        let mut recorder = recorder(&buf);
+        recorder.record(RecordDirection::Outbound, &encode(get_block_bodies()));
+        recorder.record(RecordDirection::Inbound, &encode(block_bodies()));
+        recorder.finish();
+
+        let recording = buf.recording();
+        assert!(recording.peer_id.is_some());
+        assert_eq!(recording.capabilities, vec![Capability::eth(EthVersion::Eth68)]);
+        assert_eq!(recording.messages.len(), 2);
+
+        let request = &recording.messages[0];
+        assert_eq!(request.direction, RecordDirection::Outbound);
+        assert_eq!(request.capability, Capability::eth(EthVersion::Eth68));
+        assert_eq!(request.name, "GetBlockBodies");
+        assert_eq!(request.payload, Bytes::from(encode(get_block_bodies())));
+
+        let response = recording.inbound().next().unwrap();
+        assert_eq!(response.name, "BlockBodies");
+        assert_eq!(response.payload, Bytes::from(encode(block_bodies())));
+        assert!(response.timestamp >= request.timestamp);
+    }
+
+    #[test]
+    fn record_filtered_messages() {
+        let buf = SharedBuf::default();
+        let mut recorder = recorder(&buf).with_messages(HashSet::from(["eth/BlockBodies".into()]));
+        recorder.record(RecordDirection::Outbound, &encode(get_block_bodies()));
+        recorder.record(RecordDirection::Inbound, &encode(block_bodies()));
+        recorder.finish();
+
+        let recording = buf.recording();
+        assert_eq!(recording.messages.len(), 1);
+        assert_eq!(recording.messages[0].name, "BlockBodies");
+
+        let config = RecorderConfig::new("recordings").with_peers([PeerId::ZERO]);
+        assert!(config.records_peer(&PeerId::ZERO));
+        assert!(!config.records_peer(&PeerId::with_last_byte(1)));
+    }
+
+    #[test]
+    fn recorder_flushes_periodically() {
+        let buf = SharedBuf::default();
+        let mut recorder = recorder(&buf);
+        recorder.record(RecordDirection::Inbound, &encode(block_bodies()));
+
+        // the message is written without finishing the recording
+        let start = Instant::now();
+        while buf.recording().messages.is_empty() {
+            assert!(start.elapsed() < RECORDING_FLUSH_INTERVAL * 10, "recording not flushed");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(buf.recording().messages[0].name, "BlockBodies");
+    }
+
+    #[test]
+    fn replay_messages() {
+        let buf = SharedBuf::default();
+        let mut recorder = recorder(&buf);
+        recorder.record(RecordDirection::Inbound, &encode(get_block_bodies()));
+        recorder.record(RecordDirection::Outbound, &encode(block_bodies()));
+        recorder.finish();
+
+        // relative to the shared capabilities, the eth message ids are unchanged
+        let messages = buf.recording().replay_messages(&shared_capabilities()).unwrap();
+        assert_eq!(messages, vec![Bytes::from(encode(get_block_bodies()))]);
+    }
+
+    #[test]
+    fn parse_invalid_recording() {
+        let err =
+            Recording::parse(&b"# peer 01\n1\tsideways\teth/68\tStatus\t0x00\t\n"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn replay_recording() {
+        let buf = SharedBuf::default();
+        let mut recorder = recorder(&buf);
+        recorder.record(RecordDirection::Inbound, &encode(get_block_bodies()));
+        recorder.record(RecordDirection::Outbound, &encode(block_bodies()));
+        recorder.finish();
+        let recording = buf.recording();
+
+        // replay the recording into a stream that is recorded again
+        let replay = ReplayStream::new(&recording, &shared_capabilities()).unwrap();
+        let mut p2p = P2PStream::new(replay, shared_capabilities());
+        let replay_buf = SharedBuf::default();
+        p2p.set_recorder(recorder(&replay_buf));
+        let mut eth = EthStream::<_, EthNetworkPrimitives>::new(EthVersion::Eth68, p2p);
+
+        assert_eq!(eth.next().await.unwrap().unwrap(), get_block_bodies());
+        eth.send(block_bodies()).await.unwrap();
+        assert!(eth.next().await.is_none());
+        assert_eq!(eth.inner().inner().sent().len(), 1);
+        eth.inner_mut().take_recorder().unwrap().finish();
+
+        let replayed = replay_buf.recording();
+        assert_eq!(replayed.messages.len(), recording.messages.len());
+        for (replayed, recorded) in replayed.messages.iter().zip(&recording.messages) {
+            assert_eq!(replayed.direction, recorded.direction);
+            assert_eq!(replayed.name, recorded.name);
+            assert_eq!(replayed.payload, recorded.payload);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-//! Helpers for resolving the external IP.
+//! Helpers for resolving the external IP and mapping ports on NAT gateways.
 //!
 //! ## Feature Flags
 //!
@@ -13,8 +13,12 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod net_if;
+pub mod port_mapping;
 
 pub use net_if::{NetInterfaceError, DEFAULT_NET_IF_NAME};
+pub use port_mapping::{
+    ExternalEndpoint, PortMapper, PortMapperHandle, PortMappingConfig, PortMappingProtocol,
+};
 
 use std::{
     fmt,
@@ -44,8 +48,12 @@ pub enum NatResolver {
     /// Resolve with any available resolver.
     #[default]
     Any,
-    /// Resolve external IP via `UPnP`.
+    /// Resolve external IP via `UPnP` and map the ports on the gateway.
     Upnp,
+    /// Resolve external IP via `NAT-PMP` and map the ports on the gateway.
+    NatPmp,
+    /// Map the ports on the gateway via `PCP`, which also reports the external IP.
+    Pcp,
     /// Resolve external IP via a network request.
     PublicIp,
     /// Use the given [`IpAddr`]
@@ -84,6 +92,16 @@ impl NatResolver {
             _ => None,
         }
     }
+
+    /// Returns the protocol used to map the ports on the gateway, if any.
+    pub const fn port_mapping_protocol(&self) -> Option<PortMappingProtocol> {
+        match self {
+            Self::Upnp => Some(PortMappingProtocol::Upnp),
+            Self::NatPmp => Some(PortMappingProtocol::NatPmp),
+            Self::Pcp => Some(PortMappingProtocol::Pcp),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for NatResolver {
@@ -91,6 +109,8 @@ impl fmt::Display for NatResolver {
         match self {
             Self::Any => f.write_str("any"),
             Self::Upnp => f.write_str("upnp"),
+            Self::NatPmp => f.write_str("natpmp"),
+            Self::Pcp => f.write_str("pcp"),
             Self::PublicIp => f.write_str("publicip"),
             Self::ExternalIp(ip) => write!(f, "extip:{ip}"),
             Self::ExternalAddr(domain) => write!(f, "extaddr:{domain}"),
@@ -118,6 +138,8 @@ impl FromStr for NatResolver {
         let r = match s {
             "any" => Self::Any,
             "upnp" => Self::Upnp,
+            "natpmp" | "nat-pmp" => Self::NatPmp,
+            "pcp" => Self::Pcp,
             "none" => Self::None,
             "publicip" | "public-ip" => Self::PublicIp,
             "netif" => Self::NetIf,
@@ -221,7 +243,9 @@ pub async fn external_ip() -> Option<IpAddr> {
 /// Given a [`NatResolver`] attempts to produce an IP address (best effort).
 pub async fn external_addr_with(resolver: NatResolver) -> Option<IpAddr> {
     match resolver {
-        NatResolver::Any | NatResolver::Upnp | NatResolver::PublicIp => resolve_external_ip().await,
+        NatResolver::Any | NatResolver::PublicIp | NatResolver::Pcp => resolve_external_ip().await,
+        NatResolver::Upnp => resolve_gateway_external_ip(PortMappingProtocol::Upnp).await,
+        NatResolver::NatPmp => resolve_gateway_external_ip(PortMappingProtocol::NatPmp).await,
         NatResolver::ExternalIp(ip) => Some(ip),
         NatResolver::NetIf => resolve_net_if_ip(DEFAULT_NET_IF_NAME)
             .inspect_err(|err| {
@@ -242,6 +266,21 @@ pub async fn external_addr_with(resolver: NatResolver) -> Option<IpAddr> {
     }
 }
 
+/// Asks the gateway for its external IP, falling back to the public IP APIs.
+async fn resolve_gateway_external_ip(protocol: PortMappingProtocol) -> Option<IpAddr> {
+    let res = match port_mapping::Gateway::discover(protocol, None).await {
+        Ok(gateway) => gateway.external_ip().await,
+        Err(err) => Err(err),
+    };
+    match res {
+        Ok(ip) if !ip.is_unspecified() => Some(ip),
+        res => {
+            debug!(target: "net::nat", %protocol, ?res, "Failed to resolve external IP");
+            resolve_external_ip().await
+        }
+    }
+}
+
 async fn resolve_external_ip() -> Option<IpAddr> {
     let futures = EXTERNAL_IP_APIS.iter().copied().map(resolve_external_ip_url_res).map(Box::pin);
     futures_util::future::select_ok(futures)
@@ -309,6 +348,9 @@ mod tests {
     fn test_from_str() {
         assert_eq!(NatResolver::Any, "any".parse().unwrap());
         assert_eq!(NatResolver::None, "none".parse().unwrap());
+        assert_eq!(NatResolver::NatPmp, "natpmp".parse().unwrap());
+        assert_eq!(NatResolver::NatPmp.to_string(), "natpmp");
+        assert_eq!(NatResolver::Pcp, "pcp".parse().unwrap());
 
         let ip = NatResolver::ExternalIp(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let s = "extip:0.0.0.0";
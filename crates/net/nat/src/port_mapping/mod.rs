@@ -0,0 +1,697 @@
+//! Port mapping on NAT gateways via `UPnP-IGD`, `NAT-PMP` and `PCP`.
+//!
+//! Resolving the external IP is not enough for a node behind a NAT to be reachable: the gateway
+//! must also forward the inbound connections. The [`PortMapper`] asks the gateway to forward the
+//! `RLPx` TCP port and the discovery UDP port, renews the mappings before their lease expires,
+//! publishes the resulting [`ExternalEndpoint`] whenever it changes and removes the mappings again
+//! on shutdown.
+
+mod natpmp;
+mod pcp;
+mod upnp;
+
+pub use natpmp::NatPmpClient;
+pub use pcp::PcpClient;
+pub use upnp::UpnpGateway;
+
+use std::{
+    fmt, io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::{
+    net::UdpSocket,
+    sync::{oneshot, watch},
+    task::JoinHandle,
+};
+use tracing::{debug, info, trace, warn};
+
+/// The port `NAT-PMP` and `PCP` servers listen on.
+pub const NAT_PMP_PORT: u16 = 5351;
+
+/// Default lease requested for a port mapping.
+pub const DEFAULT_LEASE_DURATION: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// How long to wait before retrying after a failed attempt to map the ports.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Lower bound for the interval at which mappings are renewed.
+const MIN_RENEWAL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long the removal of the mappings may take on shutdown.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Initial timeout of a `NAT-PMP` or `PCP` request, doubled on every retransmission.
+const INITIAL_REQUEST_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Number of times a `NAT-PMP` or `PCP` request is sent before giving up.
+const REQUEST_ATTEMPTS: u32 = 4;
+
+/// The protocols that can be used to map ports on a gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortMappingProtocol {
+    /// `UPnP` Internet Gateway Device protocol.
+    Upnp,
+    /// NAT Port Mapping Protocol, see [RFC 6886](https://www.rfc-editor.org/rfc/rfc6886).
+    NatPmp,
+    /// Port Control Protocol, see [RFC 6887](https://www.rfc-editor.org/rfc/rfc6887).
+    Pcp,
+}
+
+impl fmt::Display for PortMappingProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Upnp => f.write_str("UPnP"),
+            Self::NatPmp => f.write_str("NAT-PMP"),
+            Self::Pcp => f.write_str("PCP"),
+        }
+    }
+}
+
+/// The transport protocol of a mapped port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransportProtocol {
+    /// TCP, used by `RLPx`.
+    Tcp,
+    /// UDP, used by discovery.
+    Udp,
+}
+
+impl fmt::Display for TransportProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp => f.write_str("TCP"),
+            Self::Udp => f.write_str("UDP"),
+        }
+    }
+}
+
+/// A port mapping granted by the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
+    /// The external IP of the mapping, if reported by the gateway.
+    pub external_ip: Option<IpAddr>,
+    /// The external port inbound connections are forwarded from.
+    pub external_port: u16,
+    /// The lease of the mapping, zero if the mapping is permanent.
+    pub lifetime: Duration,
+}
+
+/// The address under which the node is reachable through the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExternalEndpoint {
+    /// The external IP of the gateway.
+    pub ip: IpAddr,
+    /// The external port that is forwarded to the local TCP port, if requested.
+    pub tcp_port: Option<u16>,
+    /// The external port that is forwarded to the local UDP port, if requested.
+    pub udp_port: Option<u16>,
+}
+
+/// Errors that can occur when mapping ports on a gateway.
+#[derive(Debug, thiserror::Error)]
+pub enum PortMappingError {
+    /// Failed to communicate with the gateway.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Failed to communicate with the `UPnP` gateway.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The gateway did not answer in time.
+    #[error("{0} gateway did not respond")]
+    Timeout(PortMappingProtocol),
+    /// No gateway could be found.
+    #[error("no {0} gateway found")]
+    NoGateway(PortMappingProtocol),
+    /// The gateway sent a response that could not be understood.
+    #[error("invalid response from {0} gateway: {1}")]
+    InvalidResponse(PortMappingProtocol, &'static str),
+    /// The `NAT-PMP` or `PCP` gateway rejected the request.
+    #[error("{protocol} gateway rejected the request with result code {code}")]
+    ResultCode {
+        /// The protocol of the gateway.
+        protocol: PortMappingProtocol,
+        /// The result code of the response.
+        code: u16,
+    },
+    /// The `UPnP` gateway rejected the request.
+    #[error("UPnP gateway rejected the request with error {code}: {description}")]
+    UpnpFault {
+        /// The `UPnP` error code.
+        code: u16,
+        /// The description of the error.
+        description: String,
+    },
+    /// The operation is not supported by the protocol.
+    #[error("{0} does not support {1}")]
+    Unsupported(PortMappingProtocol, &'static str),
+}
+
+/// A gateway that ports can be mapped on.
+#[derive(Debug)]
+pub enum Gateway {
+    /// A `UPnP` Internet Gateway Device.
+    Upnp(UpnpGateway),
+    /// A `NAT-PMP` server.
+    NatPmp(NatPmpClient),
+    /// A `PCP` server.
+    Pcp(PcpClient),
+}
+
+impl Gateway {
+    /// Discovers the gateway for the given protocol.
+    ///
+    /// `UPnP` gateways are discovered via SSDP. `NAT-PMP` and `PCP` requests are sent to the given
+    /// gateway IP, or the default gateway of the host if none is given.
+    pub async fn discover(
+        protocol: PortMappingProtocol,
+        gateway: Option<IpAddr>,
+    ) -> Result<Self, PortMappingError> {
+        let server = || {
+            gateway
+                .or_else(|| default_gateway().map(IpAddr::V4))
+                .map(|ip| SocketAddr::new(ip, NAT_PMP_PORT))
+                .ok_or(PortMappingError::NoGateway(protocol))
+        };
+        let gateway = match protocol {
+            PortMappingProtocol::Upnp => Self::Upnp(UpnpGateway::discover().await?),
+            PortMappingProtocol::NatPmp => Self::NatPmp(NatPmpClient::new(server()?)),
+            PortMappingProtocol::Pcp => Self::Pcp(PcpClient::new(server()?)),
+        };
+        debug!(target: "net::nat", ?gateway, "Discovered gateway");
+        Ok(gateway)
+    }
+
+    /// Returns the protocol used to talk to the gateway.
+    pub const fn protocol(&self) -> PortMappingProtocol {
+        match self {
+            Self::Upnp(_) => PortMappingProtocol::Upnp,
+            Self::NatPmp(_) => PortMappingProtocol::NatPmp,
+            Self::Pcp(_) => PortMappingProtocol::Pcp,
+        }
+    }
+
+    /// Requests the external IP of the gateway.
+    ///
+    /// `PCP` has no such request, the external IP is returned with every [`Mapping`] instead.
+    pub async fn external_ip(&self) -> Result<IpAddr, PortMappingError> {
+        match self {
+            Self::Upnp(gateway) => gateway.external_ip().await,
+            Self::NatPmp(client) => client.external_ip().await.map(IpAddr::V4),
+            Self::Pcp(_) => Err(PortMappingError::Unsupported(
+                PortMappingProtocol::Pcp,
+                "external address requests",
+            )),
+        }
+    }
+
+    /// Requests a mapping of the given local port, preferably to the same external port.
+    ///
+    /// Requesting an existing mapping again renews its lease.
+    pub async fn add_mapping(
+        &mut self,
+        transport: TransportProtocol,
+        internal_port: u16,
+        lease: Duration,
+        description: &str,
+    ) -> Result<Mapping, PortMappingError> {
+        match self {
+            Self::Upnp(gateway) => {
+                gateway.add_mapping(transport, internal_port, lease, description).await
+            }
+            Self::NatPmp(client) => client.add_mapping(transport, internal_port, lease).await,
+            Self::Pcp(client) => client.add_mapping(transport, internal_port, lease).await,
+        }
+    }
+
+    /// Removes the mapping of the given local port.
+    pub async fn remove_mapping(
+        &mut self,
+        transport: TransportProtocol,
+        internal_port: u16,
+    ) -> Result<(), PortMappingError> {
+        match self {
+            Self::Upnp(gateway) => gateway.remove_mapping(transport, internal_port).await,
+            Self::NatPmp(client) => client.remove_mapping(transport, internal_port).await,
+            Self::Pcp(client) => client.remove_mapping(transport, internal_port).await,
+        }
+    }
+}
+
+/// Configures the [`PortMapper`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortMappingConfig {
+    /// The protocol used to map the ports.
+    pub protocol: PortMappingProtocol,
+    /// The `NAT-PMP` or `PCP` gateway, defaults to the default gateway of the host.
+    pub gateway: Option<IpAddr>,
+    /// The lease requested for the mappings.
+    pub lease_duration: Duration,
+    /// The description of the mappings shown by `UPnP` gateways.
+    pub description: String,
+}
+
+impl PortMappingConfig {
+    /// Creates a new config for the given protocol.
+    pub fn new(protocol: PortMappingProtocol) -> Self {
+        Self {
+            protocol,
+            gateway: None,
+            lease_duration: DEFAULT_LEASE_DURATION,
+            description: "reth".to_string(),
+        }
+    }
+
+    /// Sets the `NAT-PMP` or `PCP` gateway.
+    pub const fn with_gateway(mut self, gateway: IpAddr) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    /// Sets the lease requested for the mappings.
+    pub const fn with_lease_duration(mut self, lease_duration: Duration) -> Self {
+        self.lease_duration = lease_duration;
+        self
+    }
+
+    /// Sets the description of the mappings.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+/// A mapping requested by the [`PortMapper`].
+#[derive(Debug, Clone, Copy)]
+struct RequestedMapping {
+    transport: TransportProtocol,
+    internal_port: u16,
+    /// The external port, if currently mapped.
+    external_port: Option<u16>,
+}
+
+/// Maps local ports on the gateway and keeps the mappings alive.
+///
+/// See also [`PortMapper::spawn`].
+#[derive(Debug)]
+pub struct PortMapper {
+    config: PortMappingConfig,
+    gateway: Option<Gateway>,
+    mappings: Vec<RequestedMapping>,
+    endpoint: watch::Sender<Option<ExternalEndpoint>>,
+}
+
+impl PortMapper {
+    /// Creates a new port mapper that doesn't map any ports yet.
+    pub fn new(config: PortMappingConfig) -> Self {
+        Self { config, gateway: None, mappings: Vec::new(), endpoint: watch::Sender::new(None) }
+    }
+
+    /// Uses the given gateway instead of discovering one.
+    pub fn with_gateway(mut self, gateway: Gateway) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    /// Maps the given local TCP port.
+    pub fn with_tcp_port(self, port: u16) -> Self {
+        self.with_port(TransportProtocol::Tcp, port)
+    }
+
+    /// Maps the given local UDP port.
+    pub fn with_udp_port(self, port: u16) -> Self {
+        self.with_port(TransportProtocol::Udp, port)
+    }
+
+    fn with_port(mut self, transport: TransportProtocol, internal_port: u16) -> Self {
+        self.mappings.retain(|mapping| mapping.transport != transport);
+        self.mappings.push(RequestedMapping { transport, internal_port, external_port: None });
+        self
+    }
+
+    /// Spawns the port mapper onto a new task.
+    ///
+    /// The mappings are removed from the gateway when [`PortMapperHandle::shutdown`] is called or
+    /// the handle is dropped.
+    pub fn spawn(self) -> PortMapperHandle {
+        let endpoint = self.endpoint.subscribe();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let task = tokio::spawn(self.run(shutdown_rx));
+        PortMapperHandle { endpoint, shutdown: Some(shutdown_tx), task }
+    }
+
+    async fn run(mut self, mut shutdown: oneshot::Receiver<()>) {
+        loop {
+            let renew_in = match self.refresh().await {
+                Ok(renew_in) => renew_in,
+                Err(err) => {
+                    warn!(
+                        target: "net::nat",
+                        protocol=%self.config.protocol,
+                        %err,
+                        "Failed to map ports on gateway"
+                    );
+                    // rediscover the gateway on the next attempt, it may have changed
+                    self.gateway = None;
+                    RETRY_INTERVAL
+                }
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(renew_in) => {}
+                _ = &mut shutdown => break,
+            }
+        }
+
+        self.remove_mappings().await;
+    }
+
+    /// Requests all mappings and publishes the resulting endpoint.
+    ///
+    /// Returns the duration after which the mappings need to be renewed.
+    async fn refresh(&mut self) -> Result<Duration, PortMappingError> {
+        let gateway = match self.gateway.take() {
+            Some(gateway) => gateway,
+            None => Gateway::discover(self.config.protocol, self.config.gateway).await?,
+        };
+        let gateway = self.gateway.insert(gateway);
+
+        let mut renew_in = self.config.lease_duration / 2;
+        let mut external_ip = None;
+        for requested in &mut self.mappings {
+            let mapping = gateway
+                .add_mapping(
+                    requested.transport,
+                    requested.internal_port,
+                    self.config.lease_duration,
+                    &self.config.description,
+                )
+                .await?;
+            trace!(
+                target: "net::nat",
+                transport=%requested.transport,
+                internal_port=requested.internal_port,
+                ?mapping,
+                "Mapped port"
+            );
+
+            requested.external_port = Some(mapping.external_port);
+            external_ip = external_ip.or(mapping.external_ip);
+            if !mapping.lifetime.is_zero() {
+                renew_in = renew_in.min(mapping.lifetime / 2);
+            }
+        }
+
+        let ip = match external_ip {
+            Some(ip) => ip,
+            None => gateway.external_ip().await?,
+        };
+        if ip.is_unspecified() {
+            return Err(PortMappingError::InvalidResponse(
+                gateway.protocol(),
+                "gateway has no external address",
+            ))
+        }
+
+        let endpoint = ExternalEndpoint {
+            ip,
+            tcp_port: self.external_port(TransportProtocol::Tcp),
+            udp_port: self.external_port(TransportProtocol::Udp),
+        };
+        self.endpoint.send_if_modified(|current| {
+            if *current == Some(endpoint) {
+                return false
+            }
+            if is_private(ip) {
+                warn!(
+                    target: "net::nat",
+                    %ip,
+                    "Gateway has a private external IP, the node is likely behind multiple NATs"
+                );
+            }
+            info!(
+                target: "net::nat",
+                protocol=%self.config.protocol,
+                ?endpoint,
+                "Mapped ports on gateway"
+            );
+            *current = Some(endpoint);
+            true
+        });
+
+        Ok(renew_in.max(MIN_RENEWAL_INTERVAL))
+    }
+
+    /// Returns the external port of the mapping with the given transport protocol.
+    fn external_port(&self, transport: TransportProtocol) -> Option<u16> {
+        self.mappings
+            .iter()
+            .find(|mapping| mapping.transport == transport)
+            .and_then(|mapping| mapping.external_port)
+    }
+
+    /// Removes all active mappings from the gateway.
+    async fn remove_mappings(&mut self) {
+        let Some(gateway) = self.gateway.as_mut() else { return };
+        for requested in &mut self.mappings {
+            let Some(external_port) = requested.external_port.take() else { continue };
+            let transport = requested.transport;
+            match gateway.remove_mapping(transport, requested.internal_port).await {
+                Ok(()) => {
+                    debug!(target: "net::nat", %transport, external_port, "Removed port mapping")
+                }
+                Err(err) => {
+                    debug!(target: "net::nat", %transport, external_port, %err, "Failed to remove mapping")
+                }
+            }
+        }
+        self.endpoint.send_replace(None);
+    }
+}
+
+/// A handle to a spawned [`PortMapper`].
+#[derive(Debug)]
+pub struct PortMapperHandle {
+    endpoint: watch::Receiver<Option<ExternalEndpoint>>,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl PortMapperHandle {
+    /// Returns the current external endpoint, if the ports are mapped.
+    pub fn external_endpoint(&self) -> Option<ExternalEndpoint> {
+        *self.endpoint.borrow()
+    }
+
+    /// Returns a receiver that is notified whenever the external endpoint changes.
+    pub fn subscribe(&self) -> watch::Receiver<Option<ExternalEndpoint>> {
+        self.endpoint.clone()
+    }
+
+    /// Removes the mappings from the gateway and stops the port mapper.
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut self.task).await.is_err() {
+            debug!(target: "net::nat", "Timed out removing port mappings");
+        }
+    }
+}
+
+/// Returns the default IPv4 gateway of the host, best effort.
+///
+/// On Linux this reads the routing table. Everywhere else, and if the routing table can't be read,
+/// this falls back to the first address in the subnet of the first private IPv4 interface, which is
+/// what most home and office routers use.
+pub fn default_gateway() -> Option<Ipv4Addr> {
+    #[cfg(target_os = "linux")]
+    {
+        let routes = std::fs::read_to_string("/proc/net/route").ok();
+        if let Some(gateway) = routes.as_deref().and_then(default_gateway_from_routes) {
+            return Some(gateway)
+        }
+    }
+
+    let interfaces = if_addrs::get_if_addrs().ok()?;
+    interfaces.into_iter().find_map(|interface| match interface.addr {
+        if_addrs::IfAddr::V4(addr) if addr.ip.is_private() => {
+            let network = u32::from(addr.ip) & u32::from(addr.netmask);
+            Some(Ipv4Addr::from(network + 1))
+        }
+        _ => None,
+    })
+}
+
+/// Parses the default gateway from the contents of `/proc/net/route`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn default_gateway_from_routes(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace().skip(1);
+        let (destination, gateway) = (fields.next()?, fields.next()?);
+        if destination != "00000000" {
+            return None
+        }
+        // the kernel prints the address in host byte order
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        (gateway != 0).then(|| Ipv4Addr::from(gateway.to_ne_bytes()))
+    })
+}
+
+/// Returns whether the IP is not globally routable.
+const fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local(),
+        IpAddr::V6(ip) => ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local(),
+    }
+}
+
+/// Opens a UDP socket that only receives datagrams from the given `NAT-PMP` or `PCP` server.
+async fn connect_udp(server: SocketAddr) -> io::Result<UdpSocket> {
+    let local: SocketAddr = if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(server).await?;
+    Ok(socket)
+}
+
+/// Sends the request to the `NAT-PMP` or `PCP` server and waits for the matching response.
+///
+/// The request is retransmitted with exponential backoff, as recommended by RFC 6886.
+async fn udp_request(
+    socket: &UdpSocket,
+    protocol: PortMappingProtocol,
+    request: &[u8],
+    is_response: impl Fn(&[u8]) -> bool,
+) -> Result<Vec<u8>, PortMappingError> {
+    let mut buf = [0u8; 1100];
+    let mut timeout = INITIAL_REQUEST_TIMEOUT;
+    for _ in 0..REQUEST_ATTEMPTS {
+        socket.send(request).await?;
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            match tokio::time::timeout_at(deadline, socket.recv(&mut buf)).await {
+                Ok(Ok(len)) if is_response(&buf[..len]) => return Ok(buf[..len].to_vec()),
+                // ignore unrelated datagrams
+                Ok(Ok(_)) => {}
+                // ICMP port unreachable surfaces as an error on connected sockets
+                Ok(Err(err)) if err.kind() == io::ErrorKind::ConnectionRefused => {
+                    return Err(PortMappingError::NoGateway(protocol))
+                }
+                Ok(Err(err)) => return Err(err.into()),
+                Err(_) => break,
+            }
+        }
+        timeout *= 2;
+    }
+    Err(PortMappingError::Timeout(protocol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `NAT-PMP` server on localhost that changes its external IP after the first request and
+    /// reports the lifetime of every map request.
+    async fn spawn_nat_pmp_server(
+        lifetime: u32,
+    ) -> (SocketAddr, tokio::sync::mpsc::UnboundedReceiver<(u8, u16, u32)>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            let mut external_ip = [203, 0, 113, 1];
+            while let Ok((len, from)) = socket.recv_from(&mut buf).await {
+                let opcode = buf[1];
+                let mut response = vec![0, 128 + opcode, 0, 0, 0, 0, 0, 1];
+                if opcode == 0 && len == 2 {
+                    response.extend_from_slice(&external_ip);
+                    external_ip[3] += 1;
+                } else if len == 12 {
+                    let requested = u32::from_be_bytes(buf[8..12].try_into().unwrap());
+                    let internal_port = u16::from_be_bytes([buf[4], buf[5]]);
+                    let _ = tx.send((opcode, internal_port, requested));
+                    let granted = if requested == 0 { 0 } else { lifetime };
+                    response.extend_from_slice(&buf[4..6]);
+                    response.extend_from_slice(&(internal_port + 1000).to_be_bytes());
+                    response.extend_from_slice(&granted.to_be_bytes());
+                }
+                socket.send_to(&response, from).await.unwrap();
+            }
+        });
+        (addr, rx)
+    }
+
+    #[tokio::test]
+    async fn port_mapper_renews_and_removes_mappings() {
+        reth_tracing::init_test_tracing();
+        let (server, mut requests) = spawn_nat_pmp_server(2).await;
+
+        let handle = PortMapper::new(PortMappingConfig::new(PortMappingProtocol::NatPmp))
+            .with_gateway(Gateway::NatPmp(NatPmpClient::new(server)))
+            .with_tcp_port(30303)
+            .with_udp_port(30304)
+            .spawn();
+        let mut endpoint = handle.subscribe();
+
+        endpoint.changed().await.unwrap();
+        let expected = ExternalEndpoint {
+            ip: Ipv4Addr::new(203, 0, 113, 1).into(),
+            tcp_port: Some(31303),
+            udp_port: Some(31304),
+        };
+        assert_eq!(*endpoint.borrow_and_update(), Some(expected));
+        assert_eq!(handle.external_endpoint(), Some(expected));
+        // opcode 2 is TCP, opcode 1 is UDP
+        assert_eq!(requests.recv().await, Some((2, 30303, 7200)));
+        assert_eq!(requests.recv().await, Some((1, 30304, 7200)));
+
+        // the mappings are renewed after half their lifetime, picking up the new external IP
+        endpoint.changed().await.unwrap();
+        assert_eq!(
+            *endpoint.borrow_and_update(),
+            Some(ExternalEndpoint { ip: Ipv4Addr::new(203, 0, 113, 2).into(), ..expected })
+        );
+        assert_eq!(requests.recv().await, Some((2, 30303, 7200)));
+        assert_eq!(requests.recv().await, Some((1, 30304, 7200)));
+
+        handle.shutdown().await;
+        assert_eq!(requests.recv().await, Some((2, 30303, 0)));
+        assert_eq!(requests.recv().await, Some((1, 30304, 0)));
+        assert!(endpoint.borrow().is_none());
+    }
+
+    #[tokio::test]
+    async fn port_mapper_retries_unreachable_gateway() {
+        // nothing listens on this port
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let mut mapper = PortMapper::new(PortMappingConfig::new(PortMappingProtocol::NatPmp))
+            .with_gateway(Gateway::NatPmp(NatPmpClient::new(server)))
+            .with_tcp_port(30303);
+
+        assert!(mapper.refresh().await.is_err());
+        assert!(mapper.endpoint.borrow().is_none());
+    }
+
+    #[test]
+    fn parse_default_gateway() {
+        let routes = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t0000A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+";
+        let expected = if cfg!(target_endian = "little") {
+            Ipv4Addr::new(192, 168, 1, 1)
+        } else {
+            Ipv4Addr::new(1, 1, 168, 192)
+        };
+        assert_eq!(default_gateway_from_routes(routes), Some(expected));
+
+        let no_default_route = routes.lines().take(2).collect::<Vec<_>>().join("\n");
+        assert_eq!(default_gateway_from_routes(&no_default_route), None);
+    }
+}
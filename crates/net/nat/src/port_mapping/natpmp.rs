@@ -0,0 +1,191 @@
+//! `NAT-PMP` client, see [RFC 6886](https://www.rfc-editor.org/rfc/rfc6886).
+
+use super::{
+    connect_udp, udp_request, Mapping, PortMappingError, PortMappingProtocol, TransportProtocol,
+};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+const VERSION: u8 = 0;
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+const OP_MAP_UDP: u8 = 1;
+const OP_MAP_TCP: u8 = 2;
+/// Added to the opcode of the request in the response.
+const OP_RESPONSE: u8 = 128;
+
+/// A client talking to a `NAT-PMP` server.
+#[derive(Debug, Clone, Copy)]
+pub struct NatPmpClient {
+    server: SocketAddr,
+}
+
+impl NatPmpClient {
+    /// Creates a new client for the server at the given address.
+    pub const fn new(server: SocketAddr) -> Self {
+        Self { server }
+    }
+
+    /// Returns the address of the server.
+    pub const fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// Requests the external IPv4 address of the gateway.
+    pub async fn external_ip(&self) -> Result<Ipv4Addr, PortMappingError> {
+        let response =
+            self.request(&[VERSION, OP_EXTERNAL_ADDRESS], OP_EXTERNAL_ADDRESS, 12).await?;
+        Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11]))
+    }
+
+    /// Requests a mapping of the given local port with the given lease.
+    ///
+    /// Requesting an existing mapping again renews its lease.
+    pub async fn add_mapping(
+        &self,
+        transport: TransportProtocol,
+        internal_port: u16,
+        lease: Duration,
+    ) -> Result<Mapping, PortMappingError> {
+        let lifetime = u32::try_from(lease.as_secs()).unwrap_or(u32::MAX);
+        let response = self.map(transport, internal_port, internal_port, lifetime).await?;
+        Ok(Mapping {
+            external_ip: None,
+            external_port: u16::from_be_bytes([response[10], response[11]]),
+            lifetime: Duration::from_secs(
+                u32::from_be_bytes(response[12..16].try_into().unwrap()).into(),
+            ),
+        })
+    }
+
+    /// Removes the mapping of the given local port.
+    pub async fn remove_mapping(
+        &self,
+        transport: TransportProtocol,
+        internal_port: u16,
+    ) -> Result<(), PortMappingError> {
+        self.map(transport, internal_port, 0, 0).await.map(drop)
+    }
+
+    async fn map(
+        &self,
+        transport: TransportProtocol,
+        internal_port: u16,
+        external_port: u16,
+        lifetime: u32,
+    ) -> Result<Vec<u8>, PortMappingError> {
+        let opcode = match transport {
+            TransportProtocol::Udp => OP_MAP_UDP,
+            TransportProtocol::Tcp => OP_MAP_TCP,
+        };
+        let mut request = [0u8; 12];
+        request[0] = VERSION;
+        request[1] = opcode;
+        request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+        request[6..8].copy_from_slice(&external_port.to_be_bytes());
+        request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+
+        let response = self.request(&request, opcode, 16).await?;
+        if response[8..10] != request[4..6] {
+            return Err(PortMappingError::InvalidResponse(
+                PortMappingProtocol::NatPmp,
+                "internal port mismatch",
+            ))
+        }
+        Ok(response)
+    }
+
+    /// Sends the request and returns the response if the server accepted it.
+    async fn request(
+        &self,
+        request: &[u8],
+        opcode: u8,
+        response_len: usize,
+    ) -> Result<Vec<u8>, PortMappingError> {
+        let socket = connect_udp(self.server).await?;
+        let response = udp_request(&socket, PortMappingProtocol::NatPmp, request, |response| {
+            response.len() >= 4 && response[0] == VERSION && response[1] == OP_RESPONSE + opcode
+        })
+        .await?;
+
+        let code = u16::from_be_bytes([response[2], response[3]]);
+        if code != 0 {
+            return Err(PortMappingError::ResultCode { protocol: PortMappingProtocol::NatPmp, code })
+        }
+        if response.len() < response_len {
+            return Err(PortMappingError::InvalidResponse(
+                PortMappingProtocol::NatPmp,
+                "response too short",
+            ))
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UdpSocket;
+
+    /// A `NAT-PMP` server on localhost that maps every port to the next port and rejects mappings
+    /// of port 1.
+    async fn spawn_server() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            while let Ok((len, from)) = socket.recv_from(&mut buf).await {
+                let request = &buf[..len];
+                let mut response = vec![VERSION, OP_RESPONSE + request[1], 0, 0, 0, 0, 0, 42];
+                if request[1] == OP_EXTERNAL_ADDRESS {
+                    response.extend_from_slice(&[198, 51, 100, 7]);
+                } else {
+                    let internal_port = u16::from_be_bytes([request[4], request[5]]);
+                    if internal_port == 1 {
+                        // not authorized
+                        response[3] = 2;
+                    }
+                    let external_port =
+                        if request[8..12] == [0; 4] { 0 } else { internal_port + 1 };
+                    response.extend_from_slice(&request[4..6]);
+                    response.extend_from_slice(&external_port.to_be_bytes());
+                    response.extend_from_slice(&request[8..12]);
+                }
+                socket.send_to(&response, from).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn nat_pmp_requests() {
+        let client = NatPmpClient::new(spawn_server().await);
+
+        assert_eq!(client.external_ip().await.unwrap(), Ipv4Addr::new(198, 51, 100, 7));
+
+        let mapping = client
+            .add_mapping(TransportProtocol::Tcp, 30303, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert_eq!(
+            mapping,
+            Mapping {
+                external_ip: None,
+                external_port: 30304,
+                lifetime: Duration::from_secs(3600)
+            }
+        );
+
+        client.remove_mapping(TransportProtocol::Udp, 30303).await.unwrap();
+
+        let err = client
+            .add_mapping(TransportProtocol::Udp, 1, Duration::from_secs(60))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PortMappingError::ResultCode { protocol: PortMappingProtocol::NatPmp, code: 2 }
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! `PCP` client, see [RFC 6887](https://www.rfc-editor.org/rfc/rfc6887).
+
+use super::{
+    connect_udp, udp_request, Mapping, PortMappingError, PortMappingProtocol, TransportProtocol,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+const VERSION: u8 = 2;
+const OP_MAP: u8 = 1;
+/// Set in the opcode field of responses.
+const RESPONSE_BIT: u8 = 0x80;
+const HEADER_LEN: usize = 24;
+const MAP_LEN: usize = 36;
+const NONCE_LEN: usize = 12;
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+
+/// A client talking to a `PCP` server.
+#[derive(Debug, Clone)]
+pub struct PcpClient {
+    server: SocketAddr,
+    /// The nonces of the requested mappings, they must be reused to renew or delete a mapping.
+    nonces: HashMap<(TransportProtocol, u16), [u8; NONCE_LEN]>,
+}
+
+impl PcpClient {
+    /// Creates a new client for the server at the given address.
+    pub fn new(server: SocketAddr) -> Self {
+        Self { server, nonces: HashMap::new() }
+    }
+
+    /// Returns the address of the server.
+    pub const fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// Requests a mapping of the given local port with the given lease.
+    ///
+    /// Requesting an existing mapping again renews its lease.
+    pub async fn add_mapping(
+        &mut self,
+        transport: TransportProtocol,
+        internal_port: u16,
+        lease: Duration,
+    ) -> Result<Mapping, PortMappingError> {
+        let nonce = *self.nonces.entry((transport, internal_port)).or_insert_with(rand::random);
+        let lifetime = u32::try_from(lease.as_secs()).unwrap_or(u32::MAX);
+        self.map(nonce, transport, internal_port, internal_port, lifetime).await
+    }
+
+    /// Removes the mapping of the given local port.
+    pub async fn remove_mapping(
+        &mut self,
+        transport: TransportProtocol,
+        internal_port: u16,
+    ) -> Result<(), PortMappingError> {
+        let Some(nonce) = self.nonces.remove(&(transport, internal_port)) else { return Ok(()) };
+        self.map(nonce, transport, internal_port, 0, 0).await.map(drop)
+    }
+
+    async fn map(
+        &self,
+        nonce: [u8; NONCE_LEN],
+        transport: TransportProtocol,
+        internal_port: u16,
+        external_port: u16,
+        lifetime: u32,
+    ) -> Result<Mapping, PortMappingError> {
+        let socket = connect_udp(self.server).await?;
+        let client_ip = socket.local_addr()?.ip();
+
+        let mut request = [0u8; HEADER_LEN + MAP_LEN];
+        request[0] = VERSION;
+        request[1] = OP_MAP;
+        request[4..8].copy_from_slice(&lifetime.to_be_bytes());
+        request[8..24].copy_from_slice(&to_ipv6(client_ip).octets());
+        request[24..36].copy_from_slice(&nonce);
+        request[36] = match transport {
+            TransportProtocol::Tcp => PROTOCOL_TCP,
+            TransportProtocol::Udp => PROTOCOL_UDP,
+        };
+        request[40..42].copy_from_slice(&internal_port.to_be_bytes());
+        request[42..44].copy_from_slice(&external_port.to_be_bytes());
+        // no preference for the external address, in the address family of the client
+        let any = match client_ip {
+            IpAddr::V4(_) => IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        request[44..60].copy_from_slice(&to_ipv6(any).octets());
+
+        let response = udp_request(&socket, PortMappingProtocol::Pcp, &request, |response| {
+            response.len() >= HEADER_LEN + MAP_LEN &&
+                response[1] == RESPONSE_BIT | OP_MAP &&
+                response[24..36] == nonce
+        })
+        .await?;
+
+        let code = response[3];
+        if code != 0 {
+            return Err(PortMappingError::ResultCode {
+                protocol: PortMappingProtocol::Pcp,
+                code: code.into(),
+            })
+        }
+        if response[36] != request[36] || response[40..42] != request[40..42] {
+            return Err(PortMappingError::InvalidResponse(
+                PortMappingProtocol::Pcp,
+                "mapping mismatch",
+            ))
+        }
+
+        let external_ip = Ipv6Addr::from(<[u8; 16]>::try_from(&response[44..60]).unwrap());
+        Ok(Mapping {
+            external_ip: Some(external_ip.to_canonical()),
+            external_port: u16::from_be_bytes([response[42], response[43]]),
+            lifetime: Duration::from_secs(
+                u32::from_be_bytes(response[4..8].try_into().unwrap()).into(),
+            ),
+        })
+    }
+}
+
+/// Encodes the IP as IPv6 address, IPv4 addresses are IPv4-mapped.
+const fn to_ipv6(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use tokio::{net::UdpSocket, sync::mpsc};
+
+    /// A `PCP` server on localhost that maps every port to the next port and reports the nonce and
+    /// lifetime of every request.
+    async fn spawn_server() -> (SocketAddr, mpsc::UnboundedReceiver<([u8; NONCE_LEN], u32)>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1100];
+            while let Ok((len, from)) = socket.recv_from(&mut buf).await {
+                let request = &buf[..len];
+                assert_eq!(request[..2], [VERSION, OP_MAP]);
+                // the client address must match the source of the request
+                assert_eq!(&request[8..24], &to_ipv6(from.ip()).octets());
+
+                let lifetime = u32::from_be_bytes(request[4..8].try_into().unwrap());
+                let _ = tx.send((request[24..36].try_into().unwrap(), lifetime));
+
+                let internal_port = u16::from_be_bytes([request[40], request[41]]);
+                let mut response = [0u8; HEADER_LEN + MAP_LEN];
+                response[0] = VERSION;
+                response[1] = RESPONSE_BIT | OP_MAP;
+                response[4..8].copy_from_slice(&lifetime.min(600).to_be_bytes());
+                response[24..44].copy_from_slice(&request[24..44]);
+                response[42..44].copy_from_slice(&(internal_port + 1).to_be_bytes());
+                response[44..60]
+                    .copy_from_slice(&Ipv4Addr::new(198, 51, 100, 9).to_ipv6_mapped().octets());
+                socket.send_to(&response, from).await.unwrap();
+            }
+        });
+        (addr, rx)
+    }
+
+    #[tokio::test]
+    async fn pcp_requests() {
+        let (server, mut requests) = spawn_server().await;
+        let mut client = PcpClient::new(server);
+
+        let mapping = client
+            .add_mapping(TransportProtocol::Udp, 30303, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert_eq!(
+            mapping,
+            Mapping {
+                external_ip: Some(Ipv4Addr::new(198, 51, 100, 9).into()),
+                external_port: 30304,
+                lifetime: Duration::from_secs(600)
+            }
+        );
+        let (nonce, lifetime) = requests.recv().await.unwrap();
+        assert_eq!(lifetime, 3600);
+
+        // renewals and the deletion reuse the nonce of the mapping
+        client.add_mapping(TransportProtocol::Udp, 30303, Duration::from_secs(3600)).await.unwrap();
+        assert_eq!(requests.recv().await, Some((nonce, 3600)));
+
+        client.remove_mapping(TransportProtocol::Udp, 30303).await.unwrap();
+        assert_eq!(requests.recv().await, Some((nonce, 0)));
+
+        // a new mapping gets a new nonce
+        client.add_mapping(TransportProtocol::Tcp, 30303, Duration::from_secs(3600)).await.unwrap();
+        assert_ne!(requests.recv().await.unwrap().0, nonce);
+    }
+}
@@ -0,0 +1,507 @@
+//! `UPnP` Internet Gateway Device client.
+//!
+//! Gateways are discovered via SSDP, the port mappings are managed with SOAP requests to the
+//! `WANIPConnection` or `WANPPPConnection` service of the gateway.
+
+use super::{Mapping, PortMappingError, PortMappingProtocol, TransportProtocol};
+use reqwest::{StatusCode, Url};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+use tracing::debug;
+
+/// The SSDP multicast address.
+const SSDP_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(239, 255, 255, 250)), 1900);
+
+/// The device types searched for via SSDP.
+const SEARCH_TARGETS: &[&str] = &[
+    "urn:schemas-upnp-org:device:InternetGatewayDevice:1",
+    "urn:schemas-upnp-org:device:InternetGatewayDevice:2",
+];
+
+/// How long to wait for SSDP responses.
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Timeout of HTTP requests to the gateway.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many external ports are tried if the preferred one is already mapped by another client.
+const MAX_MAPPING_ATTEMPTS: usize = 4;
+
+/// `UPnP` error returned if the external port is already mapped by another client.
+const CONFLICT_IN_MAPPING_ENTRY: u16 = 718;
+
+/// `UPnP` error returned by gateways that only support mappings without lease.
+const ONLY_PERMANENT_LEASES_SUPPORTED: u16 = 725;
+
+/// A `UPnP` Internet Gateway Device.
+#[derive(Debug, Clone)]
+pub struct UpnpGateway {
+    client: reqwest::Client,
+    /// The URL of the connection service.
+    control_url: Url,
+    /// The type of the connection service.
+    service_type: String,
+    /// The local IP the gateway forwards inbound connections to.
+    internal_client: IpAddr,
+    /// The external ports of the requested mappings, they are kept on renewal.
+    external_ports: HashMap<(TransportProtocol, u16), u16>,
+}
+
+impl UpnpGateway {
+    /// Discovers a gateway on the local network via SSDP.
+    pub async fn discover() -> Result<Self, PortMappingError> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        for target in SEARCH_TARGETS {
+            let search = format!(
+                "M-SEARCH * HTTP/1.1\r\nHOST: {SSDP_ADDR}\r\nST: {target}\r\n\
+                 MAN: \"ssdp:discover\"\r\nMX: 2\r\n\r\n"
+            );
+            socket.send_to(search.as_bytes(), SSDP_ADDR).await?;
+        }
+
+        let deadline = tokio::time::Instant::now() + DISCOVERY_TIMEOUT;
+        let mut buf = [0u8; 2048];
+        loop {
+            let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await
+            else {
+                return Err(PortMappingError::NoGateway(PortMappingProtocol::Upnp))
+            };
+            let (len, from) = received?;
+            let Some(location) = ssdp_location(&buf[..len]) else { continue };
+            match Self::from_location(location).await {
+                Ok(gateway) => return Ok(gateway),
+                Err(err) => {
+                    debug!(target: "net::nat", %from, location, %err, "Skipping UPnP device")
+                }
+            }
+        }
+    }
+
+    /// Creates a gateway from the URL of its device description.
+    pub async fn from_location(location: &str) -> Result<Self, PortMappingError> {
+        let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let location = Url::parse(location).map_err(|_| invalid("invalid device location"))?;
+        let description =
+            client.get(location.clone()).send().await?.error_for_status()?.text().await?;
+
+        let (service_type, control_url) =
+            connection_service(&description).ok_or_else(|| invalid("no WAN connection service"))?;
+        let base = match xml_text(&description, "URLBase").filter(|base| !base.is_empty()) {
+            Some(base) => Url::parse(&unescape(base)).map_err(|_| invalid("invalid base URL"))?,
+            None => location,
+        };
+        let control_url = base.join(&control_url).map_err(|_| invalid("invalid control URL"))?;
+        let internal_client = local_ip_towards(&control_url).await?;
+
+        Ok(Self {
+            client,
+            control_url,
+            service_type,
+            internal_client,
+            external_ports: HashMap::new(),
+        })
+    }
+
+    /// Returns the URL of the connection service.
+    pub const fn control_url(&self) -> &Url {
+        &self.control_url
+    }
+
+    /// Requests the external IP of the gateway.
+    pub async fn external_ip(&self) -> Result<IpAddr, PortMappingError> {
+        let response = self.soap("GetExternalIPAddress", &[]).await?;
+        xml_text(&response, "NewExternalIPAddress")
+            .and_then(|ip| ip.parse().ok())
+            .ok_or_else(|| invalid("missing external IP"))
+    }
+
+    /// Requests a mapping of the given local port with the given lease.
+    ///
+    /// The same external port is preferred, if it's already mapped by another client a random one
+    /// is used. Gateways that only support permanent mappings are asked for a permanent mapping,
+    /// which is still renewed periodically in case the gateway restarts.
+    pub async fn add_mapping(
+        &mut self,
+        transport: TransportProtocol,
+        internal_port: u16,
+        lease: Duration,
+        description: &str,
+    ) -> Result<Mapping, PortMappingError> {
+        let mut lease = u32::try_from(lease.as_secs()).unwrap_or(u32::MAX);
+        let mut external_port =
+            self.external_ports.get(&(transport, internal_port)).copied().unwrap_or(internal_port);
+
+        let mut attempts = 0;
+        loop {
+            let args = [
+                ("NewRemoteHost", String::new()),
+                ("NewExternalPort", external_port.to_string()),
+                ("NewProtocol", transport.to_string()),
+                ("NewInternalPort", internal_port.to_string()),
+                ("NewInternalClient", self.internal_client.to_string()),
+                ("NewEnabled", "1".to_string()),
+                ("NewPortMappingDescription", description.to_string()),
+                ("NewLeaseDuration", lease.to_string()),
+            ];
+            match self.soap("AddPortMapping", &args).await {
+                Ok(_) => break,
+                Err(PortMappingError::UpnpFault {
+                    code: ONLY_PERMANENT_LEASES_SUPPORTED, ..
+                }) if lease != 0 => {
+                    lease = 0;
+                }
+                Err(PortMappingError::UpnpFault { code: CONFLICT_IN_MAPPING_ENTRY, .. })
+                    if attempts < MAX_MAPPING_ATTEMPTS =>
+                {
+                    attempts += 1;
+                    external_port = rand::random_range(1024..=u16::MAX);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        self.external_ports.insert((transport, internal_port), external_port);
+        Ok(Mapping {
+            external_ip: None,
+            external_port,
+            lifetime: Duration::from_secs(lease.into()),
+        })
+    }
+
+    /// Removes the mapping of the given local port.
+    pub async fn remove_mapping(
+        &mut self,
+        transport: TransportProtocol,
+        internal_port: u16,
+    ) -> Result<(), PortMappingError> {
+        let Some(external_port) = self.external_ports.remove(&(transport, internal_port)) else {
+            return Ok(())
+        };
+        let args = [
+            ("NewRemoteHost", String::new()),
+            ("NewExternalPort", external_port.to_string()),
+            ("NewProtocol", transport.to_string()),
+        ];
+        self.soap("DeletePortMapping", &args).await.map(drop)
+    }
+
+    /// Invokes the action of the connection service and returns the response body.
+    async fn soap(
+        &self,
+        action: &str,
+        args: &[(&str, String)],
+    ) -> Result<String, PortMappingError> {
+        let mut body = format!(
+            "<?xml version=\"1.0\"?>\
+             <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+             s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+             <s:Body><u:{action} xmlns:u=\"{}\">",
+            self.service_type
+        );
+        for (name, value) in args {
+            let _ = write!(body, "<{name}>{}</{name}>", escape(value));
+        }
+        let _ = write!(body, "</u:{action}></s:Body></s:Envelope>");
+
+        let response = self
+            .client
+            .post(self.control_url.clone())
+            .header("Content-Type", "text/xml; charset=\"utf-8\"")
+            .header("SOAPAction", format!("\"{}#{action}\"", self.service_type))
+            .body(body)
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::INTERNAL_SERVER_ERROR {
+            let fault = response.text().await?;
+            let code = xml_text(&fault, "errorCode").and_then(|code| code.parse().ok());
+            return Err(PortMappingError::UpnpFault {
+                code: code.ok_or_else(|| invalid("invalid SOAP fault"))?,
+                description: xml_text(&fault, "errorDescription").map(unescape).unwrap_or_default(),
+            })
+        }
+        Ok(response.error_for_status()?.text().await?)
+    }
+}
+
+const fn invalid(reason: &'static str) -> PortMappingError {
+    PortMappingError::InvalidResponse(PortMappingProtocol::Upnp, reason)
+}
+
+/// Returns the value of the `LOCATION` header of an SSDP response.
+fn ssdp_location(response: &[u8]) -> Option<&str> {
+    let response = std::str::from_utf8(response).ok()?;
+    response.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("location").then_some(value.trim())
+    })
+}
+
+/// Returns the type and control URL of the first WAN connection service in the device
+/// description.
+fn connection_service(description: &str) -> Option<(String, String)> {
+    description.split("<service>").skip(1).find_map(|service| {
+        let service = service.split("</service>").next()?;
+        let service_type = xml_text(service, "serviceType")?;
+        if !service_type.contains(":WANIPConnection:") &&
+            !service_type.contains(":WANPPPConnection:")
+        {
+            return None
+        }
+        Some((unescape(service_type), unescape(xml_text(service, "controlURL")?)))
+    })
+}
+
+/// Returns the text of the first element with the given name, ignoring namespace prefixes.
+fn xml_text<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = rest.find('>')?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let tag_name = tag.split_whitespace().next().unwrap_or_default();
+        let local_name = tag_name.rsplit(':').next().unwrap_or_default();
+        if local_name == name && !tag_name.starts_with('/') && !tag.ends_with('/') {
+            return rest.find('<').map(|end| rest[..end].trim())
+        }
+    }
+    None
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Returns the local IP used to reach the host of the URL.
+async fn local_ip_towards(url: &Url) -> Result<IpAddr, PortMappingError> {
+    let host = url.host_str().ok_or_else(|| invalid("control URL without host"))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+    let remote = tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| invalid("unresolvable control URL"))?;
+
+    let local: SocketAddr = if remote.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(remote).await?;
+    Ok(socket.local_addr()?.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::mpsc,
+    };
+
+    const DESCRIPTION: &str = r#"<?xml version="1.0"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0">
+  <device>
+    <deviceType>urn:schemas-upnp-org:device:InternetGatewayDevice:1</deviceType>
+    <serviceList>
+      <service>
+        <serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType>
+        <controlURL>/ctl/L3F</controlURL>
+      </service>
+    </serviceList>
+    <deviceList>
+      <device>
+        <serviceList>
+          <service>
+            <serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType>
+            <controlURL>/ctl/IPConn</controlURL>
+          </service>
+        </serviceList>
+      </device>
+    </deviceList>
+  </device>
+</root>"#;
+
+    fn fault(code: u16) -> String {
+        format!(
+            "<s:Envelope><s:Body><s:Fault><detail><UPnPError>\
+             <errorCode>{code}</errorCode><errorDescription>error</errorDescription>\
+             </UPnPError></detail></s:Fault></s:Body></s:Envelope>"
+        )
+    }
+
+    /// A `UPnP` gateway on localhost that only supports permanent leases, has port 30303 mapped
+    /// for UDP by another client and reports every SOAP request.
+    async fn spawn_gateway() -> (String, mpsc::UnboundedReceiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let (head, body) = loop {
+                    let len = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..len]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    let Some((head, body)) = text.split_once("\r\n\r\n") else { continue };
+                    let content_length = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or_default();
+                    if body.len() >= content_length {
+                        break (head.to_string(), body.to_string())
+                    }
+                };
+
+                let (status, response) = if head.starts_with("GET /rootDesc.xml") {
+                    ("200 OK", DESCRIPTION.to_string())
+                } else {
+                    assert!(head.starts_with("POST /ctl/IPConn"));
+                    let action = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("soapaction").then_some(value)
+                        })
+                        .and_then(|action| action.trim().trim_matches('"').split_once('#'))
+                        .unwrap()
+                        .1
+                        .to_string();
+                    let _ = tx.send((action.clone(), body.clone()));
+                    match action.as_str() {
+                        "GetExternalIPAddress" => (
+                            "200 OK",
+                            "<s:Envelope><s:Body><u:GetExternalIPAddressResponse>\
+                             <NewExternalIPAddress>198.51.100.3</NewExternalIPAddress>\
+                             </u:GetExternalIPAddressResponse></s:Body></s:Envelope>"
+                                .to_string(),
+                        ),
+                        "AddPortMapping"
+                            if !body.contains("<NewLeaseDuration>0</NewLeaseDuration>") =>
+                        {
+                            ("500 Internal Server Error", fault(725))
+                        }
+                        "AddPortMapping"
+                            if body.contains("<NewExternalPort>30303</NewExternalPort>") &&
+                                body.contains("<NewProtocol>UDP</NewProtocol>") =>
+                        {
+                            ("500 Internal Server Error", fault(718))
+                        }
+                        _ => ("200 OK", "<s:Envelope></s:Envelope>".to_string()),
+                    }
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n\
+                     {response}",
+                    response.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{addr}/rootDesc.xml"), rx)
+    }
+
+    #[tokio::test]
+    async fn upnp_requests() {
+        let (location, mut requests) = spawn_gateway().await;
+        let mut gateway = UpnpGateway::from_location(&location).await.unwrap();
+        assert_eq!(gateway.control_url().path(), "/ctl/IPConn");
+        assert_eq!(gateway.internal_client, IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+        assert_eq!(
+            gateway.external_ip().await.unwrap(),
+            IpAddr::V4(Ipv4Addr::new(198, 51, 100, 3))
+        );
+        assert_eq!(requests.recv().await.unwrap().0, "GetExternalIPAddress");
+
+        // the gateway only supports permanent leases
+        let mapping = gateway
+            .add_mapping(TransportProtocol::Tcp, 30303, Duration::from_secs(3600), "reth")
+            .await
+            .unwrap();
+        assert_eq!(
+            mapping,
+            Mapping { external_ip: None, external_port: 30303, lifetime: Duration::ZERO }
+        );
+        let (action, body) = requests.recv().await.unwrap();
+        assert_eq!(action, "AddPortMapping");
+        assert!(body.contains("<NewLeaseDuration>3600</NewLeaseDuration>"));
+        assert!(body.contains("<NewInternalClient>127.0.0.1</NewInternalClient>"));
+        assert!(requests
+            .recv()
+            .await
+            .unwrap()
+            .1
+            .contains("<NewLeaseDuration>0</NewLeaseDuration>"));
+
+        // the UDP port is taken, a random external port is used and kept on renewal
+        let mapping = gateway
+            .add_mapping(TransportProtocol::Udp, 30303, Duration::ZERO, "reth")
+            .await
+            .unwrap();
+        assert_ne!(mapping.external_port, 30303);
+        let renewed = gateway
+            .add_mapping(TransportProtocol::Udp, 30303, Duration::ZERO, "reth")
+            .await
+            .unwrap();
+        assert_eq!(renewed, mapping);
+        while let Ok((action, _)) = requests.try_recv() {
+            assert_eq!(action, "AddPortMapping");
+        }
+
+        gateway.remove_mapping(TransportProtocol::Udp, 30303).await.unwrap();
+        let (action, body) = requests.recv().await.unwrap();
+        assert_eq!(action, "DeletePortMapping");
+        assert!(
+            body.contains(&format!("<NewExternalPort>{}</NewExternalPort>", mapping.external_port))
+        );
+    }
+
+    #[test]
+    fn parse_description() {
+        assert_eq!(
+            connection_service(DESCRIPTION),
+            Some((
+                "urn:schemas-upnp-org:service:WANIPConnection:1".to_string(),
+                "/ctl/IPConn".to_string()
+            ))
+        );
+        assert_eq!(xml_text("<a><u:b x=\"1\"> text </u:b></a>", "b"), Some("text"));
+        assert_eq!(xml_text("<a><b/></a>", "b"), None);
+    }
+
+    #[test]
+    fn parse_ssdp_response() {
+        let response = b"HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=120\r\n\
+            Location: http://192.168.1.1:5000/rootDesc.xml\r\n\r\n";
+        assert_eq!(ssdp_location(response), Some("http://192.168.1.1:5000/rootDesc.xml"));
+    }
+}
@@ -21,13 +21,14 @@ pub mod error;
 pub mod events;
 /// Implementation of network traits for that does nothing.
 pub mod noop;
+pub mod private_relay;
 
 pub mod test_utils;
 use test_utils::PeersHandleProvider;
 
 pub use alloy_rpc_types_admin::EthProtocolInfo;
 pub use reth_network_p2p::{BlockClient, HeadersClient};
-pub use reth_network_types::{PeerKind, Reputation, ReputationChangeKind};
+pub use reth_network_types::{BanEntry, BanTarget, PeerKind, Reputation, ReputationChangeKind};
 
 pub use custody::CellCustody;
 pub use downloaders::BlockDownloaderProvider;
@@ -36,6 +37,7 @@ pub use events::{
     DiscoveredEvent, DiscoveryEvent, NetworkEvent, NetworkEventListenerProvider, PeerRequest,
     PeerRequestSender, RequestMessage,
 };
+pub use private_relay::PrivateRelayProvider;
 
 use reth_eth_wire_types::{
     capability::Capabilities, Capability, DisconnectReason, EthVersion, NetworkPrimitives,
@@ -43,7 +45,7 @@ use reth_eth_wire_types::{
 };
 use reth_network_p2p::sync::NetworkSyncUpdater;
 use reth_network_peers::NodeRecord;
-use std::{future::Future, net::SocketAddr, sync::Arc, time::Instant};
+use std::{collections::BTreeMap, future::Future, net::SocketAddr, sync::Arc, time::Instant};
 
 /// The `PeerId` type.
 pub type PeerId = alloy_primitives::B512;
@@ -57,6 +59,7 @@ pub trait FullNetwork:
     + NetworkEventListenerProvider
     + Peers
     + PeersHandleProvider
+    + PrivateRelayProvider
     + Clone
     + Unpin
     + 'static
@@ -71,6 +74,7 @@ impl<T> FullNetwork for T where
         + NetworkEventListenerProvider
         + Peers
         + PeersHandleProvider
+        + PrivateRelayProvider
         + Clone
         + Unpin
         + 'static
@@ -222,6 +226,24 @@ pub trait Peers: PeersInfo {
     /// Unbans the given peer.
     fn unban_peer(&self, peer: PeerId);
 
+    /// Bans the target of the entry, which can be a peer, an IP address or a range.
+    ///
+    /// Non-trusted peers covered by the ban are removed from the peer set and disconnected.
+    ///
+    /// Returns false if the ban was ignored because the target is a trusted peer.
+    fn add_ban(&self, entry: BanEntry) -> impl Future<Output = Result<bool, NetworkError>> + Send;
+
+    /// Lifts all bans of the target.
+    ///
+    /// Returns true if the target was banned.
+    fn remove_ban(
+        &self,
+        target: BanTarget,
+    ) -> impl Future<Output = Result<bool, NetworkError>> + Send;
+
+    /// Returns all bans that are persisted across restarts.
+    fn get_bans(&self) -> impl Future<Output = Result<Vec<BanEntry>, NetworkError>> + Send;
+
     /// Connect to the given peer. NOTE: if the maximum number of outbound sessions is reached,
     /// this won't do anything. See `reth_network::SessionManager::dial_outbound`.
     fn connect_peer(&self, peer: PeerId, tcp_addr: SocketAddr) {
@@ -274,6 +296,23 @@ pub struct PeerInfo {
     pub session_established: Instant,
     /// The peer's connection kind
     pub kind: PeerKind,
+    /// Bytes exchanged with the peer during the session, if session traffic is measured.
+    pub bandwidth: Option<BandwidthStats>,
+}
+
+/// Bytes exchanged with a peer, measured on the uncompressed message payloads.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BandwidthStats {
+    /// Total bytes received from the peer.
+    pub ingress: u64,
+    /// Total bytes sent to the peer.
+    pub egress: u64,
+    /// Bytes received from the peer, by message type.
+    pub ingress_by_message: BTreeMap<String, u64>,
+    /// Bytes sent to the peer, by message type.
+    pub egress_by_message: BTreeMap<String, u64>,
 }
 
 /// The direction of the connection.
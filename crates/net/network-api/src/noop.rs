@@ -8,6 +8,7 @@ use std::net::{IpAddr, SocketAddr};
 
 use crate::{
     events::{NetworkPeersEvents, PeerEventStream},
+    private_relay::{PrivateRelayHandle, PrivateRelayProvider},
     test_utils::{PeersHandle, PeersHandleProvider},
     BlockDownloaderProvider, CellCustody, DiscoveryEvent, NetworkError, NetworkEvent,
     NetworkEventListenerProvider, NetworkInfo, NetworkStatus, PeerId, PeerInfo, PeerRequest, Peers,
@@ -20,7 +21,7 @@ use reth_eth_wire_types::{
 };
 use reth_network_p2p::{sync::NetworkSyncUpdater, NoopFullBlockClient};
 use reth_network_peers::NodeRecord;
-use reth_network_types::{PeerKind, Reputation, ReputationChangeKind};
+use reth_network_types::{BanEntry, BanTarget, PeerKind, Reputation, ReputationChangeKind};
 use reth_tokio_util::{EventSender, EventStream};
 use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
@@ -163,6 +164,18 @@ where
 
     fn unban_peer(&self, _peer: PeerId) {}
 
+    async fn add_ban(&self, _entry: BanEntry) -> Result<bool, NetworkError> {
+        Ok(false)
+    }
+
+    async fn remove_ban(&self, _target: BanTarget) -> Result<bool, NetworkError> {
+        Ok(false)
+    }
+
+    async fn get_bans(&self) -> Result<Vec<BanEntry>, NetworkError> {
+        Ok(vec![])
+    }
+
     fn connect_peer_kind(
         &self,
         _peer: PeerId,
@@ -218,6 +231,15 @@ where
     }
 }
 
+impl<Net> PrivateRelayProvider for NoopNetwork<Net>
+where
+    Net: NetworkPrimitives,
+{
+    fn private_relay(&self) -> Option<&PrivateRelayHandle<Net::PooledTransaction>> {
+        None
+    }
+}
+
 impl<Net> NetworkPeersEvents for NoopNetwork<Net>
 where
     Net: NetworkPrimitives,
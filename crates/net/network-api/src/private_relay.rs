@@ -0,0 +1,188 @@
+//! Types to interact with the private transaction relay.
+//!
+//! The relay forwards private transactions and bundles to a configured set of trusted peers
+//! instead of gossiping them on the public `eth` protocol.
+
+use crate::{NetworkEventListenerProvider, PeerId};
+use alloy_consensus::transaction::Recovered;
+use alloy_rlp::{BufMut, Decodable, Encodable, Header};
+use reth_eth_wire_types::NetworkPrimitives;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+/// A bundle of transactions that must be included atomically and in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateBundle<T> {
+    /// The transactions of the bundle.
+    pub transactions: Vec<T>,
+    /// The block the bundle targets.
+    pub block_number: u64,
+}
+
+impl<T: Encodable> PrivateBundle<T> {
+    fn payload_length(&self) -> usize {
+        self.transactions.length() + self.block_number.length()
+    }
+}
+
+impl<T: Encodable> Encodable for PrivateBundle<T> {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header { list: true, payload_length: self.payload_length() }.encode(out);
+        self.transactions.encode(out);
+        self.block_number.encode(out);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + alloy_rlp::length_of_length(payload_length)
+    }
+}
+
+impl<T: Decodable> Decodable for PrivateBundle<T> {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString)
+        }
+
+        let initial_length = buf.len();
+        let bundle = Self { transactions: Vec::decode(buf)?, block_number: u64::decode(buf)? };
+        if initial_length - buf.len() != header.payload_length {
+            return Err(alloy_rlp::Error::UnexpectedLength)
+        }
+
+        Ok(bundle)
+    }
+}
+
+/// Errors returned by the [`PrivateRelayHandle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PrivateRelayError {
+    /// None of the configured relay peers is connected.
+    #[error("no private relay peer connected")]
+    NoPeers,
+    /// The relay manager has been dropped.
+    #[error("private relay manager closed")]
+    ManagerClosed,
+}
+
+/// The outcome of a relayed message for a single peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum RelayOutcome {
+    /// The peer acknowledged the message, with one entry per relayed transaction that is `true`
+    /// if the peer accepted the transaction.
+    Acknowledged(Vec<bool>),
+    /// The peer did not acknowledge the message in time.
+    TimedOut,
+    /// The peer disconnected before acknowledging the message.
+    Disconnected,
+}
+
+/// The acknowledgement of a relayed message by a single peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RelayAck {
+    /// The relay peer.
+    pub peer_id: PeerId,
+    /// The outcome of the relayed message.
+    pub outcome: RelayOutcome,
+}
+
+/// A bundle received from a relay peer.
+#[derive(Debug, Clone)]
+pub struct ReceivedBundle<T> {
+    /// The relay peer that sent the bundle.
+    pub peer_id: PeerId,
+    /// The bundle with recovered transactions.
+    pub bundle: PrivateBundle<Recovered<T>>,
+}
+
+/// The payload of a relayed message.
+#[derive(Debug)]
+pub enum PrivateRelayPayload<T> {
+    /// Private transactions.
+    Transactions(Vec<T>),
+    /// A private bundle.
+    Bundle(PrivateBundle<T>),
+}
+
+/// Commands sent from the [`PrivateRelayHandle`] to the relay manager.
+#[derive(Debug)]
+pub enum PrivateRelayCommand<T> {
+    /// Relay the payload to all connected relay peers and respond with their acknowledgements.
+    Relay {
+        /// The relayed payload.
+        payload: PrivateRelayPayload<T>,
+        /// Receives the acknowledgements of the relay peers.
+        response: oneshot::Sender<Result<Vec<RelayAck>, PrivateRelayError>>,
+    },
+}
+
+/// A handle to interact with the private relay.
+///
+/// `T` is the pooled transaction type that is relayed.
+#[derive(Debug)]
+pub struct PrivateRelayHandle<T> {
+    to_manager: mpsc::UnboundedSender<PrivateRelayCommand<T>>,
+    bundles: broadcast::Sender<ReceivedBundle<T>>,
+}
+
+impl<T> Clone for PrivateRelayHandle<T> {
+    fn clone(&self) -> Self {
+        Self { to_manager: self.to_manager.clone(), bundles: self.bundles.clone() }
+    }
+}
+
+impl<T> PrivateRelayHandle<T> {
+    /// Creates a new handle that sends commands to the relay manager and subscribes to the
+    /// bundles it receives.
+    pub const fn new(
+        to_manager: mpsc::UnboundedSender<PrivateRelayCommand<T>>,
+        bundles: broadcast::Sender<ReceivedBundle<T>>,
+    ) -> Self {
+        Self { to_manager, bundles }
+    }
+
+    /// Relays the transactions to all connected relay peers and waits for their
+    /// acknowledgements.
+    pub async fn send_transactions(
+        &self,
+        transactions: Vec<T>,
+    ) -> Result<Vec<RelayAck>, PrivateRelayError> {
+        self.relay(PrivateRelayPayload::Transactions(transactions)).await
+    }
+
+    /// Relays the bundle to all connected relay peers and waits for their acknowledgements.
+    pub async fn send_bundle(
+        &self,
+        bundle: PrivateBundle<T>,
+    ) -> Result<Vec<RelayAck>, PrivateRelayError> {
+        self.relay(PrivateRelayPayload::Bundle(bundle)).await
+    }
+
+    /// Returns a receiver for bundles received from relay peers.
+    pub fn subscribe_bundles(&self) -> broadcast::Receiver<ReceivedBundle<T>> {
+        self.bundles.subscribe()
+    }
+
+    async fn relay(
+        &self,
+        payload: PrivateRelayPayload<T>,
+    ) -> Result<Vec<RelayAck>, PrivateRelayError> {
+        let (response, rx) = oneshot::channel();
+        self.to_manager
+            .send(PrivateRelayCommand::Relay { payload, response })
+            .map_err(|_| PrivateRelayError::ManagerClosed)?;
+        rx.await.map_err(|_| PrivateRelayError::ManagerClosed)?
+    }
+}
+
+/// Provides access to the private relay of the network.
+pub trait PrivateRelayProvider: NetworkEventListenerProvider {
+    /// Returns the handle to the private relay, or `None` if no relay peers are configured.
+    fn private_relay(
+        &self,
+    ) -> Option<&PrivateRelayHandle<<Self::Primitives as NetworkPrimitives>::PooledTransaction>>;
+}
@@ -32,4 +32,5 @@ pub use peers::{
     state::PeerConnectionState,
     ConnectionsConfig, Peer, PeersConfig, PersistedPeerInfo,
 };
-pub use session::{SessionLimits, SessionsConfig};
+pub use reth_net_banlist::{BanEntry, BanTarget};
+pub use session::{BandwidthLimits, SessionLimits, SessionsConfig};
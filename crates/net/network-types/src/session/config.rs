@@ -50,6 +50,10 @@ pub struct SessionsConfig {
     pub protocol_breach_request_timeout: Duration,
     /// The timeout after which a pending session attempt is considered failed.
     pub pending_session_timeout: Duration,
+    /// Upload and download rate limits.
+    ///
+    /// By default, no limits will be enforced.
+    pub bandwidth: BandwidthLimits,
 }
 
 impl Default for SessionsConfig {
@@ -69,6 +73,7 @@ impl Default for SessionsConfig {
             initial_internal_request_timeout: INITIAL_REQUEST_TIMEOUT,
             protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
             pending_session_timeout: PENDING_SESSION_TIMEOUT,
+            bandwidth: Default::default(),
         }
     }
 }
@@ -100,6 +105,12 @@ impl SessionsConfig {
         }
         self
     }
+
+    /// Sets the upload and download rate limits of sessions.
+    pub const fn with_bandwidth_limits(mut self, limits: BandwidthLimits) -> Self {
+        self.bandwidth = limits;
+        self
+    }
 }
 
 /// Limits for sessions.
@@ -144,6 +155,73 @@ impl SessionLimits {
     }
 }
 
+/// Upload and download rate limits for sessions, in bytes per second.
+///
+/// Rates are measured on the uncompressed message payloads. Each limit allows bursts of up to one
+/// second worth of traffic. Served bulk responses (block bodies, receipts and `snap` responses) are
+/// throttled first, gossip and our own requests only once the limit is fully exhausted.
+///
+/// By default, no limits will be enforced and traffic is not measured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BandwidthLimits {
+    /// Maximum upload rate across all sessions.
+    pub max_upload: Option<u64>,
+    /// Maximum download rate across all sessions.
+    pub max_download: Option<u64>,
+    /// Maximum upload rate of a single session.
+    pub max_peer_upload: Option<u64>,
+    /// Maximum download rate of a single session.
+    pub max_peer_download: Option<u64>,
+    /// Whether the traffic of sessions is accounted by message type even if no limits are set.
+    ///
+    /// Traffic is always measured if a limit is set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub accounting: bool,
+}
+
+impl BandwidthLimits {
+    /// Sets the maximum upload rate across all sessions.
+    pub const fn with_max_upload(mut self, limit: u64) -> Self {
+        self.max_upload = Some(limit);
+        self
+    }
+
+    /// Sets the maximum download rate across all sessions.
+    pub const fn with_max_download(mut self, limit: u64) -> Self {
+        self.max_download = Some(limit);
+        self
+    }
+
+    /// Sets the maximum upload rate of a single session.
+    pub const fn with_max_peer_upload(mut self, limit: u64) -> Self {
+        self.max_peer_upload = Some(limit);
+        self
+    }
+
+    /// Sets the maximum download rate of a single session.
+    pub const fn with_max_peer_download(mut self, limit: u64) -> Self {
+        self.max_peer_download = Some(limit);
+        self
+    }
+
+    /// Enables accounting of session traffic by message type.
+    pub const fn with_accounting(mut self) -> Self {
+        self.accounting = true;
+        self
+    }
+
+    /// Returns `true` if the traffic of sessions is measured, because accounting is enabled or
+    /// a limit is set.
+    pub const fn is_metered(&self) -> bool {
+        self.accounting ||
+            self.max_upload.is_some() ||
+            self.max_download.is_some() ||
+            self.max_peer_upload.is_some() ||
+            self.max_peer_download.is_some()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -13,6 +13,7 @@ use reth_discv5::NetworkStackId;
 use reth_dns_discovery::DnsDiscoveryConfig;
 use reth_eth_wire::{
     handshake::{EthHandshake, EthRlpxHandshake},
+    recorder::RecorderConfig,
     EthNetworkPrimitives, HelloMessage, HelloMessageWithProtocols, NetworkPrimitives,
     UnifiedStatus,
 };
@@ -103,6 +104,8 @@ pub struct NetworkConfig<C, N: NetworkPrimitives = EthNetworkPrimitives> {
     pub handshake: Arc<dyn EthRlpxHandshake>,
     /// Maximum allowed ETH message size for post-handshake ETH/Snap streams.
     pub eth_max_message_size: usize,
+    /// Records the messages of sessions to disk, if configured.
+    pub message_recorder: Option<RecorderConfig>,
     /// List of block number-hash pairs to check for required blocks.
     /// If non-empty, peers that don't have these blocks will be filtered out.
     pub required_block_hashes: Vec<BlockNumHash>,
@@ -231,6 +234,8 @@ pub struct NetworkConfigBuilder<N: NetworkPrimitives = EthNetworkPrimitives> {
     handshake: Arc<dyn EthRlpxHandshake>,
     /// Maximum allowed ETH message size for post-handshake ETH/Snap streams.
     eth_max_message_size: usize,
+    /// Records the messages of sessions to disk, if configured.
+    message_recorder: Option<RecorderConfig>,
     /// List of block hashes to check for required blocks.
     required_block_hashes: Vec<BlockNumHash>,
     /// Optional network id
@@ -278,6 +283,7 @@ impl<N: NetworkPrimitives> NetworkConfigBuilder<N> {
             nat: None,
             handshake: Arc::new(EthHandshake::default()),
             eth_max_message_size: MAX_MESSAGE_SIZE,
+            message_recorder: None,
             required_block_hashes: Vec::new(),
             network_id: None,
             snap_enabled: false,
@@ -626,6 +632,12 @@ impl<N: NetworkPrimitives> NetworkConfigBuilder<N> {
         self
     }
 
+    /// Records the messages of sessions to disk, see [`RecorderConfig`].
+    pub fn message_recorder(mut self, recorder: Option<RecorderConfig>) -> Self {
+        self.message_recorder = recorder;
+        self
+    }
+
     /// Set the optional network id.
     pub const fn network_id(mut self, network_id: Option<u64>) -> Self {
         self.network_id = network_id;
@@ -665,6 +677,7 @@ impl<N: NetworkPrimitives> NetworkConfigBuilder<N> {
             nat,
             handshake,
             eth_max_message_size,
+            message_recorder,
             required_block_hashes,
             network_id,
             snap_enabled,
@@ -752,6 +765,7 @@ impl<N: NetworkPrimitives> NetworkConfigBuilder<N> {
             nat,
             handshake,
             eth_max_message_size,
+            message_recorder,
             required_block_hashes,
         }
     }
@@ -14,7 +14,7 @@ use reth_dns_discovery::{
 use reth_ethereum_forks::{EnrForkIdEntry, ForkId};
 use reth_network_api::{DiscoveredEvent, DiscoveryEvent};
 use reth_network_peers::{NodeRecord, PeerId};
-use reth_network_types::PeerAddr;
+use reth_network_types::{BanEntry, BanTarget, PeerAddr};
 use secp256k1::SecretKey;
 use std::{
     collections::VecDeque,
@@ -22,6 +22,7 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{ready, Context, Poll},
+    time::{Instant, SystemTime},
 };
 use tokio::{net::UdpSocket, sync::mpsc, task::JoinHandle};
 use tokio_stream::{wrappers::ReceiverStream, Stream};
@@ -289,6 +290,35 @@ impl Discovery {
         }
     }
 
+    /// Bans the target of the [`BanEntry`] in the discovery service until the entry expires.
+    pub(crate) fn ban_entry(&self, entry: &BanEntry) {
+        if let Some(discv4) = &self.discv4 {
+            discv4.ban_entry(entry.clone())
+        }
+        if let Some(discv5) = &self.discv5 {
+            let until = entry.expires_at_instant(SystemTime::now(), Instant::now());
+            match entry.target {
+                BanTarget::Peer(peer_id) => discv5.ban_peer_until(peer_id, until),
+                BanTarget::Ip(ip) => discv5.ban_ip_until(ip, until),
+                BanTarget::Network(net) => discv5.ban_network_until(net, until),
+            }
+        }
+    }
+
+    /// Lifts all bans of the [`BanTarget`] in the discovery service.
+    pub(crate) fn unban(&self, target: BanTarget) {
+        if let Some(discv4) = &self.discv4 {
+            discv4.unban(target)
+        }
+        if let Some(discv5) = &self.discv5 {
+            match target {
+                BanTarget::Peer(peer_id) => discv5.unban_peer(peer_id),
+                BanTarget::Ip(ip) => discv5.unban_ip(ip),
+                BanTarget::Network(net) => discv5.unban_network(net),
+            }
+        }
+    }
+
     /// Returns a shared reference to the discv4.
     pub fn discv4(&self) -> Option<Discv4> {
         self.discv4.clone()
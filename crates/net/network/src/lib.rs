@@ -128,8 +128,10 @@ pub mod config;
 pub mod error;
 pub mod eth_requests;
 pub mod import;
+pub mod light;
 pub mod message;
 pub mod peers;
+pub mod private_relay;
 pub mod protocol;
 pub mod transactions;
 
@@ -148,14 +150,14 @@ mod state;
 mod swarm;
 mod trusted_peers_resolver;
 
-pub use reth_eth_wire::{DisconnectReason, HelloMessageWithProtocols};
+pub use reth_eth_wire::{recorder::RecorderConfig, DisconnectReason, HelloMessageWithProtocols};
 pub use reth_eth_wire_types::{primitives, EthNetworkPrimitives, NetworkPrimitives};
 pub use reth_network_api::{
     events, BlockDownloaderProvider, DiscoveredEvent, DiscoveryEvent, NetworkEvent,
     NetworkEventListenerProvider, NetworkInfo, PeerRequest, PeerRequestSender, Peers, PeersInfo,
 };
 pub use reth_network_p2p::sync::{NetworkSyncUpdater, SyncState};
-pub use reth_network_types::{PeersConfig, SessionsConfig};
+pub use reth_network_types::{BandwidthLimits, PeersConfig, SessionsConfig};
 pub use session::{
     ActiveSessionHandle, ActiveSessionMessage, Direction, EthRlpxConnection, PeerInfo,
     PendingSessionEvent, PendingSessionHandle, PendingSessionHandshakeError, SessionCommand,
@@ -0,0 +1,197 @@
+//! `RLPx` plumbing of the light serving protocol.
+
+use super::{message::LightMessage, LightEvent, LightRequest, MAX_LIGHT_PENDING_REQUESTS};
+use crate::protocol::{ConnectionHandler, OnNotSupported, ProtocolHandler};
+use alloy_primitives::bytes::BytesMut;
+use alloy_rlp::{Decodable, Encodable};
+use futures::{Stream, StreamExt};
+use reth_eth_wire::{
+    capability::SharedCapabilities, multiplex::ProtocolConnection, protocol::Protocol,
+};
+use reth_network_api::{Direction, PeerId};
+use std::{
+    fmt,
+    net::SocketAddr,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::trace;
+
+/// The [`ProtocolHandler`] of the light serving protocol.
+pub struct LightProtocol<H> {
+    to_handler: mpsc::Sender<LightEvent<H>>,
+}
+
+impl<H> LightProtocol<H> {
+    /// Creates a new protocol handler that forwards requests to the given channel.
+    pub(crate) const fn new(to_handler: mpsc::Sender<LightEvent<H>>) -> Self {
+        Self { to_handler }
+    }
+
+    fn connection_handler(&self) -> LightConnectionHandler<H> {
+        LightConnectionHandler { to_handler: self.to_handler.clone() }
+    }
+}
+
+impl<H> fmt::Debug for LightProtocol<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LightProtocol").finish_non_exhaustive()
+    }
+}
+
+impl<H> ProtocolHandler for LightProtocol<H>
+where
+    H: Encodable + Decodable + Send + Sync + Unpin + 'static,
+{
+    type ConnectionHandler = LightConnectionHandler<H>;
+
+    fn on_incoming(&self, _socket_addr: SocketAddr) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+
+    fn on_outgoing(
+        &self,
+        _socket_addr: SocketAddr,
+        _peer_id: PeerId,
+    ) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+}
+
+/// The [`ConnectionHandler`] of the light serving protocol.
+pub struct LightConnectionHandler<H> {
+    to_handler: mpsc::Sender<LightEvent<H>>,
+}
+
+impl<H> fmt::Debug for LightConnectionHandler<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LightConnectionHandler").finish_non_exhaustive()
+    }
+}
+
+impl<H> ConnectionHandler for LightConnectionHandler<H>
+where
+    H: Encodable + Decodable + Send + Sync + Unpin + 'static,
+{
+    type Connection = LightConnection<H>;
+
+    fn protocol(&self) -> Protocol {
+        LightMessage::<H>::protocol()
+    }
+
+    fn on_unsupported_by_peer(
+        self,
+        _supported: &SharedCapabilities,
+        _direction: Direction,
+        _peer_id: PeerId,
+    ) -> OnNotSupported {
+        OnNotSupported::KeepAlive
+    }
+
+    fn into_connection(
+        self,
+        _direction: Direction,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+    ) -> Self::Connection {
+        let (tx, rx) = mpsc::unbounded_channel();
+        LightConnection {
+            peer_id,
+            conn,
+            to_handler: self.to_handler,
+            pending: 0,
+            responses_tx: tx,
+            responses: UnboundedReceiverStream::new(rx),
+        }
+    }
+}
+
+/// A light serving connection with a single peer.
+///
+/// Decoded requests are forwarded to the [`LightRequestHandler`](super::LightRequestHandler),
+/// which answers them via the connection's response channel.
+///
+/// The peer is disconnected if it has more than [`MAX_LIGHT_PENDING_REQUESTS`] unanswered
+/// requests, or if the request queue of the handler is full.
+pub struct LightConnection<H> {
+    peer_id: PeerId,
+    conn: ProtocolConnection,
+    to_handler: mpsc::Sender<LightEvent<H>>,
+    /// Number of forwarded requests that were not answered yet.
+    pending: usize,
+    responses_tx: mpsc::UnboundedSender<LightMessage<H>>,
+    responses: UnboundedReceiverStream<LightMessage<H>>,
+}
+
+impl<H> fmt::Debug for LightConnection<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LightConnection").field("peer_id", &self.peer_id).finish_non_exhaustive()
+    }
+}
+
+impl<H> Stream for LightConnection<H>
+where
+    H: Encodable + Decodable + Unpin,
+{
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Poll::Ready(Some(msg)) = this.responses.poll_next_unpin(cx) {
+                this.pending = this.pending.saturating_sub(1);
+                return Poll::Ready(Some(msg.encoded()))
+            }
+
+            let Some(raw) = ready!(this.conn.poll_next_unpin(cx)) else { return Poll::Ready(None) };
+
+            let msg = match LightMessage::<H>::decode_message(&mut &raw[..]) {
+                Ok(msg) => msg,
+                Err(err) => {
+                    trace!(target: "net::light", peer_id=%this.peer_id, %err, "malformed light message");
+                    return Poll::Ready(None)
+                }
+            };
+
+            let (request_id, request) = match msg {
+                LightMessage::GetHeaderRange(pair) => {
+                    (pair.request_id, LightRequest::HeaderRange(pair.message))
+                }
+                LightMessage::GetReceiptProofs(pair) => {
+                    (pair.request_id, LightRequest::ReceiptProofs(pair.message))
+                }
+                LightMessage::GetAccountProof(pair) => {
+                    (pair.request_id, LightRequest::AccountProof(pair.message))
+                }
+                // this node only serves, responses are not expected
+                _ => {
+                    trace!(target: "net::light", peer_id=%this.peer_id, "unexpected light response");
+                    return Poll::Ready(None)
+                }
+            };
+
+            if this.pending >= MAX_LIGHT_PENDING_REQUESTS {
+                trace!(target: "net::light", peer_id=%this.peer_id, "too many pending light requests");
+                return Poll::Ready(None)
+            }
+
+            let event = LightEvent {
+                peer_id: this.peer_id,
+                request_id,
+                request,
+                response: this.responses_tx.clone(),
+            };
+            match this.to_handler.try_send(event) {
+                Ok(()) => this.pending += 1,
+                Err(TrySendError::Full(_)) => {
+                    trace!(target: "net::light", peer_id=%this.peer_id, "light request queue is full");
+                    return Poll::Ready(None)
+                }
+                Err(TrySendError::Closed(_)) => return Poll::Ready(None),
+            }
+        }
+    }
+}
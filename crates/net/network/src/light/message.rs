@@ -0,0 +1,296 @@
+//! Messages of the light serving protocol.
+
+use alloy_primitives::{
+    bytes::{Buf, BufMut, BytesMut},
+    Address, Bytes, B256,
+};
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use reth_eth_wire::{message::RequestPair, protocol::Protocol, Capability};
+
+/// The `light` capability of the light serving protocol.
+pub const LIGHT_CAPABILITY: Capability = Capability::new_static("light", 1);
+
+/// Ids of the light serving protocol messages.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightMessageId {
+    /// See [`LightMessage::GetHeaderRange`].
+    GetHeaderRange = 0x00,
+    /// See [`LightMessage::HeaderRange`].
+    HeaderRange = 0x01,
+    /// See [`LightMessage::GetReceiptProofs`].
+    GetReceiptProofs = 0x02,
+    /// See [`LightMessage::ReceiptProofs`].
+    ReceiptProofs = 0x03,
+    /// See [`LightMessage::GetAccountProof`].
+    GetAccountProof = 0x04,
+    /// See [`LightMessage::AccountProof`].
+    AccountProof = 0x05,
+    /// See [`LightMessage::Throttled`].
+    Throttled = 0x06,
+    /// See [`LightMessage::Error`].
+    Error = 0x07,
+}
+
+impl LightMessageId {
+    /// Number of messages of the protocol.
+    pub const COUNT: u8 = 8;
+
+    /// Returns the message id for the given byte.
+    pub const fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            0x00 => Some(Self::GetHeaderRange),
+            0x01 => Some(Self::HeaderRange),
+            0x02 => Some(Self::GetReceiptProofs),
+            0x03 => Some(Self::ReceiptProofs),
+            0x04 => Some(Self::GetAccountProof),
+            0x05 => Some(Self::AccountProof),
+            0x06 => Some(Self::Throttled),
+            0x07 => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Requests a contiguous range of canonical headers in ascending order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct GetHeaderRange {
+    /// Number of the first header.
+    pub start: u64,
+    /// Number of headers.
+    pub count: u64,
+}
+
+/// Canonical headers with a canonical hash trie proof.
+///
+/// The headers are linked by their parent hashes. If the last header belongs to a finalized
+/// section of [`CHT_SECTION_SIZE`](super::CHT_SECTION_SIZE) blocks, `proof` proves its hash
+/// against the canonical hash trie root of that section, otherwise it is empty.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct HeaderRange<H> {
+    /// The requested headers, may be truncated.
+    pub headers: Vec<H>,
+    /// Canonical hash trie nodes on the path to the last header.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests receipts of a block with a proof against its `receipts_root`.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct GetReceiptProofs {
+    /// Hash of the block.
+    pub block_hash: B256,
+    /// Indices of the receipts within the block.
+    pub indices: Vec<u64>,
+}
+
+/// Receipts of a block with a proof against its `receipts_root`.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct ReceiptProofs {
+    /// EIP-2718 encoded receipts with bloom, i.e. the receipt trie values, in request order.
+    ///
+    /// Empty if the block is unknown or an index is out of bounds.
+    pub receipts: Vec<Bytes>,
+    /// Receipt trie nodes on the paths to the requested receipts.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests the proof of an account and some of its storage slots against the `state_root` of a
+/// block.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct GetAccountProof {
+    /// Hash of the block.
+    pub block_hash: B256,
+    /// The account.
+    pub address: Address,
+    /// The storage slots.
+    pub slots: Vec<B256>,
+}
+
+/// Proof of an account and some of its storage slots.
+///
+/// Both proofs are empty if the state of the block is no longer available.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct AccountProof {
+    /// Account trie nodes on the path to the account.
+    pub account_proof: Vec<Bytes>,
+    /// Storage trie nodes on the paths to the requested slots.
+    pub storage_proof: Vec<Bytes>,
+}
+
+/// Reasons a request could not be served, see [`LightMessage::Error`].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightErrorCode {
+    /// The serving node failed to read the requested data, the request may be retried later.
+    Internal = 0x00,
+}
+
+impl Encodable for LightErrorCode {
+    fn encode(&self, out: &mut dyn BufMut) {
+        (*self as u8).encode(out)
+    }
+
+    fn length(&self) -> usize {
+        (*self as u8).length()
+    }
+}
+
+impl Decodable for LightErrorCode {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        match u8::decode(buf)? {
+            0x00 => Ok(Self::Internal),
+            _ => Err(alloy_rlp::Error::Custom("unknown light error code")),
+        }
+    }
+}
+
+/// A message of the light serving protocol.
+///
+/// Every request is answered with the matching response, with [`LightMessage::Throttled`] if
+/// the peer exceeded its serving budget, or with [`LightMessage::Error`] if it could not be
+/// served. Empty responses mean that the requested data is not available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LightMessage<H> {
+    /// Requests a range of canonical headers.
+    GetHeaderRange(RequestPair<GetHeaderRange>),
+    /// Response to [`LightMessage::GetHeaderRange`].
+    HeaderRange(RequestPair<HeaderRange<H>>),
+    /// Requests receipts with a proof.
+    GetReceiptProofs(RequestPair<GetReceiptProofs>),
+    /// Response to [`LightMessage::GetReceiptProofs`].
+    ReceiptProofs(RequestPair<ReceiptProofs>),
+    /// Requests an account proof.
+    GetAccountProof(RequestPair<GetAccountProof>),
+    /// Response to [`LightMessage::GetAccountProof`].
+    AccountProof(RequestPair<AccountProof>),
+    /// The request was rejected because the peer exceeded its serving budget, carries the number
+    /// of milliseconds after which the request can be retried.
+    Throttled(RequestPair<u64>),
+    /// The request could not be served.
+    Error(RequestPair<LightErrorCode>),
+}
+
+impl<H> LightMessage<H> {
+    /// Returns the protocol to announce for light serving.
+    pub const fn protocol() -> Protocol {
+        Protocol::new(LIGHT_CAPABILITY, LightMessageId::COUNT)
+    }
+
+    /// Returns the id of the message.
+    pub const fn message_id(&self) -> LightMessageId {
+        match self {
+            Self::GetHeaderRange(_) => LightMessageId::GetHeaderRange,
+            Self::HeaderRange(_) => LightMessageId::HeaderRange,
+            Self::GetReceiptProofs(_) => LightMessageId::GetReceiptProofs,
+            Self::ReceiptProofs(_) => LightMessageId::ReceiptProofs,
+            Self::GetAccountProof(_) => LightMessageId::GetAccountProof,
+            Self::AccountProof(_) => LightMessageId::AccountProof,
+            Self::Throttled(_) => LightMessageId::Throttled,
+            Self::Error(_) => LightMessageId::Error,
+        }
+    }
+}
+
+impl<H: Encodable> LightMessage<H> {
+    /// Encodes the message, prefixed with its message id.
+    pub fn encoded(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(self.message_id() as u8);
+        match self {
+            Self::GetHeaderRange(pair) => pair.encode(&mut buf),
+            Self::HeaderRange(pair) => pair.encode(&mut buf),
+            Self::GetReceiptProofs(pair) => pair.encode(&mut buf),
+            Self::ReceiptProofs(pair) => pair.encode(&mut buf),
+            Self::GetAccountProof(pair) => pair.encode(&mut buf),
+            Self::AccountProof(pair) => pair.encode(&mut buf),
+            Self::Throttled(pair) => pair.encode(&mut buf),
+            Self::Error(pair) => pair.encode(&mut buf),
+        }
+        buf
+    }
+}
+
+impl<H: Decodable> LightMessage<H> {
+    /// Decodes a message that is prefixed with its message id.
+    pub fn decode_message(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let id = *buf.first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        buf.advance(1);
+        let msg = match LightMessageId::from_u8(id) {
+            Some(LightMessageId::GetHeaderRange) => Self::GetHeaderRange(RequestPair::decode(buf)?),
+            Some(LightMessageId::HeaderRange) => Self::HeaderRange(RequestPair::decode(buf)?),
+            Some(LightMessageId::GetReceiptProofs) => {
+                Self::GetReceiptProofs(RequestPair::decode(buf)?)
+            }
+            Some(LightMessageId::ReceiptProofs) => Self::ReceiptProofs(RequestPair::decode(buf)?),
+            Some(LightMessageId::GetAccountProof) => {
+                Self::GetAccountProof(RequestPair::decode(buf)?)
+            }
+            Some(LightMessageId::AccountProof) => Self::AccountProof(RequestPair::decode(buf)?),
+            Some(LightMessageId::Throttled) => Self::Throttled(RequestPair::decode(buf)?),
+            Some(LightMessageId::Error) => Self::Error(RequestPair::decode(buf)?),
+            None => return Err(alloy_rlp::Error::Custom("unknown light message id")),
+        };
+        if !buf.is_empty() {
+            return Err(alloy_rlp::Error::UnexpectedLength)
+        }
+
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::Header;
+
+    #[test]
+    fn roundtrip_messages() {
+        let messages: [LightMessage<Header>; 8] = [
+            LightMessage::GetHeaderRange(RequestPair {
+                request_id: 1,
+                message: GetHeaderRange { start: 100, count: 10 },
+            }),
+            LightMessage::HeaderRange(RequestPair {
+                request_id: 1,
+                message: HeaderRange {
+                    headers: vec![Header::default()],
+                    proof: vec![Bytes::from_static(&[0xc0])],
+                },
+            }),
+            LightMessage::GetReceiptProofs(RequestPair {
+                request_id: 2,
+                message: GetReceiptProofs { block_hash: B256::repeat_byte(1), indices: vec![0, 3] },
+            }),
+            LightMessage::ReceiptProofs(RequestPair {
+                request_id: 2,
+                message: ReceiptProofs {
+                    receipts: vec![Bytes::from_static(&[1, 2])],
+                    proof: vec![Bytes::from_static(&[3])],
+                },
+            }),
+            LightMessage::GetAccountProof(RequestPair {
+                request_id: 3,
+                message: GetAccountProof {
+                    block_hash: B256::repeat_byte(2),
+                    address: Address::repeat_byte(3),
+                    slots: vec![B256::repeat_byte(4)],
+                },
+            }),
+            LightMessage::AccountProof(RequestPair {
+                request_id: 3,
+                message: AccountProof {
+                    account_proof: vec![Bytes::from_static(&[5])],
+                    storage_proof: vec![],
+                },
+            }),
+            LightMessage::Throttled(RequestPair { request_id: 4, message: 250 }),
+            LightMessage::Error(RequestPair { request_id: 5, message: LightErrorCode::Internal }),
+        ];
+
+        for msg in messages {
+            let encoded = msg.encoded();
+            assert_eq!(encoded[0], msg.message_id() as u8);
+            assert_eq!(LightMessage::decode_message(&mut &encoded[..]).unwrap(), msg);
+        }
+    }
+}
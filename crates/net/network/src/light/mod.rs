@@ -0,0 +1,584 @@
+//! Light serving of headers, receipts and state with Merkle proofs.
+//!
+//! The light serving protocol is an additional `RLPx` subprotocol (`light/1`) for
+//! resource-constrained clients that cannot follow the chain themselves. Every response can be
+//! verified against data the client already trusts:
+//!
+//! - header ranges come with a proof of the last header against the canonical hash trie (CHT) root
+//!   of its finalized section of [`CHT_SECTION_SIZE`] blocks, similar to LES,
+//! - receipts come with a proof against the block's `receipts_root`,
+//! - accounts and storage slots come with a proof against the block's `state_root`.
+//!
+//! Requests have a cost, and every peer gets a budget of cost units per second. Requests that
+//! exceed the budget are answered with [`LightMessage::Throttled`]. Budgets are kept across
+//! reconnects until they are refilled.
+
+use crate::{
+    budget::DEFAULT_BUDGET_TRY_DRAIN_DOWNLOADERS, cache::LruMap, poll_nested_stream_with_budget,
+};
+use alloy_consensus::{BlockHeader, ReceiptWithBloom};
+use alloy_eips::{eip2718::Encodable2718, BlockHashOrNumber};
+use alloy_primitives::{keccak256, Bytes};
+use futures::StreamExt;
+use proof::CanonicalHashTrie;
+use reth_eth_wire::message::RequestPair;
+use reth_network_api::PeerId;
+use reth_storage_api::{
+    errors::provider::ProviderResult, AccountProofNodes, BlockIdReader, BlockReader,
+    StateRangeProviderFactory,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, trace};
+
+mod connection;
+mod message;
+pub mod proof;
+
+pub use connection::{LightConnection, LightConnectionHandler, LightProtocol};
+pub use message::{
+    AccountProof, GetAccountProof, GetHeaderRange, GetReceiptProofs, HeaderRange, LightErrorCode,
+    LightMessage, LightMessageId, ReceiptProofs, LIGHT_CAPABILITY,
+};
+
+/// Number of blocks covered by one canonical hash trie.
+pub const CHT_SECTION_SIZE: u64 = 8192;
+
+/// Maximum number of headers served per request.
+pub const MAX_LIGHT_HEADERS_SERVE: u64 = 1024;
+
+/// Maximum number of receipts served per request.
+pub const MAX_LIGHT_RECEIPTS_SERVE: usize = 1024;
+
+/// Maximum number of storage slots proven per request.
+pub const MAX_LIGHT_SLOTS_SERVE: usize = 256;
+
+/// Default serving budget of a peer in cost units per second.
+pub const DEFAULT_LIGHT_SERVE_BUDGET: u64 = 2048;
+
+/// Maximum number of unanswered requests of a peer, the peer is disconnected if it sends more.
+pub const MAX_LIGHT_PENDING_REQUESTS: usize = 32;
+
+/// Maximum number of requests queued for the [`LightRequestHandler`].
+const LIGHT_REQUEST_QUEUE_SIZE: usize = 1024;
+
+/// Number of canonical hash tries of finalized sections kept in memory.
+const CHT_CACHE_SIZE: u32 = 8;
+
+/// Interval at which refilled serving budgets are dropped.
+const BUDGET_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Cost of a canonical hash trie proof, which hashes an entire section.
+const CHT_PROOF_COST: u64 = CHT_SECTION_SIZE / 64;
+
+/// Base cost of a receipt proof, which loads and hashes all receipts of a block.
+const RECEIPT_PROOF_COST: u64 = 16;
+
+/// Base cost of an account proof.
+const ACCOUNT_PROOF_COST: u64 = 32;
+
+/// Cost of every proven storage slot.
+const SLOT_PROOF_COST: u64 = 4;
+
+/// Configuration of light serving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LightServeConfig {
+    /// Serving budget of every peer in cost units per second.
+    ///
+    /// A header costs one unit, a receipt proof 16 units plus one per receipt, an account proof
+    /// 32 units plus 4 per storage slot, and a canonical hash trie proof 128 units.
+    pub budget_per_second: u64,
+}
+
+impl Default for LightServeConfig {
+    fn default() -> Self {
+        Self { budget_per_second: DEFAULT_LIGHT_SERVE_BUDGET }
+    }
+}
+
+/// A request of the light serving protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LightRequest {
+    HeaderRange(GetHeaderRange),
+    ReceiptProofs(GetReceiptProofs),
+    AccountProof(GetAccountProof),
+}
+
+impl LightRequest {
+    /// Returns the serving cost of the request.
+    fn cost(&self) -> u64 {
+        match self {
+            Self::HeaderRange(req) => req.count.min(MAX_LIGHT_HEADERS_SERVE) + CHT_PROOF_COST,
+            Self::ReceiptProofs(req) => {
+                RECEIPT_PROOF_COST + req.indices.len().min(MAX_LIGHT_RECEIPTS_SERVE) as u64
+            }
+            Self::AccountProof(req) => {
+                ACCOUNT_PROOF_COST +
+                    SLOT_PROOF_COST * req.slots.len().min(MAX_LIGHT_SLOTS_SERVE) as u64
+            }
+        }
+    }
+}
+
+/// A request sent from a light connection to the [`LightRequestHandler`].
+#[derive(Debug)]
+pub(crate) struct LightEvent<H> {
+    /// The requesting peer.
+    peer_id: PeerId,
+    /// The id of the request.
+    request_id: u64,
+    /// The request.
+    request: LightRequest,
+    /// Where to send the response.
+    response: mpsc::UnboundedSender<LightMessage<H>>,
+}
+
+/// The serving budget of a single peer.
+#[derive(Debug)]
+struct ServingBudget {
+    /// Available cost units.
+    tokens: f64,
+    /// When the budget was last refilled.
+    updated: Instant,
+}
+
+impl ServingBudget {
+    fn new(capacity: f64) -> Self {
+        Self { tokens: capacity, updated: Instant::now() }
+    }
+
+    /// Refills the budget for the time elapsed since the last update.
+    fn refill(&mut self, now: Instant, rate: f64) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = elapsed.mul_add(rate, self.tokens).min(rate);
+        self.updated = self.updated.max(now);
+    }
+
+    /// Returns `true` if the budget is refilled completely, i.e. it's equal to a new budget.
+    fn is_full(&mut self, now: Instant, rate: f64) -> bool {
+        self.refill(now, rate);
+        self.tokens >= rate
+    }
+
+    /// Takes `cost` units from the budget, or returns the time after which enough units are
+    /// available.
+    fn try_consume(&mut self, now: Instant, cost: f64, rate: f64) -> Result<(), Duration> {
+        self.refill(now, rate);
+
+        if self.tokens < cost {
+            return Err(Duration::from_secs_f64((cost - self.tokens) / rate))
+        }
+        self.tokens -= cost;
+        Ok(())
+    }
+}
+
+/// Serves light requests from the provider.
+///
+/// The handler must be spawned and its [`LightRequestHandler::protocol`] must be registered with
+/// the network as an additional `RLPx` subprotocol. Requests are served from the database, so
+/// it should run on a blocking task.
+#[must_use = "Handler does nothing unless polled."]
+pub struct LightRequestHandler<C: BlockReader> {
+    /// The client type that can interact with the chain.
+    client: C,
+    /// The serving configuration.
+    config: LightServeConfig,
+    /// Sender half for connection events, handed to the protocol handler.
+    events_tx: mpsc::Sender<LightEvent<C::Header>>,
+    /// Events from light connections.
+    events: ReceiverStream<LightEvent<C::Header>>,
+    /// Serving budgets of peers that recently sent requests.
+    ///
+    /// Budgets outlive connections, so that reconnecting doesn't reset them, and are dropped
+    /// once they are refilled.
+    budgets: HashMap<PeerId, ServingBudget>,
+    /// When refilled budgets were last dropped.
+    last_budget_prune: Instant,
+    /// Canonical hash tries of recently served finalized sections, keyed by their first block.
+    cht_cache: LruMap<u64, CanonicalHashTrie>,
+}
+
+impl<C: BlockReader> fmt::Debug for LightRequestHandler<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LightRequestHandler")
+            .field("config", &self.config)
+            .field("peers", &self.budgets.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> LightRequestHandler<C>
+where
+    C: BlockReader + BlockIdReader + StateRangeProviderFactory,
+{
+    /// Creates a new handler serving from the given client.
+    pub fn new(client: C, config: LightServeConfig) -> Self {
+        let (events_tx, events) = mpsc::channel(LIGHT_REQUEST_QUEUE_SIZE);
+        Self {
+            client,
+            config,
+            events_tx,
+            events: ReceiverStream::new(events),
+            budgets: HashMap::default(),
+            last_budget_prune: Instant::now(),
+            cht_cache: LruMap::new(CHT_CACHE_SIZE),
+        }
+    }
+
+    /// Returns the protocol handler that must be registered with the network.
+    pub fn protocol(&self) -> LightProtocol<C::Header> {
+        LightProtocol::new(self.events_tx.clone())
+    }
+
+    fn on_event(&mut self, event: LightEvent<C::Header>) {
+        let LightEvent { peer_id, request_id, request, response } = event;
+        let now = Instant::now();
+        let rate = self.config.budget_per_second.max(1) as f64;
+        self.prune_budgets(now, rate);
+
+        // requests above the budget would never be served, so charge at most the budget
+        let cost = (request.cost() as f64).min(rate);
+        let budget = self.budgets.entry(peer_id).or_insert_with(|| ServingBudget::new(rate));
+        if let Err(retry_after) = budget.try_consume(now, cost, rate) {
+            trace!(target: "net::light", %peer_id, ?request, ?retry_after, "throttled light request");
+            let retry_after = retry_after.as_millis().try_into().unwrap_or(u64::MAX);
+            response
+                .send(LightMessage::Throttled(RequestPair { request_id, message: retry_after }))
+                .ok();
+            return
+        }
+
+        let msg = self.on_request(peer_id, request_id, request);
+        response.send(msg).ok();
+    }
+
+    /// Drops the budgets that are refilled, at most once per [`BUDGET_PRUNE_INTERVAL`].
+    fn prune_budgets(&mut self, now: Instant, rate: f64) {
+        if now.saturating_duration_since(self.last_budget_prune) < BUDGET_PRUNE_INTERVAL {
+            return
+        }
+        self.last_budget_prune = now;
+        self.budgets.retain(|_, budget| !budget.is_full(now, rate));
+    }
+
+    fn on_request(
+        &mut self,
+        peer_id: PeerId,
+        request_id: u64,
+        request: LightRequest,
+    ) -> LightMessage<C::Header> {
+        let res = match request {
+            LightRequest::HeaderRange(req) => self
+                .get_header_range(req)
+                .map(|message| LightMessage::HeaderRange(RequestPair { request_id, message })),
+            LightRequest::ReceiptProofs(req) => self
+                .get_receipt_proofs(req)
+                .map(|message| LightMessage::ReceiptProofs(RequestPair { request_id, message })),
+            LightRequest::AccountProof(req) => self
+                .get_account_proof(req)
+                .map(|message| LightMessage::AccountProof(RequestPair { request_id, message })),
+        };
+
+        res.unwrap_or_else(|err| {
+            debug!(target: "net::light", %peer_id, %err, "failed to serve light request");
+            LightMessage::Error(RequestPair { request_id, message: LightErrorCode::Internal })
+        })
+    }
+
+    /// Serves canonical headers with a canonical hash trie proof of the last header.
+    fn get_header_range(&mut self, req: GetHeaderRange) -> ProviderResult<HeaderRange<C::Header>> {
+        let count = req.count.min(MAX_LIGHT_HEADERS_SERVE);
+        let best = self.client.best_block_number()?;
+        if count == 0 || req.start > best {
+            return Ok(HeaderRange { headers: Vec::new(), proof: Vec::new() })
+        }
+
+        let end = req.start.saturating_add(count - 1).min(best);
+        let headers = self.client.headers_range(req.start..=end)?;
+        let proof = match headers.last() {
+            Some(last) => self.canonical_hash_trie_proof(last.number())?.unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        Ok(HeaderRange { headers, proof })
+    }
+
+    /// Returns the canonical hash trie proof of the block, if its section is finalized.
+    ///
+    /// The tries of finalized sections never change, so they are cached.
+    fn canonical_hash_trie_proof(&mut self, number: u64) -> ProviderResult<Option<Vec<Bytes>>> {
+        let section_start = number / CHT_SECTION_SIZE * CHT_SECTION_SIZE;
+        if let Some(trie) = self.cht_cache.get(&section_start) {
+            return Ok(Some(trie.proof(number)))
+        }
+
+        let section_end = section_start + CHT_SECTION_SIZE;
+        let finalized = self.client.finalized_block_number()?;
+        if finalized.is_none_or(|finalized| finalized < section_end - 1) {
+            return Ok(None)
+        }
+
+        let hashes = self.client.canonical_hashes_range(section_start, section_end)?;
+        if hashes.len() as u64 != CHT_SECTION_SIZE {
+            return Ok(None)
+        }
+
+        let trie = CanonicalHashTrie::new(section_start, &hashes);
+        trace!(target: "net::light", section_start, root=%trie.root(), "built canonical hash trie");
+        let proof = trie.proof(number);
+        self.cht_cache.insert(section_start, trie);
+
+        Ok(Some(proof))
+    }
+
+    /// Serves receipts of a block with a proof against its `receipts_root`.
+    fn get_receipt_proofs(&self, req: GetReceiptProofs) -> ProviderResult<ReceiptProofs> {
+        let empty = ReceiptProofs { receipts: Vec::new(), proof: Vec::new() };
+        let Some(header) = self.client.header(req.block_hash)? else { return Ok(empty) };
+        let Some(receipts) =
+            self.client.receipts_by_block(BlockHashOrNumber::Hash(req.block_hash))?
+        else {
+            return Ok(empty)
+        };
+
+        let indices = req
+            .indices
+            .iter()
+            .take(MAX_LIGHT_RECEIPTS_SERVE)
+            .map(|index| usize::try_from(*index).ok().filter(|index| *index < receipts.len()))
+            .collect::<Option<Vec<_>>>();
+        let Some(indices) = indices else { return Ok(empty) };
+
+        let receipts = receipts
+            .into_iter()
+            .map(|receipt| ReceiptWithBloom::from(receipt).encoded_2718().into())
+            .collect::<Vec<Bytes>>();
+        let (root, proof) = proof::receipt_trie_proof(&receipts, &indices);
+        if root != header.receipts_root() {
+            debug!(target: "net::light", block_hash=%req.block_hash, "receipt root mismatch");
+            return Ok(empty)
+        }
+
+        Ok(ReceiptProofs {
+            receipts: indices.into_iter().map(|index| receipts[index].clone()).collect(),
+            proof,
+        })
+    }
+
+    /// Serves the proof of an account and its storage slots against the block's `state_root`.
+    fn get_account_proof(&self, req: GetAccountProof) -> ProviderResult<AccountProof> {
+        let empty = AccountProof { account_proof: Vec::new(), storage_proof: Vec::new() };
+        let Some(header) = self.client.header(req.block_hash)? else { return Ok(empty) };
+        let Some(state) = self.client.state_range_provider(header.state_root())? else {
+            return Ok(empty)
+        };
+
+        let slots = req.slots.iter().take(MAX_LIGHT_SLOTS_SERVE).map(keccak256).collect::<Vec<_>>();
+        let AccountProofNodes { account_proof, storage_proof } =
+            state.account_proof(keccak256(req.address), &slots)?;
+
+        Ok(AccountProof { account_proof, storage_proof })
+    }
+}
+
+impl<C> Future for LightRequestHandler<C>
+where
+    C: BlockReader + BlockIdReader + StateRangeProviderFactory + Unpin,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let maybe_more_events = poll_nested_stream_with_budget!(
+            "net::light",
+            "Incoming light requests stream",
+            DEFAULT_BUDGET_TRY_DRAIN_DOWNLOADERS,
+            this.events.poll_next_unpin(cx),
+            |event| this.on_event(event),
+        );
+
+        if maybe_more_events {
+            // make sure we're woken up again
+            cx.waker().wake_by_ref();
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{Header, TxType};
+    use alloy_primitives::{Address, B256};
+    use alloy_trie::proof::verify_proof;
+    use reth_ethereum_primitives::{calculate_receipt_root_no_memo, EthPrimitives, Receipt};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_storage_api::RangeEnd;
+
+    type Provider = MockEthProvider<EthPrimitives>;
+
+    fn handler(provider: Provider, budget_per_second: u64) -> LightRequestHandler<Provider> {
+        LightRequestHandler::new(provider, LightServeConfig { budget_per_second })
+    }
+
+    fn request(
+        handler: &mut LightRequestHandler<Provider>,
+        request: LightRequest,
+    ) -> LightMessage<Header> {
+        let (response, mut rx) = mpsc::unbounded_channel();
+        let peer_id = PeerId::with_last_byte(1);
+        handler.on_event(LightEvent { peer_id, request_id: 1, request, response });
+        rx.try_recv().unwrap()
+    }
+
+    #[test]
+    fn serve_header_range() {
+        let provider = Provider::default();
+        let headers = (0..4)
+            .map(|number| Header { number, ..Default::default() })
+            .map(|header| (header.hash_slow(), header));
+        provider.extend_headers(headers);
+        let mut handler = handler(provider, DEFAULT_LIGHT_SERVE_BUDGET);
+
+        let msg = request(
+            &mut handler,
+            LightRequest::HeaderRange(GetHeaderRange { start: 1, count: 10 }),
+        );
+        let LightMessage::HeaderRange(RequestPair { message, .. }) = msg else {
+            panic!("unexpected response {msg:?}")
+        };
+        assert_eq!(message.headers.iter().map(|h| h.number).collect::<Vec<_>>(), [1, 2, 3]);
+        // the section of the headers is not finalized yet
+        assert!(message.proof.is_empty());
+    }
+
+    #[test]
+    fn serve_receipt_proofs() {
+        let provider = Provider::default();
+        let receipts = (0..3)
+            .map(|i| Receipt {
+                tx_type: TxType::Eip1559,
+                success: true,
+                cumulative_gas_used: 21_000 * (i + 1),
+                logs: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let receipts_root = calculate_receipt_root_no_memo(&receipts);
+        let header = Header { number: 1, receipts_root, ..Default::default() };
+        let block_hash = header.hash_slow();
+        provider.add_header(block_hash, header);
+        provider.add_receipts(1, receipts);
+        let mut handler = handler(provider, DEFAULT_LIGHT_SERVE_BUDGET);
+
+        let msg = request(
+            &mut handler,
+            LightRequest::ReceiptProofs(GetReceiptProofs { block_hash, indices: vec![2, 0] }),
+        );
+        let LightMessage::ReceiptProofs(RequestPair { message, .. }) = msg else {
+            panic!("unexpected response {msg:?}")
+        };
+        assert_eq!(message.receipts.len(), 2);
+        for (index, receipt) in [2, 0].into_iter().zip(&message.receipts) {
+            verify_proof(
+                receipts_root,
+                proof::receipt_trie_key(index),
+                Some(receipt.to_vec()),
+                &message.proof,
+            )
+            .unwrap();
+        }
+
+        // out of bounds indices are not served
+        let msg = request(
+            &mut handler,
+            LightRequest::ReceiptProofs(GetReceiptProofs { block_hash, indices: vec![3] }),
+        );
+        let LightMessage::ReceiptProofs(RequestPair { message, .. }) = msg else {
+            panic!("unexpected response {msg:?}")
+        };
+        assert!(message.receipts.is_empty() && message.proof.is_empty());
+    }
+
+    #[test]
+    fn serve_account_proof() {
+        let provider = Provider::default();
+        let header = Header::default();
+        let block_hash = header.hash_slow();
+        provider.add_header(block_hash, header);
+        let account_proof = vec![Bytes::from_static(&[0xc0])];
+        provider.set_snap_account_range(Vec::new(), RangeEnd::Exhausted);
+        provider.set_snap_account_proof(Some(account_proof.clone()));
+        let mut handler = handler(provider, DEFAULT_LIGHT_SERVE_BUDGET);
+
+        let msg = request(
+            &mut handler,
+            LightRequest::AccountProof(GetAccountProof {
+                block_hash,
+                address: Address::repeat_byte(1),
+                slots: vec![B256::ZERO],
+            }),
+        );
+        assert_eq!(
+            msg,
+            LightMessage::AccountProof(RequestPair {
+                request_id: 1,
+                message: AccountProof { account_proof, storage_proof: Vec::new() },
+            })
+        );
+    }
+
+    #[test]
+    fn throttle_requests_over_budget() {
+        let mut handler = handler(Provider::default(), CHT_PROOF_COST + 10);
+        let req = LightRequest::HeaderRange(GetHeaderRange { start: 0, count: 10 });
+
+        assert!(matches!(request(&mut handler, req.clone()), LightMessage::HeaderRange(_)));
+        let LightMessage::Throttled(RequestPair { message: retry_after, .. }) =
+            request(&mut handler, req)
+        else {
+            panic!("request was not throttled")
+        };
+        assert!(retry_after > 0);
+
+        // the budget is only dropped once it's refilled
+        let rate = handler.config.budget_per_second as f64;
+        handler.prune_budgets(Instant::now() + BUDGET_PRUNE_INTERVAL / 2, rate);
+        assert_eq!(handler.budgets.len(), 1);
+        handler.prune_budgets(Instant::now() + BUDGET_PRUNE_INTERVAL * 2, rate);
+        assert!(handler.budgets.is_empty());
+    }
+
+    #[test]
+    fn report_serving_errors() {
+        let provider = Provider::default();
+        let header = Header::default();
+        let block_hash = header.hash_slow();
+        provider.add_header(block_hash, header);
+        provider.set_snap_state_reads_fail(true);
+        let mut handler = handler(provider, DEFAULT_LIGHT_SERVE_BUDGET);
+
+        let msg = request(
+            &mut handler,
+            LightRequest::AccountProof(GetAccountProof {
+                block_hash,
+                address: Address::repeat_byte(1),
+                slots: Vec::new(),
+            }),
+        );
+        assert_eq!(
+            msg,
+            LightMessage::Error(RequestPair { request_id: 1, message: LightErrorCode::Internal })
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Merkle proofs served by the light serving protocol.
+//!
+//! Clients verify the served proofs with [`alloy_trie::proof::verify_proof`], using the keys
+//! returned by [`canonical_hash_trie_key`] and [`receipt_trie_key`].
+
+use alloy_primitives::{Bytes, B256};
+use alloy_trie::{
+    proof::{ProofNodes, ProofRetainer},
+    root::adjust_index_for_rlp,
+    HashBuilder, Nibbles,
+};
+
+/// Returns the key of a block in the canonical hash trie of its section.
+///
+/// The canonical hash trie maps the big-endian block number to the RLP encoded block hash.
+pub fn canonical_hash_trie_key(number: u64) -> Nibbles {
+    Nibbles::unpack(number.to_be_bytes())
+}
+
+/// Returns the key of the receipt at `index` in the receipt trie of a block.
+pub fn receipt_trie_key(index: usize) -> Nibbles {
+    Nibbles::unpack(alloy_rlp::encode_fixed_size(&index))
+}
+
+/// The canonical hash trie of a section, with all of its nodes retained so that proofs of any
+/// block of the section can be served without rebuilding the trie.
+#[derive(Debug)]
+pub(crate) struct CanonicalHashTrie {
+    /// The root of the trie.
+    root: B256,
+    /// All nodes of the trie.
+    nodes: ProofNodes,
+}
+
+impl CanonicalHashTrie {
+    /// Builds the canonical hash trie of the blocks starting at `start` with the given `hashes`.
+    pub(crate) fn new(start: u64, hashes: &[B256]) -> Self {
+        let end = start + hashes.len() as u64;
+        let mut hb = HashBuilder::default()
+            .with_proof_retainer((start..end).map(canonical_hash_trie_key).collect());
+        // big-endian keys of consecutive numbers are already sorted
+        for (number, hash) in (start..).zip(hashes) {
+            hb.add_leaf(canonical_hash_trie_key(number), &alloy_rlp::encode(hash));
+        }
+        let root = hb.root();
+
+        Self { root, nodes: hb.take_proof_nodes() }
+    }
+
+    /// Returns the root of the trie.
+    pub(crate) const fn root(&self) -> B256 {
+        self.root
+    }
+
+    /// Returns the proof of block `target`.
+    pub(crate) fn proof(&self, target: u64) -> Vec<Bytes> {
+        self.nodes
+            .matching_nodes_sorted(&canonical_hash_trie_key(target))
+            .into_iter()
+            .map(|(_, node)| node)
+            .collect()
+    }
+}
+
+/// Builds the ordered trie of the given EIP-2718 encoded receipts and returns its root together
+/// with the proof of the receipts at `indices`.
+pub(crate) fn receipt_trie_proof(receipts: &[Bytes], indices: &[usize]) -> (B256, Vec<Bytes>) {
+    let mut hb = HashBuilder::default()
+        .with_proof_retainer(indices.iter().copied().map(receipt_trie_key).collect());
+    for i in 0..receipts.len() {
+        let index = adjust_index_for_rlp(i, receipts.len());
+        hb.add_leaf(receipt_trie_key(index), &receipts[index]);
+    }
+    let root = hb.root();
+    let proof = hb.take_proof_nodes().into_nodes_sorted().into_iter().map(|(_, node)| node);
+
+    (root, proof.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_trie::{proof::verify_proof, root::ordered_trie_root_encoded};
+
+    #[test]
+    fn canonical_hash_trie_proofs_verify() {
+        let start = 8192;
+        let hashes = (0..300u64).map(|i| B256::with_last_byte(i as u8)).collect::<Vec<_>>();
+
+        let trie = CanonicalHashTrie::new(start, &hashes);
+
+        for target in [start, start + 1, start + 128, start + 299] {
+            let hash = hashes[(target - start) as usize];
+            verify_proof(
+                trie.root(),
+                canonical_hash_trie_key(target),
+                Some(alloy_rlp::encode(hash)),
+                &trie.proof(target),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn receipt_trie_proofs_verify() {
+        let receipts =
+            (0..200u8).map(|i| Bytes::from(vec![i; 40 + i as usize % 7])).collect::<Vec<_>>();
+        let indices = [0, 1, 127, 128, 199];
+
+        let (root, proof) = receipt_trie_proof(&receipts, &indices);
+        assert_eq!(root, ordered_trie_root_encoded(&receipts));
+
+        for index in indices {
+            verify_proof(root, receipt_trie_key(index), Some(receipts[index].to_vec()), &proof)
+                .unwrap();
+        }
+    }
+}
@@ -42,23 +42,26 @@ use crate::{
 use futures::{Future, StreamExt};
 use parking_lot::Mutex;
 use reth_chainspec::EnrForkIdEntry;
+use reth_discv4::{Discv4, NatResolver};
+use reth_discv5::Discv5;
 use reth_eth_wire::{DisconnectReason, EthNetworkPrimitives, NetworkPrimitives};
 use reth_fs_util::{self as fs, FsPathError};
 use reth_metrics::common::mpsc::MemoryBoundedSender;
+use reth_net_nat::{ExternalEndpoint, PortMapper, PortMapperHandle, PortMappingConfig};
 use reth_network_api::{
     events::{PeerEvent, SessionInfo},
     test_utils::PeersHandle,
     EthProtocolInfo, NetworkEvent, NetworkStatus, PeerInfo, PeerRequest,
 };
 use reth_network_peers::{NodeRecord, PeerId};
-use reth_network_types::ReputationChangeKind;
+use reth_network_types::{BanEntry, ReputationChangeKind};
 use reth_storage_api::BlockNumReader;
 use reth_tasks::shutdown::GracefulShutdown;
 use reth_tokio_util::EventSender;
 use secp256k1::SecretKey;
 use std::{
     net::SocketAddr,
-    path::Path,
+    path::{Path, PathBuf},
     pin::Pin,
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
@@ -67,7 +70,10 @@ use std::{
     task::{Context, Poll},
     time::{Duration, Instant},
 };
-use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    watch,
+};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, error, trace, warn};
 
@@ -148,6 +154,10 @@ pub struct NetworkManager<N: NetworkPrimitives = EthNetworkPrimitives> {
     pending_session_failure_metrics: PendingSessionFailureMetrics,
     /// Backed off peers metrics, split by reason.
     backed_off_peers_metrics: BackedOffPeersMetrics,
+    /// Keeps the ports mapped on the NAT gateway, if configured.
+    port_mapper: Option<PortMapperHandle>,
+    /// Persists the bans after every change made by the admin, if configured.
+    bans_writer: Option<BansWriter>,
 }
 
 impl NetworkManager {
@@ -261,6 +271,7 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             nat,
             handshake,
             eth_max_message_size,
+            message_recorder,
             required_block_hashes,
         } = config;
 
@@ -305,6 +316,29 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
         let discv4 = discovery.discv4();
         let discv5 = discovery.discv5();
 
+        // forward the listener and discovery ports on the gateway if the resolver supports it
+        let port_mapper =
+            nat.as_ref().and_then(NatResolver::port_mapping_protocol).map(|protocol| {
+                let mut port_mapper = PortMapper::new(PortMappingConfig::new(protocol))
+                    .with_tcp_port(listener_addr.port());
+                let udp_port = discv4
+                    .as_ref()
+                    .map(|discv4| discv4.local_addr().port())
+                    .or_else(|| discv5.as_ref().map(|discv5| discv5.local_port()));
+                if let Some(port) = udp_port {
+                    port_mapper = port_mapper.with_udp_port(port);
+                }
+                let port_mapper = port_mapper.spawn();
+                if discv4.is_some() || discv5.is_some() {
+                    tokio::spawn(announce_external_endpoint(
+                        discv4.clone(),
+                        discv5.clone(),
+                        port_mapper.subscribe(),
+                    ));
+                }
+                port_mapper
+            });
+
         let num_active_peers = Arc::new(AtomicUsize::new(0));
 
         let sessions = SessionManager::new(
@@ -317,6 +351,7 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             extra_protocols,
             handshake,
             eth_max_message_size,
+            message_recorder,
             network_mode.is_stake(),
         );
 
@@ -347,6 +382,7 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             discv5,
             event_sender.clone(),
             nat,
+            port_mapper.as_ref().map(PortMapperHandle::subscribe),
         );
 
         // Spawn required block peer filter if configured
@@ -369,6 +405,8 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             closed_sessions_metrics: Default::default(),
             pending_session_failure_metrics: Default::default(),
             backed_off_peers_metrics: Default::default(),
+            port_mapper,
+            bans_writer: None,
         })
     }
 
@@ -460,6 +498,51 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
         Ok(())
     }
 
+    /// Atomically writes all bans that should outlive a restart to the given
+    /// `persistent_bans_file`.
+    ///
+    /// This includes bans added by the admin and bans of peers with bad reputation, but not the
+    /// short-lived IP bans used to throttle inbound connections.
+    pub fn write_bans_to_file(&self, persistent_bans_file: &Path) -> Result<(), FsPathError> {
+        write_bans(persistent_bans_file, &self.persistable_bans())
+    }
+
+    /// Returns the bans that should outlive a restart, oldest first.
+    fn persistable_bans(&self) -> Vec<BanEntry> {
+        let mut bans = self.swarm.peers().ban_entries().cloned().collect::<Vec<_>>();
+        bans.sort_unstable_by_key(|entry| entry.banned_at);
+        bans
+    }
+
+    /// Restores the bans written by [`Self::write_bans_to_file`], skipping bans that expired in
+    /// the meantime.
+    ///
+    /// From then on, the bans are written to the file on a dedicated thread whenever the admin
+    /// adds or removes a ban, so that they survive a crash.
+    ///
+    /// Ignored if the file does not exist. Returns the number of restored bans.
+    pub fn read_bans_from_file(
+        &mut self,
+        persistent_bans_file: &Path,
+    ) -> Result<usize, FsPathError> {
+        self.bans_writer = Some(
+            BansWriter::spawn(persistent_bans_file.to_path_buf())
+                .map_err(|err| FsPathError::write(err, persistent_bans_file))?,
+        );
+        if !persistent_bans_file.exists() {
+            return Ok(0)
+        }
+        let bans: Vec<BanEntry> = reth_fs_util::read_json_file(persistent_bans_file)?;
+        Ok(self.swarm.peers_mut().restore_bans(bans))
+    }
+
+    /// Writes the bans to the file set by [`Self::read_bans_from_file`], if any.
+    fn persist_bans(&self) {
+        if let Some(writer) = &self.bans_writer {
+            writer.write(self.persistable_bans());
+        }
+    }
+
     /// Returns a new [`FetchClient`] that can be cloned and shared.
     ///
     /// The [`FetchClient`] is the entrypoint for sending requests to the network, including
@@ -740,9 +823,28 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             }
             NetworkHandleMessage::BanPeer(peer_id) => {
                 self.swarm.peers_mut().ban_peer_by_admin(peer_id);
+                self.persist_bans();
             }
             NetworkHandleMessage::UnbanPeer(peer_id) => {
                 self.swarm.peers_mut().unban_peer_by_admin(peer_id);
+                self.persist_bans();
+            }
+            NetworkHandleMessage::AddBan(entry, tx) => {
+                let added = self.swarm.peers_mut().add_ban_by_admin(entry);
+                if added {
+                    self.persist_bans();
+                }
+                let _ = tx.send(added);
+            }
+            NetworkHandleMessage::RemoveBan(target, tx) => {
+                let removed = self.swarm.peers_mut().remove_ban_by_admin(target);
+                if removed {
+                    self.persist_bans();
+                }
+                let _ = tx.send(removed);
+            }
+            NetworkHandleMessage::GetBans(tx) => {
+                let _ = tx.send(self.swarm.peers().ban_entries().cloned().collect());
             }
             NetworkHandleMessage::ConnectPeer(peer_id, kind, addr) => {
                 self.swarm.state_mut().add_and_connect(peer_id, kind, addr);
@@ -1107,6 +1209,13 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
         }
 
         self.perform_network_shutdown();
+        if let Some(port_mapper) = self.port_mapper.take() {
+            port_mapper.shutdown().await;
+        }
+        // let pending writes finish, so that they don't overwrite the bans written on shutdown
+        if let Some(bans_writer) = self.bans_writer.take() {
+            bans_writer.finish();
+        }
         let res = shutdown_hook(self);
         drop(graceful_guard);
         res
@@ -1203,3 +1312,80 @@ struct NetworkManagerPollDurations {
     acc_network_handle: Duration,
     acc_swarm: Duration,
 }
+
+/// Announces the endpoint the node is reachable at through the NAT gateway in discovery, until the
+/// port mapper is stopped.
+///
+/// The local node record is updated by the [`NetworkHandle`] itself, see
+/// [`PeersInfo::local_node_record`](reth_network_api::PeersInfo::local_node_record).
+async fn announce_external_endpoint(
+    discv4: Option<Discv4>,
+    discv5: Option<Discv5>,
+    mut endpoint: watch::Receiver<Option<ExternalEndpoint>>,
+) {
+    while endpoint.changed().await.is_ok() {
+        let Some(ExternalEndpoint { ip, tcp_port, udp_port }) = *endpoint.borrow_and_update()
+        else {
+            continue
+        };
+        debug!(target: "net", %ip, ?tcp_port, ?udp_port, "Announcing mapped external endpoint");
+        if let Some(discv4) = &discv4 {
+            discv4.set_external_ip_addr(ip);
+            if let Some(port) = tcp_port {
+                discv4.set_tcp_port(port);
+            }
+            if let Some(port) = udp_port {
+                discv4.set_udp_port(port);
+            }
+        }
+        if let Some(discv5) = &discv5 {
+            discv5.set_external_endpoint(ip, tcp_port, udp_port);
+        }
+    }
+}
+
+/// Atomically writes the bans to the given file.
+fn write_bans(path: &Path, bans: &[BanEntry]) -> Result<(), FsPathError> {
+    path.parent().map(fs::create_dir_all).transpose()?;
+    reth_fs_util::atomic_write_json_file(path, &bans)?;
+    Ok(())
+}
+
+/// Writes the bans to a file on a dedicated thread, so that the [`NetworkManager`] doesn't block
+/// on file IO while polling.
+#[derive(Debug)]
+struct BansWriter {
+    /// Sends the bans to write to the thread.
+    bans: std::sync::mpsc::Sender<Vec<BanEntry>>,
+    /// The thread writing the bans.
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl BansWriter {
+    /// Spawns the thread writing the bans to `path`.
+    fn spawn(path: PathBuf) -> std::io::Result<Self> {
+        let (bans, rx) = std::sync::mpsc::channel::<Vec<BanEntry>>();
+        let thread =
+            std::thread::Builder::new().name("network-bans".to_string()).spawn(move || {
+                while let Ok(bans) = rx.recv() {
+                    // only the latest bans need to be written if they changed again in the meantime
+                    let bans = rx.try_iter().last().unwrap_or(bans);
+                    if let Err(err) = write_bans(&path, &bans) {
+                        warn!(target: "net", %err, "Failed to write network bans to file");
+                    }
+                }
+            })?;
+        Ok(Self { bans, thread })
+    }
+
+    /// Queues the bans to be written.
+    fn write(&self, bans: Vec<BanEntry>) {
+        let _ = self.bans.send(bans);
+    }
+
+    /// Waits for the queued writes to finish.
+    fn finish(self) {
+        drop(self.bans);
+        let _ = self.thread.join();
+    }
+}
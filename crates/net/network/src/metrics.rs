@@ -1,3 +1,4 @@
+use crate::session::bandwidth::{MessageKind, MESSAGE_KINDS};
 use metrics::Histogram;
 use reth_eth_wire::DisconnectReason;
 use reth_ethereum_primitives::TxType;
@@ -113,6 +114,34 @@ impl Default for PendingSessionFailureMetrics {
     }
 }
 
+/// Bytes exchanged with all peers, split by direction and message type.
+///
+/// Measured on the uncompressed message payloads, see [`MessageKind`].
+#[derive(Debug)]
+pub(crate) struct BandwidthMetrics {
+    /// Bytes received, indexed by [`MessageKind::index`].
+    pub(crate) ingress: [Counter; MESSAGE_KINDS],
+    /// Bytes sent, indexed by [`MessageKind::index`].
+    pub(crate) egress: [Counter; MESSAGE_KINDS],
+}
+
+impl Default for BandwidthMetrics {
+    fn default() -> Self {
+        let mut ingress = std::array::from_fn(|_| Counter::noop());
+        let mut egress = std::array::from_fn(|_| Counter::noop());
+        for kind in MessageKind::all() {
+            let message = kind.name();
+            ingress[kind.index()] = metrics::counter!(
+                "network_bandwidth_bytes", "direction" => "ingress", "message" => message
+            );
+            egress[kind.index()] = metrics::counter!(
+                "network_bandwidth_bytes", "direction" => "egress", "message" => message
+            );
+        }
+        Self { ingress, egress }
+    }
+}
+
 /// Metrics for backed off peers, split by reason.
 #[derive(Metrics)]
 #[metrics(scope = "network.backed_off_peers")]
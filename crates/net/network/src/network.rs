@@ -13,8 +13,10 @@ use reth_eth_wire::{
     NetworkPrimitives, NewPooledTransactionHashes, SharedTransactions,
 };
 use reth_ethereum_forks::Head;
+use reth_net_nat::ExternalEndpoint;
 use reth_network_api::{
     events::{NetworkPeersEvents, PeerEvent, PeerEventStream},
+    private_relay::{PrivateRelayHandle, PrivateRelayProvider},
     test_utils::{PeersHandle, PeersHandleProvider},
     BlockDownloaderProvider, CellCustody, DiscoveryEvent, NetworkError, NetworkEvent,
     NetworkEventListenerProvider, NetworkInfo, NetworkStatus, PeerInfo, PeerRequest, Peers,
@@ -22,19 +24,21 @@ use reth_network_api::{
 };
 use reth_network_p2p::sync::{NetworkSyncUpdater, SyncState, SyncStateProvider};
 use reth_network_peers::{NodeRecord, PeerId, TrustedPeer};
-use reth_network_types::{PeerAddr, PeerKind, Reputation, ReputationChangeKind};
+use reth_network_types::{
+    BanEntry, BanTarget, PeerAddr, PeerKind, Reputation, ReputationChangeKind,
+};
 use reth_tokio_util::{EventSender, EventStream};
 use secp256k1::SecretKey;
 use std::{
     net::SocketAddr,
     sync::{
         atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
-        Arc,
+        Arc, OnceLock,
     },
 };
 use tokio::sync::{
     mpsc::{self, UnboundedSender},
-    oneshot,
+    oneshot, watch,
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
@@ -66,6 +70,7 @@ impl<N: NetworkPrimitives> NetworkHandle<N> {
         discv5: Option<Discv5>,
         event_sender: EventSender<NetworkEvent<PeerRequest<N>>>,
         nat: Option<NatResolver>,
+        external_endpoint: Option<watch::Receiver<Option<ExternalEndpoint>>>,
     ) -> Self {
         let inner = NetworkInner {
             num_active_peers,
@@ -79,20 +84,76 @@ impl<N: NetworkPrimitives> NetworkHandle<N> {
             initial_sync_done: Arc::new(AtomicBool::new(false)),
             chain_id,
             cell_custody: CellCustody::default(),
+            private_relay: OnceLock::new(),
             tx_gossip_disabled,
             discv4,
             discv5,
             event_sender,
             nat,
+            external_endpoint,
         };
         Self { inner: Arc::new(inner) }
     }
 
+    /// Makes the private relay available through [`PrivateRelayProvider::private_relay`].
+    ///
+    /// Has no effect if a relay handle was already set.
+    pub(crate) fn set_private_relay(&self, relay: PrivateRelayHandle<N::PooledTransaction>) {
+        let _ = self.inner.private_relay.set(relay);
+    }
+
     /// Returns the [`PeerId`] used in the network.
     pub fn peer_id(&self) -> &PeerId {
         &self.inner.local_peer_id
     }
 
+    /// Returns the local node record as configured, without the endpoint mapped on the NAT
+    /// gateway.
+    fn resolve_local_node_record(&self) -> NodeRecord {
+        if let Some(discv4) = &self.inner.discv4 {
+            // Note: the discv4 services uses the same `nat` so we can directly return the node
+            // record here
+            discv4.node_record()
+        } else if let Some(discv5) = self.inner.discv5.as_ref() {
+            // for disv5 we must check if we have an external ip configured
+            if let Some(external) =
+                self.inner.nat.clone().and_then(|nat| nat.as_external_ip(discv5.local_port()))
+            {
+                NodeRecord::new((external, discv5.local_port()).into(), *self.peer_id())
+            } else {
+                // use the node record that discv5 tracks or use localhost
+                self.inner.discv5.as_ref().and_then(|d| d.node_record()).unwrap_or_else(|| {
+                    NodeRecord::new(
+                        (std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), discv5.local_port())
+                            .into(),
+                        *self.peer_id(),
+                    )
+                })
+            }
+            // also use the tcp port
+            .with_tcp_port(self.inner.listener_address.lock().port())
+        } else {
+            let mut socket_addr = *self.inner.listener_address.lock();
+
+            let external_ip =
+                self.inner.nat.clone().and_then(|nat| nat.as_external_ip(socket_addr.port()));
+
+            if let Some(ip) = external_ip {
+                // if able to resolve external ip, use it instead and also set the local address
+                socket_addr.set_ip(ip)
+            } else if socket_addr.ip().is_unspecified() {
+                // zero address is invalid
+                if socket_addr.ip().is_ipv4() {
+                    socket_addr.set_ip(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
+                } else {
+                    socket_addr.set_ip(std::net::IpAddr::V6(std::net::Ipv6Addr::LOCALHOST));
+                }
+            }
+
+            NodeRecord::new(socket_addr, *self.peer_id())
+        }
+    }
+
     fn manager(&self) -> &UnboundedSender<NetworkHandleMessage<N>> {
         &self.inner.to_manager_tx
     }
@@ -239,6 +300,12 @@ impl<N: NetworkPrimitives> NetworkEventListenerProvider for NetworkHandle<N> {
     }
 }
 
+impl<N: NetworkPrimitives> PrivateRelayProvider for NetworkHandle<N> {
+    fn private_relay(&self) -> Option<&PrivateRelayHandle<N::PooledTransaction>> {
+        self.inner.private_relay.get()
+    }
+}
+
 impl<N: NetworkPrimitives> NetworkProtocols for NetworkHandle<N> {
     fn add_rlpx_sub_protocol(&self, protocol: RlpxSubProtocol) {
         self.send_message(NetworkHandleMessage::AddRlpxSubProtocol(protocol))
@@ -251,48 +318,18 @@ impl<N: NetworkPrimitives> PeersInfo for NetworkHandle<N> {
     }
 
     fn local_node_record(&self) -> NodeRecord {
-        if let Some(discv4) = &self.inner.discv4 {
-            // Note: the discv4 services uses the same `nat` so we can directly return the node
-            // record here
-            discv4.node_record()
-        } else if let Some(discv5) = self.inner.discv5.as_ref() {
-            // for disv5 we must check if we have an external ip configured
-            if let Some(external) =
-                self.inner.nat.clone().and_then(|nat| nat.as_external_ip(discv5.local_port()))
-            {
-                NodeRecord::new((external, discv5.local_port()).into(), *self.peer_id())
-            } else {
-                // use the node record that discv5 tracks or use localhost
-                self.inner.discv5.as_ref().and_then(|d| d.node_record()).unwrap_or_else(|| {
-                    NodeRecord::new(
-                        (std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), discv5.local_port())
-                            .into(),
-                        *self.peer_id(),
-                    )
-                })
-            }
-            // also use the tcp port
-            .with_tcp_port(self.inner.listener_address.lock().port())
-        } else {
-            let mut socket_addr = *self.inner.listener_address.lock();
-
-            let external_ip =
-                self.inner.nat.clone().and_then(|nat| nat.as_external_ip(socket_addr.port()));
-
-            if let Some(ip) = external_ip {
-                // if able to resolve external ip, use it instead and also set the local address
-                socket_addr.set_ip(ip)
-            } else if socket_addr.ip().is_unspecified() {
-                // zero address is invalid
-                if socket_addr.ip().is_ipv4() {
-                    socket_addr.set_ip(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
-                } else {
-                    socket_addr.set_ip(std::net::IpAddr::V6(std::net::Ipv6Addr::LOCALHOST));
-                }
-            }
-
-            NodeRecord::new(socket_addr, *self.peer_id())
+        let mut record = self.resolve_local_node_record();
+
+        // the endpoint mapped on the NAT gateway takes precedence
+        if let Some(ExternalEndpoint { ip, tcp_port, udp_port }) =
+            self.inner.external_endpoint.as_ref().and_then(|endpoint| *endpoint.borrow())
+        {
+            record.address = ip;
+            record.tcp_port = tcp_port.unwrap_or(record.tcp_port);
+            record.udp_port = udp_port.unwrap_or(record.udp_port);
         }
+
+        record
     }
 
     fn local_enr(&self) -> Enr<SecretKey> {
@@ -400,6 +437,28 @@ impl<N: NetworkPrimitives> Peers for NetworkHandle<N> {
         self.send_message(NetworkHandleMessage::UnbanPeer(peer))
     }
 
+    /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to ban the target of the
+    /// entry.
+    async fn add_ban(&self, entry: BanEntry) -> Result<bool, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::AddBan(entry, tx));
+        Ok(rx.await?)
+    }
+
+    /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to lift all bans of the
+    /// target.
+    async fn remove_ban(&self, target: BanTarget) -> Result<bool, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::RemoveBan(target, tx));
+        Ok(rx.await?)
+    }
+
+    async fn get_bans(&self) -> Result<Vec<BanEntry>, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetBans(tx));
+        Ok(rx.await?)
+    }
+
     /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to connect to the given
     /// peer.
     ///
@@ -533,6 +592,8 @@ struct NetworkInner<N: NetworkPrimitives = EthNetworkPrimitives> {
     chain_id: Arc<AtomicU64>,
     /// Shared blob cell custody bitmap.
     cell_custody: CellCustody,
+    /// Handle to the private relay, if enabled.
+    private_relay: OnceLock<PrivateRelayHandle<N::PooledTransaction>>,
     /// Whether to disable transaction gossip
     tx_gossip_disabled: bool,
     /// The instance of the discv4 service
@@ -543,6 +604,8 @@ struct NetworkInner<N: NetworkPrimitives = EthNetworkPrimitives> {
     event_sender: EventSender<NetworkEvent<PeerRequest<N>>>,
     /// The NAT resolver
     nat: Option<NatResolver>,
+    /// The endpoint the node is reachable at through the NAT gateway, if ports are mapped.
+    external_endpoint: Option<watch::Receiver<Option<ExternalEndpoint>>>,
 }
 
 /// Provides access to modify the network's additional protocol handlers.
@@ -568,6 +631,13 @@ pub(crate) enum NetworkHandleMessage<N: NetworkPrimitives = EthNetworkPrimitives
     BanPeer(PeerId),
     /// Unbans a peer.
     UnbanPeer(PeerId),
+    /// Bans a peer, IP address or range and disconnects the non-trusted peers it covers, returns
+    /// false if the ban was ignored.
+    AddBan(BanEntry, oneshot::Sender<bool>),
+    /// Lifts all bans of a peer, IP address or range and returns whether it was banned.
+    RemoveBan(BanTarget, oneshot::Sender<bool>),
+    /// Gets all bans that are persisted across restarts.
+    GetBans(oneshot::Sender<Vec<BanEntry>>),
     /// Broadcasts an event to announce a new block to all nodes.
     AnnounceBlock(N::NewBlockPayload, B256),
     /// Sends a list of transactions to the given peer.
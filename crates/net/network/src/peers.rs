@@ -12,7 +12,7 @@ use futures::StreamExt;
 use rand::Rng;
 use reth_eth_wire::{errors::EthStreamError, DisconnectReason};
 use reth_ethereum_forks::ForkId;
-use reth_net_banlist::BanList;
+use reth_net_banlist::{BanEntry, BanList, BanTarget};
 use reth_network_api::test_utils::{PeerCommand, PeersHandle};
 use reth_network_peers::{NodeRecord, PeerId, TrustedPeer};
 use reth_network_types::{
@@ -479,7 +479,11 @@ impl PeersManager {
             self.ban_duration
         };
 
-        self.ban_list.ban_peer_until(peer_id, std::time::Instant::now() + ban_duration);
+        self.ban_list.ban(
+            BanEntry::new(BanTarget::Peer(peer_id))
+                .with_reason("bad reputation")
+                .with_duration(ban_duration),
+        );
         self.queued_actions.push_back(PeerAction::BanPeer { peer_id });
     }
 
@@ -946,14 +950,71 @@ impl PeersManager {
     /// This follows [`Self::remove_peer`] and does not override trusted status. Remove trusted
     /// peers from the trusted set before banning them.
     pub(crate) fn ban_peer_by_admin(&mut self, peer_id: PeerId) {
-        if self.trusted_peer_ids.contains(&peer_id) ||
-            self.peers.get(&peer_id).is_some_and(Peer::is_trusted)
+        self.add_ban_by_admin(BanEntry::new(BanTarget::Peer(peer_id)));
+    }
+
+    /// Bans the target of the entry and removes the peers it covers from the peer set.
+    ///
+    /// A ban of a trusted peer is ignored, trusted peers in a banned IP range are kept but can no
+    /// longer be dialed.
+    ///
+    /// Returns false if the ban was ignored.
+    pub(crate) fn add_ban_by_admin(&mut self, entry: BanEntry) -> bool {
+        if let BanTarget::Peer(peer_id) = entry.target &&
+            (self.trusted_peer_ids.contains(&peer_id) ||
+                self.peers.get(&peer_id).is_some_and(Peer::is_trusted))
         {
-            return
+            return false
         }
 
-        self.remove_peer(peer_id);
-        self.ban_list.ban_peer(peer_id);
+        let banned = self
+            .peers
+            .iter()
+            .filter(|(peer_id, peer)| match entry.target {
+                BanTarget::Peer(banned) => banned == **peer_id,
+                target => target.contains_ip(&peer.addr.tcp().ip()),
+            })
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+        for peer_id in banned {
+            self.remove_peer(peer_id);
+        }
+        self.queued_actions.push_back(PeerAction::DiscoveryBanEntry { entry: entry.clone() });
+        self.ban_list.ban(entry);
+        true
+    }
+
+    /// Lifts all bans of the target, see also [`Self::unban_peer_by_admin`].
+    ///
+    /// Returns true if the target was banned.
+    pub(crate) fn remove_ban_by_admin(&mut self, target: BanTarget) -> bool {
+        let mut removed = self.ban_list.unban(&target);
+        if let BanTarget::Peer(peer_id) = target {
+            removed |= self.peers.get(&peer_id).is_some_and(Peer::is_banned);
+            self.unban_peer_by_admin(peer_id);
+        } else {
+            self.queued_actions.push_back(PeerAction::DiscoveryUnban { target });
+        }
+        removed
+    }
+
+    /// Returns the bans that should be persisted across restarts.
+    pub(crate) fn ban_entries(&self) -> impl Iterator<Item = &BanEntry> + '_ {
+        self.ban_list.entries()
+    }
+
+    /// Restores persisted bans like [`Self::add_ban_by_admin`], skipping bans that already
+    /// expired.
+    ///
+    /// Returns the number of restored bans.
+    pub(crate) fn restore_bans(&mut self, bans: impl IntoIterator<Item = BanEntry>) -> usize {
+        let now = std::time::SystemTime::now();
+        let mut restored = 0;
+        for entry in bans.into_iter().filter(|entry| !entry.is_expired_at(now)) {
+            self.add_ban_by_admin(entry);
+            restored += 1;
+        }
+        restored
     }
 
     /// Removes the peer from the ban list and resets its reputation.
@@ -965,6 +1026,8 @@ impl PeersManager {
             peer.unban();
             self.queued_actions.push_back(PeerAction::UnBanPeer { peer_id });
         }
+        self.queued_actions
+            .push_back(PeerAction::DiscoveryUnban { target: BanTarget::Peer(peer_id) });
     }
 
     /// Connect to the given peer. NOTE: if the maximum number of outbound sessions is reached,
@@ -1437,6 +1500,16 @@ pub enum PeerAction {
         /// The IP address.
         ip_addr: IpAddr,
     },
+    /// Ban the peer, IP or range in discovery until the entry expires.
+    DiscoveryBanEntry {
+        /// The ban.
+        entry: BanEntry,
+    },
+    /// Lift all bans of the peer, IP or range in discovery.
+    DiscoveryUnban {
+        /// The banned peer, IP or range.
+        target: BanTarget,
+    },
     /// Ban the peer temporarily
     BanPeer {
         /// The peer ID.
@@ -1507,7 +1580,7 @@ mod tests {
         DisconnectReason,
     };
     use reth_ethereum_forks::{ForkHash, ForkId};
-    use reth_net_banlist::BanList;
+    use reth_net_banlist::{BanEntry, BanList, BanTarget};
     use reth_network_api::Direction;
     use reth_network_peers::{NodeRecord, PeerId, TrustedPeer};
     use reth_network_types::{
@@ -1688,6 +1761,10 @@ mod tests {
             Some(PeerAction::PeerRemoved(peer_id)) => assert_eq!(peer_id, peer),
             other => panic!("unexpected action: {other:?}"),
         }
+        assert!(matches!(
+            peers.queued_actions.pop_front(),
+            Some(PeerAction::DiscoveryBanEntry { entry }) if entry.target == BanTarget::Peer(peer)
+        ));
 
         let (_, unbanned_peers) =
             peers.ban_list.evict(std::time::Instant::now() + Duration::from_secs(1));
@@ -1729,6 +1806,75 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_admin_ban_range() {
+        let banned = PeerId::random();
+        let trusted = PeerId::random();
+        let other = PeerId::random();
+        let addr = |ip: [u8; 4]| PeerAddr::from_tcp(SocketAddr::new(IpAddr::from(ip), 30303));
+        let mut peers = PeersManager::default();
+        peers.peers.insert(banned, Peer::new(addr([10, 1, 0, 1])));
+        peers.peers.insert(trusted, Peer::trusted(addr([10, 1, 0, 2])));
+        peers.peers.insert(other, Peer::new(addr([10, 2, 0, 1])));
+
+        let target: BanTarget = "10.1.0.0/16".parse().unwrap();
+        peers.add_ban_by_admin(BanEntry::new(target).with_reason("spam"));
+
+        assert!(peers.peer_by_id(banned).is_none());
+        assert!(peers.peer_by_id(trusted).is_some());
+        assert!(peers.peer_by_id(other).is_some());
+        assert!(peers.ban_list.is_banned_ip(&IpAddr::from([10, 1, 2, 3])));
+        assert_eq!(peers.ban_entries().count(), 1);
+
+        // newly discovered peers in the range are ignored
+        let discovered = PeerId::random();
+        peers.add_peer(discovered, addr([10, 1, 0, 3]), None);
+        assert!(peers.peer_by_id(discovered).is_none());
+
+        assert!(peers.remove_ban_by_admin(target));
+        assert!(!peers.remove_ban_by_admin(target));
+        assert_eq!(peers.ban_entries().count(), 0);
+        peers.add_peer(discovered, addr([10, 1, 0, 3]), None);
+        assert!(peers.peer_by_id(discovered).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_restore_bans() {
+        let banned = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let mut peers = PeersManager::default();
+        peers.peers.insert(banned, Peer::new(PeerAddr::from_tcp(socket_addr)));
+
+        let mut expired = BanEntry::new(BanTarget::Ip(IpAddr::from([1, 1, 1, 1])));
+        expired.expires_at = Some(std::time::SystemTime::now() - Duration::from_secs(1));
+        let bans = [
+            BanEntry::new(BanTarget::Peer(banned))
+                .with_reason("bad reputation")
+                .with_duration(Duration::from_secs(3600)),
+            expired,
+        ];
+
+        assert_eq!(peers.restore_bans(bans), 1);
+        assert!(peers.ban_list.is_banned_peer(&banned));
+        assert!(peers.peer_by_id(banned).is_none());
+        assert!(!peers.ban_list.is_banned_ip(&IpAddr::from([1, 1, 1, 1])));
+    }
+
+    #[tokio::test]
+    async fn test_reputation_ban_is_persistable() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let mut peers = PeersManager::default();
+        peers.peers.insert(peer, Peer::new(PeerAddr::from_tcp(socket_addr)));
+
+        peers.ban_peer(peer);
+
+        let entry = peers.ban_entries().next().unwrap();
+        assert_eq!(entry.target, BanTarget::Peer(peer));
+        assert_eq!(entry.reason.as_deref(), Some("bad reputation"));
+        assert!(entry.expires_at.is_some());
+    }
+
     #[tokio::test]
     async fn test_backoff_on_busy() {
         let peer = PeerId::random();
@@ -0,0 +1,216 @@
+//! `RLPx` plumbing of the private relay protocol.
+
+use super::message::PrivateRelayMessage;
+use crate::protocol::{ConnectionHandler, OnNotSupported, ProtocolHandler};
+use alloy_primitives::bytes::BytesMut;
+use alloy_rlp::{Decodable, Encodable};
+use futures::{Stream, StreamExt};
+use reth_eth_wire::{
+    capability::SharedCapabilities, multiplex::ProtocolConnection, protocol::Protocol,
+};
+use reth_network_api::{Direction, PeerId};
+use std::{
+    collections::HashSet,
+    fmt,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::trace;
+
+/// Events emitted by private relay connections.
+#[derive(Debug)]
+pub(crate) enum RelayEvent<T> {
+    /// A connection with a configured relay peer has been established.
+    Established {
+        /// The remote peer.
+        peer_id: PeerId,
+        /// Sender for messages to the peer.
+        to_connection: mpsc::UnboundedSender<PrivateRelayMessage<T>>,
+    },
+    /// A message was received from a configured relay peer.
+    Message {
+        /// The remote peer.
+        peer_id: PeerId,
+        /// The received message.
+        msg: PrivateRelayMessage<T>,
+    },
+    /// The connection with a configured relay peer was closed.
+    Closed {
+        /// The remote peer.
+        peer_id: PeerId,
+    },
+}
+
+/// The [`ProtocolHandler`] of the private relay protocol.
+///
+/// The protocol is announced to every peer, because the remote's id is not known yet for incoming
+/// connections, but only connections with configured relay peers are served.
+pub struct PrivateRelayProtocol<T> {
+    peers: Arc<HashSet<PeerId>>,
+    events: mpsc::UnboundedSender<RelayEvent<T>>,
+}
+
+impl<T> PrivateRelayProtocol<T> {
+    /// Creates a new protocol handler for the given relay peers.
+    pub(crate) const fn new(
+        peers: Arc<HashSet<PeerId>>,
+        events: mpsc::UnboundedSender<RelayEvent<T>>,
+    ) -> Self {
+        Self { peers, events }
+    }
+
+    fn connection_handler(&self) -> PrivateRelayConnectionHandler<T> {
+        PrivateRelayConnectionHandler { peers: self.peers.clone(), events: self.events.clone() }
+    }
+}
+
+impl<T> fmt::Debug for PrivateRelayProtocol<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateRelayProtocol").field("peers", &self.peers).finish_non_exhaustive()
+    }
+}
+
+impl<T> ProtocolHandler for PrivateRelayProtocol<T>
+where
+    T: Encodable + Decodable + Send + Sync + Unpin + 'static,
+{
+    type ConnectionHandler = PrivateRelayConnectionHandler<T>;
+
+    fn on_incoming(&self, _socket_addr: SocketAddr) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+
+    fn on_outgoing(
+        &self,
+        _socket_addr: SocketAddr,
+        peer_id: PeerId,
+    ) -> Option<Self::ConnectionHandler> {
+        self.peers.contains(&peer_id).then(|| self.connection_handler())
+    }
+}
+
+/// The [`ConnectionHandler`] of the private relay protocol.
+#[derive(Debug)]
+pub struct PrivateRelayConnectionHandler<T> {
+    peers: Arc<HashSet<PeerId>>,
+    events: mpsc::UnboundedSender<RelayEvent<T>>,
+}
+
+impl<T> ConnectionHandler for PrivateRelayConnectionHandler<T>
+where
+    T: Encodable + Decodable + Send + Sync + Unpin + 'static,
+{
+    type Connection = PrivateRelayConnection<T>;
+
+    fn protocol(&self) -> Protocol {
+        PrivateRelayMessage::<T>::protocol()
+    }
+
+    fn on_unsupported_by_peer(
+        self,
+        _supported: &SharedCapabilities,
+        _direction: Direction,
+        _peer_id: PeerId,
+    ) -> OnNotSupported {
+        OnNotSupported::KeepAlive
+    }
+
+    fn into_connection(
+        self,
+        _direction: Direction,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+    ) -> Self::Connection {
+        if !self.peers.contains(&peer_id) {
+            trace!(target: "net::private_relay", %peer_id, "ignoring private relay connection from unconfigured peer");
+            return PrivateRelayConnection { peer_id, conn, commands: None, events: None }
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.events.send(RelayEvent::Established { peer_id, to_connection: tx }).ok();
+        PrivateRelayConnection {
+            peer_id,
+            conn,
+            commands: Some(UnboundedReceiverStream::new(rx)),
+            events: Some(self.events),
+        }
+    }
+}
+
+/// A private relay connection with a single peer.
+///
+/// Connections with peers that are not configured as relay peers never send anything and drop
+/// all inbound messages. They stay open, because closing the protocol connection would tear down
+/// the entire session.
+pub struct PrivateRelayConnection<T> {
+    peer_id: PeerId,
+    conn: ProtocolConnection,
+    /// Messages to send to the peer, `None` if the peer is not a relay peer.
+    commands: Option<UnboundedReceiverStream<PrivateRelayMessage<T>>>,
+    /// Sink for inbound messages, `None` if the peer is not a relay peer.
+    events: Option<mpsc::UnboundedSender<RelayEvent<T>>>,
+}
+
+impl<T> fmt::Debug for PrivateRelayConnection<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateRelayConnection")
+            .field("peer_id", &self.peer_id)
+            .field("relay_peer", &self.events.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> PrivateRelayConnection<T> {
+    fn on_closed(&mut self) {
+        if let Some(events) = self.events.take() {
+            events.send(RelayEvent::Closed { peer_id: self.peer_id }).ok();
+        }
+    }
+}
+
+impl<T> Stream for PrivateRelayConnection<T>
+where
+    T: Encodable + Decodable + Unpin,
+{
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(commands) = &mut this.commands &&
+                let Poll::Ready(Some(msg)) = commands.poll_next_unpin(cx)
+            {
+                return Poll::Ready(Some(msg.encoded()))
+            }
+
+            let Some(raw) = ready!(this.conn.poll_next_unpin(cx)) else {
+                this.on_closed();
+                return Poll::Ready(None)
+            };
+
+            let Some(events) = &this.events else { continue };
+
+            match PrivateRelayMessage::decode_message(&mut &raw[..]) {
+                Ok(msg) => {
+                    events.send(RelayEvent::Message { peer_id: this.peer_id, msg }).ok();
+                }
+                Err(err) => {
+                    trace!(target: "net::private_relay", peer_id=%this.peer_id, %err, "malformed private relay message");
+                    this.on_closed();
+                    return Poll::Ready(None)
+                }
+            }
+        }
+    }
+}
+
+impl<T> Drop for PrivateRelayConnection<T> {
+    fn drop(&mut self) {
+        self.on_closed();
+    }
+}
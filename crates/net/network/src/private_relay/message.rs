@@ -0,0 +1,160 @@
+//! Messages of the private relay protocol.
+
+use alloy_primitives::bytes::{Buf, BufMut, BytesMut};
+use alloy_rlp::{Decodable, Encodable};
+use reth_eth_wire::{message::RequestPair, protocol::Protocol, Capability};
+use reth_network_api::private_relay::PrivateBundle;
+
+/// The `prv` capability of the private relay protocol.
+pub const PRIVATE_RELAY_CAPABILITY: Capability = Capability::new_static("prv", 1);
+
+/// Ids of the private relay protocol messages.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateRelayMessageId {
+    /// Private transactions, see [`PrivateRelayMessage::Transactions`].
+    Transactions = 0x00,
+    /// A private bundle, see [`PrivateRelayMessage::Bundle`].
+    Bundle = 0x01,
+    /// Acknowledgement of a relayed message, see [`PrivateRelayMessage::Ack`].
+    Ack = 0x02,
+}
+
+impl PrivateRelayMessageId {
+    /// Number of messages of the protocol.
+    pub const COUNT: u8 = 3;
+
+    /// Returns the message id for the given byte.
+    pub const fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            0x00 => Some(Self::Transactions),
+            0x01 => Some(Self::Bundle),
+            0x02 => Some(Self::Ack),
+            _ => None,
+        }
+    }
+}
+
+/// A message of the private relay protocol.
+///
+/// Relayed transactions and bundles carry a request id that is echoed by the
+/// [`PrivateRelayMessage::Ack`] of the receiving peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrivateRelayMessage<T> {
+    /// Private transactions to be included by the receiving peer.
+    Transactions(RequestPair<Vec<T>>),
+    /// A private bundle to be included by the receiving peer.
+    Bundle(RequestPair<PrivateBundle<T>>),
+    /// Acknowledges a relayed message, with one entry per relayed transaction that is `true` if
+    /// the transaction was accepted.
+    Ack(RequestPair<Vec<bool>>),
+}
+
+impl<T> PrivateRelayMessage<T> {
+    /// Returns the protocol to announce for the private relay.
+    pub const fn protocol() -> Protocol {
+        Protocol::new(PRIVATE_RELAY_CAPABILITY, PrivateRelayMessageId::COUNT)
+    }
+
+    /// Returns the id of the message.
+    pub const fn message_id(&self) -> PrivateRelayMessageId {
+        match self {
+            Self::Transactions(_) => PrivateRelayMessageId::Transactions,
+            Self::Bundle(_) => PrivateRelayMessageId::Bundle,
+            Self::Ack(_) => PrivateRelayMessageId::Ack,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub const fn request_id(&self) -> u64 {
+        match self {
+            Self::Transactions(pair) => pair.request_id,
+            Self::Bundle(pair) => pair.request_id,
+            Self::Ack(pair) => pair.request_id,
+        }
+    }
+}
+
+impl<T: Encodable> PrivateRelayMessage<T> {
+    /// Encodes the message, prefixed with its message id.
+    pub fn encoded(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(self.message_id() as u8);
+        match self {
+            Self::Transactions(pair) => pair.encode(&mut buf),
+            Self::Bundle(pair) => pair.encode(&mut buf),
+            Self::Ack(pair) => pair.encode(&mut buf),
+        }
+        buf
+    }
+}
+
+impl<T: Decodable> PrivateRelayMessage<T> {
+    /// Decodes a message that is prefixed with its message id.
+    pub fn decode_message(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let id = *buf.first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        buf.advance(1);
+        let msg = match PrivateRelayMessageId::from_u8(id) {
+            Some(PrivateRelayMessageId::Transactions) => {
+                Self::Transactions(RequestPair::decode(buf)?)
+            }
+            Some(PrivateRelayMessageId::Bundle) => Self::Bundle(RequestPair::decode(buf)?),
+            Some(PrivateRelayMessageId::Ack) => Self::Ack(RequestPair::decode(buf)?),
+            None => return Err(alloy_rlp::Error::Custom("unknown private relay message id")),
+        };
+        if !buf.is_empty() {
+            return Err(alloy_rlp::Error::UnexpectedLength)
+        }
+
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::TxLegacy;
+    use alloy_primitives::Signature;
+    use reth_ethereum_primitives::PooledTransactionVariant;
+
+    fn transaction(nonce: u64) -> PooledTransactionVariant {
+        PooledTransactionVariant::Legacy(alloy_consensus::Signed::new_unhashed(
+            TxLegacy { nonce, ..Default::default() },
+            Signature::test_signature(),
+        ))
+    }
+
+    #[test]
+    fn roundtrip_messages() {
+        let messages = [
+            PrivateRelayMessage::Transactions(RequestPair {
+                request_id: 1,
+                message: vec![transaction(0), transaction(1)],
+            }),
+            PrivateRelayMessage::Bundle(RequestPair {
+                request_id: 2,
+                message: PrivateBundle { transactions: vec![transaction(2)], block_number: 100 },
+            }),
+            PrivateRelayMessage::Ack(RequestPair { request_id: 1, message: vec![true, false] }),
+        ];
+
+        for msg in messages {
+            let encoded = msg.encoded();
+            assert_eq!(encoded[0], msg.message_id() as u8);
+            assert_eq!(PrivateRelayMessage::decode_message(&mut &encoded[..]).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn reject_unknown_message_id() {
+        let mut encoded = PrivateRelayMessage::<PooledTransactionVariant>::Ack(RequestPair {
+            request_id: 1,
+            message: vec![true],
+        })
+        .encoded();
+        encoded[0] = PrivateRelayMessageId::COUNT;
+
+        assert!(PrivateRelayMessage::<PooledTransactionVariant>::decode_message(&mut &encoded[..])
+            .is_err());
+    }
+}
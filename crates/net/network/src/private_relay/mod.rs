@@ -0,0 +1,573 @@
+//! Private transaction relay between trusted peers.
+//!
+//! The private relay is an additional `RLPx` subprotocol (`prv/1`) that forwards private
+//! transactions and bundles to a configured set of peers, typically block builders, instead of
+//! gossiping them on the public `eth` protocol. Every relayed message is acknowledged by the
+//! receiving peer.
+//!
+//! Messages are confidential to the configured peers because every `RLPx` session is encrypted
+//! (ECIES) and the protocol is only served on sessions with those peers. Transactions received
+//! over the relay are inserted into the pool with [`TransactionOrigin::PrivateRelay`], which
+//! excludes them from announcements and `GetPooledTransactions` responses on the `eth` protocol.
+
+use crate::{private_relay::connection::RelayEvent, NetworkManager};
+use futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
+use reth_eth_wire::{message::RequestPair, NetworkPrimitives};
+use reth_network_api::PeerId;
+use reth_primitives_traits::SignedTransaction;
+use reth_transaction_pool::{
+    error::PoolResult, AddedTransactionOutcome, NewTransactionEvent, PoolTransaction,
+    TransactionListenerKind, TransactionOrigin, TransactionPool,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{debug, trace};
+
+mod connection;
+mod message;
+
+pub use connection::{PrivateRelayConnection, PrivateRelayConnectionHandler, PrivateRelayProtocol};
+pub use message::{PrivateRelayMessage, PrivateRelayMessageId, PRIVATE_RELAY_CAPABILITY};
+pub use reth_network_api::private_relay::{
+    PrivateBundle, PrivateRelayCommand, PrivateRelayError, PrivateRelayHandle, PrivateRelayPayload,
+    PrivateRelayProvider, ReceivedBundle, RelayAck, RelayOutcome,
+};
+
+/// Default time to wait for the acknowledgement of a relayed message.
+pub const DEFAULT_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum interval at which expired relay requests are checked.
+const TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Capacity of the channel for bundles received from relay peers.
+const BUNDLE_CHANNEL_CAPACITY: usize = 256;
+
+/// Maximum number of inbound messages that are processed at the same time.
+///
+/// Messages received beyond this limit are rejected right away.
+const MAX_PENDING_ACKS: usize = 256;
+
+/// Configuration of the private relay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateRelayConfig {
+    /// The peers private transactions and bundles are relayed to and accepted from.
+    pub peers: HashSet<PeerId>,
+    /// How long to wait for the acknowledgement of a relayed message.
+    pub ack_timeout: Duration,
+    /// Whether transactions added to the pool with [`TransactionOrigin::Private`] are forwarded
+    /// to the relay peers.
+    ///
+    /// Disabled by default, private transactions never leave the node unless this is opted into.
+    pub forward_private_transactions: bool,
+}
+
+impl PrivateRelayConfig {
+    /// Creates a new configuration for the given relay peers.
+    pub fn new(peers: impl IntoIterator<Item = PeerId>) -> Self {
+        Self {
+            peers: peers.into_iter().collect(),
+            ack_timeout: DEFAULT_ACK_TIMEOUT,
+            forward_private_transactions: false,
+        }
+    }
+
+    /// Sets the acknowledgement timeout.
+    pub const fn with_ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = ack_timeout;
+        self
+    }
+
+    /// Sets whether private pool transactions are forwarded to the relay peers.
+    pub const fn with_forward_private_transactions(mut self, forward: bool) -> Self {
+        self.forward_private_transactions = forward;
+        self
+    }
+}
+
+type RelayResponse = oneshot::Sender<Result<Vec<RelayAck>, PrivateRelayError>>;
+
+/// Converts the payload into a relay message with the given request id.
+fn into_message<T>(payload: PrivateRelayPayload<T>, request_id: u64) -> PrivateRelayMessage<T> {
+    match payload {
+        PrivateRelayPayload::Transactions(message) => {
+            PrivateRelayMessage::Transactions(RequestPair { request_id, message })
+        }
+        PrivateRelayPayload::Bundle(message) => {
+            PrivateRelayMessage::Bundle(RequestPair { request_id, message })
+        }
+    }
+}
+
+/// A relayed message that awaits acknowledgements.
+#[derive(Debug)]
+struct InflightRelay {
+    /// When the message expires.
+    deadline: Instant,
+    /// Peers that have not acknowledged the message yet.
+    pending: HashSet<PeerId>,
+    /// Outcomes collected so far.
+    acks: Vec<RelayAck>,
+    /// Where to send the outcomes, `None` for forwarded pool transactions.
+    response: Option<RelayResponse>,
+}
+
+impl InflightRelay {
+    /// Records the outcome for the peer, returns `true` if all peers are done.
+    fn on_outcome(&mut self, peer_id: PeerId, outcome: RelayOutcome) -> bool {
+        if self.pending.remove(&peer_id) {
+            self.acks.push(RelayAck { peer_id, outcome });
+        }
+        self.pending.is_empty()
+    }
+
+    fn finish(self) {
+        if let Some(response) = self.response {
+            response.send(Ok(self.acks)).ok();
+        }
+    }
+}
+
+/// Acknowledgement of an inbound message that is sent once the message was processed.
+type PendingAck = BoxFuture<'static, (PeerId, u64, Vec<bool>)>;
+
+/// Manages the private relay protocol.
+///
+/// The manager must be spawned and its [`PrivateRelayManager::protocol`] must be registered with
+/// the network as an additional `RLPx` subprotocol.
+#[must_use = "Manager does nothing unless polled."]
+pub struct PrivateRelayManager<Pool: TransactionPool> {
+    /// The transaction pool relayed transactions are inserted into.
+    pool: Pool,
+    /// The relay configuration.
+    config: PrivateRelayConfig,
+    /// Connected relay peers.
+    peers: HashMap<PeerId, mpsc::UnboundedSender<PrivateRelayMessage<PooledTx<Pool>>>>,
+    /// Sender half for connection events, handed to the protocol handler.
+    events_tx: mpsc::UnboundedSender<RelayEvent<PooledTx<Pool>>>,
+    /// Events from relay connections.
+    events: UnboundedReceiverStream<RelayEvent<PooledTx<Pool>>>,
+    /// Sender half for commands, handed to the [`PrivateRelayHandle`].
+    commands_tx: mpsc::UnboundedSender<PrivateRelayCommand<PooledTx<Pool>>>,
+    /// Commands from the [`PrivateRelayHandle`].
+    commands: UnboundedReceiverStream<PrivateRelayCommand<PooledTx<Pool>>>,
+    /// Bundles received from relay peers.
+    bundles: broadcast::Sender<ReceivedBundle<PooledTx<Pool>>>,
+    /// The id of the next relayed message.
+    next_request_id: u64,
+    /// Relayed messages that await acknowledgements.
+    inflight: HashMap<u64, InflightRelay>,
+    /// Inbound messages that are being processed, at most [`MAX_PENDING_ACKS`].
+    pending_acks: FuturesUnordered<PendingAck>,
+    /// New pool transactions, if private transactions are forwarded.
+    pool_transactions: Option<mpsc::Receiver<NewTransactionEvent<Pool::Transaction>>>,
+    /// Interval at which expired relay requests are checked.
+    timeout_interval: tokio::time::Interval,
+}
+
+type PooledTx<Pool> = <<Pool as TransactionPool>::Transaction as PoolTransaction>::Pooled;
+
+impl<Pool: TransactionPool> fmt::Debug for PrivateRelayManager<Pool> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateRelayManager")
+            .field("config", &self.config)
+            .field("peers", &self.peers.keys().collect::<Vec<_>>())
+            .field("inflight", &self.inflight.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Pool> PrivateRelayManager<Pool>
+where
+    Pool: TransactionPool + Clone + 'static,
+{
+    /// Creates a new manager for the given pool.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn new(pool: Pool, config: PrivateRelayConfig) -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let (commands_tx, commands_rx) = mpsc::unbounded_channel();
+        let (bundles, _) = broadcast::channel(BUNDLE_CHANNEL_CAPACITY);
+        let pool_transactions = config
+            .forward_private_transactions
+            .then(|| pool.new_transactions_listener_for(TransactionListenerKind::All));
+        let timeout_interval =
+            tokio::time::interval(config.ack_timeout.min(TIMEOUT_CHECK_INTERVAL));
+
+        Self {
+            pool,
+            config,
+            peers: HashMap::default(),
+            events_tx,
+            events: UnboundedReceiverStream::new(events_rx),
+            commands_tx,
+            commands: UnboundedReceiverStream::new(commands_rx),
+            bundles,
+            next_request_id: 0,
+            inflight: HashMap::default(),
+            pending_acks: FuturesUnordered::default(),
+            pool_transactions,
+            timeout_interval,
+        }
+    }
+
+    /// Returns a new handle to relay transactions and bundles.
+    pub fn handle(&self) -> PrivateRelayHandle<PooledTx<Pool>> {
+        PrivateRelayHandle::new(self.commands_tx.clone(), self.bundles.clone())
+    }
+
+    /// Returns the protocol handler that must be registered with the network.
+    pub fn protocol(&self) -> PrivateRelayProtocol<PooledTx<Pool>> {
+        PrivateRelayProtocol::new(Arc::new(self.config.peers.clone()), self.events_tx.clone())
+    }
+
+    /// Registers the [`PrivateRelayManager::protocol`] with the network and makes the
+    /// [`PrivateRelayManager::handle`] available through the network's [`PrivateRelayProvider`].
+    pub fn install<N>(&self, network: &mut NetworkManager<N>)
+    where
+        N: NetworkPrimitives<PooledTransaction = PooledTx<Pool>>,
+    {
+        network.add_rlpx_sub_protocol(self.protocol());
+        network.handle().set_private_relay(self.handle());
+    }
+
+    /// Returns the number of connected relay peers.
+    pub fn num_connected_peers(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Sends the message to all connected relay peers and tracks their acknowledgements.
+    fn relay(
+        &mut self,
+        payload: PrivateRelayPayload<PooledTx<Pool>>,
+        response: Option<RelayResponse>,
+    ) {
+        if self.peers.is_empty() {
+            if let Some(response) = response {
+                response.send(Err(PrivateRelayError::NoPeers)).ok();
+            }
+            return
+        }
+
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        let msg = into_message(payload, request_id);
+
+        let mut inflight = InflightRelay {
+            deadline: Instant::now() + self.config.ack_timeout,
+            pending: self.peers.keys().copied().collect(),
+            acks: Vec::with_capacity(self.peers.len()),
+            response,
+        };
+        for (peer_id, to_connection) in &self.peers {
+            if to_connection.send(msg.clone()).is_err() {
+                inflight.on_outcome(*peer_id, RelayOutcome::Disconnected);
+            }
+        }
+
+        if inflight.pending.is_empty() {
+            inflight.finish();
+        } else {
+            self.inflight.insert(request_id, inflight);
+        }
+    }
+
+    /// Records the outcome of a relayed message for the peer.
+    fn on_outcome(&mut self, request_id: u64, peer_id: PeerId, outcome: RelayOutcome) {
+        let Some(inflight) = self.inflight.get_mut(&request_id) else {
+            trace!(target: "net::private_relay", %peer_id, request_id, "unsolicited private relay ack");
+            return
+        };
+        if inflight.on_outcome(peer_id, outcome) &&
+            let Some(inflight) = self.inflight.remove(&request_id)
+        {
+            inflight.finish();
+        }
+    }
+
+    /// Returns `false` and rejects all transactions of the inbound message if too many inbound
+    /// messages are being processed already.
+    fn try_accept_inbound(
+        &self,
+        peer_id: PeerId,
+        request_id: u64,
+        num_transactions: usize,
+    ) -> bool {
+        if self.pending_acks.len() < MAX_PENDING_ACKS {
+            return true
+        }
+        debug!(target: "net::private_relay", %peer_id, request_id, "too many pending relay messages");
+        if let Some(to_connection) = self.peers.get(&peer_id) {
+            let ack = RequestPair { request_id, message: vec![false; num_transactions] };
+            to_connection.send(PrivateRelayMessage::Ack(ack)).ok();
+        }
+        false
+    }
+
+    /// Imports the transactions received from the peer into the pool.
+    fn on_transactions(&self, peer_id: PeerId, request_id: u64, transactions: Vec<PooledTx<Pool>>) {
+        if !self.try_accept_inbound(peer_id, request_id, transactions.len()) {
+            return
+        }
+        let pool = self.pool.clone();
+        let mut accepted = vec![false; transactions.len()];
+        let (indices, transactions): (Vec<_>, Vec<_>) = transactions
+            .into_iter()
+            .map(Pool::Transaction::try_recover)
+            .enumerate()
+            .filter_map(|(idx, tx)| tx.ok().map(|tx| (idx, tx)))
+            .unzip();
+
+        self.pending_acks.push(
+            async move {
+                let results: Vec<PoolResult<AddedTransactionOutcome>> =
+                    pool.add_transactions(TransactionOrigin::PrivateRelay, transactions).await;
+                for (idx, result) in indices.into_iter().zip(results) {
+                    accepted[idx] = result.is_ok();
+                }
+                (peer_id, request_id, accepted)
+            }
+            .boxed(),
+        );
+    }
+
+    /// Publishes the bundle received from the peer to the bundle subscribers.
+    fn on_bundle(&self, peer_id: PeerId, request_id: u64, bundle: PrivateBundle<PooledTx<Pool>>) {
+        if !self.try_accept_inbound(peer_id, request_id, bundle.transactions.len()) {
+            return
+        }
+        let PrivateBundle { transactions, block_number } = bundle;
+        let transactions =
+            transactions.into_iter().map(SignedTransaction::try_into_recovered).collect::<Vec<_>>();
+        let accepted = transactions.iter().map(Result::is_ok).collect::<Vec<_>>();
+
+        if accepted.iter().all(|ok| *ok) {
+            let transactions = transactions.into_iter().filter_map(Result::ok).collect();
+            let bundle = PrivateBundle { transactions, block_number };
+            self.bundles.send(ReceivedBundle { peer_id, bundle }).ok();
+        } else {
+            debug!(target: "net::private_relay", %peer_id, request_id, "received bundle with invalid transactions");
+        }
+
+        self.pending_acks.push(futures::future::ready((peer_id, request_id, accepted)).boxed());
+    }
+
+    fn on_event(&mut self, event: RelayEvent<PooledTx<Pool>>) {
+        match event {
+            RelayEvent::Established { peer_id, to_connection } => {
+                debug!(target: "net::private_relay", %peer_id, "private relay peer connected");
+                self.peers.insert(peer_id, to_connection);
+            }
+            RelayEvent::Message { peer_id, msg } => match msg {
+                PrivateRelayMessage::Transactions(RequestPair { request_id, message }) => {
+                    self.on_transactions(peer_id, request_id, message)
+                }
+                PrivateRelayMessage::Bundle(RequestPair { request_id, message }) => {
+                    self.on_bundle(peer_id, request_id, message)
+                }
+                PrivateRelayMessage::Ack(RequestPair { request_id, message }) => {
+                    self.on_outcome(request_id, peer_id, RelayOutcome::Acknowledged(message))
+                }
+            },
+            RelayEvent::Closed { peer_id } => {
+                debug!(target: "net::private_relay", %peer_id, "private relay peer disconnected");
+                self.peers.remove(&peer_id);
+                let request_ids = self
+                    .inflight
+                    .iter()
+                    .filter(|(_, inflight)| inflight.pending.contains(&peer_id))
+                    .map(|(request_id, _)| *request_id)
+                    .collect::<Vec<_>>();
+                for request_id in request_ids {
+                    self.on_outcome(request_id, peer_id, RelayOutcome::Disconnected);
+                }
+            }
+        }
+    }
+
+    /// Marks all peers that did not acknowledge an expired message as timed out.
+    fn on_timeout_check(&mut self) {
+        let now = Instant::now();
+        let expired = self
+            .inflight
+            .iter()
+            .filter(|(_, inflight)| inflight.deadline <= now)
+            .map(|(request_id, _)| *request_id)
+            .collect::<Vec<_>>();
+        for request_id in expired {
+            let Some(mut inflight) = self.inflight.remove(&request_id) else { continue };
+            for peer_id in std::mem::take(&mut inflight.pending) {
+                inflight.acks.push(RelayAck { peer_id, outcome: RelayOutcome::TimedOut });
+            }
+            inflight.finish();
+        }
+    }
+
+    /// Forwards a private pool transaction to the relay peers.
+    fn on_pool_transaction(&mut self, event: NewTransactionEvent<Pool::Transaction>) {
+        if !event.transaction.origin.is_private() {
+            return
+        }
+        let Some(tx) = self.pool.get_pooled_transaction_element(*event.transaction.hash()) else {
+            return
+        };
+        self.relay(PrivateRelayPayload::Transactions(vec![tx.into_inner()]), None);
+    }
+}
+
+impl<Pool> Future for PrivateRelayManager<Pool>
+where
+    Pool: TransactionPool + Clone + Unpin + 'static,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        while let Poll::Ready(Some(event)) = this.events.poll_next_unpin(cx) {
+            this.on_event(event);
+        }
+
+        while let Poll::Ready(Some(PrivateRelayCommand::Relay { payload, response })) =
+            this.commands.poll_next_unpin(cx)
+        {
+            this.relay(payload, Some(response));
+        }
+
+        if let Some(pool_transactions) = &mut this.pool_transactions {
+            let mut events = Vec::new();
+            while let Poll::Ready(Some(event)) = pool_transactions.poll_recv(cx) {
+                events.push(event);
+            }
+            for event in events {
+                this.on_pool_transaction(event);
+            }
+        }
+
+        while let Poll::Ready(Some((peer_id, request_id, accepted))) =
+            this.pending_acks.poll_next_unpin(cx)
+        {
+            if let Some(to_connection) = this.peers.get(&peer_id) {
+                let ack = RequestPair { request_id, message: accepted };
+                to_connection.send(PrivateRelayMessage::Ack(ack)).ok();
+            }
+        }
+
+        while this.timeout_interval.poll_tick(cx).is_ready() {
+            this.on_timeout_check();
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+
+    fn manager(ack_timeout: Duration) -> (PrivateRelayManager<TestPool>, PeerId) {
+        let peer_id = PeerId::with_last_byte(1);
+        let config = PrivateRelayConfig::new([peer_id]).with_ack_timeout(ack_timeout);
+        (PrivateRelayManager::new(testing_pool(), config), peer_id)
+    }
+
+    #[tokio::test]
+    async fn relay_without_peers() {
+        let (relay, _) = manager(DEFAULT_ACK_TIMEOUT);
+        let handle = relay.handle();
+        tokio::spawn(relay);
+
+        assert_eq!(handle.send_transactions(vec![]).await, Err(PrivateRelayError::NoPeers));
+    }
+
+    #[tokio::test]
+    async fn relay_acknowledged() {
+        let (relay, peer_id) = manager(DEFAULT_ACK_TIMEOUT);
+        let handle = relay.handle();
+        let events = relay.events_tx.clone();
+        let (to_connection, mut from_manager) = mpsc::unbounded_channel();
+        events.send(RelayEvent::Established { peer_id, to_connection }).unwrap();
+        tokio::spawn(relay);
+
+        let relayed = tokio::spawn(async move { handle.send_transactions(vec![]).await });
+
+        let msg = from_manager.recv().await.unwrap();
+        assert_eq!(msg.message_id(), PrivateRelayMessageId::Transactions);
+        let ack = RequestPair { request_id: msg.request_id(), message: vec![] };
+        events.send(RelayEvent::Message { peer_id, msg: PrivateRelayMessage::Ack(ack) }).unwrap();
+
+        let acks = relayed.await.unwrap().unwrap();
+        assert_eq!(acks, vec![RelayAck { peer_id, outcome: RelayOutcome::Acknowledged(vec![]) }]);
+    }
+
+    #[tokio::test]
+    async fn relay_timed_out_and_disconnected() {
+        let (relay, peer_id) = manager(Duration::from_millis(50));
+        let handle = relay.handle();
+        let events = relay.events_tx.clone();
+        let (to_connection, mut from_manager) = mpsc::unbounded_channel();
+        events.send(RelayEvent::Established { peer_id, to_connection }).unwrap();
+        tokio::spawn(relay);
+
+        let acks = handle.send_transactions(vec![]).await.unwrap();
+        assert_eq!(acks, vec![RelayAck { peer_id, outcome: RelayOutcome::TimedOut }]);
+        assert!(from_manager.recv().await.is_some());
+
+        let relayed = tokio::spawn({
+            let handle = handle.clone();
+            async move { handle.send_transactions(vec![]).await }
+        });
+        assert!(from_manager.recv().await.is_some());
+        events.send(RelayEvent::Closed { peer_id }).unwrap();
+
+        let acks = relayed.await.unwrap().unwrap();
+        assert_eq!(acks, vec![RelayAck { peer_id, outcome: RelayOutcome::Disconnected }]);
+        assert_eq!(handle.send_transactions(vec![]).await, Err(PrivateRelayError::NoPeers));
+    }
+
+    #[tokio::test]
+    async fn ack_inbound_transactions() {
+        let (relay, peer_id) = manager(DEFAULT_ACK_TIMEOUT);
+        let events = relay.events_tx.clone();
+        let (to_connection, mut from_manager) = mpsc::unbounded_channel();
+        events.send(RelayEvent::Established { peer_id, to_connection }).unwrap();
+        tokio::spawn(relay);
+
+        let msg = PrivateRelayMessage::Transactions(RequestPair { request_id: 7, message: vec![] });
+        events.send(RelayEvent::Message { peer_id, msg }).unwrap();
+
+        let ack = from_manager.recv().await.unwrap();
+        assert_eq!(ack, PrivateRelayMessage::Ack(RequestPair { request_id: 7, message: vec![] }));
+    }
+
+    #[tokio::test]
+    async fn reject_inbound_beyond_limit() {
+        let (mut relay, peer_id) = manager(DEFAULT_ACK_TIMEOUT);
+        let (to_connection, mut from_manager) = mpsc::unbounded_channel();
+        relay.on_event(RelayEvent::Established { peer_id, to_connection });
+
+        for request_id in 0..MAX_PENDING_ACKS as u64 {
+            let bundle = PrivateBundle { transactions: vec![], block_number: 1 };
+            let msg = PrivateRelayMessage::Bundle(RequestPair { request_id, message: bundle });
+            relay.on_event(RelayEvent::Message { peer_id, msg });
+        }
+        assert!(from_manager.try_recv().is_err());
+
+        let request_id = MAX_PENDING_ACKS as u64;
+        let msg = PrivateRelayMessage::Transactions(RequestPair { request_id, message: vec![] });
+        relay.on_event(RelayEvent::Message { peer_id, msg });
+
+        let ack = from_manager.try_recv().unwrap();
+        assert_eq!(ack, PrivateRelayMessage::Ack(RequestPair { request_id, message: vec![] }));
+        assert_eq!(relay.pending_acks.len(), MAX_PENDING_ACKS);
+    }
+}
@@ -17,6 +17,7 @@ use std::{
 use crate::{
     message::{NewBlockMessage, PeerMessage, PeerResponse, PeerResponseResult},
     session::{
+        bandwidth::{MessageKind, SessionBandwidth, UploadPriority},
         conn::EthRlpxConnection,
         handle::{ActiveSessionMessage, SessionCommand},
         BlockRangeInfo, EthVersion, SessionId,
@@ -24,6 +25,7 @@ use crate::{
 };
 use alloy_eips::merge::EPOCH_SLOTS;
 use alloy_primitives::Sealable;
+use alloy_rlp::Encodable;
 use futures::{stream::Fuse, SinkExt, StreamExt};
 use metrics::{Counter, Gauge};
 use reth_eth_wire::{
@@ -205,6 +207,8 @@ pub(crate) struct ActiveSession<N: NetworkPrimitives> {
     /// The last latest block number we sent in a range update
     /// Used to avoid sending unnecessary updates when block height hasn't changed significantly
     pub(crate) last_sent_latest_block: Option<u64>,
+    /// Byte accounting and rate limits of the session's traffic.
+    pub(crate) bandwidth: SessionBandwidth,
 }
 
 impl<N: NetworkPrimitives> ActiveSession<N> {
@@ -240,8 +244,11 @@ impl<N: NetworkPrimitives> ActiveSession<N> {
     fn poll_send_queued(&mut self, cx: &mut Context<'_>) -> Result<bool, EthStreamError> {
         let mut progress = false;
         while self.conn.poll_ready_unpin(cx).is_ready() {
-            let Some(msg) = self.queued_outgoing.pop_front() else { break };
+            let Some(msg) = self.next_outgoing(cx) else { break };
             progress = true;
+            if self.bandwidth.is_metered() {
+                self.bandwidth.on_sent(msg.message_kind(), msg.length());
+            }
             let res = match msg {
                 OutgoingMessage::Snap(msg) => self.conn.start_send_snap(msg),
                 OutgoingMessage::Eth(msg) => self.conn.start_send_unpin(msg),
@@ -253,6 +260,27 @@ impl<N: NetworkPrimitives> ActiveSession<N> {
         Ok(progress)
     }
 
+    /// Pops the next queued message the upload limits allow to be sent.
+    ///
+    /// Served bulk responses are throttled first: while they are held back, other messages queued
+    /// behind them are still sent. If nothing can be sent, the session is woken up once the limits
+    /// allow it again.
+    fn next_outgoing(&mut self, cx: &mut Context<'_>) -> Option<OutgoingMessage<N>> {
+        let priority = self.queued_outgoing.front()?.upload_priority();
+        if self.bandwidth.poll_upload(priority, cx).is_ready() {
+            return self.queued_outgoing.pop_front()
+        }
+        if priority == UploadPriority::Normal {
+            return None
+        }
+        let idx =
+            self.queued_outgoing.position(|msg| msg.upload_priority() == UploadPriority::Normal)?;
+        if self.bandwidth.poll_upload(UploadPriority::Normal, cx).is_pending() {
+            return None
+        }
+        self.queued_outgoing.remove(idx)
+    }
+
     /// Handle a message read from the connection.
     ///
     /// Returns an error if the message is considered to be in violation of the protocol.
@@ -911,6 +939,12 @@ impl<N: NetworkPrimitives> Future for ActiveSession<N> {
                     break 'receive
                 }
 
+                // stop reading from the wire while the download limits are exhausted, the
+                // session is woken up once they allow reading again
+                if this.bandwidth.poll_download(cx).is_pending() {
+                    break 'receive
+                }
+
                 match this.conn.poll_next_unpin(cx) {
                     Poll::Pending => {
                         receive_pending = true;
@@ -926,6 +960,19 @@ impl<N: NetworkPrimitives> Future for ActiveSession<N> {
                     Poll::Ready(Some(res)) => {
                         match res {
                             Ok(msg) => {
+                                if this.bandwidth.is_metered() {
+                                    let (kind, len) = match &msg {
+                                        EthSnapMessage::Eth(msg) => (
+                                            MessageKind::eth(msg.message_id().to_u8()),
+                                            1 + msg.length(),
+                                        ),
+                                        EthSnapMessage::Snap(msg) => (
+                                            MessageKind::snap(msg.message_id() as u8),
+                                            msg.length(),
+                                        ),
+                                    };
+                                    this.bandwidth.on_received(kind, len);
+                                }
                                 let outcome = match msg {
                                     EthSnapMessage::Eth(msg) => {
                                         trace!(target: "net::session", msg_id=?msg.message_id(), remote_peer_id=?this.remote_peer_id, "received eth message");
@@ -1130,6 +1177,40 @@ impl<N: NetworkPrimitives> OutgoingMessage<N> {
         }
     }
 
+    /// Returns the type of the message for bandwidth accounting.
+    fn message_kind(&self) -> MessageKind {
+        match self {
+            Self::Eth(msg) => MessageKind::eth(msg.message_id().to_u8()),
+            Self::Broadcast(msg) => MessageKind::eth(msg.message_id().to_u8()),
+            Self::Raw(msg) => u8::try_from(msg.id).map_or(MessageKind::OTHER, MessageKind::eth),
+            Self::Snap(msg) => MessageKind::snap(msg.message_id() as u8),
+        }
+    }
+
+    /// Returns the length of the uncompressed message, including the message id.
+    fn length(&self) -> usize {
+        match self {
+            Self::Eth(msg) => 1 + msg.length(),
+            Self::Broadcast(msg) => 1 + msg.length(),
+            Self::Raw(msg) => 1 + msg.payload.len(),
+            Self::Snap(msg) => msg.length(),
+        }
+    }
+
+    /// Returns how the message is prioritized by the upload limits.
+    const fn upload_priority(&self) -> UploadPriority {
+        match self {
+            Self::Eth(
+                EthMessage::BlockBodies(_) |
+                EthMessage::Receipts(_) |
+                EthMessage::Receipts69(_) |
+                EthMessage::Receipts70(_),
+            ) => UploadPriority::Bulk,
+            Self::Snap(msg) if msg.is_response() => UploadPriority::Bulk,
+            _ => UploadPriority::Normal,
+        }
+    }
+
     /// Returns the number of broadcast items in this message.
     ///
     /// For transaction hash announcements this is the number of hashes, for full transaction
@@ -1260,15 +1341,35 @@ impl<N: NetworkPrimitives> QueuedOutgoingMessages<N> {
         self.count.increment(1);
     }
 
+    pub(crate) fn front(&self) -> Option<&OutgoingMessage<N>> {
+        self.messages.front()
+    }
+
+    /// Returns the index of the first queued message that matches the predicate.
+    pub(crate) fn position(&self, f: impl FnMut(&OutgoingMessage<N>) -> bool) -> Option<usize> {
+        self.messages.iter().position(f)
+    }
+
     pub(crate) fn pop_front(&mut self) -> Option<OutgoingMessage<N>> {
-        self.messages.pop_front().inspect(|msg| {
-            self.count.decrement(1);
-            self.queued_responses -= msg.is_response() as usize;
-            let items = msg.broadcast_item_count();
-            if items > 0 {
-                self.broadcast_items.sub(items);
-            }
-        })
+        let msg = self.messages.pop_front()?;
+        self.on_removed(&msg);
+        Some(msg)
+    }
+
+    /// Removes the message at the given index, see [`Self::position`].
+    pub(crate) fn remove(&mut self, idx: usize) -> Option<OutgoingMessage<N>> {
+        let msg = self.messages.remove(idx)?;
+        self.on_removed(&msg);
+        Some(msg)
+    }
+
+    fn on_removed(&mut self, msg: &OutgoingMessage<N>) {
+        self.count.decrement(1);
+        self.queued_responses -= msg.is_response() as usize;
+        let items = msg.broadcast_item_count();
+        if items > 0 {
+            self.broadcast_items.sub(items);
+        }
     }
 
     /// Pushes a pooled transaction hash announcement, merging into the last queued message if
@@ -1309,16 +1410,21 @@ impl<N: NetworkPrimitives> Drop for QueuedOutgoingMessages<N> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::session::{handle::PendingSessionEvent, start_pending_incoming_session};
+    use crate::session::{
+        bandwidth::BandwidthManager, handle::PendingSessionEvent, start_pending_incoming_session,
+    };
     use alloy_eips::eip2124::ForkFilter;
     use alloy_primitives::B256;
     use futures::task::noop_waker;
     use reth_chainspec::MAINNET;
     use reth_ecies::stream::ECIESStream;
     use reth_eth_wire::{
-        handshake::EthHandshake, protocol::Protocol, EthNetworkPrimitives, EthStream,
-        GetBlockAccessLists, GetBlockBodies, HelloMessageWithProtocols, P2PStream, StatusBuilder,
-        UnauthedEthStream, UnauthedP2PStream, UnifiedStatus,
+        handshake::EthHandshake,
+        protocol::Protocol,
+        recorder::{RecordDirection, RecorderConfig, Recording},
+        EthNetworkPrimitives, EthStream, GetBlockAccessLists, GetBlockBodies,
+        HelloMessageWithProtocols, P2PStream, StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
+        UnifiedStatus,
     };
     use reth_eth_wire_types::{
         message::MAX_MESSAGE_SIZE,
@@ -1331,7 +1437,7 @@ mod tests {
     use reth_ethereum_forks::EthereumHardfork;
     use reth_network_p2p::error::RequestResult;
     use reth_network_peers::pk2id;
-    use reth_network_types::session::config::PROTOCOL_BREACH_REQUEST_TIMEOUT;
+    use reth_network_types::{session::config::PROTOCOL_BREACH_REQUEST_TIMEOUT, BandwidthLimits};
     use secp256k1::{SecretKey, SECP256K1};
     use tokio::{
         net::{TcpListener, TcpStream},
@@ -1353,6 +1459,7 @@ mod tests {
         hello: HelloMessageWithProtocols,
         status: UnifiedStatus,
         fork_filter: ForkFilter,
+        message_recorder: Option<Arc<RecorderConfig>>,
         next_id: usize,
     }
 
@@ -1405,6 +1512,7 @@ mod tests {
             tokio::task::spawn(start_pending_incoming_session(
                 Arc::new(EthHandshake::default()),
                 MAX_MESSAGE_SIZE,
+                self.message_recorder.clone(),
                 disconnect_rx,
                 session_id,
                 stream,
@@ -1473,6 +1581,7 @@ mod tests {
                         ),
                         range_update_interval: None,
                         last_sent_latest_block: None,
+                        bandwidth: Default::default(),
                     }
                 }
                 ev => {
@@ -1502,6 +1611,7 @@ mod tests {
                 fork_filter: MAINNET
                     .hardfork_fork_filter(EthereumHardfork::Frontier)
                     .expect("The Frontier fork filter should exist on mainnet"),
+                message_recorder: None,
             }
         }
     }
@@ -1653,6 +1763,112 @@ mod tests {
         rx.await.unwrap();
     }
 
+    /// Reads the recording in the directory once it contains `len` messages.
+    ///
+    /// Recordings are written in the background, so they may lag behind the session.
+    async fn read_recording(dir: &std::path::Path, len: usize) -> Recording {
+        let read = async {
+            loop {
+                if let Some(file) = std::fs::read_dir(dir).unwrap().next() {
+                    let recording = Recording::read(file.unwrap().path()).unwrap();
+                    if recording.messages.len() >= len {
+                        return recording
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), read).await.expect("recording not written")
+    }
+
+    /// Runs an incoming session that records `NewPooledTransactionHashes` messages to the
+    /// directory, while the peer runs the given closure.
+    async fn record_session<F, O>(dir: &std::path::Path, f: F)
+    where
+        F: FnOnce(EthStream<P2PStream<ECIESStream<TcpStream>>, EthNetworkPrimitives>) -> O
+            + Send
+            + 'static,
+        O: Future<Output = ()> + Send + Sync,
+    {
+        let mut builder = SessionBuilder::default();
+        builder.message_recorder = Some(Arc::new(
+            RecorderConfig::new(dir).with_messages(["eth/NewPooledTransactionHashes"]),
+        ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let fut = builder.with_client_stream(local_addr, f);
+
+        let (tx, rx) = oneshot::channel();
+
+        tokio::task::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let session = builder.connect_incoming(incoming).await;
+            session.await;
+
+            tx.send(()).unwrap();
+        });
+
+        tokio::task::spawn(fut);
+
+        rx.await.unwrap();
+    }
+
+    fn pooled_hashes(i: u8) -> EthMessage {
+        EthMessage::NewPooledTransactionHashes66(vec![B256::with_last_byte(i)].into())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn records_session_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        record_session(dir.path(), async move |mut client_stream| {
+            for i in 0..3 {
+                client_stream.send(pooled_hashes(i)).await.unwrap();
+            }
+        })
+        .await;
+
+        let recording = read_recording(dir.path(), 3).await;
+        assert!(recording.peer_id.is_some());
+        assert_eq!(recording.messages.len(), 3);
+        for msg in &recording.messages {
+            assert_eq!(msg.direction, RecordDirection::Inbound);
+            assert_eq!(msg.name, "NewPooledTransactionHashes");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn replays_recorded_session() {
+        let dir = tempfile::tempdir().unwrap();
+        record_session(dir.path(), async move |mut client_stream| {
+            for i in 0..3 {
+                client_stream.send(pooled_hashes(i)).await.unwrap();
+            }
+        })
+        .await;
+        let recording = read_recording(dir.path(), 3).await;
+
+        // replay the recorded messages to a new session, which records them again
+        let replay_dir = tempfile::tempdir().unwrap();
+        let replayed = recording.clone();
+        record_session(replay_dir.path(), async move |mut client_stream| {
+            let p2p_stream = client_stream.inner_mut();
+            let messages = replayed.replay_messages(p2p_stream.shared_capabilities()).unwrap();
+            for msg in messages {
+                p2p_stream.send(msg).await.unwrap();
+            }
+        })
+        .await;
+
+        let replayed = read_recording(replay_dir.path(), 3).await;
+        assert_eq!(replayed.messages.len(), recording.messages.len());
+        for (replayed, recorded) in replayed.messages.iter().zip(&recording.messages) {
+            assert_eq!(replayed.direction, recorded.direction);
+            assert_eq!(replayed.payload, recorded.payload);
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_request_timeout() {
         reth_tracing::init_test_tracing();
@@ -2059,6 +2275,55 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn upload_limit_throttles_bulk_responses_first() {
+        let mut builder = SessionBuilder::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let fut = builder.with_client_stream(local_addr, async move |mut client_stream| {
+            let _ = tokio::time::timeout(Duration::from_secs(100), client_stream.next()).await;
+        });
+        tokio::task::spawn(fut);
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        let mut session = builder.connect_incoming(incoming).await;
+        session.bandwidth =
+            BandwidthManager::new(BandwidthLimits::default().with_max_peer_upload(1000))
+                .new_session();
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        session
+            .queued_outgoing
+            .push_back(OutgoingMessage::Raw(RawCapabilityMessage::new(0x42, vec![0; 700].into())));
+        session.queued_outgoing.push_back(
+            EthMessage::BlockBodies(RequestPair { request_id: 1, message: Default::default() })
+                .into(),
+        );
+        session.queued_outgoing.push_back(EthMessage::NewBlockHashes(Default::default()).into());
+
+        let raw = session.next_outgoing(&mut cx).unwrap();
+        assert_eq!(raw.message_kind(), MessageKind::OTHER);
+        session.bandwidth.on_sent(raw.message_kind(), raw.length());
+
+        // the bodies response is held back, the announcement queued behind it goes out
+        let msg = session.next_outgoing(&mut cx).unwrap();
+        assert!(matches!(msg, OutgoingMessage::Eth(EthMessage::NewBlockHashes(_))));
+        assert!(session.next_outgoing(&mut cx).is_none());
+        assert_eq!(session.queued_outgoing.response_count(), 1);
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let msg = session.next_outgoing(&mut cx).unwrap();
+        assert!(matches!(msg, OutgoingMessage::Eth(EthMessage::BlockBodies(_))));
+        assert!(session.queued_outgoing.is_empty());
+
+        let stats = session.bandwidth.meter().unwrap().stats();
+        assert_eq!(stats.egress_by_message["Other"], 701);
+    }
+
     #[test]
     fn timeout_calculation_sanity_tests() {
         let rtt = Duration::from_secs(5);
@@ -0,0 +1,491 @@
+//! Byte accounting and rate limiting of session traffic.
+//!
+//! Traffic is measured on the uncompressed message payloads, including the message id, and
+//! accounted by message type. Every session records its traffic in a [`BandwidthMeter`] that is
+//! shared with its [`ActiveSessionHandle`](super::ActiveSessionHandle), and in the global
+//! [`BandwidthMetrics`].
+//!
+//! Measuring requires the encoded length of every message, so traffic is only measured if
+//! accounting is enabled or a rate limit is set, see [`BandwidthLimits::is_metered`].
+//!
+//! Rate limits are token buckets that are shared by all sessions, or owned by a single session,
+//! see [`BandwidthLimits`].
+
+use crate::metrics::BandwidthMetrics;
+use parking_lot::Mutex;
+use reth_network_api::BandwidthStats;
+use reth_network_types::BandwidthLimits;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep_until, Instant, Sleep};
+
+/// Names of the `eth` messages, indexed by message id. Unassigned ids are empty.
+const ETH_MESSAGES: [&str; 22] = [
+    "Status",
+    "NewBlockHashes",
+    "Transactions",
+    "GetBlockHeaders",
+    "BlockHeaders",
+    "GetBlockBodies",
+    "BlockBodies",
+    "NewBlock",
+    "NewPooledTransactionHashes",
+    "GetPooledTransactions",
+    "PooledTransactions",
+    "",
+    "",
+    "GetNodeData",
+    "NodeData",
+    "GetReceipts",
+    "Receipts",
+    "BlockRangeUpdate",
+    "GetBlockAccessLists",
+    "BlockAccessLists",
+    "GetCells",
+    "Cells",
+];
+
+/// Names of the `snap` messages, indexed by message id. Unassigned ids are empty.
+const SNAP_MESSAGES: [&str; 10] = [
+    "snap/GetAccountRange",
+    "snap/AccountRange",
+    "snap/GetStorageRanges",
+    "snap/StorageRanges",
+    "snap/GetByteCodes",
+    "snap/ByteCodes",
+    "",
+    "",
+    "snap/GetBlockAccessLists",
+    "snap/BlockAccessLists",
+];
+
+/// Number of message types traffic is accounted by, including [`MessageKind::OTHER`].
+pub(crate) const MESSAGE_KINDS: usize = ETH_MESSAGES.len() + SNAP_MESSAGES.len() + 1;
+
+/// The type of a message, traffic is accounted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MessageKind(usize);
+
+impl MessageKind {
+    /// Messages of unknown types and other protocols.
+    pub(crate) const OTHER: Self = Self(MESSAGE_KINDS - 1);
+
+    /// Returns the kind of the `eth` message with the given id.
+    pub(crate) fn eth(id: u8) -> Self {
+        Self::known(id as usize, &ETH_MESSAGES, 0)
+    }
+
+    /// Returns the kind of the `snap` message with the given id.
+    pub(crate) fn snap(id: u8) -> Self {
+        Self::known(id as usize, &SNAP_MESSAGES, ETH_MESSAGES.len())
+    }
+
+    fn known(id: usize, names: &[&str], offset: usize) -> Self {
+        match names.get(id) {
+            Some(name) if !name.is_empty() => Self(offset + id),
+            _ => Self::OTHER,
+        }
+    }
+
+    /// Returns all message kinds.
+    pub(crate) fn all() -> impl Iterator<Item = Self> {
+        (0..MESSAGE_KINDS).map(Self).filter(|kind| !kind.name().is_empty())
+    }
+
+    /// Returns the index of the message kind in `[_; MESSAGE_KINDS]` arrays.
+    pub(crate) const fn index(self) -> usize {
+        self.0
+    }
+
+    /// Returns the name of the message type.
+    pub(crate) fn name(self) -> &'static str {
+        ETH_MESSAGES.iter().chain(&SNAP_MESSAGES).nth(self.0).copied().unwrap_or("Other")
+    }
+}
+
+/// How a message is prioritized by the upload limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UploadPriority {
+    /// Served bulk responses: block bodies, receipts and `snap` responses.
+    ///
+    /// These are throttled once half of the burst allowance is used up.
+    Bulk,
+    /// Everything else, i.e. gossip and our own requests.
+    ///
+    /// These are only throttled once the burst allowance is exhausted.
+    Normal,
+}
+
+/// Per message type byte counters of a session.
+///
+/// Shared by the session task and its handle.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BandwidthMeter(Arc<BandwidthCounters>);
+
+#[derive(Debug)]
+struct BandwidthCounters {
+    ingress: [AtomicU64; MESSAGE_KINDS],
+    egress: [AtomicU64; MESSAGE_KINDS],
+}
+
+impl Default for BandwidthCounters {
+    fn default() -> Self {
+        Self {
+            ingress: std::array::from_fn(|_| AtomicU64::new(0)),
+            egress: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl BandwidthMeter {
+    /// Records bytes received from the peer.
+    pub(crate) fn record_ingress(&self, kind: MessageKind, bytes: usize) {
+        self.0.ingress[kind.index()].fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Records bytes sent to the peer.
+    pub(crate) fn record_egress(&self, kind: MessageKind, bytes: usize) {
+        self.0.egress[kind.index()].fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the current counters, omitting message types without traffic.
+    pub(crate) fn stats(&self) -> BandwidthStats {
+        let mut stats = BandwidthStats::default();
+        for kind in MessageKind::all() {
+            let ingress = self.0.ingress[kind.index()].load(Ordering::Relaxed);
+            if ingress > 0 {
+                stats.ingress += ingress;
+                stats.ingress_by_message.insert(kind.name().to_string(), ingress);
+            }
+            let egress = self.0.egress[kind.index()].load(Ordering::Relaxed);
+            if egress > 0 {
+                stats.egress += egress;
+                stats.egress_by_message.insert(kind.name().to_string(), egress);
+            }
+        }
+        stats
+    }
+}
+
+/// A token bucket rate limit that can be shared by multiple sessions.
+///
+/// The bucket holds up to one second worth of bytes. Messages are never split, so sending a
+/// message can take the bucket into debt, which delays subsequent messages accordingly.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter(Arc<Mutex<TokenBucket>>);
+
+impl RateLimiter {
+    /// Creates a new rate limit of `rate` bytes per second.
+    pub(crate) fn new(rate: u64) -> Self {
+        let capacity = rate.max(1) as f64;
+        Self(Arc::new(Mutex::new(TokenBucket {
+            rate: capacity,
+            capacity,
+            tokens: capacity,
+            updated: Instant::now(),
+        })))
+    }
+
+    /// Returns the instant a message of the given priority can be sent, or `None` if it can be
+    /// sent right away.
+    fn ready_at(&self, now: Instant, priority: UploadPriority) -> Option<Instant> {
+        let mut bucket = self.0.lock();
+        bucket.refill(now);
+        let threshold = match priority {
+            UploadPriority::Bulk => bucket.capacity / 2.,
+            UploadPriority::Normal => 0.,
+        };
+        let missing = threshold - bucket.tokens;
+        // wait at least a millisecond, so an almost refilled bucket doesn't spin the session
+        (missing >= 0.).then(|| {
+            now + Duration::from_secs_f64(missing / bucket.rate).max(Duration::from_millis(1))
+        })
+    }
+
+    /// Takes the given number of bytes from the bucket.
+    fn consume(&self, now: Instant, bytes: usize) {
+        let mut bucket = self.0.lock();
+        bucket.refill(now);
+        bucket.tokens -= bytes as f64;
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    /// Bytes added per second.
+    rate: f64,
+    /// Maximum number of bytes in the bucket.
+    capacity: f64,
+    /// Available bytes, negative if the bucket is in debt.
+    tokens: f64,
+    /// When the bucket was last refilled.
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = elapsed.mul_add(self.rate, self.tokens).min(self.capacity);
+        self.updated = self.updated.max(now);
+    }
+}
+
+/// The global and per session limits of one direction.
+#[derive(Debug, Clone, Default)]
+struct Limits {
+    global: Option<RateLimiter>,
+    session: Option<RateLimiter>,
+}
+
+impl Limits {
+    fn ready_at(&self, now: Instant, priority: UploadPriority) -> Option<Instant> {
+        let global = self.global.as_ref().and_then(|limit| limit.ready_at(now, priority));
+        let session = self.session.as_ref().and_then(|limit| limit.ready_at(now, priority));
+        global.max(session)
+    }
+
+    fn consume(&self, now: Instant, bytes: usize) {
+        for limit in self.global.iter().chain(&self.session) {
+            limit.consume(now, bytes);
+        }
+    }
+
+    const fn is_unlimited(&self) -> bool {
+        self.global.is_none() && self.session.is_none()
+    }
+
+    /// Returns `Poll::Ready` if a message of the given priority can be sent.
+    ///
+    /// Otherwise the delay is armed to wake the session once the limits allow it.
+    fn poll_ready(
+        &self,
+        delay: &mut Option<Pin<Box<Sleep>>>,
+        priority: UploadPriority,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        if self.is_unlimited() {
+            return Poll::Ready(())
+        }
+        loop {
+            let Some(ready_at) = self.ready_at(Instant::now(), priority) else {
+                return Poll::Ready(())
+            };
+            let delay = delay.get_or_insert_with(|| Box::pin(sleep_until(ready_at)));
+            delay.as_mut().reset(ready_at);
+            ready!(delay.as_mut().poll(cx));
+        }
+    }
+}
+
+/// Bandwidth accounting and limits shared by all sessions.
+#[derive(Debug, Default)]
+pub(crate) struct BandwidthManager {
+    limits: BandwidthLimits,
+    upload: Option<RateLimiter>,
+    download: Option<RateLimiter>,
+    metrics: Arc<BandwidthMetrics>,
+}
+
+impl BandwidthManager {
+    /// Creates a new manager that enforces the given limits.
+    pub(crate) fn new(limits: BandwidthLimits) -> Self {
+        Self {
+            limits,
+            upload: limits.max_upload.map(RateLimiter::new),
+            download: limits.max_download.map(RateLimiter::new),
+            metrics: Default::default(),
+        }
+    }
+
+    /// Returns the bandwidth accounting of a new session.
+    pub(crate) fn new_session(&self) -> SessionBandwidth {
+        SessionBandwidth {
+            meter: Default::default(),
+            metrics: self.metrics.clone(),
+            upload: Limits {
+                global: self.upload.clone(),
+                session: self.limits.max_peer_upload.map(RateLimiter::new),
+            },
+            download: Limits {
+                global: self.download.clone(),
+                session: self.limits.max_peer_download.map(RateLimiter::new),
+            },
+            metered: self.limits.is_metered(),
+            upload_delay: None,
+            download_delay: None,
+        }
+    }
+}
+
+/// Bandwidth accounting and limits of a single session.
+#[derive(Debug, Default)]
+pub(crate) struct SessionBandwidth {
+    meter: BandwidthMeter,
+    metrics: Arc<BandwidthMetrics>,
+    upload: Limits,
+    download: Limits,
+    /// Whether the traffic of the session is measured.
+    metered: bool,
+    /// Wakes the session once the upload limits allow sending again.
+    upload_delay: Option<Pin<Box<Sleep>>>,
+    /// Wakes the session once the download limits allow reading again.
+    download_delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl SessionBandwidth {
+    /// Returns the byte counters of the session, if its traffic is measured.
+    pub(crate) fn meter(&self) -> Option<BandwidthMeter> {
+        self.metered.then(|| self.meter.clone())
+    }
+
+    /// Returns `true` if the traffic of the session is measured.
+    ///
+    /// If not, [`Self::on_sent`] and [`Self::on_received`] need not be called.
+    pub(crate) const fn is_metered(&self) -> bool {
+        self.metered
+    }
+
+    /// Returns `Poll::Ready` if a message of the given priority can be sent to the peer.
+    ///
+    /// Registers the waker if the upload limits are exhausted.
+    pub(crate) fn poll_upload(
+        &mut self,
+        priority: UploadPriority,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        self.upload.poll_ready(&mut self.upload_delay, priority, cx)
+    }
+
+    /// Returns `Poll::Ready` if the next message can be read from the peer.
+    ///
+    /// Registers the waker if the download limits are exhausted.
+    pub(crate) fn poll_download(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.download.poll_ready(&mut self.download_delay, UploadPriority::Normal, cx)
+    }
+
+    /// Records a message sent to the peer.
+    pub(crate) fn on_sent(&self, kind: MessageKind, bytes: usize) {
+        self.meter.record_egress(kind, bytes);
+        self.metrics.egress[kind.index()].increment(bytes as u64);
+        if !self.upload.is_unlimited() {
+            self.upload.consume(Instant::now(), bytes);
+        }
+    }
+
+    /// Records a message received from the peer.
+    pub(crate) fn on_received(&self, kind: MessageKind, bytes: usize) {
+        self.meter.record_ingress(kind, bytes);
+        self.metrics.ingress[kind.index()].increment(bytes as u64);
+        if !self.download.is_unlimited() {
+            self.download.consume(Instant::now(), bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_eth_wire_types::{snap::SnapMessageId, EthMessageID};
+
+    #[test]
+    fn message_kind_names() {
+        assert_eq!(MessageKind::eth(EthMessageID::BlockBodies.to_u8()).name(), "BlockBodies");
+        assert_eq!(MessageKind::eth(EthMessageID::Cells.to_u8()).name(), "Cells");
+        assert_eq!(
+            MessageKind::snap(SnapMessageId::StorageRanges as u8).name(),
+            "snap/StorageRanges"
+        );
+        assert_eq!(MessageKind::eth(0x0b), MessageKind::OTHER);
+        assert_eq!(MessageKind::snap(0x06), MessageKind::OTHER);
+        assert_eq!(MessageKind::eth(u8::MAX), MessageKind::OTHER);
+        assert_eq!(MessageKind::OTHER.name(), "Other");
+        assert_eq!(MessageKind::all().count(), 20 + 8 + 1);
+    }
+
+    #[test]
+    fn meter_stats() {
+        let meter = BandwidthMeter::default();
+        let bodies = MessageKind::eth(EthMessageID::BlockBodies.to_u8());
+        meter.record_egress(bodies, 100);
+        meter.record_egress(bodies, 50);
+        meter.record_egress(MessageKind::OTHER, 1);
+        meter.record_ingress(MessageKind::eth(EthMessageID::GetBlockBodies.to_u8()), 10);
+
+        let stats = meter.stats();
+        assert_eq!(stats.egress, 151);
+        assert_eq!(stats.ingress, 10);
+        assert_eq!(stats.egress_by_message.len(), 2);
+        assert_eq!(stats.egress_by_message["BlockBodies"], 150);
+        assert_eq!(stats.ingress_by_message["GetBlockBodies"], 10);
+    }
+
+    #[test]
+    fn sessions_are_metered_if_enabled() {
+        let session = BandwidthManager::new(BandwidthLimits::default()).new_session();
+        assert!(!session.is_metered());
+        assert!(session.meter().is_none());
+
+        let limits = BandwidthLimits::default().with_accounting();
+        assert!(BandwidthManager::new(limits).new_session().meter().is_some());
+        let limits = BandwidthLimits::default().with_max_peer_download(1);
+        assert!(BandwidthManager::new(limits).new_session().is_metered());
+    }
+
+    #[test]
+    fn bulk_responses_are_throttled_first() {
+        let limit = RateLimiter::new(1000);
+        let now = Instant::now();
+        assert!(limit.ready_at(now, UploadPriority::Bulk).is_none());
+
+        // more than half of the burst allowance is used up
+        limit.consume(now, 600);
+        let bulk_ready = limit.ready_at(now, UploadPriority::Bulk).unwrap();
+        assert_eq!(bulk_ready - now, Duration::from_millis(100));
+        assert!(limit.ready_at(now, UploadPriority::Normal).is_none());
+
+        // a message larger than the remaining tokens takes the bucket into debt
+        limit.consume(now, 900);
+        let normal_ready = limit.ready_at(now, UploadPriority::Normal).unwrap();
+        assert_eq!(normal_ready - now, Duration::from_millis(500));
+        assert_eq!(
+            limit.ready_at(normal_ready, UploadPriority::Bulk).unwrap() - normal_ready,
+            Duration::from_millis(500)
+        );
+        let after = normal_ready + Duration::from_millis(1);
+        assert!(limit.ready_at(after, UploadPriority::Normal).is_none());
+
+        // the bucket refills up to one second worth of bytes
+        let later = now + Duration::from_secs(10);
+        limit.consume(later, 400);
+        assert!(limit.ready_at(later, UploadPriority::Bulk).is_none());
+    }
+
+    #[test]
+    fn session_limits_combine_global_limit() {
+        let manager = BandwidthManager::new(
+            BandwidthLimits::default().with_max_upload(1000).with_max_peer_upload(100),
+        );
+        let first = manager.new_session();
+        let second = manager.new_session();
+        let now = Instant::now();
+
+        first.upload.consume(now, 200);
+        // the first session exceeded its own limit, the second one is only affected by the
+        // global limit
+        assert!(first.upload.ready_at(now, UploadPriority::Normal).is_some());
+        assert!(second.upload.ready_at(now, UploadPriority::Normal).is_none());
+        assert!(second.upload.ready_at(now, UploadPriority::Bulk).is_none());
+
+        second.upload.consume(now, 400);
+        assert!(second.upload.ready_at(now, UploadPriority::Bulk).is_some());
+        assert!(second.download.is_unlimited());
+    }
+}
@@ -2,7 +2,10 @@
 
 use crate::{
     message::PeerMessage,
-    session::{active::BroadcastItemCounter, conn::EthRlpxConnection, Direction, SessionId},
+    session::{
+        active::BroadcastItemCounter, bandwidth::BandwidthMeter, conn::EthRlpxConnection,
+        Direction, SessionId,
+    },
     PendingSessionHandshakeError,
 };
 use reth_ecies::ECIESError;
@@ -81,6 +84,8 @@ pub struct ActiveSessionHandle<N: NetworkPrimitives> {
     pub(crate) peer_listen_port: Option<u16>,
     /// The Status message the peer sent for the `eth` handshake
     pub(crate) status: Arc<UnifiedStatus>,
+    /// Bytes exchanged with the peer, recorded by the session task if its traffic is measured.
+    pub(crate) bandwidth: Option<BandwidthMeter>,
 }
 
 // === impl ActiveSessionHandle ===
@@ -173,6 +178,7 @@ impl<N: NetworkPrimitives> ActiveSessionHandle<N> {
             status: self.status.clone(),
             session_established: self.established,
             kind,
+            bandwidth: self.bandwidth.as_ref().map(BandwidthMeter::stats),
         }
     }
 }
@@ -1,6 +1,7 @@
 //! Support for handling peer sessions.
 
 mod active;
+pub(crate) mod bandwidth;
 mod conn;
 mod counter;
 mod handle;
@@ -15,11 +16,15 @@ use crate::{
 };
 use active::QueuedOutgoingMessages;
 use alloy_primitives::map::{FbBuildHasher, HashMap};
+use bandwidth::BandwidthManager;
 use counter::SessionCounter;
 use futures::{future::Either, io, FutureExt, StreamExt};
 use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
-    errors::EthStreamError, handshake::EthRlpxHandshake, multiplex::RlpxProtocolMultiplexer,
+    errors::EthStreamError,
+    handshake::EthRlpxHandshake,
+    multiplex::RlpxProtocolMultiplexer,
+    recorder::{MessageDecoder, RecorderConfig},
     BlockRangeUpdate, Capabilities, DisconnectReason, EthSnapStream, EthStream, EthVersion,
     HelloMessageWithProtocols, NetworkPrimitives, UnauthedP2PStream, UnifiedStatus,
     HANDSHAKE_TIMEOUT,
@@ -46,7 +51,7 @@ use tokio::{
 };
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::PollSender;
-use tracing::{instrument, trace};
+use tracing::{debug, instrument, trace};
 
 use crate::session::active::{
     request_timeout_interval, BroadcastItemCounter, RANGE_UPDATE_INTERVAL,
@@ -122,12 +127,16 @@ pub struct SessionManager<N: NetworkPrimitives> {
     handshake: Arc<dyn EthRlpxHandshake>,
     /// Maximum allowed ETH message size for post-handshake ETH/Snap streams.
     eth_max_message_size: usize,
+    /// Records the messages of new sessions, if configured.
+    message_recorder: Option<Arc<RecorderConfig>>,
     /// Shared local range information that gets propagated to active sessions.
     /// This represents the range of blocks that this node can serve to other peers.
     local_range_info: BlockRangeInfo,
     /// When true, block announcement messages (`NewBlock`, `NewBlockHashes`) are rejected before
     /// RLP decoding on new sessions to avoid memory amplification.
     reject_block_announcements: bool,
+    /// Byte accounting and rate limits shared by all sessions.
+    bandwidth: BandwidthManager,
 }
 
 // === impl SessionManager ===
@@ -145,6 +154,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
         extra_protocols: RlpxSubProtocols,
         handshake: Arc<dyn EthRlpxHandshake>,
         eth_max_message_size: usize,
+        message_recorder: Option<RecorderConfig>,
         reject_block_announcements: bool,
     ) -> Self {
         let (pending_sessions_tx, pending_sessions_rx) = mpsc::channel(config.session_event_buffer);
@@ -181,8 +191,10 @@ impl<N: NetworkPrimitives> SessionManager<N> {
             metrics: Default::default(),
             handshake,
             eth_max_message_size,
+            message_recorder: message_recorder.map(Arc::new),
             local_range_info,
             reject_block_announcements,
+            bandwidth: BandwidthManager::new(config.bandwidth),
         }
     }
 
@@ -296,6 +308,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
             start_pending_incoming_session(
                 self.handshake.clone(),
                 self.eth_max_message_size,
+                self.message_recorder.clone(),
                 disconnect_rx,
                 session_id,
                 stream,
@@ -339,6 +352,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                 start_pending_outbound_session(
                     self.handshake.clone(),
                     self.eth_max_message_size,
+                    self.message_recorder.clone(),
                     disconnect_rx,
                     pending_events,
                     session_id,
@@ -577,6 +591,9 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                     conn.set_reject_block_announcements(true);
                 }
 
+                let bandwidth = self.bandwidth.new_session();
+                let bandwidth_meter = bandwidth.meter();
+
                 let session = ActiveSession {
                     next_id: 0,
                     remote_peer_id: peer_id,
@@ -606,6 +623,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                     local_range_info: self.local_range_info.clone(),
                     range_update_interval,
                     last_sent_latest_block: None,
+                    bandwidth,
                 };
 
                 let supports_snap = session.conn.supports_snap();
@@ -625,6 +643,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                     remote_addr,
                     local_addr,
                     peer_listen_port,
+                    bandwidth: bandwidth_meter,
                 };
 
                 self.active_sessions.insert(peer_id, handle);
@@ -916,6 +935,7 @@ pub(crate) async fn pending_session_with_timeout<F, N: NetworkPrimitives>(
 pub(crate) async fn start_pending_incoming_session<N: NetworkPrimitives>(
     handshake: Arc<dyn EthRlpxHandshake>,
     eth_max_message_size: usize,
+    message_recorder: Option<Arc<RecorderConfig>>,
     disconnect_rx: oneshot::Receiver<()>,
     session_id: SessionId,
     stream: TcpStream,
@@ -930,6 +950,7 @@ pub(crate) async fn start_pending_incoming_session<N: NetworkPrimitives>(
     authenticate(
         handshake,
         eth_max_message_size,
+        message_recorder,
         disconnect_rx,
         events,
         stream,
@@ -951,6 +972,7 @@ pub(crate) async fn start_pending_incoming_session<N: NetworkPrimitives>(
 async fn start_pending_outbound_session<N: NetworkPrimitives>(
     handshake: Arc<dyn EthRlpxHandshake>,
     eth_max_message_size: usize,
+    message_recorder: Option<Arc<RecorderConfig>>,
     disconnect_rx: oneshot::Receiver<()>,
     events: mpsc::Sender<PendingSessionEvent<N>>,
     session_id: SessionId,
@@ -984,6 +1006,7 @@ async fn start_pending_outbound_session<N: NetworkPrimitives>(
     authenticate(
         handshake,
         eth_max_message_size,
+        message_recorder,
         disconnect_rx,
         events,
         stream,
@@ -1004,6 +1027,7 @@ async fn start_pending_outbound_session<N: NetworkPrimitives>(
 async fn authenticate<N: NetworkPrimitives>(
     handshake: Arc<dyn EthRlpxHandshake>,
     eth_max_message_size: usize,
+    message_recorder: Option<Arc<RecorderConfig>>,
     disconnect_rx: oneshot::Receiver<()>,
     events: mpsc::Sender<PendingSessionEvent<N>>,
     stream: TcpStream,
@@ -1037,6 +1061,7 @@ async fn authenticate<N: NetworkPrimitives>(
     let auth = authenticate_stream(
         handshake,
         eth_max_message_size,
+        message_recorder,
         unauthed,
         session_id,
         remote_addr,
@@ -1091,6 +1116,7 @@ async fn get_ecies_stream<Io: AsyncRead + AsyncWrite + Unpin>(
 async fn authenticate_stream<N: NetworkPrimitives>(
     handshake: Arc<dyn EthRlpxHandshake>,
     eth_max_message_size: usize,
+    message_recorder: Option<Arc<RecorderConfig>>,
     stream: UnauthedP2PStream<ECIESStream<TcpStream>>,
     session_id: SessionId,
     remote_addr: SocketAddr,
@@ -1117,6 +1143,25 @@ async fn authenticate_stream<N: NetworkPrimitives>(
         }
     };
 
+    if let Some(recorder) = message_recorder.as_deref() {
+        match recorder.session_recorder(
+            their_hello.id,
+            p2p_stream.shared_capabilities().clone(),
+            MessageDecoder::eth::<N>(),
+        ) {
+            Ok(Some(recorder)) => p2p_stream.set_recorder(recorder),
+            Ok(None) => {}
+            Err(err) => {
+                debug!(
+                    target: "net::session",
+                    %err,
+                    peer_id=?their_hello.id,
+                    "failed to create session recording"
+                )
+            }
+        }
+    }
+
     // if we have extra handlers, check if it must be supported by the remote
     if !extra_handlers.is_empty() {
         // ensure that no extra handlers that aren't supported are not mandatory
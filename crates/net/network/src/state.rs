@@ -387,6 +387,14 @@ impl<N: NetworkPrimitives> NetworkState<N> {
                 self.ban_discovery(peer_id, ip_addr)
             }
             PeerAction::DiscoveryBanIp { ip_addr } => self.ban_ip_discovery(ip_addr),
+            PeerAction::DiscoveryBanEntry { entry } => {
+                trace!(target: "net", target=%entry.target, "Banning discovery");
+                self.discovery.ban_entry(&entry)
+            }
+            PeerAction::DiscoveryUnban { target } => {
+                trace!(target: "net", %target, "Unbanning discovery");
+                self.discovery.unban(target)
+            }
             PeerAction::PeerAdded(peer_id) => {
                 self.queued_messages.push_back(StateAction::PeerAdded(peer_id))
             }
@@ -2438,6 +2438,7 @@ mod tests {
             timestamp: Instant::now(),
             origin: TransactionOrigin::External,
             authority_ids: None,
+            simulation: Default::default(),
         })
     }
 
@@ -16,6 +16,8 @@ use reth_chainspec::{EthChainSpec, EthereumHardforks, Hardforks};
 use reth_db_api::{database::Database, database_metrics::DatabaseMetrics};
 use reth_exex::ExExContext;
 use reth_network::{
+    light::LightRequestHandler,
+    private_relay::PrivateRelayManager,
     transactions::{
         config::{AnnouncementFilteringPolicy, StrictEthAnnouncementFilter},
         TransactionPropagationPolicy, TransactionsManagerConfig,
@@ -925,7 +927,7 @@ impl<Node: FullNodeTypes> BuilderContext<Node> {
         PropPolicy: TransactionPropagationPolicy<N>,
         AnnPolicy: AnnouncementFilteringPolicy<N>,
     {
-        let (handle, network, txpool, eth) = builder
+        let (handle, mut network, txpool, eth) = builder
             .transactions_with_policies(
                 pool.clone(),
                 tx_config,
@@ -945,12 +947,37 @@ impl<Node: FullNodeTypes> BuilderContext<Node> {
         self.executor.spawn_critical_blocking_task("p2p txpool", txpool);
         self.executor.spawn_critical_blocking_task("p2p eth request handler", eth);
 
+        if let Some(relay_config) = self.config().network.private_relay_config() {
+            let relay = PrivateRelayManager::new(pool, relay_config);
+            relay.install(&mut network);
+            self.executor.spawn_critical_task("p2p private relay", relay);
+        }
+
+        if let Some(light_config) = self.config().network.light_serve_config() {
+            let light = LightRequestHandler::new(self.provider().clone(), light_config);
+            network.add_rlpx_sub_protocol(light.protocol());
+            self.executor.spawn_critical_blocking_task("p2p light request handler", light);
+        }
+
         let default_peers_path = self.config().datadir().known_peers();
         let known_peers_file = self.config().network.persistent_peers_file(default_peers_path);
+        let bans_file = self.config().datadir().banlist();
+        match network.read_bans_from_file(&bans_file) {
+            Ok(0) => {}
+            Ok(num_bans) => {
+                info!(target: "reth::cli", ?bans_file, %num_bans, "Restored network bans");
+            }
+            Err(err) => {
+                warn!(target: "reth::cli", %err, "Failed to read network bans from file");
+            }
+        }
         self.executor.spawn_critical_with_graceful_shutdown_signal(
             "p2p network task",
             |shutdown| {
-                network.run_until_graceful_shutdown(shutdown, |network| {
+                network.run_until_graceful_shutdown(shutdown, move |network| {
+                    if let Err(err) = network.write_bans_to_file(&bans_file) {
+                        warn!(target: "reth::cli", %err, "Failed to write network bans to file");
+                    }
                     if let Some(peers_file) = known_peers_file {
                         let num_known_peers = network.num_known_peers();
                         trace!(target: "reth::cli", peers_file=?peers_file, num_peers=%num_known_peers, "Saving current peers");
@@ -4,7 +4,8 @@ use crate::{BuilderContext, FullNodeTypes};
 use alloy_primitives::map::AddressSet;
 use reth_chain_state::CanonStateSubscriptions;
 use reth_chainspec::EthereumHardforks;
-use reth_node_api::{BlockTy, NodeTypes, TxTy};
+use reth_evm::{ConfigureEvm, NextBlockEnvAttributes};
+use reth_node_api::{BlockTy, NodeTypes, PrimitivesTy, TxTy};
 use reth_transaction_pool::{
     blobstore::DiskFileBlobStore, BlobStore, CoinbaseTipOrdering, PoolConfig, PoolTransaction,
     SubPoolLimit, TransactionOrdering, TransactionPool, TransactionValidationTaskExecutor,
@@ -264,6 +265,25 @@ where
     Ok(())
 }
 
+/// Spawn the task persisting the transaction history if enabled.
+fn spawn_transaction_journal_task<Node, Pool>(ctx: &BuilderContext<Node>, pool: &Pool)
+where
+    Node: FullNodeTypes,
+    Pool: reth_transaction_pool::TransactionPoolExt,
+{
+    let Some(path) = ctx.config().txpool.transaction_history_path.clone() else { return };
+    let Some(journal) = pool.transaction_journal() else { return };
+
+    ctx.task_executor().spawn_critical_with_graceful_shutdown_signal(
+        "transaction journal task",
+        |shutdown| {
+            reth_transaction_pool::pool::journal::persist_transaction_journal_task(
+                shutdown, journal, path,
+            )
+        },
+    );
+}
+
 /// Spawn the main maintenance task for transaction pool.
 fn spawn_pool_maintenance_task<Node, Pool>(
     ctx: &BuilderContext<Node>,
@@ -296,7 +316,42 @@ where
     Ok(())
 }
 
-/// Spawn all maintenance tasks for a transaction pool (backup + main maintenance).
+/// Spawn the task simulating the best pending transactions if enabled, see
+/// [`reth_transaction_pool::simulate`].
+///
+/// This is not spawned by [`spawn_maintenance_tasks`] because it requires an EVM configuration that
+/// can build the next block, which the pool itself doesn't need. It's intended to be spawned by
+/// the payload builder component that consumes the simulation outcomes.
+pub fn spawn_simulation_task<Node, Pool, Evm>(
+    ctx: &BuilderContext<Node>,
+    pool: Pool,
+    evm_config: Evm,
+) where
+    Node: FullNodeTypes,
+    Pool: reth_transaction_pool::TransactionPoolExt<
+            Transaction: PoolTransaction<Consensus = TxTy<Node::Types>>,
+        > + 'static,
+    Evm: ConfigureEvm<
+            Primitives = PrimitivesTy<Node::Types>,
+            NextBlockEnvCtx = NextBlockEnvAttributes,
+        > + 'static,
+{
+    let Some(config) = ctx.config().txpool.simulation_config() else { return };
+
+    ctx.task_executor().spawn_critical_task(
+        "txpool simulation task",
+        reth_transaction_pool::simulate::simulate_pending_transactions_future(
+            ctx.provider().clone(),
+            pool,
+            evm_config,
+            ctx.provider().canonical_state_stream(),
+            ctx.task_executor().clone(),
+            config,
+        ),
+    );
+}
+
+/// Spawn all maintenance tasks for a transaction pool (backup, history + main maintenance).
 pub fn spawn_maintenance_tasks<Node, Pool>(
     ctx: &BuilderContext<Node>,
     pool: Pool,
@@ -308,6 +363,7 @@ where
     Pool::Transaction: PoolTransaction<Consensus = TxTy<Node::Types>>,
 {
     spawn_local_backup_task(ctx, pool.clone())?;
+    spawn_transaction_journal_task(ctx, &pool);
     spawn_pool_maintenance_task(ctx, pool, pool_config)?;
     Ok(())
 }
@@ -34,8 +34,12 @@ use crate::{
     hooks::OnComponentInitializedHook,
     BuilderContext, ExExLauncher, NodeAdapter, PrimitivesTy,
 };
-use alloy_eips::eip2124::Head;
-use alloy_primitives::{BlockNumber, B256};
+use alloy_consensus::constants::ETH_TO_WEI;
+use alloy_eips::{eip2124::Head, BlockNumberOrTag};
+use alloy_genesis::GenesisAccount;
+use alloy_primitives::{BlockNumber, B256, U256};
+use alloy_provider::{network::AnyNetwork, Provider, ProviderBuilder};
+use alloy_signer_local::{coins_bip39::English, MnemonicBuilder};
 use eyre::Context;
 use rayon::ThreadPoolBuilder;
 use reth_chainspec::{Chain, EthChainSpec, EthereumHardforks};
@@ -69,8 +73,10 @@ use reth_node_metrics::{
     storage::StorageSettingsInfo,
     version::VersionInfo,
 };
+use reth_primitives_traits::SealedHeader;
 use reth_provider::{
     providers::{NodeTypesForProvider, ProviderNodeTypes, RocksDBProvider, StaticFileProvider},
+    trie_verifier::TrieVerifier,
     BalConfig, BalStoreHandle, BlockHashReader, BlockNumReader, DBProvider,
     DatabaseProviderFactory, InMemoryBalStore, MetadataProvider, MetadataWriter, ProviderError,
     ProviderFactory, ProviderResult, RocksDBProviderFactory, StageCheckpointReader,
@@ -86,13 +92,18 @@ use reth_stages::{
 };
 use reth_static_file::{blocks_per_file_for_prune_distance, StaticFileProducer, StaticFileSegment};
 use reth_storage_overlay::OverlayManager;
+use reth_storage_rpc_provider::{fork::cached_fork_block, RpcForkState};
 use reth_tasks::TaskExecutor;
 use reth_tracing::{
     throttle,
     tracing::{debug, error, info, warn},
 };
 use reth_transaction_pool::TransactionPool;
-use std::{num::NonZeroUsize, sync::Arc, thread::available_parallelism, time::Duration};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::BTreeMap, num::NonZeroUsize, sync::Arc, thread::available_parallelism,
+    time::Duration,
+};
 use tokio::sync::{
     mpsc::{unbounded_channel, UnboundedSender},
     oneshot, watch,
@@ -375,6 +386,87 @@ impl<R, ChainSpec: EthChainSpec> LaunchContextWith<Attached<WithConfigs<ChainSpe
         self
     }
 
+    /// Re-roots the chain on a block of a remote chain if the dev node is configured to fork one.
+    ///
+    /// The genesis of the chain spec is replaced by the fork block fetched from `--dev.fork-url`,
+    /// and the dev accounts that don't exist on the remote chain are funded on top of its state.
+    /// Without `--dev.fork-block`, a data directory that was already forked keeps its fork block.
+    pub async fn with_dev_fork(mut self) -> eyre::Result<Self>
+    where
+        ChainSpec::Header: DeserializeOwned,
+    {
+        let dev = self.node_config().dev.clone();
+        let Some(fork_url) = dev.fork_url else { return Ok(self) };
+        let provider =
+            ProviderBuilder::new().network::<AnyNetwork>().connect_http(fork_url.clone());
+
+        let chain_id = provider.get_chain_id().await.wrap_err("Failed to query the fork url")?;
+        if chain_id != self.chain_spec().chain_id() {
+            eyre::bail!(
+                "Fork url serves chain {chain_id}, but chain {} is configured",
+                self.chain_spec().chain_id()
+            )
+        }
+
+        let cached_block =
+            cached_fork_block(self.data_dir().fork_state())?.map(|(number, _)| number);
+        let block = dev
+            .fork_block
+            .or(cached_block)
+            .map_or(BlockNumberOrTag::Latest, BlockNumberOrTag::Number);
+        let header: Option<alloy_rpc_types::Header<ChainSpec::Header>> = provider
+            .raw_request("eth_getBlockByNumber".into(), (block, false))
+            .await
+            .wrap_err_with(|| format!("Failed to fetch fork block {block}"))?;
+        let header = header.ok_or_else(|| eyre::eyre!("Fork block {block} not found"))?;
+        let expected_hash = header.hash;
+        let header = SealedHeader::seal_slow(header.inner);
+        if header.hash() != expected_hash {
+            eyre::bail!(
+                "Fork block {block} hashes to {}, but the fork url reports {expected_hash}",
+                header.hash()
+            )
+        }
+
+        let number = header.number();
+        let balance = U256::from(10_000) * U256::from(ETH_TO_WEI);
+        let mut alloc = BTreeMap::new();
+        for index in 0..20 {
+            let address = MnemonicBuilder::<English>::default()
+                .phrase(dev.dev_mnemonic.as_str())
+                .index(index)?
+                .build()?
+                .address();
+
+            // The genesis alloc replaces the remote account, so accounts that exist on the remote
+            // chain are left untouched.
+            let (nonce, remote_balance, code) = tokio::try_join!(
+                provider.get_transaction_count(address).number(number).into_future(),
+                provider.get_balance(address).number(number).into_future(),
+                provider.get_code_at(address).number(number).into_future(),
+            )
+            .wrap_err_with(|| format!("Failed to fetch dev account {address} at fork block"))?;
+            if nonce == 0 && remote_balance.is_zero() && code.is_empty() {
+                alloc.insert(address, GenesisAccount { balance, ..Default::default() });
+            } else {
+                warn!(
+                    target: "reth::cli",
+                    %address,
+                    "Dev account exists on the forked chain, it is not funded"
+                );
+            }
+        }
+
+        let hash = header.hash();
+        let chain = (*self.chain_spec())
+            .fork_at(header, alloc)
+            .ok_or_else(|| eyre::eyre!("The configured chain spec can't be forked"))?;
+        info!(target: "reth::cli", %fork_url, number, %hash, "Forking remote chain");
+        self.node_config_mut().chain = Arc::new(chain);
+
+        Ok(self)
+    }
+
     /// Returns the container for all config types
     pub const fn configs(&self) -> &WithConfigs<ChainSpec> {
         self.attachment.left()
@@ -541,9 +633,24 @@ where
         )?
         .with_prune_modes(prune_config.segments)
         .with_minimum_pruning_distance(prune_config.minimum_pruning_distance)
+        .with_trie_changesets_archive(self.node_config().storage.trie_changesets)
         .with_overlay_manager(overlay_manager)
         .with_bal_store(bal_store);
 
+        // A forked dev chain reads the state it never wrote from the remote chain.
+        let factory = if let Some(fork_url) = self.node_config().dev.fork_url.clone() {
+            let provider = ProviderBuilder::new().network::<AnyNetwork>().connect_http(fork_url);
+            let fork_state = RpcForkState::new(
+                provider,
+                self.chain_spec().genesis_header().number(),
+                self.genesis_hash(),
+                self.data_dir().fork_state(),
+            )?;
+            factory.with_fork_state(Arc::new(fork_state))
+        } else {
+            factory
+        };
+
         // Check consistency between the database and static files, returning
         // the unwind targets for each storage layer if inconsistencies are
         // found.
@@ -1220,6 +1327,50 @@ where
         }
     }
 
+    /// Spawns the background [`TrieVerifier`] if enabled.
+    ///
+    /// The verifier walks the trie one subtrie at a time, pausing for the configured interval in
+    /// between, and persists its progress in the datadir to resume after a restart.
+    pub fn spawn_trie_verifier(&self) -> eyre::Result<()> {
+        let args = self.node_config().trie_verifier;
+        if !args.enabled {
+            return Ok(())
+        }
+
+        let mut verifier = TrieVerifier::new(self.provider_factory().clone(), args.depth)?
+            .with_progress_file(self.node_config().datadir().trie_verifier_progress());
+        info!(
+            target: "reth::cli",
+            depth = args.depth,
+            interval = ?args.interval,
+            next_prefix = ?verifier.next_prefix(),
+            "Starting background trie verifier"
+        );
+
+        self.task_executor().spawn_blocking_task(async move {
+            loop {
+                match verifier.verify_next_chunk() {
+                    Ok(chunk) if !chunk.inconsistencies.is_empty() => {
+                        error!(
+                            target: "reth::cli",
+                            prefix = ?chunk.prefix,
+                            inconsistencies = chunk.inconsistencies.len(),
+                            "Trie tables are inconsistent with the hashed state, stop the node \
+                             and run `reth db repair-trie`"
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        warn!(target: "reth::cli", %err, "Failed to verify subtrie");
+                    }
+                }
+                tokio::time::sleep(args.interval).await;
+            }
+        });
+
+        Ok(())
+    }
+
     /// Spawns the [`EthStatsService`] service if configured.
     pub async fn spawn_ethstats<St>(&self, mut engine_events: St) -> eyre::Result<()>
     where
@@ -109,6 +109,9 @@ impl EngineNodeLauncher {
             .attach(database.clone())
             // ensure certain settings take effect
             .with_adjusted_configs()
+            // re-root the chain on the remote fork block, if any
+            .with_dev_fork()
+            .await?
             // Create the provider factory with the shared overlay manager
             .with_provider_factory::<_, <CB::Components as NodeComponents<T>>::Evm>(
                 overlay_manager.clone(),
@@ -433,6 +436,7 @@ impl EngineNodeLauncher {
         on_node_started.on_event(FullNode::clone(&full_node))?;
 
         ctx.spawn_ethstats(engine_events_for_ethstats).await?;
+        ctx.spawn_trie_verifier()?;
 
         let handle = NodeHandle {
             node_exit_future: NodeExitFuture::new(async { rx.await? }),
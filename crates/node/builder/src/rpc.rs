@@ -22,6 +22,7 @@ use jsonrpsee::RpcModule;
 use parking_lot::Mutex;
 use reth_chain_state::CanonStateSubscriptions;
 use reth_chainspec::{ChainSpecProvider, EthChainSpec, EthereumHardforks, Hardforks};
+use reth_network_api::PrivateRelayProvider;
 use reth_node_api::{
     AddOnsContext, BlockTy, EngineApiValidator, EngineTypes, FullNodeComponents, FullNodeTypes,
     NodeAddOns, NodeTypes, PayloadTypes, PayloadValidator, PrimitivesTy, TreeConfig,
@@ -34,13 +35,16 @@ use reth_node_core::{
 use reth_payload_builder::{PayloadBuilderHandle, PayloadStore};
 use reth_rpc::{
     eth::{core::EthRpcConverterFor, DevSigner, EthApiTypes, FullEthApiServer},
-    AdminApi,
+    AdminApi, AdminBackupApi, RelayApi,
+};
+use reth_rpc_api::{
+    eth::helpers::EthTransactions, AdminBackupApiServer, IntoEngineApiRpcModule, RelayApiServer,
 };
-use reth_rpc_api::{eth::helpers::EthTransactions, IntoEngineApiRpcModule};
 use reth_rpc_builder::{
     auth::{AuthRpcModule, AuthServerHandle},
     config::RethRpcServerConfig,
-    RpcModuleBuilder, RpcRegistryInner, RpcServerConfig, RpcServerHandle, TransportRpcModules,
+    RethRpcModule, RpcModuleBuilder, RpcRegistryInner, RpcServerConfig, RpcServerHandle,
+    TransportRpcModules,
 };
 use reth_rpc_engine_api::{capabilities::EngineCapabilities, EngineApi};
 use reth_rpc_eth_types::{cache::cache_new_blocks_task, EthConfig, EthStateCache};
@@ -971,9 +975,17 @@ where
             engine_handle,
         } = setup_ctx;
 
+        let response_cache = config
+            .rpc
+            .rpc_response_cache
+            .response_cache_config()
+            .map(|cache_config| registry.response_cache(cache_config))
+            .transpose()?;
         let server_config = config
             .rpc
             .rpc_server_config()
+            .with_graphql(config.rpc.http_graphql.then(|| registry.graphql_layer()))
+            .with_response_cache(response_cache)
             .set_rpc_middleware(rpc_middleware)
             .with_tokio_runtime(tokio_runtime);
         let rpc_server_handle = Self::launch_rpc_server_internal(server_config, &modules).await?;
@@ -1043,9 +1055,17 @@ where
             engine_handle,
         } = setup_ctx;
 
+        let response_cache = config
+            .rpc
+            .rpc_response_cache
+            .response_cache_config()
+            .map(|cache_config| registry.response_cache(cache_config))
+            .transpose()?;
         let server_config = config
             .rpc
             .rpc_server_config()
+            .with_graphql(config.rpc.http_graphql.then(|| registry.graphql_layer()))
+            .with_response_cache(response_cache)
             .set_rpc_middleware(rpc_middleware)
             .with_tokio_runtime(tokio_runtime);
 
@@ -1149,6 +1169,25 @@ where
             registry.eth_api().signers().write().extend(signers);
         }
 
+        // `admin_backup` needs direct access to the node's storage, so it's registered here
+        // rather than with the rest of the admin namespace
+        let backup_dir =
+            config.rpc.rpc_backup_dir.clone().unwrap_or_else(|| config.datadir().backups());
+        modules.merge_if_module_configured(
+            RethRpcModule::Admin,
+            AdminBackupApi::new(node.provider().clone(), backup_dir, node.task_executor().clone())
+                .into_rpc(),
+        )?;
+
+        // the private relay is set up with the network, so `relay` is only available if relay peers
+        // are configured
+        if let Some(relay) = node.network().private_relay() {
+            modules.merge_if_module_configured(
+                RethRpcModule::Relay,
+                RelayApi::new(relay.clone(), node.task_executor().clone()).into_rpc(),
+            )?;
+        }
+
         let mut registry = RpcRegistry { registry };
         let ctx = RpcContext {
             node: node.clone(),
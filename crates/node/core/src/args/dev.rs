@@ -2,9 +2,11 @@
 
 use std::{num::NonZeroUsize, sync::OnceLock, time::Duration};
 
+use alloy_primitives::BlockNumber;
 use clap::{builder::Resettable, Args};
 use humantime::{format_duration, parse_duration};
 use reth_engine_local::DEFAULT_FINALITY_DEPTH;
+use url::Url;
 
 const DEFAULT_MNEMONIC: &str = "test test test test test test test test test test test junk";
 
@@ -86,6 +88,33 @@ pub struct DevArgs {
         default_value_t = DefaultDevArgs::get_global().dev_mnemonic.clone()
     )]
     pub dev_mnemonic: String,
+
+    /// Fork the chain served by the given JSON-RPC endpoint instead of starting from genesis.
+    ///
+    /// The dev chain starts at the fork block of the remote chain and mines new blocks on top of
+    /// it. Remote state is fetched lazily at the fork block and cached in the data directory. The
+    /// dev accounts are funded on top of the remote state, except for accounts that already exist
+    /// on the remote chain.
+    ///
+    /// The endpoint must serve state at the fork block, which usually requires an archive node.
+    /// The chain configured with --chain must match the remote chain.
+    #[arg(
+        long = "dev.fork-url",
+        help_heading = "Dev testnet",
+        value_name = "URL",
+        requires = "dev",
+        verbatim_doc_comment
+    )]
+    pub fork_url: Option<Url>,
+
+    /// Block of the remote chain to fork from. Defaults to its latest block.
+    #[arg(
+        long = "dev.fork-block",
+        help_heading = "Dev testnet",
+        value_name = "BLOCK",
+        requires = "fork_url"
+    )]
+    pub fork_block: Option<BlockNumber>,
 }
 
 /// Default values for dev testnet CLI arguments that can be customized.
@@ -185,6 +214,8 @@ impl Default for DevArgs {
             finality_depth,
             payload_wait_time,
             dev_mnemonic,
+            fork_url: None,
+            fork_block: None,
         }
     }
 }
@@ -213,6 +244,8 @@ mod tests {
                 finality_depth: DEFAULT_FINALITY_DEPTH,
                 payload_wait_time: None,
                 dev_mnemonic: DEFAULT_MNEMONIC.to_string(),
+                fork_url: None,
+                fork_block: None,
             }
         );
 
@@ -226,6 +259,8 @@ mod tests {
                 finality_depth: DEFAULT_FINALITY_DEPTH,
                 payload_wait_time: None,
                 dev_mnemonic: DEFAULT_MNEMONIC.to_string(),
+                fork_url: None,
+                fork_block: None,
             }
         );
 
@@ -239,6 +274,8 @@ mod tests {
                 finality_depth: DEFAULT_FINALITY_DEPTH,
                 payload_wait_time: None,
                 dev_mnemonic: DEFAULT_MNEMONIC.to_string(),
+                fork_url: None,
+                fork_block: None,
             }
         );
 
@@ -258,6 +295,8 @@ mod tests {
                 finality_depth: DEFAULT_FINALITY_DEPTH,
                 payload_wait_time: None,
                 dev_mnemonic: DEFAULT_MNEMONIC.to_string(),
+                fork_url: None,
+                fork_block: None,
             }
         );
 
@@ -272,6 +311,8 @@ mod tests {
                 finality_depth: DEFAULT_FINALITY_DEPTH,
                 payload_wait_time: None,
                 dev_mnemonic: DEFAULT_MNEMONIC.to_string(),
+                fork_url: None,
+                fork_block: None,
             }
         );
 
@@ -305,6 +346,36 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn test_parse_dev_fork_args() {
+        let args = CommandParser::<DevArgs>::parse_from([
+            "reth",
+            "--dev",
+            "--dev.fork-url",
+            "http://localhost:8545",
+            "--dev.fork-block",
+            "100",
+        ])
+        .args;
+        assert_eq!(args.fork_url, Some("http://localhost:8545".parse().unwrap()));
+        assert_eq!(args.fork_block, Some(100));
+
+        // The fork block requires a fork url, which requires dev mode.
+        assert!(CommandParser::<DevArgs>::try_parse_from([
+            "reth",
+            "--dev",
+            "--dev.fork-block",
+            "1"
+        ])
+        .is_err());
+        assert!(CommandParser::<DevArgs>::try_parse_from([
+            "reth",
+            "--dev.fork-url",
+            "http://localhost:8545"
+        ])
+        .is_err());
+    }
+
     #[test]
     fn dev_args_default_sanity_check() {
         let default_args = DevArgs::default();
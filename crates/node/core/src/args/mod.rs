@@ -12,6 +12,18 @@ pub use rpc_server::{DefaultRpcServerArgs, RpcServerArgs};
 mod rpc_state_cache;
 pub use rpc_state_cache::RpcStateCacheArgs;
 
+/// `RpcRateLimitArgs` struct for configuring per-client RPC rate limits
+mod rpc_rate_limit;
+pub use rpc_rate_limit::RpcRateLimitArgs;
+
+/// `RpcResponseCacheArgs` struct for configuring the RPC response cache
+mod rpc_response_cache;
+pub use rpc_response_cache::RpcResponseCacheArgs;
+
+/// `RpcRecorderArgs` struct for configuring the RPC traffic recorder
+mod rpc_recorder;
+pub use rpc_recorder::RpcRecorderArgs;
+
 /// DebugArgs struct for debugging purposes
 mod debug;
 pub use debug::{DebugArgs, InvalidBlockHookType, InvalidBlockSelection};
@@ -76,6 +88,10 @@ pub use static_files::{StaticFilesArgs, MINIMAL_BLOCKS_PER_FILE};
 mod storage;
 pub use storage::{DefaultStorageValues, StorageArgs};
 
+/// `TrieVerifierArgs` for configuring the background trie verifier.
+mod trie_verifier;
+pub use trie_verifier::TrieVerifierArgs;
+
 /// `JitArgs` for configuring JIT compilation of EVM bytecode.
 mod jit;
 pub use jit::JitArgs;
@@ -10,7 +10,7 @@ use std::{
     sync::OnceLock,
 };
 
-use crate::version::version_metadata;
+use crate::{args::database::parse_byte_size, version::version_metadata};
 use clap::{
     builder::{OsStr, Resettable},
     Args,
@@ -26,6 +26,8 @@ use reth_discv5::{
 use reth_net_banlist::IpFilter;
 use reth_net_nat::{NatResolver, DEFAULT_NET_IF_NAME};
 use reth_network::{
+    light::{LightServeConfig, DEFAULT_LIGHT_SERVE_BUDGET},
+    private_relay::PrivateRelayConfig,
     transactions::{
         config::{TransactionIngressPolicy, TransactionPropagationKind},
         constants::{
@@ -43,9 +45,10 @@ use reth_network::{
         DEFAULT_SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESP_ON_PACK_GET_POOLED_TRANSACTIONS_REQ,
         SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESPONSE,
     },
-    HelloMessageWithProtocols, NetworkConfigBuilder, NetworkPrimitives,
+    BandwidthLimits, HelloMessageWithProtocols, NetworkConfigBuilder, NetworkPrimitives,
+    RecorderConfig,
 };
-use reth_network_peers::{mainnet_nodes, TrustedPeer};
+use reth_network_peers::{mainnet_nodes, PeerId, TrustedPeer};
 use reth_tasks::Runtime;
 use secp256k1::SecretKey;
 use std::str::FromStr;
@@ -289,7 +292,9 @@ pub struct NetworkArgs {
     #[arg(long, verbatim_doc_comment)]
     pub no_persist_peers: bool,
 
-    /// NAT resolution method (any|none|upnp|publicip|extip:\<IP\>)
+    /// NAT resolution method (any|none|upnp|natpmp|pcp|publicip|extip:\<IP\>)
+    ///
+    /// `upnp`, `natpmp` and `pcp` also forward the listener and discovery ports on the gateway.
     #[arg(long, default_value_t = DefaultNetworkArgs::get_global().nat.clone())]
     pub nat: NatResolver,
 
@@ -440,6 +445,86 @@ pub struct NetworkArgs {
     /// networks that pollute the discovery table.
     #[arg(long, default_value_t = DefaultNetworkArgs::get_global().enforce_enr_fork_id)]
     pub enforce_enr_fork_id: bool,
+
+    /// Maximum upload rate across all peers per second, e.g. `10MB`.
+    ///
+    /// Serving block bodies, receipts and snap responses is throttled first, gossip and our own
+    /// requests are only throttled once the limit is exhausted.
+    #[arg(long = "max-upload-rate", value_name = "BYTES", value_parser = parse_byte_size)]
+    pub max_upload_rate: Option<usize>,
+
+    /// Maximum download rate across all peers per second, e.g. `10MB`.
+    #[arg(long = "max-download-rate", value_name = "BYTES", value_parser = parse_byte_size)]
+    pub max_download_rate: Option<usize>,
+
+    /// Maximum upload rate to a single peer per second, e.g. `1MB`.
+    #[arg(long = "max-peer-upload-rate", value_name = "BYTES", value_parser = parse_byte_size)]
+    pub max_peer_upload_rate: Option<usize>,
+
+    /// Maximum download rate from a single peer per second, e.g. `1MB`.
+    #[arg(long = "max-peer-download-rate", value_name = "BYTES", value_parser = parse_byte_size)]
+    pub max_peer_download_rate: Option<usize>,
+
+    /// Account the traffic of each peer by message type, even if no rate limit is set.
+    ///
+    /// The per peer traffic is returned by `admin_peerBandwidth` and exported as metrics. Traffic
+    /// is always accounted if a rate limit is set.
+    #[arg(long = "bandwidth-accounting")]
+    pub bandwidth_accounting: bool,
+
+    /// Records the messages of sessions to the given directory, one file per session.
+    ///
+    /// Meant for debugging interop issues, recordings can be replayed in tests.
+    #[arg(long = "net-record-dir", value_name = "DIR")]
+    pub net_record_dir: Option<PathBuf>,
+
+    /// Comma separated list of peer ids to record sessions with, all peers if empty.
+    #[arg(
+        long = "net-record-peers",
+        value_name = "PEER_ID",
+        value_delimiter = ',',
+        requires = "net_record_dir"
+    )]
+    pub net_record_peers: Vec<PeerId>,
+
+    /// Comma separated list of messages to record, all messages if empty.
+    ///
+    /// Entries are either a capability, e.g. `snap`, or a message, e.g. `eth/BlockBodies`.
+    #[arg(
+        long = "net-record-messages",
+        value_name = "MESSAGE",
+        value_delimiter = ',',
+        requires = "net_record_dir"
+    )]
+    pub net_record_messages: Vec<String>,
+
+    /// Comma separated list of peer ids to relay private transactions and bundles to.
+    ///
+    /// The peers should also be configured as `--trusted-peers`, so that sessions with them are
+    /// kept alive. Transactions and bundles are relayed with the `relay` RPC namespace.
+    #[arg(long = "private-relay-peers", value_name = "PEER_ID", value_delimiter = ',')]
+    pub private_relay_peers: Vec<PeerId>,
+
+    /// Forward transactions submitted to the local pool as private to the private relay peers.
+    ///
+    /// By default private transactions never leave the node.
+    #[arg(long = "private-relay-forward-private", requires = "private_relay_peers")]
+    pub private_relay_forward_private: bool,
+
+    /// Serve headers, receipts and account proofs to light clients via the `light` subprotocol.
+    #[arg(long = "light-serve")]
+    pub light_serve: bool,
+
+    /// Serving budget of every light client peer in cost units per second.
+    ///
+    /// Requests over the budget are rejected and the peer is told when to retry.
+    #[arg(
+        long = "light-serve-budget",
+        value_name = "UNITS",
+        default_value_t = DEFAULT_LIGHT_SERVE_BUDGET,
+        requires = "light_serve"
+    )]
+    pub light_serve_budget: u64,
 }
 
 impl NetworkArgs {
@@ -593,7 +678,11 @@ impl NetworkArgs {
         NetworkConfigBuilder::<N>::new(secret_key, executor)
             .external_ip_resolver(self.nat.clone())
             .sessions_config(
-                config.sessions.clone().with_upscaled_event_buffer(peers_config.max_peers()),
+                config
+                    .sessions
+                    .clone()
+                    .with_upscaled_event_buffer(peers_config.max_peers())
+                    .with_bandwidth_limits(self.bandwidth_limits(config.sessions.bandwidth)),
             )
             .peer_config(peers_config)
             .boot_nodes(chain_bootnodes.clone())
@@ -617,9 +706,45 @@ impl NetworkArgs {
             .disable_tx_gossip(self.disable_tx_gossip)
             .required_block_hashes(self.required_block_hashes.clone())
             .eth_max_message_size_opt(self.eth_max_message_size.map(NonZeroUsize::get))
+            .message_recorder(self.message_recorder())
             .network_id(self.network_id)
     }
 
+    /// Returns the message recorder configured via `--net-record-dir`, if any.
+    pub fn message_recorder(&self) -> Option<RecorderConfig> {
+        self.net_record_dir.as_ref().map(|dir| {
+            RecorderConfig::new(dir)
+                .with_peers(self.net_record_peers.iter().copied())
+                .with_messages(self.net_record_messages.iter().cloned())
+        })
+    }
+
+    /// Returns the private relay configured via `--private-relay-peers`, if any.
+    pub fn private_relay_config(&self) -> Option<PrivateRelayConfig> {
+        (!self.private_relay_peers.is_empty()).then(|| {
+            PrivateRelayConfig::new(self.private_relay_peers.iter().copied())
+                .with_forward_private_transactions(self.private_relay_forward_private)
+        })
+    }
+
+    /// Returns the light serving configuration if `--light-serve` is enabled.
+    pub fn light_serve_config(&self) -> Option<LightServeConfig> {
+        self.light_serve.then_some(LightServeConfig { budget_per_second: self.light_serve_budget })
+    }
+
+    /// Returns the session bandwidth limits, the rates set via CLI take precedence over the
+    /// configured ones.
+    pub fn bandwidth_limits(&self, configured: BandwidthLimits) -> BandwidthLimits {
+        let rate = |arg: Option<usize>, configured| arg.map(|rate| rate as u64).or(configured);
+        BandwidthLimits {
+            max_upload: rate(self.max_upload_rate, configured.max_upload),
+            max_download: rate(self.max_download_rate, configured.max_download),
+            max_peer_upload: rate(self.max_peer_upload_rate, configured.max_peer_upload),
+            max_peer_download: rate(self.max_peer_download_rate, configured.max_peer_download),
+            accounting: self.bandwidth_accounting || configured.accounting,
+        }
+    }
+
     /// If `no_persist_peers` is false then this returns the path to the persistent peers file path.
     pub fn persistent_peers_file(&self, peers_file: PathBuf) -> Option<PathBuf> {
         self.no_persist_peers.not().then_some(peers_file)
@@ -759,6 +884,18 @@ impl Default for NetworkArgs {
             eth_max_message_size: None,
             netrestrict: None,
             enforce_enr_fork_id,
+            max_upload_rate: None,
+            max_download_rate: None,
+            max_peer_upload_rate: None,
+            max_peer_download_rate: None,
+            bandwidth_accounting: false,
+            net_record_dir: None,
+            net_record_peers: vec![],
+            net_record_messages: vec![],
+            private_relay_peers: vec![],
+            private_relay_forward_private: false,
+            light_serve: false,
+            light_serve_budget: DEFAULT_LIGHT_SERVE_BUDGET,
         }
     }
 }
@@ -1605,6 +1742,117 @@ mod tests {
         assert!(args.ip_filter().is_err());
     }
 
+    #[test]
+    fn parse_message_recorder() {
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.message_recorder(), None);
+
+        let peer_id = PeerId::with_last_byte(1);
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--net-record-dir",
+            "recordings",
+            "--net-record-peers",
+            &peer_id.to_string(),
+            "--net-record-messages",
+            "snap,eth/BlockBodies",
+        ])
+        .args;
+        assert_eq!(
+            args.message_recorder(),
+            Some(
+                RecorderConfig::new("recordings")
+                    .with_peers([peer_id])
+                    .with_messages(["snap", "eth/BlockBodies"])
+            )
+        );
+
+        assert!(CommandParser::<NetworkArgs>::try_parse_from([
+            "reth",
+            "--net-record-messages",
+            "snap"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_private_relay_peers() {
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.private_relay_config(), None);
+
+        let peers = [PeerId::with_last_byte(1), PeerId::with_last_byte(2)];
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--private-relay-peers",
+            &format!("{},{}", peers[0], peers[1]),
+        ])
+        .args;
+        assert_eq!(args.private_relay_config(), Some(PrivateRelayConfig::new(peers)));
+
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--private-relay-peers",
+            &peers[0].to_string(),
+            "--private-relay-forward-private",
+        ])
+        .args;
+        assert_eq!(
+            args.private_relay_config(),
+            Some(PrivateRelayConfig::new([peers[0]]).with_forward_private_transactions(true))
+        );
+    }
+
+    #[test]
+    fn parse_light_serve() {
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.light_serve_config(), None);
+
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth", "--light-serve"]).args;
+        assert_eq!(args.light_serve_config(), Some(LightServeConfig::default()));
+
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--light-serve",
+            "--light-serve-budget",
+            "500",
+        ])
+        .args;
+        assert_eq!(args.light_serve_config(), Some(LightServeConfig { budget_per_second: 500 }));
+
+        assert!(CommandParser::<NetworkArgs>::try_parse_from([
+            "reth",
+            "--light-serve-budget",
+            "500"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_bandwidth_limits() {
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--max-upload-rate",
+            "10MB",
+            "--max-peer-download-rate",
+            "512KB",
+        ])
+        .args;
+        assert_eq!(args.max_upload_rate, Some(10 * 1024 * 1024));
+        assert_eq!(args.max_download_rate, None);
+
+        let configured = BandwidthLimits::default().with_max_upload(1).with_max_download(2);
+        assert_eq!(
+            args.bandwidth_limits(configured),
+            BandwidthLimits {
+                max_upload: Some(10 * 1024 * 1024),
+                max_download: Some(2),
+                max_peer_upload: None,
+                max_peer_download: Some(512 * 1024),
+                accounting: false,
+            }
+        );
+    }
+
     #[test]
     fn network_config_preserves_basic_nodes_from_peers_file() {
         let enode = "enode://6f8a80d14311c39f35f516fa664deaaaa13e85b2f7493f37f6144d86991ec012937307647bd3b9a82abe2974e1407241d54947bbb39763a4cac9f77166ad92a0@10.3.58.6:30303?discport=30301";
@@ -0,0 +1,142 @@
+use clap::Args;
+use reth_rpc_server_types::{
+    constants::rate_limit::DEFAULT_MAX_TRACKED_CLIENTS,
+    rate_limit::{MethodRule, RateLimitKey, RpcRateLimitConfig, TokenBucketConfig},
+};
+use std::net::IpAddr;
+
+/// Parameters to configure per-client RPC rate limiting.
+#[derive(Debug, Clone, Args, PartialEq, Eq)]
+#[command(next_help_heading = "RPC Rate Limit")]
+pub struct RpcRateLimitArgs {
+    /// Enable per-client rate limiting for the HTTP and WS servers.
+    ///
+    /// Every client has a token bucket and each call draws its cost from it. Calls exceeding the
+    /// quota are rejected with error code -32005 and a `retryAfter` hint.
+    #[arg(long = "rpc.ratelimit", default_value_t = false)]
+    pub enabled: bool,
+
+    /// How requests are attributed to clients: ip, jwt, connection or global.
+    ///
+    /// `ip` uses the peer address, or the `X-Forwarded-For`/`X-Real-IP` headers if the peer is
+    /// one of the `--rpc.ratelimit.trusted-proxies`, `jwt` uses the `sub` claim of the bearer
+    /// token verified with the `--rpc.jwtsecret` and falls back to the client IP.
+    #[arg(long = "rpc.ratelimit.key", value_name = "KEY", default_value_t = RateLimitKey::Ip)]
+    pub key: RateLimitKey,
+
+    /// The quota of every client, as `<burst>/<tokens per second>` or `<tokens per second>`.
+    #[arg(
+        long = "rpc.ratelimit.quota",
+        value_name = "BURST/RATE",
+        default_value_t = TokenBucketConfig::default()
+    )]
+    pub quota: TokenBucketConfig,
+
+    /// Additional per client quotas for methods, in calls, as `<method>=<burst>/<rate>`.
+    ///
+    /// Methods can be matched by prefix, e.g. `debug_*=10/1`.
+    #[arg(long = "rpc.ratelimit.method", value_name = "METHOD=BURST/RATE", value_delimiter = ',')]
+    pub method_limits: Vec<MethodRule<TokenBucketConfig>>,
+
+    /// Overrides the cost of methods in tokens, as `<method>=<cost>`.
+    ///
+    /// By default `debug_*` and `trace_*` calls cost 20 tokens, `eth_getLogs` 10 and all other
+    /// calls 1.
+    #[arg(long = "rpc.ratelimit.cost", value_name = "METHOD=COST", value_delimiter = ',')]
+    pub costs: Vec<MethodRule<u32>>,
+
+    /// Maximum number of clients tracked at the same time.
+    #[arg(long = "rpc.ratelimit.max-clients", default_value_t = DEFAULT_MAX_TRACKED_CLIENTS)]
+    pub max_clients: u32,
+
+    /// Comma separated IP addresses of the reverse proxies whose `X-Forwarded-For` and
+    /// `X-Real-IP` headers are trusted.
+    ///
+    /// The headers are ignored for requests from any other address.
+    #[arg(long = "rpc.ratelimit.trusted-proxies", value_name = "IP", value_delimiter = ',')]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl RpcRateLimitArgs {
+    /// Returns the rate limit configuration if rate limiting is enabled.
+    pub fn rate_limit_config(&self) -> Option<RpcRateLimitConfig> {
+        if !self.enabled {
+            return None
+        }
+        let mut config = RpcRateLimitConfig::default()
+            .with_key(self.key)
+            .with_per_client(self.quota)
+            .with_max_tracked_clients(self.max_clients)
+            .with_trusted_proxies(self.trusted_proxies.iter().copied());
+        for rule in &self.method_limits {
+            config = config.with_method_limit(rule.clone());
+        }
+        // later rules take precedence
+        for rule in &self.costs {
+            config = config.with_method_cost(rule.clone());
+        }
+        Some(config)
+    }
+}
+
+impl Default for RpcRateLimitArgs {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key: RateLimitKey::default(),
+            quota: TokenBucketConfig::default(),
+            method_limits: Vec::new(),
+            costs: Vec::new(),
+            max_clients: DEFAULT_MAX_TRACKED_CLIENTS,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[command(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_rate_limit_args() {
+        let args = CommandParser::<RpcRateLimitArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, RpcRateLimitArgs::default());
+        assert!(args.rate_limit_config().is_none());
+
+        let args = CommandParser::<RpcRateLimitArgs>::parse_from([
+            "reth",
+            "--rpc.ratelimit",
+            "--rpc.ratelimit.key",
+            "jwt",
+            "--rpc.ratelimit.quota",
+            "500/50",
+            "--rpc.ratelimit.method",
+            "eth_call=10/2,debug_*=5",
+            "--rpc.ratelimit.cost",
+            "eth_getLogs=25",
+            "--rpc.ratelimit.trusted-proxies",
+            "10.0.0.1,::1",
+        ])
+        .args;
+
+        let config = args.rate_limit_config().unwrap();
+        assert_eq!(config.key, RateLimitKey::Jwt);
+        assert_eq!(config.per_client, TokenBucketConfig::new(500, 50));
+        assert_eq!(config.method_limit("eth_call"), Some((0, TokenBucketConfig::new(10, 2))));
+        assert_eq!(config.method_limit("debug_traceCall"), Some((1, TokenBucketConfig::new(5, 5))));
+        assert_eq!(config.cost_of("eth_getLogs"), 25);
+        assert_eq!(config.cost_of("trace_block"), 20);
+        assert_eq!(
+            config.trusted_proxies,
+            ["10.0.0.1".parse::<IpAddr>().unwrap(), "::1".parse().unwrap()]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use clap::Args;
+use reth_rpc_server_types::{
+    constants::recorder::{DEFAULT_MAX_FILES, DEFAULT_MAX_FILE_BYTES},
+    recording::RpcRecorderConfig,
+};
+use std::path::PathBuf;
+
+/// Number of bytes in a megabyte.
+const MB: u64 = 1024 * 1024;
+
+/// Parameters to configure recording of RPC traffic.
+#[derive(Debug, Clone, Args, PartialEq)]
+#[command(next_help_heading = "RPC Recorder")]
+pub struct RpcRecorderArgs {
+    /// Record sampled HTTP, WS and IPC calls, their responses and latencies to this directory.
+    ///
+    /// Recordings can be replayed against another node with `reth rpc-replay`.
+    #[arg(long = "rpc.recorder.dir", value_name = "PATH")]
+    pub dir: Option<PathBuf>,
+
+    /// Fraction of calls that are recorded, between 0.0 and 1.0.
+    #[arg(
+        long = "rpc.recorder.sample-rate",
+        value_name = "RATE",
+        default_value_t = 1.0,
+        value_parser = parse_sample_rate
+    )]
+    pub sample_rate: f64,
+
+    /// Only record requests, without their results and errors.
+    #[arg(long = "rpc.recorder.no-responses", default_value_t = false)]
+    pub no_responses: bool,
+
+    /// Size after which a recording file is rotated, in megabytes.
+    #[arg(
+        long = "rpc.recorder.max-file-size",
+        value_name = "MB",
+        default_value_t = DEFAULT_MAX_FILE_BYTES / MB
+    )]
+    pub max_file_size_mb: u64,
+
+    /// Maximum number of recording files kept per transport, the oldest files are removed.
+    #[arg(
+        long = "rpc.recorder.max-files",
+        value_name = "COUNT",
+        default_value_t = DEFAULT_MAX_FILES
+    )]
+    pub max_files: usize,
+}
+
+// The sample rate is validated to be within `0.0..=1.0` and therefore never NaN.
+impl Eq for RpcRecorderArgs {}
+
+impl RpcRecorderArgs {
+    /// Returns the recorder configuration if recording is enabled.
+    pub fn recorder_config(&self) -> Option<RpcRecorderConfig> {
+        let dir = self.dir.as_ref()?;
+        Some(
+            RpcRecorderConfig::new(dir)
+                .with_sample_rate(self.sample_rate)
+                .with_record_responses(!self.no_responses)
+                .with_max_file_bytes(self.max_file_size_mb * MB)
+                .with_max_files(self.max_files),
+        )
+    }
+}
+
+impl Default for RpcRecorderArgs {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            sample_rate: 1.0,
+            no_responses: false,
+            max_file_size_mb: DEFAULT_MAX_FILE_BYTES / MB,
+            max_files: DEFAULT_MAX_FILES,
+        }
+    }
+}
+
+/// Parses a sample rate between `0.0` and `1.0`.
+fn parse_sample_rate(value: &str) -> Result<f64, String> {
+    let rate = value.parse::<f64>().map_err(|err| err.to_string())?;
+    if (0.0..=1.0).contains(&rate) {
+        Ok(rate)
+    } else {
+        Err(format!("sample rate must be between 0.0 and 1.0, got {rate}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[command(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_recorder_args() {
+        let args = CommandParser::<RpcRecorderArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, RpcRecorderArgs::default());
+        assert!(args.recorder_config().is_none());
+
+        let args = CommandParser::<RpcRecorderArgs>::parse_from([
+            "reth",
+            "--rpc.recorder.dir",
+            "/tmp/reth-recordings",
+            "--rpc.recorder.sample-rate",
+            "0.1",
+            "--rpc.recorder.no-responses",
+            "--rpc.recorder.max-file-size",
+            "16",
+        ])
+        .args;
+        assert_eq!(
+            args.recorder_config(),
+            Some(
+                RpcRecorderConfig::new("/tmp/reth-recordings")
+                    .with_sample_rate(0.1)
+                    .with_record_responses(false)
+                    .with_max_file_bytes(16 * MB)
+            )
+        );
+
+        assert!(CommandParser::<RpcRecorderArgs>::try_parse_from([
+            "reth",
+            "--rpc.recorder.sample-rate",
+            "1.5",
+        ])
+        .is_err());
+    }
+}
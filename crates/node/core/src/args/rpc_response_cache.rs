@@ -0,0 +1,119 @@
+use clap::Args;
+use reth_rpc_server_types::{
+    constants::response_cache::{DEFAULT_MAX_DISK_BYTES, DEFAULT_MAX_MEMORY_BYTES},
+    response_cache::{ResponseCacheDiskConfig, RpcResponseCacheConfig},
+};
+use std::path::PathBuf;
+
+/// Number of bytes in a megabyte.
+const MB: u64 = 1024 * 1024;
+
+/// Parameters to configure the cache for responses of calls on finalized data.
+#[derive(Debug, Clone, Args, PartialEq, Eq)]
+#[command(next_help_heading = "RPC Response Cache")]
+pub struct RpcResponseCacheArgs {
+    /// Cache responses of calls that only depend on finalized data for the HTTP and WS servers.
+    ///
+    /// This covers blocks, receipts, transactions, logs and traces at or below the finalized
+    /// block, requested by number or hash.
+    #[arg(long = "rpc.response-cache", default_value_t = false)]
+    pub enabled: bool,
+
+    /// Maximum size of the responses cached in memory, in megabytes.
+    #[arg(
+        long = "rpc.response-cache.max-memory",
+        value_name = "MB",
+        default_value_t = DEFAULT_MAX_MEMORY_BYTES / MB
+    )]
+    pub max_memory_mb: u64,
+
+    /// Directory to spill cached responses to when they are evicted from memory.
+    ///
+    /// Entries of previous runs in this directory are removed on startup. The disk tier is
+    /// disabled if not set.
+    #[arg(long = "rpc.response-cache.dir", value_name = "PATH")]
+    pub dir: Option<PathBuf>,
+
+    /// Maximum size of the responses cached on disk, in megabytes.
+    #[arg(
+        long = "rpc.response-cache.max-disk",
+        value_name = "MB",
+        default_value_t = DEFAULT_MAX_DISK_BYTES / MB
+    )]
+    pub max_disk_mb: u64,
+}
+
+impl RpcResponseCacheArgs {
+    /// Returns the response cache configuration if the cache is enabled.
+    pub fn response_cache_config(&self) -> Option<RpcResponseCacheConfig> {
+        if !self.enabled {
+            return None
+        }
+        let disk = self
+            .dir
+            .as_ref()
+            .map(|dir| ResponseCacheDiskConfig::new(dir).with_max_bytes(self.max_disk_mb * MB));
+        Some(
+            RpcResponseCacheConfig::default()
+                .with_max_memory_bytes(self.max_memory_mb * MB)
+                .with_disk(disk),
+        )
+    }
+}
+
+impl Default for RpcResponseCacheArgs {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_memory_mb: DEFAULT_MAX_MEMORY_BYTES / MB,
+            dir: None,
+            max_disk_mb: DEFAULT_MAX_DISK_BYTES / MB,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[command(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_response_cache_args() {
+        let args = CommandParser::<RpcResponseCacheArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, RpcResponseCacheArgs::default());
+        assert!(args.response_cache_config().is_none());
+
+        let args = CommandParser::<RpcResponseCacheArgs>::parse_from([
+            "reth",
+            "--rpc.response-cache",
+            "--rpc.response-cache.max-memory",
+            "64",
+        ])
+        .args;
+        let config = args.response_cache_config().unwrap();
+        assert_eq!(config.max_memory_bytes, 64 * MB);
+        assert!(config.disk.is_none());
+
+        let args = CommandParser::<RpcResponseCacheArgs>::parse_from([
+            "reth",
+            "--rpc.response-cache",
+            "--rpc.response-cache.dir",
+            "/tmp/reth-cache",
+            "--rpc.response-cache.max-disk",
+            "1024",
+        ])
+        .args;
+        let config = args.response_cache_config().unwrap();
+        assert_eq!(
+            config.disk,
+            Some(ResponseCacheDiskConfig::new("/tmp/reth-cache").with_max_bytes(1024 * MB))
+        );
+    }
+}
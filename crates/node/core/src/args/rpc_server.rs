@@ -2,7 +2,7 @@
 
 use crate::args::{
     types::{MaxU32, ZeroAsNoneU64},
-    GasPriceOracleArgs, RpcStateCacheArgs,
+    GasPriceOracleArgs, RpcRateLimitArgs, RpcRecorderArgs, RpcResponseCacheArgs, RpcStateCacheArgs,
 };
 use alloy_primitives::map::AddressSet;
 use alloy_rpc_types_engine::JwtSecret;
@@ -54,6 +54,7 @@ pub struct DefaultRpcServerArgs {
     http_addr: IpAddr,
     http_port: u16,
     http_disable_compression: bool,
+    http_graphql: bool,
     http_api: Option<RpcModuleSelection>,
     http_corsdomain: Option<String>,
     ws: bool,
@@ -92,6 +93,9 @@ pub struct DefaultRpcServerArgs {
     rpc_forwarder: Option<Url>,
     builder_disallow: Option<AddressSet>,
     rpc_state_cache: RpcStateCacheArgs,
+    rpc_rate_limit: RpcRateLimitArgs,
+    rpc_response_cache: RpcResponseCacheArgs,
+    rpc_recorder: RpcRecorderArgs,
     gas_price_oracle: GasPriceOracleArgs,
     rpc_send_raw_transaction_sync_timeout: Duration,
 }
@@ -131,6 +135,12 @@ impl DefaultRpcServerArgs {
         self
     }
 
+    /// Set whether to serve the GraphQL endpoint by default
+    pub const fn with_http_graphql(mut self, v: bool) -> Self {
+        self.http_graphql = v;
+        self
+    }
+
     /// Set the default HTTP API modules
     pub fn with_http_api(mut self, v: Option<RpcModuleSelection>) -> Self {
         self.http_api = v;
@@ -359,6 +369,24 @@ impl DefaultRpcServerArgs {
         self
     }
 
+    /// Set the default per-client rate limit args
+    pub fn with_rpc_rate_limit(mut self, v: RpcRateLimitArgs) -> Self {
+        self.rpc_rate_limit = v;
+        self
+    }
+
+    /// Set the default response cache args
+    pub fn with_rpc_response_cache(mut self, v: RpcResponseCacheArgs) -> Self {
+        self.rpc_response_cache = v;
+        self
+    }
+
+    /// Set the default recorder args
+    pub fn with_rpc_recorder(mut self, v: RpcRecorderArgs) -> Self {
+        self.rpc_recorder = v;
+        self
+    }
+
     /// Set the default gas price oracle args
     pub const fn with_gas_price_oracle(mut self, v: GasPriceOracleArgs) -> Self {
         self.gas_price_oracle = v;
@@ -379,6 +407,7 @@ impl Default for DefaultRpcServerArgs {
             http_addr: Ipv4Addr::LOCALHOST.into(),
             http_port: constants::DEFAULT_HTTP_RPC_PORT,
             http_disable_compression: false,
+            http_graphql: false,
             http_api: None,
             http_corsdomain: None,
             ws: false,
@@ -417,6 +446,9 @@ impl Default for DefaultRpcServerArgs {
             rpc_forwarder: None,
             builder_disallow: None,
             rpc_state_cache: RpcStateCacheArgs::default(),
+            rpc_rate_limit: RpcRateLimitArgs::default(),
+            rpc_response_cache: RpcResponseCacheArgs::default(),
+            rpc_recorder: RpcRecorderArgs::default(),
             gas_price_oracle: GasPriceOracleArgs::default(),
             rpc_send_raw_transaction_sync_timeout:
                 constants::RPC_DEFAULT_SEND_RAW_TX_SYNC_TIMEOUT_SECS,
@@ -444,6 +476,12 @@ pub struct RpcServerArgs {
     #[arg(long = "http.disable-compression", default_value_t = DefaultRpcServerArgs::get_global().http_disable_compression)]
     pub http_disable_compression: bool,
 
+    /// Serve the EIP-1767 GraphQL API on the HTTP server.
+    ///
+    /// Queries are accepted on `/graphql` and the GraphiQL explorer is served on `/graphql/ui`.
+    #[arg(long = "http.graphql", default_value_t = DefaultRpcServerArgs::get_global().http_graphql)]
+    pub http_graphql: bool,
+
     /// Rpc Modules to be configured for the HTTP server
     #[arg(long = "http.api", value_parser = RpcModuleSelectionValueParser::default(), default_value = Resettable::from(DefaultRpcServerArgs::get_global().http_api.as_ref().map(|v| v.to_string().into())))]
     pub http_api: Option<RpcModuleSelection>,
@@ -625,6 +663,9 @@ pub struct RpcServerArgs {
     /// The maximum proof window for historical proof generation.
     /// This value allows for generating historical proofs up to
     /// configured number of blocks from current tip (up to `tip - window`).
+    ///
+    /// Blocks whose trie changesets are archived with `--storage.trie-changesets` are not limited
+    /// by the window.
     #[arg(
         long = "rpc.eth-proof-window",
         default_value_t = DefaultRpcServerArgs::get_global().rpc_eth_proof_window,
@@ -656,6 +697,18 @@ pub struct RpcServerArgs {
     #[command(flatten)]
     pub rpc_state_cache: RpcStateCacheArgs,
 
+    /// Per-client rate limit configuration.
+    #[command(flatten)]
+    pub rpc_rate_limit: RpcRateLimitArgs,
+
+    /// Response cache configuration.
+    #[command(flatten)]
+    pub rpc_response_cache: RpcResponseCacheArgs,
+
+    /// RPC traffic recorder configuration.
+    #[command(flatten)]
+    pub rpc_recorder: RpcRecorderArgs,
+
     /// Gas price oracle configuration.
     #[command(flatten)]
     pub gas_price_oracle: GasPriceOracleArgs,
@@ -691,6 +744,19 @@ pub struct RpcServerArgs {
     /// By default this is disabled, meaning transactions are submitted as-is.
     #[arg(long = "rpc.force-blob-sidecar-upcasting", default_value_t = false)]
     pub rpc_force_blob_sidecar_upcasting: bool,
+
+    /// Enable the `debug_subscribeBlockTraces` and `trace_subscribeBlockTraces` subscriptions.
+    ///
+    /// Each new canonical block is re-executed once for every distinct tracer that is subscribed
+    /// to, which competes with other tracing calls for the tracing permits.
+    #[arg(long = "rpc.block-trace-subscriptions", default_value_t = false)]
+    pub rpc_block_trace_subscriptions: bool,
+
+    /// Directory `admin_backup` writes backups to, each into a new subdirectory of it.
+    ///
+    /// Defaults to `<DIR>/<CHAIN_ID>/backups`.
+    #[arg(long = "rpc.backup-dir", value_name = "PATH")]
+    pub rpc_backup_dir: Option<PathBuf>,
 }
 
 impl RpcServerArgs {
@@ -839,6 +905,7 @@ impl Default for RpcServerArgs {
             http_addr,
             http_port,
             http_disable_compression,
+            http_graphql,
             http_api,
             http_corsdomain,
             ws,
@@ -877,6 +944,9 @@ impl Default for RpcServerArgs {
             rpc_forwarder,
             builder_disallow,
             rpc_state_cache,
+            rpc_rate_limit,
+            rpc_response_cache,
+            rpc_recorder,
             gas_price_oracle,
             rpc_send_raw_transaction_sync_timeout,
         } = DefaultRpcServerArgs::get_global().clone();
@@ -885,6 +955,7 @@ impl Default for RpcServerArgs {
             http_addr,
             http_port,
             http_disable_compression,
+            http_graphql,
             http_api,
             http_corsdomain,
             ws,
@@ -923,11 +994,16 @@ impl Default for RpcServerArgs {
             rpc_forwarder,
             builder_disallow,
             rpc_state_cache,
+            rpc_rate_limit,
+            rpc_response_cache,
+            rpc_recorder,
             gas_price_oracle,
             rpc_send_raw_transaction_sync_timeout,
             testing_skip_invalid_transactions: false,
             testing_gas_limit: None,
             rpc_force_blob_sidecar_upcasting: false,
+            rpc_block_trace_subscriptions: false,
+            rpc_backup_dir: None,
         }
     }
 }
@@ -1058,6 +1134,7 @@ mod tests {
             http_addr: "127.0.0.1".parse().unwrap(),
             http_port: 8545,
             http_disable_compression: false,
+            http_graphql: false,
             http_api: Some(RpcModuleSelection::try_from_selection(["eth", "admin"]).unwrap()),
             http_corsdomain: Some("*".to_string()),
             ws: true,
@@ -1108,6 +1185,9 @@ mod tests {
                 max_concurrent_db_requests: 512,
                 max_cached_tx_hashes: 30_000,
             },
+            rpc_rate_limit: RpcRateLimitArgs::default(),
+            rpc_response_cache: RpcResponseCacheArgs::default(),
+            rpc_recorder: RpcRecorderArgs::default(),
             gas_price_oracle: GasPriceOracleArgs {
                 blocks: 20,
                 ignore_price: 2,
@@ -1119,6 +1199,8 @@ mod tests {
             testing_skip_invalid_transactions: true,
             testing_gas_limit: None,
             rpc_force_blob_sidecar_upcasting: false,
+            rpc_block_trace_subscriptions: false,
+            rpc_backup_dir: None,
         };
 
         let parsed_args = CommandParser::<RpcServerArgs>::parse_from([
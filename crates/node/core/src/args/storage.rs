@@ -59,12 +59,21 @@ pub struct StorageArgs {
         default_missing_value = "true",
     )]
     pub v2: bool,
+
+    /// Archive the trie changesets of every persisted block.
+    ///
+    /// The changesets store the trie nodes before each block, so that state proofs, e.g.
+    /// `eth_getProof`, can be served at any block after archiving started without a proof
+    /// window limit. Proofs at blocks before archiving started are still recomputed from state
+    /// reverts.
+    #[arg(long = "storage.trie-changesets", default_value_t = false)]
+    pub trie_changesets: bool,
 }
 
 impl Default for StorageArgs {
     fn default() -> Self {
         let defaults = DefaultStorageValues::get_global();
-        Self { v2: defaults.v2 }
+        Self { v2: defaults.v2, trie_changesets: false }
     }
 }
 
@@ -86,6 +95,16 @@ mod tests {
         assert!(args.v2);
     }
 
+    #[test]
+    fn test_storage_trie_changesets() {
+        let args = CommandParser::<StorageArgs>::parse_from(["reth"]).args;
+        assert!(!args.trie_changesets);
+
+        let args =
+            CommandParser::<StorageArgs>::parse_from(["reth", "--storage.trie-changesets"]).args;
+        assert!(args.trie_changesets);
+    }
+
     #[test]
     fn test_storage_v2_implicit_true() {
         let args = CommandParser::<StorageArgs>::parse_from(["reth", "--storage.v2"]).args;
@@ -0,0 +1,91 @@
+//! clap [Args](clap::Args) for the background trie verifier
+
+use clap::{builder::RangedU64ValueParser, Args};
+use std::time::Duration;
+
+/// Parameters to configure the background verification of the trie tables.
+#[derive(Debug, Clone, Copy, Args, PartialEq, Eq)]
+#[command(next_help_heading = "Trie Verifier")]
+pub struct TrieVerifierArgs {
+    /// Continuously verify the trie tables against the hashed state in the background.
+    ///
+    /// The trie is verified one subtrie at a time on short read transactions, and the progress
+    /// is persisted in the datadir so that verification resumes after a restart. Inconsistencies
+    /// are logged as errors and counted in the `trie_verifier` metrics, they can be repaired with
+    /// `reth db repair-trie` while the node is stopped.
+    #[arg(long = "trie-verifier", default_value_t = false)]
+    pub enabled: bool,
+
+    /// Length of the account trie paths the trie is split into subtries by.
+    ///
+    /// The trie is split into `16^depth` subtries. Greater depths hold read transactions open for
+    /// a shorter time.
+    #[arg(
+        long = "trie-verifier.depth",
+        value_name = "DEPTH",
+        default_value_t = 3,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..=6)
+    )]
+    pub depth: usize,
+
+    /// Fixed pause between verifying two subtries.
+    ///
+    /// This spaces out the read transactions of the verifier, it doesn't limit the bytes read
+    /// while verifying a subtrie. Use a greater depth to verify less data at a time.
+    #[arg(
+        long = "trie-verifier.interval",
+        value_name = "DURATION",
+        default_value = "10s",
+        value_parser = humantime::parse_duration
+    )]
+    pub interval: Duration,
+}
+
+impl Default for TrieVerifierArgs {
+    fn default() -> Self {
+        Self { enabled: false, depth: 3, interval: Duration::from_secs(10) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[command(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_default_trie_verifier_args() {
+        let args = CommandParser::<TrieVerifierArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, TrieVerifierArgs::default());
+    }
+
+    #[test]
+    fn test_parse_trie_verifier_args() {
+        let args = CommandParser::<TrieVerifierArgs>::parse_from([
+            "reth",
+            "--trie-verifier",
+            "--trie-verifier.depth",
+            "2",
+            "--trie-verifier.interval",
+            "500ms",
+        ])
+        .args;
+        assert_eq!(
+            args,
+            TrieVerifierArgs { enabled: true, depth: 2, interval: Duration::from_millis(500) }
+        );
+
+        assert!(CommandParser::<TrieVerifierArgs>::try_parse_from([
+            "reth",
+            "--trie-verifier.depth",
+            "7"
+        ])
+        .is_err());
+    }
+}
@@ -8,12 +8,20 @@ use reth_cli_util::{parse_duration_from_secs_or_ms, parsers::format_duration_as_
 use reth_transaction_pool::{
     blobstore::disk::DEFAULT_MAX_CACHED_BLOBS,
     maintain::MAX_QUEUED_TRANSACTION_LIFETIME,
-    pool::{NEW_TX_LISTENER_BUFFER_SIZE, PENDING_TX_LISTENER_BUFFER_SIZE},
+    pool::{
+        journal::DEFAULT_JOURNAL_MAX_TRANSACTIONS, NEW_TX_LISTENER_BUFFER_SIZE,
+        PENDING_TX_LISTENER_BUFFER_SIZE,
+    },
+    simulate::{
+        SimulationConfig, DEFAULT_SIMULATION_MAX_CONSECUTIVE_REVERTS,
+        DEFAULT_SIMULATION_MAX_TRANSACTIONS,
+    },
     validate::DEFAULT_MAX_TX_INPUT_BYTES,
-    LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
-    DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS, MAX_NEW_PENDING_TXS_NOTIFICATIONS,
-    REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
-    TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
+    LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, TransactionJournalConfig,
+    DEFAULT_PRICE_BUMP, DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS,
+    MAX_NEW_PENDING_TXS_NOTIFICATIONS, REPLACE_BLOB_PRICE_BUMP,
+    TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
+    TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
 };
 use std::{path::PathBuf, sync::OnceLock, time::Duration};
 
@@ -55,6 +63,12 @@ pub struct DefaultTxPoolValues {
     transactions_backup_path: Option<PathBuf>,
     disable_transactions_backup: bool,
     max_batch_size: usize,
+    transaction_history: bool,
+    transaction_history_max_transactions: u32,
+    transaction_history_path: Option<PathBuf>,
+    simulate: bool,
+    simulate_max_transactions: usize,
+    simulate_max_reverts: u32,
 }
 
 impl DefaultTxPoolValues {
@@ -247,6 +261,42 @@ impl DefaultTxPoolValues {
         self.max_batch_size = v;
         self
     }
+
+    /// Set whether to record the transaction history by default
+    pub const fn with_transaction_history(mut self, v: bool) -> Self {
+        self.transaction_history = v;
+        self
+    }
+
+    /// Set the default max number of transactions in the transaction history
+    pub const fn with_transaction_history_max_transactions(mut self, v: u32) -> Self {
+        self.transaction_history_max_transactions = v;
+        self
+    }
+
+    /// Set the default transaction history path
+    pub fn with_transaction_history_path(mut self, v: Option<PathBuf>) -> Self {
+        self.transaction_history_path = v;
+        self
+    }
+
+    /// Set whether to simulate pending transactions by default
+    pub const fn with_simulate(mut self, v: bool) -> Self {
+        self.simulate = v;
+        self
+    }
+
+    /// Set the default max number of pending transactions simulated per block
+    pub const fn with_simulate_max_transactions(mut self, v: usize) -> Self {
+        self.simulate_max_transactions = v;
+        self
+    }
+
+    /// Set the default number of consecutive reverts before a transaction is evicted
+    pub const fn with_simulate_max_reverts(mut self, v: u32) -> Self {
+        self.simulate_max_reverts = v;
+        self
+    }
 }
 
 impl Default for DefaultTxPoolValues {
@@ -282,6 +332,12 @@ impl Default for DefaultTxPoolValues {
             transactions_backup_path: None,
             disable_transactions_backup: false,
             max_batch_size: 1,
+            transaction_history: false,
+            transaction_history_max_transactions: DEFAULT_JOURNAL_MAX_TRANSACTIONS,
+            transaction_history_path: None,
+            simulate: false,
+            simulate_max_transactions: DEFAULT_SIMULATION_MAX_TRANSACTIONS,
+            simulate_max_reverts: DEFAULT_SIMULATION_MAX_CONSECUTIVE_REVERTS,
         }
     }
 }
@@ -411,6 +467,35 @@ pub struct TxPoolArgs {
     /// Max batch size for transaction pool insertions
     #[arg(long = "txpool.max-batch-size", default_value_t = DefaultTxPoolValues::get_global().max_batch_size)]
     pub max_batch_size: usize,
+
+    /// Records the lifecycle events of transactions, including why they were discarded, which can
+    /// be queried with `txpool_getTransactionHistory`.
+    #[arg(long = "txpool.history", default_value_t = DefaultTxPoolValues::get_global().transaction_history)]
+    pub transaction_history: bool,
+
+    /// Max number of transactions to keep the history of.
+    #[arg(long = "txpool.history-max-transactions", requires = "transaction_history", default_value_t = DefaultTxPoolValues::get_global().transaction_history_max_transactions)]
+    pub transaction_history_max_transactions: u32,
+
+    /// Path to store the transaction history at, to survive node restarts.
+    #[arg(long = "txpool.history-path", value_name = "PATH", requires = "transaction_history", default_value = Resettable::from(DefaultTxPoolValues::get_global().transaction_history_path.as_ref().map(|v| v.to_string_lossy().into())))]
+    pub transaction_history_path: Option<PathBuf>,
+
+    /// Simulates the best pending transactions on top of every new block, annotating them with
+    /// their gas used, revert status and effective tip.
+    ///
+    /// Transactions that reverted in their latest simulation are left out of built payloads.
+    #[arg(long = "txpool.simulate", default_value_t = DefaultTxPoolValues::get_global().simulate)]
+    pub simulate: bool,
+
+    /// Max number of the best pending transactions simulated per block.
+    #[arg(long = "txpool.simulate-max-transactions", requires = "simulate", default_value_t = DefaultTxPoolValues::get_global().simulate_max_transactions)]
+    pub simulate_max_transactions: usize,
+
+    /// Number of consecutive simulations a transaction may revert in before it is evicted from
+    /// the pool. Local transactions are never evicted, 0 disables the eviction.
+    #[arg(long = "txpool.simulate-max-reverts", requires = "simulate", default_value_t = DefaultTxPoolValues::get_global().simulate_max_reverts)]
+    pub simulate_max_reverts: u32,
 }
 
 impl TxPoolArgs {
@@ -429,6 +514,15 @@ impl TxPoolArgs {
         self.minimal_protocol_basefee = protocol_base_fee;
         self
     }
+
+    /// Returns the configuration of the pending transaction simulator, if enabled.
+    pub fn simulation_config(&self) -> Option<SimulationConfig> {
+        self.simulate.then(|| SimulationConfig {
+            max_transactions: self.simulate_max_transactions,
+            max_consecutive_reverts: (self.simulate_max_reverts > 0)
+                .then_some(self.simulate_max_reverts),
+        })
+    }
 }
 
 impl Default for TxPoolArgs {
@@ -464,6 +558,12 @@ impl Default for TxPoolArgs {
             transactions_backup_path,
             disable_transactions_backup,
             max_batch_size,
+            transaction_history,
+            transaction_history_max_transactions,
+            transaction_history_path,
+            simulate,
+            simulate_max_transactions,
+            simulate_max_reverts,
         } = DefaultTxPoolValues::get_global().clone();
         Self {
             pending_max_count,
@@ -496,6 +596,12 @@ impl Default for TxPoolArgs {
             transactions_backup_path,
             disable_transactions_backup,
             max_batch_size,
+            transaction_history,
+            transaction_history_max_transactions,
+            transaction_history_path,
+            simulate,
+            simulate_max_transactions,
+            simulate_max_reverts,
         }
     }
 }
@@ -540,6 +646,10 @@ impl RethTransactionPoolConfig for TxPoolArgs {
             max_new_pending_txs_notifications: self.max_new_pending_txs_notifications,
             max_queued_lifetime: self.max_queued_lifetime,
             max_inflight_delegated_slot_limit: default_config.max_inflight_delegated_slot_limit,
+            journal: self.transaction_history.then(|| TransactionJournalConfig {
+                max_transactions: self.transaction_history_max_transactions,
+                ..Default::default()
+            }),
         }
     }
 
@@ -589,6 +699,54 @@ mod tests {
         assert!(result.is_err(), "Expected an error for invalid duration");
     }
 
+    #[test]
+    fn txpool_parse_history() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert!(args.pool_config().journal.is_none());
+
+        let args = CommandParser::<TxPoolArgs>::parse_from([
+            "reth",
+            "--txpool.history",
+            "--txpool.history-max-transactions",
+            "10",
+        ])
+        .args;
+        assert_eq!(args.pool_config().journal.unwrap().max_transactions, 10);
+
+        // the history path requires the history to be enabled
+        let result = CommandParser::<TxPoolArgs>::try_parse_from([
+            "reth",
+            "--txpool.history-path",
+            "/tmp/txpool-history.json",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn txpool_parse_simulate() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert!(args.simulation_config().is_none());
+
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.simulate"]).args;
+        assert_eq!(args.simulation_config(), Some(SimulationConfig::default()));
+
+        let args = CommandParser::<TxPoolArgs>::parse_from([
+            "reth",
+            "--txpool.simulate",
+            "--txpool.simulate-max-reverts",
+            "0",
+        ])
+        .args;
+        assert_eq!(args.simulation_config().unwrap().max_consecutive_reverts, None);
+
+        let result = CommandParser::<TxPoolArgs>::try_parse_from([
+            "reth",
+            "--txpool.simulate-max-transactions",
+            "10",
+        ]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn txpool_args() {
         let args = TxPoolArgs {
@@ -625,6 +783,12 @@ mod tests {
             transactions_backup_path: Some(PathBuf::from("/tmp/txpool-backup")),
             disable_transactions_backup: false,
             max_batch_size: 10,
+            transaction_history: true,
+            transaction_history_max_transactions: 1000,
+            transaction_history_path: Some(PathBuf::from("/tmp/txpool-history.json")),
+            simulate: true,
+            simulate_max_transactions: 100,
+            simulate_max_reverts: 5,
         };
 
         let parsed_args = CommandParser::<TxPoolArgs>::parse_from([
@@ -685,6 +849,16 @@ mod tests {
             "/tmp/txpool-backup",
             "--txpool.max-batch-size",
             "10",
+            "--txpool.history",
+            "--txpool.history-max-transactions",
+            "1000",
+            "--txpool.history-path",
+            "/tmp/txpool-history.json",
+            "--txpool.simulate",
+            "--txpool.simulate-max-transactions",
+            "100",
+            "--txpool.simulate-max-reverts",
+            "5",
         ])
         .args;
 
@@ -338,6 +338,13 @@ impl<D> ChainPath<D> {
         self.data_dir().join("known-peers.json")
     }
 
+    /// Returns the path to the file the bans of peers, IP addresses and ranges are persisted to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/banlist.json`
+    pub fn banlist(&self) -> PathBuf {
+        self.data_dir().join("banlist.json")
+    }
+
     /// Returns the path to the blobstore directory for this chain where blobs of unfinalized
     /// transactions are stored.
     ///
@@ -367,6 +374,27 @@ impl<D> ChainPath<D> {
         self.data_dir().join("jwt.hex")
     }
 
+    /// Returns the path to the cache of remote state fetched by a forked dev chain.
+    ///
+    /// `<DIR>/<CHAIN_ID>/fork-state.jsonl`
+    pub fn fork_state(&self) -> PathBuf {
+        self.data_dir().join("fork-state.jsonl")
+    }
+
+    /// Returns the path to the progress of the background trie verifier.
+    ///
+    /// `<DIR>/<CHAIN_ID>/trie-verifier-progress`
+    pub fn trie_verifier_progress(&self) -> PathBuf {
+        self.data_dir().join("trie-verifier-progress")
+    }
+
+    /// Returns the path to the directory `admin_backup` writes backups to by default.
+    ///
+    /// `<DIR>/<CHAIN_ID>/backups`
+    pub fn backups(&self) -> PathBuf {
+        self.data_dir().join("backups")
+    }
+
     /// Returns the path to the invalid block hooks directory for this chain.
     ///
     /// `<DIR>/<CHAIN_ID>/invalid_block_hooks`
@@ -3,7 +3,8 @@
 use crate::{
     args::{
         DatabaseArgs, DatadirArgs, DebugArgs, DevArgs, EngineArgs, JitArgs, NetworkArgs,
-        PayloadBuilderArgs, PruningArgs, RpcServerArgs, StaticFilesArgs, StorageArgs, TxPoolArgs,
+        PayloadBuilderArgs, PruningArgs, RpcServerArgs, StaticFilesArgs, StorageArgs,
+        TrieVerifierArgs, TxPoolArgs,
     },
     dirs::{ChainPath, DataDirPath},
     utils::get_single_header,
@@ -158,6 +159,9 @@ pub struct NodeConfig<ChainSpec> {
 
     /// All JIT related arguments with --jit prefix
     pub jit: JitArgs,
+
+    /// All background trie verifier related arguments with --trie-verifier prefix
+    pub trie_verifier: TrieVerifierArgs,
 }
 
 impl NodeConfig<ChainSpec> {
@@ -191,6 +195,7 @@ impl<ChainSpec> NodeConfig<ChainSpec> {
             static_files: StaticFilesArgs::default(),
             storage: StorageArgs::default(),
             jit: JitArgs::default(),
+            trie_verifier: TrieVerifierArgs::default(),
         }
     }
 
@@ -272,6 +277,7 @@ impl<ChainSpec> NodeConfig<ChainSpec> {
             static_files,
             storage,
             jit,
+            trie_verifier,
             ..
         } = self;
         NodeConfig {
@@ -293,6 +299,7 @@ impl<ChainSpec> NodeConfig<ChainSpec> {
             static_files,
             storage,
             jit,
+            trie_verifier,
         }
     }
 
@@ -375,6 +382,12 @@ impl<ChainSpec> NodeConfig<ChainSpec> {
         self
     }
 
+    /// Set the background trie verifier args for the node
+    pub const fn with_trie_verifier(mut self, trie_verifier: TrieVerifierArgs) -> Self {
+        self.trie_verifier = trie_verifier;
+        self
+    }
+
     /// Returns pruning configuration.
     pub fn prune_config(&self) -> Option<PruneConfig>
     where
@@ -592,6 +605,7 @@ impl<ChainSpec> NodeConfig<ChainSpec> {
             static_files: self.static_files,
             storage: self.storage,
             jit: self.jit,
+            trie_verifier: self.trie_verifier,
         }
     }
 
@@ -635,6 +649,7 @@ impl<ChainSpec> Clone for NodeConfig<ChainSpec> {
             static_files: self.static_files,
             storage: self.storage,
             jit: self.jit.clone(),
+            trie_verifier: self.trie_verifier,
         }
     }
 }
@@ -28,6 +28,8 @@ fn custom_dev_defaults_apply_to_cli_and_default() {
         finality_depth: NonZeroUsize::new(2).unwrap(),
         payload_wait_time: Some(Duration::from_millis(250)),
         dev_mnemonic: "custom mnemonic".to_string(),
+        fork_url: None,
+        fork_block: None,
     };
 
     assert_eq!(CommandParser::<DevArgs>::parse_from(["reth"]).args, expected);
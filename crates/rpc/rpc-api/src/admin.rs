@@ -1,6 +1,10 @@
 use alloy_rpc_types_admin::{NodeInfo, PeerInfo};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_net_banlist::{BanEntry, BanTarget};
+use reth_network_api::BandwidthStats;
 use reth_network_peers::{AnyNode, NodeRecord};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
@@ -35,6 +39,31 @@ pub trait AdminApi {
     #[method(name = "unbanPeer")]
     fn unban_peer(&self, record: AnyNode) -> RpcResult<bool>;
 
+    /// Bans a peer id, an IP address or a CIDR range like `10.0.0.0/8`, and disconnects the
+    /// non-trusted peers it covers.
+    ///
+    /// The ban expires after `duration` seconds, or never if no duration is given. Bans are
+    /// persisted across restarts.
+    ///
+    /// Returns false if the ban was ignored because the target is a trusted peer.
+    #[method(name = "addBan")]
+    async fn add_ban(
+        &self,
+        target: BanTarget,
+        reason: Option<String>,
+        duration: Option<u64>,
+    ) -> RpcResult<bool>;
+
+    /// Lifts all bans of a peer id, IP address or CIDR range.
+    ///
+    /// Returns false if the target was not banned.
+    #[method(name = "removeBan")]
+    async fn remove_ban(&self, target: BanTarget) -> RpcResult<bool>;
+
+    /// Returns all bans with their reason and expiry.
+    #[method(name = "bans")]
+    async fn bans(&self) -> RpcResult<Vec<BanEntry>>;
+
     /// The peers administrative property can be queried for all the information known about the
     /// connected remote nodes at the networking granularity. These include general information
     /// about the nodes themselves as participants of the devp2p P2P overlay protocol, as well as
@@ -42,6 +71,13 @@ pub trait AdminApi {
     #[method(name = "peers")]
     async fn peers(&self) -> RpcResult<Vec<PeerInfo>>;
 
+    /// Returns the bytes exchanged with each connected peer, in total and by message type.
+    ///
+    /// Peers are only included if their traffic is measured, i.e. if bandwidth accounting is
+    /// enabled or a rate limit is set.
+    #[method(name = "peerBandwidth")]
+    async fn peer_bandwidth(&self) -> RpcResult<Vec<PeerBandwidth>>;
+
     /// Creates an RPC subscription which serves events received from the network.
     #[subscription(
         name = "peerEvents",
@@ -59,3 +95,44 @@ pub trait AdminApi {
     #[method(name = "clearTxpool")]
     async fn clear_txpool(&self) -> RpcResult<u64>;
 }
+
+/// Bytes exchanged with a connected peer, returned by `admin_peerBandwidth`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerBandwidth {
+    /// The id of the peer, as returned by `admin_peers`.
+    pub id: String,
+    /// The `enode://` URL of the peer.
+    pub enode: String,
+    /// The bytes exchanged with the peer during the session.
+    #[serde(flatten)]
+    pub bandwidth: BandwidthStats,
+}
+
+/// Summary of a backup written by `admin_backup`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// Directory the backup was written to.
+    pub path: PathBuf,
+    /// Highest fully synced block contained in the backup.
+    pub block: u64,
+}
+
+/// Admin namespace rpc interface for backing up the node's storage.
+///
+/// This is separate from [`AdminApi`] because it needs direct access to the node's storage.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdminBackupApi {
+    /// Writes a consistent point-in-time backup of the database, static files and `RocksDB` to
+    /// `path` on the node's filesystem, laid out like a regular datadir.
+    ///
+    /// `path` is relative to the node's backup directory and must not exist yet. Fails if another
+    /// backup is still running.
+    ///
+    /// The node keeps syncing while the backup is taken. Returns once the backup is complete. The
+    /// output can be used as a datadir as-is, or packaged with `reth download snapshot-manifest`.
+    #[method(name = "backup")]
+    async fn backup(&self, path: PathBuf) -> RpcResult<BackupInfo>;
+}
@@ -1,3 +1,4 @@
+use crate::BlockTracesNotification;
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_genesis::ChainConfig;
 use alloy_json_rpc::RpcObject;
@@ -57,6 +58,26 @@ pub trait DebugApi<TxReq: RpcObject> {
         end_inclusive: BlockNumberOrTag,
     ) -> RpcResult<Vec<BlockTraceResult>>;
 
+    /// Subscribes to the traces of new canonical blocks.
+    ///
+    /// Every block that is added to the canonical chain is traced with the given options, for
+    /// example with the `callTracer` or the `prestateTracer` in diff mode, and emitted as a
+    /// [`BlockTracesNotification`]. Blocks that are removed by a reorg are announced before the
+    /// blocks of the new chain.
+    ///
+    /// Every block is re-executed with the tracer once for all subscriptions with the same options,
+    /// and the subscription must be enabled with `--rpc.block-trace-subscriptions`. A subscription
+    /// that falls too far behind is closed.
+    #[subscription(
+        name = "subscribeBlockTraces",
+        unsubscribe = "unsubscribeBlockTraces",
+        item = BlockTracesNotification<Vec<TraceResult>>
+    )]
+    async fn debug_subscribe_block_traces(
+        &self,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult;
+
     /// The `debug_traceBlock` method will return a full stack trace of all invoked opcodes of all
     /// transaction that were included in this block.
     ///
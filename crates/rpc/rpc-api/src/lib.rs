@@ -23,6 +23,7 @@ mod mev;
 mod miner;
 mod net;
 mod otterscan;
+mod relay;
 mod reth;
 mod reth_engine;
 mod rpc;
@@ -32,8 +33,12 @@ mod txpool;
 mod validation;
 mod web3;
 
+pub use admin::{BackupInfo, PeerBandwidth};
+pub use relay::RelayBundle;
 pub use reth::RethJitAction;
 pub use testing::{TestingBuildBlockRequestV1, TESTING_BUILD_BLOCK_V1, TESTING_COMMIT_BLOCK_V1};
+pub use trace::BlockTracesNotification;
+pub use txpool::TxpoolTransaction;
 
 /// re-export of all server traits
 pub use servers::*;
@@ -41,7 +46,7 @@ pub use servers::*;
 /// Aggregates all server traits.
 pub mod servers {
     pub use crate::{
-        admin::AdminApiServer,
+        admin::{AdminApiServer, AdminBackupApiServer},
         anvil::AnvilApiServer,
         debug::DebugApiServer,
         engine::{EngineApiServer, EngineEthApiServer, IntoEngineApiRpcModule},
@@ -50,6 +55,7 @@ pub mod servers {
         miner::MinerApiServer,
         net::NetApiServer,
         otterscan::OtterscanServer,
+        relay::RelayApiServer,
         reth::RethApiServer,
         reth_engine::{RethEngineApiServer, RethNewPayloadInput, RethPayloadStatus},
         rpc::RpcApiServer,
@@ -73,7 +79,7 @@ pub use clients::*;
 #[cfg(feature = "client")]
 pub mod clients {
     pub use crate::{
-        admin::AdminApiClient,
+        admin::{AdminApiClient, AdminBackupApiClient},
         anvil::AnvilApiClient,
         debug::DebugApiClient,
         engine::{EngineApiClient, EngineEthApiClient},
@@ -82,6 +88,7 @@ pub mod clients {
         miner::MinerApiClient,
         net::NetApiClient,
         otterscan::OtterscanClient,
+        relay::RelayApiClient,
         reth::RethApiClient,
         reth_engine::RethEngineApiClient,
         rpc::RpcApiClient,
@@ -0,0 +1,47 @@
+//! Relay namespace to forward transactions and bundles over the private relay.
+
+use alloy_primitives::{Bytes, U64};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_network_api::{private_relay::RelayAck, PeerId};
+use serde::{Deserialize, Serialize};
+
+/// A bundle received from a private relay peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayBundle {
+    /// The relay peer that sent the bundle.
+    pub peer_id: PeerId,
+    /// The block the bundle targets.
+    pub block_number: U64,
+    /// The EIP-2718 encoded transactions of the bundle.
+    pub transactions: Vec<Bytes>,
+}
+
+/// Relay RPC interface to forward private transactions and bundles to the configured relay peers.
+///
+/// Only available if the node is started with `--private-relay-peers`.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "relay"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "relay"))]
+pub trait RelayApi {
+    /// Relays the EIP-2718 encoded transactions to all connected relay peers and returns their
+    /// acknowledgements.
+    #[method(name = "sendRawTransactions")]
+    async fn send_raw_transactions(&self, transactions: Vec<Bytes>) -> RpcResult<Vec<RelayAck>>;
+
+    /// Relays a bundle of EIP-2718 encoded transactions that targets the given block to all
+    /// connected relay peers and returns their acknowledgements.
+    #[method(name = "sendBundle")]
+    async fn send_bundle(
+        &self,
+        transactions: Vec<Bytes>,
+        block_number: U64,
+    ) -> RpcResult<Vec<RelayAck>>;
+
+    /// Subscribe to bundles received from relay peers.
+    #[subscription(
+        name = "subscribeBundles",
+        unsubscribe = "unsubscribeBundles",
+        item = RelayBundle
+    )]
+    async fn subscribe_bundles(&self) -> jsonrpsee::core::SubscriptionResult;
+}
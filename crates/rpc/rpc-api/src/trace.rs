@@ -7,6 +7,7 @@ use alloy_rpc_types_trace::{
     parity::*,
 };
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
 
 /// Ethereum trace API
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "trace"))]
@@ -106,4 +107,89 @@ pub trait TraceApi<TxReq> {
     /// This is the same as `trace_transactionOpcodeGas` but for all transactions in a block.
     #[method(name = "blockOpcodeGas")]
     async fn trace_block_opcode_gas(&self, block_id: BlockId) -> RpcResult<Option<BlockOpcodeGas>>;
+
+    /// Subscribes to the parity traces of new canonical blocks.
+    ///
+    /// Emits the same traces as `trace_block` for every block that is added to the canonical
+    /// chain. Blocks that are removed by a reorg are announced before the blocks of the new chain.
+    ///
+    /// Every block is re-executed with the tracer once for all subscriptions, and the subscription
+    /// must be enabled with `--rpc.block-trace-subscriptions`. A subscription that falls too far
+    /// behind is closed.
+    #[subscription(
+        name = "subscribeBlockTraces",
+        unsubscribe = "unsubscribeBlockTraces",
+        item = BlockTracesNotification<Vec<LocalizedTransactionTrace>>
+    )]
+    async fn trace_subscribe_block_traces(&self) -> jsonrpsee::core::SubscriptionResult;
+}
+
+/// Notification emitted by the block trace subscriptions, `trace_subscribeBlockTraces` and
+/// `debug_subscribeBlockTraces`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BlockTracesNotification<T> {
+    /// A block was added to the canonical chain.
+    Committed {
+        /// The number of the block.
+        #[serde(with = "alloy_serde::quantity")]
+        number: u64,
+        /// The hash of the block.
+        hash: B256,
+        /// The hash of the parent block.
+        parent_hash: B256,
+        /// The traces of the block's transactions, `None` if the block could not be traced.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        traces: Option<T>,
+        /// The reason the block could not be traced.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A block was removed from the canonical chain by a reorg.
+    Removed {
+        /// The number of the block.
+        #[serde(with = "alloy_serde::quantity")]
+        number: u64,
+        /// The hash of the block.
+        hash: B256,
+        /// The hash of the parent block.
+        parent_hash: B256,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_traces_notification_serde() {
+        let committed = BlockTracesNotification::Committed {
+            number: 16,
+            hash: B256::repeat_byte(1),
+            parent_hash: B256::repeat_byte(2),
+            traces: Some(Vec::<u64>::new()),
+            error: None,
+        };
+        let json = serde_json::to_value(&committed).unwrap();
+        assert_eq!(json["type"], "committed");
+        assert_eq!(json["number"], "0x10");
+        assert_eq!(json["parentHash"], serde_json::to_value(B256::repeat_byte(2)).unwrap());
+        assert!(json.get("error").is_none());
+        assert_eq!(
+            serde_json::from_value::<BlockTracesNotification<Vec<u64>>>(json).unwrap(),
+            committed
+        );
+
+        let removed = BlockTracesNotification::<Vec<u64>>::Removed {
+            number: 16,
+            hash: B256::repeat_byte(1),
+            parent_hash: B256::repeat_byte(2),
+        };
+        let json = serde_json::to_value(&removed).unwrap();
+        assert_eq!(json["type"], "removed");
+        assert_eq!(
+            serde_json::from_value::<BlockTracesNotification<Vec<u64>>>(json).unwrap(),
+            removed
+        );
+    }
 }
@@ -1,7 +1,23 @@
 use alloy_json_rpc::RpcObject;
-use alloy_primitives::Address;
+use alloy_primitives::{Address, TxHash};
 use alloy_rpc_types_txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolStatus};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_transaction_pool_types::{SimulationOutcome, TransactionHistory};
+use serde::{Deserialize, Serialize};
+
+/// A transaction returned by `txpool_content` and `txpool_contentFrom`.
+///
+/// This is the regular rpc transaction, annotated with the outcome of its latest speculative
+/// execution if the pool simulates pending transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolTransaction<T> {
+    /// The transaction.
+    #[serde(flatten)]
+    pub transaction: T,
+    /// The outcome of the latest simulation of the transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<SimulationOutcome>,
+}
 
 /// Txpool rpc interface.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "txpool"))]
@@ -26,12 +42,26 @@ pub trait TxPoolApi<T: RpcObject> {
     ///
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_contentFrom) for more details
     #[method(name = "contentFrom")]
-    async fn txpool_content_from(&self, from: Address) -> RpcResult<TxpoolContentFrom<T>>;
+    async fn txpool_content_from(
+        &self,
+        from: Address,
+    ) -> RpcResult<TxpoolContentFrom<TxpoolTransaction<T>>>;
 
     /// Returns the details of all transactions currently pending for inclusion in the next
     /// block(s), as well as the ones that are being scheduled for future execution only.
     ///
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content) for more details
     #[method(name = "content")]
-    async fn txpool_content(&self) -> RpcResult<TxpoolContent<T>>;
+    async fn txpool_content(&self) -> RpcResult<TxpoolContent<TxpoolTransaction<T>>>;
+
+    /// Returns the recorded lifecycle events of the transaction, e.g. sub-pool moves, replacement,
+    /// the reason why it was discarded or the block it was mined in.
+    ///
+    /// The history is kept after the transaction left the pool. Returns `null` if the transaction
+    /// is unknown or the transaction journal is disabled.
+    #[method(name = "getTransactionHistory")]
+    async fn txpool_get_transaction_history(
+        &self,
+        hash: TxHash,
+    ) -> RpcResult<Option<TransactionHistory>>;
 }
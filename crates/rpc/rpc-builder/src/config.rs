@@ -112,6 +112,7 @@ impl RethRpcServerConfig for RpcServerArgs {
             .raw_tx_forwarder(self.rpc_forwarder.clone())
             .rpc_evm_memory_limit(self.rpc_evm_memory_limit)
             .force_blob_sidecar_upcasting(self.rpc_force_blob_sidecar_upcasting)
+            .block_trace_subscriptions(self.rpc_block_trace_subscriptions)
     }
 
     fn flashbots_config(&self) -> ValidationApiConfig {
@@ -191,7 +192,9 @@ impl RethRpcServerConfig for RpcServerArgs {
     fn rpc_server_config(&self) -> RpcServerConfig {
         let mut config = RpcServerConfig::default()
             .with_jwt_secret(self.rpc_secret_key())
-            .with_rpc_metrics_enabled(self.rpc_metrics_enabled());
+            .with_rpc_metrics_enabled(self.rpc_metrics_enabled())
+            .with_rate_limit(self.rpc_rate_limit.rate_limit_config())
+            .with_recorder(self.rpc_recorder.recorder_config());
 
         if self.http_api.is_some() && !self.http {
             warn!(
@@ -212,6 +215,7 @@ impl RethRpcServerConfig for RpcServerArgs {
             config = config
                 .with_http_address(socket_address)
                 .with_http(self.http_ws_server_builder())
+                .with_http_max_response_size(self.rpc_max_response_size_bytes())
                 .with_http_cors(self.http_corsdomain.clone())
                 .with_http_disable_compression(self.http_disable_compression);
         }
@@ -222,6 +226,7 @@ impl RethRpcServerConfig for RpcServerArgs {
             config = config
                 .with_ws_address(socket_address)
                 .with_ws(self.http_ws_server_builder())
+                .with_ws_max_response_size(self.rpc_max_response_size_bytes())
                 .with_ws_cors(self.ws_allowed_origins.clone());
         }
 
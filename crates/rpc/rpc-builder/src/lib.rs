@@ -19,14 +19,22 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-use crate::{auth::AuthRpcModule, error::WsHttpSamePortError, metrics::RpcRequestMetrics};
+use crate::{
+    auth::AuthRpcModule,
+    error::WsHttpSamePortError,
+    metrics::RpcRequestMetrics,
+    rate_limiter::{RpcClientIdentityLayer, RpcRateLimiter},
+    recorder::RpcRecorder,
+    response_cache::RpcResponseCache,
+    server::RpcTcpServer,
+};
 use alloy_network::{Ethereum, IntoWallet};
 use alloy_provider::{fillers::RecommendedFillers, Provider, ProviderBuilder};
 use core::marker::PhantomData;
 use error::{ConflictingModules, RpcError, ServerKind};
 use http::{header::AUTHORIZATION, HeaderMap};
 use jsonrpsee::{
-    core::RegisterMethodError,
+    core::{RegisterMethodError, TEN_MB_SIZE_BYTES},
     server::{middleware::rpc::RpcServiceBuilder, AlreadyStoppedError, IdProvider, ServerHandle},
     Methods, RpcModule,
 };
@@ -53,8 +61,14 @@ use reth_rpc_eth_api::{
     RpcConverter, RpcHeader, RpcNodeCore, RpcReceipt, RpcTransaction, RpcTxReq,
 };
 use reth_rpc_eth_types::{receipt::EthReceiptConverter, EthConfig, EthSubscriptionIdProvider};
+use reth_rpc_graphql::GraphQlLayer;
 use reth_rpc_layer::{AuthLayer, Claims, CompressionLayer, JwtAuthValidator, JwtSecret};
 pub use reth_rpc_server_types::RethRpcModule;
+use reth_rpc_server_types::{
+    rate_limit::RpcRateLimitConfig,
+    recording::{RecordedTransport, RpcRecorderConfig},
+    response_cache::RpcResponseCacheConfig,
+};
 use reth_storage_api::{
     BlockReader, ChangeSetReader, FullRpcProvider, NodePrimitivesProvider, StateProviderFactory,
 };
@@ -112,6 +126,15 @@ use reth_rpc::eth::sim_bundle::EthSimBundle;
 // Rpc rate limiter
 pub mod rate_limiter;
 
+// Rpc response cache
+pub mod response_cache;
+
+// Rpc traffic recorder
+pub mod recorder;
+
+// Http and ws server
+mod server;
+
 /// A builder type to configure the RPC module: See [`RpcModule`]
 ///
 /// This is the main entrypoint and the easiest way to configure an RPC server.
@@ -794,6 +817,7 @@ where
             self.blocking_pool_guard.clone(),
             self.eth_config.clone(),
         )
+        .with_block_trace_subscriptions(self.eth_config.block_trace_subscriptions)
     }
 
     /// Instantiates [`EthBundle`] Api
@@ -824,6 +848,7 @@ where
             self.tasks(),
             self.engine_events.new_listener(),
         )
+        .with_block_trace_subscriptions(self.eth_config.block_trace_subscriptions)
     }
 
     /// Instantiates `NetApi`
@@ -896,6 +921,24 @@ where
         AuthRpcModule { inner: module }
     }
 
+    /// Returns a [`GraphQlLayer`] that serves the EIP-1767 GraphQL schema.
+    ///
+    /// Queries are answered with calls to the `eth` handlers of the http server the layer is
+    /// installed on, so they pass through the same RPC middleware and are only served if the
+    /// `eth` module is enabled on that server.
+    pub fn graphql_layer(&self) -> GraphQlLayer {
+        GraphQlLayer::new()
+    }
+
+    /// Returns a [`RpcResponseCache`] with the given configuration that resolves finalized blocks
+    /// using the provider of this registry.
+    pub fn response_cache(
+        &self,
+        config: RpcResponseCacheConfig,
+    ) -> std::io::Result<RpcResponseCache> {
+        RpcResponseCache::new(config, self.provider.clone())
+    }
+
     /// Helper function to create a [`RpcModule`] if it's not `None`
     fn maybe_module(&mut self, config: Option<&RpcModuleSelection>) -> Option<RpcModule<()>> {
         config.map(|config| self.module_for(config))
@@ -967,6 +1010,7 @@ where
                             &self.executor,
                             self.engine_events.new_listener(),
                         )
+                        .with_block_trace_subscriptions(self.eth_config.block_trace_subscriptions)
                         .into_rpc()
                         .into(),
                         RethRpcModule::Eth => {
@@ -994,6 +1038,7 @@ where
                             self.blocking_pool_guard.clone(),
                             self.eth_config.clone(),
                         )
+                        .with_block_trace_subscriptions(self.eth_config.block_trace_subscriptions)
                         .into_rpc()
                         .into(),
                         RethRpcModule::Web3 => Web3Api::new(self.network.clone()).into_rpc().into(),
@@ -1031,6 +1076,7 @@ where
                         // nodebuilder rpc addon stack
                         RethRpcModule::Flashbots |
                         RethRpcModule::Testing |
+                        RethRpcModule::Relay |
                         RethRpcModule::Other(_) => Default::default(),
                     })
                     .clone()
@@ -1076,7 +1122,7 @@ where
 /// Http and WS share the same settings: [`ServerBuilder`].
 ///
 /// Once the [`RpcModule`] is built via [`RpcModuleBuilder`] the servers can be started, See also
-/// [`ServerBuilder::build`] and [`Server::start`](jsonrpsee::server::Server::start).
+/// [`RpcServerConfig::start`].
 #[derive(Debug)]
 pub struct RpcServerConfig<RpcMiddleware = Identity> {
     /// Configs for JSON-RPC Http.
@@ -1101,6 +1147,20 @@ pub struct RpcServerConfig<RpcMiddleware = Identity> {
     jwt_secret: Option<JwtSecret>,
     /// Whether RPC request metrics are enabled.
     rpc_metrics_enabled: bool,
+    /// Per-client rate limits for http and ws, if enabled.
+    rate_limit: Option<RpcRateLimitConfig>,
+    /// GraphQL endpoint served by the http server, if enabled.
+    graphql: Option<GraphQlLayer>,
+    /// Cache for responses of calls on finalized data for http and ws, if enabled.
+    response_cache: Option<RpcResponseCache>,
+    /// Maximum response size of the http server, applied to responses served from the cache.
+    http_max_response_size: u32,
+    /// Maximum response size of the ws server, applied to responses served from the cache.
+    ws_max_response_size: u32,
+    /// Recorder of sampled calls, if enabled.
+    recorder: Option<RpcRecorderConfig>,
+    /// Custom tokio runtime the http and ws servers are spawned on.
+    tokio_runtime: Option<tokio::runtime::Handle>,
     /// Configurable RPC middleware
     rpc_middleware: RpcMiddleware,
 }
@@ -1122,6 +1182,13 @@ impl Default for RpcServerConfig<Identity> {
             ipc_endpoint: None,
             jwt_secret: None,
             rpc_metrics_enabled: true,
+            rate_limit: None,
+            graphql: None,
+            response_cache: None,
+            http_max_response_size: TEN_MB_SIZE_BYTES,
+            ws_max_response_size: TEN_MB_SIZE_BYTES,
+            recorder: None,
+            tokio_runtime: None,
             rpc_middleware: Default::default(),
         }
     }
@@ -1187,6 +1254,13 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
             ipc_endpoint: self.ipc_endpoint,
             jwt_secret: self.jwt_secret,
             rpc_metrics_enabled: self.rpc_metrics_enabled,
+            rate_limit: self.rate_limit,
+            graphql: self.graphql,
+            response_cache: self.response_cache,
+            http_max_response_size: self.http_max_response_size,
+            ws_max_response_size: self.ws_max_response_size,
+            recorder: self.recorder,
+            tokio_runtime: self.tokio_runtime,
             rpc_middleware,
         }
     }
@@ -1197,6 +1271,56 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         self
     }
 
+    /// Configures per-client rate limits for the http and ws servers.
+    ///
+    /// The ipc server is not rate limited.
+    pub fn with_rate_limit(mut self, rate_limit: Option<RpcRateLimitConfig>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Configures the GraphQL endpoint served by the http server.
+    ///
+    /// See also [`RpcRegistryInner::graphql_layer`].
+    pub fn with_graphql(mut self, graphql: Option<GraphQlLayer>) -> Self {
+        self.graphql = graphql;
+        self
+    }
+
+    /// Configures the response cache for the http and ws servers.
+    ///
+    /// See also [`RpcRegistryInner::response_cache`].
+    pub fn with_response_cache(mut self, response_cache: Option<RpcResponseCache>) -> Self {
+        self.response_cache = response_cache;
+        self
+    }
+
+    /// Sets the maximum response size of the http server that responses served from the response
+    /// cache are checked against.
+    ///
+    /// This should match the limit of the [`ServerConfigBuilder`] passed to [`Self::with_http`].
+    /// Defaults to the [`ServerConfigBuilder`] default.
+    pub const fn with_http_max_response_size(mut self, max_response_size: u32) -> Self {
+        self.http_max_response_size = max_response_size;
+        self
+    }
+
+    /// Sets the maximum response size of the ws server that responses served from the response
+    /// cache are checked against.
+    ///
+    /// This should match the limit of the [`ServerConfigBuilder`] passed to [`Self::with_ws`].
+    /// Defaults to the [`ServerConfigBuilder`] default.
+    pub const fn with_ws_max_response_size(mut self, max_response_size: u32) -> Self {
+        self.ws_max_response_size = max_response_size;
+        self
+    }
+
+    /// Configures recording of sampled calls of all transports to disk.
+    pub fn with_recorder(mut self, recorder: Option<RpcRecorderConfig>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
     /// Configure the cors domains for http _and_ ws
     pub fn with_cors(self, cors_domain: Option<String>) -> Self {
         self.with_http_cors(cors_domain.clone()).with_ws_cors(cors_domain)
@@ -1275,17 +1399,11 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
     /// Configures a custom tokio runtime for the rpc server.
     pub fn with_tokio_runtime(mut self, tokio_runtime: Option<tokio::runtime::Handle>) -> Self {
         let Some(tokio_runtime) = tokio_runtime else { return self };
-        if let Some(http_server_config) = self.http_server_config {
-            self.http_server_config =
-                Some(http_server_config.custom_tokio_runtime(tokio_runtime.clone()));
-        }
-        if let Some(ws_server_config) = self.ws_server_config {
-            self.ws_server_config =
-                Some(ws_server_config.custom_tokio_runtime(tokio_runtime.clone()));
-        }
         if let Some(ipc_server_config) = self.ipc_server_config {
-            self.ipc_server_config = Some(ipc_server_config.custom_tokio_runtime(tokio_runtime));
+            self.ipc_server_config =
+                Some(ipc_server_config.custom_tokio_runtime(tokio_runtime.clone()));
         }
+        self.tokio_runtime = Some(tokio_runtime);
         self
     }
 
@@ -1318,6 +1436,26 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         self.rpc_metrics_enabled
     }
 
+    /// Returns the configured per-client rate limits, if any.
+    pub const fn rate_limit(&self) -> Option<&RpcRateLimitConfig> {
+        self.rate_limit.as_ref()
+    }
+
+    /// Returns true if the GraphQL endpoint is enabled.
+    pub const fn has_graphql(&self) -> bool {
+        self.graphql.is_some()
+    }
+
+    /// Returns the configured response cache, if any.
+    pub const fn response_cache(&self) -> Option<&RpcResponseCache> {
+        self.response_cache.as_ref()
+    }
+
+    /// Returns the configuration of the RPC traffic recorder, if enabled.
+    pub const fn recorder(&self) -> Option<&RpcRecorderConfig> {
+        self.recorder.as_ref()
+    }
+
     /// Creates the [`CorsLayer`] if any
     fn maybe_cors_layer(cors: Option<String>) -> Result<Option<CorsLayer>, CorsDomainError> {
         cors.as_deref().map(cors::create_cors_layer).transpose()
@@ -1362,17 +1500,34 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         )));
 
         let rpc_metrics_enabled = self.rpc_metrics_enabled;
+        // a single limiter is shared by http and ws so that quotas apply across transports
+        let rate_limiter = self.rate_limit.clone().map(RpcRateLimiter::new);
+        // the `sub` claim is only trusted if the JWT auth layer verified the token
+        let identity_layer = rate_limiter.as_ref().map(|limiter| {
+            RpcClientIdentityLayer::new(
+                limiter.config().trusted_proxies.iter().copied(),
+                self.jwt_secret.is_some(),
+            )
+        });
+        let recorder = self
+            .recorder
+            .clone()
+            .map(RpcRecorder::new)
+            .transpose()
+            .map_err(|err| RpcError::Custom(format!("failed to start rpc recorder: {err}")))?;
         let ipc_path =
             self.ipc_endpoint.clone().unwrap_or_else(|| constants::DEFAULT_IPC_ENDPOINT.into());
 
         if let Some(builder) = self.ipc_server_config {
             let ipc = builder
                 .set_rpc_middleware(
-                    IpcRpcServiceBuilder::new().option_layer(
-                        rpc_metrics_enabled
-                            .then(|| modules.ipc.as_ref().map(RpcRequestMetrics::ipc))
-                            .flatten(),
-                    ),
+                    IpcRpcServiceBuilder::new()
+                        .option_layer(
+                            rpc_metrics_enabled
+                                .then(|| modules.ipc.as_ref().map(RpcRequestMetrics::ipc))
+                                .flatten(),
+                        )
+                        .option_layer(recorder.as_ref().map(|r| r.layer(RecordedTransport::Ipc))),
                 )
                 .build(ipc_path);
             ipc_handle = Some(ipc.start(modules.ipc.clone().expect("ipc server error")).await?);
@@ -1402,14 +1557,16 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
             modules.config.ensure_ws_http_identical()?;
 
             if let Some(config) = self.http_server_config {
-                let server = ServerBuilder::new()
+                let builder = ServerBuilder::new()
                     .set_http_middleware(
                         tower::ServiceBuilder::new()
                             .option_layer(Self::maybe_cors_layer(cors)?)
                             .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                            .option_layer(identity_layer.clone())
                             .option_layer(Self::maybe_compression_layer(
                                 self.http_disable_compression,
-                            )),
+                            ))
+                            .option_layer(self.graphql.clone()),
                     )
                     .set_rpc_middleware(
                         RpcServiceBuilder::default()
@@ -1424,19 +1581,27 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                                     })
                                     .flatten(),
                             )
+                            .option_layer(rate_limiter.clone())
+                            .option_layer(
+                                recorder.as_ref().map(|r| r.layer(RecordedTransport::Http)),
+                            )
+                            .option_layer(
+                                self.response_cache
+                                    .as_ref()
+                                    .map(|cache| cache.layer(self.http_max_response_size)),
+                            )
                             .layer(self.rpc_middleware.clone()),
                     )
-                    .set_config(config.build())
-                    .build(http_socket_addr)
-                    .await
-                    .map_err(|err| {
+                    .set_config(config.build());
+                let server =
+                    RpcTcpServer::bind(builder, http_socket_addr).await.map_err(|err| {
                         RpcError::server_error(err, ServerKind::WsHttp(http_socket_addr))
                     })?;
                 let addr = server.local_addr().map_err(|err| {
                     RpcError::server_error(err, ServerKind::WsHttp(http_socket_addr))
                 })?;
                 if let Some(module) = modules.http.as_ref().or(modules.ws.as_ref()) {
-                    let handle = server.start(module.clone());
+                    let handle = server.start(module.clone(), self.tokio_runtime.as_ref());
                     http_handle = Some(handle.clone());
                     ws_handle = Some(handle);
                 }
@@ -1458,12 +1623,13 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         let mut http_server = None;
 
         if let Some(config) = self.ws_server_config {
-            let server = ServerBuilder::new()
+            let builder = ServerBuilder::new()
                 .set_config(config.ws_only().build())
                 .set_http_middleware(
                     tower::ServiceBuilder::new()
                         .option_layer(Self::maybe_cors_layer(self.ws_cors_domains.clone())?)
-                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret)),
+                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                        .option_layer(identity_layer.clone()),
                 )
                 .set_rpc_middleware(
                     RpcServiceBuilder::default()
@@ -1472,9 +1638,16 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                                 .then(|| modules.ws.as_ref().map(RpcRequestMetrics::ws))
                                 .flatten(),
                         )
+                        .option_layer(rate_limiter.clone())
+                        .option_layer(recorder.as_ref().map(|r| r.layer(RecordedTransport::Ws)))
+                        .option_layer(
+                            self.response_cache
+                                .as_ref()
+                                .map(|cache| cache.layer(self.ws_max_response_size)),
+                        )
                         .layer(self.rpc_middleware.clone()),
-                )
-                .build(ws_socket_addr)
+                );
+            let server = RpcTcpServer::bind(builder, ws_socket_addr)
                 .await
                 .map_err(|err| RpcError::server_error(err, ServerKind::WS(ws_socket_addr)))?;
 
@@ -1487,13 +1660,15 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         }
 
         if let Some(config) = self.http_server_config {
-            let server = ServerBuilder::new()
+            let builder = ServerBuilder::new()
                 .set_config(config.http_only().build())
                 .set_http_middleware(
                     tower::ServiceBuilder::new()
                         .option_layer(Self::maybe_cors_layer(self.http_cors_domains.clone())?)
                         .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
-                        .option_layer(Self::maybe_compression_layer(self.http_disable_compression)),
+                        .option_layer(identity_layer.clone())
+                        .option_layer(Self::maybe_compression_layer(self.http_disable_compression))
+                        .option_layer(self.graphql),
                 )
                 .set_rpc_middleware(
                     RpcServiceBuilder::default()
@@ -1502,9 +1677,16 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                                 .then(|| modules.http.as_ref().map(RpcRequestMetrics::http))
                                 .flatten(),
                         )
+                        .option_layer(rate_limiter.clone())
+                        .option_layer(recorder.as_ref().map(|r| r.layer(RecordedTransport::Http)))
+                        .option_layer(
+                            self.response_cache
+                                .as_ref()
+                                .map(|cache| cache.layer(self.http_max_response_size)),
+                        )
                         .layer(self.rpc_middleware.clone()),
-                )
-                .build(http_socket_addr)
+                );
+            let server = RpcTcpServer::bind(builder, http_socket_addr)
                 .await
                 .map_err(|err| RpcError::server_error(err, ServerKind::Http(http_socket_addr)))?;
             let local_addr = server
@@ -1514,10 +1696,16 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
             http_server = Some(server);
         }
 
-        http_handle = http_server
-            .map(|http_server| http_server.start(modules.http.clone().expect("http server error")));
-        ws_handle = ws_server
-            .map(|ws_server| ws_server.start(modules.ws.clone().expect("ws server error")));
+        http_handle = http_server.map(|http_server| {
+            http_server.start(
+                modules.http.clone().expect("http server error"),
+                self.tokio_runtime.as_ref(),
+            )
+        });
+        ws_handle = ws_server.map(|ws_server| {
+            ws_server
+                .start(modules.ws.clone().expect("ws server error"), self.tokio_runtime.as_ref())
+        });
         Ok(RpcServerHandle {
             http_local_addr,
             ws_local_addr,
@@ -0,0 +1,104 @@
+//! Token bucket used by the per-client rate limiter.
+
+use reth_rpc_server_types::rate_limit::TokenBucketConfig;
+use std::time::{Duration, Instant};
+
+/// A token bucket that refills continuously.
+///
+/// Tokens are tracked as fractional values so that low refill rates still make progress between
+/// frequent requests.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenBucket {
+    /// Maximum number of tokens.
+    capacity: f64,
+    /// Tokens added per second.
+    refill_per_sec: f64,
+    /// Currently available tokens.
+    tokens: f64,
+    /// The last time tokens were added.
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a new, full bucket.
+    pub(crate) fn new(config: TokenBucketConfig, now: Instant) -> Self {
+        let capacity = config.capacity as f64;
+        Self {
+            capacity,
+            refill_per_sec: config.refill_per_sec as f64,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Adds the tokens accumulated since the last refill.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Returns `Ok` if the bucket currently holds at least `cost` tokens, otherwise returns the
+    /// time until it will.
+    ///
+    /// This does not take any tokens, see [`Self::take`].
+    pub(crate) fn check(&mut self, cost: u32, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        let cost = cost as f64;
+        if self.tokens >= cost {
+            return Ok(())
+        }
+        if cost > self.capacity || self.refill_per_sec <= 0.0 {
+            // this request can never be admitted by this bucket
+            return Err(Duration::MAX)
+        }
+        Err(Duration::from_secs_f64((cost - self.tokens) / self.refill_per_sec))
+    }
+
+    /// Takes `cost` tokens from the bucket.
+    ///
+    /// This should only be called after a successful [`Self::check`].
+    pub(crate) fn take(&mut self, cost: u32) {
+        self.tokens = (self.tokens - cost as f64).max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admits_burst_then_refills() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(TokenBucketConfig::new(10, 5), now);
+
+        assert!(bucket.check(10, now).is_ok());
+        bucket.take(10);
+
+        // empty, 1 token needs 200ms at 5 tokens per second
+        let retry = bucket.check(1, now).unwrap_err();
+        assert_eq!(retry, Duration::from_millis(200));
+
+        let later = now + Duration::from_secs(1);
+        assert!(bucket.check(5, later).is_ok());
+        bucket.take(5);
+        assert!(bucket.check(1, later).is_err());
+    }
+
+    #[test]
+    fn caps_at_capacity() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(TokenBucketConfig::new(10, 5), now);
+        let later = now + Duration::from_secs(60);
+        assert!(bucket.check(10, later).is_ok());
+        bucket.take(10);
+        assert!(bucket.check(1, later).is_err());
+    }
+
+    #[test]
+    fn rejects_cost_above_capacity() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(TokenBucketConfig::new(10, 5), now);
+        assert_eq!(bucket.check(11, now), Err(Duration::MAX));
+    }
+}
@@ -1,4 +1,14 @@
-//! [`jsonrpsee`] helper layer for rate limiting certain methods.
+//! [`jsonrpsee`] helper layers for rate limiting certain methods.
+//!
+//! [`RpcRequestRateLimiter`] caps the number of concurrent expensive calls, while
+//! [`RpcRateLimiter`] enforces per-client token bucket quotas.
+
+mod bucket;
+mod quota;
+pub use quota::{
+    RateLimitedResponseFuture, RpcClientIdentity, RpcClientIdentityLayer, RpcClientIdentityService,
+    RpcRateLimitService, RpcRateLimiter,
+};
 
 use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request};
 use std::{
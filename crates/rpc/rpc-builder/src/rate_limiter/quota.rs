@@ -0,0 +1,575 @@
+//! Per-client token bucket rate limiting for the RPC server.
+
+use super::bucket::TokenBucket;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use http::{header, Extensions, HeaderMap};
+use jsonrpsee::{
+    core::{
+        middleware::{Batch, BatchEntry, BatchEntryErr, Notification},
+        server::ConnectionId,
+    },
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, ErrorObjectOwned, Request},
+    MethodResponse,
+};
+use parking_lot::Mutex;
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_rpc_server_types::{
+    constants::rate_limit::RATE_LIMIT_EXCEEDED_CODE,
+    rate_limit::{RateLimitKey, RpcRateLimitConfig},
+};
+use schnellru::{ByLength, LruMap};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::Layer;
+
+/// Identity of the client that sent a request.
+///
+/// This is extracted from the peer address and the HTTP headers by the [`RpcClientIdentityLayer`]
+/// and attached to the request extensions, so that RPC middleware can attribute calls to clients.
+/// For WS connections the identity is taken from the upgrade request and applies to all calls of
+/// the connection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcClientIdentity {
+    /// The client IP, i.e. the peer address of the connection, or the address reported by the
+    /// peer if it is a trusted reverse proxy.
+    pub ip: Option<IpAddr>,
+    /// The `sub` claim of the bearer JWT, only set if the token has been verified by the JWT
+    /// auth layer.
+    pub jwt_subject: Option<String>,
+}
+
+impl RpcClientIdentity {
+    /// Extracts the identity of the client connected from `peer` from the given HTTP headers.
+    ///
+    /// The forwarding headers can be set by any client, so they are only honoured if the peer is
+    /// one of the trusted proxies. Likewise, the `sub` claim is only used if `jwt_verified` is
+    /// set, i.e. the token has been validated before.
+    pub fn from_headers(
+        headers: &HeaderMap,
+        peer: Option<IpAddr>,
+        trusted_proxies: &[IpAddr],
+        jwt_verified: bool,
+    ) -> Self {
+        let ip = peer.map(|peer| {
+            if trusted_proxies.contains(&peer) {
+                forwarded_ip(headers, trusted_proxies).unwrap_or(peer)
+            } else {
+                peer
+            }
+        });
+        Self { ip, jwt_subject: jwt_verified.then(|| jwt_subject(headers)).flatten() }
+    }
+}
+
+/// Returns the originating client IP reported by the trusted reverse proxies.
+///
+/// Every proxy appends the address it received the request from to `X-Forwarded-For`, so the
+/// entries left of the last trusted proxy may have been forged by the client. The client is
+/// therefore the right-most entry that is not a trusted proxy. `X-Real-IP` is only used if there
+/// is no `X-Forwarded-For` header.
+///
+/// Must only be called for requests received from a trusted proxy.
+fn forwarded_ip(headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    if let Some(forwarded) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+        return forwarded
+            .rsplit(',')
+            .map(|ip| ip.trim().parse::<IpAddr>())
+            .find(|ip| !ip.as_ref().is_ok_and(|ip| trusted_proxies.contains(ip)))
+            .and_then(Result::ok)
+    }
+    headers.get("x-real-ip").and_then(|v| v.to_str().ok()).and_then(|ip| ip.trim().parse().ok())
+}
+
+/// Returns the `sub` claim of the bearer token, if any.
+fn jwt_subject(headers: &HeaderMap) -> Option<String> {
+    #[derive(Deserialize)]
+    struct SubjectClaim {
+        sub: Option<String>,
+    }
+
+    let auth = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let token = auth.strip_prefix("Bearer ")?;
+    let payload = token.split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+    serde_json::from_slice::<SubjectClaim>(&payload).ok()?.sub
+}
+
+/// HTTP middleware layer that attaches the [`RpcClientIdentity`] to every request.
+///
+/// The peer address of the connection is read from the [`SocketAddr`] in the request extensions.
+#[derive(Debug, Clone, Default)]
+pub struct RpcClientIdentityLayer {
+    /// The reverse proxies whose forwarding headers are honoured.
+    trusted_proxies: Arc<[IpAddr]>,
+    /// Whether bearer tokens have been verified by a preceding JWT auth layer.
+    jwt_verified: bool,
+}
+
+impl RpcClientIdentityLayer {
+    /// Creates a new layer that honours the forwarding headers of the given proxies.
+    ///
+    /// `jwt_verified` must only be set if the layer is installed behind a JWT auth layer that
+    /// rejects requests with invalid tokens, otherwise the `sub` claim is ignored.
+    pub fn new(trusted_proxies: impl IntoIterator<Item = IpAddr>, jwt_verified: bool) -> Self {
+        Self { trusted_proxies: trusted_proxies.into_iter().collect(), jwt_verified }
+    }
+}
+
+impl<S> Layer<S> for RpcClientIdentityLayer {
+    type Service = RpcClientIdentityService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcClientIdentityService {
+            inner,
+            trusted_proxies: self.trusted_proxies.clone(),
+            jwt_verified: self.jwt_verified,
+        }
+    }
+}
+
+/// HTTP service that attaches the [`RpcClientIdentity`] to every request.
+#[derive(Debug, Clone)]
+pub struct RpcClientIdentityService<S> {
+    inner: S,
+    trusted_proxies: Arc<[IpAddr]>,
+    jwt_verified: bool,
+}
+
+impl<S, B> tower::Service<http::Request<B>> for RpcClientIdentityService<S>
+where
+    S: tower::Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        let identity = RpcClientIdentity::from_headers(
+            req.headers(),
+            req.extensions().get::<SocketAddr>().map(SocketAddr::ip),
+            &self.trusted_proxies,
+            self.jwt_verified,
+        );
+        req.extensions_mut().insert(identity);
+        self.inner.call(req)
+    }
+}
+
+/// The key a request is attributed to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ClientKey {
+    Ip(IpAddr),
+    Subject(String),
+    Connection(usize),
+    Global,
+}
+
+/// The quota a rejected request exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RateLimitScope {
+    /// The client's quota across all methods.
+    Client,
+    /// The client's quota for the called method.
+    Method,
+}
+
+/// Additional data attached to rate limit errors.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RateLimitErrorData {
+    /// Seconds after which the request can be retried, absent if the request can never be
+    /// admitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after: Option<u64>,
+}
+
+/// A request that was rejected by the [`RpcRateLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RateLimited {
+    /// The exceeded quota.
+    pub(crate) scope: RateLimitScope,
+    /// The time until the request would be admitted.
+    pub(crate) retry_after: Duration,
+}
+
+impl From<RateLimited> for ErrorObjectOwned {
+    fn from(err: RateLimited) -> Self {
+        let retry_after =
+            (err.retry_after != Duration::MAX).then(|| err.retry_after.as_secs_f64().ceil() as u64);
+        let msg = match err.scope {
+            RateLimitScope::Client => "rate limit exceeded",
+            RateLimitScope::Method => "method rate limit exceeded",
+        };
+        ErrorObject::owned(RATE_LIMIT_EXCEEDED_CODE, msg, Some(RateLimitErrorData { retry_after }))
+    }
+}
+
+/// The buckets of a single client.
+#[derive(Debug)]
+struct ClientBuckets {
+    /// The quota across all methods.
+    total: TokenBucket,
+    /// Per method quotas, keyed by the index of the matching rule.
+    methods: HashMap<usize, TokenBucket>,
+}
+
+/// Per-client token bucket rate limiter for the RPC server.
+///
+/// Each client, as determined by the configured [`RateLimitKey`], has its own token bucket, and
+/// every call draws its configured cost from it. Calls that exceed the quota are rejected with a
+/// JSON-RPC error that carries a `retryAfter` hint in seconds.
+///
+/// The limiter is shared by all transports it is installed on.
+#[derive(Debug, Clone)]
+pub struct RpcRateLimiter {
+    inner: Arc<RpcRateLimiterInner>,
+}
+
+impl RpcRateLimiter {
+    /// Creates a new rate limiter with the given configuration.
+    pub fn new(config: RpcRateLimitConfig) -> Self {
+        let clients = LruMap::new(ByLength::new(config.max_tracked_clients.max(1)));
+        Self {
+            inner: Arc::new(RpcRateLimiterInner {
+                config,
+                clients: Mutex::new(clients),
+                metrics: Default::default(),
+            }),
+        }
+    }
+
+    /// Returns the configuration of the rate limiter.
+    pub fn config(&self) -> &RpcRateLimitConfig {
+        &self.inner.config
+    }
+
+    /// Returns the key the request with the given extensions is attributed to.
+    ///
+    /// Requests without a client IP share the global quota, so that a client can't reset its
+    /// quota by reconnecting.
+    fn client_key(&self, extensions: &Extensions) -> ClientKey {
+        let identity = extensions.get::<RpcClientIdentity>();
+        let ip = || identity.and_then(|id| id.ip).map(ClientKey::Ip);
+        let key = match self.inner.config.key {
+            RateLimitKey::Ip => ip(),
+            RateLimitKey::Jwt => {
+                identity.and_then(|id| id.jwt_subject.clone()).map(ClientKey::Subject).or_else(ip)
+            }
+            RateLimitKey::Connection => {
+                extensions.get::<ConnectionId>().map(|id| ClientKey::Connection(id.0))
+            }
+            RateLimitKey::Global => None,
+        };
+        key.unwrap_or(ClientKey::Global)
+    }
+
+    /// Admits or rejects a call to the given method.
+    fn try_acquire(&self, key: ClientKey, method: &str, now: Instant) -> Result<(), RateLimited> {
+        let config = &self.inner.config;
+        let metrics = &self.inner.metrics;
+        let cost = config.cost_of(method);
+        let method_limit = config.method_limit(method);
+
+        let mut clients = self.inner.clients.lock();
+        let Some(buckets) = clients.get_or_insert(key, || ClientBuckets {
+            total: TokenBucket::new(config.per_client, now),
+            methods: HashMap::default(),
+        }) else {
+            // can only happen if the map has a zero length limit
+            return Ok(())
+        };
+
+        let mut method_bucket = None;
+        if let Some((idx, limit)) = method_limit {
+            let bucket = buckets.methods.entry(idx).or_insert_with(|| TokenBucket::new(limit, now));
+            // per method quotas are counted in calls
+            if let Err(retry_after) = bucket.check(1, now) {
+                metrics.rejected_method_total.increment(1);
+                return Err(RateLimited { scope: RateLimitScope::Method, retry_after })
+            }
+            method_bucket = Some(idx);
+        }
+
+        if let Err(retry_after) = buckets.total.check(cost, now) {
+            metrics.rejected_client_total.increment(1);
+            return Err(RateLimited { scope: RateLimitScope::Client, retry_after })
+        }
+
+        buckets.total.take(cost);
+        if let Some(bucket) = method_bucket.and_then(|idx| buckets.methods.get_mut(&idx)) {
+            bucket.take(1);
+        }
+
+        metrics.tracked_clients.set(clients.len() as f64);
+        metrics.admitted_total.increment(1);
+        metrics.tokens_consumed_total.increment(cost as u64);
+        Ok(())
+    }
+
+    /// Admits or rejects the given request.
+    pub(crate) fn check_request(&self, req: &Request<'_>) -> Result<(), RateLimited> {
+        self.try_acquire(self.client_key(req.extensions()), req.method_name(), Instant::now())
+    }
+}
+
+impl<S> Layer<S> for RpcRateLimiter {
+    type Service = RpcRateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcRateLimitService { inner, limiter: self.clone() }
+    }
+}
+
+/// Shared state of the [`RpcRateLimiter`].
+#[derive(Debug)]
+struct RpcRateLimiterInner {
+    /// The rate limit configuration.
+    config: RpcRateLimitConfig,
+    /// The buckets of all tracked clients.
+    clients: Mutex<LruMap<ClientKey, ClientBuckets, ByLength>>,
+    /// Rate limiter metrics.
+    metrics: RpcRateLimitMetrics,
+}
+
+/// A [`RpcServiceT`] middleware that enforces per-client quotas.
+#[derive(Debug, Clone)]
+pub struct RpcRateLimitService<S> {
+    /// The inner service being wrapped
+    inner: S,
+    /// The shared rate limiter
+    limiter: RpcRateLimiter,
+}
+
+impl<S> RpcServiceT for RpcRateLimitService<S>
+where
+    S: RpcServiceT<MethodResponse = MethodResponse> + Send + Sync + Clone + 'static,
+{
+    type MethodResponse = S::MethodResponse;
+    type NotificationResponse = S::NotificationResponse;
+    type BatchResponse = S::BatchResponse;
+
+    fn call<'a>(&self, req: Request<'a>) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+        match self.limiter.check_request(&req) {
+            Ok(()) => RateLimitedResponseFuture::Admitted { fut: self.inner.call(req) },
+            Err(err) => RateLimitedResponseFuture::Rejected {
+                response: Some(MethodResponse::error(req.id(), ErrorObjectOwned::from(err))),
+            },
+        }
+    }
+
+    fn batch<'a>(
+        &self,
+        mut req: Batch<'a>,
+    ) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+        // every call of the batch is charged individually, rejected calls are answered with an
+        // error in place
+        for entry in req.iter_mut() {
+            let rejected = match entry {
+                Ok(BatchEntry::Call(call)) => self
+                    .limiter
+                    .check_request(call)
+                    .err()
+                    .map(|err| BatchEntryErr::new(call.id(), ErrorObjectOwned::from(err))),
+                _ => None,
+            };
+            if let Some(err) = rejected {
+                *entry = Err(err);
+            }
+        }
+        self.inner.batch(req)
+    }
+
+    fn notification<'a>(
+        &self,
+        n: Notification<'a>,
+    ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+        self.inner.notification(n)
+    }
+}
+
+/// Response future of the [`RpcRateLimitService`].
+#[pin_project::pin_project(project = RateLimitedResponseFutureProj)]
+pub enum RateLimitedResponseFuture<F> {
+    /// The call was admitted and is forwarded to the inner service.
+    Admitted {
+        /// The inner response future.
+        #[pin]
+        fut: F,
+    },
+    /// The call was rejected.
+    Rejected {
+        /// The error response.
+        response: Option<MethodResponse>,
+    },
+}
+
+impl<F> std::fmt::Debug for RateLimitedResponseFuture<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RateLimitedResponseFuture")
+    }
+}
+
+impl<F: Future<Output = MethodResponse>> Future for RateLimitedResponseFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            RateLimitedResponseFutureProj::Admitted { fut } => fut.poll(cx),
+            RateLimitedResponseFutureProj::Rejected { response } => {
+                Poll::Ready(response.take().expect("polled after completion"))
+            }
+        }
+    }
+}
+
+/// Metrics for the per-client rate limiter.
+#[derive(Metrics, Clone)]
+#[metrics(scope = "rpc_server.rate_limit")]
+struct RpcRateLimitMetrics {
+    /// The number of calls admitted
+    admitted_total: Counter,
+    /// The number of calls rejected because the client exceeded its quota
+    rejected_client_total: Counter,
+    /// The number of calls rejected because the client exceeded a per method quota
+    rejected_method_total: Counter,
+    /// The number of tokens consumed by admitted calls
+    tokens_consumed_total: Counter,
+    /// The number of clients currently tracked
+    tracked_clients: Gauge,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+    use reth_rpc_server_types::rate_limit::{MethodRule, TokenBucketConfig};
+
+    #[test]
+    fn identity_from_headers() {
+        let proxy: IpAddr = "192.168.0.1".parse().unwrap();
+        let client: IpAddr = "10.0.0.2".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        // the client prepended a forged entry, the proxy appended the real client address
+        headers
+            .insert("x-forwarded-for", HeaderValue::from_static("1.1.1.1, 10.0.0.1, 192.168.0.1"));
+        // {"sub":"team-a","iat":0}
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"team-a","iat":0}"#);
+        let token = format!("Bearer eyJhbGciOiJIUzI1NiJ9.{payload}.sig");
+        headers.insert(header::AUTHORIZATION, token.parse().unwrap());
+
+        let identity = RpcClientIdentity::from_headers(&headers, Some(proxy), &[proxy], true);
+        assert_eq!(identity.ip, Some("10.0.0.1".parse().unwrap()));
+        assert_eq!(identity.jwt_subject.as_deref(), Some("team-a"));
+
+        // headers are ignored if the peer is not a trusted proxy and unverified tokens are ignored
+        let identity = RpcClientIdentity::from_headers(&headers, Some(client), &[proxy], false);
+        assert_eq!(identity, RpcClientIdentity { ip: Some(client), jwt_subject: None });
+        let identity = RpcClientIdentity::from_headers(&headers, None, &[proxy], false);
+        assert_eq!(identity, RpcClientIdentity::default());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("::1"));
+        let identity = RpcClientIdentity::from_headers(&headers, Some(proxy), &[proxy], true);
+        assert_eq!(identity.ip, Some("::1".parse().unwrap()));
+        assert_eq!(identity.jwt_subject, None);
+
+        // requests of the proxy itself are attributed to the proxy
+        let identity =
+            RpcClientIdentity::from_headers(&HeaderMap::new(), Some(proxy), &[proxy], true);
+        assert_eq!(identity.ip, Some(proxy));
+    }
+
+    #[test]
+    fn client_key_falls_back_to_global() {
+        let limiter = RpcRateLimiter::new(RpcRateLimitConfig::default());
+        let mut extensions = Extensions::new();
+        // reconnecting must not reset the quota
+        extensions.insert(ConnectionId(7));
+        assert_eq!(limiter.client_key(&extensions), ClientKey::Global);
+
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        extensions.insert(RpcClientIdentity { ip: Some(ip), jwt_subject: None });
+        assert_eq!(limiter.client_key(&extensions), ClientKey::Ip(ip));
+
+        // tokens without a `sub` claim are attributed to the client IP
+        let limiter =
+            RpcRateLimiter::new(RpcRateLimitConfig::default().with_key(RateLimitKey::Jwt));
+        assert_eq!(limiter.client_key(&extensions), ClientKey::Ip(ip));
+        extensions
+            .insert(RpcClientIdentity { ip: Some(ip), jwt_subject: Some("team-a".to_string()) });
+        assert_eq!(limiter.client_key(&extensions), ClientKey::Subject("team-a".to_string()));
+
+        let limiter =
+            RpcRateLimiter::new(RpcRateLimitConfig::default().with_key(RateLimitKey::Connection));
+        assert_eq!(limiter.client_key(&extensions), ClientKey::Connection(7));
+    }
+
+    #[test]
+    fn clients_have_separate_quotas() {
+        let config = RpcRateLimitConfig::default().with_per_client(TokenBucketConfig::new(20, 1));
+        let limiter = RpcRateLimiter::new(config);
+        let now = Instant::now();
+        let a = ClientKey::Subject("a".to_string());
+        let b = ClientKey::Subject("b".to_string());
+
+        // a single trace call drains the bucket of client a
+        assert!(limiter.try_acquire(a.clone(), "trace_block", now).is_ok());
+        let err = limiter.try_acquire(a.clone(), "eth_chainId", now).unwrap_err();
+        assert_eq!(err.scope, RateLimitScope::Client);
+        assert_eq!(err.retry_after, Duration::from_secs(1));
+
+        // client b is unaffected
+        assert!(limiter.try_acquire(b, "trace_block", now).is_ok());
+
+        // and client a recovers over time
+        assert!(limiter.try_acquire(a, "eth_chainId", now + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn method_quota() {
+        let config = RpcRateLimitConfig::default()
+            .with_method_limit(MethodRule::new("eth_call", TokenBucketConfig::new(2, 1)));
+        let limiter = RpcRateLimiter::new(config);
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(ClientKey::Global, "eth_call", now).is_ok());
+        assert!(limiter.try_acquire(ClientKey::Global, "eth_call", now).is_ok());
+        let err = limiter.try_acquire(ClientKey::Global, "eth_call", now).unwrap_err();
+        assert_eq!(err.scope, RateLimitScope::Method);
+
+        // other methods still have quota
+        assert!(limiter.try_acquire(ClientKey::Global, "eth_chainId", now).is_ok());
+    }
+
+    #[test]
+    fn rate_limit_error() {
+        let err = ErrorObjectOwned::from(RateLimited {
+            scope: RateLimitScope::Client,
+            retry_after: Duration::from_millis(1500),
+        });
+        assert_eq!(err.code(), RATE_LIMIT_EXCEEDED_CODE);
+        assert_eq!(err.data().unwrap().get(), r#"{"retryAfter":2}"#);
+
+        let err = ErrorObjectOwned::from(RateLimited {
+            scope: RateLimitScope::Method,
+            retry_after: Duration::MAX,
+        });
+        assert_eq!(err.data().unwrap().get(), "{}");
+    }
+}
@@ -0,0 +1,558 @@
+//! RPC middleware that records sampled calls to rotating files.
+//!
+//! See [`reth_rpc_server_types::recording`] for the format of the recordings.
+
+use jsonrpsee::{
+    core::middleware::{Batch, BatchEntry, Notification},
+    server::middleware::rpc::RpcServiceT,
+    types::Request,
+    BatchResponse, MethodResponse,
+};
+use reth_metrics::{metrics::Counter, Metrics};
+use reth_primitives_traits::FastInstant as Instant;
+use reth_rpc_server_types::recording::{
+    RecordedCall, RecordedTransport, RpcRecorderConfig, RECORDING_FILE_EXTENSION,
+};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, File},
+    future::Future,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::Layer;
+use tracing::{debug, warn};
+
+/// Maximum number of recorded calls buffered per transport before calls are dropped.
+const RECORDER_CHANNEL_CAPACITY: usize = 8192;
+
+/// Records sampled calls, with their responses and latencies, to rotating files.
+///
+/// Every transport is written to its own files by a dedicated thread, so that recording never
+/// blocks the server. If the writer can not keep up, calls are dropped from the recording.
+#[derive(Debug, Clone)]
+pub struct RpcRecorder {
+    inner: Arc<RpcRecorderInner>,
+}
+
+impl RpcRecorder {
+    /// Creates the recording directory and spawns the writer threads.
+    ///
+    /// Existing recordings in the directory are kept, new files continue their sequence.
+    pub fn new(config: RpcRecorderConfig) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let metrics = RpcRecorderMetrics::default();
+        let http = RecordingWriter::spawn(&config, RecordedTransport::Http, metrics.clone())?;
+        let ws = RecordingWriter::spawn(&config, RecordedTransport::Ws, metrics.clone())?;
+        let ipc = RecordingWriter::spawn(&config, RecordedTransport::Ipc, metrics.clone())?;
+        Ok(Self {
+            inner: Arc::new(RpcRecorderInner {
+                config,
+                sampled: AtomicU64::new(0),
+                http,
+                ws,
+                ipc,
+                metrics,
+            }),
+        })
+    }
+
+    /// Returns the configuration of the recorder.
+    pub fn config(&self) -> &RpcRecorderConfig {
+        &self.inner.config
+    }
+
+    /// Returns the layer that records calls of the given transport.
+    pub fn layer(&self, transport: RecordedTransport) -> RpcRecorderLayer {
+        RpcRecorderLayer { recorder: self.clone(), transport }
+    }
+
+    /// Returns true if the next call should be recorded.
+    ///
+    /// Calls are sampled deterministically, e.g. a rate of `0.1` records every tenth call.
+    fn sample(&self) -> bool {
+        let rate = self.inner.config.sample_rate;
+        if rate >= 1.0 {
+            return true
+        }
+        if rate <= 0.0 {
+            return false
+        }
+        let n = self.inner.sampled.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * rate).floor() > (n * rate).floor()
+    }
+
+    /// Sends the call to the writer of its transport.
+    fn record(&self, call: RecordedCall) {
+        let sender = match call.transport {
+            RecordedTransport::Http => &self.inner.http,
+            RecordedTransport::Ws => &self.inner.ws,
+            RecordedTransport::Ipc => &self.inner.ipc,
+        };
+        match sender.try_send(call) {
+            Ok(()) => self.inner.metrics.recorded_calls_total.increment(1),
+            Err(TrySendError::Full(_)) => self.inner.metrics.dropped_calls_total.increment(1),
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+/// Shared state of the [`RpcRecorder`].
+#[derive(Debug)]
+struct RpcRecorderInner {
+    /// The recorder configuration.
+    config: RpcRecorderConfig,
+    /// The number of calls considered for sampling.
+    sampled: AtomicU64,
+    /// Sender to the writer of http calls.
+    http: SyncSender<RecordedCall>,
+    /// Sender to the writer of ws calls.
+    ws: SyncSender<RecordedCall>,
+    /// Sender to the writer of ipc calls.
+    ipc: SyncSender<RecordedCall>,
+    /// Recorder metrics.
+    metrics: RpcRecorderMetrics,
+}
+
+/// A [`Layer`] that records the calls of a single transport.
+#[derive(Debug, Clone)]
+pub struct RpcRecorderLayer {
+    recorder: RpcRecorder,
+    transport: RecordedTransport,
+}
+
+impl<S> Layer<S> for RpcRecorderLayer {
+    type Service = RpcRecorderService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcRecorderService { inner, recorder: self.recorder.clone(), transport: self.transport }
+    }
+}
+
+/// A [`RpcServiceT`] middleware that records sampled calls.
+///
+/// Calls that are part of a batch are sampled and recorded individually, with the latency of the
+/// whole batch.
+#[derive(Debug, Clone)]
+pub struct RpcRecorderService<S> {
+    /// The inner service being wrapped
+    inner: S,
+    /// The shared recorder
+    recorder: RpcRecorder,
+    /// The transport of the calls
+    transport: RecordedTransport,
+}
+
+impl<S> RpcRecorderService<S> {
+    /// Returns the call to record for the request, without its response.
+    fn recorded_call(&self, req: &Request<'_>) -> RecordedCall {
+        RecordedCall {
+            timestamp_us: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_micros() as u64,
+            transport: self.transport,
+            method: req.method_name().to_string(),
+            params: req.params.as_deref().map(ToOwned::to_owned),
+            result: None,
+            error: None,
+            latency_us: 0,
+        }
+    }
+}
+
+impl<S> RpcServiceT for RpcRecorderService<S>
+where
+    S: RpcServiceT<MethodResponse = MethodResponse, BatchResponse: RecordableBatchResponse>
+        + Send
+        + Sync
+        + Clone
+        + 'static,
+{
+    type MethodResponse = S::MethodResponse;
+    type NotificationResponse = S::NotificationResponse;
+    type BatchResponse = S::BatchResponse;
+
+    fn call<'a>(&self, req: Request<'a>) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+        let call = self.recorder.sample().then(|| self.recorded_call(&req));
+        let recorder = self.recorder.clone();
+        let fut = self.inner.call(req);
+        async move {
+            let started = Instant::now();
+            let response = fut.await;
+            if let Some(mut call) = call {
+                call.latency_us = started.elapsed().as_micros() as u64;
+                if recorder.config().record_responses {
+                    (call.result, call.error) = ResponsePayload::parse(response.as_json())
+                        .map(ResponsePayload::into_owned)
+                        .unwrap_or_default();
+                }
+                recorder.record(call);
+            }
+            response
+        }
+    }
+
+    fn batch<'a>(&self, req: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+        // the calls are keyed by their id to match them with their responses
+        let calls = req
+            .iter()
+            .filter_map(|entry| match entry {
+                Ok(BatchEntry::Call(call)) if self.recorder.sample() => {
+                    let id = serde_json::to_string(&call.id()).unwrap_or_default();
+                    Some((id, self.recorded_call(call)))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let recorder = self.recorder.clone();
+        let fut = self.inner.batch(req);
+        async move {
+            let started = Instant::now();
+            let response = fut.await;
+            if !calls.is_empty() {
+                let latency_us = started.elapsed().as_micros() as u64;
+                let mut payloads = response
+                    .responses_json()
+                    .filter(|_| recorder.config().record_responses)
+                    .map(batch_payloads)
+                    .unwrap_or_default();
+                for (id, mut call) in calls {
+                    call.latency_us = latency_us;
+                    (call.result, call.error) = payloads.remove(&id).unwrap_or_default();
+                    recorder.record(call);
+                }
+            }
+            response
+        }
+    }
+
+    fn notification<'a>(
+        &self,
+        n: Notification<'a>,
+    ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+        self.inner.notification(n)
+    }
+}
+
+/// A batch response whose calls can be recorded.
+pub trait RecordableBatchResponse {
+    /// Returns the JSON array of the responses of the batch, if they can be inspected.
+    fn responses_json(&self) -> Option<&RawValue>;
+}
+
+impl RecordableBatchResponse for MethodResponse {
+    fn responses_json(&self) -> Option<&RawValue> {
+        Some(self.as_json())
+    }
+}
+
+/// The ipc server answers the calls of a batch individually, so they are recorded as calls.
+impl RecordableBatchResponse for BatchResponse {
+    fn responses_json(&self) -> Option<&RawValue> {
+        None
+    }
+}
+
+/// The id, result and error of a response.
+#[derive(Deserialize)]
+struct ResponsePayload<'a> {
+    #[serde(borrow, default)]
+    id: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    result: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    error: Option<&'a RawValue>,
+}
+
+impl<'a> ResponsePayload<'a> {
+    /// Parses the response, returns `None` if it is not a response object.
+    fn parse(json: &'a RawValue) -> Option<Self> {
+        serde_json::from_str(json.get()).ok()
+    }
+
+    /// Returns the result and the error of the response.
+    fn into_owned(self) -> (Option<Box<RawValue>>, Option<Box<RawValue>>) {
+        (self.result.map(ToOwned::to_owned), self.error.map(ToOwned::to_owned))
+    }
+}
+
+/// Returns the result and the error of the responses of a batch, by the id of their call.
+fn batch_payloads(
+    json: &RawValue,
+) -> HashMap<String, (Option<Box<RawValue>>, Option<Box<RawValue>>)> {
+    let payloads = serde_json::from_str::<Vec<ResponsePayload<'_>>>(json.get()).unwrap_or_default();
+    payloads
+        .into_iter()
+        .filter_map(|payload| Some((payload.id?.get().to_string(), payload.into_owned())))
+        .collect()
+}
+
+/// Writes the recorded calls of a single transport to rotating files.
+#[derive(Debug)]
+struct RecordingWriter {
+    dir: PathBuf,
+    transport: RecordedTransport,
+    max_file_bytes: u64,
+    max_files: usize,
+    /// The files of this transport, oldest first.
+    files: VecDeque<PathBuf>,
+    /// The sequence number of the next file.
+    next_sequence: u64,
+    /// The file that is currently written to and its size.
+    current: Option<(BufWriter<File>, u64)>,
+    metrics: RpcRecorderMetrics,
+}
+
+impl RecordingWriter {
+    /// Opens the writer and spawns a thread that writes the calls sent to the returned channel.
+    fn spawn(
+        config: &RpcRecorderConfig,
+        transport: RecordedTransport,
+        metrics: RpcRecorderMetrics,
+    ) -> io::Result<SyncSender<RecordedCall>> {
+        let writer = Self::open(config, transport, metrics)?;
+        let (tx, rx) = mpsc::sync_channel(RECORDER_CHANNEL_CAPACITY);
+        std::thread::Builder::new()
+            .name(format!("rpc-recorder-{transport}"))
+            .spawn(move || writer.run(rx))?;
+        Ok(tx)
+    }
+
+    /// Opens the writer, continuing the sequence of existing files.
+    fn open(
+        config: &RpcRecorderConfig,
+        transport: RecordedTransport,
+        metrics: RpcRecorderMetrics,
+    ) -> io::Result<Self> {
+        let mut existing = Vec::new();
+        for entry in fs::read_dir(&config.dir)? {
+            let path = entry?.path();
+            if let Some(sequence) = file_sequence(&path, transport) {
+                existing.push((sequence, path));
+            }
+        }
+        existing.sort_unstable();
+        let next_sequence = existing.last().map(|(sequence, _)| sequence + 1).unwrap_or_default();
+
+        Ok(Self {
+            dir: config.dir.clone(),
+            transport,
+            max_file_bytes: config.max_file_bytes,
+            max_files: config.max_files.max(1),
+            files: existing.into_iter().map(|(_, path)| path).collect(),
+            next_sequence,
+            current: None,
+            metrics,
+        })
+    }
+
+    /// Writes calls until all senders are dropped.
+    fn run(mut self, rx: Receiver<RecordedCall>) {
+        while let Ok(call) = rx.recv() {
+            self.write(&call);
+            // drain everything that is queued before flushing
+            while let Ok(call) = rx.try_recv() {
+                self.write(&call);
+            }
+            if let Some((file, _)) = &mut self.current &&
+                let Err(err) = file.flush()
+            {
+                self.metrics.write_errors_total.increment(1);
+                debug!(target: "rpc::recorder", %err, "Failed to flush recording");
+            }
+        }
+    }
+
+    /// Appends the call to the current file.
+    fn write(&mut self, call: &RecordedCall) {
+        let mut line = match serde_json::to_vec(call) {
+            Ok(line) => line,
+            Err(err) => {
+                debug!(target: "rpc::recorder", %err, "Failed to serialize recorded call");
+                return
+            }
+        };
+        line.push(b'\n');
+        if let Err(err) = self.write_line(&line) {
+            self.metrics.write_errors_total.increment(1);
+            warn!(
+                target: "rpc::recorder",
+                %err,
+                transport = %self.transport,
+                "Failed to write recording"
+            );
+            // start a new file on the next write
+            self.current = None;
+        }
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.current.as_ref().is_none_or(|(_, size)| *size >= self.max_file_bytes) {
+            self.rotate()?;
+        }
+        let (file, size) = self.current.as_mut().expect("file is open");
+        file.write_all(line)?;
+        *size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Starts a new file and removes the oldest files if there are too many.
+    fn rotate(&mut self) -> io::Result<()> {
+        if let Some((mut file, _)) = self.current.take() {
+            file.flush()?;
+            self.metrics.rotated_files_total.increment(1);
+        }
+
+        let path = self.dir.join(format!(
+            "{}-{:06}.{RECORDING_FILE_EXTENSION}",
+            self.transport, self.next_sequence
+        ));
+        self.next_sequence += 1;
+        let file = File::create(&path)?;
+        self.files.push_back(path);
+        self.current = Some((BufWriter::new(file), 0));
+
+        while self.files.len() > self.max_files {
+            let Some(oldest) = self.files.pop_front() else { break };
+            if let Err(err) = fs::remove_file(&oldest) {
+                debug!(
+                    target: "rpc::recorder",
+                    %err,
+                    path = %oldest.display(),
+                    "Failed to remove recording"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the sequence number of the recording file, if it belongs to the transport.
+fn file_sequence(path: &Path, transport: RecordedTransport) -> Option<u64> {
+    if path.extension()? != RECORDING_FILE_EXTENSION {
+        return None
+    }
+    let stem = path.file_stem()?.to_str()?;
+    stem.strip_prefix(&format!("{transport}-"))?.parse().ok()
+}
+
+/// Metrics for the RPC traffic recorder.
+#[derive(Metrics, Clone)]
+#[metrics(scope = "rpc_server.recorder")]
+struct RpcRecorderMetrics {
+    /// The number of calls queued for recording
+    recorded_calls_total: Counter,
+    /// The number of sampled calls dropped because the writer could not keep up
+    dropped_calls_total: Counter,
+    /// The number of failed writes
+    write_errors_total: Counter,
+    /// The number of recording files that were completed
+    rotated_files_total: Counter,
+}
+
+impl std::fmt::Debug for RpcRecorderMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcRecorderMetrics").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(method: &str) -> RecordedCall {
+        RecordedCall {
+            timestamp_us: 0,
+            transport: RecordedTransport::Http,
+            method: method.to_string(),
+            params: None,
+            result: Some(RawValue::from_string("\"0x1\"".to_string()).unwrap()),
+            error: None,
+            latency_us: 1,
+        }
+    }
+
+    #[test]
+    fn samples_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder =
+            RpcRecorder::new(RpcRecorderConfig::new(dir.path()).with_sample_rate(0.25)).unwrap();
+        let sampled = (0..100).filter(|_| recorder.sample()).count();
+        assert_eq!(sampled, 25);
+    }
+
+    #[test]
+    fn rotates_files() {
+        let dir = tempfile::tempdir().unwrap();
+        // a file of a previous run
+        fs::write(dir.path().join("http-000003.jsonl"), "").unwrap();
+
+        let config = RpcRecorderConfig::new(dir.path()).with_max_file_bytes(1).with_max_files(2);
+        let mut writer =
+            RecordingWriter::open(&config, RecordedTransport::Http, Default::default()).unwrap();
+        assert_eq!(writer.next_sequence, 4);
+
+        // every call starts a new file
+        for method in ["eth_chainId", "eth_blockNumber", "eth_gasPrice"] {
+            writer.write(&call(method));
+        }
+        writer.rotate().unwrap();
+
+        let mut files = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, vec!["http-000006.jsonl", "http-000007.jsonl"]);
+
+        let recorded = fs::read_to_string(dir.path().join("http-000006.jsonl")).unwrap();
+        let recorded: RecordedCall = serde_json::from_str(recorded.trim()).unwrap();
+        assert_eq!(recorded.method, "eth_gasPrice");
+    }
+
+    #[test]
+    fn batch_payloads_by_id() {
+        let json = serde_json::json!([
+            {"jsonrpc": "2.0", "id": "a", "result": "0x1"},
+            {"jsonrpc": "2.0", "id": 2, "error": {"code": -32601, "message": "Method not found"}},
+            {"jsonrpc": "2.0", "id": null, "error": {"code": -32600, "message": "Invalid request"}},
+        ]);
+        let json = RawValue::from_string(json.to_string()).unwrap();
+        // the invalid request has no id to match it with a call
+        let mut payloads = batch_payloads(&json);
+        assert_eq!(payloads.len(), 2);
+
+        let (result, error) = payloads.remove(r#""a""#).unwrap();
+        assert_eq!(result.unwrap().get(), r#""0x1""#);
+        assert!(error.is_none());
+        let (result, error) = payloads.remove("2").unwrap();
+        assert!(result.is_none());
+        assert_eq!(error.unwrap().get(), r#"{"code":-32601,"message":"Method not found"}"#);
+
+        // a response to the whole batch has no entries
+        let json = RawValue::from_string(r#"{"jsonrpc":"2.0","id":null}"#.to_string()).unwrap();
+        assert!(batch_payloads(&json).is_empty());
+    }
+
+    #[test]
+    fn file_sequence_of_transport() {
+        assert_eq!(
+            file_sequence(Path::new("/tmp/http-000012.jsonl"), RecordedTransport::Http),
+            Some(12)
+        );
+        assert_eq!(file_sequence(Path::new("/tmp/ws-000012.jsonl"), RecordedTransport::Http), None);
+        assert_eq!(
+            file_sequence(Path::new("/tmp/http-000012.json"), RecordedTransport::Http),
+            None
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Classification and canonicalization of cacheable requests.
+
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::{BlockNumber, B256};
+use alloy_rpc_types_eth::{Filter, FilterBlockOption};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{value::RawValue, Map, Value};
+
+/// How the first parameter of a cacheable method refers to the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockParam {
+    /// A [`BlockNumberOrTag`].
+    Number,
+    /// A block hash.
+    Hash,
+    /// A [`BlockId`].
+    Id,
+    /// A transaction hash.
+    Transaction,
+    /// A log [`Filter`].
+    Filter,
+}
+
+/// Methods whose responses only depend on the block their first parameter refers to.
+const CACHEABLE_METHODS: &[(&str, BlockParam)] = &[
+    ("eth_getBlockByNumber", BlockParam::Number),
+    ("eth_getBlockByHash", BlockParam::Hash),
+    ("eth_getBlockReceipts", BlockParam::Id),
+    ("eth_getBlockTransactionCountByNumber", BlockParam::Number),
+    ("eth_getBlockTransactionCountByHash", BlockParam::Hash),
+    ("eth_getTransactionByHash", BlockParam::Transaction),
+    ("eth_getTransactionReceipt", BlockParam::Transaction),
+    ("eth_getLogs", BlockParam::Filter),
+    ("debug_traceBlockByNumber", BlockParam::Number),
+    ("debug_traceBlockByHash", BlockParam::Hash),
+    ("debug_traceTransaction", BlockParam::Transaction),
+    ("trace_block", BlockParam::Id),
+    ("trace_replayBlockTransactions", BlockParam::Id),
+    ("trace_transaction", BlockParam::Transaction),
+    ("trace_replayTransaction", BlockParam::Transaction),
+];
+
+/// The chain data a request depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CacheTarget {
+    /// The block with the given number, or a range of blocks ending at it.
+    Block(BlockNumber),
+    /// The block with the given hash.
+    BlockHash(B256),
+    /// The block that includes the transaction with the given hash.
+    Transaction(B256),
+}
+
+/// A request that can be cached if its target is finalized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CacheableRequest {
+    /// The cache key, the method name and the canonicalized parameters.
+    pub(crate) key: String,
+    /// The chain data the response depends on.
+    pub(crate) target: CacheTarget,
+}
+
+impl CacheableRequest {
+    /// Returns the cacheable request for the given call, if the method is cacheable and its
+    /// parameters refer to a fixed block.
+    ///
+    /// Calls that refer to blocks by tag, e.g. `latest` or `finalized`, are never cacheable
+    /// because the block they resolve to changes over time.
+    pub(crate) fn new(method: &str, params: Option<&RawValue>) -> Option<Self> {
+        let (_, param) = CACHEABLE_METHODS.iter().find(|(name, _)| *name == method)?;
+
+        // only positional parameters are supported
+        let mut params: Vec<Value> = match params {
+            Some(params) => serde_json::from_str(params.get()).ok()?,
+            None => return None,
+        };
+        // omitted optional parameters are equivalent to `null`
+        while params.last().is_some_and(Value::is_null) {
+            params.pop();
+        }
+        let first = params.first_mut()?;
+
+        let target = match param {
+            BlockParam::Number => {
+                let number = canonical::<BlockNumberOrTag>(first)?;
+                CacheTarget::Block(fixed_number(number)?)
+            }
+            BlockParam::Hash | BlockParam::Transaction => {
+                let hash = canonical::<B256>(first)?;
+                if *param == BlockParam::Hash {
+                    CacheTarget::BlockHash(hash)
+                } else {
+                    CacheTarget::Transaction(hash)
+                }
+            }
+            BlockParam::Id => match canonical::<BlockId>(first)? {
+                BlockId::Hash(hash) => CacheTarget::BlockHash(hash.block_hash),
+                BlockId::Number(number) => CacheTarget::Block(fixed_number(number)?),
+            },
+            BlockParam::Filter => match canonical::<Filter>(first)?.block_option {
+                FilterBlockOption::AtBlockHash(hash) => CacheTarget::BlockHash(hash),
+                FilterBlockOption::Range { from_block, to_block } => {
+                    let from = fixed_number(from_block?)?;
+                    let to = fixed_number(to_block?)?;
+                    (from <= to).then_some(CacheTarget::Block(to))?
+                }
+            },
+        };
+
+        let params = sorted(Value::Array(params));
+        Some(Self { key: format!("{method}:{params}"), target })
+    }
+}
+
+/// Returns the number of the block, if it does not depend on the state of the chain.
+const fn fixed_number(number: BlockNumberOrTag) -> Option<BlockNumber> {
+    match number {
+        BlockNumberOrTag::Number(number) => Some(number),
+        BlockNumberOrTag::Earliest => Some(0),
+        _ => None,
+    }
+}
+
+/// Parses the parameter as `T` and replaces it with the canonical encoding of `T`.
+fn canonical<T: DeserializeOwned + Serialize>(param: &mut Value) -> Option<T> {
+    let value = T::deserialize(&*param).ok()?;
+    *param = serde_json::to_value(&value).ok()?;
+    Some(value)
+}
+
+/// Recursively sorts the keys of all objects of the value.
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.into_iter().map(sorted).collect()),
+        Value::Object(map) => {
+            let mut entries = map.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(entries.into_iter().map(|(k, v)| (k, sorted(v))).collect::<Map<_, _>>())
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    fn request(method: &str, params: &str) -> Option<CacheableRequest> {
+        let params = RawValue::from_string(params.to_string()).unwrap();
+        CacheableRequest::new(method, Some(&params))
+    }
+
+    #[test]
+    fn canonicalizes_params() {
+        let a = request("eth_getBlockByNumber", r#"["0x10", true]"#).unwrap();
+        let b = request("eth_getBlockByNumber", r#"["0x0010",true]"#).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.target, CacheTarget::Block(16));
+        assert_eq!(a.key, r#"eth_getBlockByNumber:["0x10",true]"#);
+
+        // different parameters are different entries
+        let c = request("eth_getBlockByNumber", r#"["0x10", false]"#).unwrap();
+        assert_ne!(a.key, c.key);
+
+        // trailing nulls are ignored and objects are sorted
+        let hash = "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add";
+        let a = request(
+            "debug_traceTransaction",
+            &format!(r#"["{hash}", {{"tracer":"callTracer","timeout":"5s"}}, null]"#),
+        )
+        .unwrap();
+        let upper = format!("0x{}", hash[2..].to_uppercase());
+        let b = request(
+            "debug_traceTransaction",
+            &format!(r#"["{upper}", {{"timeout":"5s","tracer":"callTracer"}}]"#),
+        )
+        .unwrap();
+        assert_eq!(a, b);
+        assert_eq!(
+            a.target,
+            CacheTarget::Transaction(b256!(
+                "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add"
+            ))
+        );
+    }
+
+    #[test]
+    fn tags_are_not_cacheable() {
+        assert!(request("eth_getBlockByNumber", r#"["latest", true]"#).is_none());
+        assert!(request("eth_getBlockByNumber", r#"["finalized", true]"#).is_none());
+        assert!(request("trace_block", r#"["pending"]"#).is_none());
+        assert!(request("eth_getLogs", r#"[{"fromBlock":"0x1"}]"#).is_none());
+        assert!(request("eth_getLogs", r#"[{"fromBlock":"0x2","toBlock":"0x1"}]"#).is_none());
+        assert_eq!(
+            request("eth_getLogs", r#"[{"fromBlock":"earliest","toBlock":"0x5"}]"#).unwrap().target,
+            CacheTarget::Block(5)
+        );
+    }
+
+    #[test]
+    fn other_methods_are_not_cacheable() {
+        assert!(request("eth_call", r#"[{}, "0x1"]"#).is_none());
+        assert!(request(
+            "eth_getBalance",
+            r#"["0x0000000000000000000000000000000000000000", "0x1"]"#
+        )
+        .is_none());
+        // named parameters are not supported
+        assert!(request("trace_block", r#"{"block":"0x1"}"#).is_none());
+    }
+}
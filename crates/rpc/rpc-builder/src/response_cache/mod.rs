@@ -0,0 +1,354 @@
+//! [`jsonrpsee`] middleware that caches responses of calls on finalized chain data.
+//!
+//! Blocks, receipts, logs and traces below the finalized block can never change, so the results
+//! of calls that only depend on such data can be served from a cache instead of being recomputed.
+//!
+//! A call is cached if:
+//!  - the method is one of the supported methods, e.g. `eth_getBlockByNumber`, `eth_getLogs`,
+//!    `debug_traceTransaction` or `trace_block`.
+//!  - the block the call refers to, by number, hash or transaction hash, is at or below the
+//!    finalized block. Calls that refer to a block by tag, such as `latest`, are never cached.
+//!  - the call succeeded with a non-null result.
+//!
+//! Cache keys are built from the method name and the canonicalized parameters, so that
+//! equivalent requests, e.g. with differently formatted numbers, share an entry.
+
+mod key;
+mod store;
+
+use crate::response_cache::{
+    key::{CacheTarget, CacheableRequest},
+    store::ResponseStore,
+};
+use alloy_primitives::{BlockNumber, B256};
+use jsonrpsee::{
+    core::middleware::{Batch, Notification},
+    server::middleware::rpc::RpcServiceT,
+    types::Request,
+    MethodResponse, ResponsePayload,
+};
+use reth_rpc_server_types::response_cache::RpcResponseCacheConfig;
+use reth_storage_api::{BlockIdReader, BlockReader};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{future::Future, io, sync::Arc};
+use tower::Layer;
+
+/// Resolves the blocks that requests refer to, to determine whether their responses can be
+/// cached.
+pub trait FinalizedBlockOracle: Send + Sync + 'static {
+    /// Returns the number of the finalized block.
+    fn finalized_block_number(&self) -> Option<BlockNumber>;
+
+    /// Returns the number of the canonical block with the given hash.
+    fn canonical_block_number(&self, hash: B256) -> Option<BlockNumber>;
+
+    /// Returns the number of the canonical block that includes the transaction with the given
+    /// hash.
+    fn transaction_block_number(&self, hash: B256) -> Option<BlockNumber>;
+}
+
+impl<P> FinalizedBlockOracle for P
+where
+    P: BlockIdReader + BlockReader + 'static,
+{
+    fn finalized_block_number(&self) -> Option<BlockNumber> {
+        BlockIdReader::finalized_block_number(self).ok().flatten()
+    }
+
+    fn canonical_block_number(&self, hash: B256) -> Option<BlockNumber> {
+        self.block_number(hash).ok().flatten()
+    }
+
+    fn transaction_block_number(&self, hash: B256) -> Option<BlockNumber> {
+        let id = self.transaction_id(hash).ok()??;
+        self.block_by_transaction_id(id).ok().flatten()
+    }
+}
+
+/// RPC middleware that caches responses of calls on finalized chain data.
+///
+/// The cache is shared by all transports it is installed on.
+///
+/// Note: only individual calls are cached, calls that are part of a batch are always executed.
+#[derive(Clone)]
+pub struct RpcResponseCache {
+    inner: Arc<RpcResponseCacheInner>,
+}
+
+impl RpcResponseCache {
+    /// Creates a new response cache with the given configuration.
+    ///
+    /// If the disk tier is enabled, this removes all entries of previous runs from the cache
+    /// directory.
+    pub fn new(
+        config: RpcResponseCacheConfig,
+        oracle: impl FinalizedBlockOracle,
+    ) -> io::Result<Self> {
+        let store = ResponseStore::new(&config)?;
+        Ok(Self {
+            inner: Arc::new(RpcResponseCacheInner { config, oracle: Box::new(oracle), store }),
+        })
+    }
+
+    /// Returns the configuration of the cache.
+    pub fn config(&self) -> &RpcResponseCacheConfig {
+        &self.inner.config
+    }
+
+    /// Returns a layer that installs the cache on a server with the given maximum response size.
+    ///
+    /// Cached results are checked against the limit of the server they are served from, which
+    /// can be lower than the limit of the server that computed them.
+    pub fn layer(&self, max_response_size: u32) -> RpcResponseCacheLayer {
+        RpcResponseCacheLayer { cache: self.clone(), max_response_size: max_response_size as usize }
+    }
+
+    /// Returns the cacheable request for the given call, if its response depends on finalized
+    /// chain data only.
+    fn cacheable(&self, req: &Request<'_>) -> Option<CacheableRequest> {
+        let request = CacheableRequest::new(req.method_name(), req.params.as_deref())?;
+        let oracle = &self.inner.oracle;
+        let number = match request.target {
+            CacheTarget::Block(number) => number,
+            CacheTarget::BlockHash(hash) => oracle.canonical_block_number(hash)?,
+            CacheTarget::Transaction(hash) => oracle.transaction_block_number(hash)?,
+        };
+        (number <= oracle.finalized_block_number()?).then_some(request)
+    }
+
+    /// Caches the result of the response, if the call succeeded.
+    fn cache_response(&self, key: String, response: &MethodResponse) {
+        #[derive(Deserialize)]
+        struct Success<'a> {
+            #[serde(borrow)]
+            result: &'a RawValue,
+        }
+
+        if !response.is_success() {
+            return
+        }
+        let Ok(Success { result }) = serde_json::from_str(response.as_json().get()) else { return };
+        // not found, this can happen on pruned nodes
+        if result.get() == "null" {
+            return
+        }
+        if let Ok(result) = RawValue::from_string(result.get().to_string()) {
+            self.inner.store.insert(key, Arc::from(result));
+        }
+    }
+}
+
+impl std::fmt::Debug for RpcResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcResponseCache").field("config", self.config()).finish_non_exhaustive()
+    }
+}
+
+/// A [`Layer`] that installs a [`RpcResponseCache`] on a server.
+///
+/// See [`RpcResponseCache::layer`].
+#[derive(Debug, Clone)]
+pub struct RpcResponseCacheLayer {
+    /// The shared cache
+    cache: RpcResponseCache,
+    /// The maximum response size of the server
+    max_response_size: usize,
+}
+
+impl<S> Layer<S> for RpcResponseCacheLayer {
+    type Service = RpcResponseCacheService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcResponseCacheService {
+            inner,
+            cache: self.cache.clone(),
+            max_response_size: self.max_response_size,
+        }
+    }
+}
+
+/// Shared state of the [`RpcResponseCache`].
+struct RpcResponseCacheInner {
+    /// The cache configuration.
+    config: RpcResponseCacheConfig,
+    /// Resolves the blocks calls refer to.
+    oracle: Box<dyn FinalizedBlockOracle>,
+    /// The cached results.
+    store: ResponseStore,
+}
+
+/// A [`RpcServiceT`] middleware that answers calls on finalized chain data from the cache.
+#[derive(Debug, Clone)]
+pub struct RpcResponseCacheService<S> {
+    /// The inner service being wrapped
+    inner: S,
+    /// The shared cache
+    cache: RpcResponseCache,
+    /// The maximum response size of the server
+    max_response_size: usize,
+}
+
+impl<S> RpcServiceT for RpcResponseCacheService<S>
+where
+    S: RpcServiceT<MethodResponse = MethodResponse> + Send + Sync + Clone + 'static,
+{
+    type MethodResponse = S::MethodResponse;
+    type NotificationResponse = S::NotificationResponse;
+    type BatchResponse = S::BatchResponse;
+
+    fn call<'a>(&self, req: Request<'a>) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+        let inner = self.inner.clone();
+        let cache = self.cache.clone();
+        let max_response_size = self.max_response_size;
+        async move {
+            let Some(request) = cache.cacheable(&req) else { return inner.call(req).await };
+
+            if let Some(result) = cache.inner.store.get(&request.key).await {
+                return MethodResponse::response(
+                    req.id(),
+                    ResponsePayload::success(&*result),
+                    max_response_size,
+                )
+            }
+
+            let response = inner.call(req).await;
+            cache.cache_response(request.key, &response);
+            response
+        }
+    }
+
+    fn batch<'a>(&self, req: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+        self.inner.batch(req)
+    }
+
+    fn notification<'a>(
+        &self,
+        n: Notification<'a>,
+    ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+        self.inner.notification(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::types::Id;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A chain with 100 blocks, of which the first 64 are finalized, and one transaction per
+    /// block.
+    struct TestOracle;
+
+    impl FinalizedBlockOracle for TestOracle {
+        fn finalized_block_number(&self) -> Option<BlockNumber> {
+            Some(64)
+        }
+
+        fn canonical_block_number(&self, hash: B256) -> Option<BlockNumber> {
+            let number = u64::from_be_bytes(hash[24..].try_into().unwrap());
+            (number <= 100).then_some(number)
+        }
+
+        fn transaction_block_number(&self, hash: B256) -> Option<BlockNumber> {
+            self.canonical_block_number(hash)
+        }
+    }
+
+    /// A service that counts its calls.
+    #[derive(Clone, Default)]
+    struct CountingService {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl RpcServiceT for CountingService {
+        type MethodResponse = MethodResponse;
+        type NotificationResponse = MethodResponse;
+        type BatchResponse = MethodResponse;
+
+        fn call<'a>(
+            &self,
+            req: Request<'a>,
+        ) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst);
+            std::future::ready(MethodResponse::response(
+                req.id(),
+                ResponsePayload::success(calls),
+                usize::MAX,
+            ))
+        }
+
+        fn batch<'a>(&self, _: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+            std::future::ready(MethodResponse::notification())
+        }
+
+        fn notification<'a>(
+            &self,
+            _: Notification<'a>,
+        ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+            std::future::ready(MethodResponse::notification())
+        }
+    }
+
+    fn request(id: u64, method: &'static str, params: &str) -> Request<'static> {
+        let params = RawValue::from_string(params.to_string()).unwrap();
+        Request::owned(method.into(), Some(params), Id::Number(id))
+    }
+
+    #[tokio::test]
+    async fn caches_finalized_calls() {
+        let cache = RpcResponseCache::new(RpcResponseCacheConfig::default(), TestOracle).unwrap();
+        let inner = CountingService::default();
+        let service = cache.layer(u32::MAX).layer(inner.clone());
+
+        // finalized block by number
+        let res = service.call(request(1, "eth_getBlockByNumber", r#"["0x10", true]"#)).await;
+        assert_eq!(res.as_json().get(), r#"{"jsonrpc":"2.0","id":1,"result":0}"#);
+        let res = service.call(request(2, "eth_getBlockByNumber", r#"["0x010",true]"#)).await;
+        assert_eq!(res.as_json().get(), r#"{"jsonrpc":"2.0","id":2,"result":0}"#);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        // finalized transaction
+        let tx = format!(r#"["{}"]"#, B256::with_last_byte(3));
+        service.call(request(3, "debug_traceTransaction", &tx)).await;
+        let res = service.call(request(4, "debug_traceTransaction", &tx)).await;
+        assert_eq!(res.as_json().get(), r#"{"jsonrpc":"2.0","id":4,"result":1}"#);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_cache_unfinalized_calls() {
+        let cache = RpcResponseCache::new(RpcResponseCacheConfig::default(), TestOracle).unwrap();
+        let inner = CountingService::default();
+        let service = cache.layer(u32::MAX).layer(inner.clone());
+
+        let block = format!(r#"["{}", true]"#, B256::with_last_byte(65));
+        let unknown = format!(r#"["{}"]"#, B256::with_last_byte(200));
+        for (method, params) in [
+            ("eth_getBlockByNumber", r#"["0x41", true]"#),
+            ("eth_getBlockByNumber", r#"["latest", true]"#),
+            ("eth_getBlockByHash", block.as_str()),
+            ("eth_getTransactionByHash", unknown.as_str()),
+            ("eth_getLogs", r#"[{"fromBlock":"0x1","toBlock":"0x41"}]"#),
+            ("eth_blockNumber", "[]"),
+        ] {
+            service.call(request(1, method, params)).await;
+            service.call(request(1, method, params)).await;
+        }
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 12);
+    }
+
+    #[tokio::test]
+    async fn cached_responses_respect_response_size_limit() {
+        let cache = RpcResponseCache::new(RpcResponseCacheConfig::default(), TestOracle).unwrap();
+        let inner = CountingService::default();
+        let service = cache.layer(u32::MAX).layer(inner.clone());
+        let limited = cache.layer(10).layer(inner.clone());
+
+        let res = service.call(request(1, "eth_getBlockByNumber", r#"["0x10", true]"#)).await;
+        assert!(res.is_success());
+        let res = limited.call(request(2, "eth_getBlockByNumber", r#"["0x10", true]"#)).await;
+        assert!(res.is_error());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,338 @@
+//! Memory and disk tiers of the response cache.
+
+use alloy_primitives::{hex, keccak256, B256};
+use parking_lot::Mutex;
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_rpc_server_types::response_cache::{ResponseCacheDiskConfig, RpcResponseCacheConfig};
+use schnellru::{LruMap, Unlimited};
+use serde_json::value::RawValue;
+use std::{fs, io, path::PathBuf, sync::Arc};
+use tracing::{debug, trace};
+
+/// The file name prefix of cached responses on disk.
+const DISK_ENTRY_PREFIX: &str = "rpc-response-";
+
+/// The file extension of cached responses on disk.
+const DISK_ENTRY_EXTENSION: &str = "json";
+
+/// Entries larger than this fraction of a tier's budget are not stored in that tier.
+const MAX_ENTRY_FRACTION: u64 = 4;
+
+/// Stores the results of cached calls, keyed by the canonical request.
+#[derive(Debug)]
+pub(crate) struct ResponseStore {
+    /// Most recently used results.
+    memory: Mutex<MemoryTier>,
+    /// Results that were evicted from memory, or are too large to be kept in memory.
+    disk: Option<Arc<DiskTier>>,
+    /// Cache metrics.
+    metrics: ResponseCacheMetrics,
+}
+
+impl ResponseStore {
+    /// Creates a new store, this clears the disk tier if enabled.
+    pub(crate) fn new(config: &RpcResponseCacheConfig) -> io::Result<Self> {
+        let metrics = ResponseCacheMetrics::default();
+        let disk = config
+            .disk
+            .as_ref()
+            .map(|disk| DiskTier::open(disk, metrics.clone()).map(Arc::new))
+            .transpose()?;
+        Ok(Self {
+            memory: Mutex::new(MemoryTier {
+                entries: LruMap::new(Unlimited),
+                bytes: 0,
+                max_bytes: config.max_memory_bytes,
+            }),
+            disk,
+            metrics,
+        })
+    }
+
+    /// Returns the cached result of the request with the given key.
+    pub(crate) async fn get(&self, key: &str) -> Option<Arc<RawValue>> {
+        if let Some(result) = self.memory.lock().entries.get(key).cloned() {
+            self.metrics.memory_hits_total.increment(1);
+            return Some(result)
+        }
+
+        if let Some(disk) = self.disk.clone() {
+            let id = keccak256(key);
+            let result = tokio::task::spawn_blocking(move || disk.read(&id)).await.ok().flatten();
+            if let Some(result) = result {
+                self.metrics.disk_hits_total.increment(1);
+                self.insert_memory(key.to_string(), result.clone());
+                return Some(result)
+            }
+        }
+
+        self.metrics.misses_total.increment(1);
+        None
+    }
+
+    /// Caches the result of the request with the given key.
+    pub(crate) fn insert(&self, key: String, result: Arc<RawValue>) {
+        if entry_size(&key, &result) <= self.memory.lock().max_bytes / MAX_ENTRY_FRACTION {
+            self.insert_memory(key, result);
+        } else if let Some(disk) = &self.disk {
+            disk.spawn_write(vec![(key, result)]);
+        }
+    }
+
+    /// Inserts the result into the memory tier, entries that are evicted are moved to disk.
+    fn insert_memory(&self, key: String, result: Arc<RawValue>) {
+        let evicted = {
+            let mut memory = self.memory.lock();
+            let evicted = memory.insert(key, result);
+            self.metrics.memory_bytes.set(memory.bytes as f64);
+            self.metrics.memory_entries.set(memory.entries.len() as f64);
+            evicted
+        };
+        if evicted.is_empty() {
+            return
+        }
+        self.metrics.memory_evictions_total.increment(evicted.len() as u64);
+        if let Some(disk) = &self.disk {
+            disk.spawn_write(evicted);
+        }
+    }
+}
+
+/// Returns the number of bytes an entry accounts for.
+fn entry_size(key: &str, result: &RawValue) -> u64 {
+    (key.len() + result.get().len()) as u64
+}
+
+/// In-memory LRU of results, bounded by their total size.
+#[derive(Debug)]
+struct MemoryTier {
+    entries: LruMap<String, Arc<RawValue>, Unlimited>,
+    bytes: u64,
+    max_bytes: u64,
+}
+
+impl MemoryTier {
+    /// Inserts the entry and returns the entries that were evicted to stay within the budget.
+    fn insert(&mut self, key: String, result: Arc<RawValue>) -> Vec<(String, Arc<RawValue>)> {
+        let size = entry_size(&key, &result);
+        if let Some(previous) = self.entries.remove(key.as_str()) {
+            self.bytes -= entry_size(&key, &previous);
+        }
+        self.entries.insert(key, result);
+        self.bytes += size;
+
+        let mut evicted = Vec::new();
+        while self.bytes > self.max_bytes {
+            let Some((key, result)) = self.entries.pop_oldest() else { break };
+            self.bytes -= entry_size(&key, &result);
+            evicted.push((key, result));
+        }
+        evicted
+    }
+}
+
+/// On-disk LRU of results, bounded by their total size.
+///
+/// Every result is stored in its own file, named after the hash of its key, see
+/// [`DiskTier::path`].
+#[derive(Debug)]
+struct DiskTier {
+    dir: PathBuf,
+    max_bytes: u64,
+    index: Mutex<DiskIndex>,
+    metrics: ResponseCacheMetrics,
+}
+
+/// The entries that are currently stored on disk, with their size.
+#[derive(Debug)]
+struct DiskIndex {
+    entries: LruMap<B256, u64, Unlimited>,
+    bytes: u64,
+}
+
+impl DiskTier {
+    /// Opens the disk tier and removes all entries of previous runs.
+    ///
+    /// Other files in the directory are left untouched.
+    fn open(config: &ResponseCacheDiskConfig, metrics: ResponseCacheMetrics) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        for entry in fs::read_dir(&config.dir)? {
+            let entry = entry?;
+            if entry.file_name().to_str().is_some_and(is_entry_file_name) &&
+                entry.file_type()?.is_file()
+            {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        debug!(target: "rpc::response_cache", dir = %config.dir.display(), "Opened disk cache");
+        Ok(Self {
+            dir: config.dir.clone(),
+            max_bytes: config.max_bytes,
+            index: Mutex::new(DiskIndex { entries: LruMap::new(Unlimited), bytes: 0 }),
+            metrics,
+        })
+    }
+
+    /// Returns the path of the entry.
+    fn path(&self, id: &B256) -> PathBuf {
+        self.dir.join(format!("{DISK_ENTRY_PREFIX}{}.{DISK_ENTRY_EXTENSION}", hex::encode(id)))
+    }
+
+    /// Reads the entry from disk.
+    fn read(&self, id: &B256) -> Option<Arc<RawValue>> {
+        // touch the entry so that it is evicted last
+        self.index.lock().entries.get(id)?;
+        let result = fs::read_to_string(self.path(id)).ok()?;
+        RawValue::from_string(result).ok().map(Arc::from)
+    }
+
+    /// Writes the entries to disk on a blocking task.
+    fn spawn_write(self: &Arc<Self>, entries: Vec<(String, Arc<RawValue>)>) {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            for (key, result) in entries {
+                this.write(&key, &result);
+            }
+        });
+    }
+
+    /// Writes the entry to disk and evicts the least recently used entries to stay within the
+    /// budget.
+    fn write(&self, key: &str, result: &RawValue) {
+        let size = entry_size(key, result);
+        if size > self.max_bytes / MAX_ENTRY_FRACTION {
+            return
+        }
+        let id = keccak256(key);
+        if self.index.lock().entries.peek(&id).is_some() {
+            return
+        }
+        if let Err(err) = fs::write(self.path(&id), result.get()) {
+            debug!(target: "rpc::response_cache", %err, "Failed to write response to disk");
+            return
+        }
+
+        let evicted = {
+            let mut index = self.index.lock();
+            index.entries.insert(id, size);
+            index.bytes += size;
+            let mut evicted = Vec::new();
+            while index.bytes > self.max_bytes {
+                let Some((id, size)) = index.entries.pop_oldest() else { break };
+                index.bytes -= size;
+                evicted.push(id);
+            }
+            self.metrics.disk_bytes.set(index.bytes as f64);
+            self.metrics.disk_entries.set(index.entries.len() as f64);
+            evicted
+        };
+
+        self.metrics.disk_evictions_total.increment(evicted.len() as u64);
+        for id in evicted {
+            if let Err(err) = fs::remove_file(self.path(&id)) {
+                trace!(target: "rpc::response_cache", %err, "Failed to remove evicted response");
+            }
+        }
+    }
+}
+
+/// Metrics for the RPC response cache.
+#[derive(Metrics, Clone)]
+#[metrics(scope = "rpc_server.response_cache")]
+struct ResponseCacheMetrics {
+    /// The number of calls answered from the memory tier
+    memory_hits_total: Counter,
+    /// The number of calls answered from the disk tier
+    disk_hits_total: Counter,
+    /// The number of cacheable calls that were not cached yet
+    misses_total: Counter,
+    /// The number of entries evicted from memory
+    memory_evictions_total: Counter,
+    /// The number of entries evicted from disk
+    disk_evictions_total: Counter,
+    /// The number of bytes cached in memory
+    memory_bytes: Gauge,
+    /// The number of entries cached in memory
+    memory_entries: Gauge,
+    /// The number of bytes cached on disk
+    disk_bytes: Gauge,
+    /// The number of entries cached on disk
+    disk_entries: Gauge,
+}
+
+impl std::fmt::Debug for ResponseCacheMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCacheMetrics").finish_non_exhaustive()
+    }
+}
+
+/// Returns `true` if the file name is one of a disk tier entry, see [`DiskTier::path`].
+fn is_entry_file_name(name: &str) -> bool {
+    name.strip_prefix(DISK_ENTRY_PREFIX)
+        .and_then(|name| name.strip_suffix(DISK_ENTRY_EXTENSION))
+        .and_then(|name| name.strip_suffix('.'))
+        .is_some_and(|id| id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(json: &str) -> Arc<RawValue> {
+        Arc::from(RawValue::from_string(json.to_string()).unwrap())
+    }
+
+    #[test]
+    fn memory_tier_evicts_oldest() {
+        let mut memory = MemoryTier { entries: LruMap::new(Unlimited), bytes: 0, max_bytes: 10 };
+        assert!(memory.insert("a".to_string(), result("1234")).is_empty());
+        assert!(memory.insert("b".to_string(), result("1234")).is_empty());
+        assert_eq!(memory.bytes, 10);
+
+        // touch a, so that b is evicted
+        memory.entries.get("a");
+        let evicted = memory.insert("c".to_string(), result("12")).into_iter().map(|(k, _)| k);
+        assert_eq!(evicted.collect::<Vec<_>>(), vec!["b".to_string()]);
+        assert_eq!(memory.bytes, 8);
+    }
+
+    #[tokio::test]
+    async fn spills_to_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        // a stale entry of a previous run, and an unrelated file
+        let stale =
+            format!("{DISK_ENTRY_PREFIX}{}.{DISK_ENTRY_EXTENSION}", hex::encode(B256::ZERO));
+        fs::write(dir.path().join(&stale), "1").unwrap();
+        fs::write(dir.path().join("notes.json"), "1").unwrap();
+
+        let config = RpcResponseCacheConfig::default()
+            .with_max_memory_bytes(40)
+            .with_disk(Some(ResponseCacheDiskConfig::new(dir.path()).with_max_bytes(1024)));
+        let store = ResponseStore::new(&config).unwrap();
+        assert!(!dir.path().join(&stale).exists());
+        assert!(dir.path().join("notes.json").exists());
+
+        store.insert("a".to_string(), result(r#""first""#));
+        assert_eq!(store.get("a").await.unwrap().get(), r#""first""#);
+
+        // too large for memory, so it is written to disk directly
+        store.insert("large".to_string(), result(r#""0123456789""#));
+        // evicts a from memory
+        for key in ["b", "c", "d", "e"] {
+            store.insert(key.to_string(), result(r#""0123456""#));
+        }
+        assert!(store.memory.lock().entries.peek("a").is_none());
+
+        // wait for the write to disk
+        let disk = store.disk.clone().unwrap();
+        while disk.index.lock().entries.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(store.get("a").await.unwrap().get(), r#""first""#);
+        assert_eq!(store.get("large").await.unwrap().get(), r#""0123456789""#);
+        assert!(store.get("f").await.is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! TCP server for the HTTP and WS transports.
+
+use hyper::body::Incoming;
+use jsonrpsee::{
+    core::BoxError,
+    server::{
+        serve_with_graceful_shutdown, stop_channel, HttpBody, HttpRequest, HttpResponse,
+        ServerBuilder, ServerHandle, TowerService, TowerServiceBuilder,
+    },
+    Methods,
+};
+use std::{
+    io,
+    net::SocketAddr,
+    task::{Context, Poll},
+};
+use tokio::{net::TcpListener, runtime::Handle};
+use tower::Service;
+use tracing::debug;
+
+/// An HTTP and WS server bound to a TCP listener.
+///
+/// Unlike [`jsonrpsee::server::Server`], the peer address of every connection is attached to the
+/// extensions of its requests as a [`SocketAddr`], so that middleware can attribute requests to
+/// the client that sent them.
+pub(crate) struct RpcTcpServer<RpcMiddleware, HttpMiddleware> {
+    listener: TcpListener,
+    builder: TowerServiceBuilder<RpcMiddleware, HttpMiddleware>,
+}
+
+impl<RpcMiddleware, HttpMiddleware> RpcTcpServer<RpcMiddleware, HttpMiddleware> {
+    /// Binds the server configured by the given builder to `addr`.
+    pub(crate) async fn bind(
+        builder: ServerBuilder<HttpMiddleware, RpcMiddleware>,
+        addr: SocketAddr,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self { listener, builder: builder.to_service_builder() })
+    }
+
+    /// Returns the address the server is listening on.
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Starts serving the given methods until the returned handle is stopped.
+    ///
+    /// The server runs on the given tokio runtime, or the current one if none is set.
+    pub(crate) fn start(
+        self,
+        methods: impl Into<Methods>,
+        tokio_runtime: Option<&Handle>,
+    ) -> ServerHandle
+    where
+        RpcMiddleware: Clone + Send + 'static,
+        HttpMiddleware: Clone + Send + 'static,
+        TowerService<RpcMiddleware, HttpMiddleware>: Clone + Send + 'static,
+        TowerService<RpcMiddleware, HttpMiddleware>:
+            Service<HttpRequest, Response = HttpResponse, Error = BoxError>,
+        <TowerService<RpcMiddleware, HttpMiddleware> as Service<HttpRequest>>::Future: Send,
+    {
+        let Self { listener, builder } = self;
+        let methods = methods.into();
+        let (stop_handle, server_handle) = stop_channel();
+
+        let server = async move {
+            loop {
+                let (socket, peer) = tokio::select! {
+                    res = listener.accept() => match res {
+                        Ok(conn) => conn,
+                        Err(err) => {
+                            debug!(target: "rpc", %err, "Failed to accept connection");
+                            continue
+                        }
+                    },
+                    _ = stop_handle.clone().shutdown() => break,
+                };
+                if let Err(err) = socket.set_nodelay(true) {
+                    debug!(target: "rpc", %err, %peer, "Failed to set TCP_NODELAY");
+                }
+
+                let service = PeerAddrService {
+                    inner: builder.clone().build(methods.clone(), stop_handle.clone()),
+                    peer,
+                };
+                tokio::spawn(serve_with_graceful_shutdown(
+                    socket,
+                    service,
+                    stop_handle.clone().shutdown(),
+                ));
+            }
+        };
+        match tokio_runtime {
+            Some(runtime) => runtime.spawn(server),
+            None => tokio::spawn(server),
+        };
+
+        server_handle
+    }
+}
+
+impl<RpcMiddleware, HttpMiddleware> std::fmt::Debug
+    for RpcTcpServer<RpcMiddleware, HttpMiddleware>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcTcpServer").field("listener", &self.listener).finish_non_exhaustive()
+    }
+}
+
+/// HTTP service that attaches the peer address of its connection to every request.
+#[derive(Debug, Clone)]
+struct PeerAddrService<S> {
+    inner: S,
+    peer: SocketAddr,
+}
+
+impl<S> Service<http::Request<Incoming>> for PeerAddrService<S>
+where
+    S: Service<HttpRequest>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<Incoming>) -> Self::Future {
+        let mut req = req.map(HttpBody::new);
+        req.extensions_mut().insert(self.peer);
+        self.inner.call(req)
+    }
+}
@@ -19,6 +19,7 @@ use jsonrpsee::{
     types::error::ErrorCode,
 };
 use reth_ethereum_primitives::{Receipt, TransactionSigned};
+use reth_network_api::BanTarget;
 use reth_network_peers::NodeRecord;
 use reth_rpc_api::{
     clients::{AdminApiClient, EthApiClient},
@@ -167,6 +168,12 @@ where
     AdminApiClient::remove_trusted_peer(client, node.into()).await.unwrap();
     AdminApiClient::ban_peer(client, node.into()).await.unwrap();
     AdminApiClient::unban_peer(client, node.into()).await.unwrap();
+    let target: BanTarget = "10.3.0.0/16".parse().unwrap();
+    AdminApiClient::add_ban(client, target, Some("spam".to_string()), Some(60)).await.unwrap();
+    AdminApiClient::remove_ban(client, target).await.unwrap();
+    AdminApiClient::add_ban(client, target, None, Some(u64::MAX)).await.unwrap_err();
+    AdminApiClient::bans(client).await.unwrap();
+    AdminApiClient::peer_bandwidth(client).await.unwrap();
     AdminApiClient::node_info(client).await.unwrap();
 }
 
@@ -29,7 +29,7 @@ use reth_storage_api::{
 };
 use reth_transaction_pool::{
     error::InvalidPoolTransactionError, BestTransactions, BestTransactionsAttributes,
-    PoolTransaction, TransactionPool,
+    PoolTransaction, TransactionOrigin, TransactionPool,
 };
 use reth_trie_common::ComputedTrieData;
 use revm::context_interface::{Block, Cfg as _};
@@ -330,7 +330,7 @@ pub trait LoadPendingBlock:
                     continue
                 }
 
-                if pool_tx.origin.is_private() {
+                if is_private_order_flow(pool_tx.origin) {
                     // we don't want to leak any state changes made by private transactions, so we
                     // mark them as invalid here which removes all dependent
                     // transactions from the iteratorbefore we can continue
@@ -444,6 +444,14 @@ pub trait LoadPendingBlock:
     }
 }
 
+/// Returns whether transactions of the given origin must not be included in the pending block.
+///
+/// This covers transactions submitted locally as private and transactions received over the
+/// private relay, neither of which may be revealed before they are included in a block.
+const fn is_private_order_flow(origin: TransactionOrigin) -> bool {
+    origin.is_private() || origin.is_private_relay()
+}
+
 /// A type that knows how to build a [`ConfigureEvm::NextBlockEnvCtx`] for a pending block.
 pub trait PendingEnvBuilder<Evm: ConfigureEvm>: Send + Sync + Unpin + 'static {
     /// Builds a [`ConfigureEvm::NextBlockEnvCtx`] for a pending block.
@@ -567,4 +575,12 @@ mod tests {
 
         assert_eq!(attrs.slot_number, Some(8));
     }
+
+    #[test]
+    fn excludes_private_order_flow() {
+        assert!(is_private_order_flow(TransactionOrigin::Private));
+        assert!(is_private_order_flow(TransactionOrigin::PrivateRelay));
+        assert!(!is_private_order_flow(TransactionOrigin::Local));
+        assert!(!is_private_order_flow(TransactionOrigin::External));
+    }
 }
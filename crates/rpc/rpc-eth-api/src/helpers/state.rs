@@ -33,7 +33,8 @@ pub trait EthState: LoadState + SpawnBlocking {
     /// Validates that the given block is within the configured proof window.
     ///
     /// Returns an error if the distance between the chain tip and the requested block exceeds
-    /// [`Self::max_proof_window`].
+    /// [`Self::max_proof_window`], unless the trie changesets needed to prove the block are
+    /// archived.
     fn ensure_within_proof_window(&self, block_id: BlockId) -> Result<(), Self::Error>
     where
         Self: EthApiSpec,
@@ -44,7 +45,12 @@ pub trait EthState: LoadState + SpawnBlocking {
             .block_number_for_id(block_id)
             .map_err(Self::Error::from_eth_err)?
             .ok_or(EthApiError::HeaderNotFound(block_id))?;
-        if chain_info.best_number.saturating_sub(block_number) > self.max_proof_window() {
+        if chain_info.best_number.saturating_sub(block_number) > self.max_proof_window() &&
+            !self
+                .provider()
+                .has_archived_trie_changesets(block_number)
+                .map_err(Self::Error::from_eth_err)?
+        {
             return Err(EthApiError::ExceedsMaxProofWindow.into())
         }
         Ok(())
@@ -114,6 +114,12 @@ pub struct EthConfig {
     /// This is disabled by default, allowing blob transactions with EIP-4844 sidecars to be
     /// submitted without automatic conversion.
     pub force_blob_sidecar_upcasting: bool,
+    /// Whether the `debug_subscribeBlockTraces` and `trace_subscribeBlockTraces` subscriptions
+    /// are enabled.
+    ///
+    /// Each new canonical block is re-executed once for every distinct tracer that is subscribed
+    /// to, so this is disabled by default.
+    pub block_trace_subscriptions: bool,
 }
 
 impl EthConfig {
@@ -149,6 +155,7 @@ impl Default for EthConfig {
             send_raw_transaction_sync_timeout: RPC_DEFAULT_SEND_RAW_TX_SYNC_TIMEOUT_SECS,
             rpc_evm_memory_limit: (1 << 32) - 1,
             force_blob_sidecar_upcasting: false,
+            block_trace_subscriptions: false,
         }
     }
 }
@@ -263,6 +270,12 @@ impl EthConfig {
         self.force_blob_sidecar_upcasting = force;
         self
     }
+
+    /// Configures whether the block trace subscriptions are enabled.
+    pub const fn block_trace_subscriptions(mut self, enabled: bool) -> Self {
+        self.block_trace_subscriptions = enabled;
+        self
+    }
 }
 
 /// Config for the filter
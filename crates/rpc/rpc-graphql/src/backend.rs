@@ -0,0 +1,306 @@
+//! Data source of the GraphQL schema.
+
+use alloy_eips::BlockId;
+use alloy_network::{AnyRpcBlock, AnyRpcTransaction, AnyTransactionReceipt};
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use alloy_rpc_types_eth::{
+    simulate::{SimBlock, SimCallResult, SimulatePayload},
+    Filter, Log, SyncStatus, TransactionRequest,
+};
+use futures::future::BoxFuture;
+use http::{header, Extensions, HeaderMap, HeaderValue};
+use http_body_util::BodyExt;
+use jsonrpsee_http_client::{HttpBody, HttpRequest, HttpResponse};
+use jsonrpsee_types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tower::{Service, ServiceExt};
+
+/// The result of a backend call.
+pub type BackendResult<T> = Result<T, ErrorObjectOwned>;
+
+/// The outcome of a message call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallOutcome {
+    /// The return data, or the revert data if the call failed.
+    pub data: Bytes,
+    /// The gas used by the call.
+    pub gas_used: u64,
+    /// Whether the call succeeded.
+    pub success: bool,
+}
+
+/// Provides the data served by the GraphQL schema.
+///
+/// All values are returned as network agnostic [`AnyNetwork`](alloy_network::AnyNetwork) RPC
+/// types, so that the schema does not depend on the node's RPC types.
+pub trait GraphQlBackend: Send + Sync + 'static {
+    /// Returns the block with all transactions.
+    fn block(&self, id: BlockId) -> BoxFuture<'_, BackendResult<Option<AnyRpcBlock>>>;
+
+    /// Returns the receipts of all transactions in the block.
+    fn block_receipts(
+        &self,
+        id: BlockId,
+    ) -> BoxFuture<'_, BackendResult<Option<Vec<AnyTransactionReceipt>>>>;
+
+    /// Returns the transaction with the given hash.
+    fn transaction(&self, hash: B256) -> BoxFuture<'_, BackendResult<Option<AnyRpcTransaction>>>;
+
+    /// Returns the receipt of the transaction with the given hash.
+    fn receipt(&self, hash: B256) -> BoxFuture<'_, BackendResult<Option<AnyTransactionReceipt>>>;
+
+    /// Returns the balance of the account at the given block.
+    fn balance(&self, address: Address, block: BlockId) -> BoxFuture<'_, BackendResult<U256>>;
+
+    /// Returns the nonce of the account at the given block.
+    fn nonce(&self, address: Address, block: BlockId) -> BoxFuture<'_, BackendResult<U256>>;
+
+    /// Returns the code of the account at the given block.
+    fn code(&self, address: Address, block: BlockId) -> BoxFuture<'_, BackendResult<Bytes>>;
+
+    /// Returns the value of the storage slot of the account at the given block.
+    fn storage(
+        &self,
+        address: Address,
+        slot: B256,
+        block: BlockId,
+    ) -> BoxFuture<'_, BackendResult<B256>>;
+
+    /// Executes a message call on top of the given block.
+    fn call(
+        &self,
+        request: TransactionRequest,
+        block: BlockId,
+    ) -> BoxFuture<'_, BackendResult<CallOutcome>>;
+
+    /// Estimates the gas required by a message call on top of the given block.
+    fn estimate_gas(
+        &self,
+        request: TransactionRequest,
+        block: BlockId,
+    ) -> BoxFuture<'_, BackendResult<U256>>;
+
+    /// Returns the logs matching the filter.
+    fn logs(&self, filter: Filter) -> BoxFuture<'_, BackendResult<Vec<Log>>>;
+
+    /// Returns the suggested gas price.
+    fn gas_price(&self) -> BoxFuture<'_, BackendResult<U256>>;
+
+    /// Returns the suggested priority fee.
+    fn max_priority_fee_per_gas(&self) -> BoxFuture<'_, BackendResult<U256>>;
+
+    /// Returns the chain id.
+    fn chain_id(&self) -> BoxFuture<'_, BackendResult<U256>>;
+
+    /// Returns the sync status.
+    fn syncing(&self) -> BoxFuture<'_, BackendResult<SyncStatus>>;
+
+    /// Submits a raw transaction and returns its hash.
+    fn send_raw_transaction(&self, tx: Bytes) -> BoxFuture<'_, BackendResult<B256>>;
+}
+
+/// Returns an internal error with the given message.
+fn internal_err(msg: impl Display) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, msg.to_string(), None::<()>)
+}
+
+/// A JSON-RPC response.
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: serde_json::Value,
+    error: Option<RpcError>,
+}
+
+/// The error object of a JSON-RPC response.
+#[derive(Deserialize)]
+struct RpcError {
+    code: i32,
+    message: String,
+    data: Option<serde_json::Value>,
+}
+
+/// The part of an `eth_simulateV1` block the backend is interested in.
+#[derive(Deserialize)]
+struct SimulatedCalls {
+    calls: Vec<SimCallResult>,
+}
+
+/// A [`GraphQlBackend`] that answers every query with a JSON-RPC call to the wrapped HTTP
+/// service.
+///
+/// The backend is created for every GraphQL request and forwards the headers and extensions of
+/// that request, so the calls pass through the same RPC middleware as regular JSON-RPC requests
+/// of the client: rate limits and metrics apply, and only the modules enabled on the server
+/// (`--http.api`) can be reached.
+#[derive(Debug)]
+pub struct RpcGraphQlBackend<S> {
+    /// The JSON-RPC service
+    service: S,
+    /// The headers of the GraphQL request
+    headers: HeaderMap,
+    /// The extensions of the GraphQL request
+    extensions: Extensions,
+    /// The id of the next JSON-RPC call
+    next_id: AtomicU64,
+}
+
+impl<S> RpcGraphQlBackend<S> {
+    /// Creates a new backend that sends calls to the given service on behalf of the request with
+    /// the given headers and extensions.
+    pub fn new(service: S, mut headers: HeaderMap, extensions: Extensions) -> Self {
+        headers.remove(header::CONTENT_LENGTH);
+        headers.remove(header::ACCEPT_ENCODING);
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Self { service, headers, extensions, next_id: AtomicU64::new(0) }
+    }
+}
+
+impl<S> RpcGraphQlBackend<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+    S::Error: Display,
+    S::Future: Send,
+{
+    /// Calls the JSON-RPC method with the given params.
+    async fn request<R: DeserializeOwned>(
+        &self,
+        method: &'static str,
+        params: serde_json::Value,
+    ) -> BackendResult<R> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let mut req =
+            HttpRequest::post("/").body(HttpBody::from(body.to_string())).map_err(internal_err)?;
+        *req.headers_mut() = self.headers.clone();
+        *req.extensions_mut() = self.extensions.clone();
+
+        let res = self.service.clone().oneshot(req).await.map_err(internal_err)?;
+        let body = res.into_body().collect().await.map_err(internal_err)?.to_bytes();
+        let res: RpcResponse = serde_json::from_slice(&body).map_err(internal_err)?;
+        if let Some(err) = res.error {
+            return Err(ErrorObjectOwned::owned(err.code, err.message, err.data))
+        }
+        serde_json::from_value(res.result).map_err(internal_err)
+    }
+}
+
+impl<S> GraphQlBackend for RpcGraphQlBackend<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+    S::Error: Display,
+    S::Future: Send,
+{
+    fn block(&self, id: BlockId) -> BoxFuture<'_, BackendResult<Option<AnyRpcBlock>>> {
+        Box::pin(async move {
+            match id {
+                BlockId::Hash(hash) => {
+                    self.request("eth_getBlockByHash", json!([hash.block_hash, true])).await
+                }
+                BlockId::Number(number) => {
+                    self.request("eth_getBlockByNumber", json!([number, true])).await
+                }
+            }
+        })
+    }
+
+    fn block_receipts(
+        &self,
+        id: BlockId,
+    ) -> BoxFuture<'_, BackendResult<Option<Vec<AnyTransactionReceipt>>>> {
+        Box::pin(self.request("eth_getBlockReceipts", json!([id])))
+    }
+
+    fn transaction(&self, hash: B256) -> BoxFuture<'_, BackendResult<Option<AnyRpcTransaction>>> {
+        Box::pin(self.request("eth_getTransactionByHash", json!([hash])))
+    }
+
+    fn receipt(&self, hash: B256) -> BoxFuture<'_, BackendResult<Option<AnyTransactionReceipt>>> {
+        Box::pin(self.request("eth_getTransactionReceipt", json!([hash])))
+    }
+
+    fn balance(&self, address: Address, block: BlockId) -> BoxFuture<'_, BackendResult<U256>> {
+        Box::pin(self.request("eth_getBalance", json!([address, block])))
+    }
+
+    fn nonce(&self, address: Address, block: BlockId) -> BoxFuture<'_, BackendResult<U256>> {
+        Box::pin(self.request("eth_getTransactionCount", json!([address, block])))
+    }
+
+    fn code(&self, address: Address, block: BlockId) -> BoxFuture<'_, BackendResult<Bytes>> {
+        Box::pin(self.request("eth_getCode", json!([address, block])))
+    }
+
+    fn storage(
+        &self,
+        address: Address,
+        slot: B256,
+        block: BlockId,
+    ) -> BoxFuture<'_, BackendResult<B256>> {
+        Box::pin(self.request("eth_getStorageAt", json!([address, slot, block])))
+    }
+
+    fn call(
+        &self,
+        request: TransactionRequest,
+        block: BlockId,
+    ) -> BoxFuture<'_, BackendResult<CallOutcome>> {
+        Box::pin(async move {
+            // simulate the call so that the gas used and the revert status are available
+            let payload = SimulatePayload::default().extend(SimBlock::default().call(request));
+            let blocks: Vec<SimulatedCalls> =
+                self.request("eth_simulateV1", json!([payload, block])).await?;
+            let Some(call) =
+                blocks.into_iter().next().and_then(|block| block.calls.into_iter().next())
+            else {
+                return Err(internal_err("call was not executed"))
+            };
+            Ok(CallOutcome {
+                data: call.return_data,
+                gas_used: call.gas_used,
+                success: call.status,
+            })
+        })
+    }
+
+    fn estimate_gas(
+        &self,
+        request: TransactionRequest,
+        block: BlockId,
+    ) -> BoxFuture<'_, BackendResult<U256>> {
+        Box::pin(self.request("eth_estimateGas", json!([request, block])))
+    }
+
+    fn logs(&self, filter: Filter) -> BoxFuture<'_, BackendResult<Vec<Log>>> {
+        Box::pin(self.request("eth_getLogs", json!([filter])))
+    }
+
+    fn gas_price(&self) -> BoxFuture<'_, BackendResult<U256>> {
+        Box::pin(self.request("eth_gasPrice", json!([])))
+    }
+
+    fn max_priority_fee_per_gas(&self) -> BoxFuture<'_, BackendResult<U256>> {
+        Box::pin(self.request("eth_maxPriorityFeePerGas", json!([])))
+    }
+
+    fn chain_id(&self) -> BoxFuture<'_, BackendResult<U256>> {
+        Box::pin(async move {
+            let chain_id: Option<U64> = self.request("eth_chainId", json!([])).await?;
+            Ok(chain_id.map(U256::from).unwrap_or_default())
+        })
+    }
+
+    fn syncing(&self) -> BoxFuture<'_, BackendResult<SyncStatus>> {
+        Box::pin(self.request("eth_syncing", json!([])))
+    }
+
+    fn send_raw_transaction(&self, tx: Bytes) -> BoxFuture<'_, BackendResult<B256>> {
+        Box::pin(self.request("eth_sendRawTransaction", json!([tx])))
+    }
+}
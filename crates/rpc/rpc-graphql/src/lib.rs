@@ -0,0 +1,28 @@
+//! [EIP-1767](https://eips.ethereum.org/EIPS/eip-1767) GraphQL interface for reth.
+//!
+//! The schema is served over HTTP by [`GraphQlLayer`], which is installed as an HTTP middleware of
+//! the RPC server. Queries are answered with `eth` JSON-RPC calls to the server via
+//! [`RpcGraphQlBackend`], so they are subject to the same RPC middleware and module configuration
+//! as regular JSON-RPC requests.
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod backend;
+pub mod scalars;
+mod schema;
+mod server;
+
+pub use backend::{BackendResult, CallOutcome, GraphQlBackend, RpcGraphQlBackend};
+pub use schema::{
+    build_schema, GraphQlSchema, Mutation, Query, MAX_BLOCK_RANGE, MAX_QUERY_COMPLEXITY,
+    MAX_QUERY_DEPTH,
+};
+pub use server::{
+    GraphQlLayer, GraphQlService, GRAPHIQL_PATH, GRAPHQL_PATH, MAX_GRAPHQL_REQUEST_SIZE,
+};
@@ -0,0 +1,168 @@
+//! Custom scalars defined by [EIP-1767](https://eips.ethereum.org/EIPS/eip-1767).
+
+use alloy_primitives::{hex, Address as AlloyAddress, Bytes as AlloyBytes, B256, U256};
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+
+/// Parses a string scalar value.
+fn as_str(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(s) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+/// A 32 byte binary string, represented as 0x-prefixed hexadecimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bytes32(pub B256);
+
+#[Scalar]
+impl ScalarType for Bytes32 {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        let s = as_str(&value).ok_or_else(|| InputValueError::expected_type(value.clone()))?;
+        s.parse::<B256>().map(Self).map_err(|err| InputValueError::custom(err.to_string()))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.0.to_string())
+    }
+}
+
+/// A 20 byte Ethereum address, represented as 0x-prefixed hexadecimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Address(pub AlloyAddress);
+
+#[Scalar]
+impl ScalarType for Address {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        let s = as_str(&value).ok_or_else(|| InputValueError::expected_type(value.clone()))?;
+        s.parse::<AlloyAddress>().map(Self).map_err(|err| InputValueError::custom(err.to_string()))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(hex::encode_prefixed(self.0))
+    }
+}
+
+/// An arbitrary length binary string, represented as 0x-prefixed hexadecimal.
+///
+/// An empty byte string is represented as `0x`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Bytes(pub AlloyBytes);
+
+#[Scalar]
+impl ScalarType for Bytes {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        let s = as_str(&value).ok_or_else(|| InputValueError::expected_type(value.clone()))?;
+        s.parse::<AlloyBytes>().map(Self).map_err(|err| InputValueError::custom(err.to_string()))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.0.to_string())
+    }
+}
+
+/// A large integer.
+///
+/// Input is accepted as either a JSON number or as a string, strings may be either decimal or
+/// 0x-prefixed hexadecimal. Output values are all 0x-prefixed hexadecimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BigInt(pub U256);
+
+#[Scalar]
+impl ScalarType for BigInt {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match &value {
+            Value::Number(n) => n
+                .as_u64()
+                .map(|n| Self(U256::from(n)))
+                .ok_or_else(|| InputValueError::custom("expected a non-negative integer")),
+            // `U256` parses both decimal and 0x-prefixed hexadecimal strings
+            Value::String(s) => {
+                s.parse::<U256>().map(Self).map_err(|err| InputValueError::custom(err.to_string()))
+            }
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(format!("{:#x}", self.0))
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(value: u128) -> Self {
+        Self(U256::from(value))
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> Self {
+        Self(U256::from(value))
+    }
+}
+
+/// A 64 bit unsigned integer.
+///
+/// Input is accepted as either a JSON number or as a string, strings may be either decimal or
+/// 0x-prefixed hexadecimal. Output values are JSON numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Long(pub u64);
+
+#[Scalar]
+impl ScalarType for Long {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match &value {
+            Value::Number(n) => n
+                .as_u64()
+                .map(Self)
+                .ok_or_else(|| InputValueError::custom("expected a non-negative integer")),
+            Value::String(s) => {
+                let parsed = match s.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => s.parse::<u64>(),
+                };
+                parsed.map(Self).map_err(|err| InputValueError::custom(err.to_string()))
+            }
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Number(self.0.into())
+    }
+}
+
+impl From<u64> for Long {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_accepts_hex_and_decimal() {
+        assert_eq!(Long::parse(Value::from(10)).unwrap(), Long(10));
+        assert_eq!(Long::parse(Value::from("10")).unwrap(), Long(10));
+        assert_eq!(Long::parse(Value::from("0x10")).unwrap(), Long(16));
+        assert!(Long::parse(Value::from(-1)).is_err());
+        assert_eq!(Long(16).to_value(), Value::from(16));
+    }
+
+    #[test]
+    fn big_int_roundtrip() {
+        assert_eq!(BigInt::parse(Value::from("0x10")).unwrap(), BigInt(U256::from(16)));
+        assert_eq!(BigInt::parse(Value::from("16")).unwrap(), BigInt(U256::from(16)));
+        assert_eq!(BigInt(U256::from(16)).to_value(), Value::from("0x10"));
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        assert_eq!(Bytes::default().to_value(), Value::from("0x"));
+        let bytes = Bytes::parse(Value::from("0x0102")).unwrap();
+        assert_eq!(bytes.0.as_ref(), &[1, 2]);
+        assert!(Address::parse(Value::from("0x01")).is_err());
+    }
+}
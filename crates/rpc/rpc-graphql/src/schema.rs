@@ -0,0 +1,854 @@
+//! The [EIP-1767](https://eips.ethereum.org/EIPS/eip-1767) GraphQL schema.
+//!
+//! The schema mirrors the one served by other execution clients, so that existing GraphQL
+//! tooling works against reth unchanged.
+
+use crate::{
+    backend::{CallOutcome, GraphQlBackend},
+    scalars::{Address, BigInt, Bytes, Bytes32, Long},
+};
+use alloy_consensus::{BlockHeader, Transaction as _, TxReceipt};
+use alloy_eips::{BlockId, Typed2718};
+use alloy_network::{AnyRpcBlock, AnyRpcTransaction, AnyTransactionReceipt};
+use alloy_primitives::TxKind;
+use alloy_rpc_types_eth::{
+    Filter, Log as RpcLog, SyncStatus, TransactionInput, TransactionRequest,
+};
+use async_graphql::{
+    Context, EmptySubscription, Error, InputObject, Object, Result, Schema, SimpleObject,
+};
+use jsonrpsee_types::ErrorObjectOwned;
+use std::sync::Arc;
+
+/// Maximum number of blocks that can be requested by a single `blocks` query.
+pub const MAX_BLOCK_RANGE: u64 = 1_000;
+
+/// Maximum depth of a query.
+pub const MAX_QUERY_DEPTH: usize = 32;
+
+/// Maximum complexity of a query, every selected field counts as one.
+pub const MAX_QUERY_COMPLEXITY: usize = 1_000;
+
+/// The GraphQL schema type.
+pub type GraphQlSchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// Builds the GraphQL schema.
+///
+/// The schema does not hold a [`GraphQlBackend`], the backend must be attached to every request
+/// as `Arc<dyn GraphQlBackend>` data.
+pub fn build_schema() -> GraphQlSchema {
+    Schema::build(Query, Mutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// Converts an RPC error into a GraphQL error.
+fn rpc_err(err: ErrorObjectOwned) -> Error {
+    Error::new(err.message())
+}
+
+/// Returns the backend attached to the schema.
+fn backend<'a>(ctx: &Context<'a>) -> &'a Arc<dyn GraphQlBackend> {
+    ctx.data_unchecked::<Arc<dyn GraphQlBackend>>()
+}
+
+/// Fetches a block by id.
+async fn fetch_block(ctx: &Context<'_>, id: BlockId) -> Result<Option<Block>> {
+    let block = backend(ctx).block(id).await.map_err(rpc_err)?;
+    Ok(block.map(|block| Block { block: Arc::new(block) }))
+}
+
+/// Fetches logs matching the filter.
+async fn fetch_logs(ctx: &Context<'_>, filter: Filter) -> Result<Vec<Log>> {
+    let logs = backend(ctx).logs(filter).await.map_err(rpc_err)?;
+    Ok(logs.into_iter().map(|log| Log { log }).collect())
+}
+
+/// Executes a call.
+async fn execute_call(ctx: &Context<'_>, data: CallData, block: BlockId) -> Result<CallResult> {
+    let outcome = backend(ctx).call(data.into(), block).await.map_err(rpc_err)?;
+    Ok(outcome.into())
+}
+
+/// Estimates the gas of a call.
+async fn estimate_call(ctx: &Context<'_>, data: CallData, block: BlockId) -> Result<Long> {
+    let gas = backend(ctx).estimate_gas(data.into(), block).await.map_err(rpc_err)?;
+    Ok(Long(gas.saturating_to()))
+}
+
+/// Converts an optional block number argument into a [`BlockId`], falling back to `default`.
+fn block_id_or(block: Option<Long>, default: BlockId) -> BlockId {
+    block.map(|number| BlockId::number(number.0)).unwrap_or(default)
+}
+
+/// Converts the `topics` argument of a filter into the filter's topics.
+fn apply_topics(mut filter: Filter, topics: Option<Vec<Vec<Bytes32>>>) -> Filter {
+    for (idx, topic) in topics.unwrap_or_default().into_iter().enumerate().take(4) {
+        filter.topics[idx] = topic.into_iter().map(|topic| topic.0).collect::<Vec<_>>().into();
+    }
+    filter
+}
+
+/// The root query type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Fetches an Ethereum block by number or by hash. If neither is supplied, the most recent
+    /// known block is returned.
+    async fn block(
+        &self,
+        ctx: &Context<'_>,
+        number: Option<Long>,
+        hash: Option<Bytes32>,
+    ) -> Result<Option<Block>> {
+        let id = match (number, hash) {
+            (Some(_), Some(_)) => return Err(Error::new("only one of number or hash must be set")),
+            (Some(number), None) => BlockId::number(number.0),
+            (None, Some(hash)) => BlockId::hash(hash.0),
+            (None, None) => BlockId::latest(),
+        };
+        fetch_block(ctx, id).await
+    }
+
+    /// Returns all available blocks in the inclusive range `from` to `to`. If `to` is not
+    /// supplied, it defaults to the most recent known block.
+    async fn blocks(&self, ctx: &Context<'_>, from: Long, to: Option<Long>) -> Result<Vec<Block>> {
+        let to = match to {
+            Some(to) => to.0,
+            None => match fetch_block(ctx, BlockId::latest()).await? {
+                Some(block) => block.block.header.number(),
+                None => return Ok(Vec::new()),
+            },
+        };
+        if to < from.0 {
+            return Ok(Vec::new())
+        }
+        if to - from.0 >= MAX_BLOCK_RANGE {
+            return Err(Error::new(format!("block range exceeds the limit of {MAX_BLOCK_RANGE}")))
+        }
+
+        let mut blocks = Vec::with_capacity((to - from.0 + 1) as usize);
+        for number in from.0..=to {
+            // stop at the first missing block, all later blocks are missing as well
+            let Some(block) = fetch_block(ctx, BlockId::number(number)).await? else { break };
+            blocks.push(block);
+        }
+        Ok(blocks)
+    }
+
+    /// Returns the current pending state.
+    async fn pending(&self) -> Pending {
+        Pending
+    }
+
+    /// Looks up a transaction by hash.
+    async fn transaction(&self, ctx: &Context<'_>, hash: Bytes32) -> Result<Option<Transaction>> {
+        let tx = backend(ctx).transaction(hash.0).await.map_err(rpc_err)?;
+        Ok(tx.map(Transaction::new))
+    }
+
+    /// Returns log entries matching the provided filter.
+    async fn logs(&self, ctx: &Context<'_>, filter: FilterCriteria) -> Result<Vec<Log>> {
+        let mut block_filter = Filter::new();
+        if let Some(from) = filter.from_block {
+            block_filter = block_filter.from_block(from.0);
+        }
+        if let Some(to) = filter.to_block {
+            block_filter = block_filter.to_block(to.0);
+        }
+        let block_filter = block_filter.address(
+            filter.addresses.unwrap_or_default().into_iter().map(|a| a.0).collect::<Vec<_>>(),
+        );
+        fetch_logs(ctx, apply_topics(block_filter, filter.topics)).await
+    }
+
+    /// Returns the node's estimate of a gas price sufficient to ensure a transaction is mined in
+    /// a timely fashion.
+    async fn gas_price(&self, ctx: &Context<'_>) -> Result<BigInt> {
+        backend(ctx).gas_price().await.map(BigInt).map_err(rpc_err)
+    }
+
+    /// Returns the node's estimate of a priority fee sufficient to ensure a transaction is mined
+    /// in a timely fashion.
+    async fn max_priority_fee_per_gas(&self, ctx: &Context<'_>) -> Result<BigInt> {
+        backend(ctx).max_priority_fee_per_gas().await.map(BigInt).map_err(rpc_err)
+    }
+
+    /// Returns the current sync state of the node, or null if the node is not syncing.
+    async fn syncing(&self, ctx: &Context<'_>) -> Result<Option<SyncState>> {
+        match backend(ctx).syncing().await.map_err(rpc_err)? {
+            SyncStatus::None => Ok(None),
+            SyncStatus::Info(info) => Ok(Some(SyncState {
+                starting_block: Long(info.starting_block.saturating_to()),
+                current_block: Long(info.current_block.saturating_to()),
+                highest_block: Long(info.highest_block.saturating_to()),
+            })),
+        }
+    }
+
+    /// Returns the current chain ID for transaction replay protection.
+    #[graphql(name = "chainID")]
+    async fn chain_id(&self, ctx: &Context<'_>) -> Result<BigInt> {
+        backend(ctx).chain_id().await.map(BigInt).map_err(rpc_err)
+    }
+}
+
+/// The root mutation type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mutation;
+
+#[Object]
+impl Mutation {
+    /// Sends an RLP-encoded transaction to the network and returns its hash.
+    async fn send_raw_transaction(&self, ctx: &Context<'_>, data: Bytes) -> Result<Bytes32> {
+        backend(ctx).send_raw_transaction(data.0).await.map(Bytes32).map_err(rpc_err)
+    }
+}
+
+/// An Ethereum block.
+#[derive(Debug, Clone)]
+pub struct Block {
+    block: Arc<AnyRpcBlock>,
+}
+
+impl Block {
+    /// Returns the id of this block.
+    fn id(&self) -> BlockId {
+        BlockId::hash(self.block.header.hash)
+    }
+}
+
+#[Object]
+impl Block {
+    /// The block number.
+    async fn number(&self) -> Long {
+        Long(self.block.header.number())
+    }
+
+    /// The block hash.
+    async fn hash(&self) -> Bytes32 {
+        Bytes32(self.block.header.hash)
+    }
+
+    /// The parent block, null for the genesis block.
+    async fn parent(&self, ctx: &Context<'_>) -> Result<Option<Block>> {
+        if self.block.header.number() == 0 {
+            return Ok(None)
+        }
+        fetch_block(ctx, BlockId::hash(self.block.header.parent_hash())).await
+    }
+
+    /// The block nonce, an 8 byte sequence determined by the miner.
+    async fn nonce(&self) -> Bytes {
+        Bytes(self.block.header.nonce().unwrap_or_default().0.to_vec().into())
+    }
+
+    /// The root of the transaction trie of the block.
+    async fn transactions_root(&self) -> Bytes32 {
+        Bytes32(self.block.header.transactions_root())
+    }
+
+    /// The number of transactions in the block.
+    async fn transaction_count(&self) -> Long {
+        Long(self.block.transactions.len() as u64)
+    }
+
+    /// The root of the final state trie of the block.
+    async fn state_root(&self) -> Bytes32 {
+        Bytes32(self.block.header.state_root())
+    }
+
+    /// The root of the receipts trie of the block.
+    async fn receipts_root(&self) -> Bytes32 {
+        Bytes32(self.block.header.receipts_root())
+    }
+
+    /// The account that mined this block, queried at the given block or at this block.
+    async fn miner(&self, block: Option<Long>) -> Account {
+        Account::new(self.block.header.beneficiary(), block_id_or(block, self.id()))
+    }
+
+    /// Arbitrary data set by the miner.
+    async fn extra_data(&self) -> Bytes {
+        Bytes(self.block.header.extra_data().clone())
+    }
+
+    /// The maximum amount of gas that was available to transactions in this block.
+    async fn gas_limit(&self) -> Long {
+        Long(self.block.header.gas_limit())
+    }
+
+    /// The amount of gas that was used executing transactions in this block.
+    async fn gas_used(&self) -> Long {
+        Long(self.block.header.gas_used())
+    }
+
+    /// The base fee per gas of this block, null before London.
+    async fn base_fee_per_gas(&self) -> Option<BigInt> {
+        self.block.header.base_fee_per_gas().map(BigInt::from)
+    }
+
+    /// The unix timestamp at which this block was mined.
+    async fn timestamp(&self) -> Long {
+        Long(self.block.header.timestamp())
+    }
+
+    /// The bloom filter for the logs of this block.
+    async fn logs_bloom(&self) -> Bytes {
+        Bytes(self.block.header.logs_bloom().0.to_vec().into())
+    }
+
+    /// The hash used as part of the proof of work, or the prevrandao value after the merge.
+    async fn mix_hash(&self) -> Bytes32 {
+        Bytes32(self.block.header.mix_hash().unwrap_or_default())
+    }
+
+    /// The difficulty of this block.
+    async fn difficulty(&self) -> BigInt {
+        BigInt(self.block.header.difficulty())
+    }
+
+    /// The total difficulty of the chain up to and including this block, if known.
+    async fn total_difficulty(&self) -> Option<BigInt> {
+        self.block.header.total_difficulty.map(BigInt)
+    }
+
+    /// The number of ommers of this block.
+    async fn ommer_count(&self) -> Long {
+        Long(self.block.uncles.len() as u64)
+    }
+
+    /// The hash of the ommers list of this block.
+    async fn ommer_hash(&self) -> Bytes32 {
+        Bytes32(self.block.header.ommers_hash())
+    }
+
+    /// The transactions of this block.
+    async fn transactions(&self) -> Vec<Transaction> {
+        self.block.transactions.txns().cloned().map(Transaction::new).collect()
+    }
+
+    /// Returns the transaction at the given index.
+    async fn transaction_at(&self, index: Long) -> Option<Transaction> {
+        self.block.transactions.txns().nth(index.0 as usize).cloned().map(Transaction::new)
+    }
+
+    /// Returns the logs of this block that match the filter.
+    async fn logs(&self, ctx: &Context<'_>, filter: BlockFilterCriteria) -> Result<Vec<Log>> {
+        let block_filter = Filter::new().at_block_hash(self.block.header.hash).address(
+            filter.addresses.unwrap_or_default().into_iter().map(|a| a.0).collect::<Vec<_>>(),
+        );
+        fetch_logs(ctx, apply_topics(block_filter, filter.topics)).await
+    }
+
+    /// Returns the account with the given address at this block.
+    async fn account(&self, address: Address) -> Account {
+        Account::new(address.0, self.id())
+    }
+
+    /// Executes a local call on top of the state of this block.
+    async fn call(&self, ctx: &Context<'_>, data: CallData) -> Result<CallResult> {
+        execute_call(ctx, data, self.id()).await
+    }
+
+    /// Estimates the gas a transaction would use on top of the state of this block.
+    async fn estimate_gas(&self, ctx: &Context<'_>, data: CallData) -> Result<Long> {
+        estimate_call(ctx, data, self.id()).await
+    }
+
+    /// The root of the withdrawals trie of this block, null before Shanghai.
+    async fn withdrawals_root(&self) -> Option<Bytes32> {
+        self.block.header.withdrawals_root().map(Bytes32)
+    }
+
+    /// The withdrawals of this block, null before Shanghai.
+    async fn withdrawals(&self) -> Option<Vec<Withdrawal>> {
+        self.block.withdrawals.as_ref().map(|withdrawals| {
+            withdrawals
+                .iter()
+                .map(|w| Withdrawal {
+                    index: Long(w.index),
+                    validator: Long(w.validator_index),
+                    address: Address(w.address),
+                    amount: Long(w.amount),
+                })
+                .collect()
+        })
+    }
+
+    /// The total blob gas used by the transactions of this block, null before Cancun.
+    async fn blob_gas_used(&self) -> Option<Long> {
+        self.block.header.blob_gas_used().map(Long)
+    }
+
+    /// The excess blob gas of this block, null before Cancun.
+    async fn excess_blob_gas(&self) -> Option<Long> {
+        self.block.header.excess_blob_gas().map(Long)
+    }
+}
+
+/// An Ethereum transaction.
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    tx: Arc<AnyRpcTransaction>,
+}
+
+impl Transaction {
+    fn new(tx: AnyRpcTransaction) -> Self {
+        Self { tx: Arc::new(tx) }
+    }
+
+    /// Returns the id of the block this transaction was included in, or the pending block.
+    fn block_id(&self) -> BlockId {
+        self.tx.block_hash.map(BlockId::hash).unwrap_or_else(BlockId::pending)
+    }
+
+    /// Returns the receipt of this transaction, if it was included in a block.
+    async fn receipt(&self, ctx: &Context<'_>) -> Result<Option<AnyTransactionReceipt>> {
+        if self.tx.block_hash.is_none() {
+            return Ok(None)
+        }
+        backend(ctx)
+            .receipt(alloy_network::TransactionResponse::tx_hash(&*self.tx))
+            .await
+            .map_err(rpc_err)
+    }
+}
+
+#[Object]
+impl Transaction {
+    /// The hash of this transaction.
+    async fn hash(&self) -> Bytes32 {
+        Bytes32(alloy_network::TransactionResponse::tx_hash(&*self.tx))
+    }
+
+    /// The nonce of the account this transaction was generated with.
+    async fn nonce(&self) -> Long {
+        Long(self.tx.nonce())
+    }
+
+    /// The index of this transaction in the parent block, null for pending transactions.
+    async fn index(&self) -> Option<Long> {
+        self.tx.transaction_index.map(Long)
+    }
+
+    /// The account that sent this transaction, queried at the given block or at the block this
+    /// transaction was included in.
+    async fn from(&self, block: Option<Long>) -> Account {
+        Account::new(
+            alloy_network::TransactionResponse::from(&*self.tx),
+            block_id_or(block, self.block_id()),
+        )
+    }
+
+    /// The account the transaction was sent to, null for contract creations.
+    async fn to(&self, block: Option<Long>) -> Option<Account> {
+        match self.tx.kind() {
+            TxKind::Call(to) => Some(Account::new(to, block_id_or(block, self.block_id()))),
+            TxKind::Create => None,
+        }
+    }
+
+    /// The value, in wei, sent along with this transaction.
+    async fn value(&self) -> BigInt {
+        BigInt(self.tx.value())
+    }
+
+    /// The price offered to miners for gas, in wei per unit.
+    ///
+    /// For dynamic fee transactions this is the effective gas price once included in a block.
+    async fn gas_price(&self) -> BigInt {
+        let price = self
+            .tx
+            .effective_gas_price
+            .or_else(|| alloy_consensus::Transaction::gas_price(&*self.tx))
+            .unwrap_or_else(|| self.tx.max_fee_per_gas());
+        BigInt::from(price)
+    }
+
+    /// The maximum fee per gas offered to include this transaction, null for legacy
+    /// transactions.
+    async fn max_fee_per_gas(&self) -> Option<BigInt> {
+        self.tx.is_dynamic_fee().then(|| BigInt::from(self.tx.max_fee_per_gas()))
+    }
+
+    /// The maximum priority fee per gas offered to include this transaction, null for legacy
+    /// transactions.
+    async fn max_priority_fee_per_gas(&self) -> Option<BigInt> {
+        self.tx.max_priority_fee_per_gas().map(BigInt::from)
+    }
+
+    /// The maximum fee per blob gas offered to include this transaction, null for non-blob
+    /// transactions.
+    async fn max_fee_per_blob_gas(&self) -> Option<BigInt> {
+        self.tx.max_fee_per_blob_gas().map(BigInt::from)
+    }
+
+    /// The maximum amount of gas this transaction can consume.
+    async fn gas(&self) -> Long {
+        Long(self.tx.gas_limit())
+    }
+
+    /// The data supplied to the target of the transaction.
+    async fn input_data(&self) -> Bytes {
+        Bytes(self.tx.input().clone())
+    }
+
+    /// The block this transaction was mined in, null for pending transactions.
+    async fn block(&self, ctx: &Context<'_>) -> Result<Option<Block>> {
+        match self.tx.block_hash {
+            Some(hash) => fetch_block(ctx, BlockId::hash(hash)).await,
+            None => Ok(None),
+        }
+    }
+
+    /// The return status of the transaction, 1 on success and 0 on failure. Null for pending
+    /// transactions.
+    async fn status(&self, ctx: &Context<'_>) -> Result<Option<Long>> {
+        Ok(self.receipt(ctx).await?.map(|receipt| Long(receipt.inner.inner.status() as u64)))
+    }
+
+    /// The amount of gas used by this transaction, null for pending transactions.
+    async fn gas_used(&self, ctx: &Context<'_>) -> Result<Option<Long>> {
+        Ok(self.receipt(ctx).await?.map(|receipt| Long(receipt.gas_used)))
+    }
+
+    /// The total gas used in the block up to and including this transaction, null for pending
+    /// transactions.
+    async fn cumulative_gas_used(&self, ctx: &Context<'_>) -> Result<Option<Long>> {
+        Ok(self.receipt(ctx).await?.map(|receipt| Long(receipt.inner.inner.cumulative_gas_used())))
+    }
+
+    /// The effective price paid per unit of gas, null for pending transactions.
+    async fn effective_gas_price(&self, ctx: &Context<'_>) -> Result<Option<BigInt>> {
+        Ok(self.receipt(ctx).await?.map(|receipt| BigInt::from(receipt.effective_gas_price)))
+    }
+
+    /// The amount of blob gas used by this transaction, null for non-blob or pending
+    /// transactions.
+    async fn blob_gas_used(&self, ctx: &Context<'_>) -> Result<Option<Long>> {
+        Ok(self.receipt(ctx).await?.and_then(|receipt| receipt.blob_gas_used).map(Long))
+    }
+
+    /// The price paid per unit of blob gas, null for non-blob or pending transactions.
+    async fn blob_gas_price(&self, ctx: &Context<'_>) -> Result<Option<BigInt>> {
+        Ok(self.receipt(ctx).await?.and_then(|receipt| receipt.blob_gas_price).map(BigInt::from))
+    }
+
+    /// The contract created by this transaction, null if this is not a contract creation or the
+    /// transaction is pending.
+    async fn created_contract(
+        &self,
+        ctx: &Context<'_>,
+        block: Option<Long>,
+    ) -> Result<Option<Account>> {
+        let block = block_id_or(block, self.block_id());
+        Ok(self
+            .receipt(ctx)
+            .await?
+            .and_then(|receipt| receipt.contract_address)
+            .map(|address| Account::new(address, block)))
+    }
+
+    /// The logs emitted by this transaction, null for pending transactions.
+    async fn logs(&self, ctx: &Context<'_>) -> Result<Option<Vec<Log>>> {
+        Ok(self.receipt(ctx).await?.map(|receipt| {
+            receipt.inner.inner.logs().iter().cloned().map(|log| Log { log }).collect()
+        }))
+    }
+
+    /// The EIP-2718 type of this transaction.
+    #[graphql(name = "type")]
+    async fn tx_type(&self) -> Long {
+        Long(self.tx.ty() as u64)
+    }
+
+    /// The EIP-2930 access list of this transaction, null for legacy transactions.
+    async fn access_list(&self) -> Option<Vec<AccessTuple>> {
+        self.tx.access_list().map(|list| {
+            list.iter()
+                .map(|item| AccessTuple {
+                    address: Address(item.address),
+                    storage_keys: item.storage_keys.iter().copied().map(Bytes32).collect(),
+                })
+                .collect()
+        })
+    }
+
+    /// The versioned hashes of the blobs of this transaction, null for non-blob transactions.
+    async fn blob_versioned_hashes(&self) -> Option<Vec<Bytes32>> {
+        self.tx.blob_versioned_hashes().map(|hashes| hashes.iter().copied().map(Bytes32).collect())
+    }
+}
+
+/// An Ethereum account at a particular block.
+#[derive(Debug, Clone)]
+pub struct Account {
+    address: alloy_primitives::Address,
+    block: BlockId,
+}
+
+impl Account {
+    const fn new(address: alloy_primitives::Address, block: BlockId) -> Self {
+        Self { address, block }
+    }
+}
+
+#[Object]
+impl Account {
+    /// The address of this account.
+    async fn address(&self) -> Address {
+        Address(self.address)
+    }
+
+    /// The balance of this account, in wei.
+    async fn balance(&self, ctx: &Context<'_>) -> Result<BigInt> {
+        backend(ctx).balance(self.address, self.block).await.map(BigInt).map_err(rpc_err)
+    }
+
+    /// The number of transactions sent from this account.
+    async fn transaction_count(&self, ctx: &Context<'_>) -> Result<Long> {
+        let nonce = backend(ctx).nonce(self.address, self.block).await.map_err(rpc_err)?;
+        Ok(Long(nonce.saturating_to()))
+    }
+
+    /// The code of this account, empty if this is not a contract.
+    async fn code(&self, ctx: &Context<'_>) -> Result<Bytes> {
+        backend(ctx).code(self.address, self.block).await.map(Bytes).map_err(rpc_err)
+    }
+
+    /// Returns the value of the given storage slot of this account.
+    async fn storage(&self, ctx: &Context<'_>, slot: Bytes32) -> Result<Bytes32> {
+        backend(ctx).storage(self.address, slot.0, self.block).await.map(Bytes32).map_err(rpc_err)
+    }
+}
+
+/// An Ethereum log entry.
+#[derive(Debug, Clone)]
+pub struct Log {
+    log: RpcLog,
+}
+
+#[Object]
+impl Log {
+    /// The index of this log in the block.
+    async fn index(&self) -> Long {
+        Long(self.log.log_index.unwrap_or_default())
+    }
+
+    /// The account that emitted this log, queried at the given block or at the block of the log.
+    async fn account(&self, block: Option<Long>) -> Account {
+        let default = self.log.block_hash.map(BlockId::hash).unwrap_or_else(BlockId::latest);
+        Account::new(self.log.address(), block_id_or(block, default))
+    }
+
+    /// The topics of this log.
+    async fn topics(&self) -> Vec<Bytes32> {
+        self.log.topics().iter().copied().map(Bytes32).collect()
+    }
+
+    /// The data of this log.
+    async fn data(&self) -> Bytes {
+        Bytes(self.log.data().data.clone())
+    }
+
+    /// The transaction that emitted this log.
+    async fn transaction(&self, ctx: &Context<'_>) -> Result<Option<Transaction>> {
+        let Some(hash) = self.log.transaction_hash else { return Ok(None) };
+        let tx = backend(ctx).transaction(hash).await.map_err(rpc_err)?;
+        Ok(tx.map(Transaction::new))
+    }
+}
+
+/// The current pending state.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pending;
+
+#[Object]
+impl Pending {
+    /// The number of transactions in the pending block.
+    async fn transaction_count(&self, ctx: &Context<'_>) -> Result<Long> {
+        let block = fetch_block(ctx, BlockId::pending()).await?;
+        Ok(Long(block.map(|block| block.block.transactions.len() as u64).unwrap_or_default()))
+    }
+
+    /// The transactions in the pending block.
+    async fn transactions(&self, ctx: &Context<'_>) -> Result<Option<Vec<Transaction>>> {
+        let block = fetch_block(ctx, BlockId::pending()).await?;
+        Ok(block
+            .map(|block| block.block.transactions.txns().cloned().map(Transaction::new).collect()))
+    }
+
+    /// Returns the account with the given address in the pending state.
+    async fn account(&self, address: Address) -> Account {
+        Account::new(address.0, BlockId::pending())
+    }
+
+    /// Executes a local call on top of the pending state.
+    async fn call(&self, ctx: &Context<'_>, data: CallData) -> Result<CallResult> {
+        execute_call(ctx, data, BlockId::pending()).await
+    }
+
+    /// Estimates the gas a transaction would use on top of the pending state.
+    async fn estimate_gas(&self, ctx: &Context<'_>, data: CallData) -> Result<Long> {
+        estimate_call(ctx, data, BlockId::pending()).await
+    }
+}
+
+/// The result of a local call.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CallResult {
+    /// The return data of the call.
+    data: Bytes,
+    /// The amount of gas used by the call.
+    gas_used: Long,
+    /// The return status of the call, 1 on success and 0 on failure.
+    status: Long,
+}
+
+impl From<CallOutcome> for CallResult {
+    fn from(outcome: CallOutcome) -> Self {
+        Self {
+            data: Bytes(outcome.data),
+            gas_used: Long(outcome.gas_used),
+            status: Long(outcome.success as u64),
+        }
+    }
+}
+
+/// The current sync state of the node.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SyncState {
+    /// The block number the sync started at.
+    starting_block: Long,
+    /// The current block number.
+    current_block: Long,
+    /// The highest block number known to the node.
+    highest_block: Long,
+}
+
+/// An entry of an EIP-2930 access list.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct AccessTuple {
+    /// The accessed address.
+    address: Address,
+    /// The accessed storage keys.
+    storage_keys: Vec<Bytes32>,
+}
+
+/// A validator withdrawal.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct Withdrawal {
+    /// The monotonically increasing index of the withdrawal.
+    index: Long,
+    /// The index of the validator that made the withdrawal.
+    validator: Long,
+    /// The recipient of the withdrawn funds.
+    address: Address,
+    /// The amount withdrawn, in gwei.
+    amount: Long,
+}
+
+/// The arguments of a local call.
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct CallData {
+    /// The sender of the call, defaults to the zero address.
+    from: Option<Address>,
+    /// The recipient of the call, null for contract creations.
+    to: Option<Address>,
+    /// The amount of gas provided for the call.
+    gas: Option<Long>,
+    /// The price of each unit of gas, in wei.
+    gas_price: Option<BigInt>,
+    /// The maximum fee per gas, in wei.
+    max_fee_per_gas: Option<BigInt>,
+    /// The maximum priority fee per gas, in wei.
+    max_priority_fee_per_gas: Option<BigInt>,
+    /// The value sent along with the call, in wei.
+    value: Option<BigInt>,
+    /// The input data of the call.
+    data: Option<Bytes>,
+}
+
+impl From<CallData> for TransactionRequest {
+    fn from(data: CallData) -> Self {
+        Self {
+            from: data.from.map(|from| from.0),
+            to: Some(data.to.map(|to| TxKind::Call(to.0)).unwrap_or(TxKind::Create)),
+            gas: data.gas.map(|gas| gas.0),
+            gas_price: data.gas_price.map(|price| price.0.saturating_to()),
+            max_fee_per_gas: data.max_fee_per_gas.map(|fee| fee.0.saturating_to()),
+            max_priority_fee_per_gas: data
+                .max_priority_fee_per_gas
+                .map(|fee| fee.0.saturating_to()),
+            value: data.value.map(|value| value.0),
+            input: TransactionInput::maybe_input(data.data.map(|data| data.0)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Filter criteria for log queries.
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct FilterCriteria {
+    /// The first block to search, defaults to the latest block.
+    from_block: Option<Long>,
+    /// The last block to search, defaults to the latest block.
+    to_block: Option<Long>,
+    /// The addresses that logs must originate from, any address matches if empty.
+    addresses: Option<Vec<Address>>,
+    /// The topics logs must match, each position matches any of the listed topics and an empty
+    /// position matches any topic.
+    topics: Option<Vec<Vec<Bytes32>>>,
+}
+
+/// Filter criteria for log queries within a block.
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct BlockFilterCriteria {
+    /// The addresses that logs must originate from, any address matches if empty.
+    addresses: Option<Vec<Address>>,
+    /// The topics logs must match, each position matches any of the listed topics and an empty
+    /// position matches any topic.
+    topics: Option<Vec<Vec<Bytes32>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{B256, U256};
+
+    #[test]
+    fn topics_are_applied_by_position() {
+        let topic = B256::repeat_byte(1);
+        let filter = apply_topics(Filter::new(), Some(vec![vec![], vec![Bytes32(topic)], vec![]]));
+        assert!(filter.topics[0].is_empty());
+        assert!(filter.topics[1].matches(&topic));
+        assert!(!filter.topics[1].matches(&B256::ZERO));
+    }
+
+    #[test]
+    fn call_data_into_request() {
+        let request: TransactionRequest = CallData {
+            to: Some(Address(alloy_primitives::Address::repeat_byte(2))),
+            value: Some(BigInt(U256::from(7))),
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(request.to, Some(TxKind::Call(alloy_primitives::Address::repeat_byte(2))));
+        assert_eq!(request.value, Some(U256::from(7)));
+
+        let create: TransactionRequest = CallData::default().into();
+        assert_eq!(create.to, Some(TxKind::Create));
+    }
+
+    #[test]
+    fn schema_exposes_eip1767_fields() {
+        let sdl = Schema::build(Query, Mutation, EmptySubscription).finish().sdl();
+        for field in ["chainID", "sendRawTransaction", "maxPriorityFeePerGas", "inputData"] {
+            assert!(sdl.contains(field), "missing {field}");
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! HTTP middleware that serves the GraphQL schema next to the JSON-RPC endpoint.
+
+use crate::{
+    backend::{GraphQlBackend, RpcGraphQlBackend},
+    schema::{build_schema, GraphQlSchema},
+};
+use async_graphql::{
+    http::GraphiQLSource,
+    parser::{parse_query, types::OperationType},
+    BatchRequest,
+};
+use http::{header, Method, StatusCode};
+use http_body_util::{BodyExt, Limited};
+use jsonrpsee_http_client::{HttpBody, HttpRequest, HttpResponse};
+use std::{
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// The path the GraphQL endpoint is served at.
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+/// The path the GraphiQL explorer is served at.
+pub const GRAPHIQL_PATH: &str = "/graphql/ui";
+
+/// The maximum size of a GraphQL request body, 5MB.
+pub const MAX_GRAPHQL_REQUEST_SIZE: usize = 5 * 1024 * 1024;
+
+/// A [`Layer`] that serves GraphQL queries on [`GRAPHQL_PATH`] and the GraphiQL explorer on
+/// [`GRAPHIQL_PATH`].
+///
+/// All other requests are passed through to the inner service. By default queries are answered
+/// with JSON-RPC calls to the inner service, see [`RpcGraphQlBackend`], so the layer should be
+/// installed as the innermost HTTP middleware of the RPC server.
+#[derive(Clone)]
+pub struct GraphQlLayer {
+    schema: GraphQlSchema,
+    /// A fixed backend that is used instead of the inner service.
+    backend: Option<Arc<dyn GraphQlBackend>>,
+}
+
+impl GraphQlLayer {
+    /// Creates a new layer that answers queries with calls to the inner service.
+    pub fn new() -> Self {
+        Self { schema: build_schema(), backend: None }
+    }
+
+    /// Creates a new layer that answers queries with the given backend.
+    ///
+    /// Note: queries served by this backend bypass the RPC middleware of the inner service.
+    pub fn with_backend(backend: Arc<dyn GraphQlBackend>) -> Self {
+        Self { schema: build_schema(), backend: Some(backend) }
+    }
+
+    /// Returns the schema served by this layer.
+    pub const fn schema(&self) -> &GraphQlSchema {
+        &self.schema
+    }
+}
+
+impl Default for GraphQlLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for GraphQlLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GraphQlLayer").finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for GraphQlLayer {
+    type Service = GraphQlService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GraphQlService { schema: self.schema.clone(), backend: self.backend.clone(), inner }
+    }
+}
+
+/// Service that answers GraphQL requests and forwards everything else.
+///
+/// Created by [`GraphQlLayer`].
+#[derive(Clone)]
+pub struct GraphQlService<S> {
+    schema: GraphQlSchema,
+    backend: Option<Arc<dyn GraphQlBackend>>,
+    inner: S,
+}
+
+impl<S> std::fmt::Debug for GraphQlService<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GraphQlService").finish_non_exhaustive()
+    }
+}
+
+impl<S> Service<HttpRequest> for GraphQlService<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+    S::Error: Display,
+    S::Future: Send + 'static,
+{
+    type Response = HttpResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: HttpRequest) -> Self::Future {
+        match req.uri().path() {
+            GRAPHQL_PATH => {
+                let schema = self.schema.clone();
+                let backend = self.backend.clone().unwrap_or_else(|| {
+                    Arc::new(RpcGraphQlBackend::new(
+                        self.inner.clone(),
+                        req.headers().clone(),
+                        req.extensions().clone(),
+                    ))
+                });
+                Box::pin(async move { Ok(execute(schema, backend, req).await) })
+            }
+            GRAPHIQL_PATH if req.method() == Method::GET => {
+                let page = GraphiQLSource::build().endpoint(GRAPHQL_PATH).finish();
+                Box::pin(async move { Ok(response(StatusCode::OK, "text/html", page)) })
+            }
+            _ => {
+                let fut = self.inner.call(req);
+                Box::pin(fut)
+            }
+        }
+    }
+}
+
+/// Executes the GraphQL request.
+///
+/// Queries are accepted as a JSON body of `POST` requests, or in the query string of `GET`
+/// requests. Mutations are only accepted over `POST`.
+async fn execute(
+    schema: GraphQlSchema,
+    backend: Arc<dyn GraphQlBackend>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let request = match *req.method() {
+        Method::GET => {
+            let request = match async_graphql::http::parse_query_string(
+                req.uri().query().unwrap_or_default(),
+            ) {
+                Ok(request) => request,
+                Err(err) => return bad_request(err.to_string()),
+            };
+            let is_mutation = parse_query(&request.query).is_ok_and(|doc| {
+                doc.operations.iter().any(|(_, op)| op.node.ty == OperationType::Mutation)
+            });
+            if is_mutation {
+                return response(
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "text/plain",
+                    "mutations are only allowed over POST".to_string(),
+                )
+            }
+            BatchRequest::Single(request)
+        }
+        Method::POST => {
+            let body = match Limited::new(req.into_body(), MAX_GRAPHQL_REQUEST_SIZE).collect().await
+            {
+                Ok(body) => body.to_bytes(),
+                Err(err) => return bad_request(err.to_string()),
+            };
+            match serde_json::from_slice::<BatchRequest>(&body) {
+                Ok(request) => request,
+                Err(err) => return bad_request(err.to_string()),
+            }
+        }
+        _ => return response(StatusCode::METHOD_NOT_ALLOWED, "text/plain", String::new()),
+    };
+
+    let res = schema.execute_batch(request.data(backend)).await;
+    match serde_json::to_string(&res) {
+        Ok(body) => response(StatusCode::OK, "application/json", body),
+        Err(err) => response(StatusCode::INTERNAL_SERVER_ERROR, "text/plain", err.to_string()),
+    }
+}
+
+/// Returns a `400 Bad Request` response with the given message.
+fn bad_request(msg: String) -> HttpResponse {
+    response(StatusCode::BAD_REQUEST, "text/plain", msg)
+}
+
+/// Builds a response with the given status, content type and body.
+fn response(status: StatusCode, content_type: &'static str, body: String) -> HttpResponse {
+    HttpResponse::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(HttpBody::from(body))
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{BackendResult, CallOutcome},
+        schema::MAX_QUERY_DEPTH,
+    };
+    use alloy_eips::BlockId;
+    use alloy_network::{AnyRpcBlock, AnyRpcTransaction, AnyTransactionReceipt};
+    use alloy_primitives::{Address, Bytes, B256, U256};
+    use alloy_rpc_types_eth::{Filter, Log, SyncStatus, TransactionRequest};
+    use futures::future::{ready, BoxFuture};
+    use std::convert::Infallible;
+
+    /// A backend for a chain without any blocks.
+    struct EmptyBackend;
+
+    impl GraphQlBackend for EmptyBackend {
+        fn block(&self, _: BlockId) -> BoxFuture<'_, BackendResult<Option<AnyRpcBlock>>> {
+            Box::pin(ready(Ok(None)))
+        }
+
+        fn block_receipts(
+            &self,
+            _: BlockId,
+        ) -> BoxFuture<'_, BackendResult<Option<Vec<AnyTransactionReceipt>>>> {
+            Box::pin(ready(Ok(None)))
+        }
+
+        fn transaction(&self, _: B256) -> BoxFuture<'_, BackendResult<Option<AnyRpcTransaction>>> {
+            Box::pin(ready(Ok(None)))
+        }
+
+        fn receipt(&self, _: B256) -> BoxFuture<'_, BackendResult<Option<AnyTransactionReceipt>>> {
+            Box::pin(ready(Ok(None)))
+        }
+
+        fn balance(&self, _: Address, _: BlockId) -> BoxFuture<'_, BackendResult<U256>> {
+            Box::pin(ready(Ok(U256::from(100))))
+        }
+
+        fn nonce(&self, _: Address, _: BlockId) -> BoxFuture<'_, BackendResult<U256>> {
+            Box::pin(ready(Ok(U256::ZERO)))
+        }
+
+        fn code(&self, _: Address, _: BlockId) -> BoxFuture<'_, BackendResult<Bytes>> {
+            Box::pin(ready(Ok(Bytes::new())))
+        }
+
+        fn storage(&self, _: Address, _: B256, _: BlockId) -> BoxFuture<'_, BackendResult<B256>> {
+            Box::pin(ready(Ok(B256::ZERO)))
+        }
+
+        fn call(
+            &self,
+            _: TransactionRequest,
+            _: BlockId,
+        ) -> BoxFuture<'_, BackendResult<CallOutcome>> {
+            Box::pin(ready(Ok(CallOutcome { data: Bytes::new(), gas_used: 21_000, success: true })))
+        }
+
+        fn estimate_gas(
+            &self,
+            _: TransactionRequest,
+            _: BlockId,
+        ) -> BoxFuture<'_, BackendResult<U256>> {
+            Box::pin(ready(Ok(U256::from(21_000))))
+        }
+
+        fn logs(&self, _: Filter) -> BoxFuture<'_, BackendResult<Vec<Log>>> {
+            Box::pin(ready(Ok(Vec::new())))
+        }
+
+        fn gas_price(&self) -> BoxFuture<'_, BackendResult<U256>> {
+            Box::pin(ready(Ok(U256::from(1))))
+        }
+
+        fn max_priority_fee_per_gas(&self) -> BoxFuture<'_, BackendResult<U256>> {
+            Box::pin(ready(Ok(U256::from(1))))
+        }
+
+        fn chain_id(&self) -> BoxFuture<'_, BackendResult<U256>> {
+            Box::pin(ready(Ok(U256::from(1))))
+        }
+
+        fn syncing(&self) -> BoxFuture<'_, BackendResult<SyncStatus>> {
+            Box::pin(ready(Ok(SyncStatus::None)))
+        }
+
+        fn send_raw_transaction(&self, _: Bytes) -> BoxFuture<'_, BackendResult<B256>> {
+            Box::pin(ready(Ok(B256::ZERO)))
+        }
+    }
+
+    #[derive(Clone)]
+    struct Passthrough;
+
+    impl Service<HttpRequest> for Passthrough {
+        type Response = HttpResponse;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: HttpRequest) -> Self::Future {
+            std::future::ready(Ok(HttpResponse::new(HttpBody::from("jsonrpc"))))
+        }
+    }
+
+    async fn body(res: HttpResponse) -> String {
+        String::from_utf8(res.into_body().collect().await.unwrap().to_bytes().to_vec()).unwrap()
+    }
+
+    fn service() -> GraphQlService<Passthrough> {
+        GraphQlLayer::with_backend(Arc::new(EmptyBackend)).layer(Passthrough)
+    }
+
+    #[tokio::test]
+    async fn serves_post_queries() {
+        let req = HttpRequest::post(GRAPHQL_PATH)
+            .body(HttpBody::from(
+                r#"{"query":"{ chainID block { number } account: pending { account(address: \"0x0000000000000000000000000000000000000001\") { balance } } }"}"#,
+            ))
+            .unwrap();
+        let res = service().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&body(res).await).unwrap();
+        assert_eq!(body["data"]["chainID"], "0x1");
+        assert!(body["data"]["block"].is_null());
+        assert_eq!(body["data"]["account"]["account"]["balance"], "0x64");
+    }
+
+    #[tokio::test]
+    async fn serves_get_queries() {
+        let req =
+            HttpRequest::get("/graphql?query=%7BgasPrice%7D").body(HttpBody::empty()).unwrap();
+        let res = service().call(req).await.unwrap();
+        assert_eq!(body(res).await, r#"{"data":{"gasPrice":"0x1"}}"#);
+    }
+
+    #[tokio::test]
+    async fn rejects_get_mutations() {
+        let req =
+            HttpRequest::get("/graphql?query=mutation%7BsendRawTransaction(data%3A%220x00%22)%7D")
+                .body(HttpBody::empty())
+                .unwrap();
+        let res = service().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn rejects_deep_queries() {
+        let fragment = "transactions { block { ".repeat(MAX_QUERY_DEPTH);
+        let query = format!(
+            r#"{{"query":"{{ block {{ {fragment} number {} }} }}"}}"#,
+            "} }".repeat(MAX_QUERY_DEPTH)
+        );
+        let req = HttpRequest::post(GRAPHQL_PATH).body(HttpBody::from(query)).unwrap();
+        let res = service().call(req).await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body(res).await).unwrap();
+        assert!(body["errors"][0]["message"].as_str().unwrap().contains("nested too deep"));
+    }
+
+    #[tokio::test]
+    async fn queries_inner_service() {
+        /// A JSON-RPC server that only answers `eth_chainId`.
+        #[derive(Clone)]
+        struct ChainIdRpc;
+
+        impl Service<HttpRequest> for ChainIdRpc {
+            type Response = HttpResponse;
+            type Error = Infallible;
+            type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+            fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, req: HttpRequest) -> Self::Future {
+                Box::pin(async move {
+                    let body = req.into_body().collect().await.unwrap().to_bytes();
+                    let req: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let res = if req["method"] == "eth_chainId" {
+                        serde_json::json!({ "jsonrpc": "2.0", "id": req["id"], "result": "0x5" })
+                    } else {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": req["id"],
+                            "error": { "code": -32601, "message": "Method not found" }
+                        })
+                    };
+                    Ok(HttpResponse::new(HttpBody::from(res.to_string())))
+                })
+            }
+        }
+
+        let mut service = GraphQlLayer::new().layer(ChainIdRpc);
+        let req = HttpRequest::post(GRAPHQL_PATH)
+            .body(HttpBody::from(r#"{"query":"{ chainID }"}"#))
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(body(res).await, r#"{"data":{"chainID":"0x5"}}"#);
+
+        // methods that are not served by the inner service are not available
+        let req = HttpRequest::post(GRAPHQL_PATH)
+            .body(HttpBody::from(r#"{"query":"{ gasPrice }"}"#))
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body(res).await).unwrap();
+        assert_eq!(body["errors"][0]["message"], "Method not found");
+    }
+
+    #[tokio::test]
+    async fn passes_through_other_paths() {
+        let req = HttpRequest::post("/").body(HttpBody::empty()).unwrap();
+        let res = service().call(req).await.unwrap();
+        assert_eq!(body(res).await, "jsonrpc");
+    }
+}
@@ -132,3 +132,47 @@ pub mod cache {
     /// Default maximum number of transaction hashes to cache for lookups.
     pub const DEFAULT_MAX_CACHED_TX_HASHES: u32 = 30_000;
 }
+
+/// Rate limiting specific constants
+pub mod rate_limit {
+    /// Default burst capacity of a client's token bucket.
+    pub const DEFAULT_CLIENT_BURST: u32 = 1_000;
+
+    /// Default number of tokens a client's bucket is refilled with per second.
+    pub const DEFAULT_CLIENT_REFILL_PER_SEC: u32 = 200;
+
+    /// Default cost of `debug_` namespace calls.
+    pub const DEFAULT_DEBUG_COST: u32 = 20;
+
+    /// Default cost of `trace_` namespace calls.
+    pub const DEFAULT_TRACE_COST: u32 = 20;
+
+    /// Default cost of `eth_getLogs` calls.
+    pub const DEFAULT_GET_LOGS_COST: u32 = 10;
+
+    /// Default maximum number of clients the rate limiter keeps track of.
+    pub const DEFAULT_MAX_TRACKED_CLIENTS: u32 = 10_000;
+
+    /// JSON-RPC error code returned if a request exceeds its quota.
+    ///
+    /// This is the `Limit exceeded` code defined in [EIP-1474](https://eips.ethereum.org/EIPS/eip-1474).
+    pub const RATE_LIMIT_EXCEEDED_CODE: i32 = -32005;
+}
+
+/// Response cache specific constants
+pub mod response_cache {
+    /// Default memory budget of the response cache, 256MB.
+    pub const DEFAULT_MAX_MEMORY_BYTES: u64 = 256 * 1024 * 1024;
+
+    /// Default disk budget of the response cache, 4GB.
+    pub const DEFAULT_MAX_DISK_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+}
+
+/// RPC traffic recorder specific constants
+pub mod recorder {
+    /// Default size after which a recording file is rotated, 128MB.
+    pub const DEFAULT_MAX_FILE_BYTES: u64 = 128 * 1024 * 1024;
+
+    /// Default number of recording files kept per transport.
+    pub const DEFAULT_MAX_FILES: usize = 16;
+}
@@ -10,6 +10,9 @@
 
 /// Common RPC constants.
 pub mod constants;
+pub mod rate_limit;
+pub mod recording;
+pub mod response_cache;
 pub mod result;
 
 mod module;
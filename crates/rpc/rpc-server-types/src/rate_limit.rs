@@ -0,0 +1,357 @@
+//! Configuration types for per-client RPC rate limiting.
+
+use crate::constants::rate_limit::{
+    DEFAULT_CLIENT_BURST, DEFAULT_CLIENT_REFILL_PER_SEC, DEFAULT_DEBUG_COST, DEFAULT_GET_LOGS_COST,
+    DEFAULT_TRACE_COST,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, net::IpAddr, str::FromStr};
+
+/// Determines how requests are attributed to a client for rate limiting purposes.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    strum::EnumString,
+    strum::Display,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum RateLimitKey {
+    /// Attribute requests to the client IP address.
+    ///
+    /// This is the peer address of the connection. If the peer is one of the
+    /// [`RpcRateLimitConfig::trusted_proxies`], the address is taken from its `X-Forwarded-For` or
+    /// `X-Real-IP` headers instead.
+    #[default]
+    Ip,
+    /// Attribute requests to the `sub` claim of the bearer JWT.
+    ///
+    /// The claim is only used if the server verifies tokens with a JWT secret. Requests without
+    /// a verified token, or with a token that carries no `sub` claim, are attributed to the client
+    /// IP address.
+    Jwt,
+    /// Attribute requests to the connection they arrived on.
+    Connection,
+    /// All requests share a single quota.
+    Global,
+}
+
+/// Configuration of a single token bucket.
+///
+/// A bucket holds up to `capacity` tokens and is refilled at `refill_per_sec` tokens per second.
+/// A request is admitted if the bucket holds at least as many tokens as the request costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBucketConfig {
+    /// Maximum number of tokens the bucket can hold, this is the allowed burst.
+    pub capacity: u32,
+    /// Number of tokens that are added to the bucket every second.
+    pub refill_per_sec: u32,
+}
+
+impl TokenBucketConfig {
+    /// Creates a new bucket configuration.
+    pub const fn new(capacity: u32, refill_per_sec: u32) -> Self {
+        Self { capacity, refill_per_sec }
+    }
+}
+
+impl Default for TokenBucketConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_CLIENT_BURST, DEFAULT_CLIENT_REFILL_PER_SEC)
+    }
+}
+
+impl fmt::Display for TokenBucketConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.capacity, self.refill_per_sec)
+    }
+}
+
+impl FromStr for TokenBucketConfig {
+    type Err = RateLimitParseError;
+
+    /// Parses either `<rate>`, which uses the rate as burst capacity, or `<capacity>/<rate>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |v: &str| {
+            v.trim().parse::<u32>().map_err(|_| RateLimitParseError::InvalidNumber(v.to_string()))
+        };
+        match s.split_once('/') {
+            Some((capacity, rate)) => Ok(Self::new(parse(capacity)?, parse(rate)?)),
+            None => {
+                let rate = parse(s)?;
+                Ok(Self::new(rate, rate))
+            }
+        }
+    }
+}
+
+/// A pattern that matches RPC method names.
+///
+/// A pattern ending in `*` matches all methods with the given prefix, e.g. `trace_*`, otherwise
+/// the method name must match exactly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MethodPattern(String);
+
+impl MethodPattern {
+    /// Creates a new pattern.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self(pattern.into())
+    }
+
+    /// Returns true if the given method name matches this pattern.
+    pub fn matches(&self, method: &str) -> bool {
+        match self.0.strip_suffix('*') {
+            Some(prefix) => method.starts_with(prefix),
+            None => self.0 == method,
+        }
+    }
+
+    /// Returns the pattern as string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MethodPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A rule that assigns a value to all methods matching a [`MethodPattern`].
+///
+/// Parsed from `<pattern>=<value>`, e.g. `eth_getLogs=10` or `debug_*=50/5`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodRule<T> {
+    /// The methods this rule applies to.
+    pub pattern: MethodPattern,
+    /// The value assigned to the matching methods.
+    pub value: T,
+}
+
+impl<T> MethodRule<T> {
+    /// Creates a new rule.
+    pub fn new(pattern: impl Into<String>, value: T) -> Self {
+        Self { pattern: MethodPattern::new(pattern), value }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for MethodRule<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.pattern, self.value)
+    }
+}
+
+impl<T> FromStr for MethodRule<T>
+where
+    T: FromStr,
+{
+    type Err = RateLimitParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pattern, value) =
+            s.split_once('=').ok_or_else(|| RateLimitParseError::MissingValue(s.to_string()))?;
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(RateLimitParseError::EmptyPattern(s.to_string()))
+        }
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| RateLimitParseError::InvalidNumber(value.to_string()))?;
+        Ok(Self::new(pattern, value))
+    }
+}
+
+/// Errors that can occur when parsing rate limit rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitParseError {
+    /// The rule has no `=<value>` part.
+    MissingValue(String),
+    /// The rule has no method pattern.
+    EmptyPattern(String),
+    /// A numeric value could not be parsed.
+    InvalidNumber(String),
+}
+
+impl fmt::Display for RateLimitParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingValue(rule) => write!(f, "expected <method>=<value>, got {rule:?}"),
+            Self::EmptyPattern(rule) => write!(f, "missing method pattern in {rule:?}"),
+            Self::InvalidNumber(value) => write!(f, "invalid number {value:?}"),
+        }
+    }
+}
+
+impl core::error::Error for RateLimitParseError {}
+
+/// Configuration for the per-client RPC rate limiter.
+///
+/// Every client gets its own token bucket with the configured [`Self::per_client`] quota, each
+/// request draws its [cost](Self::cost_of) from that bucket. Additionally, methods matching one of
+/// the [`Self::per_method`] rules are limited by a dedicated bucket per client and rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcRateLimitConfig {
+    /// How requests are attributed to clients.
+    pub key: RateLimitKey,
+    /// The quota of every client across all methods.
+    pub per_client: TokenBucketConfig,
+    /// Additional per client quotas for specific methods.
+    ///
+    /// The first matching rule applies.
+    pub per_method: Vec<MethodRule<TokenBucketConfig>>,
+    /// The cost of methods that are more expensive than a regular call.
+    ///
+    /// The first matching rule applies, all other methods cost a single token.
+    pub costs: Vec<MethodRule<u32>>,
+    /// The maximum number of clients that are tracked at the same time.
+    ///
+    /// If exceeded, the least recently seen client is evicted and starts with a full bucket the
+    /// next time it is seen.
+    pub max_tracked_clients: u32,
+    /// The reverse proxies whose `X-Forwarded-For` and `X-Real-IP` headers are honoured.
+    ///
+    /// The headers can be set by any client, so they are ignored for requests that were not
+    /// received from one of these addresses.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl RpcRateLimitConfig {
+    /// Returns the default costs for expensive methods.
+    pub fn default_costs() -> Vec<MethodRule<u32>> {
+        vec![
+            MethodRule::new("debug_*", DEFAULT_DEBUG_COST),
+            MethodRule::new("trace_*", DEFAULT_TRACE_COST),
+            MethodRule::new("eth_getLogs", DEFAULT_GET_LOGS_COST),
+        ]
+    }
+
+    /// Sets how requests are attributed to clients.
+    pub const fn with_key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Sets the quota of every client.
+    pub const fn with_per_client(mut self, per_client: TokenBucketConfig) -> Self {
+        self.per_client = per_client;
+        self
+    }
+
+    /// Adds a per method quota.
+    pub fn with_method_limit(mut self, rule: MethodRule<TokenBucketConfig>) -> Self {
+        self.per_method.push(rule);
+        self
+    }
+
+    /// Adds a method cost, this takes precedence over all previously configured costs.
+    pub fn with_method_cost(mut self, rule: MethodRule<u32>) -> Self {
+        self.costs.insert(0, rule);
+        self
+    }
+
+    /// Sets the maximum number of tracked clients.
+    pub const fn with_max_tracked_clients(mut self, max: u32) -> Self {
+        self.max_tracked_clients = max;
+        self
+    }
+
+    /// Sets the reverse proxies whose forwarding headers are honoured.
+    pub fn with_trusted_proxies(mut self, proxies: impl IntoIterator<Item = IpAddr>) -> Self {
+        self.trusted_proxies = proxies.into_iter().collect();
+        self
+    }
+
+    /// Returns the number of tokens a call to the given method costs.
+    pub fn cost_of(&self, method: &str) -> u32 {
+        self.costs.iter().find(|rule| rule.pattern.matches(method)).map_or(1, |rule| rule.value)
+    }
+
+    /// Returns the index and quota of the per method rule that applies to the given method, if
+    /// any.
+    pub fn method_limit(&self, method: &str) -> Option<(usize, TokenBucketConfig)> {
+        self.per_method
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.pattern.matches(method))
+            .map(|(idx, rule)| (idx, rule.value))
+    }
+}
+
+impl Default for RpcRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            key: RateLimitKey::default(),
+            per_client: TokenBucketConfig::default(),
+            per_method: Vec::new(),
+            costs: Self::default_costs(),
+            max_tracked_clients: crate::constants::rate_limit::DEFAULT_MAX_TRACKED_CLIENTS,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bucket() {
+        assert_eq!("10".parse::<TokenBucketConfig>().unwrap(), TokenBucketConfig::new(10, 10));
+        assert_eq!("100/5".parse::<TokenBucketConfig>().unwrap(), TokenBucketConfig::new(100, 5));
+        assert!("a/5".parse::<TokenBucketConfig>().is_err());
+    }
+
+    #[test]
+    fn parse_rules() {
+        let rule = "trace_*=50".parse::<MethodRule<u32>>().unwrap();
+        assert_eq!(rule, MethodRule::new("trace_*", 50));
+        assert_eq!(rule.to_string(), "trace_*=50");
+
+        let rule = "eth_call=100/10".parse::<MethodRule<TokenBucketConfig>>().unwrap();
+        assert_eq!(rule.value, TokenBucketConfig::new(100, 10));
+
+        assert!("eth_call".parse::<MethodRule<u32>>().is_err());
+        assert!("=5".parse::<MethodRule<u32>>().is_err());
+    }
+
+    #[test]
+    fn method_costs() {
+        let config = RpcRateLimitConfig::default();
+        assert_eq!(config.cost_of("eth_blockNumber"), 1);
+        assert_eq!(config.cost_of("eth_getLogs"), DEFAULT_GET_LOGS_COST);
+        assert_eq!(config.cost_of("debug_traceTransaction"), DEFAULT_DEBUG_COST);
+        assert_eq!(config.cost_of("trace_block"), DEFAULT_TRACE_COST);
+
+        let config = config.with_method_cost(MethodRule::new("debug_traceCall", 100));
+        assert_eq!(config.cost_of("debug_traceCall"), 100);
+        assert_eq!(config.cost_of("debug_traceTransaction"), DEFAULT_DEBUG_COST);
+    }
+
+    #[test]
+    fn method_limits() {
+        let config = RpcRateLimitConfig::default()
+            .with_method_limit(MethodRule::new("eth_call", TokenBucketConfig::new(5, 1)))
+            .with_method_limit(MethodRule::new("eth_*", TokenBucketConfig::new(50, 10)));
+        assert_eq!(config.method_limit("eth_call"), Some((0, TokenBucketConfig::new(5, 1))));
+        assert_eq!(config.method_limit("eth_chainId"), Some((1, TokenBucketConfig::new(50, 10))));
+        assert_eq!(config.method_limit("net_version"), None);
+    }
+
+    #[test]
+    fn parse_key() {
+        assert_eq!("ip".parse::<RateLimitKey>().unwrap(), RateLimitKey::Ip);
+        assert_eq!("jwt".parse::<RateLimitKey>().unwrap(), RateLimitKey::Jwt);
+        assert_eq!(RateLimitKey::Connection.to_string(), "connection");
+    }
+}
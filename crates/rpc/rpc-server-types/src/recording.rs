@@ -0,0 +1,126 @@
+//! Types of the RPC traffic recorder.
+//!
+//! Recordings are directories of newline delimited JSON files, one [`RecordedCall`] per line.
+//! Every transport is recorded to its own files, named `<transport>-<sequence>.jsonl`, which are
+//! rotated once they exceed the configured size.
+
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::path::PathBuf;
+
+/// The file extension of recording files.
+pub const RECORDING_FILE_EXTENSION: &str = "jsonl";
+
+/// The transport a call was received on.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    strum::EnumString,
+    strum::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum RecordedTransport {
+    /// HTTP, this includes WS if both are served on the same port.
+    Http,
+    /// WebSocket.
+    Ws,
+    /// IPC.
+    Ipc,
+}
+
+/// A single recorded call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedCall {
+    /// Unix timestamp in microseconds at which the call was received.
+    pub timestamp_us: u64,
+    /// The transport the call was received on.
+    pub transport: RecordedTransport,
+    /// The called method.
+    pub method: String,
+    /// The parameters of the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Box<RawValue>>,
+    /// The result of the call, if it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Box<RawValue>>,
+    /// The error of the call, if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Box<RawValue>>,
+    /// The time it took to answer the call, in microseconds.
+    pub latency_us: u64,
+}
+
+/// Configuration of the RPC traffic recorder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcRecorderConfig {
+    /// The directory the recording files are written to.
+    pub dir: PathBuf,
+    /// The fraction of calls that are recorded, between `0.0` and `1.0`.
+    pub sample_rate: f64,
+    /// Whether results and errors are recorded, otherwise only requests are recorded.
+    pub record_responses: bool,
+    /// The size in bytes after which a recording file is rotated.
+    pub max_file_bytes: u64,
+    /// The maximum number of files kept per transport, older files are removed.
+    pub max_files: usize,
+}
+
+impl RpcRecorderConfig {
+    /// Creates a new configuration that records all calls to the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            sample_rate: 1.0,
+            record_responses: true,
+            max_file_bytes: crate::constants::recorder::DEFAULT_MAX_FILE_BYTES,
+            max_files: crate::constants::recorder::DEFAULT_MAX_FILES,
+        }
+    }
+
+    /// Sets the fraction of calls that are recorded.
+    pub const fn with_sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Sets whether results and errors are recorded.
+    pub const fn with_record_responses(mut self, record_responses: bool) -> Self {
+        self.record_responses = record_responses;
+        self
+    }
+
+    /// Sets the size after which a recording file is rotated.
+    pub const fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = max_file_bytes;
+        self
+    }
+
+    /// Sets the maximum number of files kept per transport.
+    pub const fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_call_serde() {
+        let line = r#"{"timestampUs":1,"transport":"ws","method":"eth_getBalance","params":["0x0000000000000000000000000000000000000000","latest"],"result":"0x0","latencyUs":42}"#;
+        let call: RecordedCall = serde_json::from_str(line).unwrap();
+        assert_eq!(call.transport, RecordedTransport::Ws);
+        assert_eq!(call.result.as_ref().unwrap().get(), r#""0x0""#);
+        assert!(call.error.is_none());
+        assert_eq!(serde_json::to_string(&call).unwrap(), line);
+    }
+}
@@ -0,0 +1,65 @@
+//! Configuration types for the RPC response cache.
+
+use crate::constants::response_cache::{DEFAULT_MAX_DISK_BYTES, DEFAULT_MAX_MEMORY_BYTES};
+use std::path::PathBuf;
+
+/// Configuration of the RPC response cache.
+///
+/// The cache stores the results of calls that only depend on finalized chain data, such as
+/// `eth_getBlockByNumber` for a finalized block or `debug_traceTransaction` of a finalized
+/// transaction. These responses can never change, so they can be served from the cache without
+/// re-executing the call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcResponseCacheConfig {
+    /// Maximum number of bytes of cached responses kept in memory.
+    pub max_memory_bytes: u64,
+    /// Configuration of the on-disk tier, if enabled.
+    ///
+    /// Responses that are evicted from memory, or that are too large for the memory tier, are
+    /// written to disk.
+    pub disk: Option<ResponseCacheDiskConfig>,
+}
+
+impl RpcResponseCacheConfig {
+    /// Sets the memory budget.
+    pub const fn with_max_memory_bytes(mut self, max_memory_bytes: u64) -> Self {
+        self.max_memory_bytes = max_memory_bytes;
+        self
+    }
+
+    /// Enables the on-disk tier.
+    pub fn with_disk(mut self, disk: Option<ResponseCacheDiskConfig>) -> Self {
+        self.disk = disk;
+        self
+    }
+}
+
+impl Default for RpcResponseCacheConfig {
+    fn default() -> Self {
+        Self { max_memory_bytes: DEFAULT_MAX_MEMORY_BYTES, disk: None }
+    }
+}
+
+/// Configuration of the on-disk tier of the RPC response cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseCacheDiskConfig {
+    /// The directory the responses are stored in.
+    ///
+    /// The directory is owned by the cache and cleared on startup.
+    pub dir: PathBuf,
+    /// Maximum number of bytes of cached responses kept on disk.
+    pub max_bytes: u64,
+}
+
+impl ResponseCacheDiskConfig {
+    /// Creates a new disk configuration with the default budget.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), max_bytes: DEFAULT_MAX_DISK_BYTES }
+    }
+
+    /// Sets the disk budget.
+    pub const fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
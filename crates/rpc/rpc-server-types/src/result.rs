@@ -107,6 +107,7 @@ impl_to_rpc_result!(ConsensusError);
 impl_to_rpc_result!(reth_errors::RethError);
 impl_to_rpc_result!(reth_errors::ProviderError);
 impl_to_rpc_result!(reth_network_api::NetworkError);
+impl_to_rpc_result!(reth_network_api::private_relay::PrivateRelayError);
 
 /// Constructs an invalid params JSON-RPC error.
 pub fn invalid_params_rpc_err(
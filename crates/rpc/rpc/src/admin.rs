@@ -1,4 +1,11 @@
-use std::sync::Arc;
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use alloy_genesis::ChainConfig;
 use alloy_primitives::keccak256;
@@ -9,11 +16,16 @@ use alloy_rpc_types_admin::{
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use reth_chainspec::{EthChainSpec, EthereumHardfork, EthereumHardforks, ForkCondition};
-use reth_network_api::{NetworkInfo, Peers};
+use reth_network_api::{BanEntry, BanTarget, NetworkInfo, Peers};
 use reth_network_peers::{AnyNode, NodeRecord};
 use reth_network_types::PeerKind;
-use reth_rpc_api::AdminApiServer;
-use reth_rpc_server_types::ToRpcResult;
+use reth_rpc_api::{AdminApiServer, AdminBackupApiServer, BackupInfo, PeerBandwidth};
+use reth_rpc_server_types::{
+    result::{internal_rpc_err, invalid_params_rpc_err},
+    ToRpcResult,
+};
+use reth_storage_api::StorageBackupProvider;
+use reth_tasks::Runtime;
 use reth_transaction_pool::TransactionPool;
 
 /// `admin` API implementation.
@@ -89,6 +101,36 @@ where
         Ok(true)
     }
 
+    /// Handler for `admin_addBan`
+    async fn add_ban(
+        &self,
+        target: BanTarget,
+        reason: Option<String>,
+        duration: Option<u64>,
+    ) -> RpcResult<bool> {
+        let mut entry = BanEntry::new(target);
+        entry.reason = reason;
+        if let Some(duration) = duration {
+            entry = entry.with_duration(Duration::from_secs(duration));
+            if entry.expires_at.is_none() {
+                return Err(invalid_params_rpc_err(format!("ban duration too large: {duration}s")))
+            }
+        }
+        self.network.add_ban(entry).await.to_rpc_result()
+    }
+
+    /// Handler for `admin_removeBan`
+    async fn remove_ban(&self, target: BanTarget) -> RpcResult<bool> {
+        self.network.remove_ban(target).await.to_rpc_result()
+    }
+
+    /// Handler for `admin_bans`
+    async fn bans(&self) -> RpcResult<Vec<BanEntry>> {
+        let mut bans = self.network.get_bans().await.to_rpc_result()?;
+        bans.sort_unstable_by_key(|entry| entry.banned_at);
+        Ok(bans)
+    }
+
     /// Handler for `admin_peers`
     async fn peers(&self) -> RpcResult<Vec<PeerInfo>> {
         let peers = self.network.get_all_peers().await.to_rpc_result()?;
@@ -119,6 +161,21 @@ where
         Ok(infos)
     }
 
+    /// Handler for `admin_peerBandwidth`
+    async fn peer_bandwidth(&self) -> RpcResult<Vec<PeerBandwidth>> {
+        let peers = self.network.get_all_peers().await.to_rpc_result()?;
+        Ok(peers
+            .into_iter()
+            .filter_map(|peer| {
+                Some(PeerBandwidth {
+                    id: alloy_primitives::hex::encode(keccak256(peer.remote_id.as_slice())),
+                    bandwidth: peer.bandwidth?,
+                    enode: peer.enode,
+                })
+            })
+            .collect())
+    }
+
     /// Handler for `admin_nodeInfo`
     async fn node_info(&self) -> RpcResult<NodeInfo> {
         let enode = self.network.local_node_record();
@@ -218,3 +275,110 @@ impl<N, ChainSpec, Pool> std::fmt::Debug for AdminApi<N, ChainSpec, Pool> {
         f.debug_struct("AdminApi").finish_non_exhaustive()
     }
 }
+
+/// `admin_backup` implementation.
+///
+/// Backups run on a blocking task, since copying the storage of a synced node can take a long
+/// time. Only one backup runs at a time, and backups are only written to new subdirectories of
+/// the configured backup directory.
+pub struct AdminBackupApi<Provider> {
+    /// Provider with access to the node's storage.
+    provider: Provider,
+    /// Directory the backups are written to.
+    backup_dir: PathBuf,
+    /// Whether a backup is currently running.
+    running: Arc<AtomicBool>,
+    /// Spawner for the blocking backup task.
+    task_spawner: Runtime,
+}
+
+impl<Provider> AdminBackupApi<Provider> {
+    /// Creates a new instance of `AdminBackupApi` writing backups into `backup_dir`.
+    pub fn new(provider: Provider, backup_dir: PathBuf, task_spawner: Runtime) -> Self {
+        Self { provider, backup_dir, running: Default::default(), task_spawner }
+    }
+}
+
+/// Resolves the destination of a backup named `name` in `backup_dir`.
+///
+/// The name must be a relative path without `..` components, and the destination must not exist
+/// yet.
+fn backup_destination(backup_dir: &Path, name: &Path) -> RpcResult<PathBuf> {
+    if name.as_os_str().is_empty() ||
+        !name.components().all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(invalid_params_rpc_err(format!(
+            "backup path {} must be relative to the backup directory",
+            name.display()
+        )))
+    }
+    let dest = backup_dir.join(name);
+    if dest.exists() {
+        return Err(invalid_params_rpc_err(format!("backup path {} already exists", dest.display())))
+    }
+    Ok(dest)
+}
+
+/// Clears the running flag of [`AdminBackupApi`] once the backup task finishes.
+struct RunningBackup(Arc<AtomicBool>);
+
+impl Drop for RunningBackup {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+#[async_trait]
+impl<Provider> AdminBackupApiServer for AdminBackupApi<Provider>
+where
+    Provider: StorageBackupProvider + Clone + 'static,
+{
+    /// Handler for `admin_backup`
+    async fn backup(&self, path: PathBuf) -> RpcResult<BackupInfo> {
+        let dest = backup_destination(&self.backup_dir, &path)?;
+        if self.running.swap(true, Ordering::AcqRel) {
+            return Err(internal_rpc_err("a backup is already running"))
+        }
+        // the flag is cleared by the blocking task, which keeps running if the call is dropped
+        let running = RunningBackup(self.running.clone());
+
+        let provider = self.provider.clone();
+        let backup = self
+            .task_spawner
+            .spawn_blocking(move || {
+                let _running = running;
+                provider.backup_storage(&dest)
+            })
+            .await
+            .map_err(|err| internal_rpc_err(err.to_string()))?
+            .to_rpc_result()?;
+        Ok(BackupInfo { path: backup.path, block: backup.block })
+    }
+}
+
+impl<Provider> std::fmt::Debug for AdminBackupApi<Provider> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminBackupApi")
+            .field("backup_dir", &self.backup_dir)
+            .field("running", &self.running)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_destination_stays_in_backup_dir() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(
+            backup_destination(dir, Path::new("backups/reth")).unwrap(),
+            dir.join("backups/reth")
+        );
+        for name in ["", "src", "../escape", "nested/../../escape", "/absolute"] {
+            assert!(backup_destination(dir, Path::new(name)).is_err(), "{name}");
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! Shared implementation of the `debug_subscribeBlockTraces` and `trace_subscribeBlockTraces`
+//! subscriptions.
+
+use alloy_consensus::BlockHeader;
+use futures::{future::BoxFuture, stream::FuturesOrdered, FutureExt, Stream, StreamExt};
+use jsonrpsee::{SubscriptionMessage, SubscriptionSink};
+use parking_lot::Mutex;
+use reth_chain_state::CanonStateNotification;
+use reth_primitives_traits::{NodePrimitives, RecoveredBlock};
+use reth_rpc_api::BlockTracesNotification;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    future::Future,
+    hash::Hash,
+    sync::Arc,
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, error};
+
+/// The maximum number of blocks of a feed that are traced but not yet sent.
+///
+/// Once reached, no further blocks are traced until the oldest pending block has been traced and
+/// sent. This is also the number of notifications a subscription can fall behind its feed before
+/// it is closed.
+const MAX_PENDING_BLOCKS: usize = 64;
+
+/// Error message of the block trace subscriptions if they are disabled.
+pub(crate) const BLOCK_TRACE_SUBSCRIPTIONS_DISABLED: &str =
+    "block trace subscriptions are disabled, enable them with --rpc.block-trace-subscriptions";
+
+type FeedSender<T> = broadcast::Sender<Arc<BlockTracesNotification<T>>>;
+
+/// The block trace feeds of an API, keyed by the tracing options of their subscriptions.
+///
+/// The engine's execution results carry no traces, so a feed re-executes every committed block
+/// with its tracer on top of the parent state. Each block is traced once per feed and the traces
+/// are sent to all subscriptions with the same options. A feed is spawned by its first
+/// subscription and stops once it has no subscriptions left.
+#[derive(Debug)]
+pub(crate) struct BlockTraceFeeds<K, T> {
+    feeds: Arc<Mutex<HashMap<K, FeedSender<T>>>>,
+}
+
+impl<K, T> Default for BlockTraceFeeds<K, T> {
+    fn default() -> Self {
+        Self { feeds: Default::default() }
+    }
+}
+
+impl<K, T> Clone for BlockTraceFeeds<K, T> {
+    fn clone(&self) -> Self {
+        Self { feeds: self.feeds.clone() }
+    }
+}
+
+impl<K, T> BlockTraceFeeds<K, T>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    T: Serialize + Send + Sync + 'static,
+{
+    /// Sends the block traces of the feed for `key` to the sink until the subscription is closed.
+    ///
+    /// If there is no feed for `key` yet, one is spawned that traces the blocks of `canon_state`
+    /// with the futures returned by `trace_block`, which are expected to run the tracing on the
+    /// blocking tracing pool.
+    pub(crate) async fn pipe<N, S, E, F, Fut>(
+        &self,
+        sink: SubscriptionSink,
+        key: K,
+        canon_state: S,
+        trace_block: F,
+    ) where
+        N: NodePrimitives,
+        S: Stream<Item = CanonStateNotification<N>> + Unpin + Send + 'static,
+        E: Display + Send + 'static,
+        F: FnMut(Arc<RecoveredBlock<N::Block>>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+    {
+        let mut traces = self.subscribe(key, canon_state, trace_block);
+        loop {
+            let item = tokio::select! {
+                _ = sink.closed() => break,
+                item = traces.recv() => match item {
+                    Ok(item) => item,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!(target: "rpc", skipped, "Block trace subscription lagged behind");
+                        break
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            let msg = match SubscriptionMessage::new(
+                sink.method_name(),
+                sink.subscription_id(),
+                &*item,
+            ) {
+                Ok(msg) => msg,
+                Err(err) => {
+                    error!(target: "rpc", %err, "Failed to serialize subscription message");
+                    break
+                }
+            };
+            if sink.send(msg).await.is_err() {
+                break
+            }
+        }
+    }
+
+    /// Subscribes to the feed for `key`, spawning the feed if there is none.
+    fn subscribe<N, S, E, F, Fut>(
+        &self,
+        key: K,
+        canon_state: S,
+        trace_block: F,
+    ) -> broadcast::Receiver<Arc<BlockTracesNotification<T>>>
+    where
+        N: NodePrimitives,
+        S: Stream<Item = CanonStateNotification<N>> + Unpin + Send + 'static,
+        E: Display + Send + 'static,
+        F: FnMut(Arc<RecoveredBlock<N::Block>>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+    {
+        let mut feeds = self.feeds.lock();
+        if let Some(feed) = feeds.get(&key) {
+            return feed.subscribe()
+        }
+
+        let (feed, traces) = broadcast::channel(MAX_PENDING_BLOCKS);
+        feeds.insert(key.clone(), feed.clone());
+        tokio::spawn(run_feed(self.clone(), key, feed, canon_state, trace_block));
+        traces
+    }
+
+    /// Removes the feed for `key` if it has no subscriptions left, returns `true` if it was
+    /// removed.
+    ///
+    /// New subscriptions are added under the same lock, so a removed feed is never subscribed to.
+    fn remove_if_unused(&self, key: &K, feed: &FeedSender<T>) -> bool {
+        let mut feeds = self.feeds.lock();
+        if feed.receiver_count() > 0 {
+            return false
+        }
+        feeds.remove(key);
+        true
+    }
+}
+
+/// Traces the blocks of every canonical state notification and sends the traces to the feed.
+///
+/// Up to [`MAX_PENDING_BLOCKS`] blocks are traced concurrently, and the results are sent in
+/// order. Blocks that were reverted by a notification are announced first, from the tip
+/// downwards, followed by the traces of the committed blocks in ascending order.
+async fn run_feed<K, T, N, S, E, F, Fut>(
+    feeds: BlockTraceFeeds<K, T>,
+    key: K,
+    feed: FeedSender<T>,
+    mut canon_state: S,
+    mut trace_block: F,
+) where
+    K: Hash + Eq + Clone + Send + 'static,
+    T: Serialize + Send + Sync + 'static,
+    N: NodePrimitives,
+    S: Stream<Item = CanonStateNotification<N>> + Unpin,
+    E: Display + Send + 'static,
+    F: FnMut(Arc<RecoveredBlock<N::Block>>) -> Fut,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+{
+    // blocks of consumed notifications that are not being traced yet
+    let mut queued = VecDeque::new();
+    let mut pending = FuturesOrdered::<BoxFuture<'static, BlockTracesNotification<T>>>::new();
+    loop {
+        while pending.len() < MAX_PENDING_BLOCKS &&
+            let Some(block) = queued.pop_front()
+        {
+            let item = match block {
+                BlockNotification::Removed(block) => futures::future::ready(removed(block)).boxed(),
+                BlockNotification::Committed(block) => {
+                    committed(block.clone(), trace_block(block)).boxed()
+                }
+            };
+            pending.push_back(item);
+        }
+
+        tokio::select! {
+            notification = canon_state.next(), if queued.is_empty() => {
+                let Some(notification) = notification else { break };
+                if feeds.remove_if_unused(&key, &feed) {
+                    return
+                }
+                queued.extend(block_notifications(&notification));
+            }
+            Some(item) = pending.next() => {
+                if feed.send(Arc::new(item)).is_err() && feeds.remove_if_unused(&key, &feed) {
+                    return
+                }
+            }
+        }
+    }
+
+    feeds.feeds.lock().remove(&key);
+}
+
+/// Returns the notification for a block added to the canonical chain, once it has been traced.
+async fn committed<B, T, E>(
+    block: Arc<RecoveredBlock<B>>,
+    traces: impl Future<Output = Result<T, E>>,
+) -> BlockTracesNotification<T>
+where
+    B: reth_primitives_traits::Block,
+    E: Display,
+{
+    let (traces, error) = match traces.await {
+        Ok(traces) => (Some(traces), None),
+        Err(err) => {
+            debug!(
+                target: "rpc",
+                %err,
+                block = %block.hash(),
+                "Failed to trace block for subscription"
+            );
+            (None, Some(err.to_string()))
+        }
+    };
+    BlockTracesNotification::Committed {
+        number: block.number(),
+        hash: block.hash(),
+        parent_hash: block.parent_hash(),
+        traces,
+        error,
+    }
+}
+
+/// A block level event of a canonical state notification.
+#[derive(Debug)]
+enum BlockNotification<B: reth_primitives_traits::Block> {
+    /// The block was removed from the canonical chain.
+    Removed(Arc<RecoveredBlock<B>>),
+    /// The block was added to the canonical chain.
+    Committed(Arc<RecoveredBlock<B>>),
+}
+
+/// Splits the notification into block events, in the order they should be emitted.
+fn block_notifications<N: NodePrimitives>(
+    notification: &CanonStateNotification<N>,
+) -> Vec<BlockNotification<N::Block>> {
+    let removed = notification
+        .reverted()
+        .map(|old| old.blocks_iter().rev().cloned().map(BlockNotification::Removed).collect())
+        .unwrap_or_default();
+    let committed = notification.committed();
+    removed
+        .into_iter()
+        .chain(committed.blocks_iter().cloned().map(BlockNotification::Committed))
+        .collect()
+}
+
+/// Returns the notification for a block removed from the canonical chain.
+fn removed<B: reth_primitives_traits::Block, T>(
+    block: Arc<RecoveredBlock<B>>,
+) -> BlockTracesNotification<T> {
+    BlockTracesNotification::Removed {
+        number: block.number(),
+        hash: block.hash(),
+        parent_hash: block.parent_hash(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_ethereum_primitives::{Block, EthPrimitives};
+    use reth_execution_types::{Chain, ExecutionOutcome};
+    use std::{
+        collections::BTreeMap,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    fn chain(numbers: std::ops::RangeInclusive<u64>) -> Arc<Chain> {
+        let blocks = numbers
+            .map(|number| {
+                let mut block: RecoveredBlock<reth_ethereum_primitives::Block> = Default::default();
+                block.set_block_number(number);
+                block
+            })
+            .collect();
+        Arc::new(Chain::new(blocks, ExecutionOutcome::default(), BTreeMap::new()))
+    }
+
+    fn numbers(events: &[BlockNotification<reth_ethereum_primitives::Block>]) -> Vec<(bool, u64)> {
+        events
+            .iter()
+            .map(|event| match event {
+                BlockNotification::Removed(block) => (false, block.number()),
+                BlockNotification::Committed(block) => (true, block.number()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commit_emits_blocks_in_order() {
+        let notification = CanonStateNotification::Commit { new: chain(1..=3) };
+        assert_eq!(
+            numbers(&block_notifications(&notification)),
+            vec![(true, 1), (true, 2), (true, 3)]
+        );
+    }
+
+    #[test]
+    fn reorg_emits_removed_blocks_first() {
+        let notification = CanonStateNotification::Reorg { old: chain(2..=3), new: chain(2..=4) };
+        assert_eq!(
+            numbers(&block_notifications(&notification)),
+            vec![(false, 3), (false, 2), (true, 2), (true, 3), (true, 4)]
+        );
+    }
+
+    #[tokio::test]
+    async fn traces_each_block_once_for_all_subscriptions() {
+        let feeds = BlockTraceFeeds::<(), u64>::default();
+        let (notifications_tx, notifications) = mpsc::unbounded_channel();
+        let traced = Arc::new(AtomicUsize::new(0));
+        let trace_block = {
+            let traced = traced.clone();
+            move |block: Arc<RecoveredBlock<Block>>| {
+                traced.fetch_add(1, Ordering::Relaxed);
+                futures::future::ready(Ok::<_, String>(block.number() * 10))
+            }
+        };
+
+        let mut first =
+            feeds.subscribe((), UnboundedReceiverStream::new(notifications), trace_block);
+        let mut second = feeds.subscribe(
+            (),
+            futures::stream::pending::<CanonStateNotification<EthPrimitives>>(),
+            |_| futures::future::ready(Ok::<_, String>(0)),
+        );
+        notifications_tx.send(CanonStateNotification::Commit { new: chain(1..=3) }).unwrap();
+
+        for traces in [&mut first, &mut second] {
+            for expected in 1..=3 {
+                let item = traces.recv().await.unwrap();
+                let BlockTracesNotification::Committed { number, traces, .. } = &*item else {
+                    panic!("expected committed block")
+                };
+                assert_eq!((*number, *traces), (expected, Some(expected * 10)));
+            }
+        }
+        assert_eq!(traced.load(Ordering::Relaxed), 3);
+
+        drop((first, second));
+        notifications_tx.send(CanonStateNotification::Commit { new: chain(4..=4) }).unwrap();
+        while !feeds.feeds.lock().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(traced.load(Ordering::Relaxed), 3);
+    }
+}
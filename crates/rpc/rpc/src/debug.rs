@@ -1,3 +1,4 @@
+use crate::block_traces::{BlockTraceFeeds, BLOCK_TRACE_SUBSCRIPTIONS_DISABLED};
 use alloy_consensus::{constants::KECCAK_EMPTY, transaction::TxHashRef, BlockHeader};
 use alloy_eips::{eip2718::Encodable2718, BlockId, BlockNumberOrTag};
 use alloy_evm::{env::BlockEnvironment, Evm};
@@ -14,8 +15,9 @@ use alloy_rpc_types_trace::geth::{
 };
 use async_trait::async_trait;
 use futures::Stream;
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::{core::RpcResult, PendingSubscriptionSink};
 use parking_lot::RwLock;
+use reth_chain_state::CanonStateSubscriptions;
 use reth_chainspec::{ChainSpecProvider, EthChainSpec, EthereumHardforks};
 use reth_engine_primitives::ConsensusEngineEvent;
 use reth_errors::RethError;
@@ -55,6 +57,9 @@ use tokio_stream::StreamExt;
 /// This type provides the functionality for handling `debug` related requests.
 pub struct DebugApi<Eth: RpcNodeCore> {
     inner: Arc<DebugApiInner<Eth>>,
+    /// Feeds of `debug_subscribeBlockTraces` keyed by the serialized tracing options, `None` if
+    /// the subscription is disabled.
+    block_trace_feeds: Option<BlockTraceFeeds<String, Vec<TraceResult>>>,
 }
 
 impl<Eth> DebugApi<Eth>
@@ -86,7 +91,16 @@ where
             }
         });
 
-        Self { inner }
+        Self { inner, block_trace_feeds: None }
+    }
+
+    /// Configures whether `debug_subscribeBlockTraces` is enabled.
+    ///
+    /// Disabled by default, since every new canonical block is re-executed once for each distinct
+    /// set of tracing options that is subscribed to.
+    pub fn with_block_trace_subscriptions(mut self, enabled: bool) -> Self {
+        self.block_trace_feeds = enabled.then(BlockTraceFeeds::default);
+        self
     }
 
     /// Access the underlying `Eth` API.
@@ -217,6 +231,21 @@ where
         self.trace_block(block, evm_env, opts).await
     }
 
+    /// Replays a block that was just added to the canonical chain and returns the trace of each
+    /// transaction.
+    ///
+    /// Unlike [`Self::debug_trace_block`] the block is not looked up, the block announced by the
+    /// engine is re-executed with the tracer on top of its parent state. The block trace
+    /// subscriptions call this once per block for all subscriptions with the same options.
+    pub async fn debug_trace_canonical_block(
+        &self,
+        block: Arc<RecoveredBlock<ProviderBlock<Eth::Provider>>>,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>, Eth::Error> {
+        let evm_env = self.eth_api().evm_env_for_header(block.sealed_block().sealed_header())?;
+        self.trace_block(block, evm_env, opts).await
+    }
+
     /// Trace the transaction according to the provided options.
     ///
     /// Ref: <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers>
@@ -895,6 +924,34 @@ where
         Err(internal_rpc_err("unimplemented"))
     }
 
+    /// Handler for `debug_subscribeBlockTraces`
+    async fn debug_subscribe_block_traces(
+        &self,
+        pending: PendingSubscriptionSink,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let Some(feeds) = &self.block_trace_feeds else {
+            pending.reject(internal_rpc_err(BLOCK_TRACE_SUBSCRIPTIONS_DISABLED)).await;
+            return Ok(())
+        };
+        let opts = opts.unwrap_or_default();
+        let key = serde_json::to_string(&opts)?;
+        let sink = pending.accept().await?;
+        let canon_state = self.provider().canonical_state_stream();
+        let this = self.clone();
+        feeds
+            .pipe(sink, key, canon_state, move |block| {
+                let this = this.clone();
+                let opts = opts.clone();
+                async move {
+                    let _permit = this.acquire_trace_permit().await;
+                    this.debug_trace_canonical_block(block, opts).await
+                }
+            })
+            .await;
+        Ok(())
+    }
+
     /// Handler for `debug_traceBlock`
     async fn debug_trace_block(
         &self,
@@ -1222,7 +1279,7 @@ impl<Eth: RpcNodeCore> std::fmt::Debug for DebugApi<Eth> {
 
 impl<Eth: RpcNodeCore> Clone for DebugApi<Eth> {
     fn clone(&self) -> Self {
-        Self { inner: Arc::clone(&self.inner) }
+        Self { inner: Arc::clone(&self.inner), block_trace_feeds: self.block_trace_feeds.clone() }
     }
 }
 
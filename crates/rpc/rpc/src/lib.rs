@@ -27,12 +27,14 @@
 
 mod admin;
 mod aliases;
+mod block_traces;
 mod debug;
 mod engine;
 pub mod eth;
 mod miner;
 mod net;
 mod otterscan;
+mod relay;
 mod reth;
 mod rpc;
 mod testing;
@@ -41,7 +43,7 @@ mod txpool;
 mod validation;
 mod web3;
 
-pub use admin::AdminApi;
+pub use admin::{AdminApi, AdminBackupApi};
 pub use aliases::*;
 pub use debug::DebugApi;
 pub use engine::{EngineApi, EngineEthApi};
@@ -49,6 +51,7 @@ pub use eth::{helpers::SyncListener, EthApi, EthApiBuilder, EthBundle, EthFilter
 pub use miner::MinerApi;
 pub use net::NetApi;
 pub use otterscan::OtterscanApi;
+pub use relay::RelayApi;
 pub use reth::RethApi;
 pub use reth_rpc_convert::RpcTypes;
 pub use rpc::RPCApi;
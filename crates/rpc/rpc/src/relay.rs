@@ -0,0 +1,116 @@
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Bytes, U64};
+use async_trait::async_trait;
+use jsonrpsee::{core::RpcResult, PendingSubscriptionSink, SubscriptionMessage};
+use reth_network_api::private_relay::{PrivateBundle, PrivateRelayHandle, RelayAck};
+use reth_primitives_traits::SignedTransaction;
+use reth_rpc_api::{RelayApiServer, RelayBundle};
+use reth_rpc_eth_types::{utils::recover_raw_transaction, EthResult};
+use reth_rpc_server_types::ToRpcResult;
+use reth_tasks::Runtime;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error};
+
+/// `relay` API implementation.
+///
+/// Forwards private transactions and bundles over the private relay of the network.
+pub struct RelayApi<T> {
+    /// Handle to the private relay.
+    relay: PrivateRelayHandle<T>,
+    /// Spawner for the bundle subscriptions.
+    task_spawner: Runtime,
+}
+
+impl<T> RelayApi<T> {
+    /// Creates a new instance of `RelayApi`.
+    pub const fn new(relay: PrivateRelayHandle<T>, task_spawner: Runtime) -> Self {
+        Self { relay, task_spawner }
+    }
+}
+
+impl<T: SignedTransaction> RelayApi<T> {
+    /// Decodes the raw transactions and checks their signatures.
+    fn decode_transactions(transactions: &[Bytes]) -> RpcResult<Vec<T>> {
+        let transactions = transactions
+            .iter()
+            .map(|tx| recover_raw_transaction::<T>(tx).map(|tx| tx.into_inner()))
+            .collect::<EthResult<_>>()?;
+        Ok(transactions)
+    }
+}
+
+#[async_trait]
+impl<T: SignedTransaction> RelayApiServer for RelayApi<T> {
+    /// Handler for `relay_sendRawTransactions`
+    async fn send_raw_transactions(&self, transactions: Vec<Bytes>) -> RpcResult<Vec<RelayAck>> {
+        let transactions = Self::decode_transactions(&transactions)?;
+        self.relay.send_transactions(transactions).await.to_rpc_result()
+    }
+
+    /// Handler for `relay_sendBundle`
+    async fn send_bundle(
+        &self,
+        transactions: Vec<Bytes>,
+        block_number: U64,
+    ) -> RpcResult<Vec<RelayAck>> {
+        let transactions = Self::decode_transactions(&transactions)?;
+        let bundle = PrivateBundle { transactions, block_number: block_number.to() };
+        self.relay.send_bundle(bundle).await.to_rpc_result()
+    }
+
+    /// Handler for `relay_subscribeBundles`
+    async fn subscribe_bundles(
+        &self,
+        pending: PendingSubscriptionSink,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let sink = pending.accept().await?;
+        let mut bundles = self.relay.subscribe_bundles();
+        self.task_spawner.spawn_task(async move {
+            loop {
+                let received = tokio::select! {
+                    _ = sink.closed() => break,
+                    received = bundles.recv() => match received {
+                        Ok(received) => received,
+                        Err(RecvError::Lagged(skipped)) => {
+                            debug!(target: "rpc::relay", skipped, "Bundle subscription lagged");
+                            continue
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+                let bundle = RelayBundle {
+                    peer_id: received.peer_id,
+                    block_number: U64::from(received.bundle.block_number),
+                    transactions: received
+                        .bundle
+                        .transactions
+                        .iter()
+                        .map(|tx| tx.encoded_2718().into())
+                        .collect(),
+                };
+                let msg = match SubscriptionMessage::new(
+                    sink.method_name(),
+                    sink.subscription_id(),
+                    &bundle,
+                ) {
+                    Ok(msg) => msg,
+                    Err(err) => {
+                        error!(target: "rpc::relay", %err, "Failed to serialize bundle");
+                        break
+                    }
+                };
+                if sink.send(msg).await.is_err() {
+                    break
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl<T> std::fmt::Debug for RelayApi<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelayApi").finish_non_exhaustive()
+    }
+}
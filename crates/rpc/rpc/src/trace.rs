@@ -1,3 +1,4 @@
+use crate::block_traces::{BlockTraceFeeds, BLOCK_TRACE_SUBSCRIPTIONS_DISABLED};
 use alloy_consensus::BlockHeader as _;
 use alloy_eips::BlockId;
 use alloy_evm::block::calc::{base_block_reward_pre_merge, block_reward, ommer_reward};
@@ -17,10 +18,11 @@ use alloy_rpc_types_trace::{
 };
 use async_trait::async_trait;
 use futures::StreamExt;
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::{core::RpcResult, PendingSubscriptionSink};
+use reth_chain_state::CanonStateSubscriptions;
 use reth_chainspec::{ChainSpecProvider, EthereumHardforks};
 use reth_evm::ConfigureEvm;
-use reth_primitives_traits::{BlockBody, BlockHeader};
+use reth_primitives_traits::{BlockBody, BlockHeader, RecoveredBlock};
 use reth_rpc_api::TraceApiServer;
 use reth_rpc_convert::RpcTxReq;
 use reth_rpc_eth_api::{
@@ -28,7 +30,8 @@ use reth_rpc_eth_api::{
     FromEthApiError, RpcNodeCore,
 };
 use reth_rpc_eth_types::{error::EthApiError, utils::recover_raw_transaction, EthConfig};
-use reth_storage_api::{BlockNumReader, BlockReader};
+use reth_rpc_server_types::result::internal_rpc_err;
+use reth_storage_api::{BlockNumReader, BlockReader, ProviderBlock};
 use reth_tasks::pool::BlockingTaskGuard;
 use reth_transaction_pool::{PoolPooledTx, PoolTransaction, TransactionPool};
 use revm::DatabaseCommit;
@@ -51,6 +54,8 @@ const TRACE_FILTER_FETCH_CHUNK_SIZE: usize = 16;
 /// This type provides the functionality for handling `trace` related requests.
 pub struct TraceApi<Eth> {
     inner: Arc<TraceApiInner<Eth>>,
+    /// The feed of `trace_subscribeBlockTraces`, `None` if the subscription is disabled.
+    block_trace_feeds: Option<BlockTraceFeeds<(), Vec<LocalizedTransactionTrace>>>,
 }
 
 // === impl TraceApi ===
@@ -63,7 +68,16 @@ impl<Eth> TraceApi<Eth> {
         eth_config: EthConfig,
     ) -> Self {
         let inner = Arc::new(TraceApiInner { eth_api, blocking_task_guard, eth_config });
-        Self { inner }
+        Self { inner, block_trace_feeds: None }
+    }
+
+    /// Configures whether `trace_subscribeBlockTraces` is enabled.
+    ///
+    /// Disabled by default, since every new canonical block is re-executed while there are
+    /// subscriptions.
+    pub fn with_block_trace_subscriptions(mut self, enabled: bool) -> Self {
+        self.block_trace_feeds = enabled.then(BlockTraceFeeds::default);
+        self
     }
 
     /// Acquires a permit to execute a tracing call.
@@ -506,6 +520,28 @@ where
             return Err(EthApiError::HeaderNotFound(block_id).into());
         };
 
+        self.trace_recovered_block(block_id, block).await
+    }
+
+    /// Returns the parity traces of a block that was just added to the canonical chain.
+    ///
+    /// Unlike [`Self::trace_block`] the block is not looked up, the block announced by the engine
+    /// is re-executed with the tracer on top of its parent state. The block trace subscriptions
+    /// call this once per block for all subscriptions.
+    pub async fn trace_canonical_block(
+        &self,
+        block: Arc<RecoveredBlock<ProviderBlock<Eth::Provider>>>,
+    ) -> Result<Vec<LocalizedTransactionTrace>, Eth::Error> {
+        let traces = self.trace_recovered_block(block.hash().into(), block).await?;
+        Ok(traces.unwrap_or_default())
+    }
+
+    /// Returns the parity traces of all transactions in the block, including the reward traces.
+    async fn trace_recovered_block(
+        &self,
+        block_id: BlockId,
+        block: Arc<RecoveredBlock<ProviderBlock<Eth::Provider>>>,
+    ) -> Result<Option<Vec<LocalizedTransactionTrace>>, Eth::Error> {
         let mut traces = self
             .eth_api()
             .trace_block_with(
@@ -754,6 +790,30 @@ where
         Ok(Self::trace_block(self, block_id).await.map_err(Into::into)?)
     }
 
+    /// Handler for `trace_subscribeBlockTraces`
+    async fn trace_subscribe_block_traces(
+        &self,
+        pending: PendingSubscriptionSink,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let Some(feeds) = &self.block_trace_feeds else {
+            pending.reject(internal_rpc_err(BLOCK_TRACE_SUBSCRIPTIONS_DISABLED)).await;
+            return Ok(())
+        };
+        let sink = pending.accept().await?;
+        let canon_state = self.provider().canonical_state_stream();
+        let this = self.clone();
+        feeds
+            .pipe(sink, (), canon_state, move |block| {
+                let this = this.clone();
+                async move {
+                    let _permit = this.acquire_trace_permit().await;
+                    this.trace_canonical_block(block).await
+                }
+            })
+            .await;
+        Ok(())
+    }
+
     /// Handler for `trace_filter`
     ///
     /// This is similar to `eth_getLogs` but for traces.
@@ -809,7 +869,7 @@ impl<Eth> std::fmt::Debug for TraceApi<Eth> {
 }
 impl<Eth> Clone for TraceApi<Eth> {
     fn clone(&self) -> Self {
-        Self { inner: Arc::clone(&self.inner) }
+        Self { inner: Arc::clone(&self.inner), block_trace_feeds: self.block_trace_feeds.clone() }
     }
 }
 
@@ -2,18 +2,19 @@ use core::fmt;
 use std::collections::BTreeMap;
 
 use alloy_consensus::Transaction;
-use alloy_primitives::Address;
+use alloy_primitives::{Address, TxHash};
 use alloy_rpc_types_txpool::{
     TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolInspectSummary, TxpoolStatus,
 };
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use reth_primitives_traits::NodePrimitives;
-use reth_rpc_api::TxPoolApiServer;
+use reth_rpc_api::{TxPoolApiServer, TxpoolTransaction};
 use reth_rpc_convert::{RpcConvert, RpcTypes};
 use reth_rpc_eth_api::RpcTransaction;
 use reth_transaction_pool::{
-    AllPoolTransactions, PoolConsensusTx, PoolTransaction, TransactionPool,
+    AllPoolTransactions, PoolConsensusTx, PoolTransaction, TransactionHistory, TransactionPool,
+    ValidPoolTransaction,
 };
 use tracing::trace;
 
@@ -39,13 +40,18 @@ where
     Pool: TransactionPool<Transaction: PoolTransaction<Consensus: Transaction>> + 'static,
     Eth: RpcConvert<Primitives: NodePrimitives<SignedTx = PoolConsensusTx<Pool>>>,
 {
-    fn content(&self) -> Result<TxpoolContent<RpcTransaction<Eth::Network>>, Eth::Error> {
+    fn content(
+        &self,
+    ) -> Result<TxpoolContent<TxpoolTransaction<RpcTransaction<Eth::Network>>>, Eth::Error> {
         #[inline]
         fn insert<Tx, RpcTxB>(
-            tx: &Tx,
+            tx: &ValidPoolTransaction<Tx>,
             content: &mut BTreeMap<
                 Address,
-                BTreeMap<String, <RpcTxB::Network as RpcTypes>::TransactionResponse>,
+                BTreeMap<
+                    String,
+                    TxpoolTransaction<<RpcTxB::Network as RpcTypes>::TransactionResponse>,
+                >,
             >,
             resp_builder: &RpcTxB,
         ) -> Result<(), RpcTxB::Error>
@@ -55,7 +61,10 @@ where
         {
             content.entry(tx.sender()).or_default().insert(
                 tx.nonce().to_string(),
-                resp_builder.fill_pending(tx.clone_into_consensus())?,
+                TxpoolTransaction {
+                    transaction: resp_builder.fill_pending(tx.to_consensus())?,
+                    simulation: tx.simulation(),
+                },
             );
 
             Ok(())
@@ -65,10 +74,10 @@ where
 
         let mut content = TxpoolContent::default();
         for pending in pending {
-            insert::<_, Eth>(&pending.transaction, &mut content.pending, &self.converter)?;
+            insert::<_, Eth>(&pending, &mut content.pending, &self.converter)?;
         }
         for queued in queued {
-            insert::<_, Eth>(&queued.transaction, &mut content.queued, &self.converter)?;
+            insert::<_, Eth>(&queued, &mut content.queued, &self.converter)?;
         }
 
         Ok(content)
@@ -133,7 +142,7 @@ where
     async fn txpool_content_from(
         &self,
         from: Address,
-    ) -> RpcResult<TxpoolContentFrom<RpcTransaction<Eth::Network>>> {
+    ) -> RpcResult<TxpoolContentFrom<TxpoolTransaction<RpcTransaction<Eth::Network>>>> {
         trace!(target: "rpc::eth", ?from, "Serving txpool_contentFrom");
         Ok(self.content().map_err(Into::into)?.remove_from(&from))
     }
@@ -143,10 +152,23 @@ where
     ///
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content) for more details
     /// Handler for `txpool_content`
-    async fn txpool_content(&self) -> RpcResult<TxpoolContent<RpcTransaction<Eth::Network>>> {
+    async fn txpool_content(
+        &self,
+    ) -> RpcResult<TxpoolContent<TxpoolTransaction<RpcTransaction<Eth::Network>>>> {
         trace!(target: "rpc::eth", "Serving txpool_content");
         Ok(self.content().map_err(Into::into)?)
     }
+
+    /// Returns the recorded lifecycle events of the transaction.
+    ///
+    /// Handler for `txpool_getTransactionHistory`
+    async fn txpool_get_transaction_history(
+        &self,
+        hash: TxHash,
+    ) -> RpcResult<Option<TransactionHistory>> {
+        trace!(target: "rpc::eth", ?hash, "Serving txpool_getTransactionHistory");
+        Ok(self.pool.transaction_history(hash))
+    }
 }
 
 impl<Pool, Eth> fmt::Debug for TxPoolApi<Pool, Eth> {
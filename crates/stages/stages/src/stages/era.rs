@@ -1,4 +1,4 @@
-use crate::{StageCheckpoint, StageId};
+use crate::{stages::utils::etl_collector, StageCheckpoint, StageId};
 use alloy_primitives::{BlockHash, BlockNumber};
 use futures_util::{Stream, StreamExt};
 use reqwest::{Client, Url};
@@ -173,7 +173,7 @@ impl<Header, Body, F> EraStage<Header, Body, F> {
             source,
             item: None,
             stream: None,
-            hash_collector: Collector::new(etl_config.file_size, etl_config.dir),
+            hash_collector: etl_collector(&etl_config, StageId::Era),
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::stages::utils::etl_collector;
 use alloy_primitives::{keccak256, B256};
 use itertools::Itertools;
 use reth_config::config::{EtlConfig, HashingConfig};
@@ -184,8 +185,7 @@ where
             tx.clear::<tables::HashedAccounts>()?;
 
             let mut accounts_cursor = tx.cursor_read::<RawTable<tables::PlainAccountState>>()?;
-            let mut collector =
-                Collector::new(self.etl_config.file_size, self.etl_config.dir.clone());
+            let mut collector = etl_collector(&self.etl_config, StageId::AccountHashing);
             let mut channels = Vec::with_capacity(MAXIMUM_CHANNELS);
 
             // channels used to return result of account hashing
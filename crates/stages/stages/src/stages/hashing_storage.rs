@@ -1,3 +1,4 @@
+use crate::stages::utils::etl_collector;
 use alloy_primitives::{b256, bytes::BufMut, keccak256, Address, B256};
 use itertools::Itertools;
 use reth_config::config::{EtlConfig, HashingConfig};
@@ -114,8 +115,7 @@ where
             tx.clear::<tables::HashedStorages>()?;
 
             let mut storage_cursor = tx.cursor_read::<tables::PlainStorageState>()?;
-            let mut collector =
-                Collector::new(self.etl_config.file_size, self.etl_config.dir.clone());
+            let mut collector = etl_collector(&self.etl_config, StageId::StorageHashing);
             let mut channels = Vec::with_capacity(MAXIMUM_CHANNELS);
 
             for chunk in &storage_cursor.walk(None)?.chunks(WORKER_CHUNK_SIZE) {
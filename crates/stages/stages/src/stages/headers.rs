@@ -1,3 +1,4 @@
+use crate::stages::utils::etl_collector;
 use alloy_consensus::BlockHeader;
 use alloy_primitives::{BlockHash, BlockNumber, Bytes, B256};
 use alloy_rlp::Decodable;
@@ -79,8 +80,10 @@ where
             downloader,
             tip,
             sync_gap: None,
-            hash_collector: Collector::new(etl_config.file_size / 2, etl_config.dir.clone()),
-            header_collector: Collector::new(etl_config.file_size / 2, etl_config.dir),
+            hash_collector: etl_collector(&etl_config, StageId::Headers)
+                .with_buffer_capacity(etl_config.file_size / 2),
+            header_collector: etl_collector(&etl_config, StageId::Headers)
+                .with_buffer_capacity(etl_config.file_size / 2),
             is_etl_ready: false,
         }
     }
@@ -130,6 +130,7 @@ where
                 ShardedKey::new,
                 |(index, value)| (index, value.address),
                 &self.etl_config,
+                StageId::IndexAccountHistory,
             )?
         };
 
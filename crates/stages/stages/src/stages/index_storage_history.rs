@@ -135,6 +135,7 @@ where
                 },
                 |(key, value)| (key.block_number(), AddressStorageKey((key.address(), value.key))),
                 &self.etl_config,
+                StageId::IndexStorageHistory,
             )?
         };
 
@@ -1,3 +1,4 @@
+use crate::stages::utils::etl_collector;
 use alloy_consensus::transaction::TxHashRef;
 use alloy_primitives::{TxHash, TxNumber};
 use num_traits::Zero;
@@ -119,7 +120,7 @@ where
 
         // 500MB temporary files
         let mut hash_collector: Collector<TxHash, TxNumber> =
-            Collector::new(self.etl_config.file_size, self.etl_config.dir.clone());
+            etl_collector(&self.etl_config, StageId::TransactionLookup);
 
         info!(
             target: "sync::stages::transaction_lookup",
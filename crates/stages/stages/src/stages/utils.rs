@@ -7,7 +7,7 @@ use reth_db_api::{
         sharded_key::NUM_OF_INDICES_IN_SHARD, storage_sharded_key::StorageShardedKey,
         AccountBeforeTx, AddressStorageKey, BlockNumberAddress, ShardedKey,
     },
-    table::{Decode, Decompress, Table},
+    table::{Decode, Decompress, Key, Table, Value},
     transaction::DbTx,
     BlockNumberList,
 };
@@ -17,7 +17,7 @@ use reth_provider::{
     providers::StaticFileProvider, to_range, BlockReader, DBProvider, EitherWriter, ProviderError,
     StaticFileProviderFactory,
 };
-use reth_stages_api::StageError;
+use reth_stages_api::{StageError, StageId};
 use reth_static_file_types::StaticFileSegment;
 use reth_storage_api::{ChangeSetReader, StorageChangeSetReader};
 use std::{collections::HashMap, hash::Hash, ops::RangeBounds};
@@ -26,6 +26,17 @@ use tracing::info;
 /// Number of blocks before pushing indices from cache to [`Collector`]
 const DEFAULT_CACHE_THRESHOLD: u64 = 100_000;
 
+/// Creates an ETL collector from `etl_config`, recording throughput metrics labeled with the stage.
+pub(crate) fn etl_collector<K: Key, V: Value>(
+    etl_config: &EtlConfig,
+    stage_id: StageId,
+) -> Collector<K, V> {
+    Collector::new(etl_config.file_size, etl_config.dir.clone())
+        .with_compression(etl_config.compression)
+        .with_merge_threads(etl_config.merge_threads)
+        .with_metrics(stage_id.as_str())
+}
+
 /// Collects all history (`H`) indices for a range of changesets (`CS`) and stores them in a
 /// [`Collector`].
 ///
@@ -49,6 +60,7 @@ pub(crate) fn collect_history_indices<Provider, CS, H, P>(
     sharded_key_factory: impl Fn(P, BlockNumber) -> H::Key,
     partial_key_factory: impl Fn((CS::Key, CS::Value)) -> (u64, P),
     etl_config: &EtlConfig,
+    stage_id: StageId,
 ) -> Result<Collector<H::Key, H::Value>, StageError>
 where
     Provider: DBProvider,
@@ -58,7 +70,7 @@ where
 {
     let mut changeset_cursor = provider.tx_ref().cursor_read::<CS>()?;
 
-    let mut collector = Collector::new(etl_config.file_size, etl_config.dir.clone());
+    let mut collector = etl_collector(etl_config, stage_id);
     let mut cache: HashMap<P, Vec<u64>> = HashMap::default();
 
     let mut collect = |cache: &mut HashMap<P, Vec<u64>>| {
@@ -122,7 +134,7 @@ pub(crate) fn collect_account_history_indices<Provider>(
 where
     Provider: DBProvider + ChangeSetReader + StaticFileProviderFactory,
 {
-    let mut collector = Collector::new(etl_config.file_size, etl_config.dir.clone());
+    let mut collector = etl_collector(etl_config, StageId::IndexAccountHistory);
     let mut cache: AddressMap<Vec<u64>> = AddressMap::default();
 
     let mut insert_fn = |address: Address, indices: Vec<u64>| {
@@ -178,7 +190,7 @@ pub(crate) fn collect_storage_history_indices<Provider>(
 where
     Provider: DBProvider + StorageChangeSetReader + StaticFileProviderFactory,
 {
-    let mut collector = Collector::new(etl_config.file_size, etl_config.dir.clone());
+    let mut collector = etl_collector(etl_config, StageId::IndexStorageHistory);
     let mut cache: HashMap<AddressStorageKey, Vec<u64>> = HashMap::default();
 
     let mut insert_fn = |key: AddressStorageKey, indices: Vec<u64>| {
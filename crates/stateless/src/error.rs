@@ -0,0 +1,83 @@
+use alloy_primitives::{Address, BlockNumber, B256};
+use reth_consensus::ConsensusError;
+use reth_evm::execute::BlockExecutionError;
+use reth_primitives_traits::GotExpected;
+use reth_trie_sparse::errors::{SparseStateTrieError, SparseTrieError};
+
+/// Errors returned by [`stateless_validation`](crate::stateless_validation).
+#[derive(Debug, thiserror::Error)]
+pub enum StatelessValidationError {
+    /// A witness header could not be decoded.
+    #[error("failed to decode witness header: {0}")]
+    HeaderDecode(alloy_rlp::Error),
+    /// The witness does not contain any headers.
+    #[error("witness does not contain the parent header of block {0}")]
+    MissingParentHeader(BlockNumber),
+    /// The witness headers do not form a contiguous, hash-linked chain.
+    #[error("witness header {number} is not the child of the preceding witness header")]
+    InvalidAncestorChain {
+        /// The number of the header that does not link to its predecessor.
+        number: BlockNumber,
+    },
+    /// The newest witness header is not the parent of the validated block.
+    #[error("witness headers do not end in the block parent: {0}")]
+    ParentHashMismatch(GotExpected<B256>),
+    /// The witness state nodes could not be decoded.
+    #[error("failed to decode witness state node: {0}")]
+    StateDecode(alloy_rlp::Error),
+    /// The witness does not contain the root node of the pre-state.
+    #[error("witness does not contain the pre-state root node {0}")]
+    MissingPreStateRoot(B256),
+    /// The witness does not prove the value or absence of an account.
+    #[error("witness is incomplete for account {0}")]
+    MissingAccount(Address),
+    /// The witness does not prove the value or absence of a storage slot.
+    #[error("witness is incomplete for storage slot {slot} of account {address}")]
+    MissingStorage {
+        /// The account address.
+        address: Address,
+        /// The storage slot.
+        slot: B256,
+    },
+    /// The witness does not contain a bytecode referenced by an account.
+    #[error("witness does not contain bytecode {0}")]
+    MissingBytecode(B256),
+    /// The witness does not contain the header of a block whose hash was requested.
+    #[error("witness does not contain the header of block {0}")]
+    MissingBlockHash(BlockNumber),
+    /// The witness is missing trie nodes required to apply a state change.
+    #[error(
+        "witness is missing trie nodes to update account {hashed_address}{}",
+        hashed_slot.map(|slot| format!(" storage slot {slot}")).unwrap_or_default()
+    )]
+    IncompletePostState {
+        /// The hashed address of the updated account.
+        hashed_address: B256,
+        /// The hashed storage slot, if the update was a storage update.
+        hashed_slot: Option<B256>,
+    },
+    /// A leaf value in the witness is not a valid account or storage value.
+    #[error("invalid trie leaf for {hashed_key}: {error}")]
+    InvalidLeaf {
+        /// The hashed key of the leaf.
+        hashed_key: B256,
+        /// The decoding error.
+        error: alloy_rlp::Error,
+    },
+    /// Revealing or updating the sparse state trie failed.
+    #[error(transparent)]
+    SparseStateTrie(#[from] SparseStateTrieError),
+    /// Updating a sparse trie failed.
+    #[error(transparent)]
+    SparseTrie(#[from] SparseTrieError),
+    /// The block failed consensus validation, including post-execution checks of gas used,
+    /// receipts root, logs bloom and requests hash.
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+    /// The block failed to execute.
+    #[error(transparent)]
+    Execution(#[from] BlockExecutionError),
+    /// The post-state root computed from the witness does not match the block header.
+    #[error("post-state root mismatch: {0}")]
+    StateRootMismatch(GotExpected<B256>),
+}
@@ -0,0 +1,33 @@
+//! Stateless block validation.
+//!
+//! Validates a block without access to a database, using only an [`ExecutionWitness`] as
+//! produced by `debug_executionWitness`:
+//!
+//! - the witness `headers` must form a hash-linked chain that ends in the block's parent,
+//! - the witness `state` nodes are revealed into a sparse trie rooted in the parent's state root,
+//! - the block is executed against the revealed state and the witness `codes`,
+//! - receipts, gas and the post-state root are checked against the block header.
+//!
+//! See [`stateless_validation`].
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod error;
+pub use error::StatelessValidationError;
+
+mod trie;
+pub use trie::StatelessTrie;
+
+mod validation;
+pub use validation::stateless_validation;
+
+mod witness_db;
+pub use witness_db::WitnessDatabase;
+
+pub use alloy_rpc_types_debug::ExecutionWitness;
@@ -0,0 +1,302 @@
+use crate::StatelessValidationError;
+use alloy_primitives::{keccak256, map::B256Map, Address, B256, U256};
+use alloy_rlp::{Decodable, Encodable};
+use alloy_rpc_types_debug::ExecutionWitness;
+use reth_primitives_traits::Account;
+use reth_trie_common::{DecodedMultiProofV2, HashedPostState, TrieAccount, EMPTY_ROOT_HASH};
+use reth_trie_sparse::{LeafUpdate, RevealableSparseTrie, SparseStateTrie, TrieNodeEpoch};
+
+/// Epoch assigned to trie nodes modified while applying the post-state.
+const POST_STATE_EPOCH: TrieNodeEpoch = TrieNodeEpoch::new(1);
+
+/// A sparse state trie revealed from the state nodes of an [`ExecutionWitness`].
+///
+/// Reads of accounts and storage slots only succeed if the witness proves either the value or its
+/// absence. Paths that end in a node missing from the witness are reported as errors instead of
+/// being treated as empty.
+#[derive(Debug)]
+pub struct StatelessTrie {
+    trie: SparseStateTrie,
+}
+
+impl StatelessTrie {
+    /// Reveals the witness state nodes reachable from `pre_state_root`.
+    ///
+    /// Nodes that are not reachable from the root are ignored.
+    pub fn new(
+        witness: &ExecutionWitness,
+        pre_state_root: B256,
+    ) -> Result<Self, StatelessValidationError> {
+        let mut trie = SparseStateTrie::new();
+        if pre_state_root == EMPTY_ROOT_HASH {
+            *trie.trie_mut() = RevealableSparseTrie::revealed_empty();
+            return Ok(Self { trie })
+        }
+
+        let nodes: B256Map<_> = witness.state.iter().map(|node| (keccak256(node), node)).collect();
+        if !nodes.contains_key(&pre_state_root) {
+            return Err(StatelessValidationError::MissingPreStateRoot(pre_state_root))
+        }
+
+        let multiproof = DecodedMultiProofV2::from_witness(pre_state_root, &nodes)
+            .map_err(StatelessValidationError::StateDecode)?;
+        trie.reveal_decoded_multiproof_v2(multiproof)?;
+
+        Ok(Self { trie })
+    }
+
+    /// Returns the pre-state account, or `None` if the witness proves that it does not exist.
+    pub fn account(
+        &self,
+        address: Address,
+    ) -> Result<Option<TrieAccount>, StatelessValidationError> {
+        self.revealed_account(keccak256(address))?
+            .ok_or(StatelessValidationError::MissingAccount(address))
+    }
+
+    /// Returns the pre-state value of a storage slot.
+    pub fn storage(&self, address: Address, slot: B256) -> Result<U256, StatelessValidationError> {
+        let Some(account) = self.account(address)? else { return Ok(U256::ZERO) };
+        if account.storage_root == EMPTY_ROOT_HASH {
+            return Ok(U256::ZERO)
+        }
+
+        let hashed_address = keccak256(address);
+        let hashed_slot = keccak256(slot);
+        if let Some(value) = self.trie.get_storage_slot_value(&hashed_address, &hashed_slot) {
+            return decode_leaf(hashed_slot, value)
+        }
+
+        if self.trie.check_valid_storage_witness(hashed_address, hashed_slot) {
+            Ok(U256::ZERO)
+        } else {
+            Err(StatelessValidationError::MissingStorage { address, slot })
+        }
+    }
+
+    /// Applies the post-state to the revealed trie and returns the resulting state root.
+    ///
+    /// Fails with [`StatelessValidationError::IncompletePostState`] if an update touches a node
+    /// that is not part of the witness, e.g. the sibling of a removed leaf.
+    pub fn calculate_state_root(
+        &mut self,
+        state: &HashedPostState,
+    ) -> Result<B256, StatelessValidationError> {
+        let mut changed_storages = Vec::with_capacity(state.storages.len());
+        for (&hashed_address, storage) in &state.storages {
+            if !storage.wiped && storage.storage.is_empty() {
+                continue
+            }
+            changed_storages.push(hashed_address);
+
+            // Storage tries of wiped accounts and of accounts without storage are not part of the
+            // witness, so they start out as empty revealed tries.
+            let pre_account = self.revealed_account(hashed_address)?;
+            if storage.wiped ||
+                pre_account.is_some_and(|account| {
+                    account.is_none_or(|a| a.storage_root == EMPTY_ROOT_HASH)
+                })
+            {
+                self.trie
+                    .insert_storage_trie(hashed_address, RevealableSparseTrie::revealed_empty());
+            }
+
+            let mut updates: B256Map<_> = storage
+                .storage
+                .iter()
+                .map(|(&slot, value)| {
+                    let encoded = if value.is_zero() {
+                        Vec::new()
+                    } else {
+                        alloy_rlp::encode_fixed_size(value).to_vec()
+                    };
+                    (slot, LeafUpdate::Changed(encoded))
+                })
+                .collect();
+            self.trie
+                .get_or_create_storage_trie_mut(hashed_address)
+                .update_leaves(&mut updates, |_, _| {})?;
+            if let Some(&hashed_slot) = updates.keys().next() {
+                return Err(StatelessValidationError::IncompletePostState {
+                    hashed_address,
+                    hashed_slot: Some(hashed_slot),
+                })
+            }
+        }
+
+        let mut account_updates =
+            B256Map::with_capacity_and_hasher(state.accounts.len(), Default::default());
+        let mut account_rlp_buf = Vec::new();
+        for (&hashed_address, &account) in &state.accounts {
+            let incomplete = || StatelessValidationError::IncompletePostState {
+                hashed_address,
+                hashed_slot: None,
+            };
+            let storage_root = if changed_storages.contains(&hashed_address) {
+                self.trie.storage_root(&hashed_address, POST_STATE_EPOCH).ok_or_else(incomplete)?
+            } else {
+                self.revealed_account(hashed_address)?
+                    .ok_or_else(incomplete)?
+                    .map_or(EMPTY_ROOT_HASH, |account| account.storage_root)
+            };
+
+            let encoded = encode_account_leaf_value(account, storage_root, &mut account_rlp_buf);
+            account_updates.insert(hashed_address, LeafUpdate::Changed(encoded));
+        }
+
+        self.trie.trie_mut().update_leaves(&mut account_updates, |_, _| {})?;
+        if let Some(&hashed_address) = account_updates.keys().next() {
+            return Err(StatelessValidationError::IncompletePostState {
+                hashed_address,
+                hashed_slot: None,
+            })
+        }
+
+        Ok(self.trie.root(POST_STATE_EPOCH)?)
+    }
+
+    /// Returns the pre-state account at the hashed address.
+    ///
+    /// The outer option is `None` if the witness proves neither the account nor its absence.
+    fn revealed_account(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Option<Option<TrieAccount>>, StatelessValidationError> {
+        if let Some(value) = self.trie.get_account_value(&hashed_address) {
+            return decode_leaf(hashed_address, value).map(|account| Some(Some(account)))
+        }
+        Ok(self.trie.is_account_revealed(hashed_address).then_some(None))
+    }
+}
+
+/// Decodes an account or storage leaf value.
+fn decode_leaf<T: Decodable>(
+    hashed_key: B256,
+    value: &[u8],
+) -> Result<T, StatelessValidationError> {
+    T::decode(&mut &value[..])
+        .map_err(|error| StatelessValidationError::InvalidLeaf { hashed_key, error })
+}
+
+/// Encodes the account leaf value, returning an empty value if the leaf should be removed.
+fn encode_account_leaf_value(
+    account: Option<Account>,
+    storage_root: B256,
+    account_rlp_buf: &mut Vec<u8>,
+) -> Vec<u8> {
+    if account.is_none_or(|account| account.is_empty()) && storage_root == EMPTY_ROOT_HASH {
+        return Vec::new()
+    }
+
+    account_rlp_buf.clear();
+    account.unwrap_or_default().into_trie_account(storage_root).encode(account_rlp_buf);
+    account_rlp_buf.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, Bytes};
+    use reth_trie_common::{proof::ProofRetainer, HashBuilder, HashedStorage, Nibbles};
+
+    /// Builds the state root and a witness containing every node of the given state.
+    fn state_with_witness(
+        accounts: &[(Address, Account, Vec<(B256, U256)>)],
+    ) -> (B256, ExecutionWitness) {
+        let mut nodes = Vec::new();
+        let mut leaves = Vec::new();
+        for (address, account, storage) in accounts {
+            let mut storage: Vec<_> =
+                storage.iter().map(|(slot, value)| (keccak256(slot), *value)).collect();
+            storage.sort_unstable_by_key(|(slot, _)| *slot);
+
+            let storage_root = if storage.is_empty() {
+                EMPTY_ROOT_HASH
+            } else {
+                let paths = storage.iter().map(|(slot, _)| Nibbles::unpack(slot));
+                let mut hb =
+                    HashBuilder::default().with_proof_retainer(ProofRetainer::from_iter(paths));
+                for (slot, value) in &storage {
+                    hb.add_leaf(Nibbles::unpack(slot), &alloy_rlp::encode_fixed_size(value));
+                }
+                let root = hb.root();
+                nodes.extend(
+                    hb.take_proof_nodes().into_nodes_sorted().into_iter().map(|(_, node)| node),
+                );
+                root
+            };
+            leaves.push((keccak256(address), account.into_trie_account(storage_root)));
+        }
+        leaves.sort_unstable_by_key(|(hashed_address, _)| *hashed_address);
+
+        let paths = leaves.iter().map(|(hashed_address, _)| Nibbles::unpack(hashed_address));
+        let mut hb = HashBuilder::default().with_proof_retainer(ProofRetainer::from_iter(paths));
+        for (hashed_address, account) in &leaves {
+            hb.add_leaf(Nibbles::unpack(hashed_address), &alloy_rlp::encode(account));
+        }
+        let root = hb.root();
+        nodes.extend(hb.take_proof_nodes().into_nodes_sorted().into_iter().map(|(_, node)| node));
+
+        (root, ExecutionWitness { state: nodes, ..Default::default() })
+    }
+
+    fn account(balance: u64) -> Account {
+        Account { nonce: 1, balance: U256::from(balance), bytecode_hash: None }
+    }
+
+    #[test]
+    fn reads_and_updates_revealed_state() {
+        let alice = address!("0x00000000000000000000000000000000000a11ce");
+        let bob = address!("0x0000000000000000000000000000000000000b0b");
+        let carol = address!("0x00000000000000000000000000000000000ca201");
+        let slot = B256::with_last_byte(1);
+
+        let (root, witness) = state_with_witness(&[
+            (alice, account(100), vec![(slot, U256::from(7))]),
+            (bob, account(200), vec![]),
+        ]);
+        let mut trie = StatelessTrie::new(&witness, root).unwrap();
+
+        assert_eq!(trie.account(bob).unwrap().unwrap().balance, U256::from(200));
+        assert_eq!(trie.account(carol).unwrap(), None);
+        assert_eq!(trie.storage(alice, slot).unwrap(), U256::from(7));
+        assert_eq!(trie.storage(alice, B256::with_last_byte(2)).unwrap(), U256::ZERO);
+
+        let mut post_state = HashedPostState::default();
+        post_state.accounts.insert(keccak256(alice), Some(account(50)));
+        post_state.accounts.insert(keccak256(carol), Some(account(50)));
+        post_state
+            .storages
+            .insert(keccak256(alice), HashedStorage::from_iter([(keccak256(slot), U256::from(8))]));
+
+        let (expected, _) = state_with_witness(&[
+            (alice, account(50), vec![(slot, U256::from(8))]),
+            (bob, account(200), vec![]),
+            (carol, account(50), vec![]),
+        ]);
+        assert_eq!(trie.calculate_state_root(&post_state).unwrap(), expected);
+    }
+
+    #[test]
+    fn reports_incomplete_witness() {
+        let alice = address!("0x00000000000000000000000000000000000a11ce");
+        let bob = address!("0x0000000000000000000000000000000000000b0b");
+
+        let (root, mut witness) =
+            state_with_witness(&[(alice, account(100), vec![]), (bob, account(200), vec![])]);
+        // Keep only the root node, blinding both account leaves.
+        witness.state.retain(|node: &Bytes| keccak256(node) == root);
+        let trie = StatelessTrie::new(&witness, root).unwrap();
+
+        assert!(matches!(
+            trie.account(alice),
+            Err(StatelessValidationError::MissingAccount(address)) if address == alice
+        ));
+
+        witness.state.clear();
+        assert!(matches!(
+            StatelessTrie::new(&witness, root),
+            Err(StatelessValidationError::MissingPreStateRoot(missing)) if missing == root
+        ));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{StatelessTrie, StatelessValidationError, WitnessDatabase};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{
+    keccak256,
+    map::{B256Map, HashMap},
+};
+use alloy_rlp::Decodable;
+use alloy_rpc_types_debug::ExecutionWitness;
+use reth_consensus::FullConsensus;
+use reth_evm::{
+    execute::{BlockExecutionOutput, Executor},
+    ConfigureEvm,
+};
+use reth_primitives_traits::{
+    Bytecode, GotExpected, HeaderTy, NodePrimitives, ReceiptTy, RecoveredBlock, SealedHeader,
+};
+use reth_revm::database::StateProviderDatabase;
+use reth_trie_common::{HashedPostState, KeccakKeyHasher};
+
+/// Validates a block using only the state contained in an [`ExecutionWitness`].
+///
+/// The witness `headers` must be a contiguous chain of RLP-encoded ancestors ending in the block's
+/// parent. The parent's state root anchors the witness `state` nodes, and the ancestor hashes
+/// serve `BLOCKHASH` lookups.
+///
+/// The block is validated against its parent by `consensus`, executed with `evm_config` and checked
+/// for gas used, receipts root, logs bloom, requests hash and the post-state root. Every failure is
+/// reported as a distinct [`StatelessValidationError`] variant, so a mismatch can be told apart
+/// from an incomplete witness.
+///
+/// Returns the execution output of the block on success.
+pub fn stateless_validation<E, C>(
+    block: &RecoveredBlock<<E::Primitives as NodePrimitives>::Block>,
+    witness: &ExecutionWitness,
+    evm_config: &E,
+    consensus: &C,
+) -> Result<BlockExecutionOutput<ReceiptTy<E::Primitives>>, StatelessValidationError>
+where
+    E: ConfigureEvm,
+    C: FullConsensus<E::Primitives>,
+{
+    let mut ancestors = witness
+        .headers
+        .iter()
+        .map(|rlp| {
+            HeaderTy::<E::Primitives>::decode(&mut rlp.as_ref()).map(SealedHeader::seal_slow)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(StatelessValidationError::HeaderDecode)?;
+    ancestors.sort_unstable_by_key(|header| header.number());
+
+    for pair in ancestors.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        if next.parent_hash() != prev.hash() || next.number() != prev.number() + 1 {
+            return Err(StatelessValidationError::InvalidAncestorChain { number: next.number() })
+        }
+    }
+    let parent =
+        ancestors.last().ok_or(StatelessValidationError::MissingParentHeader(block.number()))?;
+    if parent.hash() != block.parent_hash() {
+        return Err(StatelessValidationError::ParentHashMismatch(GotExpected {
+            got: parent.hash(),
+            expected: block.parent_hash(),
+        }))
+    }
+
+    consensus.validate_header(block.sealed_header())?;
+    consensus.validate_header_against_parent(block.sealed_header(), parent)?;
+    consensus.validate_block_pre_execution(block.sealed_block())?;
+
+    let mut trie = StatelessTrie::new(witness, parent.state_root())?;
+    let bytecodes: B256Map<_> = witness
+        .codes
+        .iter()
+        .map(|code| (keccak256(code), Bytecode::new_raw(code.clone())))
+        .collect();
+    let block_hashes: HashMap<_, _> =
+        ancestors.iter().map(|header| (header.number(), header.hash())).collect();
+
+    let db = WitnessDatabase::new(&trie, &bytecodes, &block_hashes);
+    let output = evm_config.executor(StateProviderDatabase::new(db)).execute(block)?;
+
+    consensus.validate_block_post_execution(block, &output.result, None, None)?;
+
+    let hashed_state = HashedPostState::from_bundle_state::<KeccakKeyHasher>(&output.state.state);
+    let state_root = trie.calculate_state_root(&hashed_state)?;
+    if state_root != block.state_root() {
+        return Err(StatelessValidationError::StateRootMismatch(GotExpected {
+            got: state_root,
+            expected: block.state_root(),
+        }))
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{constants::ETH_TO_WEI, Header, TxEip1559, TxReceipt};
+    use alloy_genesis::{Genesis, GenesisAccount};
+    use alloy_primitives::{bytes, logs_bloom, Address, Bytes, TxKind, B256, U256};
+    use reth_chainspec::{ChainSpec, ChainSpecBuilder, EthChainSpec, MAINNET};
+    use reth_db_common::init::init_genesis;
+    use reth_ethereum_consensus::EthBeaconConsensus;
+    use reth_ethereum_primitives::{Block, BlockBody, Transaction};
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_primitives_traits::{
+        crypto::secp256k1::public_key_to_address,
+        proofs::{calculate_receipt_root, calculate_transaction_root},
+    };
+    use reth_provider::{
+        test_utils::create_test_provider_factory_with_chain_spec, HeaderProvider,
+        StateProviderFactory, StateRootProvider,
+    };
+    use reth_revm::witness::ExecutionWitnessRecord;
+    use reth_testing_utils::generators::{self, generate_key, sign_tx_with_key_pair};
+    use reth_trie_common::ExecutionWitnessMode;
+    use std::sync::Arc;
+
+    /// Increments storage slot 0 on every call.
+    const COUNTER_CODE: Bytes = bytes!("60016000540160005500");
+
+    const COUNTER_ADDRESS: Address = Address::new([0x42; 20]);
+
+    /// Builds a block with a transfer and a counter call on top of genesis and generates its
+    /// execution witness the same way `debug_executionWitness` does.
+    fn block_with_witness() -> (Arc<ChainSpec>, RecoveredBlock<Block>, ExecutionWitness) {
+        let key_pair = generate_key(&mut generators::rng());
+        let signer = public_key_to_address(key_pair.public_key());
+
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::default()
+                .chain(MAINNET.chain)
+                .genesis(Genesis {
+                    gas_limit: 30_000_000,
+                    alloc: [
+                        (
+                            signer,
+                            GenesisAccount {
+                                balance: U256::from(ETH_TO_WEI),
+                                ..Default::default()
+                            },
+                        ),
+                        (
+                            COUNTER_ADDRESS,
+                            GenesisAccount {
+                                code: Some(COUNTER_CODE),
+                                storage: Some([(B256::ZERO, B256::with_last_byte(1))].into()),
+                                ..Default::default()
+                            },
+                        ),
+                    ]
+                    .into(),
+                    ..MAINNET.genesis.clone()
+                })
+                .paris_activated()
+                .build(),
+        );
+        let provider_factory = create_test_provider_factory_with_chain_spec(chain_spec.clone());
+        init_genesis(&provider_factory).unwrap();
+        let genesis = provider_factory.sealed_header(0).unwrap().unwrap();
+
+        let timestamp = genesis.timestamp + 12;
+        let base_fee = chain_spec.next_block_base_fee(genesis.header(), timestamp).unwrap();
+        let tx = |nonce, to, gas_limit| {
+            sign_tx_with_key_pair(
+                key_pair,
+                Transaction::Eip1559(TxEip1559 {
+                    chain_id: chain_spec.chain.id(),
+                    nonce,
+                    gas_limit,
+                    max_fee_per_gas: base_fee as u128,
+                    to: TxKind::Call(to),
+                    value: U256::from(1),
+                    ..Default::default()
+                }),
+            )
+        };
+        let transactions =
+            vec![tx(0, Address::new([0x11; 20]), 21_000), tx(1, COUNTER_ADDRESS, 50_000)];
+
+        let mut header = Header {
+            parent_hash: genesis.hash(),
+            number: 1,
+            gas_limit: genesis.gas_limit,
+            base_fee_per_gas: Some(base_fee),
+            timestamp,
+            transactions_root: calculate_transaction_root(&transactions),
+            ..Default::default()
+        };
+        let body = BlockBody { transactions, ..Default::default() };
+        let senders = vec![signer; body.transactions.len()];
+
+        let state_provider = provider_factory.latest().unwrap();
+        let mut witness = None;
+        let output = EthEvmConfig::new(chain_spec.clone())
+            .executor(StateProviderDatabase::new(&*state_provider))
+            .execute_with_state_closure(
+                &RecoveredBlock::new_unhashed(
+                    Block::new(header.clone(), body.clone()),
+                    senders.clone(),
+                ),
+                |state| {
+                    witness = Some(ExecutionWitnessRecord::new(state).into_execution_witness(
+                        state.database.0,
+                        &provider_factory,
+                        header.number,
+                        ExecutionWitnessMode::Legacy,
+                    ));
+                },
+            )
+            .unwrap();
+
+        let hashed_state =
+            HashedPostState::from_bundle_state::<KeccakKeyHasher>(&output.state.state);
+        header.state_root = state_provider.state_root(hashed_state).unwrap();
+        header.gas_used = output.gas_used;
+        header.receipts_root = calculate_receipt_root(
+            &output.receipts.iter().map(|receipt| receipt.with_bloom_ref()).collect::<Vec<_>>(),
+        );
+        header.logs_bloom = logs_bloom(output.receipts.iter().flat_map(|receipt| receipt.logs()));
+
+        let block = RecoveredBlock::new_unhashed(Block::new(header, body), senders);
+        (chain_spec, block, witness.unwrap().unwrap())
+    }
+
+    #[test]
+    fn validate_block_from_execution_witness() {
+        let (chain_spec, block, witness) = block_with_witness();
+        let evm_config = EthEvmConfig::new(chain_spec.clone());
+        let consensus = EthBeaconConsensus::new(chain_spec);
+
+        let output = stateless_validation(&block, &witness, &evm_config, &consensus).unwrap();
+        assert_eq!(output.gas_used, block.gas_used());
+        assert_eq!(output.receipts.len(), 2);
+    }
+
+    #[test]
+    fn reject_tampered_execution_witness() {
+        let (chain_spec, block, witness) = block_with_witness();
+        let evm_config = EthEvmConfig::new(chain_spec.clone());
+        let consensus = EthBeaconConsensus::new(chain_spec);
+        let validate = |witness: &ExecutionWitness| {
+            stateless_validation(&block, witness, &evm_config, &consensus)
+        };
+        let parent = Header::decode(&mut witness.headers[0].as_ref()).unwrap();
+
+        // a forged parent header no longer hashes to the block's parent hash
+        let mut tampered = witness.clone();
+        let forged = Header { extra_data: bytes!("ff"), ..parent.clone() };
+        tampered.headers[0] = alloy_rlp::encode(&forged).into();
+        assert!(matches!(
+            validate(&tampered),
+            Err(StatelessValidationError::ParentHashMismatch(_))
+        ));
+
+        // the pre-state root node is missing
+        let mut tampered = witness.clone();
+        tampered.state.retain(|node| keccak256(node) != parent.state_root);
+        assert!(matches!(
+            validate(&tampered),
+            Err(StatelessValidationError::MissingPreStateRoot(root)) if root == parent.state_root
+        ));
+
+        // only the pre-state root node is left, so the accounts can't be read
+        let mut tampered = witness.clone();
+        tampered.state.retain(|node| keccak256(node) == parent.state_root);
+        assert!(matches!(validate(&tampered), Err(StatelessValidationError::Execution(_))));
+
+        // the counter bytecode is missing
+        let mut tampered = witness;
+        tampered.codes.retain(|code| code != &COUNTER_CODE);
+        assert!(matches!(validate(&tampered), Err(StatelessValidationError::Execution(_))));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{StatelessTrie, StatelessValidationError};
+use alloy_primitives::{
+    map::{B256Map, HashMap},
+    Address, BlockNumber, StorageKey, StorageValue, B256,
+};
+use reth_primitives_traits::{Account, Bytecode};
+use reth_revm::database::EvmStateProvider;
+use reth_storage_errors::provider::{ProviderError, ProviderResult};
+
+/// An [`EvmStateProvider`] that serves state exclusively from an execution witness.
+///
+/// Accounts and storage are read from a [`StatelessTrie`], bytecodes and block hashes from the
+/// witness `codes` and `headers`. Reads the witness cannot answer fail with
+/// [`ProviderError::TrieWitnessError`] instead of returning empty values, so an incomplete witness
+/// can never make execution succeed with wrong state.
+#[derive(Debug)]
+pub struct WitnessDatabase<'a> {
+    trie: &'a StatelessTrie,
+    bytecodes: &'a B256Map<Bytecode>,
+    block_hashes: &'a HashMap<BlockNumber, B256>,
+}
+
+impl<'a> WitnessDatabase<'a> {
+    /// Creates a new witness database.
+    pub const fn new(
+        trie: &'a StatelessTrie,
+        bytecodes: &'a B256Map<Bytecode>,
+        block_hashes: &'a HashMap<BlockNumber, B256>,
+    ) -> Self {
+        Self { trie, bytecodes, block_hashes }
+    }
+}
+
+impl EvmStateProvider for WitnessDatabase<'_> {
+    fn basic_account(&self, address: &Address) -> ProviderResult<Option<Account>> {
+        Ok(self.trie.account(*address).map_err(witness_error)?.map(Into::into))
+    }
+
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.block_hashes
+            .get(&number)
+            .copied()
+            .map(Some)
+            .ok_or_else(|| witness_error(StatelessValidationError::MissingBlockHash(number)))
+    }
+
+    fn bytecode_by_hash(&self, code_hash: &B256) -> ProviderResult<Option<Bytecode>> {
+        self.bytecodes
+            .get(code_hash)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| witness_error(StatelessValidationError::MissingBytecode(*code_hash)))
+    }
+
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        self.trie.storage(account, storage_key).map(Some).map_err(witness_error)
+    }
+}
+
+fn witness_error(error: StatelessValidationError) -> ProviderError {
+    ProviderError::TrieWitnessError(error.to_string())
+}
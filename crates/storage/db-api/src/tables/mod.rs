@@ -18,7 +18,7 @@ pub use raw::{RawDupSort, RawKey, RawTable, RawValue, TableRawRow};
 
 use crate::{
     models::{
-        accounts::BlockNumberAddress,
+        accounts::{BlockNumberAddress, BlockNumberHashedAddress},
         blocks::{HeaderHash, StoredBlockOmmers},
         storage_sharded_key::StorageShardedKey,
         AccountBeforeTx, ClientVersion, CompactU256, IntegerList, ShardedKey,
@@ -34,7 +34,7 @@ use reth_prune_types::{PruneCheckpoint, PruneSegment};
 use reth_stages_types::StageCheckpoint;
 use reth_trie_common::{
     BranchNodeCompact, PackedStorageTrieEntry, PackedStoredNibbles, PackedStoredNibblesSubKey,
-    StorageTrieEntry, StoredNibbles, StoredNibblesSubKey,
+    StorageTrieEntry, StoredNibbles, StoredNibblesSubKey, TrieChangeSetsEntry,
 };
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -493,6 +493,26 @@ tables! {
         type SubKey = StoredNibblesSubKey;
     }
 
+    /// Stores the account trie nodes before a block changed them, only written in trie changeset
+    /// archive mode.
+    ///
+    /// If [`TrieChangeSetsEntry::node`] is `None`, the node did not exist before the block.
+    table AccountsTrieChangeSets {
+        type Key = BlockNumber;
+        type Value = TrieChangeSetsEntry;
+        type SubKey = PackedStoredNibblesSubKey;
+    }
+
+    /// Stores the storage trie nodes before a block changed them, only written in trie changeset
+    /// archive mode.
+    ///
+    /// If [`TrieChangeSetsEntry::node`] is `None`, the node did not exist before the block.
+    table StoragesTrieChangeSets {
+        type Key = BlockNumberHashedAddress;
+        type Value = TrieChangeSetsEntry;
+        type SubKey = PackedStoredNibblesSubKey;
+    }
+
     /// Stores the transaction sender for each canonical transaction.
     /// It is needed to speed up execution stage and allows fetching signer without doing
     /// transaction signed recovery
@@ -578,6 +598,10 @@ pub enum ChainStateKey {
     LastFinalizedBlock,
     /// Last safe block key
     LastSafeBlock,
+    /// First block of the contiguous range of persisted trie changesets
+    EarliestTrieChangeSetBlock,
+    /// Last block of the contiguous range of persisted trie changesets
+    LatestTrieChangeSetBlock,
 }
 
 impl Encode for ChainStateKey {
@@ -587,6 +611,8 @@ impl Encode for ChainStateKey {
         match self {
             Self::LastFinalizedBlock => [0],
             Self::LastSafeBlock => [1],
+            Self::EarliestTrieChangeSetBlock => [2],
+            Self::LatestTrieChangeSetBlock => [3],
         }
     }
 }
@@ -596,6 +622,8 @@ impl Decode for ChainStateKey {
         match value {
             [0] => Ok(Self::LastFinalizedBlock),
             [1] => Ok(Self::LastSafeBlock),
+            [2] => Ok(Self::EarliestTrieChangeSetBlock),
+            [3] => Ok(Self::LatestTrieChangeSetBlock),
             _ => Err(crate::DatabaseError::Decode),
         }
     }
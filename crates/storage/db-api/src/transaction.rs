@@ -3,7 +3,7 @@ use crate::{
     table::{DupSort, Encode, Table},
     DatabaseError,
 };
-use std::fmt::Debug;
+use std::{fmt::Debug, path::Path};
 
 /// Helper adapter type for accessing [`DbTx`] cursor.
 pub type CursorTy<TX, T> = <TX as DbTx>::Cursor<T>;
@@ -46,6 +46,14 @@ pub trait DbTx: Debug + Send {
     fn entries<T: Table>(&self) -> Result<usize, DatabaseError>;
     /// Disables long-lived read transaction safety guarantees.
     fn disable_long_read_transaction_safety(&mut self);
+    /// Writes a copy of the database, as seen by this transaction, to a new file at `dest`.
+    ///
+    /// Writers are not blocked while the copy is made. Returns an error if the backend does not
+    /// support copies.
+    fn copy_to(&self, dest: &Path) -> Result<(), DatabaseError> {
+        let _ = dest;
+        Err(DatabaseError::Other("database copies are not supported".to_string()))
+    }
 }
 
 /// Read write transaction that allows writing to database
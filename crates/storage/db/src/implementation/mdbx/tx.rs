@@ -9,13 +9,18 @@ use reth_db_api::{
     table::{Compress, DupSort, Encode, IntoVec, Table, TableImporter},
     transaction::{DbTx, DbTxMut},
 };
-use reth_libmdbx::{ffi::MDBX_dbi, CommitLatency, Transaction, TransactionKind, WriteFlags, RW};
+use reth_libmdbx::{
+    ffi::{self, MDBX_dbi},
+    CommitLatency, Transaction, TransactionKind, WriteFlags, RW,
+};
 use reth_storage_errors::db::{DatabaseWriteError, DatabaseWriteOperation};
 use reth_tracing::tracing::{debug, instrument, trace, warn};
 use std::{
     backtrace::Backtrace,
     collections::HashMap,
+    ffi::CString,
     marker::PhantomData,
+    path::Path,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -346,6 +351,23 @@ impl<K: TransactionKind> DbTx for Tx<K> {
 
         self.inner.disable_timeout();
     }
+
+    fn copy_to(&self, dest: &Path) -> Result<(), DatabaseError> {
+        let dest = dest
+            .to_str()
+            .and_then(|dest| CString::new(dest).ok())
+            .ok_or_else(|| DatabaseError::Other(format!("invalid copy destination {dest:?}")))?;
+        let rc = self
+            .inner
+            .txn_execute(|txn| unsafe {
+                ffi::mdbx_txn_copy2pathname(txn, dest.as_ptr(), ffi::MDBX_CP_DEFAULTS)
+            })
+            .map_err(|e| DatabaseError::Read(e.into()))?;
+        if rc != 0 {
+            return Err(DatabaseError::Read(reth_libmdbx::Error::from_err_code(rc).into()))
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy)]
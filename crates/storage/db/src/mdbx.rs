@@ -10,7 +10,10 @@ pub use reth_libmdbx::*;
 
 /// Tables that have been removed from the schema but may still exist on disk from previous
 /// versions. These will be dropped during database initialization.
-const ORPHAN_TABLES: &[&str] = &["AccountsTrieChangeSets", "StoragesTrieChangeSets"];
+///
+/// `AccountsTrieChangeSets` and `StoragesTrieChangeSets` are part of the schema again, stale rows
+/// of previous versions are cleared when the trie changeset archive starts.
+const ORPHAN_TABLES: &[&str] = &[];
 
 /// Checks if the given path resides on a ZFS filesystem and logs a warning.
 ///
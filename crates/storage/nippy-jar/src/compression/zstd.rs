@@ -13,7 +13,7 @@ pub use zstd::{bulk::Decompressor, dict::DecoderDictionary};
 type RawDictionary = Vec<u8>;
 
 /// Represents the state of a Zstandard compression operation.
-#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ZstdState {
     /// The compressor is pending a dictionary.
     #[default]
@@ -23,7 +23,7 @@ pub enum ZstdState {
 }
 
 #[cfg_attr(test, derive(PartialEq))]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// Zstd compression structure. Supports a compression dictionary per column.
 pub struct Zstd {
     /// State. Should be ready before compressing.
@@ -70,8 +70,20 @@ impl Zstd {
         Ok(vec![])
     }
 
+    /// Returns the maximum size of a trained dictionary.
+    pub const fn max_dict_size(&self) -> usize {
+        self.max_dict_size
+    }
+
+    /// Returns the sizes of the trained column dictionaries, if any.
+    pub fn dictionary_sizes(&self) -> Option<Vec<usize>> {
+        self.dictionaries.as_ref().map(|dictionaries| {
+            dictionaries.iter().map(|dictionary| dictionary.raw().len()).collect()
+        })
+    }
+
     /// If using dictionaries, creates a list of [`Compressor`].
-    pub fn compressors(&self) -> Result<Option<Vec<Compressor<'_>>>, NippyJarError> {
+    pub fn compressors(&self) -> Result<Option<Vec<Compressor<'static>>>, NippyJarError> {
         match self.state {
             ZstdState::PendingDictionary => Err(NippyJarError::CompressorNotReady),
             ZstdState::Ready => {
@@ -81,7 +93,7 @@ impl Zstd {
 
                 if let Some(dictionaries) = &self.dictionaries {
                     debug!(target: "nippy-jar", count=?dictionaries.len(), "Generating ZSTD compressor dictionaries.");
-                    return Ok(Some(dictionaries.compressors()?))
+                    return Ok(Some(dictionaries.compressors(self.level)?))
                 }
                 Ok(None)
             }
@@ -189,8 +201,7 @@ impl Compression for Zstd {
         matches!(self.state, ZstdState::Ready)
     }
 
-    #[cfg(test)]
-    /// If using it with dictionaries, prepares a dictionary for each column.
+    /// If using it with dictionaries, trains a dictionary for each column from the sampled values.
     fn prepare_compression(
         &mut self,
         columns: Vec<impl IntoIterator<Item = Vec<u8>>>,
@@ -261,7 +272,7 @@ mod dictionaries_serde {
         D: Deserializer<'de>,
     {
         let dictionaries: Option<Vec<RawDictionary>> = Option::deserialize(deserializer)?;
-        Ok(dictionaries.map(|dicts| Arc::new(ZstdDictionaries::load(dicts))))
+        Ok(dictionaries.map(|dicts| Arc::new(ZstdDictionaries::new(dicts))))
     }
 }
 
@@ -277,69 +288,53 @@ impl std::fmt::Debug for ZstdDictionaries<'_> {
 }
 
 impl ZstdDictionaries<'_> {
-    #[cfg(test)]
-    /// Creates [`ZstdDictionaries`].
+    /// Creates [`ZstdDictionaries`] from a list of [`RawDictionary`].
     pub(crate) fn new(raw: Vec<RawDictionary>) -> Self {
-        Self(raw.into_iter().map(ZstdDictionary::Raw).collect())
-    }
-
-    /// Loads a list [`RawDictionary`] into a list of [`ZstdDictionary::Loaded`].
-    pub(crate) fn load(raw: Vec<RawDictionary>) -> Self {
-        Self(
-            raw.into_iter()
-                .map(|dict| ZstdDictionary::Loaded(DecoderDictionary::copy(&dict)))
-                .collect(),
-        )
+        Self(raw.into_iter().map(ZstdDictionary::new).collect())
     }
 
-    /// Creates a list of decompressors from a list of [`ZstdDictionary::Loaded`].
+    /// Creates a list of decompressors.
     pub(crate) fn decompressors(&self) -> Result<Vec<Decompressor<'_>>, NippyJarError> {
         Ok(self
             .iter()
-            .flat_map(|dict| {
-                dict.loaded()
-                    .ok_or(NippyJarError::DictionaryNotLoaded)
-                    .map(Decompressor::with_prepared_dictionary)
-            })
+            .map(|dict| Decompressor::with_prepared_dictionary(dict.loaded()))
             .collect::<Result<Vec<_>, _>>()?)
     }
 
-    /// Creates a list of compressors from a list of [`ZstdDictionary::Raw`].
-    pub(crate) fn compressors(&self) -> Result<Vec<Compressor<'_>>, NippyJarError> {
+    /// Creates a list of compressors with the given compression level.
+    pub(crate) fn compressors(
+        &self,
+        level: i32,
+    ) -> Result<Vec<Compressor<'static>>, NippyJarError> {
         Ok(self
             .iter()
-            .flat_map(|dict| {
-                dict.raw()
-                    .ok_or(NippyJarError::CompressorNotAllowed)
-                    .map(|dict| Compressor::with_dictionary(0, dict))
-            })
+            .map(|dict| Compressor::with_dictionary(level, dict.raw()))
             .collect::<Result<Vec<_>, _>>()?)
     }
 }
 
-/// A Zstd dictionary. It's created and serialized with [`ZstdDictionary::Raw`], and deserialized as
-/// [`ZstdDictionary::Loaded`].
-pub(crate) enum ZstdDictionary<'a> {
-    #[cfg_attr(not(test), expect(dead_code))]
-    Raw(RawDictionary),
-    Loaded(DecoderDictionary<'a>),
+/// A Zstd dictionary. Holds the raw dictionary, which is what gets serialized and what compressors
+/// are created from, alongside its [`DecoderDictionary`] used for decompression.
+pub(crate) struct ZstdDictionary<'a> {
+    raw: RawDictionary,
+    loaded: DecoderDictionary<'a>,
 }
 
 impl ZstdDictionary<'_> {
-    /// Returns a reference to the expected `RawDictionary`
-    pub(crate) const fn raw(&self) -> Option<&RawDictionary> {
-        match self {
-            ZstdDictionary::Raw(dict) => Some(dict),
-            ZstdDictionary::Loaded(_) => None,
-        }
+    /// Creates a [`ZstdDictionary`] by loading the [`RawDictionary`].
+    pub(crate) fn new(raw: RawDictionary) -> Self {
+        let loaded = DecoderDictionary::copy(&raw);
+        Self { raw, loaded }
     }
 
-    /// Returns a reference to the expected `DecoderDictionary`
-    pub(crate) const fn loaded(&self) -> Option<&DecoderDictionary<'_>> {
-        match self {
-            ZstdDictionary::Raw(_) => None,
-            ZstdDictionary::Loaded(dict) => Some(dict),
-        }
+    /// Returns a reference to the `RawDictionary`
+    pub(crate) const fn raw(&self) -> &RawDictionary {
+        &self.raw
+    }
+
+    /// Returns a reference to the `DecoderDictionary`
+    pub(crate) const fn loaded(&self) -> &DecoderDictionary<'_> {
+        &self.loaded
     }
 }
 
@@ -348,8 +343,7 @@ impl<'de> Deserialize<'de> for ZstdDictionary<'_> {
     where
         D: Deserializer<'de>,
     {
-        let dict = RawDictionary::deserialize(deserializer)?;
-        Ok(Self::Loaded(DecoderDictionary::copy(&dict)))
+        Ok(Self::new(RawDictionary::deserialize(deserializer)?))
     }
 }
 
@@ -358,21 +352,13 @@ impl Serialize for ZstdDictionary<'_> {
     where
         S: Serializer,
     {
-        match self {
-            ZstdDictionary::Raw(r) => r.serialize(serializer),
-            ZstdDictionary::Loaded(_) => unreachable!(),
-        }
+        self.raw.serialize(serializer)
     }
 }
 
 #[cfg(test)]
 impl PartialEq for ZstdDictionary<'_> {
     fn eq(&self, other: &Self) -> bool {
-        if let (Self::Raw(a), Self::Raw(b)) = (self, &other) {
-            return a == b
-        }
-        unimplemented!(
-            "`DecoderDictionary` can't be compared. So comparison should be done after decompressing a value."
-        );
+        self.raw == other.raw
     }
 }
@@ -1,14 +1,17 @@
-use crate::{writer::OFFSET_SIZE_BYTES, NippyJar, NippyJarError, NippyJarHeader};
+use crate::{
+    writer::OFFSET_SIZE_BYTES, NippyJar, NippyJarError, NippyJarHeader, CHECKSUM_SIZE_BYTES,
+};
 use std::{
     cmp::Ordering,
     fs::{File, OpenOptions},
-    io::{BufWriter, Seek, SeekFrom},
+    io::{BufWriter, Seek, SeekFrom, Write},
     path::Path,
 };
 
 /// Performs consistency checks or heals on the [`NippyJar`] file
 /// * Is the offsets file size expected?
 /// * Is the data file size expected?
+/// * Is the checksums file size expected, if the jar has checksums?
 ///
 /// This is based on the assumption that [`NippyJar`] configuration is **always** the last one
 /// to be updated when something is written, as by the `NippyJarWriter::commit()` function shows.
@@ -25,6 +28,8 @@ pub struct NippyJarChecker<H: NippyJarHeader = ()> {
     pub(crate) data_file: Option<BufWriter<File>>,
     /// File handle to where the offsets are stored.
     pub(crate) offsets_file: Option<BufWriter<File>>,
+    /// File handle to where the row checksums are stored, if the jar has checksums.
+    pub(crate) checksums_file: Option<BufWriter<File>>,
 }
 
 impl<H: NippyJarHeader> NippyJarChecker<H> {
@@ -34,19 +39,21 @@ impl<H: NippyJarHeader> NippyJarChecker<H> {
     /// the data or offsets files. The [`NippyJar`] passed in contains all necessary
     /// configurations for handling data.
     pub const fn new(jar: NippyJar<H>) -> Self {
-        Self { jar, data_file: None, offsets_file: None }
+        Self { jar, data_file: None, offsets_file: None, checksums_file: None }
     }
 
     /// It will throw an error if the [`NippyJar`] is in an inconsistent state.
     pub fn check_consistency(&mut self) -> Result<(), NippyJarError> {
-        self.handle_consistency(ConsistencyFailStrategy::ThrowError)
+        self.handle_consistency(ConsistencyFailStrategy::ThrowError)?;
+        self.handle_checksums_consistency(ConsistencyFailStrategy::ThrowError)
     }
 
     /// It will attempt to heal if the [`NippyJar`] is in an inconsistent state.
     ///
     /// **ATTENTION**: disk commit should be handled externally by consuming `Self`
     pub fn ensure_consistency(&mut self) -> Result<(), NippyJarError> {
-        self.handle_consistency(ConsistencyFailStrategy::Heal)
+        self.handle_consistency(ConsistencyFailStrategy::Heal)?;
+        self.handle_checksums_consistency(ConsistencyFailStrategy::Heal)
     }
 
     fn handle_consistency(&mut self, mode: ConsistencyFailStrategy) -> Result<(), NippyJarError> {
@@ -151,6 +158,59 @@ impl<H: NippyJarHeader> NippyJarChecker<H> {
         Ok(())
     }
 
+    /// Checks that there is exactly one checksum per row, once data and offsets are consistent.
+    ///
+    /// Checksums are synced before the configuration, so extra checksums belong to rows that
+    /// weren't committed and are truncated. Rows without a checksum can't be verified, so they're
+    /// pruned like rows whose data wasn't committed. A missing checksums file is never healed.
+    fn handle_checksums_consistency(
+        &mut self,
+        mode: ConsistencyFailStrategy,
+    ) -> Result<(), NippyJarError> {
+        if !self.jar.checksums {
+            return Ok(())
+        }
+
+        let path = self.jar.checksums_path();
+        if !path.exists() {
+            return Err(NippyJarError::MissingFile(path))
+        }
+        self.checksums_file = Some(BufWriter::new(
+            OpenOptions::new().read(true).write(mode.should_heal()).open(path)?,
+        ));
+
+        let expected_checksums_file_size = (self.jar.rows * CHECKSUM_SIZE_BYTES) as u64;
+        let actual_checksums_file_size = self.checksums_file().get_ref().metadata()?.len();
+
+        if mode.should_err() && expected_checksums_file_size != actual_checksums_file_size {
+            return Err(NippyJarError::InconsistentState)
+        }
+
+        match expected_checksums_file_size.cmp(&actual_checksums_file_size) {
+            Ordering::Less => {
+                self.checksums_file().get_mut().set_len(expected_checksums_file_size)?;
+            }
+            Ordering::Greater => {
+                // A partially written checksum doesn't cover its row either.
+                self.jar.rows = actual_checksums_file_size as usize / CHECKSUM_SIZE_BYTES;
+
+                // Freeze row count changed
+                self.jar.freeze_config()?;
+
+                // Truncates the offsets and data of the pruned rows.
+                self.handle_consistency(ConsistencyFailStrategy::Heal)?;
+                self.checksums_file()
+                    .get_mut()
+                    .set_len((self.jar.rows * CHECKSUM_SIZE_BYTES) as u64)?;
+            }
+            Ordering::Equal => {}
+        }
+
+        self.checksums_file().seek(SeekFrom::End(0))?;
+
+        Ok(())
+    }
+
     /// Loads data and offsets files.
     fn load_files(&mut self, mode: ConsistencyFailStrategy) -> Result<(), NippyJarError> {
         let load_file = |path: &Path| -> Result<BufWriter<File>, NippyJarError> {
@@ -178,6 +238,13 @@ impl<H: NippyJarHeader> NippyJarChecker<H> {
     const fn data_file(&mut self) -> &mut BufWriter<File> {
         self.data_file.as_mut().expect("should exist")
     }
+
+    /// Returns a mutable reference to checksums file.
+    ///
+    /// **Panics** if it does not exist.
+    const fn checksums_file(&mut self) -> &mut BufWriter<File> {
+        self.checksums_file.as_mut().expect("should exist")
+    }
 }
 
 /// Strategy on encountering an inconsistent state on [`NippyJarChecker`].
@@ -16,6 +16,9 @@ pub struct NippyJarCursor<'a, H = ()> {
     internal_buffer: Vec<u8>,
     /// Cursor row position.
     row: u64,
+    /// Whether rows are verified against their checksum before being returned, if the reader
+    /// has the checksums mapped.
+    verify_checksums: bool,
 }
 
 impl<H: NippyJarHeader> std::fmt::Debug for NippyJarCursor<'_, H> {
@@ -26,19 +29,18 @@ impl<H: NippyJarHeader> std::fmt::Debug for NippyJarCursor<'_, H> {
 
 impl<'a, H: NippyJarHeader> NippyJarCursor<'a, H> {
     /// Creates a new instance of [`NippyJarCursor`] for the given [`NippyJar`].
+    ///
+    /// If the jar has checksums, every row is verified against its checksum before it's returned.
+    /// Fails if the jar has checksums but its checksums file is missing.
     pub fn new(jar: &'a NippyJar<H>) -> Result<Self, NippyJarError> {
-        let max_row_size = jar.max_row_size;
-        Ok(Self {
-            jar,
-            reader: Arc::new(jar.open_data_reader()?),
-            // Makes sure that we have enough buffer capacity to decompress any row of data.
-            internal_buffer: Vec::with_capacity(max_row_size),
-            row: 0,
-        })
+        Self::with_reader(jar, Arc::new(jar.open_verifying_data_reader()?))
     }
 
     /// Creates a new instance of [`NippyJarCursor`] with the specified [`NippyJar`] and data
     /// reader.
+    ///
+    /// Rows are verified against their checksum if the reader was opened with
+    /// [`NippyJar::open_verifying_data_reader`].
     pub fn with_reader(
         jar: &'a NippyJar<H>,
         reader: Arc<DataReader>,
@@ -50,9 +52,17 @@ impl<'a, H: NippyJarHeader> NippyJarCursor<'a, H> {
             // Makes sure that we have enough buffer capacity to decompress any row of data.
             internal_buffer: Vec::with_capacity(max_row_size),
             row: 0,
+            verify_checksums: true,
         })
     }
 
+    /// Returns rows without verifying them against their checksum, trading the detection of
+    /// corrupted rows for read performance.
+    pub const fn without_checksum_verification(mut self) -> Self {
+        self.verify_checksums = false;
+        self
+    }
+
     /// Returns a reference to the related [`NippyJar`]
     pub const fn jar(&self) -> &NippyJar<H> {
         self.jar
@@ -83,6 +93,11 @@ impl<'a, H: NippyJarHeader> NippyJarCursor<'a, H> {
             return Ok(None)
         }
 
+        if self.verify_checksums {
+            // Corrupted data is never decompressed or returned.
+            self.reader.verify_row(self.row as usize, self.jar.columns, self.jar.rows)?;
+        }
+
         let mut row = Vec::with_capacity(self.jar.columns);
 
         // Retrieve all column values from the row
@@ -124,6 +139,11 @@ impl<'a, H: NippyJarHeader> NippyJarCursor<'a, H> {
         }
 
         let columns = self.jar.columns;
+        if self.verify_checksums {
+            // The whole row is verified even if only some of its columns are read.
+            self.reader.verify_row(self.row as usize, columns, self.jar.rows)?;
+        }
+
         let mut row = Vec::with_capacity(columns);
 
         for column in 0..columns {
@@ -165,13 +185,10 @@ impl<'a, H: NippyJarHeader> NippyJarCursor<'a, H> {
             let from = self.internal_buffer.len();
             match compression {
                 Compressors::Zstd(z) if z.use_dict => {
-                    // If we are here, then for sure we have the necessary dictionaries and they're
-                    // loaded (happens during deserialization). Otherwise, there's an issue
-                    // somewhere else and we can't recover here anyway.
-                    let dictionaries = z.dictionaries.as_ref().expect("dictionaries to exist")
-                        [column]
-                        .loaded()
-                        .expect("dictionary to be loaded");
+                    // If we are here, then for sure we have the necessary dictionaries. Otherwise,
+                    // there's an issue somewhere else and we can't recover here anyway.
+                    let dictionaries =
+                        z.dictionaries.as_ref().expect("dictionaries to exist")[column].loaded();
                     let mut decompressor = Decompressor::with_prepared_dictionary(dictionaries)?;
                     Zstd::decompress_with_dictionary(
                         self.reader.data(column_offset_range),
@@ -89,7 +89,27 @@ pub enum NippyJarError {
     #[error("File is in an inconsistent state.")]
     InconsistentState,
 
+    /// A committed rewrite of the jar was interrupted and has to be completed by its writer with
+    /// [`crate::NippyJar::complete_rewrites`] before the jar can be loaded.
+    #[error("jar {} has an interrupted rewrite", .0.display())]
+    InterruptedRewrite(PathBuf),
+
     /// A specified file is missing.
     #[error("Missing file: {}", .0.display())]
     MissingFile(PathBuf),
+
+    /// A row of a jar with checksums isn't covered by its checksums file.
+    #[error("missing checksum of row {0}")]
+    MissingChecksum(u64),
+
+    /// The data of a row doesn't match its stored checksum, the row is corrupted.
+    #[error("checksum mismatch of row {row}: expected {expected:#010x}, got {actual:#010x}")]
+    ChecksumMismatch {
+        /// The corrupted row.
+        row: u64,
+        /// The stored checksum of the row.
+        expected: u32,
+        /// The checksum of the row's data.
+        actual: u32,
+    },
 }
@@ -15,6 +15,7 @@
 use memmap2::Mmap;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     error::Error as StdError,
     fs::File,
     io::{self, Read, Write},
@@ -25,9 +26,7 @@ use tracing::*;
 
 /// Compression algorithms supported by `NippyJar`.
 pub mod compression;
-#[cfg(test)]
-use compression::Compression;
-use compression::Compressors;
+use compression::{Compression, Compressors};
 
 /// empty enum for backwards compatibility
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,6 +50,8 @@ pub use writer::NippyJarWriter;
 mod consistency;
 pub use consistency::NippyJarChecker;
 
+/// Size of one row checksum in bytes.
+pub(crate) const CHECKSUM_SIZE_BYTES: usize = 4;
 /// The version number of the Nippy Jar format.
 const NIPPY_JAR_VERSION: usize = 1;
 /// The file extension used for index files.
@@ -61,6 +62,12 @@ const OFFSETS_FILE_EXTENSION: &str = "off";
 pub const CONFIG_FILE_EXTENSION: &str = "conf";
 /// The file extension used for changeset offset sidecar files.
 pub const CHANGESET_OFFSETS_FILE_EXTENSION: &str = "csoff";
+/// The file extension used for row checksum sidecar files.
+pub const CHECKSUMS_FILE_EXTENSION: &str = "sum";
+/// The directory, relative to the jar, where [`NippyJar::recompress`] stages the rewritten files.
+const RECOMPRESS_DIRECTORY: &str = "recompress";
+/// The file extension of the marker which commits the staged files of a rewritten jar.
+const REWRITE_COMMIT_EXTENSION: &str = "commit";
 
 /// A [`RefRow`] is a list of column value slices pointing to either an internal buffer or a
 /// memory-mapped file.
@@ -111,6 +118,12 @@ pub struct NippyJar<H = ()> {
     /// Data path for file. Supporting files will have a format `{path}.{extension}`.
     #[serde(skip)]
     path: PathBuf,
+    /// Whether a checksum of each row is kept in the checksums sidecar file.
+    ///
+    /// Serialized after the rest of the configuration, so that configurations written before
+    /// checksums existed can still be loaded, as jars without checksums.
+    #[serde(skip)]
+    checksums: bool,
 }
 
 impl<H: NippyJarHeader> std::fmt::Debug for NippyJar<H> {
@@ -125,6 +138,7 @@ impl<H: NippyJarHeader> std::fmt::Debug for NippyJar<H> {
             .field("phf", &self.phf)
             .field("path", &self.path)
             .field("max_row_size", &self.max_row_size)
+            .field("checksums", &self.checksums)
             .finish_non_exhaustive()
     }
 }
@@ -139,6 +153,28 @@ impl NippyJar<()> {
     pub fn load_without_header(path: &Path) -> Result<Self, NippyJarError> {
         Self::load(path)
     }
+
+    /// Completes the committed rewrites of the jars in `dir` which were interrupted, see
+    /// [`NippyJar::recompress`].
+    ///
+    /// This moves files within `dir`, so it must only be called by the single writer of the
+    /// directory before loading any of its jars.
+    pub fn complete_rewrites(dir: &Path) -> Result<(), NippyJarError> {
+        let staging_dir = dir.join(RECOMPRESS_DIRECTORY);
+        if !staging_dir.is_dir() {
+            return Ok(())
+        }
+
+        for entry in reth_fs_util::read_dir(&staging_dir)?.filter_map(Result::ok) {
+            let marker = entry.path();
+            if marker.extension().is_some_and(|extension| extension == REWRITE_COMMIT_EXTENSION) &&
+                let Some(file_name) = marker.file_stem()
+            {
+                Self::complete_rewrite(&dir.join(file_name))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<H: NippyJarHeader> NippyJar<H> {
@@ -154,6 +190,7 @@ impl<H: NippyJarHeader> NippyJar<H> {
             filter: None,
             phf: None,
             path: path.to_path_buf(),
+            checksums: false,
         }
     }
 
@@ -170,6 +207,27 @@ impl<H: NippyJarHeader> NippyJar<H> {
         self
     }
 
+    /// Sets the compressor, e.g. one inherited from another jar with already trained dictionaries.
+    pub fn with_compressor(mut self, compressor: Option<Compressors>) -> Self {
+        self.compressor = compressor;
+        self
+    }
+
+    /// Keeps a CRC32 checksum of each row in a sidecar file, which cursors verify rows against
+    /// unless created with [`NippyJarCursor::without_checksum_verification`].
+    ///
+    /// Only has an effect on jars which are created by the [`NippyJarWriter`], existing jars keep
+    /// checksums if and only if they were created with them.
+    pub const fn with_checksums(mut self) -> Self {
+        self.checksums = true;
+        self
+    }
+
+    /// Returns whether the jar keeps a checksum of each row.
+    pub const fn has_checksums(&self) -> bool {
+        self.checksums
+    }
+
     /// Gets a reference to the user header.
     pub const fn user_header(&self) -> &H {
         &self.user_header
@@ -197,8 +255,15 @@ impl<H: NippyJarHeader> NippyJar<H> {
 
     /// Loads the file configuration and returns [`Self`].
     ///
+    /// Fails with [`NippyJarError::InterruptedRewrite`] if a committed rewrite of the jar was
+    /// interrupted, since its files may only be partially moved. Loading never modifies any files.
+    ///
     /// **The user must ensure the header type matches the one used during the jar's creation.**
     pub fn load(path: &Path) -> Result<Self, NippyJarError> {
+        if rewrite_marker(path).is_some_and(|marker| marker.exists()) {
+            return Err(NippyJarError::InterruptedRewrite(path.to_path_buf()))
+        }
+
         // Read [`Self`] located at the data file.
         let config_path = path.with_extension(CONFIG_FILE_EXTENSION);
         let config_file = File::open(&config_path)
@@ -213,13 +278,28 @@ impl<H: NippyJarHeader> NippyJar<H> {
     }
 
     /// Deserializes an instance of [`Self`] from a [`Read`] type.
-    pub fn load_from_reader<R: Read>(reader: R) -> Result<Self, NippyJarError> {
-        Ok(bincode::deserialize_from(reader)?)
+    pub fn load_from_reader<R: Read>(mut reader: R) -> Result<Self, NippyJarError> {
+        let mut jar: Self = bincode::deserialize_from(&mut reader)?;
+        jar.checksums = match bincode::deserialize_from(reader) {
+            Ok(checksums) => checksums,
+            // Configurations written before checksums existed end here.
+            Err(err)
+                if matches!(
+                    &*err,
+                    bincode::ErrorKind::Io(source) if source.kind() == io::ErrorKind::UnexpectedEof
+                ) =>
+            {
+                false
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(jar)
     }
 
     /// Serializes an instance of [`Self`] to a [`Write`] type.
-    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<(), NippyJarError> {
-        Ok(bincode::serialize_into(writer, self)?)
+    pub fn save_to_writer<W: Write>(&self, mut writer: W) -> Result<(), NippyJarError> {
+        bincode::serialize_into(&mut writer, self)?;
+        Ok(bincode::serialize_into(writer, &self.checksums)?)
     }
 
     /// Returns the path for the data file
@@ -247,6 +327,11 @@ impl<H: NippyJarHeader> NippyJar<H> {
         self.path.with_extension(CHANGESET_OFFSETS_FILE_EXTENSION)
     }
 
+    /// Returns the path for the row checksums sidecar file.
+    pub fn checksums_path(&self) -> PathBuf {
+        self.path.with_extension(CHECKSUMS_FILE_EXTENSION)
+    }
+
     /// Deletes from disk this [`NippyJar`] alongside every satellite file.
     pub fn delete(self) -> Result<(), NippyJarError> {
         // TODO(joshie): ensure consistency on unexpected shutdown
@@ -257,6 +342,7 @@ impl<H: NippyJarHeader> NippyJar<H> {
             self.offsets_path(),
             self.config_path(),
             self.changeset_offsets_path(),
+            self.checksums_path(),
         ] {
             if path.exists() {
                 debug!(target: "nippy-jar", ?path, "Removing file.");
@@ -272,15 +358,26 @@ impl<H: NippyJarHeader> NippyJar<H> {
         DataReader::new(self.data_path())
     }
 
-    /// Writes all necessary configuration to file.
-    fn freeze_config(&self) -> Result<(), NippyJarError> {
-        Ok(reth_fs_util::atomic_write_file(&self.config_path(), |file| self.save_to_writer(file))?)
+    /// Returns a [`DataReader`] which also maps the row checksums, if the jar has them.
+    ///
+    /// Fails if the jar has checksums but its checksums file is missing.
+    pub fn open_verifying_data_reader(&self) -> Result<DataReader, NippyJarError> {
+        let mut reader = self.open_data_reader()?;
+        if self.checksums {
+            let path = self.checksums_path();
+            if !path.exists() {
+                return Err(NippyJarError::MissingFile(path))
+            }
+            // SAFETY: File is read-only and the mmap handle does not depend on its descriptor.
+            reader.checksums_mmap = Some(unsafe { Mmap::map(&File::open(path)?)? });
+        }
+        Ok(reader)
     }
-}
 
-#[cfg(test)]
-impl<H: NippyJarHeader> NippyJar<H> {
     /// If required, prepares any compression algorithm to an early pass of the data.
+    ///
+    /// For [`compression::Zstd`] with dictionaries, this trains one dictionary per column from the
+    /// given sample values.
     pub fn prepare_compression(
         &mut self,
         columns: Vec<impl IntoIterator<Item = Vec<u8>>>,
@@ -293,6 +390,189 @@ impl<H: NippyJarHeader> NippyJar<H> {
         Ok(())
     }
 
+    /// Rewrites the data and offsets files of this jar with a different compressor, and returns
+    /// the updated jar.
+    ///
+    /// The new files are staged under a `recompress` directory next to the jar and then moved over
+    /// the original ones, followed by the configuration. Row numbers are preserved, so satellite
+    /// files like the changeset offsets sidecar remain valid. The jar must not be read or written
+    /// concurrently.
+    ///
+    /// The staged files are synced and committed by a marker file before any of them is moved, so
+    /// if the process is interrupted midway, [`NippyJar::complete_rewrites`] finishes moving them.
+    /// Until then, [`NippyJar::load`] fails. Without a marker, the staged files are ignored and the
+    /// original jar is left untouched.
+    pub fn recompress(self, compressor: Option<Compressors>) -> Result<Self, NippyJarError>
+    where
+        H: Clone,
+    {
+        self.rewrite(compressor, BTreeMap::new())
+    }
+
+    /// Rewrites the data and offsets files of this jar with some rows replaced, and returns the
+    /// updated jar.
+    ///
+    /// `rows` maps row numbers to the uncompressed values of all columns of the replacing row.
+    /// Replaced rows are never read, so this repairs rows which fail their checksum or can't be
+    /// decompressed, while all other rows must pass their checksum. The jar is rewritten the same
+    /// way as by [`NippyJar::recompress`], keeping its compressor.
+    pub fn replace_rows(self, rows: BTreeMap<usize, Vec<Vec<u8>>>) -> Result<Self, NippyJarError>
+    where
+        H: Clone,
+    {
+        if let Some(&row) = rows.range(self.rows..).next().map(|(row, _)| row) {
+            return Err(NippyJarError::Custom(format!(
+                "row {row} is out of bounds of a jar with {} rows",
+                self.rows
+            )))
+        }
+        if let Some(values) = rows.values().find(|values| values.len() != self.columns) {
+            return Err(NippyJarError::ColumnLenMismatch(self.columns, values.len()))
+        }
+
+        let compressor = self.compressor.clone();
+        self.rewrite(compressor, rows)
+    }
+
+    /// Rewrites the jar with the given compressor through a staging directory, replacing the
+    /// given rows.
+    fn rewrite(
+        self,
+        compressor: Option<Compressors>,
+        replaced_rows: BTreeMap<usize, Vec<Vec<u8>>>,
+    ) -> Result<Self, NippyJarError>
+    where
+        H: Clone,
+    {
+        let file_name = self.path.file_name().ok_or_else(|| {
+            NippyJarError::Custom(format!("invalid jar path: {}", self.path.display()))
+        })?;
+        let staging_dir = self.path.with_file_name(RECOMPRESS_DIRECTORY);
+        reth_fs_util::create_dir_all(&staging_dir)?;
+
+        let mut staged =
+            Self::new(self.columns, &staging_dir.join(file_name), self.user_header.clone())
+                .with_compressor(compressor);
+        staged.version = self.version;
+        staged.checksums = self.checksums;
+
+        // Leftovers of an interrupted run would otherwise be treated as an existing jar.
+        let staged_paths = [
+            staged.data_path().to_path_buf(),
+            staged.offsets_path(),
+            staged.config_path(),
+            staged.checksums_path(),
+        ];
+        for path in &staged_paths {
+            reth_fs_util::remove_file_if_exists(path)?;
+        }
+
+        let mut writer = NippyJarWriter::new(staged)?;
+        let result = self.copy_rows(&mut writer, &replaced_rows).and_then(|()| writer.commit());
+        if let Err(err) = result {
+            // The original jar is untouched, so the staged files are discarded.
+            drop(writer);
+            for path in staged_paths {
+                let _ = std::fs::remove_file(path);
+            }
+            let _ = std::fs::remove_dir(&staging_dir);
+            return Err(err)
+        }
+
+        // The staged files are synced by the commit, so from here on the rewrite is rolled forward
+        // by `Self::complete_rewrite` if interrupted.
+        let marker = writer.jar().path.with_extension(REWRITE_COMMIT_EXTENSION);
+        reth_fs_util::atomic_write_file(&marker, |_| Ok::<_, io::Error>(()))?;
+
+        let mut jar = writer.into_jar();
+        debug!(
+            target: "nippy-jar",
+            path=?self.path,
+            rows=jar.rows,
+            replaced_rows=replaced_rows.len(),
+            "Rewrote jar."
+        );
+
+        Self::complete_rewrite(&self.path)?;
+        jar.path = self.path;
+
+        Ok(jar)
+    }
+
+    /// Appends all rows of this jar to the writer, replacing the given rows.
+    fn copy_rows(
+        &self,
+        writer: &mut NippyJarWriter<H>,
+        replaced_rows: &BTreeMap<usize, Vec<Vec<u8>>>,
+    ) -> Result<(), NippyJarError> {
+        // Rows are verified, so that corrupted rows don't get new checksums.
+        let mut cursor = NippyJarCursor::new(self)?;
+        for row in 0..self.rows {
+            if let Some(values) = replaced_rows.get(&row) {
+                for value in values {
+                    writer.append_column(Some(Ok(value)))?;
+                }
+            } else if let Some(values) = cursor.row_by_number(row)? {
+                for value in values {
+                    writer.append_column(Some(Ok(value)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the staged files of a committed rewrite of the jar at `path` over the original ones,
+    /// with the configuration last. Does nothing if no rewrite was committed.
+    ///
+    /// Files which were already moved by an interrupted call are skipped, so this can be retried.
+    fn complete_rewrite(path: &Path) -> Result<(), NippyJarError> {
+        let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else { return Ok(()) };
+        let staging_dir = dir.join(RECOMPRESS_DIRECTORY);
+        let staged = staging_dir.join(file_name);
+        let marker = staged.with_extension(REWRITE_COMMIT_EXTENSION);
+        if !marker.exists() {
+            return Ok(())
+        }
+
+        debug!(target: "nippy-jar", ?path, "Completing committed jar rewrite.");
+        let paths = [OFFSETS_FILE_EXTENSION, CHECKSUMS_FILE_EXTENSION, CONFIG_FILE_EXTENSION]
+            .map(|extension| (staged.with_extension(extension), path.with_extension(extension)));
+        for (from, to) in std::iter::once((staged.clone(), path.to_path_buf())).chain(paths) {
+            if from.exists() {
+                reth_fs_util::rename(from, to)?;
+            }
+        }
+        sync_dir(dir)?;
+        reth_fs_util::remove_file(&marker)?;
+
+        // Only succeeds once no other jar is being rewritten in the same directory.
+        let _ = std::fs::remove_dir(&staging_dir);
+
+        Ok(())
+    }
+
+    /// Writes all necessary configuration to file.
+    fn freeze_config(&self) -> Result<(), NippyJarError> {
+        Ok(reth_fs_util::atomic_write_file(&self.config_path(), |file| self.save_to_writer(file))?)
+    }
+}
+
+/// Returns the path of the marker committing a rewrite of the jar at `path`.
+fn rewrite_marker(path: &Path) -> Option<PathBuf> {
+    let (dir, file_name) = (path.parent()?, path.file_name()?);
+    Some(dir.join(RECOMPRESS_DIRECTORY).join(file_name).with_extension(REWRITE_COMMIT_EXTENSION))
+}
+
+/// Syncs the entries of a directory, so that renames within it are durable.
+fn sync_dir(dir: &Path) -> Result<(), NippyJarError> {
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|err| reth_fs_util::FsPathError::fsync(err, dir))?;
+    Ok(())
+}
+
+#[cfg(test)]
+impl<H: NippyJarHeader> NippyJar<H> {
     /// Writes all data and configuration to a file and the offset index to another.
     pub fn freeze(
         self,
@@ -352,6 +632,8 @@ pub struct DataReader {
     offset_mmap: Mmap,
     /// Number of bytes that represent one offset.
     offset_size: u8,
+    /// Mmap handle for the row checksums, if opened by [`NippyJar::open_verifying_data_reader`].
+    checksums_mmap: Option<Mmap>,
 }
 
 impl DataReader {
@@ -375,7 +657,14 @@ impl DataReader {
             return Err(NippyJarError::OffsetSizeTooSmall { offset_size })
         }
 
-        Ok(Self { data_file, data_mmap, offset_file, offset_size, offset_mmap })
+        Ok(Self {
+            data_file,
+            data_mmap,
+            offset_file,
+            offset_size,
+            offset_mmap,
+            checksums_mmap: None,
+        })
     }
 
     /// Returns the offset for the requested data index
@@ -438,6 +727,42 @@ impl DataReader {
     pub fn offsets_size(&self) -> usize {
         self.offset_mmap.len()
     }
+
+    /// Returns the stored checksum of a row, if the checksums are mapped and the row is covered by
+    /// them.
+    pub fn checksum(&self, row: usize) -> Option<u32> {
+        let from = row.checked_mul(CHECKSUM_SIZE_BYTES)?;
+        let bytes = self.checksums_mmap.as_ref()?.get(from..from + CHECKSUM_SIZE_BYTES)?;
+        Some(u32::from_le_bytes(bytes.try_into().expect("checksum is 4 bytes")))
+    }
+
+    /// Returns the byte range of all columns of a row in the data file.
+    pub fn row_range(
+        &self,
+        row: usize,
+        columns: usize,
+        rows: usize,
+    ) -> Result<Range<usize>, NippyJarError> {
+        let start = self.offset(row * columns)? as usize;
+        let end =
+            if row + 1 == rows { self.size() } else { self.offset((row + 1) * columns)? as usize };
+        Ok(start..end)
+    }
+
+    /// Verifies a row against its stored checksum.
+    ///
+    /// Always passes if no checksums are mapped, and fails if the row isn't covered by them.
+    pub fn verify_row(&self, row: usize, columns: usize, rows: usize) -> Result<(), NippyJarError> {
+        if self.checksums_mmap.is_none() {
+            return Ok(())
+        }
+        let expected = self.checksum(row).ok_or(NippyJarError::MissingChecksum(row as u64))?;
+        let actual = crc32fast::hash(self.data(self.row_range(row, columns, rows)?));
+        if actual != expected {
+            return Err(NippyJarError::ChecksumMismatch { row: row as u64, expected, actual })
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -574,6 +899,233 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_recompress() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len() as u64;
+        let num_columns = 2;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        let assert_rows = |jar: &NippyJar, expected: &[(&ColumnValues, &ColumnValues)]| {
+            let mut cursor = NippyJarCursor::new(jar).unwrap();
+            let expected = expected.iter().flat_map(|(col1, col2)| col1.iter().zip(col2.iter()));
+            let mut rows = 0;
+            for (v0, v1) in expected {
+                let row = cursor.next_row().unwrap().unwrap();
+                assert_eq!((row[0], row[1]), (v0.as_slice(), v1.as_slice()));
+                rows += 1;
+            }
+            assert!(cursor.next_row().unwrap().is_none());
+            assert_eq!(jar.rows(), rows);
+        };
+
+        NippyJar::new_without_header(num_columns, file_path.path())
+            .with_lz4()
+            .freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows)
+            .unwrap();
+
+        // Recompress with dictionaries trained on the jar contents
+        let mut compressor = Compressors::Zstd(compression::Zstd::new(true, 5000, num_columns));
+        compressor.prepare_compression(vec![col1.clone(), col2.clone()]).unwrap();
+        let jar = NippyJar::load_without_header(file_path.path()).unwrap();
+        let jar = jar.recompress(Some(compressor)).unwrap();
+        assert_eq!(jar.data_path(), file_path.path());
+        let staged_path = file_path
+            .path()
+            .with_file_name(RECOMPRESS_DIRECTORY)
+            .join(file_path.path().file_name().unwrap());
+        assert!(!staged_path.exists());
+        assert_rows(&jar, &[(&col1, &col2)]);
+
+        // Reopened jar keeps appending with the trained dictionaries
+        let loaded = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert!(matches!(
+            loaded.compressor(),
+            Some(Compressors::Zstd(zstd))
+                if zstd.dictionary_sizes().is_some_and(|sizes| sizes.len() == num_columns)
+        ));
+        let mut writer = NippyJarWriter::new(loaded).unwrap();
+        writer
+            .append_rows(vec![clone_with_result(&col2), clone_with_result(&col1)], num_rows)
+            .unwrap();
+        writer.commit().unwrap();
+        let loaded = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert_rows(&loaded, &[(&col1, &col2), (&col2, &col1)]);
+
+        // Recompress back to uncompressed
+        let jar = loaded.recompress(None).unwrap();
+        assert!(jar.compressor().is_none());
+        let loaded = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert_rows(&loaded, &[(&col1, &col2), (&col2, &col1)]);
+        assert_eq!(loaded.open_data_reader().unwrap().size(), 2 * num_rows as usize * 64);
+    }
+
+    #[test]
+    fn test_complete_interrupted_rewrite() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len() as u64;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jar");
+        let staged_path = dir.path().join(RECOMPRESS_DIRECTORY).join("jar");
+        let marker = staged_path.with_extension(REWRITE_COMMIT_EXTENSION);
+
+        let assert_rows = |jar: &NippyJar, col1: &ColumnValues, col2: &ColumnValues| {
+            let mut cursor = NippyJarCursor::new(jar).unwrap();
+            for (v0, v1) in col1.iter().zip(col2.iter()) {
+                let row = cursor.next_row().unwrap().unwrap();
+                assert_eq!((row[0], row[1]), (v0.as_slice(), v1.as_slice()));
+            }
+            assert!(cursor.next_row().unwrap().is_none());
+        };
+
+        NippyJar::new_without_header(2, &path)
+            .with_lz4()
+            .freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows)
+            .unwrap();
+
+        // Stage an uncompressed rewrite with swapped columns, interrupted before its commit
+        reth_fs_util::create_dir_all(dir.path().join(RECOMPRESS_DIRECTORY)).unwrap();
+        NippyJar::new_without_header(2, &staged_path)
+            .freeze(vec![clone_with_result(&col2), clone_with_result(&col1)], num_rows)
+            .unwrap();
+        let jar = NippyJar::load_without_header(&path).unwrap();
+        assert!(jar.compressor().is_some());
+        assert_rows(&jar, &col1, &col2);
+
+        // Commit the rewrite and get interrupted after moving the data file only
+        reth_fs_util::atomic_write_file(&marker, |_| Ok::<_, io::Error>(())).unwrap();
+        reth_fs_util::rename(&staged_path, &path).unwrap();
+
+        // Loading doesn't touch the partially moved files
+        assert!(matches!(
+            NippyJar::load_without_header(&path),
+            Err(NippyJarError::InterruptedRewrite(_))
+        ));
+        assert!(marker.exists());
+
+        NippyJar::complete_rewrites(dir.path()).unwrap();
+        let jar = NippyJar::load_without_header(&path).unwrap();
+        assert!(jar.compressor().is_none());
+        assert_rows(&jar, &col2, &col1);
+        assert!(!marker.exists());
+        assert!(!dir.path().join(RECOMPRESS_DIRECTORY).exists());
+    }
+
+    #[test]
+    fn test_checksums() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len();
+        let num_columns = 2;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+        let checksums_size = |jar: &NippyJar| jar.checksums_path().metadata().unwrap().len();
+
+        NippyJar::new_without_header(num_columns, file_path.path())
+            .with_lz4()
+            .with_checksums()
+            .freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows as u64)
+            .unwrap();
+
+        let jar = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert!(jar.has_checksums());
+        assert_eq!(checksums_size(&jar), (num_rows * CHECKSUM_SIZE_BYTES) as u64);
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        while cursor.next_row().unwrap().is_some() {}
+
+        // Flip a bit of a row
+        let corrupted_row = 10;
+        let range = jar
+            .open_data_reader()
+            .unwrap()
+            .row_range(corrupted_row, num_columns, num_rows)
+            .unwrap();
+        let mut data = std::fs::read(jar.data_path()).unwrap();
+        data[range.start] ^= 1;
+        std::fs::write(jar.data_path(), data).unwrap();
+
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        assert!(cursor.row_by_number(corrupted_row - 1).unwrap().is_some());
+        assert!(matches!(
+            cursor.row_by_number(corrupted_row),
+            Err(NippyJarError::ChecksumMismatch { row, .. }) if row == corrupted_row as u64
+        ));
+        assert!(matches!(
+            cursor.row_by_number_with_cols(corrupted_row, 0b10),
+            Err(NippyJarError::ChecksumMismatch { .. })
+        ));
+        drop(cursor);
+
+        // Verification can be opted out of
+        let mut cursor = NippyJarCursor::new(&jar).unwrap().without_checksum_verification();
+        assert!(cursor.row_by_number(corrupted_row).unwrap().is_some());
+        drop(cursor);
+
+        // Rewriting the jar doesn't give the corrupted row a new checksum
+        let replace = |jar: NippyJar, row: usize| {
+            jar.replace_rows(BTreeMap::from([(row, vec![col1[row].clone(), col2[row].clone()])]))
+        };
+        assert!(matches!(
+            replace(NippyJar::load_without_header(file_path.path()).unwrap(), 0),
+            Err(NippyJarError::ChecksumMismatch { .. })
+        ));
+
+        // Replacing the row repairs the jar
+        let jar = replace(jar, corrupted_row).unwrap();
+        assert!(jar.has_checksums());
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        for (v0, v1) in col1.iter().zip(col2.iter()) {
+            let row = cursor.next_row().unwrap().unwrap();
+            assert_eq!((row[0], row[1]), (v0.as_slice(), v1.as_slice()));
+        }
+        drop(cursor);
+
+        // Rows without a checksum are pruned instead of getting one computed from their data
+        let file = OpenOptions::new().write(true).open(jar.checksums_path()).unwrap();
+        file.set_len(checksums_size(&jar) - 6).unwrap();
+        let jar = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert!(matches!(
+            NippyJarChecker::new(NippyJar::load_without_header(file_path.path()).unwrap())
+                .check_consistency(),
+            Err(NippyJarError::InconsistentState)
+        ));
+        let mut writer = NippyJarWriter::new(jar).unwrap();
+        assert_eq!(writer.rows(), num_rows - 2);
+        assert_eq!(checksums_size(writer.jar()), ((num_rows - 2) * CHECKSUM_SIZE_BYTES) as u64);
+
+        // Pruned rows drop their checksums
+        writer.prune_rows(5).unwrap();
+        let jar = writer.into_jar();
+        assert_eq!(checksums_size(&jar), ((num_rows - 7) * CHECKSUM_SIZE_BYTES) as u64);
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        while cursor.next_row().unwrap().is_some() {}
+        drop(cursor);
+
+        // A deleted checksums file doesn't disable the checksums
+        reth_fs_util::remove_file(jar.checksums_path()).unwrap();
+        let jar = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert!(jar.has_checksums());
+        assert!(matches!(NippyJarCursor::new(&jar), Err(NippyJarError::MissingFile(_))));
+        assert!(matches!(
+            NippyJarChecker::new(NippyJar::load_without_header(file_path.path()).unwrap())
+                .check_consistency(),
+            Err(NippyJarError::MissingFile(_))
+        ));
+        assert!(matches!(NippyJarWriter::new(jar), Err(NippyJarError::MissingFile(_))));
+
+        // Jars without checksums don't get them when reopened
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+        NippyJar::new_without_header(num_columns, file_path.path())
+            .freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows as u64)
+            .unwrap();
+        let jar = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert!(!jar.has_checksums());
+        let writer = NippyJarWriter::new(jar).unwrap();
+        assert!(!writer.jar().checksums_path().exists());
+
+        // Configurations written before checksums existed are loaded as jars without them
+        let config = bincode::serialize(&writer.into_jar().with_checksums()).unwrap();
+        assert!(!NippyJar::<()>::load_from_reader(config.as_slice()).unwrap().has_checksums());
+    }
+
     #[test]
     fn test_lz4() {
         let (col1, col2) = test_data(None);
@@ -1,12 +1,13 @@
 use crate::{
-    compression::Compression, ColumnResult, NippyJar, NippyJarChecker, NippyJarError,
-    NippyJarHeader,
+    compression::{Compression, Compressors},
+    ColumnResult, NippyJar, NippyJarChecker, NippyJarError, NippyJarHeader, CHECKSUM_SIZE_BYTES,
 };
 use std::{
     fs::{File, OpenOptions},
     io::{BufWriter, Read, Seek, SeekFrom, Write},
     path::Path,
 };
+use zstd::bulk::Compressor;
 
 /// Size of one offset in bytes.
 pub(crate) const OFFSET_SIZE_BYTES: u8 = 8;
@@ -24,6 +25,10 @@ pub(crate) const OFFSET_SIZE_BYTES: u8 = 8;
 ///
 /// ## Data file layout
 /// The data file is represented just as a sequence of bytes of data without any delimiters
+///
+/// ## Checksums file layout
+/// If the jar has checksums, the checksums file contains one little-endian CRC32 per row, of the
+/// data of all columns of the row as stored in the data file.
 #[derive(Debug)]
 pub struct NippyJarWriter<H: NippyJarHeader = ()> {
     /// Associated [`NippyJar`], containing all necessary configurations for data
@@ -33,8 +38,14 @@ pub struct NippyJarWriter<H: NippyJarHeader = ()> {
     data_file: BufWriter<File>,
     /// File handle to where the offsets are stored.
     offsets_file: BufWriter<File>,
+    /// File handle to where the row checksums are stored, if the jar has checksums.
+    checksums_file: Option<BufWriter<File>>,
+    /// Checksum of the row which is being written.
+    row_hasher: crc32fast::Hasher,
     /// Temporary buffer to reuse when compressing data.
     tmp_buf: Vec<u8>,
+    /// Per column compressors, if the jar uses zstd with trained dictionaries.
+    dictionary_compressors: Option<DictionaryCompressors>,
     /// Used to find the maximum uncompressed size of a row in a jar.
     uncompressed_row_size: usize,
     /// Partial offset list which hasn't been flushed to disk.
@@ -53,28 +64,42 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
         let (data_file, offsets_file, is_created) =
             Self::create_or_open_files(jar.data_path(), &jar.offsets_path())?;
 
-        let (jar, data_file, offsets_file) = if is_created {
+        let (jar, data_file, offsets_file, checksums_file) = if is_created {
+            let checksums_file = jar
+                .checksums
+                .then(|| File::create(jar.checksums_path()).map(BufWriter::new))
+                .transpose()?;
+
             // Makes sure we don't have dangling data and offset files when we just created the file
             jar.freeze_config()?;
 
-            (jar, BufWriter::new(data_file), BufWriter::new(offsets_file))
+            (jar, BufWriter::new(data_file), BufWriter::new(offsets_file), checksums_file)
         } else {
             // If we are opening a previously created jar, we need to check its consistency, and
             // make changes if necessary.
             let mut checker = NippyJarChecker::new(jar);
             checker.ensure_consistency()?;
 
-            let NippyJarChecker { jar, data_file, offsets_file } = checker;
+            let NippyJarChecker { jar, data_file, offsets_file, checksums_file } = checker;
 
-            // Calling ensure_consistency, will fill data_file and offsets_file
-            (jar, data_file.expect("qed"), offsets_file.expect("qed"))
+            // Calling ensure_consistency, will fill data_file and offsets_file, and checksums_file
+            // if the jar has checksums
+            (jar, data_file.expect("qed"), offsets_file.expect("qed"), checksums_file)
+        };
+
+        let dictionary_compressors = match jar.compressor() {
+            Some(Compressors::Zstd(zstd)) => zstd.compressors()?.map(DictionaryCompressors),
+            _ => None,
         };
 
         let mut writer = Self {
             jar,
             data_file,
             offsets_file,
+            checksums_file,
+            row_hasher: crc32fast::Hasher::new(),
             tmp_buf: Vec::with_capacity(1_000_000),
+            dictionary_compressors,
             uncompressed_row_size: 0,
             offsets: Vec::with_capacity(1_000_000),
             column: 0,
@@ -227,20 +252,29 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
     /// Writes column to data file. If it's the last column of the row, call `finalize_row()`
     fn write_column(&mut self, value: &[u8]) -> Result<usize, NippyJarError> {
         self.uncompressed_row_size += value.len();
-        let len = if let Some(compression) = &self.jar.compressor {
+        let written = if let Some(compressors) = &mut self.dictionary_compressors {
+            // The buffer is written from its start, with enough capacity for the worst case.
+            self.tmp_buf.clear();
+            self.tmp_buf.reserve(zstd::zstd_safe::compress_bound(value.len()));
+            let len = compressors.0[self.column].compress_to_buffer(value, &mut self.tmp_buf)?;
+            &self.tmp_buf[..len]
+        } else if let Some(compression) = &self.jar.compressor {
             let before = self.tmp_buf.len();
             let len = compression.compress_to(value, &mut self.tmp_buf)?;
-            self.data_file.write_all(&self.tmp_buf[before..before + len])?;
-            len
+            &self.tmp_buf[before..before + len]
         } else {
-            self.data_file.write_all(value)?;
-            value.len()
+            value
         };
+        self.data_file.write_all(written)?;
+        if self.checksums_file.is_some() {
+            self.row_hasher.update(written);
+        }
+        let len = written.len();
 
         self.column += 1;
 
         if self.jar.columns == self.column {
-            self.finalize_row();
+            self.finalize_row()?;
         }
 
         Ok(len)
@@ -329,20 +363,35 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
         if self.jar.rows == 0 {
             self.jar.max_row_size = 0;
         }
+
+        if let Some(checksums_file) = &mut self.checksums_file {
+            checksums_file.flush()?;
+            checksums_file.get_mut().set_len((self.jar.rows * CHECKSUM_SIZE_BYTES) as u64)?;
+            checksums_file.get_ref().sync_all()?;
+            checksums_file.seek(SeekFrom::End(0))?;
+        }
+
         self.jar.freeze_config()?;
 
         Ok(())
     }
 
-    /// Updates [`NippyJar`] with the new row count and maximum uncompressed row size, while
-    /// resetting internal fields.
-    fn finalize_row(&mut self) {
+    /// Updates [`NippyJar`] with the new row count and maximum uncompressed row size, and writes
+    /// the checksum of the row, while resetting internal fields.
+    fn finalize_row(&mut self) -> Result<(), NippyJarError> {
+        if let Some(checksums_file) = &mut self.checksums_file {
+            let checksum = std::mem::take(&mut self.row_hasher).finalize();
+            checksums_file.write_all(&checksum.to_le_bytes())?;
+        }
+
         self.jar.max_row_size = self.jar.max_row_size.max(self.uncompressed_row_size);
         self.jar.rows += 1;
 
         self.tmp_buf.clear();
         self.uncompressed_row_size = 0;
         self.column = 0;
+
+        Ok(())
     }
 
     /// Commits configuration and offsets to disk. It drains the internal offset list.
@@ -360,6 +409,11 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
         self.data_file.flush()?;
         self.data_file.get_ref().sync_all()?;
 
+        if let Some(checksums_file) = &mut self.checksums_file {
+            checksums_file.flush()?;
+            checksums_file.get_ref().sync_all()?;
+        }
+
         self.commit_offsets()?;
         Ok(())
     }
@@ -382,6 +436,9 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
     #[cfg(feature = "test-utils")]
     pub fn commit_without_sync_all(&mut self) -> Result<(), NippyJarError> {
         self.data_file.flush()?;
+        if let Some(checksums_file) = &mut self.checksums_file {
+            checksums_file.flush()?;
+        }
 
         self.commit_offsets_without_sync_all()?;
 
@@ -485,3 +542,12 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
         &self.jar
     }
 }
+
+/// Zstd compressors loaded with the trained dictionary of each column.
+struct DictionaryCompressors(Vec<Compressor<'static>>);
+
+impl std::fmt::Debug for DictionaryCompressors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DictionaryCompressors").field("num", &self.0.len()).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,104 @@
+//! Online backups of a node's storage.
+
+use crate::{
+    DBProvider, DatabaseProviderFactory, RocksDBProviderFactory, StageCheckpointReader,
+    StaticFileProviderFactory,
+};
+use reth_db::version::create_db_version_file;
+use reth_db_api::transaction::DbTx;
+use reth_stages_types::StageId;
+use reth_storage_api::{StorageBackup, StorageSettingsCache};
+use reth_storage_errors::provider::{ProviderError, ProviderResult};
+use std::path::Path;
+use tracing::info;
+
+/// MDBX data file name inside of the database directory.
+const MDBX_DATA_FILE: &str = "mdbx.dat";
+
+/// Writes a consistent point-in-time backup of the database, static files and `RocksDB` into
+/// `dest`, using the same `db`, `static_files` and `rocksdb` layout as a regular datadir.
+///
+/// Writers are not blocked while the backup runs, only static file pruning waits for the static
+/// files to be copied. The storage backends are captured in the order they are committed in
+/// reverse: MDBX is copied from a single read transaction first, then `RocksDB` is checkpointed
+/// and static files are copied last. The later ones may therefore only be ahead of the database,
+/// which the node heals on startup like after an unclean shutdown.
+///
+/// `dest` must not exist or be an empty directory.
+pub fn backup_datadir<F>(factory: &F, dest: &Path) -> ProviderResult<StorageBackup>
+where
+    F: DatabaseProviderFactory<Provider: StageCheckpointReader + StorageSettingsCache>
+        + StaticFileProviderFactory
+        + RocksDBProviderFactory,
+{
+    if dest.exists() && reth_fs_util::read_dir(dest).map_err(ProviderError::other)?.next().is_some()
+    {
+        return Err(ProviderError::other(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("backup destination {} is not empty", dest.display()),
+        )))
+    }
+
+    // The read transaction has to outlive the copy, which can take longer than the maximum
+    // duration of a read transaction on large databases.
+    let provider = factory.database_provider_ro()?.disable_long_read_transaction_safety();
+    let block = provider.get_stage_checkpoint(StageId::Finish)?.unwrap_or_default().block_number;
+    let storage_v2 = provider.cached_storage_settings().storage_v2;
+
+    let db_dest = dest.join("db");
+    reth_fs_util::create_dir_all(&db_dest).map_err(ProviderError::other)?;
+    info!(target: "providers::backup", ?dest, block, "Copying database");
+    provider.tx_ref().copy_to(&db_dest.join(MDBX_DATA_FILE))?;
+    create_db_version_file(&db_dest).map_err(ProviderError::other)?;
+    drop(provider);
+
+    if storage_v2 {
+        info!(target: "providers::backup", ?dest, "Creating RocksDB checkpoint");
+        factory.rocksdb_provider().create_checkpoint(&dest.join("rocksdb"))?;
+    }
+
+    info!(target: "providers::backup", ?dest, "Copying static files");
+    factory.static_file_provider().copy_to(&dest.join("static_files"))?;
+
+    info!(target: "providers::backup", ?dest, block, "Backup complete");
+    Ok(StorageBackup { path: dest.to_path_buf(), block })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{blocks::BlockchainTestData, create_test_provider_factory},
+        BlockWriter, StageCheckpointWriter,
+    };
+    use reth_db::{open_db_read_only, tables, Database};
+    use reth_stages_types::StageCheckpoint;
+
+    #[test]
+    fn backup_datadir_copies_all_storage() {
+        let factory = create_test_provider_factory();
+        let data = BlockchainTestData::default();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.insert_block(&data.genesis.try_recover().unwrap()).unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(0)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let backup_dir = dest.path().join("backup");
+        let backup = backup_datadir(&factory, &backup_dir).unwrap();
+        assert_eq!(backup, StorageBackup { path: backup_dir.clone(), block: 0 });
+
+        let db = open_db_read_only(backup_dir.join("db"), Default::default()).unwrap();
+        let tx = db.tx().unwrap();
+        assert_eq!(
+            tx.get::<tables::StageCheckpoints>(StageId::Finish.to_string()).unwrap(),
+            Some(StageCheckpoint::new(0))
+        );
+        assert!(tx.get::<tables::BlockBodyIndices>(0).unwrap().is_some());
+        assert!(reth_fs_util::read_dir(backup_dir.join("static_files")).unwrap().next().is_some());
+
+        // Backups are never written over existing data.
+        assert!(backup_datadir(&factory, &backup_dir).is_err());
+    }
+}
@@ -28,6 +28,10 @@ pub use providers::{
     StaticFileWriteCtx, StaticFileWriter,
 };
 
+pub mod backup;
+
+pub mod trie_verifier;
+
 pub mod changeset_walker;
 pub mod changesets_utils;
 
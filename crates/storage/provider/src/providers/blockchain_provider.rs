@@ -16,42 +16,114 @@ use alloy_eips::{BlockHashOrNumber, BlockId, BlockNumHash, BlockNumberOrTag};
 use alloy_primitives::{Address, BlockHash, BlockNumber, Bytes, TxHash, TxNumber, B256};
 use alloy_rpc_types_engine::ForkchoiceState;
 use reth_chain_state::{
-    BlockState, CanonicalInMemoryState, ForkChoiceNotifications, ForkChoiceSubscriptions,
-    MemoryOverlayStateProvider, PersistedBlockNotifications, PersistedBlockSubscriptions,
+    BlockState, CanonStateNotification, CanonicalInMemoryState, ForkChoiceNotifications,
+    ForkChoiceSubscriptions, MemoryOverlayStateProvider, PersistedBlockNotifications,
+    PersistedBlockSubscriptions,
 };
 use reth_chainspec::ChainInfo;
 use reth_db_api::models::{AccountBeforeTx, BlockNumberAddress, StoredBlockBodyIndices};
-use reth_execution_types::ExecutionOutcome;
+use reth_execution_types::{Chain, ExecutionOutcome};
 use reth_node_types::{BlockTy, HeaderTy, NodeTypesWithDB, ReceiptTy, TxTy};
 use reth_primitives_traits::{
-    Account, RecoveredBlock, SealedHeader, SealedOrRecoveredBlock, StorageEntry,
+    Account, NodePrimitives, RecoveredBlock, SealedHeader, SealedOrRecoveredBlock, StorageEntry,
 };
 use reth_prune_types::{PruneCheckpoint, PruneSegment};
 use reth_stages_types::{StageCheckpoint, StageId};
 use reth_static_file_types::StaticFileSegment;
 use reth_storage_api::{
-    BlockBodyIndicesProvider, NodePrimitivesProvider, RangeEnd, RangeResponse, RangeResult,
-    StateRangeProvider, StateRangeProviderFactory, StateRangeView, StorageChangeSetReader,
-    StorageRangeResult,
+    AccountProofNodes, BlockBodyIndicesProvider, NodePrimitivesProvider, RangeEnd, RangeResponse,
+    RangeResult, StateRangeProvider, StateRangeProviderFactory, StateRangeView, StorageBackup,
+    StorageBackupProvider, StorageChangeSetReader, StorageRangeResult,
 };
 use reth_storage_errors::provider::ProviderResult;
 use reth_trie::{
     hashed_cursor::{HashedCursor, HashedCursorFactory},
     metrics::TrieRootMetrics,
     proof::{Proof, StorageProof},
-    MultiProofTargets, StorageRoot, TrieInput, TrieInputSorted, TrieType,
+    DecodedMultiProofV2, MultiProofTargets, MultiProofTargetsV2, ProofTrieNodeV2, ProofV2Target,
+    StorageRoot, TrieInput, TrieInputSorted, TrieType,
 };
 use std::{
+    collections::VecDeque,
     ops::{RangeBounds, RangeInclusive},
+    path::Path,
     sync::Arc,
     time::Instant,
 };
-use tracing::trace;
+use tracing::{debug, trace};
 
 /// Number of most-recent blocks whose state roots remain resolvable via
 /// [`StateRangeProviderFactory::state_range_provider`].
 pub const SNAPSHOT_STATE_RETENTION: u64 = 128;
 
+/// Maximum number of blocks sent as a single canonical state notification by
+/// [`BlockchainProvider::catch_up_with_storage`].
+pub const MAX_CATCH_UP_NOTIFICATION_BLOCKS: usize = 64;
+
+/// Blocks recently announced by [`BlockchainProvider::catch_up_with_storage`], with their receipts.
+///
+/// They are kept to announce them as reverted if another node reorgs them out of storage. At most
+/// [`MAX_CATCH_UP_NOTIFICATION_BLOCKS`] blocks are kept, ending at the canonical head.
+#[derive(Debug)]
+pub struct CaughtUpBlocks<N: NodePrimitives> {
+    blocks: VecDeque<(Arc<RecoveredBlock<N::Block>>, Vec<N::Receipt>)>,
+}
+
+impl<N: NodePrimitives> Default for CaughtUpBlocks<N> {
+    fn default() -> Self {
+        Self { blocks: VecDeque::new() }
+    }
+}
+
+impl<N: NodePrimitives> CaughtUpBlocks<N> {
+    /// Returns the number of the highest announced block, or the parent of the first one, that is
+    /// still canonical in storage.
+    fn fork_block(&self, provider: &impl BlockHashReader) -> ProviderResult<Option<BlockNumber>> {
+        for (block, _) in self.blocks.iter().rev() {
+            if provider.block_hash(block.number())? == Some(block.hash()) {
+                return Ok(Some(block.number()))
+            }
+        }
+        let Some(first) = self.blocks.front().map(|(block, _)| block) else { return Ok(None) };
+        let Some(parent) = first.number().checked_sub(1) else { return Ok(None) };
+        Ok((provider.block_hash(parent)? == Some(first.parent_hash())).then_some(parent))
+    }
+
+    /// Removes the blocks above `number` and returns them as a chain.
+    fn split_off(&mut self, number: BlockNumber) -> Option<Chain<N>> {
+        let index = self.blocks.iter().position(|(block, _)| block.number() > number)?;
+        Some(Self::chain(self.blocks.split_off(index)))
+    }
+
+    /// Appends the blocks and returns them as a chain.
+    fn extend(
+        &mut self,
+        blocks: impl IntoIterator<Item = (Arc<RecoveredBlock<N::Block>>, Vec<N::Receipt>)>,
+    ) -> Chain<N> {
+        let blocks = blocks.into_iter().collect::<Vec<_>>();
+        self.blocks.extend(blocks.iter().cloned());
+        let excess = self.blocks.len().saturating_sub(MAX_CATCH_UP_NOTIFICATION_BLOCKS);
+        self.blocks.drain(..excess);
+        Self::chain(blocks)
+    }
+
+    /// Removes all blocks.
+    fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Returns the blocks and their receipts as a chain.
+    fn chain(
+        blocks: impl IntoIterator<Item = (Arc<RecoveredBlock<N::Block>>, Vec<N::Receipt>)>,
+    ) -> Chain<N> {
+        let (blocks, receipts): (Vec<_>, Vec<_>) = blocks.into_iter().unzip();
+        let first = blocks.first().map(|block| block.number()).unwrap_or_default();
+        let execution_outcome =
+            ExecutionOutcome::new(Default::default(), receipts, first, Vec::new());
+        Chain::new(blocks, execution_outcome, Default::default())
+    }
+}
+
 type StateRangeDbProvider<N> = <ProviderFactory<N> as DatabaseProviderFactory>::Provider;
 type HistoricalStateRangeProvider<N> = OverlayStateProvider<StateRangeDbProvider<N>>;
 
@@ -139,6 +211,90 @@ impl<N: ProviderNodeTypes> BlockchainProvider<N> {
         self.canonical_in_memory_state.clone()
     }
 
+    /// Catches up with the blocks another node persisted to the storage of this provider.
+    ///
+    /// This is meant for providers opened read-only on the datadir of a running node: it advances
+    /// the canonical head, safe and finalized blocks to what the primary persisted. Blocks that
+    /// extend the previous head are sent as a canonical state notification. If the primary
+    /// reorged blocks in `announced` out of storage, they are sent as reverted in a reorg
+    /// notification. Notifications are skipped if more than
+    /// [`MAX_CATCH_UP_NOTIFICATION_BLOCKS`] were persisted at once, or if the reorged blocks are
+    /// no longer known.
+    ///
+    /// Returns the new canonical head if it changed.
+    pub fn catch_up_with_storage(
+        &self,
+        announced: &mut CaughtUpBlocks<N::Primitives>,
+    ) -> ProviderResult<Option<SealedHeader<HeaderTy<N>>>> {
+        let provider = self.database.provider()?;
+
+        if let Some(num) = provider.last_safe_block_number()? &&
+            self.canonical_in_memory_state.get_safe_num_hash().map(|safe| safe.number) !=
+                Some(num) &&
+            let Some(header) = provider.sealed_header(num)?
+        {
+            self.canonical_in_memory_state.set_safe(header);
+        }
+        if let Some(num) = provider.last_finalized_block_number()? &&
+            self.canonical_in_memory_state.get_finalized_num_hash().map(|fin| fin.number) !=
+                Some(num) &&
+            let Some(header) = provider.sealed_header(num)?
+        {
+            self.canonical_in_memory_state.set_finalized(header);
+        }
+
+        let best = provider.chain_info()?;
+        let head = self.canonical_in_memory_state.get_canonical_head();
+        if best.best_hash == head.hash() {
+            return Ok(None)
+        }
+        let new_head = provider
+            .sealed_header(best.best_number)?
+            .ok_or_else(|| ProviderError::HeaderNotFound(best.best_number.into()))?;
+        self.canonical_in_memory_state.set_canonical_head(new_head.clone());
+
+        // The new blocks are built on the highest previous block that is still in storage, the
+        // announced blocks above it were reorged out.
+        let fork_block = if provider.block_hash(head.number())? == Some(head.hash()) {
+            Some(head.number())
+        } else {
+            announced.fork_block(&provider)?
+        };
+        let notification = match fork_block {
+            Some(fork_block)
+                if fork_block < best.best_number &&
+                    best.best_number - fork_block <= MAX_CATCH_UP_NOTIFICATION_BLOCKS as u64 =>
+            {
+                let range = fork_block + 1..=best.best_number;
+                let blocks = provider.recovered_block_range(range.clone())?;
+                let receipts = provider.receipts_by_block_range(range)?;
+                let old = announced.split_off(fork_block);
+                let new =
+                    Arc::new(announced.extend(blocks.into_iter().map(Arc::new).zip(receipts)));
+                Some(match old {
+                    Some(old) => CanonStateNotification::Reorg { old: Arc::new(old), new },
+                    None => CanonStateNotification::Commit { new },
+                })
+            }
+            _ => None,
+        };
+
+        if let Some(notification) = notification {
+            self.canonical_in_memory_state.notify_canon_state(notification);
+        } else {
+            debug!(
+                target: "providers::blockchain",
+                old_head = ?head.num_hash(),
+                new_head = ?new_head.num_hash(),
+                ?fork_block,
+                "Skipping canonical state notification for caught up blocks"
+            );
+            announced.clear();
+        }
+
+        Ok(Some(new_head))
+    }
+
     /// Returns a provider with a created `DbTx` inside, which allows fetching data from the
     /// database using different types of providers. Example: [`HeaderProvider`]
     /// [`BlockHashReader`]. This may fail if the inner read database transaction fails to open.
@@ -361,6 +517,38 @@ impl<N: ProviderNodeTypes> StateRangeProvider for HistoricalStateRangeView<N> {
             .map_err(ProviderError::from)?;
         Ok(multiproof.subtree.into_nodes_sorted().into_iter().map(|(_, bytes)| bytes).collect())
     }
+
+    fn account_proof(
+        &self,
+        hashed_address: B256,
+        slots: &[B256],
+    ) -> ProviderResult<AccountProofNodes> {
+        // the proof calculator expects targets sorted lexicographically
+        let mut slots = slots.to_vec();
+        slots.sort_unstable();
+        slots.dedup();
+        let mut targets = MultiProofTargetsV2 {
+            account_targets: vec![ProofV2Target::new(hashed_address)],
+            ..Default::default()
+        };
+        if !slots.is_empty() {
+            targets
+                .storage_targets
+                .insert(hashed_address, slots.into_iter().map(ProofV2Target::new).collect());
+        }
+
+        let DecodedMultiProofV2 { account_proofs, mut storage_proofs } =
+            Proof::new(&self.provider, &self.provider)
+                .multiproof_v2(targets)
+                .map_err(ProviderError::from)?;
+        let encode = |nodes: Vec<ProofTrieNodeV2>| {
+            nodes.iter().flat_map(ProofTrieNodeV2::encode_mpt_nodes).collect::<Vec<_>>()
+        };
+        Ok(AccountProofNodes {
+            account_proof: encode(account_proofs),
+            storage_proof: storage_proofs.remove(&hashed_address).map(encode).unwrap_or_default(),
+        })
+    }
 }
 
 impl<N: ProviderNodeTypes> DatabaseProviderFactory for BlockchainProvider<N> {
@@ -391,6 +579,12 @@ impl<N: ProviderNodeTypes> StaticFileProviderFactory for BlockchainProvider<N> {
     }
 }
 
+impl<N: ProviderNodeTypes> StorageBackupProvider for BlockchainProvider<N> {
+    fn backup_storage(&self, dest: &Path) -> ProviderResult<StorageBackup> {
+        self.database.backup_storage(dest)
+    }
+}
+
 impl<N: ProviderNodeTypes> RocksDBProviderFactory for BlockchainProvider<N> {
     fn rocksdb_provider(&self) -> RocksDBProvider {
         self.database.rocksdb_provider()
@@ -834,6 +1028,10 @@ impl<N: ProviderNodeTypes> StateProviderFactory for BlockchainProvider<N> {
 
         Ok(None)
     }
+
+    fn has_archived_trie_changesets(&self, block: BlockNumber) -> ProviderResult<bool> {
+        self.database.provider()?.has_archived_trie_changesets(block)
+    }
 }
 
 impl<N: ProviderNodeTypes> CanonChainTracker for BlockchainProvider<N> {
@@ -2977,6 +3175,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn state_range_provider_account_proof_matches_range_proofs() -> eyre::Result<()> {
+        let factory = test_provider_factory_with_genesis()?;
+        let provider_rw = factory.provider_rw()?;
+
+        let (address, account) = random_account(1);
+        let hashed_address = keccak256(address);
+        let slot = B256::with_last_byte(1);
+        provider_rw.insert_account_for_hashing([(address, Some(account))])?;
+        provider_rw.insert_storage_for_hashing([(
+            address,
+            [StorageEntry { key: slot, value: U256::from(10) }],
+        )])?;
+        provider_rw.commit()?;
+
+        let provider = BlockchainProvider::new(factory)?;
+        let state = provider.state_range_provider(EMPTY_ROOT_HASH)?.unwrap();
+
+        let hashed_slot = keccak256(slot);
+        let AccountProofNodes { mut account_proof, mut storage_proof } =
+            state.account_proof(hashed_address, &[hashed_slot])?;
+        let mut expected_account_proof = state.account_range_proof(&[hashed_address])?;
+        let mut expected_storage_proof =
+            state.storage_range_proof(hashed_address, &[hashed_slot])?;
+        for proof in [
+            &mut account_proof,
+            &mut storage_proof,
+            &mut expected_account_proof,
+            &mut expected_storage_proof,
+        ] {
+            proof.sort_unstable();
+        }
+        assert_eq!(account_proof, expected_account_proof);
+        assert_eq!(storage_proof, expected_storage_proof);
+
+        Ok(())
+    }
+
     #[test]
     fn state_range_provider_serves_recent_root_and_rejects_expired_root() -> eyre::Result<()> {
         let mut rng = generators::rng();
@@ -3263,4 +3499,74 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn catch_up_with_storage_follows_persisted_blocks() -> eyre::Result<()> {
+        let mut rng = generators::rng();
+        let factory = create_test_provider_factory();
+        let blocks = random_block_range(
+            &mut rng,
+            0..=3,
+            BlockRangeParams { parent: Some(B256::ZERO), tx_count: 0..1, ..Default::default() },
+        );
+
+        let provider_rw = factory.provider_rw()?;
+        provider_rw.insert_block(&blocks[0].clone().try_recover().expect("failed to recover"))?;
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(0))?;
+        provider_rw.commit()?;
+
+        let provider = BlockchainProvider::new(factory.clone())?;
+        let mut notifications = provider.subscribe_to_canonical_state();
+        let mut announced = CaughtUpBlocks::default();
+        assert_eq!(provider.catch_up_with_storage(&mut announced)?, None);
+
+        // Another writer persists blocks behind the provider's back.
+        let provider_rw = factory.provider_rw()?;
+        for block in &blocks[1..] {
+            provider_rw.insert_block(&block.clone().try_recover().expect("failed to recover"))?;
+        }
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(3))?;
+        provider_rw.commit()?;
+
+        let head = provider.catch_up_with_storage(&mut announced)?.expect("head should advance");
+        assert_eq!(head.hash(), blocks[3].hash());
+        assert_eq!(provider.best_block_number()?, 3);
+
+        let CanonStateNotification::Commit { new } = notifications.try_recv()? else {
+            panic!("expected a commit notification")
+        };
+        assert_eq!(new.range(), 1..=3);
+        assert_eq!(provider.catch_up_with_storage(&mut announced)?, None);
+
+        // Another writer reorgs the announced blocks above block 1 out of storage.
+        let fork = random_block_range(
+            &mut rng,
+            2..=4,
+            BlockRangeParams {
+                parent: Some(blocks[1].hash()),
+                tx_count: 0..1,
+                ..Default::default()
+            },
+        );
+        let provider_rw = factory.provider_rw()?;
+        provider_rw.remove_blocks_above(1)?;
+        for block in &fork {
+            provider_rw.insert_block(&block.clone().try_recover().expect("failed to recover"))?;
+        }
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(4))?;
+        provider_rw.commit()?;
+
+        let head = provider.catch_up_with_storage(&mut announced)?.expect("head should advance");
+        assert_eq!(head.hash(), fork[2].hash());
+
+        let CanonStateNotification::Reorg { old, new } = notifications.try_recv()? else {
+            panic!("expected a reorg notification")
+        };
+        assert_eq!(old.range(), 2..=3);
+        assert_eq!(old.tip().hash(), blocks[3].hash());
+        assert_eq!(new.range(), 2..=4);
+        assert_eq!(new.tip().hash(), fork[2].hash());
+
+        Ok(())
+    }
 }
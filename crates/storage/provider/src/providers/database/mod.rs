@@ -1,4 +1,5 @@
 use crate::{
+    backup::backup_datadir,
     providers::{
         state::latest::LatestStateProvider, NodeTypesForProvider, RocksDBProvider,
         StaticFileProvider, StaticFileProviderRWRefMut,
@@ -30,7 +31,8 @@ use reth_stages_types::{PipelineTarget, StageCheckpoint, StageId};
 use reth_static_file_types::StaticFileSegment;
 use reth_storage_api::{
     BlockBodyIndicesProvider, ChainStateBlockReader, ChainStateBlockWriter, DBProvider,
-    NodePrimitivesProvider, StorageSettings, StorageSettingsCache, TryIntoHistoricalStateProvider,
+    ForkStateSource, NodePrimitivesProvider, StorageBackup, StorageBackupProvider, StorageSettings,
+    StorageSettingsCache, TryIntoHistoricalStateProvider,
 };
 use reth_storage_errors::provider::ProviderResult;
 use reth_storage_overlay::OverlayManager;
@@ -94,6 +96,10 @@ pub struct ProviderFactory<N: NodeTypesWithDB> {
     runtime: reth_tasks::Runtime,
     /// Minimum distance from tip required before pruning can occur.
     minimum_pruning_distance: u64,
+    /// Whether the trie changesets of persisted blocks are archived.
+    trie_changesets_archive: bool,
+    /// State of the remote chain this chain was forked from, if any.
+    fork_state: Option<Arc<dyn ForkStateSource>>,
     /// Database provider metrics shared by providers created from this factory.
     database_provider_metrics: Arc<DatabaseProviderMetrics>,
     /// State for on-demand syncing of `RocksDB` secondary and static file indexes.
@@ -160,6 +166,8 @@ impl<N: ProviderNodeTypes> ProviderFactory<N> {
             bal_store: BalStoreHandle::new(InMemoryBalStore::default()),
             runtime,
             minimum_pruning_distance: MINIMUM_UNWIND_SAFE_DISTANCE,
+            trie_changesets_archive: false,
+            fork_state: None,
             database_provider_metrics,
             read_only_sync: None,
         })
@@ -216,6 +224,26 @@ impl<N: NodeTypesWithDB> ProviderFactory<N> {
         self
     }
 
+    /// Enables archiving the trie changesets of persisted blocks for an existing
+    /// [`ProviderFactory`].
+    ///
+    /// Archived changesets allow state proofs at any block after archiving started, without
+    /// recomputing the historical trie from state reverts.
+    pub const fn with_trie_changesets_archive(mut self, enabled: bool) -> Self {
+        self.trie_changesets_archive = enabled;
+        self
+    }
+
+    /// Sets the state of the remote chain this chain was forked from for an existing
+    /// [`ProviderFactory`].
+    ///
+    /// State providers fall back to the fork state for accounts, storage slots, bytecodes and block
+    /// hashes that were never written locally. See [`ForkStateSource`].
+    pub fn with_fork_state(mut self, fork_state: Arc<dyn ForkStateSource>) -> Self {
+        self.fork_state = Some(fork_state);
+        self
+    }
+
     /// Enables on-demand syncing of `RocksDB` secondary and static file indexes for read-only
     /// factories. Initializes the tracker to the current MDBX txn ID.
     ///
@@ -401,7 +429,9 @@ impl<N: ProviderNodeTypes> ProviderFactory<N> {
             self.db.path(),
             self.database_provider_metrics.clone(),
         )
-        .with_minimum_pruning_distance(self.minimum_pruning_distance))
+        .with_minimum_pruning_distance(self.minimum_pruning_distance)
+        .with_trie_changesets_archive(self.trie_changesets_archive)
+        .with_fork_state(self.fork_state.clone()))
     }
 
     /// Returns a provider with a created `DbTxMut` inside, which allows fetching and updating
@@ -425,7 +455,9 @@ impl<N: ProviderNodeTypes> ProviderFactory<N> {
                 self.database_provider_metrics.clone(),
             )
             .with_reader_txn_tracker(self.db.clone())
-            .with_minimum_pruning_distance(self.minimum_pruning_distance),
+            .with_minimum_pruning_distance(self.minimum_pruning_distance)
+            .with_trie_changesets_archive(self.trie_changesets_archive)
+            .with_fork_state(self.fork_state.clone()),
         ))
     }
 
@@ -453,14 +485,23 @@ impl<N: ProviderNodeTypes> ProviderFactory<N> {
             self.database_provider_metrics.clone(),
         )
         .with_reader_txn_tracker(self.db.clone())
-        .with_minimum_pruning_distance(self.minimum_pruning_distance))
+        .with_minimum_pruning_distance(self.minimum_pruning_distance)
+        .with_trie_changesets_archive(self.trie_changesets_archive)
+        .with_fork_state(self.fork_state.clone()))
     }
 
     /// State provider for latest block
     #[track_caller]
     pub fn latest(&self) -> ProviderResult<StateProviderBox> {
         trace!(target: "providers::db", "Returning latest state provider");
-        Ok(Box::new(LatestStateProvider::new(self.database_provider_ro()?)))
+        let provider = self.database_provider_ro()?;
+        if self.fork_state.is_some() {
+            // Plain state alone can't tell keys deleted locally from keys that only exist on the
+            // forked chain, so go through the history indices.
+            let best_block_number = provider.best_block_number()?;
+            return provider.try_into_history_at_block(best_block_number)
+        }
+        Ok(Box::new(LatestStateProvider::new(provider)))
     }
 
     /// Storage provider for state at that given block
@@ -962,6 +1003,12 @@ impl<N: ProviderNodeTypes> MetadataProvider for ProviderFactory<N> {
     }
 }
 
+impl<N: ProviderNodeTypes> StorageBackupProvider for ProviderFactory<N> {
+    fn backup_storage(&self, dest: &Path) -> ProviderResult<StorageBackup> {
+        backup_datadir(self, dest)
+    }
+}
+
 impl<N> fmt::Debug for ProviderFactory<N>
 where
     N: NodeTypesWithDB<DB: fmt::Debug, ChainSpec: fmt::Debug, Storage: fmt::Debug>,
@@ -979,6 +1026,8 @@ where
             bal_store,
             runtime,
             minimum_pruning_distance,
+            trie_changesets_archive,
+            fork_state,
             database_provider_metrics: _,
             read_only_sync,
         } = self;
@@ -994,6 +1043,8 @@ where
             .field("bal_store", &bal_store)
             .field("runtime", &runtime)
             .field("minimum_pruning_distance", &minimum_pruning_distance)
+            .field("trie_changesets_archive", &trie_changesets_archive)
+            .field("fork_state", &fork_state)
             .field(
                 "read_only_sync",
                 &read_only_sync.as_ref().map(|s| s.last_synced_txnid.load(Ordering::Relaxed)),
@@ -1016,6 +1067,8 @@ impl<N: NodeTypesWithDB> Clone for ProviderFactory<N> {
             bal_store: self.bal_store.clone(),
             runtime: self.runtime.clone(),
             minimum_pruning_distance: self.minimum_pruning_distance,
+            trie_changesets_archive: self.trie_changesets_archive,
+            fork_state: self.fork_state.clone(),
             database_provider_metrics: self.database_provider_metrics.clone(),
             read_only_sync: self.read_only_sync.clone(),
         }
@@ -41,8 +41,8 @@ use reth_db_api::{
     database::{Database, ReaderTxnTracker},
     models::{
         sharded_key, storage_sharded_key::StorageShardedKey, AccountBeforeTx, BlockNumberAddress,
-        BlockNumberAddressRange, ShardedKey, StorageBeforeTx, StorageSettings,
-        StoredBlockBodyIndices,
+        BlockNumberAddressRange, BlockNumberHashedAddress, ShardedKey, StorageBeforeTx,
+        StorageSettings, StoredBlockBodyIndices,
     },
     table::Table,
     tables,
@@ -61,17 +61,19 @@ use reth_prune_types::{
 use reth_stages_types::{FinishCheckpoint, StageCheckpoint, StageId};
 use reth_static_file_types::StaticFileSegment;
 use reth_storage_api::{
-    BlockBodyIndicesProvider, BlockBodyReader, MetadataProvider, MetadataWriter,
+    BlockBodyIndicesProvider, BlockBodyReader, ForkStateSource, MetadataProvider, MetadataWriter,
     NodePrimitivesProvider, StateProvider, StateReader, StateWriteConfig, StorageChangeSetReader,
     StoragePath, StorageSettingsCache, TryIntoHistoricalStateProvider, WriteStateInput,
 };
 use reth_storage_errors::provider::{ProviderResult, StaticFileWriterError};
 use reth_storage_overlay::OverlayManager;
 use reth_trie::{
+    changesets::compute_trie_changesets,
+    trie_cursor::InMemoryTrieCursorFactory,
     updates::{StorageTrieUpdatesSorted, TrieUpdatesSorted},
     ComputedTrieData, HashedPostStateSorted,
 };
-use reth_trie_db::{DatabaseStorageTrieCursor, TrieTableAdapter};
+use reth_trie_db::{DatabaseStorageTrieCursor, DatabaseTrieCursorFactory, TrieTableAdapter};
 use revm::database::states::{
     PlainStateReverts, PlainStorageChangeset, PlainStorageRevert, StateChangeset,
 };
@@ -213,6 +215,10 @@ pub struct DatabaseProvider<TX, N: NodeTypes> {
     commit_order: CommitOrder,
     /// Minimum distance from tip required for pruning
     minimum_pruning_distance: u64,
+    /// Whether the trie changesets of persisted blocks are archived
+    trie_changesets_archive: bool,
+    /// State of the remote chain this chain was forked from
+    fork_state: Option<Arc<dyn ForkStateSource>>,
     /// Database provider metrics
     metrics: Arc<DatabaseProviderMetrics>,
     /// Database handle used to inspect active MDBX readers during unwind commits.
@@ -234,6 +240,8 @@ impl<TX: Debug, N: NodeTypes> Debug for DatabaseProvider<TX, N> {
             .field("pending_rocksdb_batches", &"<pending batches>")
             .field("commit_order", &self.commit_order)
             .field("minimum_pruning_distance", &self.minimum_pruning_distance)
+            .field("trie_changesets_archive", &self.trie_changesets_archive)
+            .field("fork_state", &self.fork_state)
             .field("reader_txn_tracker", &"<reader txn tracker>")
             .finish()
     }
@@ -251,6 +259,18 @@ impl<TX, N: NodeTypes> DatabaseProvider<TX, N> {
         self
     }
 
+    /// Sets whether the trie changesets of persisted blocks are archived.
+    pub const fn with_trie_changesets_archive(mut self, enabled: bool) -> Self {
+        self.trie_changesets_archive = enabled;
+        self
+    }
+
+    /// Sets the state of the remote chain this chain was forked from.
+    pub fn with_fork_state(mut self, fork_state: Option<Arc<dyn ForkStateSource>>) -> Self {
+        self.fork_state = fork_state;
+        self
+    }
+
     /// Attaches reader tracking so unwind commits can wait on active readers.
     pub(crate) fn with_reader_txn_tracker<T>(mut self, reader_txn_tracker: T) -> Self
     where
@@ -262,6 +282,16 @@ impl<TX, N: NodeTypes> DatabaseProvider<TX, N> {
 }
 
 impl<TX: DbTx + 'static, N: NodeTypes> DatabaseProvider<TX, N> {
+    /// Returns `true` if the trie changesets of all blocks after `block` up to the database tip
+    /// are archived.
+    pub fn has_archived_trie_changesets(&self, block: BlockNumber) -> ProviderResult<bool> {
+        let Some(archived) = reth_trie_db::archived_trie_changesets_range(&self.tx)? else {
+            return Ok(false)
+        };
+        let db_tip = self.best_block_number()?;
+        Ok(block >= db_tip || (*archived.start() <= block + 1 && *archived.end() >= db_tip))
+    }
+
     /// Commits unwind writes in MDBX -> `RocksDB` -> static-file order.
     ///
     /// This keeps MDBX as the first durable step so an interrupted unwind can be recovered by
@@ -313,7 +343,8 @@ impl<TX: DbTx + 'static, N: NodeTypes> DatabaseProvider<TX, N> {
         &'a self,
         mut block_number: BlockNumber,
     ) -> ProviderResult<Box<dyn StateProvider + 'a>> {
-        if block_number == self.best_block_number().unwrap_or_default() &&
+        if self.fork_state.is_none() &&
+            block_number == self.best_block_number().unwrap_or_default() &&
             block_number == self.last_block_number().unwrap_or_default()
         {
             return Ok(Box::new(LatestStateProviderRef::new(self)))
@@ -328,7 +359,8 @@ impl<TX: DbTx + 'static, N: NodeTypes> DatabaseProvider<TX, N> {
             self.get_prune_checkpoint(PruneSegment::StorageHistory)?;
 
         let mut state_provider =
-            HistoricalStateProviderRef::new(self, block_number, self.overlay_manager.clone());
+            HistoricalStateProviderRef::new(self, block_number, self.overlay_manager.clone())
+                .with_fork_state(self.fork_state.clone());
         // If we pruned account or storage history, we can't return state on every historical block.
         // Instead, we should cap it at the latest prune checkpoint for corresponding prune segment.
         if let Some(prune_checkpoint_block_number) =
@@ -435,6 +467,8 @@ impl<TX: DbTxMut, N: NodeTypes> DatabaseProvider<TX, N> {
             pending_rocksdb_batches: Default::default(),
             commit_order,
             minimum_pruning_distance: MINIMUM_UNWIND_SAFE_DISTANCE,
+            trie_changesets_archive: false,
+            fork_state: None,
             metrics,
             reader_txn_tracker: None,
         }
@@ -596,6 +630,12 @@ impl<TX: DbTx + DbTxMut + 'static, N: NodeTypesForProvider> DatabaseProvider<TX,
             ))))
         }
 
+        // Changesets are computed against the trie tables, so they must be archived before the
+        // trie updates are written.
+        if self.trie_changesets_archive {
+            self.write_trie_changesets_archive(input)?;
+        }
+
         self.save_blocks_inner(
             input.persist_rest_blocks(),
             input.state_trie_blocks(),
@@ -606,6 +646,107 @@ impl<TX: DbTx + DbTxMut + 'static, N: NodeTypesForProvider> DatabaseProvider<TX,
         )
     }
 
+    /// Archives the trie changesets of the blocks whose data is persisted by the input.
+    ///
+    /// The trie tables are at `prev_partial_state_trie`, so the trie before each block is the
+    /// database trie overlaid with the trie updates of the preceding blocks of the input.
+    ///
+    /// The archive is restarted at the first persisted block if it does not cover the block
+    /// before, e.g. because the archive was disabled or blocks were synced by the pipeline.
+    ///
+    /// Rows outside of the archived range are removed before writing, so that rows left behind
+    /// by earlier runs never end up in the archive.
+    fn write_trie_changesets_archive(
+        &self,
+        input: &SaveBlocksInput<N::Primitives>,
+    ) -> ProviderResult<()> {
+        let Some(first_block) = input.first_persist_rest_block() else { return Ok(()) };
+        let first_number = first_block.recovered_block().number();
+        let start = Instant::now();
+
+        let archived = reth_trie_db::archived_trie_changesets_range(&self.tx)?;
+        if archived.is_none_or(|archived| *archived.end() + 1 != first_number) {
+            // remove all changesets of a previous archive
+            self.tx.clear::<tables::AccountsTrieChangeSets>()?;
+            self.tx.clear::<tables::StoragesTrieChangeSets>()?;
+            self.tx.put::<tables::ChainState>(
+                tables::ChainStateKey::EarliestTrieChangeSetBlock,
+                first_number,
+            )?;
+        } else {
+            // remove changesets past the archived range, e.g. of blocks that were unwound while
+            // the archive was disabled
+            self.remove_trie_changesets_from(first_number)?;
+        }
+
+        reth_trie_db::with_adapter!(self, |A| {
+            let db_cursor_factory = DatabaseTrieCursorFactory::<_, A>::new(&self.tx);
+            let mut overlay = TrieUpdatesSorted::default();
+            for block in input.blocks() {
+                let block_number = block.recovered_block().number();
+                let trie_updates = block.trie_data.get().sorted.trie_updates.clone();
+                if block_number >= first_number {
+                    let cursor_factory =
+                        InMemoryTrieCursorFactory::new(db_cursor_factory.clone(), &overlay);
+                    let changesets = compute_trie_changesets(&cursor_factory, &trie_updates)?;
+                    reth_trie_db::write_block_trie_changesets(&self.tx, block_number, &changesets)?;
+                }
+                overlay.extend_ref_and_sort(&trie_updates);
+            }
+        });
+
+        self.tx.put::<tables::ChainState>(
+            tables::ChainStateKey::LatestTrieChangeSetBlock,
+            input.new_db_tip(),
+        )?;
+
+        debug!(
+            target: "providers::db",
+            first_number,
+            last_number = input.new_db_tip(),
+            elapsed = ?start.elapsed(),
+            "Archived trie changesets"
+        );
+        Ok(())
+    }
+
+    /// Removes the archived trie changesets of all blocks from `from` onwards and shrinks the
+    /// archived range accordingly.
+    fn remove_trie_changesets_archive_from(&self, from: BlockNumber) -> ProviderResult<()> {
+        let Some(archived) = reth_trie_db::archived_trie_changesets_range(&self.tx)? else {
+            // stale changesets are removed when archiving restarts
+            return Ok(())
+        };
+
+        self.remove_trie_changesets_from(from)?;
+
+        if from <= *archived.start() {
+            self.tx.delete::<tables::ChainState>(
+                tables::ChainStateKey::EarliestTrieChangeSetBlock,
+                None,
+            )?;
+            self.tx.delete::<tables::ChainState>(
+                tables::ChainStateKey::LatestTrieChangeSetBlock,
+                None,
+            )?;
+        } else if from <= *archived.end() {
+            self.tx.put::<tables::ChainState>(
+                tables::ChainStateKey::LatestTrieChangeSetBlock,
+                from - 1,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Removes the trie changesets of all blocks from `from` onwards.
+    fn remove_trie_changesets_from(&self, from: BlockNumber) -> ProviderResult<()> {
+        self.remove::<tables::AccountsTrieChangeSets>(from..)?;
+        self.remove::<tables::StoragesTrieChangeSets>(
+            BlockNumberHashedAddress((from, B256::ZERO))..,
+        )?;
+        Ok(())
+    }
+
     fn save_blocks_inner(
         &self,
         blocks: &[ExecutedBlock<N::Primitives>],
@@ -960,6 +1101,8 @@ impl<TX: DbTx + DbTxMut + 'static, N: NodeTypesForProvider> DatabaseProvider<TX,
             .get_or_compute_cached_changesets_range(self, from..=db_tip_block)?;
         self.write_trie_updates_sorted(&trie_revert)?;
 
+        self.remove_trie_changesets_archive_from(from)?;
+
         Ok(())
     }
 
@@ -1016,8 +1159,10 @@ impl<TX: DbTx + 'static, N: NodeTypes> TryIntoHistoricalStateProvider for Databa
             });
         }
 
-        // If requesting state at the best block, use the latest state provider
-        if block_number == best_block {
+        // If requesting state at the best block, use the latest state provider. A forked chain
+        // always goes through history, which tells keys that were never written locally apart
+        // from deleted ones.
+        if block_number == best_block && self.fork_state.is_none() {
             return Ok(Box::new(LatestStateProvider::new(self)));
         }
 
@@ -1029,8 +1174,10 @@ impl<TX: DbTx + 'static, N: NodeTypes> TryIntoHistoricalStateProvider for Databa
         let storage_history_prune_checkpoint =
             self.get_prune_checkpoint(PruneSegment::StorageHistory)?;
         let overlay_manager = self.overlay_manager.clone();
+        let fork_state = self.fork_state.clone();
 
-        let mut state_provider = HistoricalStateProvider::new(self, block_number, overlay_manager);
+        let mut state_provider = HistoricalStateProvider::new(self, block_number, overlay_manager)
+            .with_fork_state(fork_state);
 
         // If we pruned account or storage history, we can't return state on every historical block.
         // Instead, we should cap it at the latest prune checkpoint for corresponding prune segment.
@@ -1146,6 +1293,8 @@ impl<TX: DbTx + 'static, N: NodeTypesForProvider> DatabaseProvider<TX, N> {
             pending_rocksdb_batches: Default::default(),
             commit_order: CommitOrder::Normal,
             minimum_pruning_distance: MINIMUM_UNWIND_SAFE_DISTANCE,
+            trie_changesets_archive: false,
+            fork_state: None,
             metrics,
             reader_txn_tracker: None,
         }
@@ -4080,9 +4229,7 @@ mod tests {
         map::{AddressMap, B256Map},
         U256,
     };
-    #[cfg(feature = "partial-persistence")]
-    use reth_chain_state::test_utils::TestBlockBuilder;
-    use reth_chain_state::ExecutedBlock;
+    use reth_chain_state::{test_utils::TestBlockBuilder, ExecutedBlock};
     use reth_db_api::models::StorageSettings;
     use reth_ethereum_primitives::Receipt;
     use reth_execution_types::{AccountRevertInit, BlockExecutionOutput, BlockExecutionResult};
@@ -4633,6 +4780,68 @@ mod tests {
         provider_rw.commit().unwrap();
     }
 
+    #[test]
+    fn test_save_blocks_archives_trie_changesets() {
+        use reth_trie::{updates::TrieUpdatesSorted, BranchNodeCompact, HashedPostStateSorted};
+
+        fn branch(mask: u16) -> BranchNodeCompact {
+            BranchNodeCompact::new(mask, 0, 0, vec![], None)
+        }
+
+        let factory = create_test_provider_factory().with_trie_changesets_archive(true);
+        let mut test_block_builder = TestBlockBuilder::eth().with_state();
+        let genesis = test_block_builder.get_executed_blocks(0..1).next().unwrap();
+        let blocks: Vec<_> = test_block_builder
+            .get_executed_blocks(1..3)
+            .zip([0b0011, 0b1100])
+            .map(|(block, mask)| {
+                let trie_updates = TrieUpdatesSorted::new(
+                    vec![(Nibbles::from_nibbles([0xf, 0xe]), Some(branch(mask)))],
+                    B256Map::default(),
+                );
+                ExecutedBlock::new(
+                    block.recovered_block,
+                    block.execution_output,
+                    ComputedTrieData::new(
+                        Arc::new(HashedPostStateSorted::default()),
+                        Arc::new(trie_updates),
+                    ),
+                )
+            })
+            .collect();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        save_genesis(&provider_rw, &genesis).unwrap();
+        // rows left behind by an earlier run are not part of the archive
+        let stale = TrieUpdatesSorted::new(
+            vec![(Nibbles::from_nibbles([0x1]), Some(branch(0b0001)))],
+            B256Map::default(),
+        );
+        reth_trie_db::write_block_trie_changesets(provider_rw.tx_ref(), 5, &stale).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.save_blocks(&SaveBlocksInput::new(blocks, 0, 0, 2, 2)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_ref();
+        assert_eq!(reth_trie_db::archived_trie_changesets_range(tx).unwrap(), Some(1..=2));
+        assert!(tx.get::<tables::AccountsTrieChangeSets>(5).unwrap().is_none());
+
+        let node = |changesets: TrieUpdatesSorted| changesets.account_nodes_ref()[0].1.clone();
+        let changesets = reth_trie_db::read_range_trie_changesets(tx, 2..=2).unwrap().unwrap();
+        assert_eq!(node(changesets), Some(branch(0b0011)));
+        let changesets = reth_trie_db::read_range_trie_changesets(tx, 1..=2).unwrap().unwrap();
+        assert_eq!(node(changesets), None);
+        assert!(provider_rw.has_archived_trie_changesets(0).unwrap());
+
+        provider_rw.remove_trie_changesets_archive_from(2).unwrap();
+        assert_eq!(reth_trie_db::archived_trie_changesets_range(tx).unwrap(), Some(1..=1));
+        assert!(reth_trie_db::read_range_trie_changesets(tx, 2..=2).unwrap().is_none());
+        assert!(!provider_rw.has_archived_trie_changesets(0).unwrap());
+    }
+
     #[cfg(feature = "partial-persistence")]
     #[test]
     fn test_save_blocks_only_masks_trie_with_deferred_blocks() {
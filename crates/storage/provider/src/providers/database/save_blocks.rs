@@ -112,6 +112,11 @@ impl<N: NodePrimitives> SaveBlocksInput<N> {
         self.new_partial_state_trie
     }
 
+    /// Returns all blocks of the input, covering `(prev_partial_state_trie, new_db_tip]`.
+    pub fn blocks(&self) -> &[ExecutedBlock<N>] {
+        &self.blocks
+    }
+
     /// Returns the block at the new Finish checkpoint.
     pub fn last_block(&self) -> BlockNumHash {
         self.blocks
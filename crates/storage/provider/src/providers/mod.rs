@@ -24,7 +24,9 @@ pub use state::{
 };
 
 mod blockchain_provider;
-pub use blockchain_provider::{BlockchainProvider, SNAPSHOT_STATE_RETENTION};
+pub use blockchain_provider::{
+    BlockchainProvider, CaughtUpBlocks, MAX_CATCH_UP_NOTIFICATION_BLOCKS, SNAPSHOT_STATE_RETENTION,
+};
 
 mod consistent;
 pub use consistent::ConsistentProvider;
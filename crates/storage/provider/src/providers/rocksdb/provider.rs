@@ -26,8 +26,8 @@ use reth_storage_errors::{
     provider::{ProviderError, ProviderResult},
 };
 use rocksdb::{
-    BlockBasedOptions, Cache, ColumnFamilyDescriptor, CompactionPri, DBCompressionType,
-    DBRawIteratorWithThreadMode, IteratorMode, OptimisticTransactionDB,
+    checkpoint::Checkpoint, BlockBasedOptions, Cache, ColumnFamilyDescriptor, CompactionPri,
+    DBCompressionType, DBRawIteratorWithThreadMode, IteratorMode, OptimisticTransactionDB,
     OptimisticTransactionOptions, Options, SnapshotWithThreadMode, Transaction,
     WriteBatchWithTransaction, WriteBufferManager, WriteOptions, DB,
 };
@@ -1094,6 +1094,51 @@ impl RocksDBProvider {
         Ok(())
     }
 
+    /// Creates a point-in-time checkpoint of the database in the `dest` directory.
+    ///
+    /// SST files are hard-linked if `dest` is on the same filesystem and copied otherwise.
+    /// Secondary instances don't support checkpoints, so they catch up with the primary and
+    /// copy all entries into a new database instead. The directory must not exist yet.
+    #[instrument(level = "debug", target = "providers::rocksdb", skip_all, fields(?dest))]
+    pub fn create_checkpoint(&self, dest: &Path) -> ProviderResult<()> {
+        let checkpoint_error = |e: rocksdb::Error| {
+            ProviderError::Database(DatabaseError::Other(format!(
+                "failed to create RocksDB checkpoint at {}: {e}",
+                dest.display()
+            )))
+        };
+
+        match self.0.as_ref() {
+            RocksDBProviderInner::ReadWrite { db, .. } => Checkpoint::new(db)
+                .and_then(|checkpoint| checkpoint.create_checkpoint(dest))
+                .map_err(checkpoint_error),
+            RocksDBProviderInner::Secondary { db, .. } => {
+                // The secondary only changes when catching up, so all column families are copied
+                // from the same point in time.
+                self.try_catch_up_with_primary()?;
+                let target = RocksDBBuilder::new(dest).with_default_tables().build()?;
+                for cf_name in ROCKSDB_TABLES {
+                    let (Some(source_cf), Some(target_cf)) =
+                        (db.cf_handle(cf_name), target.0.db_rw().cf_handle(cf_name))
+                    else {
+                        continue
+                    };
+
+                    let mut batch = WriteBatchWithTransaction::<true>::default();
+                    for entry in db.iterator_cf(source_cf, IteratorMode::Start) {
+                        let (key, value) = entry.map_err(checkpoint_error)?;
+                        batch.put_cf(target_cf, key, value);
+                        if batch.size_in_bytes() >= DEFAULT_AUTO_COMMIT_THRESHOLD {
+                            target.commit_batch(std::mem::take(&mut batch))?;
+                        }
+                    }
+                    target.commit_batch(batch)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Creates a raw iterator over all entries in the specified table.
     ///
     /// Returns raw `(key_bytes, value_bytes)` pairs without decoding.
@@ -3192,6 +3237,30 @@ mod tests {
         assert_eq!(result, HistoryInfo::InChangeset(500));
     }
 
+    #[test]
+    fn test_create_checkpoint_from_secondary() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("checkpoint");
+        let shard_key = ShardedKey::new(Address::from([0x42; 20]), u64::MAX);
+        let chunk = IntegerList::new([100, 200, 300]).unwrap();
+
+        let rw_provider =
+            RocksDBBuilder::new(temp_dir.path().join("db")).with_default_tables().build().unwrap();
+        let ro_provider = RocksDBBuilder::new(temp_dir.path().join("db"))
+            .with_default_tables()
+            .with_read_only(true)
+            .build()
+            .unwrap();
+
+        // Written after the secondary was opened, so only copied after catching up.
+        rw_provider.put::<tables::AccountsHistory>(shard_key.clone(), &chunk).unwrap();
+        ro_provider.create_checkpoint(&dest).unwrap();
+        drop(ro_provider);
+
+        let checkpoint = RocksDBBuilder::new(&dest).with_default_tables().build().unwrap();
+        assert_eq!(checkpoint.get::<tables::AccountsHistory>(shard_key).unwrap(), Some(chunk));
+    }
+
     #[test]
     fn test_account_history_info_ignores_blocks_above_visible_tip() {
         let temp_dir = TempDir::new().unwrap();
@@ -13,9 +13,9 @@ use reth_db_api::{
 };
 use reth_primitives_traits::{Account, Bytecode, NodePrimitives};
 use reth_storage_api::{
-    BlockNumReader, BytecodeReader, DBProvider, NodePrimitivesProvider, PruneCheckpointReader,
-    StageCheckpointReader, StateProofProvider, StorageChangeSetReader, StorageRootProvider,
-    StorageSettingsCache,
+    BlockNumReader, BytecodeReader, DBProvider, ForkStateSource, NodePrimitivesProvider,
+    PruneCheckpointReader, StageCheckpointReader, StateProofProvider, StorageChangeSetReader,
+    StorageRootProvider, StorageSettingsCache,
 };
 use reth_storage_errors::provider::ProviderResult;
 use reth_storage_overlay::{Overlay, OverlayManager};
@@ -118,6 +118,10 @@ impl HistoryInfo {
 /// - [`tables::StoragesHistory`]
 /// - [`tables::AccountChangeSets`]
 /// - [`tables::StorageChangeSets`]
+///
+/// If the chain was forked from a remote chain, accounts and storage slots that were never written
+/// locally, as well as missing bytecodes and block hashes below the fork, are read from the
+/// [`ForkStateSource`].
 #[derive(Debug)]
 pub struct HistoricalStateProviderRef<
     'b,
@@ -134,6 +138,8 @@ pub struct HistoricalStateProviderRef<
     block_number: BlockNumber,
     /// Lowest blocks at which different parts of the state are available.
     lowest_available_blocks: LowestAvailableBlocks,
+    /// State of the remote chain this chain was forked from.
+    fork_state: Option<Arc<dyn ForkStateSource>>,
 }
 
 impl<'b, Provider, N> HistoricalStateProviderRef<'b, Provider, N>
@@ -156,6 +162,7 @@ where
             overlay_manager,
             block_number,
             lowest_available_blocks: Default::default(),
+            fork_state: None,
         }
     }
 
@@ -167,7 +174,13 @@ where
         lowest_available_blocks: LowestAvailableBlocks,
         overlay_manager: OverlayManager<N>,
     ) -> Self {
-        Self { provider, overlay_manager, block_number, lowest_available_blocks }
+        Self { provider, overlay_manager, block_number, lowest_available_blocks, fork_state: None }
+    }
+
+    /// Sets the state of the remote chain this chain was forked from.
+    pub fn with_fork_state(mut self, fork_state: Option<Arc<dyn ForkStateSource>>) -> Self {
+        self.fork_state = fork_state;
+        self
     }
 
     /// Lookup an account in the `AccountsHistory` table using `EitherReader`.
@@ -234,7 +247,10 @@ where
         Provider: StorageSettingsCache + RocksDBProviderFactory + NodePrimitivesProvider,
     {
         match self.storage_history_lookup(address, lookup_key)? {
-            HistoryInfo::NotYetWritten => Ok(None),
+            HistoryInfo::NotYetWritten => match &self.fork_state {
+                Some(fork_state) => fork_state.storage(address, lookup_key).map(Some),
+                None => Ok(None),
+            },
             HistoryInfo::InChangeset(changeset_block_number) => self
                 .provider
                 .get_storage_before_block(changeset_block_number, address, lookup_key)?
@@ -351,7 +367,10 @@ where
     /// Get basic account information.
     fn basic_account(&self, address: &Address) -> ProviderResult<Option<Account>> {
         match self.account_history_lookup(*address)? {
-            HistoryInfo::NotYetWritten => Ok(None),
+            HistoryInfo::NotYetWritten => match &self.fork_state {
+                Some(fork_state) => fork_state.basic_account(address),
+                None => Ok(None),
+            },
             HistoryInfo::InChangeset(changeset_block_number) => {
                 // Use ChangeSetReader trait method to get the account from changesets
                 self.provider
@@ -382,6 +401,11 @@ where
 {
     /// Get block hash by number.
     fn block_hash(&self, number: u64) -> ProviderResult<Option<B256>> {
+        if let Some(fork_state) = &self.fork_state &&
+            number < fork_state.fork_block()
+        {
+            return fork_state.block_hash(number)
+        }
         self.provider.block_hash(number)
     }
 
@@ -700,7 +724,11 @@ where
 {
     /// Get account code by its hash
     fn bytecode_by_hash(&self, code_hash: &B256) -> ProviderResult<Option<Bytecode>> {
-        self.tx().get_by_encoded_key::<tables::Bytecodes>(code_hash).map_err(Into::into)
+        let bytecode = self.tx().get_by_encoded_key::<tables::Bytecodes>(code_hash)?;
+        match (bytecode, &self.fork_state) {
+            (None, Some(fork_state)) => fork_state.bytecode_by_hash(code_hash),
+            (bytecode, _) => Ok(bytecode),
+        }
     }
 }
 
@@ -716,6 +744,8 @@ pub struct HistoricalStateProvider<Provider: NodePrimitivesProvider> {
     block_number: BlockNumber,
     /// Lowest blocks at which different parts of the state are available.
     lowest_available_blocks: LowestAvailableBlocks,
+    /// State of the remote chain this chain was forked from.
+    fork_state: Option<Arc<dyn ForkStateSource>>,
 }
 
 impl<
@@ -737,9 +767,16 @@ impl<
             overlay_manager,
             block_number,
             lowest_available_blocks: Default::default(),
+            fork_state: None,
         }
     }
 
+    /// Sets the state of the remote chain this chain was forked from.
+    pub fn with_fork_state(mut self, fork_state: Option<Arc<dyn ForkStateSource>>) -> Self {
+        self.fork_state = fork_state;
+        self
+    }
+
     /// Set the lowest block number at which the account history is available.
     pub const fn with_lowest_available_account_history_block_number(
         mut self,
@@ -776,6 +813,7 @@ impl<
             self.lowest_available_blocks,
             self.overlay_manager.clone(),
         )
+        .with_fork_state(self.fork_state.clone())
     }
 }
 
@@ -1562,6 +1600,110 @@ mod tests {
         assert!(hashed_state.storages.is_empty());
     }
 
+    #[test]
+    fn history_provider_falls_back_to_fork_state() {
+        use alloy_primitives::{BlockNumber, Bytes, StorageKey, StorageValue};
+        use reth_primitives_traits::Bytecode;
+        use reth_storage_api::{BytecodeReader, ForkStateSource};
+        use reth_storage_errors::provider::ProviderResult;
+        use std::sync::Arc;
+
+        const FORK_BLOCK: BlockNumber = 100;
+
+        #[derive(Debug)]
+        struct MockForkState;
+
+        impl ForkStateSource for MockForkState {
+            fn fork_block(&self) -> BlockNumber {
+                FORK_BLOCK
+            }
+
+            fn basic_account(&self, _address: &Address) -> ProviderResult<Option<Account>> {
+                Ok(Some(Account { nonce: 42, balance: U256::from(42), bytecode_hash: None }))
+            }
+
+            fn storage(
+                &self,
+                _address: Address,
+                _storage_key: StorageKey,
+            ) -> ProviderResult<StorageValue> {
+                Ok(U256::from(42))
+            }
+
+            fn bytecode_by_hash(&self, _code_hash: &B256) -> ProviderResult<Option<Bytecode>> {
+                Ok(Some(Bytecode::new_raw(Bytes::from_static(&[0x00]))))
+            }
+
+            fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+                Ok(Some(B256::with_last_byte(number as u8)))
+            }
+        }
+
+        let factory = create_test_provider_factory();
+        let tx = factory.provider_rw().unwrap().into_tx();
+
+        // `ADDRESS` is created locally at block 3, `HIGHER_ADDRESS` is deleted locally at block 4.
+        let acc_local = Account { nonce: 1, balance: U256::ZERO, bytecode_hash: None };
+        tx.put::<tables::AccountsHistory>(
+            ShardedKey { key: ADDRESS, highest_block_number: u64::MAX },
+            BlockNumberList::new([3]).unwrap(),
+        )
+        .unwrap();
+        tx.put::<tables::AccountsHistory>(
+            ShardedKey { key: HIGHER_ADDRESS, highest_block_number: u64::MAX },
+            BlockNumberList::new([4]).unwrap(),
+        )
+        .unwrap();
+        tx.put::<tables::AccountChangeSets>(3, AccountBeforeTx { address: ADDRESS, info: None })
+            .unwrap();
+        tx.put::<tables::AccountChangeSets>(
+            4,
+            AccountBeforeTx { address: HIGHER_ADDRESS, info: Some(acc_local) },
+        )
+        .unwrap();
+        tx.put::<tables::PlainAccountState>(ADDRESS, acc_local).unwrap();
+
+        // Slot `STORAGE` of `ADDRESS` is cleared locally at block 3.
+        tx.put::<tables::StoragesHistory>(
+            StorageShardedKey {
+                address: ADDRESS,
+                sharded_key: ShardedKey { key: STORAGE, highest_block_number: u64::MAX },
+            },
+            BlockNumberList::new([3]).unwrap(),
+        )
+        .unwrap();
+        tx.put::<tables::StorageChangeSets>(
+            (3, ADDRESS).into(),
+            StorageEntry { key: STORAGE, value: U256::from(1) },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let db = factory.provider().unwrap();
+        let provider = HistoricalStateProviderRef::new(&db, 5, OverlayManager::default())
+            .with_fork_state(Some(Arc::new(MockForkState)));
+
+        // Written locally
+        assert_eq!(provider.basic_account(&ADDRESS).unwrap(), Some(acc_local));
+        assert_eq!(provider.basic_account(&HIGHER_ADDRESS).unwrap(), None);
+        assert_eq!(provider.storage(ADDRESS, STORAGE).unwrap(), Some(U256::ZERO));
+
+        // Never written locally
+        let fork_address = address!("0x0000000000000000000000000000000000000003");
+        assert_eq!(
+            provider.basic_account(&fork_address).unwrap(),
+            Some(Account { nonce: 42, balance: U256::from(42), bytecode_hash: None })
+        );
+        assert_eq!(provider.storage(ADDRESS, B256::ZERO).unwrap(), Some(U256::from(42)));
+        assert!(provider.bytecode_by_hash(&B256::ZERO).unwrap().is_some());
+        assert_eq!(provider.block_hash(FORK_BLOCK - 1).unwrap(), Some(B256::with_last_byte(99)));
+
+        // Without fork state, keys that were never written are missing
+        let provider = HistoricalStateProviderRef::new(&db, 5, OverlayManager::default());
+        assert_eq!(provider.basic_account(&fork_address).unwrap(), None);
+        assert_eq!(provider.storage(ADDRESS, B256::ZERO).unwrap(), None);
+    }
+
     #[test]
     fn test_needs_prev_shard_check() {
         // Only needs check when rank == 0 and found_block != block_number
@@ -33,7 +33,7 @@ use reth_db_api::{
     transaction::DbTx,
 };
 use reth_ethereum_primitives::{Receipt, TransactionSigned};
-use reth_nippy_jar::{NippyJar, NippyJarChecker};
+use reth_nippy_jar::{NippyJar, NippyJarChecker, CONFIG_FILE_EXTENSION};
 use reth_node_types::NodePrimitives;
 use reth_primitives_traits::{
     dashmap::DashMap, AlloyBlockHeader as _, BlockBody as _, RecoveredBlock, SealedHeader,
@@ -53,6 +53,7 @@ use reth_storage_errors::provider::{ProviderError, ProviderResult, StaticFileWri
 use std::{
     collections::BTreeMap,
     fmt::Debug,
+    io::Read,
     ops::{Bound, Deref, Range, RangeBounds, RangeInclusive},
     path::{Path, PathBuf},
     sync::{atomic::AtomicU64, mpsc, Arc},
@@ -291,6 +292,9 @@ pub struct StaticFileProviderInner<N> {
     path: PathBuf,
     /// Maintains a writer set of [`StaticFileSegment`].
     writers: StaticFileWriters<N>,
+    /// Held for reading by [`StaticFileProvider::copy_to`] and for writing while deleting jars, so
+    /// that jars aren't deleted while they're copied.
+    jar_deletions: RwLock<()>,
     /// Metrics for the static files.
     metrics: Option<Arc<StaticFileProviderMetrics>>,
     /// Access rights of the provider.
@@ -307,7 +311,12 @@ impl<N: NodePrimitives> StaticFileProviderInner<N> {
     /// Creates a new [`StaticFileProviderInner`].
     fn new(path: impl AsRef<Path>, access: StaticFileAccess) -> ProviderResult<Self> {
         let _lock_file = if access.is_read_write() {
-            StorageLock::try_acquire(path.as_ref()).map_err(ProviderError::other)?.into()
+            let lock = StorageLock::try_acquire(path.as_ref()).map_err(ProviderError::other)?;
+            // Only the writer may roll forward interrupted rewrites, reads fail until then.
+            if path.as_ref().exists() {
+                NippyJar::complete_rewrites(path.as_ref()).map_err(ProviderError::other)?;
+            }
+            Some(lock)
         } else {
             None
         };
@@ -321,6 +330,7 @@ impl<N: NodePrimitives> StaticFileProviderInner<N> {
             map: Default::default(),
             indexes: Default::default(),
             writers: Default::default(),
+            jar_deletions: Default::default(),
             earliest_history_height: Default::default(),
             path: path.as_ref().to_path_buf(),
             metrics: None,
@@ -891,6 +901,8 @@ impl<N: NodePrimitives> StaticFileProvider<N> {
         segment: StaticFileSegment,
         block: BlockNumber,
     ) -> ProviderResult<SegmentHeader> {
+        let _copies = self.jar_deletions.write();
+
         let fixed_block_range = self.find_fixed_range(segment, block);
         let key = (fixed_block_range.end(), segment);
         let file = self.path.join(segment.filename(&fixed_block_range));
@@ -956,6 +968,43 @@ impl<N: NodePrimitives> StaticFileProvider<N> {
         Ok(deleted_headers)
     }
 
+    /// Returns a writer for the static file of a segment containing `block`, whose block range
+    /// is below the highest static file of the segment, e.g. because it was deleted by pruning.
+    ///
+    /// The writer is not cached like the ones returned by [`StaticFileWriter::get_writer`]. It
+    /// creates the static file, or resumes appending to it if it was partially rewritten before.
+    ///
+    /// CAUTION: the index only expects the highest static file of a segment to be written to. It
+    /// must be rebuilt with [`Self::initialize_index`] once the writer is committed.
+    pub fn pruned_range_writer(
+        &self,
+        segment: StaticFileSegment,
+        block: BlockNumber,
+    ) -> ProviderResult<StaticFileProviderRW<N>> {
+        if self.access.is_read_only() {
+            return Err(ProviderError::ReadOnlyStaticFileAccess);
+        }
+
+        let block_range = self.find_fixed_range(segment, block);
+        let highest_block = self.get_highest_static_file_block(segment);
+        if highest_block.is_none_or(|highest| highest <= block_range.end()) {
+            return Err(StaticFileWriterError::new(format!(
+                "{segment} static file of block range {block_range} is not below the highest one, \
+                 use the latest writer instead"
+            ))
+            .into())
+        }
+
+        let writer = StaticFileProviderRW::new(
+            segment,
+            block,
+            Arc::downgrade(&self.0),
+            self.metrics.clone(),
+        );
+        self.initialize_index()?;
+        writer
+    }
+
     /// Given a segment and block range it returns a cached
     /// [`StaticFileJarProvider`]. TODO(joshie): we should check the size and pop N if there's too
     /// many.
@@ -2138,6 +2187,71 @@ impl<N: NodePrimitives> StaticFileProvider<N> {
         &self.path
     }
 
+    /// Copies all static files into the `dest` directory.
+    ///
+    /// The latest jar of every segment is captured while holding the segment writer lock, which
+    /// only covers reading its configuration and the sizes of its files. Its files are copied after
+    /// releasing the lock, up to the captured sizes, so rows appended while the copy runs are left
+    /// out. If the jar is truncated while copying, the rows missing from the copy are discarded by
+    /// the consistency check once the copy is opened.
+    ///
+    /// Jars can't be pruned through this provider until the copy completes. Jars deleted while
+    /// copying otherwise, i.e. the latest jar by an unwind or any jar by another process, are left
+    /// out, as if they were deleted before.
+    pub fn copy_to(&self, dest: &Path) -> ProviderResult<()> {
+        reth_fs_util::create_dir_all(dest).map_err(ProviderError::other)?;
+        let _pin = self.jar_deletions.read();
+
+        let mut jars = BTreeMap::<_, Vec<PathBuf>>::new();
+        for entry in reth_fs_util::read_dir(&self.path).map_err(ProviderError::other)? {
+            let entry = entry.map_err(ProviderError::other)?;
+            if !entry.file_type().map_err(ProviderError::other)?.is_file() {
+                continue
+            }
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let stem = file_name.split_once('.').map_or(file_name.as_str(), |(stem, _)| stem);
+
+            // Files that don't belong to a jar (e.g. the storage lock) are not copied.
+            if let Some((segment, range)) = StaticFileSegment::parse_filename(stem) {
+                jars.entry((segment, range.start())).or_default().push(entry.path());
+            }
+        }
+
+        let copy_jar = |files: &[PathBuf], snapshot: Option<JarSnapshot>| -> ProviderResult<()> {
+            let copied = match snapshot {
+                Some(snapshot) => snapshot.copy_to(dest).map_err(ProviderError::other)?,
+                None => false,
+            };
+            if !copied {
+                debug!(
+                    target: "providers::static_file",
+                    jar = ?files.first(),
+                    "Static file deleted while copying, skipping"
+                );
+            }
+            Ok(())
+        };
+
+        for segment in StaticFileSegment::iter() {
+            let Some((&latest, latest_files)) =
+                jars.range((segment, 0)..=(segment, u64::MAX)).next_back()
+            else {
+                continue
+            };
+            let latest_snapshot = {
+                let _lock = self.writers.lock(segment);
+                JarSnapshot::capture(latest_files).map_err(ProviderError::other)?
+            };
+
+            for (_, files) in jars.range((segment, 0)..latest) {
+                copy_jar(files, JarSnapshot::capture(files).map_err(ProviderError::other)?)?;
+            }
+            copy_jar(latest_files, latest_snapshot)?;
+        }
+
+        Ok(())
+    }
+
     /// Retrieves data from the database or static file, wherever it's available.
     ///
     /// # Arguments
@@ -3059,14 +3173,75 @@ where
     Ok((*tx.tx_hash(), tx_id))
 }
 
+/// The files of a static file jar, captured for [`StaticFileProvider::copy_to`].
+#[derive(Debug)]
+struct JarSnapshot {
+    /// Path and contents of the configuration file, which defines the committed rows of the jar.
+    config: (PathBuf, Vec<u8>),
+    /// Paths and sizes of all other files of the jar.
+    files: Vec<(PathBuf, u64)>,
+}
+
+impl JarSnapshot {
+    /// Captures the configuration and file sizes of a jar.
+    ///
+    /// Returns `None` if the jar has no configuration, because it was deleted or isn't fully
+    /// created yet.
+    fn capture(paths: &[PathBuf]) -> std::io::Result<Option<Self>> {
+        let mut config = None;
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let result = if path.extension().is_some_and(|ext| ext == CONFIG_FILE_EXTENSION) {
+                std::fs::read(path).map(|contents| config = Some((path.clone(), contents)))
+            } else {
+                std::fs::metadata(path).map(|metadata| files.push((path.clone(), metadata.len())))
+            };
+            match result {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(config.map(|config| Self { config, files }))
+    }
+
+    /// Copies the captured files into `dest`, with the configuration last.
+    ///
+    /// Returns `false` and removes the partial copy if the jar was deleted while copying.
+    fn copy_to(&self, dest: &Path) -> std::io::Result<bool> {
+        let target = |path: &Path| dest.join(path.file_name().expect("listed from a directory"));
+        let result = self.files.iter().try_for_each(|(path, len)| {
+            let mut source = std::fs::File::open(path)?.take(*len);
+            std::io::copy(&mut source, &mut std::fs::File::create(target(path))?).map(drop)
+        });
+        match result {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                for (path, _) in &self.files {
+                    let _ = std::fs::remove_file(target(path));
+                }
+                return Ok(false)
+            }
+            Err(err) => return Err(err),
+        }
+
+        let (path, contents) = &self.config;
+        std::fs::write(target(path), contents)?;
+        Ok(true)
+    }
+}
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
 
+    use alloy_consensus::Header;
+    use alloy_primitives::B256;
     use reth_chain_state::EthPrimitives;
     use reth_db::test_utils::create_test_static_files_dir;
-    use reth_static_file_types::{SegmentRangeInclusive, StaticFileSegment};
+    use reth_nippy_jar::{NippyJar, NippyJarChecker};
+    use reth_static_file_types::{SegmentHeader, SegmentRangeInclusive, StaticFileSegment};
 
+    use super::JarSnapshot;
     use crate::{providers::StaticFileProvider, StaticFileProviderBuilder};
 
     #[test]
@@ -3188,4 +3363,46 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_copy_jar_snapshot() -> eyre::Result<()> {
+        let (static_dir, _) = create_test_static_files_dir();
+        let sf_rw: StaticFileProvider<EthPrimitives> =
+            StaticFileProviderBuilder::read_write(&static_dir).build()?;
+        let segment = StaticFileSegment::Headers;
+        let append = |numbers: std::ops::Range<u64>| -> eyre::Result<()> {
+            let mut writer = sf_rw.latest_writer(segment)?;
+            for number in numbers {
+                writer.append_header(&Header { number, ..Default::default() }, &B256::ZERO)?;
+            }
+            Ok(writer.commit()?)
+        };
+        append(0..2)?;
+
+        let file_name = segment.filename(&sf_rw.find_fixed_range(segment, 0));
+        let files = reth_fs_util::read_dir(sf_rw.directory())?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.file_stem().is_some_and(|stem| stem == file_name.as_str()))
+            .collect::<Vec<_>>();
+        let snapshot = JarSnapshot::capture(&files)?.expect("jar exists");
+
+        // Rows committed after the capture are left out of the copy
+        append(2..4)?;
+        let dest = tempfile::tempdir()?;
+        assert!(snapshot.copy_to(dest.path())?);
+        let copy = NippyJar::<SegmentHeader>::load(&dest.path().join(&file_name))?;
+        assert_eq!(copy.rows(), 2);
+        NippyJarChecker::new(copy).check_consistency()?;
+
+        // A jar deleted while copying is left out
+        let dest = tempfile::tempdir()?;
+        let snapshot = JarSnapshot::capture(&files)?.expect("jar exists");
+        sf_rw.delete_jar(segment, 0)?;
+        assert!(!snapshot.copy_to(dest.path())?);
+        assert!(reth_fs_util::read_dir(dest.path())?.next().is_none());
+        assert!(JarSnapshot::capture(&files)?.is_none());
+
+        Ok(())
+    }
 }
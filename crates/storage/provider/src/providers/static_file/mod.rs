@@ -34,7 +34,8 @@ pub struct LoadedJar {
 
 impl LoadedJar {
     fn new(jar: NippyJar<SegmentHeader>) -> ProviderResult<Self> {
-        match jar.open_data_reader() {
+        // Cursors created from the mapped checksums verify every row they read.
+        match jar.open_verifying_data_reader() {
             Ok(data_reader) => {
                 let mmap_handle = Arc::new(data_reader);
 
@@ -189,7 +190,7 @@ mod tests {
         let (static_dir, _) = create_test_static_files_dir();
 
         let blocks_per_file = 10; // Number of headers per file
-        let files_per_range = 3; // Number of files per range (data/conf/offset files)
+        let files_per_range = 4; // Number of files per range (data/conf/offset/checksum files)
         let file_set_count = 3; // Number of sets of files to create
         let initial_file_count = files_per_range * file_set_count;
         let tip = blocks_per_file * file_set_count - 1; // Initial highest block (29 in this case)
@@ -450,7 +451,7 @@ mod tests {
     fn test_tx_based_truncation() {
         let segments = [StaticFileSegment::Transactions, StaticFileSegment::Receipts];
         let blocks_per_file = 10; // Number of blocks per file
-        let files_per_range = 3; // Number of files per range (data/conf/offset files)
+        let files_per_range = 4; // Number of files per range (data/conf/offset/checksum files)
         let file_set_count = 3; // Number of sets of files to create
         let initial_file_count = files_per_range * file_set_count;
 
@@ -879,8 +880,9 @@ mod tests {
         let (static_dir, _) = create_test_static_files_dir();
 
         let blocks_per_file = 10;
-        // 3 main files (jar, dat, idx) + 1 csoff sidecar file for changeset segments
-        let files_per_range = 4;
+        // 3 main files (jar, dat, idx) + 1 checksum sidecar file + 1 csoff sidecar file for
+        // changeset segments
+        let files_per_range = 5;
         let file_set_count = 3;
         let initial_file_count = files_per_range * file_set_count;
         let tip = blocks_per_file * file_set_count - 1;
@@ -1216,8 +1218,9 @@ mod tests {
         let (static_dir, _) = create_test_static_files_dir();
 
         let blocks_per_file = 10;
-        // 3 main files (jar, dat, idx) + 1 csoff sidecar file for changeset segments
-        let files_per_range = 4;
+        // 3 main files (jar, dat, idx) + 1 checksum sidecar file + 1 csoff sidecar file for
+        // changeset segments
+        let files_per_range = 5;
         let file_set_count = 3;
         let initial_file_count = files_per_range * file_set_count;
         let tip = blocks_per_file * file_set_count - 1;
@@ -1417,4 +1420,54 @@ mod tests {
         let entry = result.unwrap();
         assert_eq!(entry.value, U256::from(42));
     }
+
+    #[test]
+    fn test_pruned_range_writer() {
+        let (static_dir, _) = create_test_static_files_dir();
+        let segment = StaticFileSegment::Receipts;
+
+        let sf_rw: StaticFileProvider<EthPrimitives> =
+            StaticFileProviderBuilder::read_write(&static_dir)
+                .with_blocks_per_file(10)
+                .build()
+                .expect("Failed to build static file provider");
+
+        // One receipt per block, identified by its cumulative gas used.
+        let append_blocks = |writer: &mut StaticFileProviderRW<EthPrimitives>,
+                             blocks: Range<u64>| {
+            for block in blocks {
+                writer.increment_block(block).unwrap();
+                let receipt = Receipt { cumulative_gas_used: block, ..Default::default() };
+                writer.append_receipt(block, &receipt).unwrap();
+            }
+            writer.commit().unwrap();
+        };
+        append_blocks(&mut *sf_rw.latest_writer(segment).unwrap(), 0..30);
+
+        sf_rw.delete_segment_below_block(segment, 20).unwrap();
+        assert_eq!(sf_rw.get_lowest_range_start(segment), Some(20));
+        assert!(sf_rw.receipt(15).unwrap().is_none());
+
+        // The highest static file must be written with the latest writer.
+        assert!(sf_rw.pruned_range_writer(segment, 25).is_err());
+
+        // Partially restore a deleted static file, and resume it.
+        append_blocks(&mut sf_rw.pruned_range_writer(segment, 10).unwrap(), 10..15);
+        sf_rw.initialize_index().unwrap();
+        let mut writer = sf_rw.pruned_range_writer(segment, 10).unwrap();
+        assert_eq!(writer.next_block_number(), 15);
+        append_blocks(&mut writer, 15..20);
+        drop(writer);
+        sf_rw.initialize_index().unwrap();
+
+        assert_eq!(sf_rw.get_lowest_range_start(segment), Some(10));
+        assert_eq!(sf_rw.get_highest_static_file_block(segment), Some(29));
+        for tx in [10, 15, 19, 25] {
+            assert_eq!(sf_rw.receipt(tx).unwrap().unwrap().cumulative_gas_used, tx);
+        }
+
+        // New blocks are still appended to the highest static file.
+        append_blocks(&mut *sf_rw.latest_writer(segment).unwrap(), 30..31);
+        assert_eq!(sf_rw.receipt(30).unwrap().unwrap().cumulative_gas_used, 30);
+    }
 }
@@ -99,14 +99,7 @@ impl<N: NodePrimitives> StaticFileWriters<N> {
         segment: StaticFileSegment,
         create_fn: impl FnOnce() -> ProviderResult<StaticFileProviderRW<N>>,
     ) -> ProviderResult<StaticFileProviderRWRefMut<'_, N>> {
-        let mut write_guard = match segment {
-            StaticFileSegment::Headers => self.headers.write(),
-            StaticFileSegment::Transactions => self.transactions.write(),
-            StaticFileSegment::Receipts => self.receipts.write(),
-            StaticFileSegment::TransactionSenders => self.transaction_senders.write(),
-            StaticFileSegment::AccountChangeSets => self.account_change_sets.write(),
-            StaticFileSegment::StorageChangeSets => self.storage_change_sets.write(),
-        };
+        let mut write_guard = self.lock(segment);
 
         if write_guard.is_none() {
             *write_guard = Some(create_fn()?);
@@ -117,16 +110,24 @@ impl<N: NodePrimitives> StaticFileWriters<N> {
 
     /// Drops the cached writer for a segment before destructive segment-level operations.
     pub(crate) fn remove(&self, segment: StaticFileSegment) {
-        let mut write_guard = match segment {
+        *self.lock(segment) = None;
+    }
+
+    /// Locks the writer slot of a segment without creating a writer.
+    ///
+    /// No writes to the segment can happen until the returned guard is dropped.
+    pub(crate) fn lock(
+        &self,
+        segment: StaticFileSegment,
+    ) -> RwLockWriteGuard<'_, RawRwLock, Option<StaticFileProviderRW<N>>> {
+        match segment {
             StaticFileSegment::Headers => self.headers.write(),
             StaticFileSegment::Transactions => self.transactions.write(),
             StaticFileSegment::Receipts => self.receipts.write(),
             StaticFileSegment::TransactionSenders => self.transaction_senders.write(),
             StaticFileSegment::AccountChangeSets => self.account_change_sets.write(),
             StaticFileSegment::StorageChangeSets => self.storage_change_sets.write(),
-        };
-
-        *write_guard = None;
+        }
     }
 
     #[instrument(
@@ -315,7 +316,18 @@ impl<N: NodePrimitives> StaticFileProviderRW<N> {
             ),
             Err(ProviderError::MissingStaticFileBlock(_, _)) => {
                 let path = static_file_provider.directory().join(segment.filename(&block_range));
-                (create_jar(segment, &path, block_range), path)
+                let mut jar = create_jar(segment, &path, block_range);
+
+                // Carry over the compressor of the previous jar, so that a segment recompressed
+                // with `reth db static-file recompress` keeps its codec and dictionaries.
+                if let Some(previous) = block_range.start().checked_sub(1) &&
+                    let Ok(provider) = static_file_provider
+                        .get_segment_provider_for_block(segment, previous, None)
+                {
+                    jar = jar.with_compressor(provider.compressor().cloned());
+                }
+
+                (jar, path)
             }
             Err(err) => return Err(err),
         };
@@ -1728,11 +1740,13 @@ fn create_jar(
     path: &Path,
     expected_block_range: SegmentRangeInclusive,
 ) -> NippyJar<SegmentHeader> {
+    // Checksums detect corrupted rows, see `reth db static-file verify`.
     let mut jar = NippyJar::new(
         segment.columns(),
         path,
         SegmentHeader::new(expected_block_range, None, None, segment),
-    );
+    )
+    .with_checksums();
 
     // Transaction and Receipt already have the compression scheme used natively in its encoding.
     // (zstd-dictionary)
@@ -1,11 +1,11 @@
 use crate::{
     traits::{BlockSource, ReceiptProvider},
-    AccountReader, BalProvider, BalStoreHandle, BlockHashReader, BlockIdReader, BlockNumReader,
-    BlockReader, BlockReaderIdExt, ChainSpecProvider, ChangeSetReader, HeaderProvider,
-    PruneCheckpointReader, RangeEnd, RangeResponse, RangeResult, ReceiptProviderIdExt,
-    StateProvider, StateProviderBox, StateProviderFactory, StateRangeProvider,
-    StateRangeProviderFactory, StateRangeView, StateReader, StateRootProvider, StorageRangeResult,
-    TransactionVariant, TransactionsProvider,
+    AccountProofNodes, AccountReader, BalProvider, BalStoreHandle, BlockHashReader, BlockIdReader,
+    BlockNumReader, BlockReader, BlockReaderIdExt, ChainSpecProvider, ChangeSetReader,
+    HeaderProvider, PruneCheckpointReader, RangeEnd, RangeResponse, RangeResult,
+    ReceiptProviderIdExt, StateProvider, StateProviderBox, StateProviderFactory,
+    StateRangeProvider, StateRangeProviderFactory, StateRangeView, StateReader, StateRootProvider,
+    StorageRangeResult, TransactionVariant, TransactionsProvider,
 };
 use alloy_consensus::{
     constants::EMPTY_ROOT_HASH,
@@ -428,6 +428,22 @@ impl<T: NodePrimitives, ChainSpec> StateRangeProvider for MockEthProvider<T, Cha
         self.ensure_snap_state_reads_succeed()?;
         self.snap_storage_proof.lock().clone().ok_or(ProviderError::BestBlockNotFound)
     }
+
+    fn account_proof(
+        &self,
+        _hashed_address: B256,
+        _slots: &[B256],
+    ) -> ProviderResult<AccountProofNodes> {
+        self.ensure_snap_state_reads_succeed()?;
+        Ok(AccountProofNodes {
+            account_proof: self
+                .snap_account_proof
+                .lock()
+                .clone()
+                .ok_or(ProviderError::BestBlockNotFound)?,
+            storage_proof: self.snap_storage_proof.lock().clone().unwrap_or_default(),
+        })
+    }
 }
 
 /// An extended account for local store
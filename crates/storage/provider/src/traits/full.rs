@@ -9,7 +9,9 @@ use reth_chain_state::{
     CanonStateSubscriptions, ForkChoiceSubscriptions, PersistedBlockSubscriptions,
 };
 use reth_node_types::{BlockTy, HeaderTy, NodeTypesWithDB, ReceiptTy, TxTy};
-use reth_storage_api::{NodePrimitivesProvider, StorageChangeSetReader, StorageSettingsCache};
+use reth_storage_api::{
+    NodePrimitivesProvider, StorageBackupProvider, StorageChangeSetReader, StorageSettingsCache,
+};
 use std::fmt::Debug;
 
 /// Helper trait to unify all provider traits for simplicity.
@@ -42,6 +44,7 @@ pub trait FullProvider<N: NodeTypesWithDB>:
     + PersistedBlockSubscriptions
     + StageCheckpointReader
     + PruneCheckpointReader
+    + StorageBackupProvider
     + Clone
     + Debug
     + Unpin
@@ -78,6 +81,7 @@ impl<T, N: NodeTypesWithDB> FullProvider<N> for T where
         + PersistedBlockSubscriptions
         + StageCheckpointReader
         + PruneCheckpointReader
+        + StorageBackupProvider
         + Clone
         + Debug
         + Unpin
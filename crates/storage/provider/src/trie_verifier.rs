@@ -0,0 +1,314 @@
+//! Incremental verification of the trie tables against the hashed state while the node runs.
+
+use crate::{DBProvider, DatabaseProviderFactory};
+use reth_db_api::transaction::DbTx;
+use reth_metrics::{
+    metrics::{Counter, Gauge, Histogram},
+    Metrics,
+};
+use reth_storage_api::StorageSettingsCache;
+use reth_storage_errors::provider::ProviderResult;
+use reth_trie::{
+    verify::{Output, SubtrieCursorFactory, Verifier},
+    Nibbles,
+};
+use reth_trie_db::{DatabaseHashedCursorFactory, DatabaseTrieCursorFactory, TrieTableAdapter};
+use std::{
+    fmt,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, info, warn};
+
+/// Maximum depth of the account subtries the trie is split into.
+pub const MAX_TRIE_VERIFIER_DEPTH: usize = 6;
+
+/// Verifies the account and storage trie tables against the hashed state tables, one account
+/// subtrie at a time.
+///
+/// The account trie is split into the `16^depth` subtries under the account paths of length
+/// `depth`. Each call to [`TrieVerifier::verify_next_chunk`] verifies the next subtrie together
+/// with the storage tries of its accounts on a fresh read transaction, so that the database is
+/// only held open for a short time and the node keeps committing blocks in between. Once all
+/// subtries have been verified, the next pass starts over from the first one.
+///
+/// Account trie nodes above the subtries are not verified. These are rewritten by almost every
+/// block, so corruption of them surfaces as a state root mismatch right away.
+///
+/// Inconsistencies are logged as errors and counted in the `trie_verifier` metrics. The trie
+/// tables are never modified, `reth db repair-trie` repairs them with the node stopped.
+#[derive(Debug)]
+pub struct TrieVerifier<F> {
+    factory: F,
+    depth: usize,
+    next_chunk: u64,
+    progress_file: Option<PathBuf>,
+    pass_started_at: Instant,
+    metrics: TrieVerifierMetrics,
+}
+
+impl<F> TrieVerifier<F> {
+    /// Creates a new verifier splitting the account trie into subtries of the given depth.
+    ///
+    /// Returns an error if `depth` is zero or greater than [`MAX_TRIE_VERIFIER_DEPTH`].
+    pub fn new(factory: F, depth: usize) -> Result<Self, InvalidTrieVerifierDepth> {
+        if !(1..=MAX_TRIE_VERIFIER_DEPTH).contains(&depth) {
+            return Err(InvalidTrieVerifierDepth(depth))
+        }
+        Ok(Self {
+            factory,
+            depth,
+            next_chunk: 0,
+            progress_file: None,
+            pass_started_at: Instant::now(),
+            metrics: Default::default(),
+        })
+    }
+
+    /// Persists the progress to the given file after each chunk and resumes from the progress
+    /// already stored in it.
+    ///
+    /// Progress stored with a different depth, or which can't be read, is discarded.
+    pub fn with_progress_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.next_chunk = match reth_fs_util::read_to_string(&path) {
+            Ok(progress) => self.parse_progress(progress.trim()).unwrap_or_else(|| {
+                warn!(target: "providers::trie_verifier", ?path, %progress, "Discarding progress");
+                0
+            }),
+            Err(_) if !path.exists() => 0,
+            Err(err) => {
+                warn!(target: "providers::trie_verifier", %err, "Failed to read progress");
+                0
+            }
+        };
+        self.progress_file = Some(path);
+        self
+    }
+
+    /// Returns the number of subtries a pass is split into.
+    pub const fn chunk_count(&self) -> u64 {
+        1 << (4 * self.depth)
+    }
+
+    /// Returns the path prefix of the subtrie that is verified next.
+    pub fn next_prefix(&self) -> Nibbles {
+        Nibbles::from_nibbles(
+            (0..self.depth)
+                .rev()
+                .map(|shift| ((self.next_chunk >> (4 * shift)) & 0xf) as u8)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Parses progress persisted as the hex encoded prefix of the next subtrie.
+    fn parse_progress(&self, progress: &str) -> Option<u64> {
+        if progress.len() != self.depth {
+            return None
+        }
+        u64::from_str_radix(progress, 16).ok()
+    }
+
+    /// Persists the progress as the hex encoded prefix of the next subtrie.
+    fn persist_progress(&self) {
+        let Some(path) = &self.progress_file else { return };
+        let progress = format!("{:0width$x}", self.next_chunk, width = self.depth);
+        if let Err(err) = reth_fs_util::write(path, progress) {
+            warn!(target: "providers::trie_verifier", %err, "Failed to persist progress");
+        }
+    }
+}
+
+impl<F> TrieVerifier<F>
+where
+    F: DatabaseProviderFactory<Provider: StorageSettingsCache>,
+{
+    /// Verifies the next subtrie and advances the progress, returning the found inconsistencies.
+    ///
+    /// The progress isn't advanced if verification fails, so that the subtrie is retried.
+    pub fn verify_next_chunk(&mut self) -> ProviderResult<TrieVerifierChunk> {
+        let prefix = self.next_prefix();
+        let started_at = Instant::now();
+
+        let provider = self.factory.database_provider_ro()?;
+        let inconsistencies = reth_trie_db::with_adapter!(provider, |A| {
+            verify_subtrie::<_, A>(provider.tx_ref(), prefix)?
+        });
+        drop(provider);
+
+        let elapsed = started_at.elapsed();
+        self.metrics.chunks_verified.increment(1);
+        self.metrics.chunk_duration.record(elapsed);
+        for inconsistency in &inconsistencies {
+            match inconsistency {
+                Output::AccountExtra(..) |
+                Output::AccountWrong { .. } |
+                Output::AccountMissing(..) => self.metrics.account_inconsistencies.increment(1),
+                Output::StorageExtra(..) |
+                Output::StorageWrong { .. } |
+                Output::StorageMissing(..) => self.metrics.storage_inconsistencies.increment(1),
+                Output::Progress(_) => {}
+            }
+            error!(target: "providers::trie_verifier", ?inconsistency, "Trie inconsistency found");
+        }
+        debug!(
+            target: "providers::trie_verifier",
+            ?prefix,
+            ?elapsed,
+            inconsistencies = inconsistencies.len(),
+            "Verified subtrie"
+        );
+
+        self.next_chunk = (self.next_chunk + 1) % self.chunk_count();
+        let pass_completed = self.next_chunk == 0;
+        if pass_completed {
+            self.metrics.passes_completed.increment(1);
+            info!(
+                target: "providers::trie_verifier",
+                elapsed = ?self.pass_started_at.elapsed(),
+                "Completed trie verification pass"
+            );
+            self.pass_started_at = Instant::now();
+        }
+        self.metrics.pass_progress.set(self.next_chunk as f64 / self.chunk_count() as f64);
+        self.persist_progress();
+
+        Ok(TrieVerifierChunk { prefix, inconsistencies, elapsed, pass_completed })
+    }
+}
+
+/// Error returned by [`TrieVerifier::new`] for a depth outside of `1..=MAX_TRIE_VERIFIER_DEPTH`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTrieVerifierDepth(pub usize);
+
+impl fmt::Display for InvalidTrieVerifierDepth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "trie verifier depth must be between 1 and {MAX_TRIE_VERIFIER_DEPTH}, got {}",
+            self.0
+        )
+    }
+}
+
+impl core::error::Error for InvalidTrieVerifierDepth {}
+
+/// Outcome of verifying a single subtrie with [`TrieVerifier::verify_next_chunk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieVerifierChunk {
+    /// Path prefix of the verified subtrie.
+    pub prefix: Nibbles,
+    /// Inconsistencies of the trie tables found in the subtrie.
+    pub inconsistencies: Vec<Output>,
+    /// Time it took to verify the subtrie.
+    pub elapsed: Duration,
+    /// Whether this was the last subtrie of a pass.
+    pub pass_completed: bool,
+}
+
+/// Verifies the account subtrie under `prefix` and the storage tries of its accounts.
+fn verify_subtrie<TX: DbTx, A: TrieTableAdapter>(
+    tx: &TX,
+    prefix: Nibbles,
+) -> ProviderResult<Vec<Output>> {
+    let hashed_cursor_factory =
+        SubtrieCursorFactory::new(DatabaseHashedCursorFactory::new(tx), prefix);
+    let trie_cursor_factory =
+        SubtrieCursorFactory::new(DatabaseTrieCursorFactory::<_, A>::new(tx), prefix);
+
+    let mut inconsistencies = Vec::new();
+    for output in Verifier::new(&trie_cursor_factory, hashed_cursor_factory)? {
+        let output = output?;
+        if !matches!(output, Output::Progress(_)) {
+            inconsistencies.push(output);
+        }
+    }
+    Ok(inconsistencies)
+}
+
+/// Metrics of the [`TrieVerifier`].
+#[derive(Metrics)]
+#[metrics(scope = "trie_verifier")]
+struct TrieVerifierMetrics {
+    /// Number of verified subtries
+    chunks_verified: Counter,
+    /// Number of completed passes over the whole trie
+    passes_completed: Counter,
+    /// Number of inconsistent account trie nodes found
+    account_inconsistencies: Counter,
+    /// Number of inconsistent storage trie nodes found
+    storage_inconsistencies: Counter,
+    /// Time it took to verify a subtrie
+    chunk_duration: Histogram,
+    /// Fraction of the current pass that has been verified
+    pass_progress: Gauge,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::create_test_provider_factory;
+    use alloy_primitives::B256;
+    use reth_db_api::transaction::DbTxMut;
+    use reth_trie::{BranchNodeCompact, TrieMask};
+    use reth_trie_db::TrieKeyAdapter;
+
+    #[test]
+    fn verifies_subtries_and_resumes() {
+        let factory = create_test_provider_factory();
+        let dir = tempfile::tempdir().unwrap();
+        let progress_file = dir.path().join("trie-verifier-progress");
+
+        // An account trie node without any hashed accounts below it is extraneous.
+        let path = Nibbles::from_nibbles([0x1, 0x2, 0x3]);
+        let node = BranchNodeCompact::new(
+            TrieMask::new(0b11),
+            TrieMask::new(0),
+            TrieMask::new(0b11),
+            vec![B256::ZERO, B256::ZERO],
+            None,
+        );
+        let provider_rw = factory.database_provider_rw().unwrap();
+        reth_trie_db::with_adapter!(provider_rw, |A| {
+            let key: <A as TrieKeyAdapter>::AccountKey = path.into();
+            provider_rw.tx_ref().put::<A::AccountTrieTable>(key, node.clone()).unwrap()
+        });
+        provider_rw.commit().unwrap();
+
+        assert_eq!(TrieVerifier::new(factory.clone(), 0).unwrap_err(), InvalidTrieVerifierDepth(0));
+        assert_eq!(
+            TrieVerifier::new(factory.clone(), MAX_TRIE_VERIFIER_DEPTH + 1).unwrap_err(),
+            InvalidTrieVerifierDepth(MAX_TRIE_VERIFIER_DEPTH + 1)
+        );
+
+        let mut verifier =
+            TrieVerifier::new(factory.clone(), 1).unwrap().with_progress_file(&progress_file);
+        assert_eq!(verifier.chunk_count(), 16);
+
+        let chunk = verifier.verify_next_chunk().unwrap();
+        assert_eq!(chunk.prefix, Nibbles::from_nibbles([0x0]));
+        assert!(chunk.inconsistencies.is_empty());
+        assert!(!chunk.pass_completed);
+
+        // Resume from the persisted progress.
+        let mut verifier =
+            TrieVerifier::new(factory.clone(), 1).unwrap().with_progress_file(&progress_file);
+        assert_eq!(verifier.next_prefix(), Nibbles::from_nibbles([0x1]));
+        let chunk = verifier.verify_next_chunk().unwrap();
+        assert_eq!(chunk.inconsistencies, vec![Output::AccountExtra(path, node)]);
+
+        // Progress of a different depth is discarded.
+        let other = TrieVerifier::new(factory, 2).unwrap().with_progress_file(&progress_file);
+        assert_eq!(other.next_prefix(), Nibbles::from_nibbles([0x0, 0x0]));
+
+        for _ in 2..15 {
+            let chunk = verifier.verify_next_chunk().unwrap();
+            assert!(chunk.inconsistencies.is_empty());
+            assert!(!chunk.pass_completed);
+        }
+        let chunk = verifier.verify_next_chunk().unwrap();
+        assert_eq!(chunk.prefix, Nibbles::from_nibbles([0xf]));
+        assert!(chunk.pass_completed);
+        assert_eq!(verifier.next_prefix(), Nibbles::from_nibbles([0x0]));
+    }
+}
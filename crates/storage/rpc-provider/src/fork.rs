@@ -0,0 +1,420 @@
+//! Remote chain state for forking a chain locally.
+
+use alloy_consensus::constants::KECCAK_EMPTY;
+use alloy_network::{primitives::HeaderResponse, BlockResponse, Network};
+use alloy_primitives::{
+    keccak256, Address, BlockNumber, Bytes, StorageKey, StorageValue, B256, U256,
+};
+use alloy_provider::Provider;
+use dashmap::DashMap;
+use reth_errors::{ProviderError, ProviderResult};
+use reth_primitives_traits::{Account, Bytecode};
+use reth_storage_api::ForkStateSource;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    future::{Future, IntoFuture},
+    io::{self, BufRead, BufReader, Write},
+    marker::PhantomData,
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+use tokio::runtime::{Handle, Runtime, RuntimeFlavor};
+use tracing::{debug, warn};
+
+/// [`ForkStateSource`] that lazily fetches the state of a remote chain at the fork block over
+/// JSON-RPC.
+///
+/// Only standard `eth_` methods are used, so any node that serves state at the fork block works.
+/// Every fetched value is appended to a cache file, which is replayed on startup so restarting a
+/// forked node doesn't fetch the same state again. The cache is discarded if it belongs to a
+/// different fork block.
+///
+/// Bytecodes are fetched together with the accounts that use them, because there is no standard
+/// method to fetch code by its hash.
+pub struct RpcForkState<P, N> {
+    /// The remote node.
+    provider: P,
+    /// Block the local chain was forked from.
+    fork_block: BlockNumber,
+    /// Runtime used to drive requests from threads outside of it.
+    handle: Handle,
+    /// Runtime used to drive requests from within a current-thread runtime, which can't be
+    /// blocked on.
+    fallback_runtime: OnceLock<Runtime>,
+    /// Accounts at the fork block, `None` if the account doesn't exist.
+    accounts: DashMap<Address, Option<Account>>,
+    /// Storage slots at the fork block.
+    storage: DashMap<(Address, StorageKey), StorageValue>,
+    /// Bytecodes of the fetched accounts.
+    bytecodes: DashMap<B256, Bytecode>,
+    /// Hashes of blocks at or below the fork block.
+    block_hashes: DashMap<BlockNumber, B256>,
+    /// Append-only cache file.
+    cache_file: Mutex<File>,
+    /// Network marker.
+    _network: PhantomData<N>,
+}
+
+impl<P, N> std::fmt::Debug for RpcForkState<P, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcForkState")
+            .field("fork_block", &self.fork_block)
+            .field("accounts", &self.accounts.len())
+            .field("storage", &self.storage.len())
+            .field("bytecodes", &self.bytecodes.len())
+            .field("block_hashes", &self.block_hashes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P, N> RpcForkState<P, N>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    /// Creates a fork state for the block with the given number and hash, loading previously
+    /// fetched values from the cache file at `cache_path`.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn new(
+        provider: P,
+        fork_block: BlockNumber,
+        fork_block_hash: B256,
+        cache_path: impl AsRef<Path>,
+    ) -> io::Result<Self> {
+        let cache_path = cache_path.as_ref();
+        let file = OpenOptions::new().create(true).read(true).append(true).open(cache_path)?;
+        let this = Self {
+            provider,
+            fork_block,
+            handle: Handle::current(),
+            fallback_runtime: OnceLock::new(),
+            accounts: DashMap::default(),
+            storage: DashMap::default(),
+            bytecodes: DashMap::default(),
+            block_hashes: DashMap::default(),
+            cache_file: Mutex::new(file),
+            _network: PhantomData,
+        };
+
+        let fork = CacheEntry::Fork { number: fork_block, hash: fork_block_hash };
+        if !this.load(cache_path, &fork)? {
+            let mut file = this.cache_file.lock().unwrap_or_else(|err| err.into_inner());
+            file.set_len(0)?;
+            write_entry(&mut file, &fork)?;
+        }
+
+        debug!(target: "providers::rpc::fork", ?this, "Loaded fork state cache");
+        Ok(this)
+    }
+
+    /// Replays the cache file. Returns `false` if it is empty or belongs to a different fork.
+    fn load(&self, path: &Path, fork: &CacheEntry) -> io::Result<bool> {
+        let file = self.cache_file.lock().unwrap_or_else(|err| err.into_inner());
+        let mut lines = BufReader::new(&*file).lines();
+
+        match lines.next().transpose()?.map(|line| serde_json::from_str::<CacheEntry>(&line)) {
+            None => return Ok(false),
+            Some(Ok(entry)) if entry == *fork => {}
+            Some(_) => {
+                warn!(
+                    target: "providers::rpc::fork",
+                    path = %path.display(),
+                    "Fork state cache belongs to a different fork, discarding"
+                );
+                return Ok(false)
+            }
+        }
+
+        for line in lines {
+            // A partially written last line is expected if the node was killed mid-write.
+            let Ok(entry) = serde_json::from_str::<CacheEntry>(&line?) else { break };
+            match entry {
+                CacheEntry::Fork { .. } => {}
+                CacheEntry::Account { address, account } => {
+                    self.accounts.insert(address, account.map(Into::into));
+                }
+                CacheEntry::Storage { address, key, value } => {
+                    self.storage.insert((address, key), value);
+                }
+                CacheEntry::Bytecode { code } => {
+                    self.bytecodes.insert(keccak256(&code), Bytecode::new_raw(code));
+                }
+                CacheEntry::BlockHash { number, hash } => {
+                    self.block_hashes.insert(number, hash);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Appends an entry to the cache file.
+    fn persist(&self, entry: &CacheEntry) {
+        let mut file = self.cache_file.lock().unwrap_or_else(|err| err.into_inner());
+        if let Err(err) = write_entry(&mut file, entry) {
+            warn!(target: "providers::rpc::fork", %err, "Failed to write fork state cache");
+        }
+    }
+
+    /// Drives the future to completion.
+    ///
+    /// Within a multi-thread runtime, the future is driven in place. `block_in_place` isn't
+    /// supported on a current-thread runtime, and its IO can't be driven while it is blocked, so
+    /// within one the future is driven by a dedicated runtime on a separate thread instead.
+    fn block_on<F>(&self, fut: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(fut))
+            }
+            Ok(_) => std::thread::scope(|scope| {
+                scope
+                    .spawn(|| self.fallback_runtime().block_on(fut))
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            }),
+            Err(_) => self.handle.block_on(fut),
+        }
+    }
+
+    /// Returns the runtime used to drive requests from within a current-thread runtime.
+    fn fallback_runtime(&self) -> &Runtime {
+        self.fallback_runtime.get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("fork-state")
+                .enable_all()
+                .build()
+                .expect("failed to build fork state runtime")
+        })
+    }
+
+    /// Fetches an account and its bytecode at the fork block.
+    fn fetch_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        let block = self.fork_block.into();
+        let (nonce, balance, code) = self.block_on(async {
+            tokio::join!(
+                self.provider.get_transaction_count(address).block_id(block),
+                self.provider.get_balance(address).block_id(block),
+                self.provider.get_code_at(address).block_id(block),
+            )
+        });
+        let nonce = nonce.map_err(ProviderError::other)?;
+        let balance = balance.map_err(ProviderError::other)?;
+        let code = code.map_err(ProviderError::other)?;
+
+        if nonce == 0 && balance.is_zero() && code.is_empty() {
+            return Ok(None)
+        }
+
+        let bytecode_hash = (!code.is_empty()).then(|| keccak256(&code));
+        if let Some(hash) = bytecode_hash &&
+            hash != KECCAK_EMPTY &&
+            !self.bytecodes.contains_key(&hash)
+        {
+            self.persist(&CacheEntry::Bytecode { code: code.clone() });
+            self.bytecodes.insert(hash, Bytecode::new_raw(code));
+        }
+
+        Ok(Some(Account { nonce, balance, bytecode_hash }))
+    }
+}
+
+impl<P, N> Drop for RpcForkState<P, N> {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks, which panics within an async context.
+        if let Some(runtime) = self.fallback_runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl<P, N> ForkStateSource for RpcForkState<P, N>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    fn fork_block(&self) -> BlockNumber {
+        self.fork_block
+    }
+
+    fn basic_account(&self, address: &Address) -> ProviderResult<Option<Account>> {
+        if let Some(account) = self.accounts.get(address) {
+            return Ok(*account)
+        }
+
+        let account = self.fetch_account(*address)?;
+        self.persist(&CacheEntry::Account { address: *address, account: account.map(Into::into) });
+        self.accounts.insert(*address, account);
+        Ok(account)
+    }
+
+    fn storage(&self, address: Address, storage_key: StorageKey) -> ProviderResult<StorageValue> {
+        if let Some(value) = self.storage.get(&(address, storage_key)) {
+            return Ok(*value)
+        }
+
+        let value = self
+            .block_on(
+                self.provider
+                    .get_storage_at(address, storage_key.into())
+                    .block_id(self.fork_block.into())
+                    .into_future(),
+            )
+            .map_err(ProviderError::other)?;
+        self.persist(&CacheEntry::Storage { address, key: storage_key, value });
+        self.storage.insert((address, storage_key), value);
+        Ok(value)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: &B256) -> ProviderResult<Option<Bytecode>> {
+        Ok(self.bytecodes.get(code_hash).map(|bytecode| bytecode.clone()))
+    }
+
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        if number > self.fork_block {
+            return Ok(None)
+        }
+        if let Some(hash) = self.block_hashes.get(&number) {
+            return Ok(Some(*hash))
+        }
+
+        let block = self
+            .block_on(self.provider.get_block_by_number(number.into()).into_future())
+            .map_err(ProviderError::other)?;
+        let Some(hash) = block.map(|block| block.header().hash()) else { return Ok(None) };
+        self.persist(&CacheEntry::BlockHash { number, hash });
+        self.block_hashes.insert(number, hash);
+        Ok(Some(hash))
+    }
+}
+
+/// Returns the number and hash of the fork block the cache file at `cache_path` belongs to, if
+/// it exists.
+pub fn cached_fork_block(cache_path: impl AsRef<Path>) -> io::Result<Option<(BlockNumber, B256)>> {
+    let file = match File::open(cache_path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let Some(line) = BufReader::new(file).lines().next().transpose()? else { return Ok(None) };
+    match serde_json::from_str(&line) {
+        Ok(CacheEntry::Fork { number, hash }) => Ok(Some((number, hash))),
+        _ => Ok(None),
+    }
+}
+
+/// Line of the fork state cache file.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum CacheEntry {
+    /// First line, identifying the fork the cache belongs to.
+    Fork { number: BlockNumber, hash: B256 },
+    /// An account at the fork block.
+    Account { address: Address, account: Option<CachedAccount> },
+    /// A storage slot at the fork block.
+    Storage { address: Address, key: StorageKey, value: StorageValue },
+    /// Bytecode of an account, keyed by its hash.
+    Bytecode { code: Bytes },
+    /// Hash of a block at or below the fork block.
+    BlockHash { number: BlockNumber, hash: B256 },
+}
+
+/// Serialized form of an [`Account`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedAccount {
+    nonce: u64,
+    balance: U256,
+    bytecode_hash: Option<B256>,
+}
+
+impl From<Account> for CachedAccount {
+    fn from(Account { nonce, balance, bytecode_hash }: Account) -> Self {
+        Self { nonce, balance, bytecode_hash }
+    }
+}
+
+impl From<CachedAccount> for Account {
+    fn from(CachedAccount { nonce, balance, bytecode_hash }: CachedAccount) -> Self {
+        Self { nonce, balance, bytecode_hash }
+    }
+}
+
+fn write_entry(file: &mut File, entry: &CacheEntry) -> io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    file.write_all(&line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_network::AnyNetwork;
+    use alloy_primitives::{address, bytes, U64};
+    use alloy_provider::ProviderBuilder;
+    use alloy_transport::mock::Asserter;
+
+    const ADDRESS: Address = address!("0x0000000000000000000000000000000000000001");
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fork_state_within_current_thread_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let asserter = Asserter::new();
+        let provider =
+            ProviderBuilder::new().network::<AnyNetwork>().connect_mocked_client(asserter.clone());
+        asserter.push_success(&U256::from(3));
+
+        let fork_state =
+            RpcForkState::new(provider, 10, B256::ZERO, dir.path().join("fork-state.jsonl"))
+                .unwrap();
+        assert_eq!(fork_state.storage(ADDRESS, B256::ZERO).unwrap(), U256::from(3));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fork_state_is_cached_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("fork-state.jsonl");
+        let fork_hash = B256::with_last_byte(1);
+        let code = bytes!("0x60006000");
+
+        let asserter = Asserter::new();
+        let provider =
+            ProviderBuilder::new().network::<AnyNetwork>().connect_mocked_client(asserter.clone());
+
+        // nonce, balance and code of the account, then the storage slot
+        asserter.push_success(&U64::from(1));
+        asserter.push_success(&U256::from(2));
+        asserter.push_success(&code);
+        asserter.push_success(&U256::from(3));
+
+        let expected =
+            Account { nonce: 1, balance: U256::from(2), bytecode_hash: Some(keccak256(&code)) };
+        let fork_state = RpcForkState::new(provider.clone(), 10, fork_hash, &cache_path).unwrap();
+        assert_eq!(fork_state.basic_account(&ADDRESS).unwrap(), Some(expected));
+        assert_eq!(fork_state.storage(ADDRESS, B256::ZERO).unwrap(), U256::from(3));
+        assert_eq!(
+            fork_state.bytecode_by_hash(&keccak256(&code)).unwrap(),
+            Some(Bytecode::new_raw(code.clone()))
+        );
+        drop(fork_state);
+        assert_eq!(cached_fork_block(&cache_path).unwrap(), Some((10, fork_hash)));
+
+        // Reopening the same fork serves everything from the cache without any requests.
+        let fork_state = RpcForkState::new(provider.clone(), 10, fork_hash, &cache_path).unwrap();
+        assert_eq!(fork_state.basic_account(&ADDRESS).unwrap(), Some(expected));
+        assert_eq!(fork_state.storage(ADDRESS, B256::ZERO).unwrap(), U256::from(3));
+        assert!(fork_state.bytecode_by_hash(&keccak256(&code)).unwrap().is_some());
+        drop(fork_state);
+
+        // The cache of a different fork is discarded.
+        let fork_state =
+            RpcForkState::new(provider, 10, B256::with_last_byte(2), &cache_path).unwrap();
+        assert!(fork_state.bytecode_by_hash(&keccak256(&code)).unwrap().is_none());
+        assert!(fork_state.basic_account(&ADDRESS).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+use alloy_primitives::BlockNumber;
+use reth_storage_errors::provider::ProviderResult;
+use std::path::{Path, PathBuf};
+
+/// Summary of a backup written by [`StorageBackupProvider::backup_storage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageBackup {
+    /// Directory the backup was written to.
+    pub path: PathBuf,
+    /// Highest fully synced block contained in the backup.
+    pub block: BlockNumber,
+}
+
+/// Creates online backups of the node's storage.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait StorageBackupProvider: Send + Sync {
+    /// Writes a consistent point-in-time backup of the node's storage into `dest`, laid out like
+    /// a regular datadir. `dest` must not exist or be an empty directory.
+    ///
+    /// This may take a long time on large databases, but doesn't block writers.
+    fn backup_storage(&self, dest: &Path) -> ProviderResult<StorageBackup>;
+}
@@ -0,0 +1,27 @@
+use alloy_primitives::{Address, BlockNumber, StorageKey, StorageValue, B256};
+use core::fmt::Debug;
+use reth_primitives_traits::{Account, Bytecode};
+use reth_storage_errors::provider::ProviderResult;
+
+/// State of a remote chain at the block a local chain was forked from.
+///
+/// Historical state providers fall back to this source for accounts and storage slots that were
+/// never written locally, and for bytecodes and block hashes missing from the local database. This
+/// lets a local chain continue from a block of another chain without syncing its state first.
+#[auto_impl::auto_impl(&, Box, Arc)]
+pub trait ForkStateSource: Debug + Send + Sync {
+    /// Returns the number of the block the local chain was forked from.
+    fn fork_block(&self) -> BlockNumber;
+
+    /// Get basic account information at the fork block.
+    fn basic_account(&self, address: &Address) -> ProviderResult<Option<Account>>;
+
+    /// Get the value of a storage slot at the fork block. Slots that are not set are zero.
+    fn storage(&self, address: Address, storage_key: StorageKey) -> ProviderResult<StorageValue>;
+
+    /// Get account code by its hash.
+    fn bytecode_by_hash(&self, code_hash: &B256) -> ProviderResult<Option<Bytecode>>;
+
+    /// Get the hash of a block at or below the fork block.
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>>;
+}
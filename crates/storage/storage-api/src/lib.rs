@@ -28,6 +28,9 @@ pub use block_id::*;
 mod block_hash;
 pub use block_hash::*;
 
+mod fork;
+pub use fork::*;
+
 #[cfg(feature = "db-api")]
 mod chain;
 #[cfg(feature = "db-api")]
@@ -96,6 +99,11 @@ pub use block_writer::*;
 mod state_writer;
 pub use state_writer::*;
 
+#[cfg(feature = "std")]
+mod backup;
+#[cfg(feature = "std")]
+pub use backup::*;
+
 mod header_sync_gap;
 pub use header_sync_gap::HeaderSyncGapProvider;
 
@@ -201,4 +201,13 @@ pub trait StateProviderFactory: BlockIdReader + Send {
     ///
     /// This will return `None` if there's no pending state.
     fn maybe_pending(&self) -> ProviderResult<Option<StateProviderBox>>;
+
+    /// Returns `true` if the trie changesets needed to revert the persisted trie to the state
+    /// after the given block are archived.
+    ///
+    /// Proofs at such blocks are served from the archive instead of being recomputed from state
+    /// reverts.
+    fn has_archived_trie_changesets(&self, _block: BlockNumber) -> ProviderResult<bool> {
+        Ok(false)
+    }
 }
@@ -103,6 +103,14 @@ pub trait StateRangeProvider {
         hashed_address: B256,
         keys: &[B256],
     ) -> ProviderResult<Vec<Bytes>>;
+
+    /// Returns the account-trie proof of `hashed_address` together with the storage-trie proof of
+    /// the already-hashed `slots`, as RLP encoded MPT nodes.
+    fn account_proof(
+        &self,
+        hashed_address: B256,
+        slots: &[B256],
+    ) -> ProviderResult<AccountProofNodes>;
 }
 
 /// A type that resolves retained state roots into reusable state range views.
@@ -112,6 +120,16 @@ pub trait StateRangeProviderFactory {
     fn state_range_provider(&self, state_root: B256) -> ProviderResult<Option<StateRangeView>>;
 }
 
+/// Proof nodes of an account and some of its storage slots, see
+/// [`StateRangeProvider::account_proof`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountProofNodes {
+    /// Account-trie nodes on the path to the account.
+    pub account_proof: Vec<Bytes>,
+    /// Storage-trie nodes on the paths to the requested slots.
+    pub storage_proof: Vec<Bytes>,
+}
+
 /// A range query's items and why the range ended where it did.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RangeResponse<T> {
@@ -215,8 +215,9 @@ impl ChangesetCache {
     /// per-block trie changesets (reverts) in reverse order (newest to oldest), so that older
     /// values take precedence when there are conflicts.
     ///
-    /// If any block is missing from cache, this falls back to one aggregate database computation
-    /// for the whole range. The aggregate result restores the trie to the state before the range
+    /// If any block is missing from cache, the range is read from the trie changeset archive if it
+    /// covers the range. Otherwise this falls back to one aggregate database computation for the
+    /// whole range. The aggregate result restores the trie to the state before the range
     /// and is inserted into the range cache.
     ///
     /// # Arguments
@@ -361,6 +362,28 @@ impl ChangesetCache {
             return Ok(accumulated_reverts)
         }
 
+        // In archive mode the per-block changesets are persisted, which is much cheaper than
+        // recomputing them from state reverts.
+        if let Some(archived_reverts) =
+            reth_trie_db::read_range_trie_changesets(provider.tx_ref(), start_block..=end_block)?
+        {
+            let accumulated_reverts = Arc::new(archived_reverts);
+
+            debug!(
+                target: "trie::changeset_cache",
+                elapsed = ?timer.elapsed(),
+                start_block,
+                end_block,
+                num_blocks = end_block.saturating_sub(start_block).saturating_add(1),
+                num_account_nodes = accumulated_reverts.account_nodes_ref().len(),
+                num_storage_tries = accumulated_reverts.storage_tries_ref().len(),
+                "Read archived trie reverts for block range"
+            );
+
+            self.inner.write().insert(range_key, Arc::clone(&accumulated_reverts));
+            return Ok(accumulated_reverts)
+        }
+
         warn!(
             target: "trie::changeset_cache",
             start_block,
@@ -1,6 +1,9 @@
 use crate::{
     maintain::MAX_QUEUED_TRANSACTION_LIFETIME,
-    pool::{NEW_TX_LISTENER_BUFFER_SIZE, PENDING_TX_LISTENER_BUFFER_SIZE},
+    pool::{
+        journal::TransactionJournalConfig, NEW_TX_LISTENER_BUFFER_SIZE,
+        PENDING_TX_LISTENER_BUFFER_SIZE,
+    },
     PoolSize, TransactionOrigin,
 };
 use alloy_consensus::constants::EIP4844_TX_TYPE_ID;
@@ -72,6 +75,10 @@ pub struct PoolConfig {
     ///
     /// This restricts how many executable transaction a delegated sender can stack.
     pub max_inflight_delegated_slot_limit: usize,
+    /// Journal of transaction lifecycle events, disabled if `None`.
+    ///
+    /// See [`TransactionJournal`](crate::pool::journal::TransactionJournal).
+    pub journal: Option<TransactionJournalConfig>,
 }
 
 impl PoolConfig {
@@ -91,6 +98,12 @@ impl PoolConfig {
         self
     }
 
+    /// Enables the journal of transaction lifecycle events.
+    pub const fn with_journal(mut self, journal: TransactionJournalConfig) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
     /// Configures how many slots are available for a delegated sender.
     pub const fn with_max_inflight_delegated_slots(
         mut self,
@@ -129,6 +142,7 @@ impl Default for PoolConfig {
             max_new_pending_txs_notifications: MAX_NEW_PENDING_TXS_NOTIFICATIONS,
             max_queued_lifetime: MAX_QUEUED_TRANSACTION_LIFETIME,
             max_inflight_delegated_slot_limit: DEFAULT_MAX_INFLIGHT_DELEGATED_SLOTS,
+            journal: None,
         }
     }
 }
@@ -132,6 +132,8 @@
 //! 1. **Block Updates**: Removes mined txs, updates accounts/fees, triggers movements
 //! 2. **Size Enforcement**: Discards worst transactions when limits exceeded
 //! 3. **Propagation**: External (always), Local (configurable), Private (never)
+//! 4. **Simulation** (optional): Pre-executes the best pending txs on top of the latest block, see
+//!    [`simulate`]
 //!
 //! ## Assumptions
 //!
@@ -291,10 +293,16 @@ pub use crate::{
     error::{PoolResult, RawPoolTransactionError},
     ordering::{CoinbaseTipOrdering, Priority, TransactionOrdering},
     pool::{
-        blob_tx_priority, fee_delta, state::SubPool, AddedTransactionOutcome,
-        AllTransactionsEvents, FullTransactionEvent, NewTransactionEvent, TransactionEvent,
-        TransactionEvents, TransactionListenerKind,
+        blob_tx_priority, fee_delta,
+        journal::{
+            TransactionHistory, TransactionJournal, TransactionJournalConfig,
+            TransactionJournalEntry, TransactionLifecycleEvent,
+        },
+        state::SubPool,
+        AddedTransactionOutcome, AllTransactionsEvents, DiscardReason, FullTransactionEvent,
+        NewTransactionEvent, TransactionEvent, TransactionEvents, TransactionListenerKind,
     },
+    simulate::{SimulationConfig, SimulationOutcome, TransactionSimulation},
     traits::*,
     validate::{
         EthTransactionValidator, StatefulValidationFn, StatelessValidationFn,
@@ -325,6 +333,7 @@ pub mod maintain;
 pub mod metrics;
 pub mod noop;
 pub mod pool;
+pub mod simulate;
 pub mod validate;
 
 pub mod batcher;
@@ -535,6 +544,10 @@ where
         self.pool.add_all_transactions_event_listener()
     }
 
+    fn transaction_history(&self, tx_hash: TxHash) -> Option<TransactionHistory> {
+        self.pool.transaction_history(&tx_hash)
+    }
+
     fn pending_transactions_listener_for(&self, kind: TransactionListenerKind) -> Receiver<TxHash> {
         self.pool.add_pending_listener(kind)
     }
@@ -868,6 +881,24 @@ where
     fn cleanup_blobs(&self) {
         self.pool.cleanup_blobs()
     }
+
+    fn remove_stale_transactions(
+        &self,
+        hashes: Vec<TxHash>,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.pool.remove_transactions_with_reason(hashes, DiscardReason::Lifetime)
+    }
+
+    fn remove_reverting_transactions(
+        &self,
+        hashes: Vec<TxHash>,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.pool.remove_transactions_with_reason(hashes, DiscardReason::Reverted)
+    }
+
+    fn transaction_journal(&self) -> Option<TransactionJournal> {
+        self.pool.transaction_journal()
+    }
 }
 
 impl<V, T, S> ValidatingPool for Pool<V, T, S>
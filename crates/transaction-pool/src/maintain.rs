@@ -289,7 +289,7 @@ pub async fn maintain_transaction_pool<N, Client, P, St>(
                     })
                     .collect();
                 debug!(target: "txpool", count=%stale_txs.len(), "removing stale transactions");
-                pool.remove_transactions(stale_txs);
+                pool.remove_stale_transactions(stale_txs);
                 pool.delete_blobs(stale_blobs);
             }
         }
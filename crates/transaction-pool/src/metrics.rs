@@ -121,6 +121,18 @@ impl MaintainPoolMetrics {
     }
 }
 
+/// Pending transaction simulation metrics
+#[derive(Metrics)]
+#[metrics(scope = "transaction_pool.simulation")]
+pub struct SimulationMetrics {
+    /// Counter for the number of simulated transactions.
+    pub simulated_transactions: Counter,
+    /// Counter for the number of simulated transactions that reverted.
+    pub reverted_transactions: Counter,
+    /// Counter for the number of transactions evicted because they kept reverting.
+    pub evicted_transactions: Counter,
+}
+
 /// All Transactions metrics
 #[derive(Metrics)]
 #[metrics(scope = "transaction_pool")]
@@ -446,7 +446,7 @@ impl<T: EthPoolTransaction> TransactionValidator for MockTransactionValidator<T>
             propagate: match origin {
                 TransactionOrigin::External => true,
                 TransactionOrigin::Local => self.propagate_local,
-                TransactionOrigin::Private => false,
+                TransactionOrigin::Private | TransactionOrigin::PrivateRelay => false,
             },
             authorities: None,
         }
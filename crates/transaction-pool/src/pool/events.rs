@@ -2,7 +2,7 @@ use crate::{traits::PropagateKind, PoolTransaction, SubPool, ValidPoolTransactio
 use alloy_primitives::{TxHash, B256};
 use std::sync::Arc;
 
-use crate::pool::QueuedReason;
+use crate::pool::{DiscardReason, QueuedReason};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -31,8 +31,8 @@ pub enum FullTransactionEvent<T: PoolTransaction> {
         /// The transaction that replaced the event subject.
         replaced_by: TxHash,
     },
-    /// Transaction was dropped due to configured limits.
-    Discarded(TxHash),
+    /// Transaction was removed from the pool for the given reason.
+    Discarded(TxHash, DiscardReason),
     /// Transaction became invalid indefinitely.
     Invalid(TxHash),
     /// Transaction was propagated to peers.
@@ -50,7 +50,7 @@ impl<T: PoolTransaction> Clone for FullTransactionEvent<T> {
             Self::Replaced { transaction, replaced_by } => {
                 Self::Replaced { transaction: Arc::clone(transaction), replaced_by: *replaced_by }
             }
-            Self::Discarded(hash) => Self::Discarded(*hash),
+            Self::Discarded(hash, reason) => Self::Discarded(*hash, *reason),
             Self::Invalid(hash) => Self::Invalid(*hash),
             Self::Propagated(propagated) => Self::Propagated(Arc::clone(propagated)),
         }
@@ -71,8 +71,8 @@ pub enum TransactionEvent {
     ///
     /// E.g. same (sender + nonce) pair
     Replaced(TxHash),
-    /// Transaction was dropped due to configured limits.
-    Discarded,
+    /// Transaction was removed from the pool for the given reason.
+    Discarded(DiscardReason),
     /// Transaction became invalid indefinitely.
     Invalid,
     /// Transaction was propagated to peers.
@@ -83,7 +83,7 @@ impl TransactionEvent {
     /// Returns `true` if the event is final and no more events are expected for this transaction
     /// hash.
     pub const fn is_final(&self) -> bool {
-        matches!(self, Self::Replaced(_) | Self::Mined(_) | Self::Discarded | Self::Invalid)
+        matches!(self, Self::Replaced(_) | Self::Mined(_) | Self::Discarded(_) | Self::Invalid)
     }
 }
 
@@ -0,0 +1,308 @@
+//! A bounded journal of transaction lifecycle events.
+//!
+//! The journal keeps the events of a transaction after it left the pool, so that it can be
+//! explained later why a transaction was dropped, see [`TransactionJournal::history`].
+
+use alloy_primitives::TxHash;
+use parking_lot::Mutex;
+use reth_fs_util::FsPathError;
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, info, warn};
+
+pub use reth_transaction_pool_types::{
+    TransactionHistory, TransactionJournalEntry, TransactionLifecycleEvent,
+};
+
+/// Default number of transactions tracked by the [`TransactionJournal`].
+pub const DEFAULT_JOURNAL_MAX_TRANSACTIONS: u32 = 100_000;
+
+/// Default number of events kept per transaction by the [`TransactionJournal`].
+pub const DEFAULT_JOURNAL_MAX_EVENTS_PER_TRANSACTION: usize = 32;
+
+/// Configuration of the [`TransactionJournal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionJournalConfig {
+    /// Maximum number of transactions to keep the history of, the least recently updated
+    /// transactions are evicted first.
+    pub max_transactions: u32,
+    /// Maximum number of events kept per transaction, the oldest events are evicted first.
+    pub max_events_per_transaction: usize,
+}
+
+impl Default for TransactionJournalConfig {
+    fn default() -> Self {
+        Self {
+            max_transactions: DEFAULT_JOURNAL_MAX_TRANSACTIONS,
+            max_events_per_transaction: DEFAULT_JOURNAL_MAX_EVENTS_PER_TRANSACTION,
+        }
+    }
+}
+
+/// A bounded journal of transaction lifecycle events.
+///
+/// This is a cheaply cloneable handle, all clones share the same journal.
+#[derive(Clone)]
+pub struct TransactionJournal {
+    inner: Arc<Mutex<JournalInner>>,
+}
+
+struct JournalInner {
+    max_events_per_transaction: usize,
+    transactions: LruMap<TxHash, VecDeque<TransactionJournalEntry>, ByLength>,
+}
+
+impl TransactionJournal {
+    /// Creates a new, empty journal.
+    pub fn new(config: TransactionJournalConfig) -> Self {
+        let inner = JournalInner {
+            max_events_per_transaction: config.max_events_per_transaction.max(1),
+            transactions: LruMap::new(ByLength::new(config.max_transactions)),
+        };
+        Self { inner: Arc::new(Mutex::new(inner)) }
+    }
+
+    /// Records an event of the transaction.
+    pub fn record(&self, hash: TxHash, event: TransactionLifecycleEvent) {
+        self.record_entry(hash, TransactionJournalEntry { timestamp: now_millis(), event });
+    }
+
+    /// Records an event of the transaction only if the journal already tracks the transaction.
+    pub fn record_if_tracked(&self, hash: TxHash, event: TransactionLifecycleEvent) {
+        let mut inner = self.inner.lock();
+        let max_events = inner.max_events_per_transaction;
+        if let Some(events) = inner.transactions.get(&hash) {
+            if events.len() >= max_events {
+                events.pop_front();
+            }
+            events.push_back(TransactionJournalEntry { timestamp: now_millis(), event });
+        }
+    }
+
+    fn record_entry(&self, hash: TxHash, entry: TransactionJournalEntry) {
+        let mut inner = self.inner.lock();
+        let max_events = inner.max_events_per_transaction;
+        if let Some(events) = inner.transactions.get_or_insert(hash, VecDeque::new) {
+            if events.len() >= max_events {
+                events.pop_front();
+            }
+            events.push_back(entry);
+        }
+    }
+
+    /// Returns the recorded history of the transaction.
+    pub fn history(&self, hash: &TxHash) -> Option<TransactionHistory> {
+        let inner = self.inner.lock();
+        let events = inner.transactions.peek(hash)?;
+        Some(TransactionHistory { hash: *hash, events: events.iter().cloned().collect() })
+    }
+
+    /// Returns the number of tracked transactions.
+    pub fn len(&self) -> usize {
+        self.inner.lock().transactions.len()
+    }
+
+    /// Returns `true` if no transactions are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes the journal to the file as JSON.
+    pub fn save(&self, path: &Path) -> Result<(), TransactionJournalError> {
+        let histories = {
+            let inner = self.inner.lock();
+            // least recently updated first, so that loading restores the eviction order
+            let mut histories = inner
+                .transactions
+                .iter()
+                .map(|(hash, events)| TransactionHistory {
+                    hash: *hash,
+                    events: events.iter().cloned().collect(),
+                })
+                .collect::<Vec<_>>();
+            histories.reverse();
+            histories
+        };
+
+        let json = serde_json::to_string(&histories)?;
+        if let Some(parent) = path.parent() {
+            reth_fs_util::create_dir_all(parent)?;
+        }
+        reth_fs_util::atomic_write_file(path, |file| file.write_all(json.as_bytes()))?;
+
+        Ok(())
+    }
+
+    /// Loads a journal previously written with [`Self::save`], returning the number of loaded
+    /// transactions.
+    ///
+    /// Transactions that were already recorded by this journal keep their recorded history.
+    pub fn load(&self, path: &Path) -> Result<usize, TransactionJournalError> {
+        if !path.exists() {
+            return Ok(0)
+        }
+        let data = reth_fs_util::read(path)?;
+        let histories = serde_json::from_slice::<Vec<TransactionHistory>>(&data)?;
+
+        let mut inner = self.inner.lock();
+        let mut loaded = 0;
+        for TransactionHistory { hash, events } in histories {
+            if inner.transactions.peek(&hash).is_some() {
+                continue
+            }
+            inner.transactions.insert(hash, events.into());
+            loaded += 1;
+        }
+
+        Ok(loaded)
+    }
+}
+
+impl fmt::Debug for TransactionJournal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionJournal").field("transactions", &self.len()).finish()
+    }
+}
+
+/// Returns the current time in milliseconds since the unix epoch.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Errors when persisting the [`TransactionJournal`].
+#[derive(thiserror::Error, Debug)]
+pub enum TransactionJournalError {
+    /// Error during json encoding or decoding of the journal.
+    #[error("failed to encode or decode transaction journal: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Error accessing the journal file.
+    #[error("failed to access transaction journal file: {0}")]
+    FsPath(#[from] FsPathError),
+}
+
+/// Task which loads the journal from the file on startup and writes it back on shutdown.
+pub async fn persist_transaction_journal_task(
+    shutdown: reth_tasks::shutdown::GracefulShutdown,
+    journal: TransactionJournal,
+    path: PathBuf,
+) {
+    match journal.load(&path) {
+        Ok(loaded) => {
+            debug!(target: "txpool", journal_file=?path, %loaded, "Loaded transaction journal")
+        }
+        Err(err) => {
+            warn!(target: "txpool", %err, journal_file=?path, "Failed to load transaction journal")
+        }
+    }
+
+    let graceful_guard = shutdown.await;
+
+    match journal.save(&path) {
+        Ok(()) => {
+            info!(target: "txpool", journal_file=?path, transactions=%journal.len(), "Wrote transaction journal to file")
+        }
+        Err(err) => {
+            warn!(target: "txpool", %err, journal_file=?path, "Failed to write transaction journal to file")
+        }
+    }
+
+    drop(graceful_guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiscardReason;
+
+    fn entry(timestamp: u64, event: TransactionLifecycleEvent) -> TransactionJournalEntry {
+        TransactionJournalEntry { timestamp, event }
+    }
+
+    #[test]
+    fn bounded_journal() {
+        let journal = TransactionJournal::new(TransactionJournalConfig {
+            max_transactions: 2,
+            max_events_per_transaction: 2,
+        });
+        let [a, b, c] = [1, 2, 3].map(TxHash::with_last_byte);
+
+        journal.record_entry(a, entry(1, TransactionLifecycleEvent::Queued { reason: None }));
+        journal.record_entry(a, entry(2, TransactionLifecycleEvent::Pending));
+        journal.record_entry(
+            a,
+            entry(3, TransactionLifecycleEvent::Discarded { reason: DiscardReason::SizeLimit }),
+        );
+        // only the latest events are kept
+        assert_eq!(
+            journal.history(&a).unwrap().events,
+            [
+                entry(2, TransactionLifecycleEvent::Pending),
+                entry(3, TransactionLifecycleEvent::Discarded { reason: DiscardReason::SizeLimit }),
+            ]
+        );
+
+        journal.record(b, TransactionLifecycleEvent::Pending);
+        journal.record(c, TransactionLifecycleEvent::Pending);
+        // the least recently updated transaction is evicted
+        assert!(journal.history(&a).is_none());
+        assert_eq!(journal.len(), 2);
+    }
+
+    #[test]
+    fn record_if_tracked() {
+        let journal = TransactionJournal::new(TransactionJournalConfig {
+            max_transactions: 1,
+            ..Default::default()
+        });
+        let (a, b) = (TxHash::with_last_byte(1), TxHash::with_last_byte(2));
+        let invalid = || TransactionLifecycleEvent::Invalid { error: "nonce too low".to_string() };
+
+        journal.record(a, TransactionLifecycleEvent::Pending);
+        // untracked transactions don't evict tracked ones
+        journal.record_if_tracked(b, invalid());
+        assert!(journal.history(&b).is_none());
+
+        journal.record_if_tracked(a, invalid());
+        let events = journal.history(&a).unwrap().events;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].event, invalid());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.json");
+        let config = TransactionJournalConfig { max_transactions: 2, ..Default::default() };
+
+        let journal = TransactionJournal::new(config);
+        let (a, b) = (TxHash::with_last_byte(1), TxHash::with_last_byte(2));
+        journal.record_entry(a, entry(1, TransactionLifecycleEvent::Pending));
+        journal.record_entry(
+            b,
+            entry(2, TransactionLifecycleEvent::Invalid { error: "nonce too low".to_string() }),
+        );
+        journal.save(&path).unwrap();
+
+        let loaded = TransactionJournal::new(config);
+        loaded.record_entry(a, entry(3, TransactionLifecycleEvent::Pending));
+        assert_eq!(loaded.load(&path).unwrap(), 1);
+        assert_eq!(
+            loaded.history(&a).unwrap().events,
+            [entry(3, TransactionLifecycleEvent::Pending)]
+        );
+        assert_eq!(loaded.history(&b), journal.history(&b));
+
+        // missing files are ignored
+        assert_eq!(loaded.load(&dir.path().join("missing.json")).unwrap(), 0);
+    }
+}
@@ -1,11 +1,13 @@
 //! Listeners for the transaction-pool
 
 use crate::{
+    error::InvalidPoolTransactionError,
     pool::{
-        events::{FullTransactionEvent, NewTransactionEvent, TransactionEvent},
+        events::{DiscardReason, FullTransactionEvent, NewTransactionEvent, TransactionEvent},
+        journal::{TransactionJournal, TransactionLifecycleEvent},
         QueuedReason,
     },
-    traits::{NewBlobSidecar, PropagateKind},
+    traits::{NewBlobSidecar, PropagateKind, TransactionOrigin},
     PoolTransaction, ValidPoolTransaction,
 };
 use alloy_primitives::{
@@ -83,12 +85,16 @@ impl<T: PoolTransaction> Stream for AllTransactionsEvents<T> {
 ///
 /// This is essentially a multi-producer, multi-consumer channel where each event is broadcast to
 /// all active receivers.
+///
+/// If a [`TransactionJournal`] is installed, all events are also recorded in the journal.
 #[derive(Debug)]
 pub struct PoolEventBroadcast<T: PoolTransaction> {
     /// All listeners for all transaction events.
     all_events_broadcaster: AllPoolEventsBroadcaster<T>,
     /// All listeners for events for a certain transaction hash.
     broadcasters_by_hash: B256Map<PoolEventBroadcaster>,
+    /// Records the lifecycle events of all transactions, if enabled.
+    journal: Option<TransactionJournal>,
 }
 
 impl<T: PoolTransaction> Default for PoolEventBroadcast<T> {
@@ -96,11 +102,30 @@ impl<T: PoolTransaction> Default for PoolEventBroadcast<T> {
         Self {
             all_events_broadcaster: AllPoolEventsBroadcaster::default(),
             broadcasters_by_hash: B256Map::default(),
+            journal: None,
         }
     }
 }
 
 impl<T: PoolTransaction> PoolEventBroadcast<T> {
+    /// Creates a new broadcaster that records all events in the given journal.
+    pub fn with_journal(journal: TransactionJournal) -> Self {
+        Self { journal: Some(journal), ..Default::default() }
+    }
+
+    /// Returns the installed journal, if any.
+    pub const fn journal(&self) -> Option<&TransactionJournal> {
+        self.journal.as_ref()
+    }
+
+    /// Records the event in the journal, if one is installed.
+    #[inline]
+    fn record(&self, hash: &TxHash, event: impl FnOnce() -> TransactionLifecycleEvent) {
+        if let Some(journal) = &self.journal {
+            journal.record(*hash, event());
+        }
+    }
+
     /// Calls the broadcast callback with the `PoolEventBroadcaster` that belongs to the hash.
     fn broadcast_event(
         &mut self,
@@ -121,10 +146,12 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
         self.all_events_broadcaster.broadcast(pool_event);
     }
 
-    /// Returns true if no listeners are installed
+    /// Returns true if no listeners and no journal are installed
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.all_events_broadcaster.is_empty() && self.broadcasters_by_hash.is_empty()
+        self.all_events_broadcaster.is_empty() &&
+            self.broadcasters_by_hash.is_empty() &&
+            self.journal.is_none()
     }
 
     /// Create a new subscription for the given transaction hash.
@@ -151,6 +178,7 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
 
     /// Notify listeners about a transaction that was added to the pending queue.
     pub fn pending(&mut self, tx: &TxHash, replaced: Option<Arc<ValidPoolTransaction<T>>>) {
+        self.record(tx, || TransactionLifecycleEvent::Pending);
         self.broadcast_event(tx, TransactionEvent::Pending, FullTransactionEvent::Pending(*tx));
 
         if let Some(replaced) = replaced {
@@ -161,6 +189,7 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
 
     /// Notify listeners about a transaction that was replaced.
     pub fn replaced(&mut self, tx: Arc<ValidPoolTransaction<T>>, replaced_by: TxHash) {
+        self.record(tx.hash(), || TransactionLifecycleEvent::Replaced { replaced_by });
         let transaction = Arc::clone(&tx);
         self.broadcast_event(
             tx.hash(),
@@ -171,6 +200,7 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
 
     /// Notify listeners about a transaction that was added to the queued pool.
     pub fn queued(&mut self, tx: &TxHash, reason: Option<QueuedReason>) {
+        self.record(tx, || TransactionLifecycleEvent::Queued { reason: reason.clone() });
         self.broadcast_event(
             tx,
             TransactionEvent::Queued,
@@ -180,6 +210,7 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
 
     /// Notify listeners about a transaction that was propagated.
     pub fn propagated(&mut self, tx: &TxHash, peers: Vec<PropagateKind>) {
+        self.record(tx, || TransactionLifecycleEvent::Propagated { peers: peers.len() });
         let peers = Arc::new(peers);
         self.broadcast_event(
             tx,
@@ -188,29 +219,56 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
         );
     }
 
-    /// Notify listeners about all discarded transactions.
+    /// Notify listeners about all transactions that were discarded for the same reason.
     #[inline]
-    pub fn discarded_many(&mut self, discarded: &[Arc<ValidPoolTransaction<T>>]) {
+    pub fn discarded_many(
+        &mut self,
+        discarded: &[Arc<ValidPoolTransaction<T>>],
+        reason: DiscardReason,
+    ) {
         if self.is_empty() {
             return
         }
         for tx in discarded {
-            self.discarded(tx.hash());
+            self.discarded(tx.hash(), reason);
         }
     }
 
     /// Notify listeners about a transaction that was discarded.
-    pub fn discarded(&mut self, tx: &TxHash) {
-        self.broadcast_event(tx, TransactionEvent::Discarded, FullTransactionEvent::Discarded(*tx));
+    pub fn discarded(&mut self, tx: &TxHash, reason: DiscardReason) {
+        self.record(tx, || TransactionLifecycleEvent::Discarded { reason });
+        self.broadcast_event(
+            tx,
+            TransactionEvent::Discarded(reason),
+            FullTransactionEvent::Discarded(*tx, reason),
+        );
     }
 
     /// Notify listeners about a transaction that was invalid.
-    pub fn invalid(&mut self, tx: &TxHash) {
+    ///
+    /// Invalid [external](TransactionOrigin::External) transactions are only recorded in the
+    /// journal if it already tracks them, so that peers can't evict the tracked transactions by
+    /// sending invalid transactions.
+    pub fn invalid(
+        &mut self,
+        tx: &TxHash,
+        origin: TransactionOrigin,
+        error: &InvalidPoolTransactionError,
+    ) {
+        if let Some(journal) = &self.journal {
+            let event = TransactionLifecycleEvent::Invalid { error: error.to_string() };
+            if origin.is_external() {
+                journal.record_if_tracked(*tx, event);
+            } else {
+                journal.record(*tx, event);
+            }
+        }
         self.broadcast_event(tx, TransactionEvent::Invalid, FullTransactionEvent::Invalid(*tx));
     }
 
     /// Notify listeners that the transaction was mined
     pub fn mined(&mut self, tx: &TxHash, block_hash: B256) {
+        self.record(tx, || TransactionLifecycleEvent::Mined { block_hash });
         self.broadcast_event(
             tx,
             TransactionEvent::Mined(block_hash),
@@ -71,13 +71,14 @@ use crate::{
     identifier::{SenderId, SenderIdentifiers, TransactionId},
     metrics::BlobStoreMetrics,
     pool::{
+        journal::{TransactionHistory, TransactionJournal},
         listener::{
             BlobTransactionSidecarListener, PendingTransactionHashListener, PoolEventBroadcast,
             TransactionListener,
         },
         state::SubPool,
         txpool::{SenderInfo, TxPool},
-        update::UpdateOutcome,
+        update::{DemotedTransaction, UpdateOutcome},
     },
     traits::{
         AllPoolTransactions, BestTransactionsAttributes, BlockInfo, GetPooledTransactionLimit,
@@ -116,11 +117,13 @@ pub use events::{FullTransactionEvent, NewTransactionEvent, TransactionEvent};
 pub use listener::{AllTransactionsEvents, TransactionEvents, TransactionListenerKind};
 pub use parked::{BasefeeOrd, ParkedOrd, ParkedPool, QueuedOrd};
 pub use pending::PendingPool;
+pub use reth_transaction_pool_types::{DiscardReason, QueuedReason};
 
 mod best;
 pub use best::BestTransactions;
 
 mod blob;
+pub mod journal;
 pub mod listener;
 mod parked;
 pub mod pending;
@@ -175,11 +178,15 @@ where
 {
     /// Create a new transaction pool instance.
     pub fn new(validator: V, ordering: T, blob_store: S, config: PoolConfig) -> Self {
+        let event_listener = config
+            .journal
+            .map(|journal| PoolEventBroadcast::with_journal(TransactionJournal::new(journal)))
+            .unwrap_or_default();
         Self {
             identifiers: Default::default(),
             validator,
-            event_listener: Default::default(),
-            has_event_listeners: AtomicBool::new(false),
+            has_event_listeners: AtomicBool::new(!event_listener.is_empty()),
+            event_listener: RwLock::new(event_listener),
             pool: RwLock::new(TxPool::new(ordering, config.clone())),
             pending_transaction_listener: Default::default(),
             transaction_listener: Default::default(),
@@ -207,12 +214,13 @@ where
     /// Sets the currently tracked block.
     ///
     /// This will also notify subscribers about any transactions that were promoted to the pending
-    /// pool due to fee changes.
+    /// pool or moved to a parked pool due to fee changes.
     pub fn set_block_info(&self, info: BlockInfo) {
         let outcome = self.pool.write().set_block_info(info);
 
         // Notify subscribers about promoted transactions due to fee changes
         self.notify_on_transaction_updates(outcome.promoted, outcome.discarded);
+        self.notify_on_demoted_transactions(outcome.demoted);
     }
 
     /// Returns the internal [`SenderId`] for this address, allocating a new mapping when the
@@ -327,6 +335,16 @@ where
         events
     }
 
+    /// Returns the journal of transaction lifecycle events, if enabled.
+    pub fn transaction_journal(&self) -> Option<TransactionJournal> {
+        self.event_listener.read().journal().cloned()
+    }
+
+    /// Returns the recorded lifecycle events of the transaction, if the journal is enabled.
+    pub fn transaction_history(&self, tx_hash: &TxHash) -> Option<TransactionHistory> {
+        self.event_listener.read().journal()?.history(tx_hash)
+    }
+
     #[inline]
     fn has_event_listeners(&self) -> bool {
         self.has_event_listeners.load(Ordering::Relaxed)
@@ -562,10 +580,11 @@ where
     /// This should be invoked when the pool drifted and accounts are updated manually
     pub fn update_accounts(&self, accounts: Vec<ChangedAccount>) {
         let changed_senders = self.changed_senders(accounts.into_iter());
-        let UpdateOutcome { promoted, discarded } =
+        let UpdateOutcome { promoted, discarded, demoted } =
             self.pool.write().update_accounts(changed_senders);
 
         self.notify_on_transaction_updates(promoted, discarded);
+        self.notify_on_demoted_transactions(demoted);
     }
 
     /// Add a single validated transaction into the pool.
@@ -612,6 +631,7 @@ where
                     timestamp: Instant::now(),
                     origin,
                     authority_ids: authorities.map(|auths| self.get_sender_ids(auths)),
+                    simulation: Default::default(),
                 };
 
                 let added = match pool.add_transaction(tx, balance, state_nonce, bytecode_hash) {
@@ -626,11 +646,13 @@ where
                 (Ok(AddedTransactionOutcome { hash, state }), Some(meta))
             }
             TransactionValidationOutcome::Invalid(tx, err) => {
-                self.with_event_listener(|listener| listener.invalid(tx.hash()));
+                self.with_event_listener(|listener| listener.invalid(tx.hash(), origin, &err));
                 (Err(PoolError::new(*tx.hash(), err)), None)
             }
             TransactionValidationOutcome::Error(tx_hash, err) => {
-                self.with_event_listener(|listener| listener.discarded(&tx_hash));
+                self.with_event_listener(|listener| {
+                    listener.discarded(&tx_hash, DiscardReason::ValidationError)
+                });
                 (Err(PoolError::other(tx_hash, err)), None)
             }
         }
@@ -712,16 +734,20 @@ where
 
         if !discarded.is_empty() {
             // Delete any blobs associated with discarded blob transactions
-            self.delete_discarded_blobs(discarded.iter());
-            self.with_event_listener(|listener| listener.discarded_many(&discarded));
+            self.delete_discarded_blobs(discarded.iter().map(|(tx, _)| tx));
+            self.with_event_listener(|listener| {
+                for (tx, reason) in &discarded {
+                    listener.discarded(tx.hash(), *reason);
+                }
+            });
 
             // Linear search avoids allocating a hash set for small eviction batches.
             const MAX_LINEAR_SEARCH_DISCARDS: usize = 4;
             let discarded_hashes = (discarded.len() > MAX_LINEAR_SEARCH_DISCARDS)
-                .then(|| discarded.iter().map(|tx| *tx.hash()).collect::<HashSet<_>>());
+                .then(|| discarded.iter().map(|(tx, _)| *tx.hash()).collect::<HashSet<_>>());
             let is_discarded = |hash: &TxHash| match &discarded_hashes {
                 Some(hashes) => hashes.contains(hash),
-                None => discarded.iter().any(|tx| tx.hash() == hash),
+                None => discarded.iter().any(|(tx, _)| tx.hash() == hash),
             };
 
             // A newly added transaction may be immediately discarded, so we need to
@@ -894,7 +920,8 @@ where
             self.transaction_listener.write().retain(|l| !l.sender.is_closed());
         }
 
-        let OnNewCanonicalStateOutcome { mined, promoted, discarded, block_hash } = outcome;
+        let OnNewCanonicalStateOutcome { mined, promoted, discarded, demoted, block_hash } =
+            outcome;
 
         // broadcast specific transaction events
         self.with_event_listener(|listener| {
@@ -905,11 +932,26 @@ where
                 listener.pending(tx.hash(), None);
             }
             for tx in &discarded {
-                listener.discarded(tx.hash());
+                listener.discarded(tx.hash(), DiscardReason::NonceTooLow);
+            }
+            for tx in demoted {
+                listener.queued(tx.transaction.hash(), tx.queued_reason);
             }
         })
     }
 
+    /// Notifies event listeners about transactions that were moved to a parked pool.
+    fn notify_on_demoted_transactions(&self, demoted: Vec<DemotedTransaction<T::Transaction>>) {
+        if demoted.is_empty() {
+            return
+        }
+        self.with_event_listener(|listener| {
+            for tx in demoted {
+                listener.queued(tx.transaction.hash(), tx.queued_reason);
+            }
+        });
+    }
+
     /// Notifies all listeners about the transaction movements.
     ///
     /// This will emit events according to the provided changes.
@@ -972,7 +1014,7 @@ where
                 listener.pending(tx.hash(), None);
             }
             for tx in &discarded {
-                listener.discarded(tx.hash());
+                listener.discarded(tx.hash(), DiscardReason::NonceTooLow);
             }
         });
 
@@ -1001,7 +1043,7 @@ where
                     listener.pending(tx.hash(), None);
                 }
                 for tx in discarded {
-                    listener.discarded(tx.hash());
+                    listener.discarded(tx.hash(), DiscardReason::NonceTooLow);
                 }
             }
             AddedTransaction::Parked { transaction, replaced, queued_reason, .. } => {
@@ -1067,13 +1109,25 @@ where
     pub fn remove_transactions(
         &self,
         hashes: Vec<TxHash>,
+    ) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.remove_transactions_with_reason(hashes, DiscardReason::Removed)
+    }
+
+    /// Removes and returns all matching transactions from the pool, reporting them as discarded
+    /// for the given reason.
+    ///
+    /// See also [`Self::remove_transactions`].
+    pub fn remove_transactions_with_reason(
+        &self,
+        hashes: Vec<TxHash>,
+        reason: DiscardReason,
     ) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
         if hashes.is_empty() {
             return Vec::new()
         }
         let removed = self.pool.write().remove_transactions(hashes);
 
-        self.with_event_listener(|listener| listener.discarded_many(&removed));
+        self.with_event_listener(|listener| listener.discarded_many(&removed, reason));
 
         removed
     }
@@ -1091,7 +1145,7 @@ where
 
         self.with_event_listener(|listener| {
             for tx in &removed {
-                listener.discarded(tx.hash());
+                listener.discarded(tx.hash(), DiscardReason::Removed);
             }
         });
 
@@ -1106,7 +1160,9 @@ where
         let Some(sender_id) = self.sender_id(&sender) else { return Vec::new() };
         let removed = self.pool.write().remove_transactions_by_sender(sender_id);
 
-        self.with_event_listener(|listener| listener.discarded_many(&removed));
+        self.with_event_listener(|listener| {
+            listener.discarded_many(&removed, DiscardReason::Removed)
+        });
 
         removed
     }
@@ -1555,23 +1611,6 @@ impl<T: PoolTransaction> AddedTransaction<T> {
     }
 }
 
-/// The specific reason why a transaction is queued (not ready for execution)
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum QueuedReason {
-    /// Transaction has a nonce gap - missing prior transactions
-    NonceGap,
-    /// Transaction has parked ancestors - waiting for other transactions to be mined
-    ParkedAncestors,
-    /// Sender has insufficient balance to cover the transaction cost
-    InsufficientBalance,
-    /// Transaction exceeds the block gas limit
-    TooMuchGas,
-    /// Transaction doesn't meet the base fee requirement
-    InsufficientBaseFee,
-    /// Transaction doesn't meet the blob fee requirement (EIP-4844)
-    InsufficientBlobFee,
-}
-
 /// The state of a transaction when is was added to the pool
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AddedTransactionState {
@@ -1633,6 +1672,8 @@ pub(crate) struct OnNewCanonicalStateOutcome<T: PoolTransaction> {
     pub(crate) promoted: Vec<Arc<ValidPoolTransaction<T>>>,
     /// transaction that were discarded during the update
     pub(crate) discarded: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// Transactions moved to a parked pool.
+    pub(crate) demoted: Vec<DemotedTransaction<T>>,
 }
 
 impl<T: PoolTransaction> OnNewCanonicalStateOutcome<T> {
@@ -14,8 +14,8 @@ use crate::{
         parked::{BasefeeOrd, ParkedPool, QueuedOrd},
         pending::PendingPool,
         state::{SubPool, TxState},
-        update::{Destination, PoolUpdate, UpdateOutcome},
-        AddedPendingTransaction, AddedTransaction, OnNewCanonicalStateOutcome,
+        update::{DemotedTransaction, Destination, PoolUpdate, UpdateOutcome},
+        AddedPendingTransaction, AddedTransaction, DiscardReason, OnNewCanonicalStateOutcome,
     },
     traits::{BestTransactionsAttributes, BlockInfo, PoolSize},
     PoolConfig, PoolResult, PoolTransaction, PoolUpdateKind, PriceBumpConfig, TransactionOrdering,
@@ -221,13 +221,15 @@ impl<T: TransactionOrdering> TxPool<T> {
     }
 
     /// Updates the tracked blob fee
-    fn update_blob_fee<F>(
+    fn update_blob_fee<F, D>(
         &mut self,
         mut pending_blob_fee: u128,
         base_fee_update: Ordering,
         mut on_promoted: F,
+        mut on_demoted: D,
     ) where
         F: FnMut(&Arc<ValidPoolTransaction<T::Transaction>>),
+        D: FnMut(DemotedTransaction<T::Transaction>),
     {
         std::mem::swap(&mut self.all_transactions.pending_fees.blob_fee, &mut pending_blob_fee);
         match (self.all_transactions.pending_fees.blob_fee.cmp(&pending_blob_fee), base_fee_update)
@@ -240,15 +242,20 @@ impl<T: TransactionOrdering> TxPool<T> {
                 let removed =
                     self.pending_pool.update_blob_fee(self.all_transactions.pending_fees.blob_fee);
                 for tx in removed {
-                    let to = {
+                    let (to, queued_reason) = {
                         let tx =
                             self.all_transactions.txs.get_mut(tx.id()).expect("tx exists in set");
 
                         // the blob fee is too high now, unset the blob fee cap block flag
                         tx.state.remove(TxState::ENOUGH_BLOB_FEE_CAP_BLOCK);
                         tx.subpool = tx.state.into();
-                        tx.subpool
+                        (tx.subpool, tx.state.determine_queued_reason(tx.subpool))
                     };
+                    on_demoted(DemotedTransaction {
+                        transaction: tx.clone(),
+                        subpool: to,
+                        queued_reason,
+                    });
                     self.add_transaction_to_subpool(to, tx);
                 }
             }
@@ -280,9 +287,15 @@ impl<T: TransactionOrdering> TxPool<T> {
     ///
     /// Depending on the change in direction of the basefee, this will promote or demote
     /// transactions from the basefee pool.
-    fn update_basefee<F>(&mut self, mut pending_basefee: u64, mut on_promoted: F) -> Ordering
+    fn update_basefee<F, D>(
+        &mut self,
+        mut pending_basefee: u64,
+        mut on_promoted: F,
+        mut on_demoted: D,
+    ) -> Ordering
     where
         F: FnMut(&Arc<ValidPoolTransaction<T::Transaction>>),
+        D: FnMut(DemotedTransaction<T::Transaction>),
     {
         std::mem::swap(&mut self.all_transactions.pending_fees.base_fee, &mut pending_basefee);
         match self.all_transactions.pending_fees.base_fee.cmp(&pending_basefee) {
@@ -295,13 +308,18 @@ impl<T: TransactionOrdering> TxPool<T> {
                 let removed =
                     self.pending_pool.update_base_fee(self.all_transactions.pending_fees.base_fee);
                 for tx in removed {
-                    let to = {
+                    let (to, queued_reason) = {
                         let tx =
                             self.all_transactions.txs.get_mut(tx.id()).expect("tx exists in set");
                         tx.state.remove(TxState::ENOUGH_FEE_CAP_BLOCK);
                         tx.subpool = tx.state.into();
-                        tx.subpool
+                        (tx.subpool, tx.state.determine_queued_reason(tx.subpool))
                     };
+                    on_demoted(DemotedTransaction {
+                        transaction: tx.clone(),
+                        subpool: to,
+                        queued_reason,
+                    });
                     self.add_transaction_to_subpool(to, tx);
                 }
 
@@ -357,18 +375,24 @@ impl<T: TransactionOrdering> TxPool<T> {
     ///
     /// This will also apply updates to the pool based on the new base fee and blob fee.
     ///
-    /// Returns the outcome containing any transactions that were promoted due to fee changes.
+    /// Returns the outcome containing any transactions that were promoted or demoted due to fee
+    /// changes.
     pub fn set_block_info(&mut self, info: BlockInfo) -> UpdateOutcome<T::Transaction> {
         let mut outcome = UpdateOutcome::default();
 
-        // first update the subpools based on the new values, collecting promoted transactions
-        let basefee_ordering = self.update_basefee(info.pending_basefee, |tx| {
-            outcome.promoted.push(tx.clone());
-        });
+        // first update the subpools based on the new values, collecting moved transactions
+        let basefee_ordering = self.update_basefee(
+            info.pending_basefee,
+            |tx| outcome.promoted.push(tx.clone()),
+            |tx| outcome.demoted.push(tx),
+        );
         if let Some(blob_fee) = info.pending_blob_fee {
-            self.update_blob_fee(blob_fee, basefee_ordering, |tx| {
-                outcome.promoted.push(tx.clone());
-            })
+            self.update_blob_fee(
+                blob_fee,
+                basefee_ordering,
+                |tx| outcome.promoted.push(tx.clone()),
+                |tx| outcome.demoted.push(tx),
+            )
         }
         // then update tracked values
         self.all_transactions.set_block_info(info);
@@ -604,7 +628,7 @@ impl<T: TransactionOrdering> TxPool<T> {
 
     /// Applies fee-based promotion updates based on the previous fees.
     ///
-    /// Records promoted transactions based on fee swings.
+    /// Records promoted and demoted transactions based on fee swings.
     ///
     /// Caution: This expects that the fees were previously already updated via
     /// [`Self::update_pending_fees_only`].
@@ -627,13 +651,18 @@ impl<T: TransactionOrdering> TxPool<T> {
         self.all_transactions.pending_fees.base_fee = prev_base_fee;
         self.all_transactions.pending_fees.blob_fee = prev_blob_fee;
 
-        let base_fee_ordering = self.update_basefee(new_base_fee, |tx| {
-            outcome.promoted.push(tx.clone());
-        });
+        let base_fee_ordering = self.update_basefee(
+            new_base_fee,
+            |tx| outcome.promoted.push(tx.clone()),
+            |tx| outcome.demoted.push(tx),
+        );
 
-        self.update_blob_fee(new_blob_fee, base_fee_ordering, |tx| {
-            outcome.promoted.push(tx.clone());
-        });
+        self.update_blob_fee(
+            new_blob_fee,
+            base_fee_ordering,
+            |tx| outcome.promoted.push(tx.clone()),
+            |tx| outcome.demoted.push(tx),
+        );
     }
 
     /// Updates the transactions for the changed senders.
@@ -704,6 +733,7 @@ impl<T: TransactionOrdering> TxPool<T> {
             mined: mined_transactions,
             promoted: outcome.promoted,
             discarded: outcome.discarded,
+            demoted: outcome.demoted,
         }
     }
 
@@ -767,14 +797,16 @@ impl<T: TransactionOrdering> TxPool<T> {
                 let (promoted, discarded) = match split {
                     // All updates are lower-nonce — promote them first, then add new tx
                     None => {
-                        let UpdateOutcome { promoted, discarded } = self.process_updates(updates);
+                        let UpdateOutcome { promoted, discarded, .. } =
+                            self.process_updates(updates);
                         self.add_new_transaction(transaction.clone(), replaced_tx.clone(), move_to);
                         (promoted, discarded)
                     }
                     // All updates are higher-nonce — add new tx first, then promote
                     Some(0) => {
                         self.add_new_transaction(transaction.clone(), replaced_tx.clone(), move_to);
-                        let UpdateOutcome { promoted, discarded } = self.process_updates(updates);
+                        let UpdateOutcome { promoted, discarded, .. } =
+                            self.process_updates(updates);
                         (promoted, discarded)
                     }
                     // Mixed — split and interleave
@@ -986,12 +1018,22 @@ impl<T: TransactionOrdering> TxPool<T> {
                 }
                 Destination::Pool(move_to) => {
                     debug_assert_ne!(&move_to, &current, "destination must be different");
-                    let moved = self.move_transaction(current, move_to, &id);
-                    if matches!(move_to, SubPool::Pending) &&
-                        let Some(tx) = moved
-                    {
+                    let Some(tx) = self.move_transaction(current, move_to, &id) else { continue };
+                    if move_to.is_pending() {
                         trace!(target: "txpool", hash=%tx.transaction.hash(), "Promoted transaction to pending");
                         outcome.promoted.push(tx);
+                    } else {
+                        let queued_reason = self
+                            .all_transactions
+                            .txs
+                            .get(&id)
+                            .and_then(|tx| tx.state.determine_queued_reason(move_to));
+                        trace!(target: "txpool", hash=%tx.transaction.hash(), pool=?move_to, "Demoted transaction");
+                        outcome.demoted.push(DemotedTransaction {
+                            transaction: tx,
+                            subpool: move_to,
+                            queued_reason,
+                        });
                     }
                 }
             }
@@ -1228,13 +1270,17 @@ impl<T: TransactionOrdering> TxPool<T> {
     /// If the current size exceeds the given bounds, the worst transactions are evicted from the
     /// pool and returned.
     ///
-    /// This returns all transactions that were removed from the entire pool.
-    pub(crate) fn discard_worst(&mut self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+    /// This returns all transactions that were removed from the entire pool, together with the
+    /// reason why they were removed.
+    pub(crate) fn discard_worst(
+        &mut self,
+    ) -> Vec<(Arc<ValidPoolTransaction<T::Transaction>>, DiscardReason)> {
         let mut removed = Vec::new();
+        let mut descendants = Vec::new();
 
         // Helper macro that discards the worst transactions for the pools
         macro_rules! discard_worst {
-            ($this:ident, $removed:ident, [$($limit:ident => ($pool:ident, $metric:ident)),* $(,)*]) => {
+            ($this:ident, $removed:ident, [$($limit:ident => ($pool:ident, $metric:ident, $reason:expr)),* $(,)*]) => {
                 $ (
                 while $this.$pool.exceeds(&$this.config.$limit)
                     {
@@ -1268,10 +1314,13 @@ impl<T: TransactionOrdering> TxPool<T> {
                             let id = *tx.id();
 
                             // keep track of removed transaction
-                            removed.push(tx);
+                            $removed.push((tx, $reason));
 
                             // 3. remove all its descendants from the entire pool
-                            $this.remove_descendants(&id, &mut $removed);
+                            $this.remove_descendants(&id, &mut descendants);
+                            $removed.extend(
+                                descendants.drain(..).map(|tx| (tx, DiscardReason::NonceGap)),
+                            );
                         }
                     }
 
@@ -1281,10 +1330,10 @@ impl<T: TransactionOrdering> TxPool<T> {
 
         discard_worst!(
             self, removed, [
-                pending_limit => (pending_pool, pending_transactions_evicted),
-                basefee_limit => (basefee_pool, basefee_transactions_evicted),
-                blob_limit    => (blob_pool, blob_transactions_evicted),
-                queued_limit  => (queued_pool, queued_transactions_evicted),
+                pending_limit => (pending_pool, pending_transactions_evicted, DiscardReason::SizeLimit),
+                basefee_limit => (basefee_pool, basefee_transactions_evicted, DiscardReason::FeeTooLow),
+                blob_limit    => (blob_pool, blob_transactions_evicted, DiscardReason::SizeLimit),
+                queued_limit  => (queued_pool, queued_transactions_evicted, DiscardReason::SizeLimit),
             ]
         );
 
@@ -2359,6 +2408,7 @@ impl SenderInfo {
 mod tests {
     use super::*;
     use crate::{
+        pool::QueuedReason,
         test_utils::{MockOrdering, MockTransaction, MockTransactionFactory, MockTransactionSet},
         traits::TransactionOrigin,
         SubPoolLimit,
@@ -3396,7 +3446,7 @@ mod tests {
 
         assert_eq!(pool.pending_pool.len(), 1);
 
-        pool.update_basefee((tx.max_fee_per_gas() + 1) as u64, |_| {});
+        pool.update_basefee((tx.max_fee_per_gas() + 1) as u64, |_| {}, |_| {});
 
         assert!(pool.pending_pool.is_empty());
         assert_eq!(pool.basefee_pool.len(), 1);
@@ -3427,6 +3477,51 @@ mod tests {
         assert_eq!(pool.all_transactions.txs.get(&id).unwrap().subpool, SubPool::BaseFee)
     }
 
+    #[test]
+    fn report_demoted_on_basefee_increase() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(MockOrdering::default(), Default::default());
+
+        let tx = MockTransaction::eip1559().inc_price_by(10);
+        let validated = f.validated(tx.clone());
+        pool.add_transaction(validated, U256::from(1_000), 0, None).unwrap();
+
+        let mut block_info = pool.block_info();
+        block_info.pending_basefee = (tx.max_fee_per_gas() + 1) as u64;
+        let outcome = pool.set_block_info(block_info);
+
+        assert!(outcome.promoted.is_empty());
+        assert_eq!(outcome.demoted.len(), 1);
+        let demoted = &outcome.demoted[0];
+        assert_eq!(*demoted.transaction.hash(), *tx.hash());
+        assert_eq!(demoted.subpool, SubPool::BaseFee);
+        assert_eq!(demoted.queued_reason, Some(QueuedReason::InsufficientBaseFee));
+    }
+
+    #[test]
+    fn report_demoted_on_balance_drop() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(MockOrdering::default(), Default::default());
+
+        let tx = MockTransaction::eip1559().inc_price_by(10);
+        let validated = f.validated(tx.clone());
+        let id = *validated.id();
+        pool.add_transaction(validated, U256::from(1_000), 0, None).unwrap();
+        assert_eq!(pool.pending_pool.len(), 1);
+
+        let mut changed_senders = HashMap::default();
+        changed_senders.insert(id.sender, SenderInfo { state_nonce: 0, balance: U256::ZERO });
+        let outcome = pool.update_accounts(changed_senders);
+
+        assert!(outcome.discarded.is_empty());
+        assert_eq!(outcome.demoted.len(), 1);
+        let demoted = &outcome.demoted[0];
+        assert_eq!(*demoted.transaction.hash(), *tx.hash());
+        assert_eq!(demoted.subpool, SubPool::Queued);
+        assert_eq!(demoted.queued_reason, Some(QueuedReason::InsufficientBalance));
+        assert_eq!(pool.queued_pool.len(), 1);
+    }
+
     #[test]
     fn basefee_decrease_promotes_affordable_and_keeps_unaffordable() {
         use alloy_primitives::address;
@@ -3534,7 +3629,7 @@ mod tests {
         assert_eq!(pool.pending_pool.len(), 1);
 
         // Raise base fee beyond the transaction's cap so it gets parked in BaseFee pool.
-        pool.update_basefee(600, |_| {});
+        pool.update_basefee(600, |_| {}, |_| {});
         assert!(pool.pending_pool.is_empty());
         assert_eq!(pool.basefee_pool.len(), 1);
 
@@ -3575,7 +3670,7 @@ mod tests {
 
         // Raise blob fee beyond the transaction's cap so it gets parked in Blob pool.
         let increased_blob_fee = tx.max_fee_per_blob_gas().unwrap() + 200;
-        pool.update_blob_fee(increased_blob_fee, Ordering::Equal, |_| {});
+        pool.update_blob_fee(increased_blob_fee, Ordering::Equal, |_| {}, |_| {});
         assert!(pool.pending_pool.is_empty());
         assert_eq!(pool.blob_pool.len(), 1);
 
@@ -3620,7 +3715,7 @@ mod tests {
 
         // Raise base fee beyond the transaction's cap so it gets parked in Blob pool.
         let high_base_fee = 600;
-        pool.update_basefee(high_base_fee, |_| {});
+        pool.update_basefee(high_base_fee, |_| {}, |_| {});
         assert!(pool.pending_pool.is_empty());
         assert_eq!(pool.blob_pool.len(), 1);
 
@@ -3839,7 +3934,7 @@ mod tests {
 
         // set the base fee of the pool
         let pool_base_fee = 100;
-        pool.update_basefee(pool_base_fee, |_| {});
+        pool.update_basefee(pool_base_fee, |_| {}, |_| {});
 
         // 2 txs, that should put the pool over the size limit but not max txs
         let a_txs = MockTransactionSet::dependent(a_sender, 0, 3, TxType::Eip1559)
@@ -4626,7 +4721,7 @@ mod tests {
             .inc_limit();
 
         // Set high basefee so transaction goes to BaseFee pool initially
-        pool.update_basefee(600, |_| {});
+        pool.update_basefee(600, |_| {}, |_| {});
 
         let validated = f.validated(non_4844_tx);
         let tx_id = *validated.id();
@@ -4642,7 +4737,7 @@ mod tests {
 
         // Decrease basefee - transaction should be promoted to Pending
         // This is where PR #18215 bug would manifest: blob fee bit incorrectly removed
-        pool.update_basefee(400, |_| {});
+        pool.update_basefee(400, |_| {}, |_| {});
 
         // After basefee decrease: should be promoted to Pending with blob fee bit preserved
         let tx_meta = pool.all_transactions.txs.get(&tx_id).unwrap();
@@ -1,7 +1,9 @@
 //! Support types for updating the pool.
 
 use crate::{
-    identifier::TransactionId, pool::state::SubPool, PoolTransaction, ValidPoolTransaction,
+    identifier::TransactionId,
+    pool::{state::SubPool, QueuedReason},
+    PoolTransaction, ValidPoolTransaction,
 };
 use std::sync::Arc;
 
@@ -40,10 +42,24 @@ pub struct UpdateOutcome<T: PoolTransaction> {
     pub promoted: Vec<Arc<ValidPoolTransaction<T>>>,
     /// transaction that failed and were discarded
     pub discarded: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// transactions moved to a parked pool
+    pub demoted: Vec<DemotedTransaction<T>>,
 }
 
 impl<T: PoolTransaction> Default for UpdateOutcome<T> {
     fn default() -> Self {
-        Self { promoted: vec![], discarded: vec![] }
+        Self { promoted: vec![], discarded: vec![], demoted: vec![] }
     }
 }
+
+/// A transaction that was moved to a parked pool, e.g. because the base fee increased or the
+/// sender's balance dropped.
+#[derive(Debug)]
+pub struct DemotedTransaction<T: PoolTransaction> {
+    /// The moved transaction.
+    pub transaction: Arc<ValidPoolTransaction<T>>,
+    /// The parked pool the transaction was moved to.
+    pub subpool: SubPool,
+    /// Why the transaction is no longer executable.
+    pub queued_reason: Option<QueuedReason>,
+}
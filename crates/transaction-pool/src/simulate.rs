@@ -0,0 +1,501 @@
+//! Speculative pre-execution of pending transactions.
+//!
+//! The simulator executes the best pending transactions on top of the latest block whenever the
+//! canonical chain advances and annotates each [`ValidPoolTransaction`] with the
+//! [`SimulationOutcome`], see [`ValidPoolTransaction::simulation`].
+//!
+//! Transactions that revert in consecutive simulations are evicted from the pool, see
+//! [`SimulationConfig::max_consecutive_reverts`]. Reverting transactions are not demoted within
+//! the pool, payload builders can skip them instead, see [`reverted_in_simulation`].
+
+use crate::{
+    error::{InvalidPoolTransactionError, PoolTransactionError},
+    metrics::SimulationMetrics,
+    traits::{BestTransactions, BestTransactionsAttributes, PoolTransaction, TransactionPoolExt},
+    ValidPoolTransaction,
+};
+use alloy_consensus::{BlockHeader, Transaction};
+use alloy_primitives::{TxHash, B256};
+use futures_util::{future::BoxFuture, FutureExt, Stream, StreamExt};
+use parking_lot::RwLock;
+use reth_chain_state::CanonStateNotification;
+use reth_evm::{
+    block::TxResult,
+    execute::{BlockBuilder, BlockExecutionError, BlockValidationError},
+    ConfigureEvm, Evm as _, NextBlockEnvAttributes,
+};
+use reth_primitives_traits::{NodePrimitives, SealedHeader};
+use reth_revm::{database::StateProviderDatabase, db::State};
+use reth_storage_api::{errors::provider::ProviderError, HeaderProvider, StateProviderFactory};
+use reth_tasks::Runtime;
+use revm::context_interface::Block as _;
+use std::any::Any;
+use tokio::sync::oneshot;
+use tracing::{debug, trace, warn};
+
+pub use reth_transaction_pool_types::SimulationOutcome;
+
+/// Default number of pending transactions simulated per block.
+pub const DEFAULT_SIMULATION_MAX_TRANSACTIONS: usize = 256;
+
+/// Default number of consecutive simulations a transaction may revert in before it is evicted.
+pub const DEFAULT_SIMULATION_MAX_CONSECUTIVE_REVERTS: u32 = 3;
+
+/// Block time assumed for the next block if it can't be derived from the latest blocks.
+const DEFAULT_BLOCK_TIME: u64 = 12;
+
+/// Settings for the pending transaction simulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationConfig {
+    /// Maximum number of the best pending transactions simulated per block.
+    pub max_transactions: usize,
+    /// Number of consecutive simulations a non local transaction may revert in before it is
+    /// evicted from the pool.
+    ///
+    /// Blocks that arrive while a block is simulated are skipped, so this counts simulations
+    /// rather than blocks.
+    ///
+    /// `None` disables the eviction.
+    pub max_consecutive_reverts: Option<u32>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            max_transactions: DEFAULT_SIMULATION_MAX_TRANSACTIONS,
+            max_consecutive_reverts: Some(DEFAULT_SIMULATION_MAX_CONSECUTIVE_REVERTS),
+        }
+    }
+}
+
+/// Holds the latest [`SimulationOutcome`] of a [`ValidPoolTransaction`].
+#[derive(Debug, Default)]
+pub struct TransactionSimulation(RwLock<Option<SimulationOutcome>>);
+
+impl TransactionSimulation {
+    /// Returns the latest outcome, if the transaction was simulated.
+    pub fn get(&self) -> Option<SimulationOutcome> {
+        *self.0.read()
+    }
+
+    /// Records the outcome of a simulation in the given block and returns it.
+    ///
+    /// Every simulation in a later block counts as another revert, even if blocks in between were
+    /// skipped. Simulating the transaction again in the same block replaces the previous outcome
+    /// without counting another revert.
+    pub fn record(
+        &self,
+        block_number: u64,
+        gas_used: u64,
+        reverted: bool,
+        effective_tip: u128,
+    ) -> SimulationOutcome {
+        let mut latest = self.0.write();
+        let consecutive_reverts = match *latest {
+            _ if !reverted => 0,
+            Some(prev) if prev.block_number == block_number => prev.consecutive_reverts.max(1),
+            Some(prev) if prev.block_number < block_number => prev.consecutive_reverts + 1,
+            _ => 1,
+        };
+        let outcome = SimulationOutcome {
+            block_number,
+            gas_used,
+            reverted,
+            effective_tip,
+            consecutive_reverts,
+        };
+        *latest = Some(outcome);
+        outcome
+    }
+}
+
+impl Clone for TransactionSimulation {
+    fn clone(&self) -> Self {
+        Self(RwLock::new(self.get()))
+    }
+}
+
+/// Marks a transaction that reverted in its latest simulation.
+///
+/// This is not a bad transaction, reverting transactions are valid, but a payload builder may
+/// choose to not include them.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("transaction reverted in simulation")]
+pub struct SimulatedRevertError;
+
+impl PoolTransactionError for SimulatedRevertError {
+    fn is_bad_transaction(&self) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Marks a transaction that could not be executed in the simulated block, e.g. because its nonce
+/// was already used by a transaction of the latest block.
+///
+/// The transaction is skipped for the remainder of the simulation, it's not removed from the pool.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("transaction failed to execute in simulation: {0}")]
+pub struct SimulationExecutionError(String);
+
+impl PoolTransactionError for SimulationExecutionError {
+    fn is_bad_transaction(&self) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Returns `true` if the transaction reverted when it was simulated in the block with the given
+/// number.
+///
+/// Outcomes of other blocks are ignored, the state the transaction reverted on may have changed
+/// since.
+pub fn reverted_in_simulation<T: PoolTransaction>(
+    tx: &ValidPoolTransaction<T>,
+    block_number: u64,
+) -> bool {
+    tx.simulation().is_some_and(|outcome| outcome.reverted && outcome.block_number == block_number)
+}
+
+/// Errors that abort the simulation of a block.
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+    /// Failed to access the state of the latest block.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// Failed to configure the EVM for the block.
+    #[error("failed to configure evm: {0}")]
+    EvmEnv(Box<dyn core::error::Error + Send + Sync>),
+    /// Failed to execute the block.
+    #[error(transparent)]
+    Execution(#[from] BlockExecutionError),
+}
+
+/// Summary of the simulation of a block.
+#[derive(Debug, Default)]
+struct SimulationReport {
+    /// Number of simulated transactions.
+    simulated: usize,
+    /// Number of reverted transactions.
+    reverted: usize,
+    /// Transactions that exceeded the allowed number of consecutive reverts.
+    evict: Vec<TxHash>,
+}
+
+/// Returns a spawnable future that simulates the best pending transactions on every new block.
+pub fn simulate_pending_transactions_future<N, Client, P, EvmConfig, St>(
+    client: Client,
+    pool: P,
+    evm_config: EvmConfig,
+    events: St,
+    task_spawner: Runtime,
+    config: SimulationConfig,
+) -> BoxFuture<'static, ()>
+where
+    N: NodePrimitives,
+    Client: StateProviderFactory + HeaderProvider<Header = N::BlockHeader> + Clone + 'static,
+    P: TransactionPoolExt<Transaction: PoolTransaction<Consensus = N::SignedTx>> + 'static,
+    EvmConfig: ConfigureEvm<Primitives = N, NextBlockEnvCtx = NextBlockEnvAttributes> + 'static,
+    St: Stream<Item = CanonStateNotification<N>> + Send + Unpin + 'static,
+{
+    async move {
+        simulate_pending_transactions(client, pool, evm_config, events, task_spawner, config).await;
+    }
+    .boxed()
+}
+
+/// Simulates the best pending transactions on top of every new canonical block.
+///
+/// If new blocks arrive while a block is simulated, only the latest of them is simulated next.
+pub async fn simulate_pending_transactions<N, Client, P, EvmConfig, St>(
+    client: Client,
+    pool: P,
+    evm_config: EvmConfig,
+    mut events: St,
+    task_spawner: Runtime,
+    config: SimulationConfig,
+) where
+    N: NodePrimitives,
+    Client: StateProviderFactory + HeaderProvider<Header = N::BlockHeader> + Clone + 'static,
+    P: TransactionPoolExt<Transaction: PoolTransaction<Consensus = N::SignedTx>> + 'static,
+    EvmConfig: ConfigureEvm<Primitives = N, NextBlockEnvCtx = NextBlockEnvAttributes> + 'static,
+    St: Stream<Item = CanonStateNotification<N>> + Send + Unpin + 'static,
+{
+    let metrics = SimulationMetrics::default();
+
+    while let Some(mut event) = events.next().await {
+        // skip to the latest block
+        while let Some(Some(next)) = events.next().now_or_never() {
+            event = next;
+        }
+        let tip = event.tip().clone_sealed_header();
+
+        let (tx, rx) = oneshot::channel();
+        let (c, p, evm) = (client.clone(), pool.clone(), evm_config.clone());
+        task_spawner.spawn_blocking_task(async move {
+            let _ = tx.send(simulate_best_transactions(&c, &p, &evm, &tip, config));
+        });
+
+        let report = match rx.await {
+            Ok(Ok(report)) => report,
+            Ok(Err(err)) => {
+                warn!(target: "txpool", %err, "Failed to simulate pending transactions");
+                continue
+            }
+            // the runtime is shutting down
+            Err(_) => return,
+        };
+
+        metrics.simulated_transactions.increment(report.simulated as u64);
+        metrics.reverted_transactions.increment(report.reverted as u64);
+
+        if !report.evict.is_empty() {
+            let evicted = pool.remove_reverting_transactions(report.evict);
+            metrics.evicted_transactions.increment(evicted.len() as u64);
+            debug!(target: "txpool", evicted = evicted.len(), "Evicted reverting transactions");
+        }
+    }
+}
+
+/// Executes the best pending transactions in a block on top of `tip` and records the outcomes.
+fn simulate_best_transactions<N, Client, P, EvmConfig>(
+    client: &Client,
+    pool: &P,
+    evm_config: &EvmConfig,
+    tip: &SealedHeader<N::BlockHeader>,
+    config: SimulationConfig,
+) -> Result<SimulationReport, SimulationError>
+where
+    N: NodePrimitives,
+    Client: StateProviderFactory + HeaderProvider<Header = N::BlockHeader>,
+    P: TransactionPoolExt<Transaction: PoolTransaction<Consensus = N::SignedTx>>,
+    EvmConfig: ConfigureEvm<Primitives = N, NextBlockEnvCtx = NextBlockEnvAttributes>,
+{
+    let state = client.state_by_block_hash(tip.hash())?;
+
+    // assume the next block follows the tip at the same interval as the tip followed its parent
+    let block_time = client
+        .header(tip.parent_hash())?
+        .map(|parent| tip.timestamp().saturating_sub(parent.timestamp()))
+        .filter(|block_time| *block_time > 0)
+        .unwrap_or(DEFAULT_BLOCK_TIME);
+    let attributes = NextBlockEnvAttributes {
+        timestamp: tip.timestamp().saturating_add(block_time),
+        suggested_fee_recipient: tip.beneficiary(),
+        prev_randao: tip.mix_hash().unwrap_or_default(),
+        gas_limit: tip.gas_limit(),
+        parent_beacon_block_root: tip.parent_beacon_block_root().map(|_| B256::ZERO),
+        withdrawals: tip.withdrawals_root().map(|_| Default::default()),
+        extra_data: tip.extra_data().clone(),
+        slot_number: tip.slot_number().map(|slot| slot.saturating_add(1)),
+    };
+    let block_number = tip.number() + 1;
+
+    let mut db = State::builder().with_database(StateProviderDatabase::new(state)).build();
+    let mut builder = evm_config
+        .builder_for_next_block(&mut db, tip, attributes)
+        .map_err(|err| SimulationError::EvmEnv(Box::new(err)))?;
+    builder.apply_pre_execution_changes()?;
+
+    let base_fee = builder.evm_mut().block().basefee();
+    let blob_fee = builder.evm_mut().block().blob_gasprice().map(|gasprice| gasprice as u64);
+
+    let mut best_txs =
+        pool.best_transactions_with_attributes(BestTransactionsAttributes::new(base_fee, blob_fee));
+    // the blob space of the block isn't tracked here
+    best_txs.skip_blobs();
+
+    let mut report = SimulationReport::default();
+
+    while report.simulated < config.max_transactions &&
+        let Some(pool_tx) = best_txs.next()
+    {
+        let tx = pool_tx.to_consensus();
+        let mut reverted = false;
+        // descendants are executed on top of the transaction
+        let gas_output = match builder.execute_transaction_with_result_closure(tx, |result| {
+            reverted = !result.result().result.is_success();
+        }) {
+            Ok(gas_output) => gas_output,
+            Err(BlockExecutionError::Validation(BlockValidationError::InvalidTx {
+                error, ..
+            })) => {
+                // the transaction can't be executed on top of the latest block, e.g. because it
+                // was just mined, skip it and all of its descendants
+                trace!(target: "txpool", %error, tx_hash=?pool_tx.hash(), "skipping invalid transaction");
+                best_txs.mark_invalid(
+                    &pool_tx,
+                    InvalidPoolTransactionError::other(SimulationExecutionError(error.to_string())),
+                );
+                continue
+            }
+            Err(BlockExecutionError::Validation(
+                BlockValidationError::TransactionGasLimitMoreThanAvailableBlockGas {
+                    transaction_gas_limit,
+                    block_available_gas,
+                },
+            )) => {
+                best_txs.mark_invalid(
+                    &pool_tx,
+                    InvalidPoolTransactionError::ExceedsGasLimit(
+                        transaction_gas_limit,
+                        block_available_gas,
+                    ),
+                );
+                continue
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let gas_used = gas_output.tx_gas_used();
+        let effective_tip = pool_tx.transaction.effective_tip_per_gas(base_fee).unwrap_or_default();
+        let outcome = pool_tx.simulation.record(block_number, gas_used, reverted, effective_tip);
+
+        report.simulated += 1;
+        if reverted {
+            report.reverted += 1;
+            trace!(target: "txpool", tx_hash=?pool_tx.hash(), consecutive_reverts=outcome.consecutive_reverts, "transaction reverted");
+
+            if !pool_tx.origin.is_local() &&
+                config
+                    .max_consecutive_reverts
+                    .is_some_and(|max| outcome.consecutive_reverts >= max)
+            {
+                report.evict.push(*pool_tx.hash());
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{testing_pool, MockTransaction},
+        TransactionPool,
+    };
+    use alloy_consensus::Header;
+    use alloy_primitives::{bytes, U256};
+    use reth_chainspec::{ChainSpecBuilder, MIN_TRANSACTION_GAS};
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use std::sync::Arc;
+
+    fn header(number: u64) -> SealedHeader {
+        SealedHeader::seal_slow(Header {
+            number,
+            timestamp: number * 12,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn simulate_reverting_transaction() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().london_activated().build());
+        let provider = MockEthProvider::default().with_chain_spec(chain_spec.clone());
+        let evm_config = EthEvmConfig::new(chain_spec);
+        let pool = testing_pool();
+        let config = SimulationConfig { max_transactions: 10, max_consecutive_reverts: Some(2) };
+
+        // calls a contract that always reverts
+        let reverting = MockTransaction::eip1559().with_gas_limit(100_000);
+        let contract = reverting.to().unwrap();
+        provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(bytes!("60006000fd")),
+        );
+        let transfer = MockTransaction::eip1559().with_gas_limit(MIN_TRANSACTION_GAS);
+        for tx in [&reverting, &transfer] {
+            provider.add_account(tx.sender(), ExtendedAccount::new(0, U256::from(u64::MAX)));
+            pool.add_external_transaction(tx.clone()).await.unwrap();
+        }
+
+        let report =
+            simulate_best_transactions(&provider, &pool, &evm_config, &header(1), config).unwrap();
+        assert_eq!((report.simulated, report.reverted), (2, 1));
+        assert!(report.evict.is_empty());
+
+        let reverting_tx = pool.get(reverting.hash()).unwrap();
+        let outcome = reverting_tx.simulation().unwrap();
+        assert!(outcome.reverted);
+        assert_eq!(outcome.block_number, 2);
+        assert_eq!(outcome.consecutive_reverts, 1);
+        assert!(reverted_in_simulation(&reverting_tx, 2));
+        // the outcome is not trusted for other blocks
+        assert!(!reverted_in_simulation(&reverting_tx, 3));
+
+        let transfer_tx = pool.get(transfer.hash()).unwrap();
+        let outcome = transfer_tx.simulation().unwrap();
+        assert!(!outcome.reverted);
+        assert_eq!(outcome.gas_used, MIN_TRANSACTION_GAS);
+        assert!(!reverted_in_simulation(&transfer_tx, 2));
+
+        // reverting again in the next simulation exceeds the allowed number of consecutive reverts
+        let report =
+            simulate_best_transactions(&provider, &pool, &evm_config, &header(2), config).unwrap();
+        assert_eq!(report.evict, vec![*reverting.hash()]);
+        assert_eq!(reverting_tx.simulation().unwrap().consecutive_reverts, 2);
+
+        pool.remove_reverting_transactions(report.evict);
+        assert!(!pool.contains(reverting.hash()));
+        assert!(pool.contains(transfer.hash()));
+    }
+
+    #[test]
+    fn record_consecutive_reverts() {
+        let simulation = TransactionSimulation::default();
+        assert!(simulation.get().is_none());
+
+        assert_eq!(simulation.record(10, 21_000, true, 1).consecutive_reverts, 1);
+        // simulating the same block again doesn't count as another revert
+        assert_eq!(simulation.record(10, 21_000, true, 1).consecutive_reverts, 1);
+        assert_eq!(simulation.record(11, 21_000, true, 1).consecutive_reverts, 2);
+        // skipped blocks still count as consecutive simulations
+        assert_eq!(simulation.record(13, 21_000, true, 1).consecutive_reverts, 3);
+        assert_eq!(simulation.record(14, 50_000, false, 2).consecutive_reverts, 0);
+
+        let outcome = simulation.get().unwrap();
+        assert_eq!(
+            outcome,
+            SimulationOutcome {
+                block_number: 14,
+                gas_used: 50_000,
+                reverted: false,
+                effective_tip: 2,
+                consecutive_reverts: 0,
+            }
+        );
+        assert_eq!(simulation.clone().get(), Some(outcome));
+    }
+
+    #[test]
+    fn outcome_json() {
+        let outcome = SimulationOutcome {
+            block_number: 1,
+            gas_used: 21_000,
+            reverted: true,
+            effective_tip: 2,
+            consecutive_reverts: 1,
+        };
+        assert_eq!(
+            serde_json::to_value(outcome).unwrap(),
+            serde_json::json!({
+                "blockNumber": 1,
+                "gasUsed": 21000,
+                "reverted": true,
+                "effectiveTip": 2,
+                "consecutiveReverts": 1,
+            })
+        );
+    }
+}
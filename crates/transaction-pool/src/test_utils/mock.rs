@@ -1394,6 +1394,7 @@ impl MockTransactionFactory {
             timestamp: Instant::now(),
             origin,
             authority_ids: None,
+            simulation: Default::default(),
         }
     }
 
@@ -54,8 +54,9 @@ use crate::{
     blobstore::{BlobCellAvailability, BlobStore, BlobStoreError, PooledBlobSidecar},
     error::{InvalidPoolTransactionError, PoolError, PoolResult, RawPoolTransactionError},
     pool::{
-        state::SubPool, BestTransactionFilter, NewTransactionEvent, TransactionEvents,
-        TransactionListenerKind,
+        journal::{TransactionHistory, TransactionJournal},
+        state::SubPool,
+        BestTransactionFilter, NewTransactionEvent, TransactionEvents, TransactionListenerKind,
     },
     validate::{TransactionValidationOutcome, TransactionValidator, ValidPoolTransaction},
     AddedTransactionOutcome, AllTransactionsEvents,
@@ -236,6 +237,15 @@ pub trait TransactionPool: Clone + Debug + Send + Sync {
     /// Returns a new transaction change event stream for _all_ transactions in the pool.
     fn all_transactions_event_listener(&self) -> AllTransactionsEvents<Self::Transaction>;
 
+    /// Returns the recorded lifecycle events of the transaction, including events after it left
+    /// the pool.
+    ///
+    /// Returns `None` if the transaction is unknown or the journal is disabled, see
+    /// [`PoolConfig::journal`](crate::PoolConfig::journal).
+    fn transaction_history(&self, _tx_hash: TxHash) -> Option<TransactionHistory> {
+        None
+    }
+
     /// Returns a new Stream that yields transactions hashes for new __pending__ transactions
     /// inserted into the pool that are allowed to be propagated.
     ///
@@ -797,6 +807,39 @@ pub trait TransactionPoolExt: TransactionPool {
 
     /// Maintenance function to cleanup blobs that are no longer needed.
     fn cleanup_blobs(&self);
+
+    /// Removes all transactions corresponding to the given hashes because they exceeded their
+    /// lifetime in the pool.
+    ///
+    /// Same as [`TransactionPool::remove_transactions`], but the transactions are reported as
+    /// discarded with [`DiscardReason::Lifetime`](crate::DiscardReason::Lifetime).
+    ///
+    /// By default, this delegates to [`TransactionPool::remove_transactions`].
+    fn remove_stale_transactions(
+        &self,
+        hashes: Vec<TxHash>,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.remove_transactions(hashes)
+    }
+
+    /// Removes all transactions corresponding to the given hashes because they kept reverting in
+    /// the speculative execution, see [`crate::simulate`].
+    ///
+    /// Same as [`TransactionPool::remove_transactions`], but the transactions are reported as
+    /// discarded with [`DiscardReason::Reverted`](crate::DiscardReason::Reverted).
+    ///
+    /// By default, this delegates to [`TransactionPool::remove_transactions`].
+    fn remove_reverting_transactions(
+        &self,
+        hashes: Vec<TxHash>,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.remove_transactions(hashes)
+    }
+
+    /// Returns the journal of transaction lifecycle events, if enabled.
+    fn transaction_journal(&self) -> Option<TransactionJournal> {
+        None
+    }
 }
 
 /// Extension for [`TransactionPool`] that exposes the pool's underlying [`TransactionValidator`].
@@ -987,6 +1030,11 @@ pub enum TransactionOrigin {
     /// This type of transaction should not be propagated to the network. It's meant for
     /// private usage within the local node only.
     Private,
+    /// Transaction has been received from a trusted peer via the private relay protocol.
+    ///
+    /// Like [`TransactionOrigin::Private`] transactions, these must never be announced on the
+    /// public network.
+    PrivateRelay,
 }
 
 // === impl TransactionOrigin ===
@@ -1005,6 +1053,11 @@ impl TransactionOrigin {
     pub const fn is_private(&self) -> bool {
         matches!(self, Self::Private)
     }
+
+    /// Whether the transaction has been received via the private relay protocol.
+    pub const fn is_private_relay(&self) -> bool {
+        matches!(self, Self::PrivateRelay)
+    }
 }
 
 /// Represents the kind of update to the canonical state.
@@ -713,7 +713,7 @@ where
                 TransactionOrigin::Local => {
                     self.local_transactions_config.propagate_local_transactions
                 }
-                TransactionOrigin::Private => false,
+                TransactionOrigin::Private | TransactionOrigin::PrivateRelay => false,
             },
             authorities,
         }
@@ -2060,6 +2060,15 @@ mod tests {
         assert!(outcome.is_invalid()); // Still invalid because sender not in whitelist
     }
 
+    #[tokio::test]
+    async fn private_relay_transactions_are_not_propagated() {
+        let (transaction, provider) = setup_priority_fee_test();
+        let validator = create_validator_with_minimum_fee(provider, None, None);
+
+        let outcome = validator.validate_one(TransactionOrigin::PrivateRelay, transaction);
+        assert!(matches!(outcome, TransactionValidationOutcome::Valid { propagate: false, .. }));
+    }
+
     #[test]
     fn reject_oversized_tx() {
         let mut transaction = get_transaction();
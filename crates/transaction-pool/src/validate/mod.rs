@@ -4,6 +4,7 @@ use crate::{
     blobstore::PooledBlobSidecar,
     error::InvalidPoolTransactionError,
     identifier::{SenderId, TransactionId},
+    simulate::{SimulationOutcome, TransactionSimulation},
     traits::{PoolTransaction, TransactionOrigin},
     PriceBumpConfig,
 };
@@ -308,6 +309,8 @@ pub struct ValidPoolTransaction<T: PoolTransaction> {
     pub origin: TransactionOrigin,
     /// The sender ids of the 7702 transaction authorities.
     pub authority_ids: Option<Vec<SenderId>>,
+    /// The outcome of the latest speculative execution, see [`crate::simulate`].
+    pub simulation: TransactionSimulation,
 }
 
 // === impl ValidPoolTransaction ===
@@ -323,6 +326,12 @@ impl<T: PoolTransaction> ValidPoolTransaction<T> {
         self.transaction.ty()
     }
 
+    /// Returns the outcome of the latest speculative execution of the transaction, if it was
+    /// simulated.
+    pub fn simulation(&self) -> Option<SimulationOutcome> {
+        self.simulation.get()
+    }
+
     /// Returns the address of the sender
     pub fn sender(&self) -> Address {
         self.transaction.sender()
@@ -514,6 +523,7 @@ impl<T: PoolTransaction> Clone for ValidPoolTransaction<T> {
             timestamp: self.timestamp,
             origin: self.origin,
             authority_ids: self.authority_ids.clone(),
+            simulation: self.simulation.clone(),
         }
     }
 }
@@ -2,8 +2,8 @@ use assert_matches::assert_matches;
 use reth_transaction_pool::{
     noop::MockTransactionValidator,
     test_utils::{MockTransactionFactory, TestPoolBuilder},
-    FullTransactionEvent, PoolTransaction, TransactionEvent, TransactionListenerKind,
-    TransactionOrigin, TransactionPool,
+    DiscardReason, FullTransactionEvent, PoolConfig, PoolTransaction, TransactionEvent,
+    TransactionLifecycleEvent, TransactionListenerKind, TransactionOrigin, TransactionPool,
 };
 use std::{future::poll_fn, task::Poll};
 use tokio_stream::StreamExt;
@@ -25,7 +25,31 @@ async fn txpool_listener_by_hash() {
     let removed_txs = txpool.remove_transactions(vec![*transaction.transaction.hash()]);
     assert_eq!(transaction.transaction.hash(), removed_txs[0].transaction.hash());
 
-    assert_matches!(events.next().await, Some(TransactionEvent::Discarded));
+    assert_matches!(events.next().await, Some(TransactionEvent::Discarded(DiscardReason::Removed)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn txpool_transaction_history() {
+    let txpool = TestPoolBuilder::default()
+        .with_config(PoolConfig::default().with_journal(Default::default()));
+    let mut mock_tx_factory = MockTransactionFactory::default();
+    let transaction = mock_tx_factory.create_eip1559();
+    let hash = *transaction.transaction.get_hash();
+
+    assert!(txpool.transaction_history(hash).is_none());
+
+    txpool.add_transaction(TransactionOrigin::External, transaction.transaction).await.unwrap();
+    txpool.remove_transactions(vec![hash]);
+
+    // the history is kept after the transaction left the pool
+    let history = txpool.transaction_history(hash).unwrap();
+    assert_eq!(
+        history.events.into_iter().map(|entry| entry.event).collect::<Vec<_>>(),
+        [
+            TransactionLifecycleEvent::Pending,
+            TransactionLifecycleEvent::Discarded { reason: DiscardReason::Removed },
+        ]
+    );
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -170,7 +194,7 @@ async fn txpool_listener_all() {
 
     assert_eq!(transaction.transaction.hash(), removed_txs[0].transaction.hash());
 
-    assert_matches!(all_tx_events.next().await, Some(FullTransactionEvent::Discarded(hash)) if hash == *transaction.transaction.get_hash());
+    assert_matches!(all_tx_events.next().await, Some(FullTransactionEvent::Discarded(hash, DiscardReason::Removed)) if hash == *transaction.transaction.get_hash());
 }
 
 #[tokio::test(flavor = "multi_thread")]
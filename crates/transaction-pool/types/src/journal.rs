@@ -0,0 +1,88 @@
+//! Transaction lifecycle events recorded by the transaction pool journal.
+
+use crate::{DiscardReason, QueuedReason};
+use alloy_primitives::{TxHash, B256};
+use serde::{Deserialize, Serialize};
+
+/// A lifecycle event of a transaction recorded by the transaction pool journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TransactionLifecycleEvent {
+    /// Transaction was moved to the pending sub-pool.
+    Pending,
+    /// Transaction was moved to a parked sub-pool.
+    Queued {
+        /// Why the transaction is not executable, if known.
+        reason: Option<QueuedReason>,
+    },
+    /// Transaction was included in the block with this hash.
+    Mined {
+        /// Hash of the block.
+        block_hash: B256,
+    },
+    /// Transaction was replaced by another transaction with the same sender and nonce.
+    Replaced {
+        /// Hash of the replacement.
+        replaced_by: TxHash,
+    },
+    /// Transaction was removed from the pool.
+    Discarded {
+        /// Why the transaction was removed.
+        reason: DiscardReason,
+    },
+    /// Transaction was rejected by the validator.
+    Invalid {
+        /// The validation error.
+        error: String,
+    },
+    /// Transaction was propagated to peers.
+    Propagated {
+        /// Number of peers the transaction was sent or announced to.
+        peers: usize,
+    },
+}
+
+/// A recorded [`TransactionLifecycleEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionJournalEntry {
+    /// When the event happened, in milliseconds since the unix epoch.
+    pub timestamp: u64,
+    /// The event.
+    #[serde(flatten)]
+    pub event: TransactionLifecycleEvent,
+}
+
+/// The recorded lifecycle of a transaction, oldest event first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionHistory {
+    /// Hash of the transaction.
+    pub hash: TxHash,
+    /// The recorded events.
+    pub events: Vec<TransactionJournalEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_json() {
+        let history = TransactionHistory {
+            hash: TxHash::ZERO,
+            events: vec![TransactionJournalEntry {
+                timestamp: 1,
+                event: TransactionLifecycleEvent::Discarded { reason: DiscardReason::NonceGap },
+            }],
+        };
+        let json = serde_json::to_value(&history).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "hash": TxHash::ZERO,
+                "events": [{ "timestamp": 1, "event": "discarded", "reason": "nonceGap" }],
+            })
+        );
+    }
+}
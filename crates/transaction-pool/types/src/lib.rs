@@ -0,0 +1,21 @@
+//! Commonly used transaction pool types.
+//!
+//! These are the types the transaction pool exposes over RPC, kept in a separate crate so that
+//! API crates don't depend on the pool implementation.
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod journal;
+pub use journal::{TransactionHistory, TransactionJournalEntry, TransactionLifecycleEvent};
+
+mod pool;
+pub use pool::{DiscardReason, QueuedReason};
+
+mod simulation;
+pub use simulation::SimulationOutcome;
@@ -0,0 +1,45 @@
+//! Reasons for the state changes of pool transactions.
+
+use serde::{Deserialize, Serialize};
+
+/// The specific reason why a transaction is queued (not ready for execution)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueuedReason {
+    /// Transaction has a nonce gap - missing prior transactions
+    NonceGap,
+    /// Transaction has parked ancestors - waiting for other transactions to be mined
+    ParkedAncestors,
+    /// Sender has insufficient balance to cover the transaction cost
+    InsufficientBalance,
+    /// Transaction exceeds the block gas limit
+    TooMuchGas,
+    /// Transaction doesn't meet the base fee requirement
+    InsufficientBaseFee,
+    /// Transaction doesn't meet the blob fee requirement (EIP-4844)
+    InsufficientBlobFee,
+}
+
+/// The reason why a transaction was removed from the pool without being mined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiscardReason {
+    /// The transaction was evicted because its sub-pool exceeded the configured size limits.
+    SizeLimit,
+    /// The transaction was evicted because the basefee sub-pool exceeded the configured size
+    /// limits, its fee cap was among the lowest and too low for the current base fee.
+    FeeTooLow,
+    /// A transaction with a lower nonce of the same sender was removed, which left a nonce gap.
+    NonceGap,
+    /// The sender's on-chain nonce advanced past the nonce of the transaction, e.g. because a
+    /// different transaction with the same nonce was mined.
+    NonceTooLow,
+    /// The transaction was not mined within the configured lifetime.
+    Lifetime,
+    /// The transaction was removed on request, e.g. via RPC.
+    Removed,
+    /// The transaction could not be validated due to an internal error.
+    ValidationError,
+    /// The transaction reverted in the simulation of consecutive blocks.
+    Reverted,
+}
@@ -0,0 +1,21 @@
+//! Outcomes of the simulation of pool transactions.
+
+use serde::{Deserialize, Serialize};
+
+/// The outcome of the latest simulation of a pool transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationOutcome {
+    /// Number of the block the transaction was simulated in, this is the block on top of the
+    /// latest block.
+    pub block_number: u64,
+    /// Gas used by the transaction.
+    pub gas_used: u64,
+    /// Whether the transaction reverted or halted.
+    pub reverted: bool,
+    /// The effective tip per gas paid to the block producer.
+    pub effective_tip: u128,
+    /// Number of consecutive simulations the transaction reverted in, `0` if the transaction
+    /// succeeded.
+    pub consecutive_reverts: u32,
+}
@@ -52,7 +52,7 @@ pub use nibbles::{
 };
 
 mod storage;
-pub use storage::{PackedStorageTrieEntry, StorageTrieEntry};
+pub use storage::{PackedStorageTrieEntry, StorageTrieEntry, TrieChangeSetsEntry};
 
 mod subnode;
 pub use subnode::StoredSubNode;
@@ -87,3 +87,71 @@ impl reth_codecs::Compact for PackedStorageTrieEntry {
 
 #[cfg(any(test, feature = "reth-codec"))]
 reth_codecs::impl_compression_for_compact!(PackedStorageTrieEntry);
+
+/// Trie node value of a block's trie changeset.
+///
+/// Holds the value of the node at `nibbles` before the block was applied, `None` if the node did
+/// not exist. `nibbles` is the subkey when used as a value in the `AccountsTrieChangeSets` and
+/// `StoragesTrieChangeSets` tables.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(any(test, feature = "serde"), derive(serde::Serialize, serde::Deserialize))]
+pub struct TrieChangeSetsEntry {
+    /// The nibbles of the intermediate node
+    pub nibbles: PackedStoredNibblesSubKey,
+    /// Encoded node before the block, `None` if the node did not exist.
+    pub node: Option<BranchNodeCompact>,
+}
+
+impl ValueWithSubKey for TrieChangeSetsEntry {
+    type SubKey = PackedStoredNibblesSubKey;
+
+    fn get_subkey(&self) -> Self::SubKey {
+        self.nibbles.clone()
+    }
+}
+
+#[cfg(any(test, feature = "reth-codec"))]
+impl reth_codecs::Compact for TrieChangeSetsEntry {
+    fn to_compact<B>(&self, buf: &mut B) -> usize
+    where
+        B: bytes::BufMut + AsMut<[u8]>,
+    {
+        let nibbles_len = self.nibbles.to_compact(buf);
+        let node_len = self.node.as_ref().map(|node| node.to_compact(buf)).unwrap_or_default();
+        nibbles_len + node_len
+    }
+
+    fn from_compact(buf: &[u8], len: usize) -> (Self, &[u8]) {
+        let (nibbles, buf) = PackedStoredNibblesSubKey::from_compact(buf, 33);
+        // a removed node is encoded without a node value
+        if len <= 33 {
+            return (Self { nibbles, node: None }, buf)
+        }
+        let (node, buf) = BranchNodeCompact::from_compact(buf, len - 33);
+        (Self { nibbles, node: Some(node) }, buf)
+    }
+}
+
+#[cfg(any(test, feature = "reth-codec"))]
+reth_codecs::impl_compression_for_compact!(TrieChangeSetsEntry);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_trie::Nibbles;
+    use reth_codecs::Compact;
+
+    #[test]
+    fn trie_changesets_entry_roundtrip() {
+        let nibbles = PackedStoredNibblesSubKey::from(Nibbles::from_nibbles([0x1, 0x2, 0x3]));
+        let node = BranchNodeCompact::new(0b11, 0, 0b1, vec![], None);
+        for entry in [
+            TrieChangeSetsEntry { nibbles: nibbles.clone(), node: Some(node) },
+            TrieChangeSetsEntry { nibbles, node: None },
+        ] {
+            let mut buf = Vec::new();
+            let len = entry.to_compact(&mut buf);
+            assert_eq!(TrieChangeSetsEntry::from_compact(&buf, len).0, entry);
+        }
+    }
+}
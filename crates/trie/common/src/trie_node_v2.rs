@@ -2,7 +2,7 @@
 
 use crate::BranchNodeMasks;
 use alloc::vec::Vec;
-use alloy_primitives::hex;
+use alloy_primitives::{hex, Bytes};
 use alloy_rlp::{bytes, Decodable, Encodable, EMPTY_STRING_CODE};
 use alloy_trie::{
     nodes::{BranchNodeRef, ExtensionNode, ExtensionNodeRef, LeafNode, RlpNode, TrieNode},
@@ -93,6 +93,23 @@ impl ProofTrieNodeV2 {
 
         result
     }
+
+    /// Returns the RLP encoded MPT nodes represented by this node.
+    ///
+    /// A branch with a parent extension yields the extension node followed by the branch node,
+    /// as proof verifiers expect them as separate nodes. Every other node yields its own encoding.
+    pub fn encode_mpt_nodes(&self) -> Vec<Bytes> {
+        match &self.node {
+            TrieNodeV2::Branch(branch) if !branch.key.is_empty() => {
+                let branch_node =
+                    alloy_rlp::encode(BranchNodeRef::new(&branch.stack, branch.state_mask));
+                let child = RlpNode::from_rlp(&branch_node);
+                let extension = alloy_rlp::encode(ExtensionNodeRef::new(&branch.key, &child));
+                alloc::vec![extension.into(), branch_node.into()]
+            }
+            node => alloc::vec![alloy_rlp::encode(node).into()],
+        }
+    }
 }
 
 /// Enum representing an MPT trie node.
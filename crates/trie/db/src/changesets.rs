@@ -6,14 +6,28 @@
 use crate::{
     DatabaseHashedCursorFactory, DatabaseStateRoot, DatabaseTrieCursorFactory, TrieTableAdapter,
 };
-use alloy_primitives::BlockNumber;
+use alloy_primitives::{map::B256Map, BlockNumber};
+use reth_db_api::{
+    cursor::{DbCursorRO, DbCursorRW},
+    models::BlockNumberHashedAddress,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseError,
+};
 use reth_storage_api::{
     BlockNumReader, ChangeSetReader, DBProvider, StorageChangeSetReader, StorageSettingsCache,
 };
 use reth_storage_errors::provider::ProviderError;
-use reth_trie::TrieInputSorted;
-use reth_trie_common::updates::TrieUpdatesSorted;
-use std::{ops::RangeInclusive, sync::Arc};
+use reth_trie::{Nibbles, TrieInputSorted};
+use reth_trie_common::{
+    updates::{StorageTrieUpdatesSorted, TrieUpdatesSorted},
+    BranchNodeCompact, TrieChangeSetsEntry,
+};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    ops::RangeInclusive,
+    sync::Arc,
+};
 use tracing::debug;
 
 /// Computes trie changesets for a block.
@@ -173,3 +187,152 @@ where
 
     Ok(range_trie_revert)
 }
+
+/// Writes the trie changesets of a block to the trie changeset archive.
+///
+/// The changesets must contain the trie node values before the block, as returned by
+/// [`reth_trie::changesets::compute_trie_changesets`]. Storage trie wipes are not recorded, all
+/// removed storage trie nodes must be included individually.
+pub fn write_block_trie_changesets<TX: DbTxMut + DbTx>(
+    tx: &TX,
+    block_number: BlockNumber,
+    changesets: &TrieUpdatesSorted,
+) -> Result<(), DatabaseError> {
+    let mut account_cursor = tx.cursor_dup_write::<tables::AccountsTrieChangeSets>()?;
+    for (nibbles, node) in changesets.account_nodes_ref() {
+        let entry = TrieChangeSetsEntry { nibbles: (*nibbles).into(), node: node.clone() };
+        account_cursor.upsert(block_number, &entry)?;
+    }
+
+    let mut storage_cursor = tx.cursor_dup_write::<tables::StoragesTrieChangeSets>()?;
+    for (hashed_address, storage) in changesets.storage_tries_ref() {
+        let key = BlockNumberHashedAddress((block_number, *hashed_address));
+        for (nibbles, node) in storage.storage_nodes_ref() {
+            let entry = TrieChangeSetsEntry { nibbles: (*nibbles).into(), node: node.clone() };
+            storage_cursor.upsert(key, &entry)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the block range covered by the trie changeset archive, if any.
+///
+/// Every block in the returned range has its trie changesets persisted.
+pub fn archived_trie_changesets_range<TX: DbTx>(
+    tx: &TX,
+) -> Result<Option<RangeInclusive<BlockNumber>>, DatabaseError> {
+    let earliest =
+        tx.get::<tables::ChainState>(tables::ChainStateKey::EarliestTrieChangeSetBlock)?;
+    let latest = tx.get::<tables::ChainState>(tables::ChainStateKey::LatestTrieChangeSetBlock)?;
+    Ok(earliest.zip(latest).map(|(earliest, latest)| earliest..=latest).filter(|r| !r.is_empty()))
+}
+
+/// Reads aggregate trie changesets for an inclusive block range from the trie changeset archive.
+///
+/// The returned changesets restore the trie from the state after `range.end()` to the state before
+/// `range.start()`, like [`compute_range_trie_changesets`]. Returns `None` if the archive does not
+/// cover the whole range.
+pub fn read_range_trie_changesets<TX: DbTx>(
+    tx: &TX,
+    range: RangeInclusive<BlockNumber>,
+) -> Result<Option<TrieUpdatesSorted>, DatabaseError> {
+    let Some(archived) = archived_trie_changesets_range(tx)? else { return Ok(None) };
+    if !archived.contains(range.start()) || !archived.contains(range.end()) {
+        return Ok(None)
+    }
+
+    // Entries are walked from the oldest to the newest block, the oldest value of a node is the
+    // one before the range.
+    let mut account_nodes = BTreeMap::<Nibbles, Option<BranchNodeCompact>>::new();
+    let mut account_cursor = tx.cursor_dup_read::<tables::AccountsTrieChangeSets>()?;
+    for entry in account_cursor.walk_range(range.clone())? {
+        let (_, entry) = entry?;
+        if let Entry::Vacant(vacant) = account_nodes.entry(entry.nibbles.0) {
+            vacant.insert(entry.node);
+        }
+    }
+
+    let mut storage_tries = B256Map::<BTreeMap<Nibbles, Option<BranchNodeCompact>>>::default();
+    let mut storage_cursor = tx.cursor_dup_read::<tables::StoragesTrieChangeSets>()?;
+    for entry in storage_cursor.walk_range(BlockNumberHashedAddress::range(range))? {
+        let (key, entry) = entry?;
+        let storage_nodes = storage_tries.entry(key.hashed_address()).or_default();
+        if let Entry::Vacant(vacant) = storage_nodes.entry(entry.nibbles.0) {
+            vacant.insert(entry.node);
+        }
+    }
+
+    let storage_tries = storage_tries
+        .into_iter()
+        .map(|(hashed_address, storage_nodes)| {
+            let storage_nodes = storage_nodes.into_iter().collect();
+            (hashed_address, StorageTrieUpdatesSorted { is_deleted: false, storage_nodes })
+        })
+        .collect();
+    Ok(Some(TrieUpdatesSorted::new(account_nodes.into_iter().collect(), storage_tries)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+    use reth_provider::test_utils::create_test_provider_factory;
+
+    fn node(state_mask: u16) -> BranchNodeCompact {
+        BranchNodeCompact::new(state_mask, 0, 0, Vec::default(), None)
+    }
+
+    fn changesets(
+        account_nodes: Vec<(Nibbles, Option<BranchNodeCompact>)>,
+        storage_nodes: Vec<(Nibbles, Option<BranchNodeCompact>)>,
+    ) -> TrieUpdatesSorted {
+        let storage = StorageTrieUpdatesSorted { is_deleted: false, storage_nodes };
+        TrieUpdatesSorted::new(
+            account_nodes,
+            B256Map::from_iter([(B256::with_last_byte(1), storage)]),
+        )
+    }
+
+    #[test]
+    fn reads_archived_range_with_oldest_values() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let tx = provider.tx_ref();
+
+        let a = Nibbles::from_nibbles([0x1]);
+        let b = Nibbles::from_nibbles([0x1, 0x2]);
+        write_block_trie_changesets(
+            tx,
+            1,
+            &changesets(vec![(a, Some(node(0b11)))], vec![(a, None), (b, Some(node(0b11)))]),
+        )
+        .unwrap();
+        write_block_trie_changesets(
+            tx,
+            2,
+            &changesets(vec![(a, Some(node(0b101))), (b, None)], vec![(b, Some(node(0b101)))]),
+        )
+        .unwrap();
+
+        // the archive is not marked as covering the blocks yet
+        assert_eq!(read_range_trie_changesets(tx, 1..=2).unwrap(), None);
+
+        tx.put::<tables::ChainState>(tables::ChainStateKey::EarliestTrieChangeSetBlock, 1).unwrap();
+        tx.put::<tables::ChainState>(tables::ChainStateKey::LatestTrieChangeSetBlock, 2).unwrap();
+        assert_eq!(archived_trie_changesets_range(tx).unwrap(), Some(1..=2));
+
+        assert_eq!(
+            read_range_trie_changesets(tx, 1..=2).unwrap(),
+            Some(changesets(
+                vec![(a, Some(node(0b11))), (b, None)],
+                vec![(a, None), (b, Some(node(0b11)))]
+            ))
+        );
+        assert_eq!(
+            read_range_trie_changesets(tx, 2..=2).unwrap(),
+            Some(changesets(vec![(a, Some(node(0b101))), (b, None)], vec![(b, Some(node(0b101)))]))
+        );
+        assert_eq!(read_range_trie_changesets(tx, 2..=3).unwrap(), None);
+    }
+}
@@ -467,6 +467,165 @@ impl<'a, T: TrieCursorFactory, H: HashedCursorFactory + Clone> Iterator for Veri
     }
 }
 
+/// Restricts the account trie and the hashed accounts of a cursor factory to the subtrie under a
+/// path prefix.
+///
+/// A [`Verifier`] built on top of this factory only checks the account trie nodes under the prefix
+/// and the storage tries of the accounts whose hashed address starts with the prefix, so that the
+/// whole trie can be verified in independent chunks. Account trie nodes with a path shorter than
+/// the prefix are not part of any subtrie and are never checked this way.
+#[derive(Debug, Clone)]
+pub struct SubtrieCursorFactory<F> {
+    inner: F,
+    prefix: Nibbles,
+}
+
+impl<F> SubtrieCursorFactory<F> {
+    /// Creates a new factory restricted to the subtrie under `prefix`.
+    pub const fn new(inner: F, prefix: Nibbles) -> Self {
+        Self { inner, prefix }
+    }
+
+    /// Returns the prefix of the subtrie.
+    pub const fn prefix(&self) -> &Nibbles {
+        &self.prefix
+    }
+}
+
+impl<F: TrieCursorFactory> TrieCursorFactory for SubtrieCursorFactory<F> {
+    type AccountTrieCursor<'a>
+        = SubtrieTrieCursor<F::AccountTrieCursor<'a>>
+    where
+        Self: 'a;
+    type StorageTrieCursor<'a>
+        = F::StorageTrieCursor<'a>
+    where
+        Self: 'a;
+
+    fn account_trie_cursor(&self) -> Result<Self::AccountTrieCursor<'_>, DatabaseError> {
+        Ok(SubtrieTrieCursor { inner: self.inner.account_trie_cursor()?, prefix: self.prefix })
+    }
+
+    fn storage_trie_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Self::StorageTrieCursor<'_>, DatabaseError> {
+        self.inner.storage_trie_cursor(hashed_address)
+    }
+}
+
+impl<F: HashedCursorFactory> HashedCursorFactory for SubtrieCursorFactory<F> {
+    type AccountCursor<'a>
+        = SubtrieHashedCursor<F::AccountCursor<'a>>
+    where
+        Self: 'a;
+    type StorageCursor<'a>
+        = F::StorageCursor<'a>
+    where
+        Self: 'a;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor<'_>, DatabaseError> {
+        let mut lower_bound = [0u8; 32];
+        self.prefix.pack_to(&mut lower_bound);
+        Ok(SubtrieHashedCursor {
+            inner: self.inner.hashed_account_cursor()?,
+            prefix: self.prefix,
+            lower_bound: B256::from(lower_bound),
+        })
+    }
+
+    fn hashed_storage_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Self::StorageCursor<'_>, DatabaseError> {
+        self.inner.hashed_storage_cursor(hashed_address)
+    }
+}
+
+/// Trie cursor which only yields the nodes under a path prefix, see [`SubtrieCursorFactory`].
+#[derive(Debug)]
+pub struct SubtrieTrieCursor<C> {
+    inner: C,
+    prefix: Nibbles,
+}
+
+impl<C> SubtrieTrieCursor<C> {
+    fn filter(
+        &self,
+        entry: Option<(Nibbles, BranchNodeCompact)>,
+    ) -> Option<(Nibbles, BranchNodeCompact)> {
+        entry.filter(|(path, _)| path.starts_with(&self.prefix))
+    }
+}
+
+impl<C: TrieCursor> TrieCursor for SubtrieTrieCursor<C> {
+    fn seek_exact(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        if !key.starts_with(&self.prefix) {
+            return Ok(None)
+        }
+        self.inner.seek_exact(key)
+    }
+
+    fn seek(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        // All paths under the prefix are ordered after the prefix itself.
+        let entry = self.inner.seek(key.max(self.prefix))?;
+        Ok(self.filter(entry))
+    }
+
+    fn next(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let entry = self.inner.next()?;
+        Ok(self.filter(entry))
+    }
+
+    fn current(&mut self) -> Result<Option<Nibbles>, DatabaseError> {
+        Ok(self.inner.current()?.filter(|path| path.starts_with(&self.prefix)))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset()
+    }
+}
+
+/// Hashed cursor which only yields the keys starting with a nibble prefix, see
+/// [`SubtrieCursorFactory`].
+#[derive(Debug)]
+pub struct SubtrieHashedCursor<C> {
+    inner: C,
+    prefix: Nibbles,
+    /// The smallest key starting with the prefix.
+    lower_bound: B256,
+}
+
+impl<C: HashedCursor> SubtrieHashedCursor<C> {
+    fn filter(&self, entry: Option<(B256, C::Value)>) -> Option<(B256, C::Value)> {
+        entry.filter(|(key, _)| Nibbles::unpack(key).starts_with(&self.prefix))
+    }
+}
+
+impl<C: HashedCursor> HashedCursor for SubtrieHashedCursor<C> {
+    type Value = C::Value;
+
+    fn seek(&mut self, key: B256) -> Result<Option<(B256, Self::Value)>, DatabaseError> {
+        let entry = self.inner.seek(key.max(self.lower_bound))?;
+        Ok(self.filter(entry))
+    }
+
+    fn next(&mut self) -> Result<Option<(B256, Self::Value)>, DatabaseError> {
+        let entry = self.inner.next()?;
+        Ok(self.filter(entry))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1008,4 +1167,56 @@ mod tests {
         }
         assert!(outputs.is_empty());
     }
+
+    #[test]
+    fn test_subtrie_verifier() {
+        let accounts = (0..1000u64)
+            .map(|i| {
+                (keccak256(B256::from(U256::from(i))), Account { nonce: i, ..Default::default() })
+            })
+            .collect::<BTreeMap<_, _>>();
+        let storages = accounts.keys().map(|account| (*account, BTreeMap::new())).collect();
+        let hashed_cursor_factory = MockHashedCursorFactory::new(accounts, storages);
+
+        let (_, updates) = StateRoot::new(NoopTrieCursorFactory, hashed_cursor_factory.clone())
+            .root_with_updates()
+            .unwrap();
+        let mut account_nodes = updates.account_nodes.into_iter().collect::<BTreeMap<_, _>>();
+
+        let verify_subtrie = |account_nodes: &BTreeMap<Nibbles, BranchNodeCompact>, nibble| {
+            let prefix = Nibbles::from_nibbles([nibble]);
+            let trie_cursor_factory = SubtrieCursorFactory::new(
+                MockTrieCursorFactory::new(account_nodes.clone(), B256Map::default()),
+                prefix,
+            );
+            let hashed_cursor_factory =
+                SubtrieCursorFactory::new(hashed_cursor_factory.clone(), prefix);
+            Verifier::new(&trie_cursor_factory, hashed_cursor_factory)
+                .unwrap()
+                .map(Result::unwrap)
+                .filter(|output| !matches!(output, Output::Progress(_)))
+                .collect::<Vec<_>>()
+        };
+
+        for nibble in 0..16 {
+            assert_eq!(verify_subtrie(&account_nodes, nibble), vec![]);
+        }
+
+        // Remove a node, it must only be reported by the subtrie containing it.
+        let (path, node) = account_nodes
+            .iter()
+            .find(|(path, _)| path.len() == 2)
+            .map(|(path, node)| (*path, node.clone()))
+            .unwrap();
+        account_nodes.remove(&path);
+
+        for nibble in 0..16 {
+            let outputs = verify_subtrie(&account_nodes, nibble);
+            if nibble == path.get_unchecked(0) {
+                assert_eq!(outputs, vec![Output::AccountMissing(path, node.clone())]);
+            } else {
+                assert_eq!(outputs, vec![]);
+            }
+        }
+    }
 }
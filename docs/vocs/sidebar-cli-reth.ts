@@ -69,6 +69,10 @@ export const rethCliSidebar: SidebarItem = {
                     text: "reth db copy",
                     link: "/cli/reth/db/copy"
                 },
+                {
+                    text: "reth db backup",
+                    link: "/cli/reth/db/backup"
+                },
                 {
                     text: "reth db diff",
                     link: "/cli/reth/db/diff"
@@ -130,6 +134,21 @@ export const rethCliSidebar: SidebarItem = {
                         }
                     ]
                 },
+                {
+                    text: "reth db static-file",
+                    link: "/cli/reth/db/static-file",
+                    collapsed: true,
+                    items: [
+                        {
+                            text: "reth db static-file recompress",
+                            link: "/cli/reth/db/static-file/recompress"
+                        },
+                        {
+                            text: "reth db static-file verify",
+                            link: "/cli/reth/db/static-file/verify"
+                        }
+                    ]
+                },
                 {
                     text: "reth db version",
                     link: "/cli/reth/db/version"
@@ -262,6 +281,10 @@ export const rethCliSidebar: SidebarItem = {
                             link: "/cli/reth/stage/unwind/num-blocks"
                         }
                     ]
+                },
+                {
+                    text: "reth stage restore-receipts",
+                    link: "/cli/reth/stage/restore-receipts"
                 }
             ]
         },
@@ -296,6 +319,14 @@ export const rethCliSidebar: SidebarItem = {
                 {
                     text: "reth p2p enode",
                     link: "/cli/reth/p2p/enode"
+                },
+                {
+                    text: "reth p2p dns-tree",
+                    link: "/cli/reth/p2p/dns-tree"
+                },
+                {
+                    text: "reth p2p crawl",
+                    link: "/cli/reth/p2p/crawl"
                 }
             ]
         },
@@ -310,6 +341,18 @@ export const rethCliSidebar: SidebarItem = {
         {
             text: "reth re-execute",
             link: "/cli/reth/re-execute"
+        },
+        {
+            text: "reth replica",
+            link: "/cli/reth/replica"
+        },
+        {
+            text: "reth rpc-replay",
+            link: "/cli/reth/rpc-replay"
+        },
+        {
+            text: "reth stateless-validate",
+            link: "/cli/reth/stateless-validate"
         }
     ]
 };
@@ -71,8 +71,11 @@ where
                 println!("Transaction added to pending pool: {:?}", tx_events.hash());
                 break;
             }
-            TransactionEvent::Discarded => {
-                return Err(eyre::eyre!("Transaction discarded: {:?}", tx_events.hash(),));
+            TransactionEvent::Discarded(reason) => {
+                return Err(eyre::eyre!(
+                    "Transaction discarded ({reason:?}): {:?}",
+                    tx_events.hash()
+                ));
             }
             _ => {
                 // Continue waiting for added or rejected event